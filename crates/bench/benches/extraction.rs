@@ -0,0 +1,51 @@
+//! Benchmarks of extracting data from requests.
+//!
+//! Run with `cargo bench -p salvo-bench --bench extraction`.
+use criterion::{criterion_group, criterion_main, Criterion};
+use salvo::prelude::*;
+use salvo::test::TestClient;
+use salvo_bench::{extraction_app, Item, Paging};
+use tokio::runtime::Runtime;
+
+fn bench_parse(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let item = Item::samples(1).remove(0);
+    let mut group = c.benchmark_group("parse");
+    group.bench_function("queries", |b| {
+        b.iter_batched(
+            || TestClient::get("http://127.0.0.1:5800/items?page=3&size=20&sort=name").build(),
+            |mut req| req.parse_queries::<Paging>().unwrap().page,
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("json", |b| {
+        b.to_async(&rt).iter_batched(
+            || TestClient::put("http://127.0.0.1:5800/items/7").json(&item).build(),
+            |mut req| async move { req.parse_json::<Item>().await.unwrap().price },
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn bench_extract(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let service = Service::new(extraction_app());
+    let item = Item::samples(1).remove(0);
+    let mut group = c.benchmark_group("extract");
+    group.bench_function("queries", |b| {
+        b.to_async(&rt)
+            .iter(|| TestClient::get("http://127.0.0.1:5800/items?page=3&size=20&sort=name").send(&service));
+    });
+    group.bench_function("param_and_json", |b| {
+        b.to_async(&rt).iter(|| {
+            TestClient::put("http://127.0.0.1:5800/items/7")
+                .json(&item)
+                .send(&service)
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_extract);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+//! Benchmarks of middleware stacks, the body of the response is read so the cost of compression is included.
+//!
+//! Run with `cargo bench -p salvo-bench --bench middleware`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use salvo::http::header::{ACCEPT_ENCODING, ORIGIN};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use salvo_bench::{middleware_app, STACKS};
+use tokio::runtime::Runtime;
+
+fn bench_stacks(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    for (group_name, path, method) in [
+        ("middleware/small", "hello", "GET"),
+        ("middleware/large", "large", "GET"),
+        ("middleware/preflight", "hello", "OPTIONS"),
+    ] {
+        let mut group = c.benchmark_group(group_name);
+        for stack in STACKS {
+            let service = Service::new(middleware_app(stack));
+            let url = format!("http://127.0.0.1:5800/{path}");
+            group.bench_function(BenchmarkId::from_parameter(stack), |b| {
+                b.to_async(&rt).iter(|| async {
+                    let builder = if method == "OPTIONS" {
+                        TestClient::options(&url)
+                    } else {
+                        TestClient::get(&url)
+                    };
+                    let mut res = builder
+                        .add_header(ORIGIN, "https://salvo.rs", true)
+                        .add_header(ACCEPT_ENCODING, "gzip, br", true)
+                        .send(&service)
+                        .await;
+                    res.take_bytes(None).await.unwrap().len()
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_stacks);
+criterion_main!(benches);
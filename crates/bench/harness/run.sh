@@ -0,0 +1,56 @@
+#!/usr/bin/env bash
+# Load tests `salvo-bench-server` with `wrk` or `oha`.
+#
+# Usage: harness/run.sh [wrk|oha] [scenario...]
+#
+# Environment variables:
+#   DURATION     duration of each scenario, default `10s`
+#   CONNECTIONS  concurrent connections, default `64`
+#   THREADS      wrk threads, default `4`
+#   ADDR         server address, default `127.0.0.1:5800`
+set -euo pipefail
+
+cd "$(dirname "$0")"
+tool="${1:-wrk}"
+shift || true
+duration="${DURATION:-10s}"
+connections="${CONNECTIONS:-64}"
+threads="${THREADS:-4}"
+addr="${ADDR:-127.0.0.1:5800}"
+
+cargo build --release -p salvo-bench --bin salvo-bench-server
+SALVO_BENCH_ADDR="$addr" RUST_LOG=warn cargo run --release -q -p salvo-bench --bin salvo-bench-server &
+server=$!
+trap 'kill $server' EXIT
+until curl -sf "http://$addr/middleware/bare/hello" >/dev/null; do sleep 0.2; done
+
+while read -r name method path script; do
+    [[ -z "$name" || "$name" == \#* ]] && continue
+    if [[ $# -gt 0 && ! " $* " =~ " $name " ]]; then
+        continue
+    fi
+    echo "==> $name: $method $path"
+    case "$tool" in
+        wrk)
+            args=(-t "$threads" -c "$connections" -d "$duration" --latency)
+            [[ -n "$script" ]] && args+=(-s "wrk/$script")
+            wrk "${args[@]}" "http://$addr$path"
+            ;;
+        oha)
+            args=(-c "$connections" -z "$duration" --no-tui -m "$method")
+            case "$script" in
+                put_item.lua)
+                    args+=(-T application/json -d '{"name":"item 7","tags":["bench","tag-0"],"price":700}')
+                    ;;
+                cors_compression.lua)
+                    args+=(-H "Origin: https://salvo.rs" -H "Accept-Encoding: gzip, br")
+                    ;;
+            esac
+            oha "${args[@]}" "http://$addr$path"
+            ;;
+        *)
+            echo "unknown tool: $tool" >&2
+            exit 1
+            ;;
+    esac
+done < scenarios.txt
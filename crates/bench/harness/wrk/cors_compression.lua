@@ -0,0 +1,3 @@
+-- Sends requests as a browser from another origin which accepts compressed bodies.
+wrk.headers["Origin"] = "https://salvo.rs"
+wrk.headers["Accept-Encoding"] = "gzip, br"
@@ -0,0 +1,4 @@
+-- Sends `PUT /extraction/items/7` with a JSON body.
+wrk.method = "PUT"
+wrk.headers["Content-Type"] = "application/json"
+wrk.body = '{"name":"item 7","tags":["bench","tag-0"],"price":700}'
@@ -0,0 +1,178 @@
+//! Benchmarks and load test harness of salvo web server framework.
+//!
+//! The apps served by the criterion benchmarks and by the `salvo-bench-server` binary are built here, so the numbers
+//! of micro benchmarks and of load tests by `wrk` or `oha` are measured against the same routes and middleware
+//! stacks. See the README for how to run them.
+//!
+//! Routing benchmarks are in `salvo_core`, they match requests against routers without the cost of handlers.
+use std::time::Duration;
+
+use salvo::compression::Compression;
+use salvo::cors::Cors;
+use salvo::http::Method;
+use salvo::logging::Logger;
+use salvo::prelude::*;
+use salvo::request_id::RequestId;
+use salvo::secure_headers::SecureHeaders;
+use salvo::timeout::Timeout;
+use serde::{Deserialize, Serialize};
+
+/// The middleware stacks measured by the benchmarks, in order of cost.
+pub const STACKS: [&str; 5] = ["bare", "cors", "compression", "cors_compression", "full"];
+
+#[handler]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+/// A body large enough to be compressed.
+#[handler]
+async fn large() -> Text<String> {
+    Text::Json(serde_json::to_string(&Item::samples(64)).unwrap())
+}
+
+/// An item of the extraction benchmarks.
+#[derive(Serialize, Deserialize, Extractible, Clone, Debug)]
+#[salvo(extract(default_source(from = "body", format = "json")))]
+pub struct Item {
+    /// Item id.
+    #[salvo(extract(source(from = "param")))]
+    #[serde(default)]
+    pub id: u64,
+    /// Item name.
+    pub name: String,
+    /// Item tags.
+    pub tags: Vec<String>,
+    /// Item price in cents.
+    pub price: u64,
+}
+impl Item {
+    /// Create `count` sample items.
+    pub fn samples(count: usize) -> Vec<Item> {
+        (0..count as u64)
+            .map(|id| Item {
+                id,
+                name: format!("item {id}"),
+                tags: vec!["bench".into(), format!("tag-{}", id % 7)],
+                price: id * 100,
+            })
+            .collect()
+    }
+}
+
+/// The query of the extraction benchmarks.
+#[derive(Deserialize, Debug)]
+pub struct Paging {
+    /// Page number.
+    pub page: u32,
+    /// Page size.
+    pub size: u32,
+    /// Sort field.
+    pub sort: Option<String>,
+}
+
+#[handler]
+async fn list_items(req: &mut Request, res: &mut Response) {
+    match req.parse_queries::<Paging>() {
+        Ok(paging) => res.render(Json(Item::samples(paging.size.min(100) as usize))),
+        Err(e) => res.render(StatusError::bad_request().brief(e.to_string())),
+    }
+}
+
+#[handler]
+async fn update_item(item: Item) -> Json<Item> {
+    Json(item)
+}
+
+/// A router which extracts queries, params and JSON bodies.
+pub fn extraction_app() -> Router {
+    Router::with_path("items")
+        .get(list_items)
+        .push(Router::with_path("<id:num>").put(update_item))
+}
+
+/// A router with the middleware stack named `stack`, which is one of [`STACKS`].
+///
+/// # Panics
+///
+/// Panics if the stack is unknown.
+pub fn middleware_app(stack: &str) -> Router {
+    let cors = || {
+        Cors::new()
+            .allow_origin("https://salvo.rs")
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers("authorization")
+            .into_handler()
+    };
+    let compression = || Compression::new().min_length(1024);
+    let router = match stack {
+        "bare" => Router::new(),
+        "cors" => Router::with_hoop(cors()),
+        "compression" => Router::with_hoop(compression()),
+        "cors_compression" => Router::with_hoop(cors()).hoop(compression()),
+        "full" => Router::with_hoop(Logger::new())
+            .hoop(RequestId::new())
+            .hoop(Timeout::new(Duration::from_secs(5)))
+            .hoop(SecureHeaders::new())
+            .hoop(cors())
+            .hoop(compression()),
+        _ => panic!("unknown middleware stack `{stack}`"),
+    };
+    router
+        .push(Router::with_path("hello").get(hello).options(handler::empty()))
+        .push(Router::with_path("large").get(large))
+}
+
+/// A router with all the apps, served by the `salvo-bench-server` binary.
+///
+/// The extraction app is served at `/extraction` and each middleware stack at `/middleware/<stack>`.
+pub fn app() -> Router {
+    let mut middleware = Router::with_path("middleware");
+    for stack in STACKS {
+        middleware = middleware.push(Router::with_path(stack).push(middleware_app(stack)));
+    }
+    Router::new()
+        .push(Router::with_path("extraction").push(extraction_app()))
+        .push(middleware)
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, ORIGIN};
+    use salvo::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_app() {
+        let service = Service::new(app());
+        for path in ["middleware/bare/hello", "extraction/items?page=1&size=10"] {
+            let res = TestClient::get(format!("http://127.0.0.1:5800/{path}"))
+                .send(&service)
+                .await;
+            assert_eq!(res.status_code, Some(StatusCode::OK), "{path}");
+        }
+
+        let item = TestClient::put("http://127.0.0.1:5800/extraction/items/7")
+            .json(&Item::samples(1)[0])
+            .send(&service)
+            .await
+            .take_json::<Item>()
+            .await
+            .unwrap();
+        assert_eq!(item.id, 7);
+
+        for stack in STACKS {
+            let res = TestClient::get(format!("http://127.0.0.1:5800/middleware/{stack}/large"))
+                .add_header(ORIGIN, "https://salvo.rs", true)
+                .add_header(ACCEPT_ENCODING, "gzip", true)
+                .send(&service)
+                .await;
+            assert_eq!(res.status_code, Some(StatusCode::OK), "{stack}");
+            assert_eq!(
+                res.headers().contains_key(CONTENT_ENCODING),
+                stack.contains("compression") || stack == "full"
+            );
+        }
+    }
+}
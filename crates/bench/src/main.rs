@@ -0,0 +1,13 @@
+//! Server of the apps in `salvo-bench`, load tested by the scripts in `harness`.
+//!
+//! The listen address can be set by `SALVO_BENCH_ADDR`, it is `127.0.0.1:5800` by default.
+use salvo::prelude::*;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().init();
+
+    let addr = std::env::var("SALVO_BENCH_ADDR").unwrap_or_else(|_| "127.0.0.1:5800".into());
+    let acceptor = TcpListener::new(addr).bind().await;
+    Server::new(acceptor).serve(salvo_bench::app()).await;
+}
@@ -9,6 +9,11 @@
 //! The default cache store is [`MokaStore`], which is a wrapper of [`moka`].
 //! You can define your own cache store by implementing [`CacheStore`].
 //!
+//! Enable [`Cache::http_caching`] to behave like an HTTP shared cache, which respects `Cache-Control`, `Expires`
+//! and `Vary` from the origin and revalidates stale responses.
+//!
+//! [`SingleFlight`] deduplicates concurrent identical requests, so only one of them calls the handler.
+//!
 //! Example: [cache-simple](https://github.com/salvo-rs/salvo/tree/main/examples/cache-simple)
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
@@ -22,14 +27,20 @@ use std::borrow::Borrow;
 use std::collections::VecDeque;
 use std::error::Error as StdError;
 use std::hash::Hash;
+use std::time::SystemTime;
 
 use bytes::Bytes;
 use salvo_core::handler::Skipper;
+use salvo_core::http::header::{AGE, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use salvo_core::http::{HeaderMap, ResBody, StatusCode};
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
 
 mod skipper;
 pub use skipper::MethodSkipper;
+mod single_flight;
+pub use single_flight::SingleFlight;
+mod policy;
+pub use policy::CachePolicy;
 
 #[macro_use]
 mod cfg;
@@ -209,11 +220,18 @@ pub struct CachedEntry {
     ///
     /// *Notice: If the response's body is streaming, it will be ignored an not cached.
     pub body: CachedBody,
+    /// Freshness information, it is only set when [`Cache::http_caching`] is enabled.
+    pub policy: Option<CachePolicy>,
 }
 impl CachedEntry {
     /// Create a new `CachedEntry`.
     pub fn new(status: Option<StatusCode>, headers: HeaderMap, body: CachedBody) -> Self {
-        Self { status, headers, body }
+        Self {
+            status,
+            headers,
+            body,
+            policy: None,
+        }
     }
 
     /// Get the response status.
@@ -232,6 +250,26 @@ impl CachedEntry {
     pub fn body(&self) -> &CachedBody {
         &self.body
     }
+
+    /// Get the freshness information.
+    pub fn policy(&self) -> Option<&CachePolicy> {
+        self.policy.as_ref()
+    }
+
+    fn write_to(self, res: &mut Response, now: SystemTime) {
+        let CachedEntry {
+            status,
+            headers,
+            body,
+            policy,
+        } = self;
+        res.status_code(status.unwrap_or(StatusCode::OK));
+        *res.headers_mut() = headers;
+        if let Some(policy) = policy {
+            res.headers_mut().insert(AGE, policy.age(now).as_secs().into());
+        }
+        *res.body_mut() = body.into();
+    }
 }
 
 /// A constructed via `salvo_cache::Cache::builder()`.
@@ -243,6 +281,8 @@ pub struct Cache<S, I> {
     pub issuer: I,
     /// Skipper.
     pub skipper: Box<dyn Skipper>,
+    /// Whether to behave like an HTTP shared cache.
+    pub http_caching: bool,
 }
 
 impl<S, I> Cache<S, I> {
@@ -254,6 +294,7 @@ impl<S, I> Cache<S, I> {
             store,
             issuer,
             skipper: Box::new(skipper),
+            http_caching: false,
         }
     }
     /// Sets skipper and returns new `Cache`.
@@ -262,6 +303,105 @@ impl<S, I> Cache<S, I> {
         self.skipper = Box::new(skipper);
         self
     }
+    /// Sets whether to behave like an HTTP shared cache following RFC 9111, and returns new `Cache`.
+    ///
+    /// When it is enabled, responses are only stored if `Cache-Control`, `Expires` or validators of the
+    /// response allow it, and `Vary` is respected. Stale responses are revalidated with `If-None-Match` and
+    /// `If-Modified-Since`, and they are served when the origin fails if `stale-if-error` permits it.
+    ///
+    /// Only one variant of a `Vary` response is stored for a key. The time to live of the store should cover
+    /// the freshness and `stale-if-error` lifetime of responses.
+    #[inline]
+    pub fn http_caching(mut self, value: bool) -> Self {
+        self.http_caching = value;
+        self
+    }
+}
+
+impl<S, I> Cache<S, I>
+where
+    S: CacheStore<Key = I::Key>,
+    I: CacheIssuer,
+{
+    async fn save_response(&self, key: S::Key, req: &Request, res: &Response, now: SystemTime) {
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        if status == StatusCode::NOT_MODIFIED || res.body.is_stream() || res.body.is_error() {
+            return;
+        }
+        let Some(policy) = CachePolicy::new(req.headers(), status, res.headers(), now) else {
+            return;
+        };
+        match CachedBody::try_from(&res.body) {
+            Ok(body) => {
+                let mut entry = CachedEntry::new(res.status_code, res.headers().clone(), body);
+                entry.policy = Some(policy);
+                if let Err(e) = self.store.save_entry(key, entry).await {
+                    tracing::error!(error = ?e, "cache failed");
+                }
+            }
+            Err(e) => tracing::error!(error = ?e, "cache failed"),
+        }
+    }
+
+    async fn handle_http_caching(
+        &self,
+        key: S::Key,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let now = SystemTime::now();
+        let entry = self
+            .store
+            .load_entry(&key)
+            .await
+            .filter(|entry| entry.policy().map(|p| p.matches_vary(req.headers())).unwrap_or(true));
+        let Some(mut entry) = entry else {
+            ctrl.call_next(req, depot, res).await;
+            self.save_response(key, req, res, now).await;
+            return;
+        };
+        let fresh = entry.policy().map(|p| p.is_fresh(now)).unwrap_or(true);
+        if fresh && !policy::request_no_cache(req.headers()) {
+            entry.write_to(res, now);
+            ctrl.skip_rest();
+            return;
+        }
+
+        if let Some(etag) = entry.headers.get(ETAG) {
+            req.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = entry.headers.get(LAST_MODIFIED) {
+            req.headers_mut().insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        ctrl.call_next(req, depot, res).await;
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        if status == StatusCode::NOT_MODIFIED {
+            for name in res.headers().keys() {
+                if name == CONTENT_LENGTH {
+                    continue;
+                }
+                entry.headers.remove(name);
+                for value in res.headers().get_all(name) {
+                    entry.headers.append(name.clone(), value.clone());
+                }
+            }
+            let stored_status = entry.status.unwrap_or(StatusCode::OK);
+            entry.policy = CachePolicy::new(req.headers(), stored_status, &entry.headers, now);
+            if entry.policy.is_some() {
+                if let Err(e) = self.store.save_entry(key, entry.clone()).await {
+                    tracing::error!(error = ?e, "cache failed");
+                }
+            }
+            entry.write_to(res, now);
+        } else if status.is_server_error() && entry.policy().map(|p| p.allows_stale_if_error(now)).unwrap_or(false) {
+            tracing::warn!(status = %status, "origin failed, serve stale response");
+            entry.write_to(res, now);
+        } else {
+            self.save_response(key, req, res, now).await;
+        }
+    }
 }
 
 #[async_trait]
@@ -280,6 +420,10 @@ where
                 return;
             }
         };
+        if self.http_caching {
+            self.handle_http_caching(key, req, depot, res, ctrl).await;
+            return;
+        }
         let cache = match self.store.load_entry(&key).await {
             Some(cache) => cache,
             None => {
@@ -300,7 +444,9 @@ where
                 return;
             }
         };
-        let CachedEntry { status, headers, body } = cache;
+        let CachedEntry {
+            status, headers, body, ..
+        } = cache;
         if let Some(status) = status {
             res.status_code(status);
         }
@@ -350,4 +496,91 @@ mod tests {
 
         assert_ne!(content0, content2);
     }
+
+    struct Origin {
+        count: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        fail: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    }
+    #[async_trait]
+    impl Handler for Origin {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            use std::sync::atomic::Ordering;
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.fail.load(Ordering::SeqCst) {
+                res.render(StatusError::bad_gateway());
+                return;
+            }
+            let cache_control = req.query::<String>("cc").unwrap_or_else(|| "max-age=1".into());
+            res.add_header("cache-control", cache_control, true).unwrap();
+            res.add_header("etag", "\"v1\"", true).unwrap();
+            if req.header::<String>("if-none-match").as_deref() == Some("\"v1\"") {
+                res.status_code(StatusCode::NOT_MODIFIED);
+                return;
+            }
+            res.add_header("vary", "accept-language", true).unwrap();
+            res.render(format!("call {count}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_caching() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let fail = Arc::new(AtomicBool::new(false));
+        let cache = Cache::new(
+            MokaStore::builder()
+                .time_to_live(std::time::Duration::from_secs(60))
+                .build(),
+            RequestIssuer::default(),
+        )
+        .http_caching(true);
+        let router = Router::new().hoop(cache).goal(Origin {
+            count: count.clone(),
+            fail: fail.clone(),
+        });
+        let service = Service::new(router);
+        async fn get(service: &Service, url: &str) -> (Response, String) {
+            let mut res = TestClient::get(format!("http://127.0.0.1:5801{url}"))
+                .add_header("accept-language", "en", true)
+                .send(service)
+                .await;
+            let body = res.take_string().await.unwrap();
+            (res, body)
+        }
+
+        let (_, body) = get(&service, "/").await;
+        assert_eq!(body, "call 1");
+        let (res, body) = get(&service, "/").await;
+        assert_eq!(body, "call 1");
+        assert!(res.headers().contains_key("age"));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        let (res, body) = get(&service, "/").await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(body, "call 1");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("accept-language", "fr", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "call 3");
+
+        let (_, body) = get(&service, "/?cc=no-store").await;
+        assert_eq!(body, "call 4");
+        let (_, body) = get(&service, "/?cc=no-store").await;
+        assert_eq!(body, "call 5");
+
+        let (_, body) = get(&service, "/?cc=max-age=0,stale-if-error=60").await;
+        assert_eq!(body, "call 6");
+        fail.store(true, Ordering::SeqCst);
+        let (res, body) = get(&service, "/?cc=max-age=0,stale-if-error=60").await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(body, "call 6");
+        let (res, _) = get(&service, "/").await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_GATEWAY));
+    }
 }
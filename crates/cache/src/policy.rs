@@ -0,0 +1,263 @@
+//! HTTP caching semantics of shared caches.
+//!
+//! Read more: <https://www.rfc-editor.org/rfc/rfc9111>
+use std::time::{Duration, SystemTime};
+
+use salvo_core::http::header::{
+    HeaderName, HeaderValue, AGE, AUTHORIZATION, CACHE_CONTROL, ETAG, EXPIRES, LAST_MODIFIED, PRAGMA, VARY,
+};
+use salvo_core::http::headers::{Date, Expires, HeaderMapExt};
+use salvo_core::http::{HeaderMap, StatusCode};
+
+/// Status codes which are cacheable without explicit freshness information.
+const HEURISTICALLY_CACHEABLE: [u16; 11] = [200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// Parsed `Cache-Control` directives.
+#[derive(Default, Debug)]
+pub(crate) struct Directives {
+    pub(crate) no_store: bool,
+    pub(crate) no_cache: bool,
+    pub(crate) private: bool,
+    pub(crate) public: bool,
+    pub(crate) must_revalidate: bool,
+    pub(crate) max_age: Option<u64>,
+    pub(crate) s_maxage: Option<u64>,
+    pub(crate) stale_if_error: Option<u64>,
+}
+impl Directives {
+    pub(crate) fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = Self::default();
+        for value in headers.get_all(CACHE_CONTROL) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for directive in value.split(',') {
+                let (name, arg) = match directive.split_once('=') {
+                    Some((name, arg)) => (name.trim(), Some(arg.trim().trim_matches('"'))),
+                    None => (directive.trim(), None),
+                };
+                let seconds = arg.and_then(|arg| arg.parse::<u64>().ok());
+                match &*name.to_ascii_lowercase() {
+                    "no-store" => directives.no_store = true,
+                    "no-cache" => directives.no_cache = true,
+                    "private" => directives.private = true,
+                    "public" => directives.public = true,
+                    "must-revalidate" | "proxy-revalidate" => directives.must_revalidate = true,
+                    "max-age" => directives.max_age = seconds.or(Some(0)),
+                    "s-maxage" => directives.s_maxage = seconds.or(Some(0)),
+                    "stale-if-error" => directives.stale_if_error = seconds,
+                    _ => {}
+                }
+            }
+        }
+        directives
+    }
+}
+
+/// Returns `true` if the request asks the cache to revalidate the stored response.
+pub(crate) fn request_no_cache(headers: &HeaderMap) -> bool {
+    let directives = Directives::parse(headers);
+    directives.no_cache
+        || directives.max_age == Some(0)
+        || headers
+            .get(PRAGMA)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.eq_ignore_ascii_case("no-cache"))
+            .unwrap_or(false)
+}
+
+/// Freshness information of a stored response, computed from response headers following RFC 9111.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CachePolicy {
+    /// Time when the response was generated by the origin, the `Age` header of the response is taken into
+    /// account.
+    pub stored_at: SystemTime,
+    /// The response is fresh until this time.
+    pub fresh_until: SystemTime,
+    /// How long the stale response can be served when the origin fails, from `stale-if-error`.
+    pub stale_if_error: Duration,
+    /// Whether `must-revalidate`, `proxy-revalidate` or `s-maxage` is present, stale responses are never served
+    /// if it is `true`.
+    pub must_revalidate: bool,
+    /// Request headers nominated by the `Vary` header and their values of the request.
+    pub vary: Vec<(HeaderName, Option<HeaderValue>)>,
+}
+impl CachePolicy {
+    /// Create a `CachePolicy` for the response, returns `None` if the response must not be stored by a shared
+    /// cache.
+    pub fn new(req_headers: &HeaderMap, status: StatusCode, res_headers: &HeaderMap, now: SystemTime) -> Option<Self> {
+        let directives = Directives::parse(res_headers);
+        if directives.no_store || directives.private {
+            return None;
+        }
+        if req_headers.contains_key(AUTHORIZATION)
+            && !(directives.public || directives.must_revalidate || directives.s_maxage.is_some())
+        {
+            return None;
+        }
+        let mut vary = Vec::new();
+        for value in res_headers.get_all(VARY) {
+            for name in value.to_str().ok()?.split(',') {
+                let name = name.trim();
+                if name == "*" {
+                    return None;
+                }
+                if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+                    let value = req_headers.get(&name).cloned();
+                    vary.push((name, value));
+                }
+            }
+        }
+
+        let lifetime = if directives.no_cache {
+            Some(0)
+        } else if let Some(seconds) = directives.s_maxage.or(directives.max_age) {
+            Some(seconds)
+        } else if res_headers.contains_key(EXPIRES) {
+            // Invalid `Expires` value means the response is already expired.
+            let date = res_headers.typed_get::<Date>().map(SystemTime::from).unwrap_or(now);
+            let expires = res_headers.typed_get::<Expires>().map(SystemTime::from);
+            Some(
+                expires
+                    .and_then(|expires| expires.duration_since(date).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            )
+        } else {
+            None
+        };
+        let lifetime = match lifetime {
+            Some(lifetime) => lifetime,
+            None if HEURISTICALLY_CACHEABLE.contains(&status.as_u16())
+                && (res_headers.contains_key(ETAG) || res_headers.contains_key(LAST_MODIFIED)) =>
+            {
+                0
+            }
+            None => return None,
+        };
+
+        let age = res_headers
+            .get(AGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        let stored_at = now.checked_sub(Duration::from_secs(age)).unwrap_or(now);
+        Some(Self {
+            stored_at,
+            fresh_until: stored_at + Duration::from_secs(lifetime),
+            stale_if_error: Duration::from_secs(directives.stale_if_error.unwrap_or(0)),
+            must_revalidate: directives.must_revalidate || directives.s_maxage.is_some(),
+            vary,
+        })
+    }
+    /// Returns `true` if the stored response is fresh.
+    #[inline]
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        now < self.fresh_until
+    }
+    /// Returns `true` if the stale response can be served when the origin fails.
+    #[inline]
+    pub fn allows_stale_if_error(&self, now: SystemTime) -> bool {
+        !self.must_revalidate && now < self.fresh_until + self.stale_if_error
+    }
+    /// Returns `true` if the request headers nominated by `Vary` match the stored request.
+    #[inline]
+    pub fn matches_vary(&self, req_headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| req_headers.get(name) == value.as_ref())
+    }
+    /// Returns the current age of the stored response.
+    #[inline]
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.stored_at).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name.clone(), HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_cache_policy() {
+        let now = SystemTime::now();
+        let req = HeaderMap::new();
+        let policy = CachePolicy::new(
+            &req,
+            StatusCode::OK,
+            &headers(&[(CACHE_CONTROL, "max-age=60, stale-if-error=30"), (AGE, "10")]),
+            now,
+        )
+        .unwrap();
+        assert!(policy.is_fresh(now + Duration::from_secs(49)));
+        assert!(!policy.is_fresh(now + Duration::from_secs(50)));
+        assert!(policy.allows_stale_if_error(now + Duration::from_secs(79)));
+        assert!(!policy.allows_stale_if_error(now + Duration::from_secs(80)));
+        assert_eq!(policy.age(now), Duration::from_secs(10));
+
+        let policy = CachePolicy::new(
+            &req,
+            StatusCode::OK,
+            &headers(&[(CACHE_CONTROL, "s-maxage=5, max-age=60")]),
+            now,
+        )
+        .unwrap();
+        assert!(!policy.is_fresh(now + Duration::from_secs(5)));
+        assert!(!policy.allows_stale_if_error(now + Duration::from_secs(5)));
+
+        let policy = CachePolicy::new(&req, StatusCode::OK, &headers(&[(ETAG, "\"v1\"")]), now).unwrap();
+        assert!(!policy.is_fresh(now));
+        let policy = CachePolicy::new(&req, StatusCode::OK, &headers(&[(EXPIRES, "0")]), now).unwrap();
+        assert!(!policy.is_fresh(now));
+
+        assert!(CachePolicy::new(&req, StatusCode::OK, &headers(&[(CACHE_CONTROL, "no-store")]), now).is_none());
+        assert!(CachePolicy::new(
+            &req,
+            StatusCode::OK,
+            &headers(&[(CACHE_CONTROL, "private, max-age=60")]),
+            now
+        )
+        .is_none());
+        assert!(CachePolicy::new(
+            &req,
+            StatusCode::OK,
+            &headers(&[(VARY, "*"), (CACHE_CONTROL, "max-age=60")]),
+            now
+        )
+        .is_none());
+        assert!(CachePolicy::new(&req, StatusCode::OK, &HeaderMap::new(), now).is_none());
+        assert!(CachePolicy::new(&req, StatusCode::CREATED, &headers(&[(ETAG, "\"v1\"")]), now).is_none());
+        let auth = headers(&[(AUTHORIZATION, "Bearer token")]);
+        assert!(CachePolicy::new(&auth, StatusCode::OK, &headers(&[(CACHE_CONTROL, "max-age=60")]), now).is_none());
+        assert!(CachePolicy::new(
+            &auth,
+            StatusCode::OK,
+            &headers(&[(CACHE_CONTROL, "public, max-age=60")]),
+            now
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_cache_policy_vary() {
+        let now = SystemTime::now();
+        let req = headers(&[(salvo_core::http::header::ACCEPT_LANGUAGE, "en")]);
+        let res = headers(&[
+            (VARY, "Accept-Language, Accept-Encoding"),
+            (CACHE_CONTROL, "max-age=60"),
+        ]);
+        let policy = CachePolicy::new(&req, StatusCode::OK, &res, now).unwrap();
+        assert!(policy.matches_vary(&req));
+        assert!(!policy.matches_vary(&headers(&[(salvo_core::http::header::ACCEPT_LANGUAGE, "fr")])));
+        assert!(!policy.matches_vary(&HeaderMap::new()));
+    }
+}
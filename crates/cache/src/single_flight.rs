@@ -0,0 +1,215 @@
+//! Single-flight middleware.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use salvo_core::handler::Skipper;
+use salvo_core::http::header::SET_COOKIE;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use tokio::sync::watch;
+
+use crate::{CacheIssuer, CachedBody, CachedEntry, MethodSkipper};
+
+type Flight = watch::Receiver<Option<Option<CachedEntry>>>;
+
+/// Single-flight middleware deduplicates concurrent identical requests.
+///
+/// While a request with a key is being handled, other requests with the same key wait for it and share its
+/// response instead of calling the handler again. This protects expensive handlers during cache stampedes.
+/// It is usually added before [`Cache`](crate::Cache).
+///
+/// Responses with `Set-Cookie` headers or streaming bodies are not shared, waiting requests call the handler
+/// by themselves.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_cache::{RequestIssuer, SingleFlight};
+///
+/// #[handler]
+/// async fn report() -> &'static str {
+///     "expensive report"
+/// }
+///
+/// let router = Router::with_path("report")
+///     .hoop(SingleFlight::new(RequestIssuer::default()))
+///     .get(report);
+/// ```
+#[non_exhaustive]
+pub struct SingleFlight<I: CacheIssuer> {
+    /// Key issuer.
+    pub issuer: I,
+    /// Skipper.
+    pub skipper: Box<dyn Skipper>,
+    flights: Mutex<HashMap<I::Key, Flight>>,
+}
+
+impl<I: CacheIssuer> SingleFlight<I> {
+    /// Create new `SingleFlight`, only `GET` requests are deduplicated by default.
+    #[inline]
+    pub fn new(issuer: I) -> Self {
+        let skipper = MethodSkipper::new().skip_all().skip_get(false);
+        SingleFlight {
+            issuer,
+            skipper: Box::new(skipper),
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Sets skipper and returns new `SingleFlight`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+/// Removes the flight when the leading request finishes, even if it is cancelled.
+struct FlightGuard<'a, K: Hash + Eq> {
+    flights: &'a Mutex<HashMap<K, Flight>>,
+    key: K,
+}
+impl<K: Hash + Eq> Drop for FlightGuard<'_, K> {
+    fn drop(&mut self) {
+        self.flights.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+    }
+}
+
+#[async_trait]
+impl<I> Handler for SingleFlight<I>
+where
+    I: CacheIssuer,
+    I::Key: Clone,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let Some(key) = self.issuer.issue(req, depot).await else {
+            return;
+        };
+        let leader = {
+            let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+            match flights.get(&key) {
+                Some(flight) => Err(flight.clone()),
+                None => {
+                    let (tx, rx) = watch::channel(None);
+                    flights.insert(key.clone(), rx);
+                    Ok(tx)
+                }
+            }
+        };
+        match leader {
+            Ok(tx) => {
+                let _guard = FlightGuard {
+                    flights: &self.flights,
+                    key,
+                };
+                ctrl.call_next(req, depot, res).await;
+                let entry = if res.headers().contains_key(SET_COOKIE) || res.body.is_error() {
+                    None
+                } else {
+                    CachedBody::try_from(&res.body)
+                        .ok()
+                        .map(|body| CachedEntry::new(res.status_code, res.headers().clone(), body))
+                };
+                tx.send_replace(Some(entry));
+            }
+            Err(mut flight) => {
+                let entry = match flight.wait_for(|entry| entry.is_some()).await {
+                    Ok(entry) => entry.clone().flatten(),
+                    Err(_) => None,
+                };
+                match entry {
+                    Some(CachedEntry {
+                        status, headers, body, ..
+                    }) => {
+                        if let Some(status) = status {
+                            res.status_code(status);
+                        }
+                        *res.headers_mut() = headers;
+                        *res.body_mut() = body.into();
+                        ctrl.skip_rest();
+                    }
+                    None => {
+                        tracing::debug!("response is not shared, handle request again");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::RequestIssuer;
+
+    struct Expensive {
+        count: Arc<AtomicUsize>,
+    }
+    #[async_trait]
+    impl Handler for Expensive {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if req.query::<bool>("cookie").unwrap_or(false) {
+                res.headers_mut()
+                    .insert(SET_COOKIE, salvo_core::http::HeaderValue::from_static("a=b"));
+            }
+            res.render(format!("call {count}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_flight() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let router = Router::new()
+            .hoop(SingleFlight::new(RequestIssuer::default()))
+            .goal(Expensive { count: count.clone() });
+        let service = Arc::new(Service::new(router));
+
+        let tasks = (0..5)
+            .map(|_| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    TestClient::get("http://127.0.0.1:5801/")
+                        .send(&*service)
+                        .await
+                        .take_string()
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), "call 1");
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&*service).await;
+        assert_eq!(res.take_string().await.unwrap(), "call 2");
+
+        let tasks = (0..2)
+            .map(|_| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    TestClient::get("http://127.0.0.1:5801/?cookie=true")
+                        .send(&*service)
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,207 @@
+//! Shared dictionaries for compression dictionary transport.
+use std::fmt::{self, Debug, Formatter};
+
+use base64::engine::{general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+
+use salvo_core::http::header::{HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use salvo_core::http::{Mime, Request, ResBody, Response};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// `Use-As-Dictionary` response header.
+pub const USE_AS_DICTIONARY: HeaderName = HeaderName::from_static("use-as-dictionary");
+/// `Available-Dictionary` request header.
+pub const AVAILABLE_DICTIONARY: HeaderName = HeaderName::from_static("available-dictionary");
+
+/// Magic number of `dcz` encoding, it is a zstd skippable frame containing the hash of the dictionary.
+pub(crate) const DCZ_MAGIC: [u8; 8] = [0x5e, 0x2a, 0x4d, 0x18, 0x20, 0x00, 0x00, 0x00];
+
+/// Shared dictionary used to compress responses with `dcz` encoding, as described by
+/// [RFC 9842](https://www.rfc-editor.org/rfc/rfc9842).
+///
+/// A client stores the dictionary when it is served with a `Use-As-Dictionary` header, `Dictionary`
+/// implements [`Handler`] to serve it. Later requests to urls matching `match_path` send the hash of the
+/// dictionary in the `Available-Dictionary` header, and [`Compression`](crate::Compression) compresses the
+/// response with the dictionary if it is registered by [`Compression::dictionary`](crate::Compression::dictionary).
+///
+/// Responses of repetitive content, such as JSON API payloads, are much smaller when they are compressed
+/// with a dictionary built from typical responses.
+///
+/// # Example
+///
+/// ```
+/// use salvo_compression::{Compression, Dictionary};
+/// use salvo_core::http::mime;
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn users() -> &'static str {
+///     r#"[{"id": 1, "name": "alice", "email": "alice@example.com"}]"#
+/// }
+///
+/// let dictionary = Dictionary::new(r#"[{"id": , "name": "", "email": "@example.com"}]"#, "/api/*")
+///     .id("api-v1")
+///     .content_types(&[mime::APPLICATION_JSON]);
+/// let router = Router::new()
+///     .hoop(Compression::new().dictionary(dictionary.clone()))
+///     .push(Router::with_path("dictionaries/api.dict").get(dictionary))
+///     .push(Router::with_path("api/users").get(users));
+/// ```
+#[derive(Clone)]
+pub struct Dictionary {
+    content: Bytes,
+    hash: [u8; 32],
+    match_path: String,
+    id: Option<String>,
+    content_types: Vec<Mime>,
+}
+impl Debug for Dictionary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionary")
+            .field("size", &self.content.len())
+            .field("hash", &STANDARD.encode(self.hash))
+            .field("match_path", &self.match_path)
+            .field("id", &self.id)
+            .field("content_types", &self.content_types)
+            .finish()
+    }
+}
+impl Dictionary {
+    /// Create a new `Dictionary` with its content and the url path pattern it applies to.
+    ///
+    /// `*` in `match_path` matches any characters, for example `/api/*`.
+    pub fn new(content: impl Into<Bytes>, match_path: impl Into<String>) -> Self {
+        let content = content.into();
+        let hash = Sha256::digest(&content).into();
+        Self {
+            content,
+            hash,
+            match_path: match_path.into(),
+            id: None,
+            content_types: Vec::new(),
+        }
+    }
+
+    /// Sets id of the dictionary, the client sends it back in the `Dictionary-ID` header.
+    #[inline]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets content types of responses compressed with the dictionary, all content types are allowed if it is
+    /// empty.
+    #[inline]
+    pub fn content_types(mut self, content_types: &[Mime]) -> Self {
+        self.content_types = content_types.to_vec();
+        self
+    }
+
+    /// Get content of the dictionary.
+    #[inline]
+    pub fn content(&self) -> &Bytes {
+        &self.content
+    }
+
+    /// Get SHA-256 hash of the dictionary.
+    #[inline]
+    pub fn hash(&self) -> &[u8; 32] {
+        &self.hash
+    }
+
+    /// Get value of the `Use-As-Dictionary` header.
+    pub fn use_as_dictionary(&self) -> HeaderValue {
+        let mut value = format!("match=\"{}\"", escape(&self.match_path));
+        if let Some(id) = &self.id {
+            value.push_str(&format!(", id=\"{}\"", escape(id)));
+        }
+        HeaderValue::from_str(&value).expect("`Use-As-Dictionary` header value should be valid")
+    }
+
+    /// Returns `true` if the dictionary can be used for the response of the request.
+    pub(crate) fn matches(&self, path: &str, available: &[u8], content_type: Option<&Mime>) -> bool {
+        if available != self.hash {
+            return false;
+        }
+        if !self.content_types.is_empty() {
+            let Some(content_type) = content_type else {
+                return false;
+            };
+            if !self.content_types.iter().any(|citem| {
+                citem.type_() == content_type.type_()
+                    && (citem.subtype() == "*" || citem.subtype() == content_type.subtype())
+            }) {
+                return false;
+            }
+        }
+        wildcard_match(&self.match_path, path)
+    }
+}
+
+#[async_trait]
+impl Handler for Dictionary {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let headers = res.headers_mut();
+        headers.insert(USE_AS_DICTIONARY, self.use_as_dictionary());
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        if !headers.contains_key(CACHE_CONTROL) {
+            headers.insert(CACHE_CONTROL, HeaderValue::from_static("public, max-age=86400"));
+        }
+        res.body(ResBody::Once(self.content.clone()));
+    }
+}
+
+/// Parse value of the `Available-Dictionary` header, it is a structured field byte sequence.
+pub(crate) fn parse_available_dictionary(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim().strip_prefix(':')?.strip_suffix(':')?;
+    STANDARD.decode(value).ok()
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn wildcard_match(pattern: &str, path: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("/api/*", "/api/users"));
+        assert!(wildcard_match("/api/*", "/api/"));
+        assert!(!wildcard_match("/api/*", "/static/app.js"));
+        assert!(wildcard_match("/api/*/items", "/api/users/items"));
+        assert!(!wildcard_match("/api/*/items", "/api/users"));
+        assert!(wildcard_match("/app.js", "/app.js"));
+        assert!(!wildcard_match("/app.js", "/app.json"));
+    }
+
+    #[test]
+    fn test_use_as_dictionary() {
+        let dictionary = Dictionary::new("dictionary", "/api/*").id("v1");
+        assert_eq!(dictionary.use_as_dictionary(), r#"match="/api/*", id="v1""#);
+        let value = format!(":{}:", STANDARD.encode(dictionary.hash()));
+        assert_eq!(parse_available_dictionary(&value).unwrap(), dictionary.hash());
+        assert!(parse_available_dictionary("abc").is_none());
+    }
+}
@@ -11,6 +11,8 @@ use flate2::write::ZlibEncoder;
 #[cfg(feature = "zstd")]
 use zstd::stream::write::Encoder as ZstdEncoder;
 
+#[cfg(feature = "dictionary")]
+use super::dictionary::{Dictionary, DCZ_MAGIC};
 use super::{CompressionAlgo, CompressionLevel};
 
 pub(super) struct Writer {
@@ -82,14 +84,18 @@ impl CompressionLevel {
     }
 
     #[cfg(feature = "zstd")]
-    fn into_zstd(self) -> ZstdEncoder<'static, Writer> {
-        let quality = match self {
+    fn zstd_quality(self) -> i32 {
+        match self {
             Self::Fastest => 1,
             Self::Minsize => 21,
             Self::Precise(quality) => quality.min(21) as i32,
             Self::Default => 1,
-        };
-        ZstdEncoder::new(Writer::new(), quality).unwrap()
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    fn into_zstd(self) -> ZstdEncoder<'static, Writer> {
+        ZstdEncoder::new(Writer::new(), self.zstd_quality()).unwrap()
     }
 }
 
@@ -118,6 +124,17 @@ impl Encoder {
             CompressionAlgo::Zstd => Self::Zstd(level.into_zstd()),
         }
     }
+
+    /// Create `dcz` encoder, the output starts with the magic number and the hash of the dictionary.
+    #[cfg(feature = "dictionary")]
+    pub(super) fn with_dictionary(level: CompressionLevel, dictionary: &Dictionary) -> IoResult<Self> {
+        let mut writer = Writer::new();
+        writer.write_all(&DCZ_MAGIC)?;
+        writer.write_all(dictionary.hash())?;
+        let encoder = ZstdEncoder::with_dictionary(writer, level.zstd_quality(), dictionary.content())?;
+        Ok(Self::Zstd(encoder))
+    }
+
     #[inline]
     pub(super) fn take(&mut self) -> IoResult<Bytes> {
         match *self {
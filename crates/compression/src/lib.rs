@@ -1,19 +1,32 @@
 //! Compression middleware for for Savlo web server framework.
 //!
 //! Read more: <https://salvo.rs>
+use std::fmt::{self, Debug, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use indexmap::IndexMap;
 
+use salvo_core::handler::{none_skipper, Skipper};
 use salvo_core::http::body::ResBody;
-use salvo_core::http::header::{HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE};
-use salvo_core::http::{Mime, StatusCode};
+#[cfg(feature = "dictionary")]
+use salvo_core::http::header::VARY;
+use salvo_core::http::header::{
+    HeaderValue, ACCEPT_ENCODING, ACCEPT_RANGES, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE,
+    CONTENT_TYPE, ETAG,
+};
+use salvo_core::http::{HeaderMap, Mime, NoBuffering, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 
+#[cfg(feature = "dictionary")]
+mod dictionary;
 mod encoder;
 mod stream;
+#[cfg(feature = "dictionary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+pub use dictionary::{Dictionary, AVAILABLE_DICTIONARY, USE_AS_DICTIONARY};
 use encoder::Encoder;
-use stream::EncodeStream;
+use stream::{EncodeMapper, EncodeStream};
 
 /// Level of compression data should be compressed with.
 #[non_exhaustive]
@@ -94,8 +107,30 @@ impl From<CompressionAlgo> for HeaderValue {
     }
 }
 
+/// How the `ETag` header of a compressed response is changed.
+///
+/// The compressed response is a different representation, so it must not share a strong `ETag` with the
+/// uncompressed one, otherwise a client resuming a download with `If-Range` gets a range of the uncompressed
+/// content appended to the compressed content it already has.
+#[non_exhaustive]
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq)]
+pub enum ETagPolicy {
+    /// Turn strong `ETag` into weak one, e.g. `"abc"` becomes `W/"abc"`. `If-None-Match` still matches, since it
+    /// uses weak comparison, while `If-Range` never matches and the full content is sent.
+    #[default]
+    Weaken,
+    /// Append the content encoding to the `ETag`, e.g. `"abc"` becomes `"abc-gzip"`.
+    Suffix,
+    /// Remove the `ETag` header.
+    Remove,
+}
+
 /// Compression
-#[derive(Clone, Debug)]
+///
+/// Responses are not compressed if they are already encoded, are partial content of a `Range` request, or have
+/// `Cache-Control: no-transform`. `Accept-Ranges` is removed from compressed responses, and their `ETag` is
+/// changed by [`ETagPolicy`].
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct Compression {
     /// Compression algorithms to use.
@@ -106,6 +141,28 @@ pub struct Compression {
     pub min_length: usize,
     /// Ignore request algorithms order in `Accept-Encoding` header and always server's config.
     pub force_priority: bool,
+    /// How the `ETag` header of compressed responses is changed.
+    pub etag_policy: ETagPolicy,
+    /// Shared dictionaries for `dcz` encoding.
+    #[cfg(feature = "dictionary")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+    pub dictionaries: Vec<Dictionary>,
+    /// Responses are not compressed when skipper returns `true`.
+    pub skipper: Arc<dyn Skipper>,
+}
+
+impl Debug for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Compression");
+        s.field("algos", &self.algos)
+            .field("content_types", &self.content_types)
+            .field("min_length", &self.min_length)
+            .field("force_priority", &self.force_priority)
+            .field("etag_policy", &self.etag_policy);
+        #[cfg(feature = "dictionary")]
+        s.field("dictionaries", &self.dictionaries);
+        s.finish()
+    }
 }
 
 impl Default for Compression {
@@ -134,6 +191,10 @@ impl Default for Compression {
             ],
             min_length: 0,
             force_priority: false,
+            etag_policy: ETagPolicy::default(),
+            #[cfg(feature = "dictionary")]
+            dictionaries: Vec::new(),
+            skipper: Arc::new(none_skipper),
         }
     }
 }
@@ -145,6 +206,39 @@ impl Compression {
         Default::default()
     }
 
+    /// Create a new `Compression` from config, `enabled` in config is not checked.
+    #[cfg(feature = "config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_config(config: &salvo_core::config::CompressionConfig) -> salvo_core::Result<Self> {
+        let mut compression = Self::new().min_length(config.min_length);
+        let level = config.level.map(CompressionLevel::Precise).unwrap_or_default();
+        if config.algorithms.is_empty() {
+            for algo_level in compression.algos.values_mut() {
+                *algo_level = level;
+            }
+        } else {
+            let mut algos = IndexMap::new();
+            for algo in &config.algorithms {
+                algos.insert(
+                    algo.parse::<CompressionAlgo>().map_err(salvo_core::Error::other)?,
+                    level,
+                );
+            }
+            compression.algos = algos;
+        }
+        if !config.content_types.is_empty() {
+            compression.content_types = config
+                .content_types
+                .iter()
+                .map(|v| {
+                    v.parse::<Mime>()
+                        .map_err(|_| salvo_core::Error::other(format!("invalid content type `{v}` in config")))
+                })
+                .collect::<salvo_core::Result<_>>()?;
+        }
+        Ok(compression)
+    }
+
     /// Remove all compression algorithms.
     #[inline]
     pub fn disable_all(mut self) -> Self {
@@ -233,6 +327,20 @@ impl Compression {
         self
     }
 
+    /// Sets how the `ETag` header of compressed responses is changed.
+    #[inline]
+    pub fn etag_policy(mut self, etag_policy: ETagPolicy) -> Self {
+        self.etag_policy = etag_policy;
+        self
+    }
+
+    /// Sets skipper, responses are not compressed when it returns `true`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Arc::new(skipper);
+        self
+    }
+
     /// Sets `Compression` with content types list.
     #[inline]
     pub fn content_types(mut self, content_types: &[Mime]) -> Self {
@@ -240,35 +348,46 @@ impl Compression {
         self
     }
 
-    fn negotiate(&self, req: &Request, res: &Response) -> Option<(CompressionAlgo, CompressionLevel)> {
-        if req.headers().contains_key(&CONTENT_ENCODING) {
+    /// Register a shared dictionary, responses are compressed with `dcz` encoding when the client has the
+    /// dictionary and accepts `dcz`.
+    ///
+    /// The level of `dcz` encoding is the level of zstd algorithm.
+    #[cfg(feature = "dictionary")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+    #[inline]
+    pub fn dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionaries.push(dictionary);
+        self
+    }
+
+    fn negotiate(&self, req_headers: &HeaderMap, path: &str, res: &Response) -> Option<(Encoder, HeaderValue)> {
+        if req_headers.contains_key(&CONTENT_ENCODING) {
             return None;
         }
 
+        let content_type = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<Mime>().ok());
         if !self.content_types.is_empty() {
-            let content_type = res
-                .headers()
-                .get(CONTENT_TYPE)
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or_default();
-            if content_type.is_empty() {
-                return None;
-            }
-            if let Ok(content_type) = content_type.parse::<Mime>() {
-                if !self.content_types.iter().any(|citem| {
-                    citem.type_() == content_type.type_()
-                        && (citem.subtype() == "*" || citem.subtype() == content_type.subtype())
-                }) {
-                    return None;
-                }
-            } else {
+            let content_type = content_type.as_ref()?;
+            if !self.content_types.iter().any(|citem| {
+                citem.type_() == content_type.type_()
+                    && (citem.subtype() == "*" || citem.subtype() == content_type.subtype())
+            }) {
                 return None;
             }
         }
-        let header = req.headers().get(ACCEPT_ENCODING).and_then(|v| v.to_str().ok())?;
+        let header = req_headers.get(ACCEPT_ENCODING).and_then(|v| v.to_str().ok())?;
+
+        #[cfg(feature = "dictionary")]
+        if let Some(encoder) = self.negotiate_dictionary(req_headers, path, header, content_type.as_ref()) {
+            return Some((encoder, HeaderValue::from_static("dcz")));
+        }
 
         let accept_algos = parse_accept_encoding(header);
-        if self.force_priority {
+        let (algo, level) = if self.force_priority {
             let accept_algos = accept_algos.into_iter().map(|(algo, _)| algo).collect::<Vec<_>>();
             self.algos
                 .iter()
@@ -278,10 +397,82 @@ impl Compression {
             accept_algos
                 .into_iter()
                 .find_map(|(algo, _)| self.algos.get(&algo).map(|level| (algo, *level)))
+        }?;
+        Some((Encoder::new(algo, level), algo.into()))
+    }
+
+    /// Set `Content-Encoding`, and update headers which are not valid for the compressed representation.
+    fn update_headers(&self, res: &mut Response, encoding: HeaderValue) {
+        let headers = res.headers_mut();
+        if let Some(etag) = headers.remove(ETAG) {
+            let etag = match self.etag_policy {
+                ETagPolicy::Weaken if etag.as_bytes().starts_with(b"W/") => Some(etag),
+                ETagPolicy::Weaken => HeaderValue::from_bytes(&[&b"W/"[..], etag.as_bytes()].concat()).ok(),
+                ETagPolicy::Suffix => match etag.as_bytes().strip_suffix(b"\"") {
+                    Some(tag) => {
+                        HeaderValue::from_bytes(&[tag, &b"-"[..], encoding.as_bytes(), &b"\""[..]].concat()).ok()
+                    }
+                    None => None,
+                },
+                ETagPolicy::Remove => None,
+            };
+            if let Some(etag) = etag {
+                headers.insert(ETAG, etag);
+            }
+        }
+        headers.remove(ACCEPT_RANGES);
+        headers.insert(CONTENT_ENCODING, encoding);
+    }
+
+    /// Find the registered dictionary the client has, and create `dcz` encoder with it.
+    #[cfg(feature = "dictionary")]
+    fn negotiate_dictionary(
+        &self,
+        req_headers: &HeaderMap,
+        path: &str,
+        accept_encoding: &str,
+        content_type: Option<&Mime>,
+    ) -> Option<Encoder> {
+        if self.dictionaries.is_empty() || !accepts_encoding(accept_encoding, "dcz") {
+            return None;
+        }
+        let available = req_headers
+            .get(AVAILABLE_DICTIONARY)
+            .and_then(|v| v.to_str().ok())
+            .and_then(dictionary::parse_available_dictionary)?;
+        let dictionary = self
+            .dictionaries
+            .iter()
+            .find(|dictionary| dictionary.matches(path, &available, content_type))?;
+        let level = self.algos.get(&CompressionAlgo::Zstd).copied().unwrap_or_default();
+        match Encoder::with_dictionary(level, dictionary) {
+            Ok(encoder) => Some(encoder),
+            Err(e) => {
+                tracing::error!(error = ?e, "create dictionary encoder failed");
+                None
+            }
         }
     }
 }
 
+/// Returns `true` if the encoding is in `Accept-Encoding` header and its quality is not zero.
+#[cfg(feature = "dictionary")]
+fn accepts_encoding(header: &str, encoding: &str) -> bool {
+    header.split(',').any(|s| {
+        let mut iter = s.trim().split(';');
+        iter.next()
+            .map(|v| v.trim().eq_ignore_ascii_case(encoding))
+            .unwrap_or(false)
+            && !iter.any(|q| {
+                q.trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .map(|q| q <= 0.0)
+                    .unwrap_or(false)
+            })
+    })
+}
+
 fn parse_accept_encoding(header: &str) -> Vec<(CompressionAlgo, u8)> {
     let mut vec = header
         .split(',')
@@ -311,15 +502,29 @@ fn parse_accept_encoding(header: &str) -> Vec<(CompressionAlgo, u8)> {
 #[async_trait]
 impl Handler for Compression {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        ctrl.call_next(req, depot, res).await;
-        if ctrl.is_ceased() || res.headers().contains_key(CONTENT_ENCODING) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
             return;
         }
-
-        if let Some(code) = res.status_code {
-            if code == StatusCode::SWITCHING_PROTOCOLS || code == StatusCode::NO_CONTENT {
-                return;
-            }
+        if req.route_metadata().contains::<NoBuffering>() {
+            // Compress flushed data chunk by chunk, the code after `call_next` runs after the response is flushed.
+            let compression = self.clone();
+            let req_headers = req.headers().clone();
+            let path = req.uri().path().to_owned();
+            res.before_flush(move |res| {
+                if !is_compressible(res) {
+                    return;
+                }
+                if let Some((encoder, encoding)) = compression.negotiate(&req_headers, &path, res) {
+                    res.map_body(EncodeMapper::new(encoder));
+                    compression.update_headers(res, encoding);
+                    add_vary(res);
+                }
+            });
+        }
+        ctrl.call_next(req, depot, res).await;
+        if ctrl.is_ceased() || res.is_flushed() || !is_compressible(res) {
+            return;
         }
 
         match res.take_body() {
@@ -331,10 +536,10 @@ impl Handler for Compression {
                     res.body(ResBody::Once(bytes));
                     return;
                 }
-                match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, Some(bytes)));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                match self.negotiate(req.headers(), req.uri().path(), res) {
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, Some(bytes)));
+                        self.update_headers(res, encoding);
                     }
                     None => {
                         res.body(ResBody::Once(bytes));
@@ -350,10 +555,10 @@ impl Handler for Compression {
                         return;
                     }
                 }
-                match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, chunks));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                match self.negotiate(req.headers(), req.uri().path(), res) {
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, chunks));
+                        self.update_headers(res, encoding);
                     }
                     None => {
                         res.body(ResBody::Chunks(chunks));
@@ -361,10 +566,10 @@ impl Handler for Compression {
                     }
                 }
             }
-            ResBody::Hyper(body) => match self.negotiate(req, res) {
-                Some((algo, level)) => {
-                    res.stream(EncodeStream::new(algo, level, body));
-                    res.headers_mut().append(CONTENT_ENCODING, algo.into());
+            ResBody::Hyper(body) => match self.negotiate(req.headers(), req.uri().path(), res) {
+                Some((encoder, encoding)) => {
+                    res.stream(EncodeStream::new(encoder, body));
+                    self.update_headers(res, encoding);
                 }
                 None => {
                     res.body(ResBody::Hyper(body));
@@ -373,10 +578,10 @@ impl Handler for Compression {
             },
             ResBody::Stream(body) => {
                 let body = body.into_inner();
-                match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, body));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                match self.negotiate(req.headers(), req.uri().path(), res) {
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, body));
+                        self.update_headers(res, encoding);
                     }
                     None => {
                         res.body(ResBody::stream(body));
@@ -386,12 +591,52 @@ impl Handler for Compression {
             }
             _ => {}
         }
+        add_vary(res);
         res.headers_mut().remove(CONTENT_LENGTH);
     }
 }
 
+/// Returns `false` if the response is already encoded, is partial content, or must not be transformed.
+fn is_compressible(res: &Response) -> bool {
+    if let Some(code) = res.status_code {
+        if code == StatusCode::SWITCHING_PROTOCOLS
+            || code == StatusCode::NO_CONTENT
+            || code == StatusCode::PARTIAL_CONTENT
+        {
+            return false;
+        }
+    }
+    let headers = res.headers();
+    if headers.contains_key(CONTENT_RANGE) {
+        return false;
+    }
+    if headers
+        .get(CONTENT_ENCODING)
+        .map(|v| v.as_bytes() != b"identity")
+        .unwrap_or(false)
+    {
+        return false;
+    }
+    !headers.get_all(CACHE_CONTROL).iter().any(|v| {
+        v.to_str()
+            .map(|v| v.split(',').any(|d| d.trim().eq_ignore_ascii_case("no-transform")))
+            .unwrap_or(false)
+    })
+}
+
+/// Responses compressed with a dictionary are varied by the dictionary the client has.
+#[allow(unused_variables)]
+fn add_vary(res: &mut Response) {
+    #[cfg(feature = "dictionary")]
+    if res.headers().get(CONTENT_ENCODING).map(|v| v == "dcz").unwrap_or(false) {
+        res.headers_mut()
+            .append(VARY, HeaderValue::from_static("accept-encoding, available-dictionary"));
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use salvo_core::handler::RequestSkipper;
     use salvo_core::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
@@ -417,6 +662,30 @@ mod tests {
         assert_eq!(content, "hello");
     }
 
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_skipper() {
+        let comp_handler = Compression::new()
+            .min_length(1)
+            .skipper(RequestSkipper::new().path("/healthz"));
+        let router = Router::with_hoop(comp_handler)
+            .push(Router::with_path("hello").get(hello))
+            .push(Router::with_path("healthz").get(hello));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        let mut res = TestClient::get("http://127.0.0.1:5801/healthz")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert!(res.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+    }
+
     #[tokio::test]
     async fn test_brotli() {
         let comp_handler = Compression::new().min_length(1);
@@ -444,4 +713,211 @@ mod tests {
         let content = res.take_string().await.unwrap();
         assert_eq!(content, "hello");
     }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_etag_and_range() {
+        #[handler]
+        async fn file(req: &mut Request, res: &mut Response) {
+            res.add_header(ETAG, "\"abc\"", true).unwrap();
+            res.add_header(ACCEPT_RANGES, "bytes", true).unwrap();
+            if req.headers().contains_key("range") {
+                res.status_code(StatusCode::PARTIAL_CONTENT);
+                res.add_header(CONTENT_RANGE, "bytes 0-4/11", true).unwrap();
+                res.render("hello");
+            } else {
+                res.render("hello world");
+            }
+        }
+        #[handler]
+        async fn no_transform(res: &mut Response) {
+            res.add_header(CACHE_CONTROL, "public, no-transform", true).unwrap();
+            res.render("hello world");
+        }
+        let router = Router::new()
+            .push(
+                Router::with_path("weaken")
+                    .hoop(Compression::new().min_length(1))
+                    .get(file),
+            )
+            .push(
+                Router::with_path("suffix")
+                    .hoop(Compression::new().min_length(1).etag_policy(ETagPolicy::Suffix))
+                    .get(file),
+            )
+            .push(
+                Router::with_path("no-transform")
+                    .hoop(Compression::new().min_length(1))
+                    .get(no_transform),
+            );
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/weaken")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(res.headers()[ETAG], "W/\"abc\"");
+        assert!(!res.headers().contains_key(ACCEPT_RANGES));
+        assert_eq!(res.take_string().await.unwrap(), "hello world");
+
+        let res = TestClient::get("http://127.0.0.1:5801/suffix")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[ETAG], "\"abc-gzip\"");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/weaken")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .add_header("range", "bytes=0-4", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PARTIAL_CONTENT);
+        assert!(!res.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(res.headers()[ETAG], "\"abc\"");
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let res = TestClient::get("http://127.0.0.1:5801/no-transform")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert!(!res.headers().contains_key(CONTENT_ENCODING));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_flushed_response() {
+        use std::io::Read;
+
+        use futures_util::StreamExt;
+        use salvo_core::conn::SocketAddr;
+        use salvo_core::http::body::ReqBody;
+        use salvo_core::http::uri::Scheme;
+        use salvo_core::hyper::service::Service as _;
+
+        #[handler]
+        async fn progressive(res: &mut Response) {
+            res.add_header(CONTENT_TYPE, "text/html", true).unwrap();
+            res.write_body("<html><body>").unwrap();
+            res.flush().await.unwrap();
+            res.write_body("hello</body></html>").unwrap();
+        }
+        let router = Router::with_hoop(Compression::new()).meta(NoBuffering).get(progressive);
+        let service = Service::new(router);
+        let handler = service.hyper_handler(SocketAddr::Unknown, SocketAddr::Unknown, Scheme::HTTP, None);
+        let req = salvo_core::hyper::Request::builder()
+            .uri("http://127.0.0.1:5801/")
+            .header(ACCEPT_ENCODING, "gzip")
+            .body(ReqBody::None)
+            .unwrap();
+        let res = handler.call(req).await.unwrap();
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+
+        let mut body = res.into_body();
+        let mut data = Vec::new();
+        while let Some(frame) = body.next().await {
+            data.extend_from_slice(&frame.unwrap().into_data().unwrap());
+        }
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(&data[..])
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "<html><body>hello</body></html>");
+    }
+
+    #[cfg(feature = "dictionary")]
+    #[tokio::test]
+    async fn test_dictionary() {
+        use std::io::Read;
+
+        use base64::engine::{general_purpose::STANDARD, Engine};
+        use salvo_core::http::header::VARY;
+
+        #[handler]
+        async fn users(res: &mut Response) {
+            res.render(Json(serde_json::json!([
+                {"id": 1, "name": "alice", "email": "alice@example.com", "active": true},
+                {"id": 2, "name": "bob", "email": "bob@example.com", "active": false},
+            ])));
+        }
+        let dictionary = Dictionary::new(
+            r#"[{"id":,"name":"","email":"@example.com","active":true},{"active":false}]"#,
+            "/api/*",
+        )
+        .id("api")
+        .content_types(&["application/json".parse().unwrap()]);
+        let router = Router::with_hoop(Compression::new().dictionary(dictionary.clone()))
+            .push(Router::with_path("dictionary").get(dictionary.clone()))
+            .push(Router::with_path("api/users").get(users))
+            .push(Router::with_path("hello").get(hello));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/dictionary").send(&service).await;
+        assert_eq!(res.headers()[USE_AS_DICTIONARY], r#"match="/api/*", id="api""#);
+
+        let available = format!(":{}:", STANDARD.encode(dictionary.hash()));
+        let mut res = TestClient::get("http://127.0.0.1:5801/api/users")
+            .add_header(ACCEPT_ENCODING, "gzip, br, zstd, dcz", true)
+            .add_header(AVAILABLE_DICTIONARY, &available, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_ENCODING], "dcz");
+        assert_eq!(res.headers()[VARY], "accept-encoding, available-dictionary");
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(&body[..8], &dictionary::DCZ_MAGIC);
+        assert_eq!(&body[8..40], dictionary.hash());
+        let mut decoder = zstd::stream::read::Decoder::with_dictionary(&body[40..], dictionary.content()).unwrap();
+        let mut content = String::new();
+        decoder.read_to_string(&mut content).unwrap();
+        assert!(content.contains("bob@example.com"));
+
+        // The content type is not allowed by the dictionary.
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "gzip, dcz", true)
+            .add_header(AVAILABLE_DICTIONARY, &available, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+
+        // The client does not have the dictionary.
+        let res = TestClient::get("http://127.0.0.1:5801/api/users")
+            .add_header(ACCEPT_ENCODING, "gzip, dcz", true)
+            .add_header(AVAILABLE_DICTIONARY, ":AAAA:", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+
+        let res = TestClient::get("http://127.0.0.1:5801/api/users")
+            .add_header(ACCEPT_ENCODING, "gzip, dcz;q=0", true)
+            .add_header(AVAILABLE_DICTIONARY, &available, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+    }
+
+    #[cfg(all(feature = "config", feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn test_compression_from_config() {
+        let config: salvo_core::config::CompressionConfig = serde_json::from_value(serde_json::json!({
+            "algorithms": "gzip, zstd",
+            "level": 3,
+            "min_length": 1024,
+            "content_types": ["text/*"],
+        }))
+        .unwrap();
+        let compression = Compression::from_config(&config).unwrap();
+        assert_eq!(
+            compression.algos.iter().collect::<Vec<_>>(),
+            vec![
+                (&CompressionAlgo::Gzip, &CompressionLevel::Precise(3)),
+                (&CompressionAlgo::Zstd, &CompressionLevel::Precise(3))
+            ]
+        );
+        assert_eq!(compression.min_length, 1024);
+        assert_eq!(compression.content_types, vec!["text/*".parse::<Mime>().unwrap()]);
+
+        let config: salvo_core::config::CompressionConfig =
+            serde_json::from_value(serde_json::json!({ "algorithms": "lzma" })).unwrap();
+        assert!(Compression::from_config(&config).is_err());
+    }
 }
@@ -9,10 +9,10 @@ use bytes::Bytes;
 use futures_util::stream::{BoxStream, Stream};
 use tokio::task::{spawn_blocking, JoinHandle};
 
-use salvo_core::http::body::{Body, BytesFrame, HyperBody};
+use salvo_core::http::body::{Body, BodyMapper, BytesFrame, HyperBody};
 use salvo_core::BoxedError;
 
-use super::{CompressionAlgo, CompressionLevel, Encoder};
+use super::Encoder;
 
 const MAX_CHUNK_SIZE_ENCODE_IN_PLACE: usize = 1024;
 
@@ -24,13 +24,12 @@ pub(super) struct EncodeStream<B> {
 }
 
 impl<B> EncodeStream<B> {
-    #[allow(unused_variables)]
-    pub(super) fn new(algo: CompressionAlgo, level: CompressionLevel, body: B) -> Self {
+    pub(super) fn new(encoder: Encoder, body: B) -> Self {
         Self {
             body,
             eof: false,
             encoding: None,
-            encoder: Some(Encoder::new(algo, level)),
+            encoder: Some(encoder),
         }
     }
 }
@@ -151,3 +150,31 @@ impl_stream!(BoxStream<'static, Result<BytesFrame, BoxedError>>);
 impl_stream!(HyperBody);
 impl_stream!(Option<Bytes>);
 impl_stream!(VecDeque<Bytes>);
+
+/// Compress body data chunk by chunk, every chunk is flushed so it can be decoded by the client immediately.
+pub(super) struct EncodeMapper {
+    encoder: Option<Encoder>,
+}
+impl EncodeMapper {
+    pub(super) fn new(encoder: Encoder) -> Self {
+        Self { encoder: Some(encoder) }
+    }
+}
+impl BodyMapper for EncodeMapper {
+    fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+        match &mut self.encoder {
+            Some(encoder) => {
+                encoder.write(&chunk)?;
+                Ok(encoder.take()?)
+            }
+            None => Ok(chunk),
+        }
+    }
+
+    fn finish(&mut self) -> Result<Bytes, BoxedError> {
+        match self.encoder.take() {
+            Some(encoder) => Ok(encoder.finish()?),
+            None => Ok(Bytes::new()),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Benchmarks of matching requests against routers.
+//!
+//! Run with `cargo bench -p salvo_core --bench routing`.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use salvo_core::http::Method;
+use salvo_core::prelude::*;
+use salvo_core::routing::PathState;
+
+#[handler]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+/// A router like the API of a typical service, with static routes, params, typed params and rest params.
+fn api_router() -> Router {
+    let mut router = Router::with_path("api/v1");
+    for resource in [
+        "users", "orders", "products", "invoices", "teams", "projects", "issues", "comments",
+    ] {
+        router = router.push(
+            Router::with_path(resource).get(hello).post(hello).push(
+                Router::with_path("<id:num>")
+                    .get(hello)
+                    .patch(hello)
+                    .delete(hello)
+                    .push(Router::with_path("items/<item_id>").get(hello)),
+            ),
+        );
+    }
+    Router::new()
+        .push(router)
+        .push(Router::with_path("static/<**path>").get(hello))
+        .push(Router::with_path("health").get(hello))
+}
+
+fn request(method: Method, path: &str) -> Request {
+    let mut req = Request::new();
+    *req.method_mut() = method;
+    *req.uri_mut() = format!("http://127.0.0.1:5800{path}").parse().unwrap();
+    req
+}
+
+fn bench_detect(c: &mut Criterion) {
+    let router = api_router();
+    let mut group = c.benchmark_group("detect");
+    for (name, method, path) in [
+        ("static", Method::GET, "/health"),
+        ("nested_static", Method::POST, "/api/v1/comments"),
+        ("param", Method::GET, "/api/v1/comments/42"),
+        ("two_params", Method::GET, "/api/v1/comments/42/items/abc"),
+        ("rest", Method::GET, "/static/css/site/main.css"),
+        ("percent_encoded", Method::GET, "/api/v1/comments/42/items/a%20b"),
+        ("not_found", Method::GET, "/api/v2/unknown/path"),
+    ] {
+        let mut req = request(method, path);
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let mut path_state = PathState::new(req.uri().path());
+                black_box(router.detect(&mut req, &mut path_state).is_some())
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_path_state(c: &mut Criterion) {
+    c.bench_function("path_state_new", |b| {
+        b.iter(|| PathState::new(black_box("/api/v1/comments/42/items/abc")))
+    });
+}
+
+criterion_group!(benches, bench_detect, bench_path_state);
+criterion_main!(benches);
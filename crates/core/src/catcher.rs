@@ -32,6 +32,7 @@
 //! [`write_error_default`] to capture processing errors and send the default error page.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -48,8 +49,28 @@ static SUPPORTED_FORMATS: Lazy<Vec<mime::Name>> = Lazy::new(|| vec![mime::JSON,
 const EMPTY_CAUSE_MSG: &str = "There is no more detailed explanation.";
 const SALVO_LINK: &str = r#"<a href="https://salvo.rs" target="_blank">salvo</a>"#;
 
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[inline]
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
 #[inline]
-fn status_error_html(code: StatusCode, name: &str, brief: &str, cause: Option<&str>, footer: Option<&str>) -> String {
+fn status_error_html(
+    code: StatusCode,
+    name: &str,
+    brief: &str,
+    cause: Option<&str>,
+    request_id: Option<&str>,
+    footer: Option<&str>,
+) -> String {
+    let request_id = request_id
+        .map(|id| format!("<p>Request ID: <code>{}</code></p>", escape_html(id)))
+        .unwrap_or_default();
     format!(
         r#"<!DOCTYPE html>
 <html>
@@ -82,18 +103,25 @@ fn status_error_html(code: StatusCode, name: &str, brief: &str, cause: Option<&s
     </style>
 </head>
 <body>
-    <div><h1>{0}: {1}</h1><h3>{2}</h3><pre>{3}</pre><hr><footer>{4}</footer></div>
+    <div><h1>{0}: {1}</h1><h3>{2}</h3><pre>{3}</pre>{4}<hr><footer>{5}</footer></div>
 </body>
 </html>"#,
         code.as_u16(),
         name,
         brief,
         cause.unwrap_or(EMPTY_CAUSE_MSG),
+        request_id,
         footer.unwrap_or(SALVO_LINK)
     )
 }
 #[inline]
-fn status_error_json(code: StatusCode, name: &str, brief: &str, cause: Option<&str>) -> String {
+fn status_error_json(
+    code: StatusCode,
+    name: &str,
+    brief: &str,
+    cause: Option<&str>,
+    request_id: Option<&str>,
+) -> String {
     #[derive(Serialize)]
     struct Data<'a> {
         error: Error<'a>,
@@ -104,6 +132,8 @@ fn status_error_json(code: StatusCode, name: &str, brief: &str, cause: Option<&s
         name: &'a str,
         brief: &'a str,
         cause: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_id: Option<&'a str>,
     }
     let data = Data {
         error: Error {
@@ -111,28 +141,48 @@ fn status_error_json(code: StatusCode, name: &str, brief: &str, cause: Option<&s
             name,
             brief,
             cause: cause.unwrap_or(EMPTY_CAUSE_MSG),
+            request_id,
         },
     };
     serde_json::to_string(&data).unwrap()
 }
 #[inline]
-fn status_error_plain(code: StatusCode, name: &str, brief: &str, cause: Option<&str>) -> String {
-    format!(
+fn status_error_plain(
+    code: StatusCode,
+    name: &str,
+    brief: &str,
+    cause: Option<&str>,
+    request_id: Option<&str>,
+) -> String {
+    let mut content = format!(
         "code: {}\n\nname: {}\n\nbrief: {}\n\ncause: {}",
         code.as_u16(),
         name,
         brief,
         cause.unwrap_or(EMPTY_CAUSE_MSG)
-    )
+    );
+    if let Some(request_id) = request_id {
+        content.push_str("\n\nrequest_id: ");
+        content.push_str(request_id);
+    }
+    content
 }
 #[inline]
-fn status_error_xml(code: StatusCode, name: &str, brief: &str, cause: Option<&str>) -> String {
+fn status_error_xml(
+    code: StatusCode,
+    name: &str,
+    brief: &str,
+    cause: Option<&str>,
+    request_id: Option<&str>,
+) -> String {
     #[derive(Serialize)]
     struct Data<'a> {
         code: u16,
         name: &'a str,
         brief: &'a str,
         cause: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_id: Option<&'a str>,
     }
 
     let data = Data {
@@ -140,12 +190,22 @@ fn status_error_xml(code: StatusCode, name: &str, brief: &str, cause: Option<&st
         name,
         brief,
         cause: cause.unwrap_or(EMPTY_CAUSE_MSG),
+        request_id,
     };
     serde_xml_rs::to_string(&data).unwrap()
 }
 /// Create bytes from `StatusError`.
 #[inline]
 pub fn status_error_bytes(err: &StatusError, prefer_format: &Mime, footer: Option<&str>) -> (Mime, Bytes) {
+    status_error_bytes_with_request_id(err, prefer_format, None, footer)
+}
+/// Create bytes from `StatusError`, the request id is rendered in the error page if it is provided.
+pub fn status_error_bytes_with_request_id(
+    err: &StatusError,
+    prefer_format: &Mime,
+    request_id: Option<&str>,
+    footer: Option<&str>,
+) -> (Mime, Bytes) {
     let format = if !SUPPORTED_FORMATS.contains(&prefer_format.subtype()) {
         "text/html".parse().unwrap()
     } else {
@@ -156,10 +216,10 @@ pub fn status_error_bytes(err: &StatusError, prefer_format: &Mime, footer: Optio
     #[cfg(not(debug_assertions))]
     let cause: Option<String> = None;
     let content = match format.subtype().as_ref() {
-        "plain" => status_error_plain(err.code, &err.name, &err.brief, cause.as_deref()),
-        "json" => status_error_json(err.code, &err.name, &err.brief, cause.as_deref()),
-        "xml" => status_error_xml(err.code, &err.name, &err.brief, cause.as_deref()),
-        _ => status_error_html(err.code, &err.name, &err.brief, cause.as_deref(), footer),
+        "plain" => status_error_plain(err.code, &err.name, &err.brief, cause.as_deref(), request_id),
+        "json" => status_error_json(err.code, &err.name, &err.brief, cause.as_deref(), request_id),
+        "xml" => status_error_xml(err.code, &err.name, &err.brief, cause.as_deref(), request_id),
+        _ => status_error_html(err.code, &err.name, &err.brief, cause.as_deref(), request_id, footer),
     };
     (format, Bytes::from(content))
 }
@@ -191,6 +251,13 @@ impl Catcher {
         }
     }
 
+    /// Set the goal handler, it is called after all middlewares.
+    #[inline]
+    pub fn goal<H: Handler>(mut self, goal: H) -> Self {
+        self.goal = Arc::new(goal);
+        self
+    }
+
     /// Get current catcher's middlewares reference.
     #[inline]
     pub fn hoops(&self) -> &Vec<Arc<dyn Handler>> {
@@ -238,20 +305,117 @@ where
     }
 }
 
+/// Information of the error passed to [`ErrorTemplate`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ErrorContext<'a> {
+    /// Http status code.
+    pub code: StatusCode,
+    /// Name of the error.
+    pub name: &'a str,
+    /// Brief of the error.
+    pub brief: &'a str,
+    /// Cause of the error, it is only provided in debug builds.
+    pub cause: Option<&'a str>,
+    /// Request id from `x-request-id` response header.
+    pub request_id: Option<&'a str>,
+}
+
+type TemplateFn = dyn Fn(&ErrorContext<'_>, &Request, &Depot) -> String + Send + Sync;
+
+/// HTML template of error page, used by [`DefaultGoal`] when the client accepts HTML.
+///
+/// A template can be a string with placeholders `{code}`, `{name}`, `{brief}`, `{cause}` and `{request_id}`,
+/// they are replaced with HTML escaped values. Or it can be a function with access to [`Request`] and [`Depot`],
+/// such as for rendering the page in the locale of the request.
+#[derive(Clone)]
+pub struct ErrorTemplate(TemplateKind);
+#[derive(Clone)]
+enum TemplateKind {
+    Text(Cow<'static, str>),
+    Fn(Arc<TemplateFn>),
+}
+impl ErrorTemplate {
+    /// Create template from a string with placeholders.
+    #[inline]
+    pub fn new(template: impl Into<Cow<'static, str>>) -> Self {
+        Self(TemplateKind::Text(template.into()))
+    }
+    /// Create template from a function.
+    #[inline]
+    pub fn with_fn<F>(f: F) -> Self
+    where
+        F: Fn(&ErrorContext<'_>, &Request, &Depot) -> String + Send + Sync + 'static,
+    {
+        Self(TemplateKind::Fn(Arc::new(f)))
+    }
+
+    /// Render error page.
+    pub fn render(&self, ctx: &ErrorContext<'_>, req: &Request, depot: &Depot) -> String {
+        match &self.0 {
+            TemplateKind::Text(template) => template
+                .replace("{code}", &ctx.code.as_u16().to_string())
+                .replace("{name}", &escape_html(ctx.name))
+                .replace("{brief}", &escape_html(ctx.brief))
+                .replace("{cause}", &escape_html(ctx.cause.unwrap_or(EMPTY_CAUSE_MSG)))
+                .replace("{request_id}", &escape_html(ctx.request_id.unwrap_or_default())),
+            TemplateKind::Fn(f) => f(ctx, req, depot),
+        }
+    }
+}
+impl std::fmt::Debug for ErrorTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            TemplateKind::Text(template) => f.debug_tuple("ErrorTemplate").field(template).finish(),
+            TemplateKind::Fn(_) => f.debug_tuple("ErrorTemplate").field(&"Fn").finish(),
+        }
+    }
+}
+impl From<&'static str> for ErrorTemplate {
+    #[inline]
+    fn from(template: &'static str) -> Self {
+        Self::new(template)
+    }
+}
+impl From<String> for ErrorTemplate {
+    #[inline]
+    fn from(template: String) -> Self {
+        Self::new(template)
+    }
+}
+
 /// Default [`Handler`] for [`Catcher`].
 ///
 /// If http status is error, and user is not set custom catcher to catch them,
 /// `write_error_default` will used to catch them.
 ///
-/// `Catcher` supports sending error pages in `XML`, `JSON`, `HTML`, `Text` formats.
+/// `Catcher` supports sending error pages in `XML`, `JSON`, `HTML`, `Text` formats, the format is negotiated
+/// by the `Accept` header of the request. HTML pages can be customized with [`ErrorTemplate`]s registered for
+/// a status code or a status class, the template of status code is preferred.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::catcher::{Catcher, DefaultGoal, ErrorTemplate};
+/// use salvo_core::prelude::*;
+///
+/// let goal = DefaultGoal::new()
+///     .template(StatusCode::NOT_FOUND, "<h1>{code}: page not found</h1>")
+///     .class_template(5, ErrorTemplate::with_fn(|ctx, _req, _depot| {
+///         format!("<h1>{}: please retry later</h1>", ctx.code.as_u16())
+///     }));
+/// let service = Service::new(Router::new()).catcher(Catcher::default().goal(goal));
+/// ```
 #[derive(Default)]
 pub struct DefaultGoal {
     footer: Option<Cow<'static, str>>,
+    templates: HashMap<StatusCode, ErrorTemplate>,
+    class_templates: HashMap<u16, ErrorTemplate>,
 }
 impl DefaultGoal {
     /// Create new `Catcher`.
     pub fn new() -> Self {
-        DefaultGoal { footer: None }
+        Self::default()
     }
     /// Create with footer.
     #[inline]
@@ -264,25 +428,98 @@ impl DefaultGoal {
         self.footer = Some(footer.into());
         self
     }
+
+    /// Set HTML template for the status code.
+    #[inline]
+    pub fn template(mut self, code: StatusCode, template: impl Into<ErrorTemplate>) -> Self {
+        self.templates.insert(code, template.into());
+        self
+    }
+
+    /// Set HTML template for the status class, `4` for client errors and `5` for server errors.
+    #[inline]
+    pub fn class_template(mut self, class: u16, template: impl Into<ErrorTemplate>) -> Self {
+        self.class_templates.insert(class, template.into());
+        self
+    }
+
+    fn find_template(&self, code: StatusCode) -> Option<&ErrorTemplate> {
+        self.templates
+            .get(&code)
+            .or_else(|| self.class_templates.get(&(code.as_u16() / 100)))
+    }
 }
 #[async_trait]
 impl Handler for DefaultGoal {
-    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
         let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
         if (status.is_server_error() || status.is_client_error()) && (res.body.is_none() || res.body.is_error()) {
+            if let Some(template) = self.find_template(status) {
+                if guess_accept_mime(req, None).subtype() == mime::HTML {
+                    write_error_template(req, depot, res, template);
+                    return;
+                }
+            }
             write_error_default(req, res, self.footer.as_deref());
         }
     }
 }
 
+fn write_error_template(req: &Request, depot: &Depot, res: &mut Response, template: &ErrorTemplate) {
+    let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
+    let request_id = res
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(ToOwned::to_owned);
+    let fallback;
+    let err = if let ResBody::Error(err) = &res.body {
+        err
+    } else {
+        fallback = StatusError::from_code(status).unwrap();
+        &fallback
+    };
+    #[cfg(debug_assertions)]
+    let cause = err.cause.as_ref().map(|e| format!("{:#?}", e.as_ref()));
+    #[cfg(not(debug_assertions))]
+    let cause: Option<String> = None;
+    let ctx = ErrorContext {
+        code: status,
+        name: &err.name,
+        brief: &err.brief,
+        cause: cause.as_deref(),
+        request_id: request_id.as_deref(),
+    };
+    let content = template.render(&ctx, req, depot);
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    res.write_body(content).ok();
+}
+
+/// Write the default error page to response.
+///
+/// If the response has `x-request-id` header, such as set by `RequestId` middleware of `salvo-extra`,
+/// the request id is rendered in the error page.
 #[doc(hidden)]
 pub fn write_error_default(req: &Request, res: &mut Response, footer: Option<&str>) {
     let format = guess_accept_mime(req, None);
+    let request_id = res
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(ToOwned::to_owned);
     let (format, data) = if let ResBody::Error(body) = &res.body {
-        status_error_bytes(body, &format, footer)
+        status_error_bytes_with_request_id(body, &format, request_id.as_deref(), footer)
     } else {
         let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
-        status_error_bytes(&StatusError::from_code(status).unwrap(), &format, footer)
+        status_error_bytes_with_request_id(
+            &StatusError::from_code(status).unwrap(),
+            &format,
+            request_id.as_deref(),
+            footer,
+        )
     };
     res.headers_mut()
         .insert(header::CONTENT_TYPE, format.to_string().parse().unwrap());
@@ -354,4 +591,49 @@ mod tests {
 
         assert_eq!(access(&service, "notfound").await, "Custom 404 Error Page");
     }
+
+    #[tokio::test]
+    async fn test_error_templates() {
+        #[handler]
+        async fn fail(depot: &mut Depot) -> Result<(), StatusError> {
+            depot.insert("locale", "zh");
+            Err(StatusError::service_unavailable().brief("<down>"))
+        }
+        let goal = DefaultGoal::new()
+            .template(StatusCode::NOT_FOUND, "<p>{code} {name}</p>")
+            .class_template(
+                5,
+                ErrorTemplate::with_fn(|ctx, _req, depot| {
+                    format!(
+                        "{} {} {}",
+                        ctx.code.as_u16(),
+                        depot.get::<&str>("locale").copied().unwrap_or_default(),
+                        ctx.brief
+                    )
+                }),
+            );
+        let router = Router::new().push(Router::with_path("fail").get(fail));
+        let service = Service::new(router).catcher(Catcher::default().goal(goal));
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/notfound")
+            .add_header("accept", "text/html", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        assert_eq!(res.take_string().await.unwrap(), "<p>404 Not Found</p>");
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/fail")
+            .add_header("accept", "text/html", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(res.take_string().await.unwrap(), "503 zh <down>");
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/fail")
+            .add_header("accept", "application/json", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "application/json");
+        assert!(res.take_string().await.unwrap().contains(r#""code":503"#));
+    }
 }
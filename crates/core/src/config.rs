@@ -0,0 +1,833 @@
+//! Server configuration.
+//!
+//! [`ServerConfig`] covers settings which are usually different between deployments. It can be loaded by
+//! [`ConfigLoader`] from TOML, YAML or JSON files and environment variables, later sources override earlier ones.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::config::{ConfigLoader, ServerConfig};
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config: ServerConfig = ConfigLoader::new()
+//!         .file("server.toml")
+//!         .optional_file("server.local.toml")
+//!         .env("SERVER")
+//!         .load()
+//!         .unwrap();
+//!     config.limits.apply();
+//!     let server = Server::from_config(&config).await.unwrap();
+//!     server.serve(Router::new().get(hello)).await;
+//! }
+//! ```
+//!
+//! And `server.toml` may be like this:
+//!
+//! ```toml
+//! listen = ["0.0.0.0:80", "[::]:80"]
+//!
+//! [timeouts]
+//! idle = "60s"
+//! graceful_shutdown = "30s"
+//!
+//! [limits]
+//! max_body_size = 1048576
+//! ```
+use std::fmt::{self, Formatter};
+use std::io::Error as IoError;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, Unexpected, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+/// Server settings, see [module level documentation](self) for example.
+///
+/// All `Vec<String>` fields also accept a string of comma separated values, so they are easy to set by
+/// environment variables.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ServerConfig {
+    /// Addresses to listen on. Default is `127.0.0.1:5800`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub listen: Vec<String>,
+    /// TLS settings, it is used by all listen addresses. It requires `rustls` feature.
+    pub tls: Option<TlsConfig>,
+    /// Timeout settings.
+    pub timeouts: TimeoutsConfig,
+    /// Limit settings.
+    pub limits: LimitsConfig,
+    /// Default settings for compression middleware.
+    pub compression: CompressionConfig,
+    /// Default settings for CORS middleware.
+    pub cors: CorsConfig,
+}
+impl Default for ServerConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            listen: vec!["127.0.0.1:5800".into()],
+            tls: None,
+            timeouts: TimeoutsConfig::default(),
+            limits: LimitsConfig::default(),
+            compression: CompressionConfig::default(),
+            cors: CorsConfig::default(),
+        }
+    }
+}
+
+/// TLS settings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TlsConfig {
+    /// Path of certificate chain file in PEM format.
+    pub cert: PathBuf,
+    /// Path of private key file in PEM format.
+    pub key: PathBuf,
+}
+
+/// Timeout settings.
+///
+/// Durations are numbers of seconds, or strings with unit such as `"500ms"`, `"30s"`, `"5m"` and `"1h"`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TimeoutsConfig {
+    /// Connection idle timeout, see [`Server::idle_timeout`](crate::Server::idle_timeout).
+    #[serde(with = "option_duration")]
+    pub idle: Option<Duration>,
+    /// Request handling timeout, it can be used to create timeout middleware.
+    #[serde(with = "option_duration")]
+    pub request: Option<Duration>,
+    /// Graceful shutdown timeout, it can be passed to
+    /// [`Server::serve_with_graceful_shutdown`](crate::Server::serve_with_graceful_shutdown).
+    #[serde(with = "option_duration")]
+    pub graceful_shutdown: Option<Duration>,
+    /// Background tasks timeout, see [`Server::background_task_timeout`](crate::Server::background_task_timeout).
+    /// Default is 10 seconds.
+    #[serde(with = "duration")]
+    pub background_task: Duration,
+}
+impl Default for TimeoutsConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            idle: None,
+            request: None,
+            graceful_shutdown: None,
+            background_task: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Limit settings.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LimitsConfig {
+    /// Max size in bytes of request body, see [`set_secure_max_size`](crate::http::request::set_secure_max_size).
+    pub max_body_size: Option<usize>,
+}
+impl LimitsConfig {
+    /// Apply the limits.
+    ///
+    /// `max_body_size` is a process wide setting, so it is not applied by
+    /// [`Server::from_config`](crate::Server::from_config) and must be applied explicitly by this method.
+    #[inline]
+    pub fn apply(&self) {
+        if let Some(size) = self.max_body_size {
+            crate::http::request::set_secure_max_size(size);
+        }
+    }
+}
+
+/// Default settings for compression middleware.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct CompressionConfig {
+    /// Whether compression is enabled.
+    pub enabled: bool,
+    /// Algorithms in priority order such as `zstd`, `gzip`, `deflate` and `br`, all enabled algorithms are used if
+    /// it is empty.
+    #[serde(deserialize_with = "one_or_many")]
+    pub algorithms: Vec<String>,
+    /// Precise compression level, the default level of every algorithm is used if it is not set.
+    pub level: Option<u32>,
+    /// Minimum body size to compress.
+    pub min_length: usize,
+    /// Content types to compress, the middleware's default is used if it is empty.
+    #[serde(deserialize_with = "one_or_many")]
+    pub content_types: Vec<String>,
+}
+
+/// Default settings for CORS middleware.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct CorsConfig {
+    /// Whether CORS is enabled.
+    pub enabled: bool,
+    /// Allowed origins, `*` allows any origin.
+    #[serde(deserialize_with = "one_or_many")]
+    pub allow_origins: Vec<String>,
+    /// Allowed methods.
+    #[serde(deserialize_with = "one_or_many")]
+    pub allow_methods: Vec<String>,
+    /// Allowed request headers, `*` allows any header.
+    #[serde(deserialize_with = "one_or_many")]
+    pub allow_headers: Vec<String>,
+    /// Exposed response headers.
+    #[serde(deserialize_with = "one_or_many")]
+    pub expose_headers: Vec<String>,
+    /// Whether credentials are allowed.
+    pub allow_credentials: bool,
+    /// How long the preflight response can be cached.
+    #[serde(with = "option_duration")]
+    pub max_age: Option<Duration>,
+}
+
+#[derive(Debug)]
+enum Source {
+    File { path: PathBuf, required: bool },
+    Env(String),
+}
+
+/// Loads configuration from layered sources, values in later sources override values in earlier sources.
+///
+/// Nested tables are merged, other values such as arrays are replaced.
+#[derive(Debug, Default)]
+pub struct ConfigLoader {
+    sources: Vec<Source>,
+}
+impl ConfigLoader {
+    /// Create a new `ConfigLoader` without sources.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a config file, the format is decided by extension: `toml`, `yaml`, `yml` or `json`.
+    ///
+    /// Loading fails if the file does not exist.
+    #[inline]
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            required: true,
+        });
+        self
+    }
+
+    /// Add a config file which is skipped if it does not exist.
+    #[inline]
+    pub fn optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            required: false,
+        });
+        self
+    }
+
+    /// Add environment variables starting with `{prefix}_`.
+    ///
+    /// The rest of the name is lowercased, `__` separates nested keys. For example, `SERVER_TIMEOUTS__IDLE=30s`
+    /// sets `timeouts.idle` when prefix is `SERVER`. Variables which do not match a field of the config are ignored,
+    /// so other programs can use variables with the same prefix.
+    ///
+    /// Values are kept as strings unless the field is a number or a bool, which is parsed from the value, or a
+    /// sequence, map or struct, which is parsed from the value as JSON.
+    #[inline]
+    pub fn env(mut self, prefix: impl Into<String>) -> Self {
+        self.sources.push(Source::Env(prefix.into()));
+        self
+    }
+
+    /// Load all sources and merge them into config.
+    pub fn load<T>(&self) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut merged = Value::Object(Map::new());
+        let mut env = Value::Object(Map::new());
+        for source in &self.sources {
+            match source {
+                Source::File { path, required } => {
+                    if !required && !path.exists() {
+                        continue;
+                    }
+                    let value = read_file(path)?;
+                    prune(&mut env, &value);
+                    merge(&mut merged, value);
+                }
+                Source::Env(prefix) => {
+                    merge(&mut env, read_env(prefix, std::env::vars()));
+                }
+            }
+        }
+        let layered = Layered {
+            base: Some(merged),
+            env: Some(env),
+        };
+        T::deserialize(layered).map_err(|e| Error::other(format!("invalid config: {e}")))
+    }
+}
+
+fn read_file(path: &PathBuf) -> crate::Result<Value> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        Error::Io(IoError::new(
+            e.kind(),
+            format!("failed to read config file `{}`: {e}", path.display()),
+        ))
+    })?;
+    let parse_error =
+        |e: &dyn fmt::Display| Error::other(format!("failed to parse config file `{}`: {e}", path.display()));
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| parse_error(&e)),
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).map_err(|e| parse_error(&e)),
+        Some("json") => serde_json::from_str(&content).map_err(|e| parse_error(&e)),
+        _ => Err(Error::other(format!(
+            "unsupported config file format `{}`",
+            path.display()
+        ))),
+    }
+}
+
+fn read_env(prefix: &str, vars: impl Iterator<Item = (String, String)>) -> Value {
+    let prefix = format!("{prefix}_");
+    let mut root = Value::Object(Map::new());
+    for (key, value) in vars {
+        let Some(key) = key.strip_prefix(&prefix) else {
+            continue;
+        };
+        let value = Value::String(value);
+        let mut node = &mut root;
+        let mut keys = key.split("__").map(|k| k.to_lowercase()).peekable();
+        while let Some(key) = keys.next() {
+            let Value::Object(map) = node else {
+                break;
+            };
+            if keys.peek().is_none() {
+                map.insert(key, value);
+                break;
+            }
+            node = map.entry(key).or_insert_with(|| Value::Object(Map::new()));
+        }
+    }
+    root
+}
+
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Remove values of environment variables which are overridden by a later config file.
+fn prune(env: &mut Value, overlay: &Value) {
+    if let (Value::Object(env), Value::Object(overlay)) = (env, overlay) {
+        for (key, value) in overlay {
+            let overridden = match env.get_mut(key) {
+                Some(existing @ Value::Object(_)) if value.is_object() => {
+                    prune(existing, value);
+                    false
+                }
+                Some(_) => true,
+                None => false,
+            };
+            if overridden {
+                env.remove(key);
+            }
+        }
+    }
+}
+
+/// Values loaded from config files, overlaid by values of environment variables.
+///
+/// Values of environment variables are strings until the type of the field they are deserialized to is known, and
+/// keys of environment variables which are not fields of a struct are ignored.
+struct Layered {
+    base: Option<Value>,
+    env: Option<Value>,
+}
+impl Layered {
+    fn into_value(self) -> Value {
+        let mut base = self.base.unwrap_or(Value::Null);
+        if let Some(env) = self.env {
+            merge(&mut base, env);
+        }
+        base
+    }
+
+    fn into_entries(self, fields: Option<&[&str]>) -> Result<LayeredEntries, Self> {
+        let (base, mut env) = match (self.base, self.env) {
+            (None | Some(Value::Null), Some(Value::Object(env))) => (Map::new(), env),
+            (Some(Value::Object(base)), Some(Value::Object(env))) => (base, env),
+            (base, env) => return Err(Self { base, env }),
+        };
+        let mut entries = Vec::with_capacity(base.len() + env.len());
+        for (key, value) in base {
+            let env = env.remove(&key);
+            entries.push((key, Self { base: Some(value), env }));
+        }
+        for (key, env) in env {
+            if let Some(fields) = fields {
+                if !fields.contains(&key.as_str()) {
+                    continue;
+                }
+            }
+            entries.push((
+                key,
+                Self {
+                    base: None,
+                    env: Some(env),
+                },
+            ));
+        }
+        Ok(LayeredEntries {
+            entries: entries.into_iter(),
+            value: None,
+        })
+    }
+}
+
+struct LayeredEntries {
+    entries: std::vec::IntoIter<(String, Layered)>,
+    value: Option<Layered>,
+}
+impl<'de> MapAccess<'de> for LayeredEntries {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Value::String(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)+) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.env {
+                Some(Value::String(raw)) => match raw.trim().parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&raw), &visitor)),
+                },
+                env => Self { base: self.base, env }.into_value().$method(visitor),
+            }
+        }
+    )+};
+}
+macro_rules! deserialize_json {
+    ($($method:ident($($arg:ident: $ty:ty),*),)+) => {$(
+        fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+            match self.env {
+                Some(Value::String(raw)) => {
+                    serde_json::from_str(&raw).unwrap_or(Value::String(raw)).$method($($arg,)* visitor)
+                }
+                env => Self { base: self.base, env }.into_value().$method($($arg,)* visitor),
+            }
+        }
+    )+};
+}
+impl<'de> Deserializer<'de> for Layered {
+    type Error = serde_json::Error;
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    deserialize_json! {
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.env {
+            Some(Value::String(raw)) => visitor.visit_string(raw),
+            env => Self { base: self.base, env }.into_value().deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.into_value().deserialize_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.into_value().deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.env.is_some() {
+            visitor.visit_some(self)
+        } else {
+            self.into_value().deserialize_option(visitor)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.into_value().deserialize_unit(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        self.into_value().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.env.is_some() {
+            visitor.visit_newtype_struct(self)
+        } else {
+            self.into_value().deserialize_newtype_struct(name, visitor)
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.into_entries(None) {
+            Ok(entries) => visitor.visit_map(entries),
+            Err(Self {
+                env: Some(Value::String(raw)),
+                ..
+            }) => serde_json::from_str(&raw)
+                .unwrap_or(Value::String(raw))
+                .deserialize_map(visitor),
+            Err(layered) => layered.into_value().deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.into_entries(Some(fields)) {
+            Ok(entries) => visitor.visit_map(entries),
+            Err(Self {
+                env: Some(Value::String(raw)),
+                ..
+            }) => serde_json::from_str(&raw)
+                .unwrap_or(Value::String(raw))
+                .deserialize_struct(name, fields, visitor),
+            Err(layered) => layered.into_value().deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => value
+            .split(',')
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())
+            .collect(),
+        OneOrMany::Many(values) => values,
+    })
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("invalid duration `{value}`"))?;
+    let secs = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(format!("invalid duration unit in `{value}`")),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("invalid duration `{value}`"))
+}
+
+struct DurationValue(Duration);
+impl Serialize for DurationValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.subsec_nanos() == 0 {
+            serializer.serialize_str(&format!("{}s", self.0.as_secs()))
+        } else {
+            serializer.serialize_str(&format!("{}ms", self.0.as_millis()))
+        }
+    }
+}
+impl<'de> Deserialize<'de> for DurationValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DurationVisitor;
+        impl<'de> Visitor<'de> for DurationVisitor {
+            type Value = DurationValue;
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                formatter.write_str("seconds or a string like `30s`")
+            }
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(DurationValue(Duration::from_secs(value)))
+            }
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(|v| DurationValue(Duration::from_secs(v)))
+                    .map_err(|_| E::custom("duration must not be negative"))
+            }
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                Duration::try_from_secs_f64(value).map(DurationValue).map_err(E::custom)
+            }
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                parse_duration(value).map(DurationValue).map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+mod duration {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        DurationValue(*value).serialize(serializer)
+    }
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        DurationValue::deserialize(deserializer).map(|d| d.0)
+    }
+}
+
+mod option_duration {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.map(DurationValue).serialize(serializer)
+    }
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<DurationValue>::deserialize(deserializer).map(|d| d.map(|d| d.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("server.toml");
+        std::fs::write(
+            &toml_path,
+            r#"
+listen = ["0.0.0.0:80", "[::]:80"]
+[timeouts]
+idle = "60s"
+graceful_shutdown = 30
+[cors]
+enabled = true
+allow_origins = ["https://salvo.rs"]
+"#,
+        )
+        .unwrap();
+        let yaml_path = dir.path().join("server.yaml");
+        std::fs::write(
+            &yaml_path,
+            "timeouts:\n  request: 500ms\nlimits:\n  max_body_size: 1024\n",
+        )
+        .unwrap();
+
+        let config: ServerConfig = ConfigLoader::new()
+            .file(&toml_path)
+            .file(&yaml_path)
+            .optional_file(dir.path().join("missing.json"))
+            .load()
+            .unwrap();
+        assert_eq!(config.listen, vec!["0.0.0.0:80", "[::]:80"]);
+        assert_eq!(config.timeouts.idle, Some(Duration::from_secs(60)));
+        assert_eq!(config.timeouts.graceful_shutdown, Some(Duration::from_secs(30)));
+        assert_eq!(config.timeouts.request, Some(Duration::from_millis(500)));
+        assert_eq!(config.timeouts.background_task, Duration::from_secs(10));
+        assert_eq!(config.limits.max_body_size, Some(1024));
+        assert!(config.cors.enabled);
+        assert_eq!(config.cors.allow_origins, vec!["https://salvo.rs"]);
+        assert!(!config.compression.enabled);
+
+        assert!(ConfigLoader::new()
+            .file(dir.path().join("missing.toml"))
+            .load::<ServerConfig>()
+            .is_err());
+        std::fs::write(&toml_path, "listn = \"0.0.0.0:80\"").unwrap();
+        assert!(ConfigLoader::new().file(&toml_path).load::<ServerConfig>().is_err());
+    }
+
+    #[test]
+    fn test_env_strings() {
+        #[derive(Deserialize, Debug)]
+        #[serde(deny_unknown_fields)]
+        struct AppConfig {
+            name: String,
+            port: u16,
+            debug: bool,
+            tags: Vec<String>,
+            ratio: Option<f64>,
+        }
+        let vars = [
+            ("APP_NAME", "007"),
+            ("APP_PORT", "8080"),
+            ("APP_DEBUG", "true"),
+            ("APP_TAGS", r#"["a", "b"]"#),
+            ("APP_RATIO", "0.5"),
+        ];
+        let layered = Layered {
+            base: None,
+            env: Some(read_env(
+                "APP",
+                vars.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+            )),
+        };
+        let config = AppConfig::deserialize(layered).unwrap();
+        assert_eq!(config.name, "007");
+        assert_eq!(config.port, 8080);
+        assert!(config.debug);
+        assert_eq!(config.tags, vec!["a", "b"]);
+        assert_eq!(config.ratio, Some(0.5));
+
+        let layered = Layered {
+            base: None,
+            env: Some(read_env(
+                "APP",
+                [("APP_PORT".to_owned(), "http".to_owned())].into_iter(),
+            )),
+        };
+        assert!(AppConfig::deserialize(layered).is_err());
+    }
+
+    #[test]
+    fn test_env_override() {
+        let vars = [
+            ("APP_LISTEN", "0.0.0.0:8080, 0.0.0.0:8443"),
+            ("APP_TIMEOUTS__IDLE", "5s"),
+            ("APP_LIMITS__MAX_BODY_SIZE", "2048"),
+            ("APP_COMPRESSION__ENABLED", "true"),
+            ("APP_CORS__ALLOW_ORIGINS", "https://salvo.rs"),
+            ("APP_HOME", "/home/app"),
+            ("APP_TIMEOUTS__UNKNOWN", "1s"),
+            ("OTHER_LISTEN", "0.0.0.0:80"),
+        ];
+        let env = || read_env("APP", vars.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        let merged = serde_json::json!({"timeouts": {"request": "1s", "idle": "10s"}});
+        let layered = Layered {
+            base: Some(merged.clone()),
+            env: Some(env()),
+        };
+        let config = ServerConfig::deserialize(layered).unwrap();
+        assert_eq!(config.listen, vec!["0.0.0.0:8080", "0.0.0.0:8443"]);
+        assert_eq!(config.timeouts.idle, Some(Duration::from_secs(5)));
+        assert_eq!(config.timeouts.request, Some(Duration::from_secs(1)));
+        assert_eq!(config.limits.max_body_size, Some(2048));
+        assert!(config.compression.enabled);
+        assert_eq!(config.cors.allow_origins, vec!["https://salvo.rs"]);
+
+        // unknown keys of config files are still rejected.
+        let layered = Layered {
+            base: Some(serde_json::json!({"timeouts": {"unknown": "1s"}})),
+            env: Some(env()),
+        };
+        assert!(ServerConfig::deserialize(layered).is_err());
+
+        // later config files override environment variables.
+        let mut env = env();
+        prune(&mut env, &serde_json::json!({"timeouts": {"idle": "20s"}}));
+        let layered = Layered {
+            base: Some(merged),
+            env: Some(env),
+        };
+        let overridden = ServerConfig::deserialize(layered).unwrap();
+        assert_eq!(overridden.timeouts.idle, Some(Duration::from_secs(10)));
+        assert_eq!(overridden.limits.max_body_size, Some(2048));
+
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["timeouts"]["idle"], "5s");
+        assert_eq!(serde_json::from_value::<ServerConfig>(value).unwrap(), config);
+    }
+}
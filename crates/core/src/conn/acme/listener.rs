@@ -10,7 +10,7 @@ use tokio_rustls::rustls::PrivateKey;
 use tokio_rustls::server::TlsStream;
 use tokio_rustls::TlsAcceptor;
 
-use crate::conn::{Accepted, Acceptor, Holding, Listener};
+use crate::conn::{Accepted, Acceptor, Holding, Listener, TlsInfo};
 
 use crate::http::uri::Scheme;
 use crate::http::Version;
@@ -350,18 +350,21 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            ..
         } = self.inner.accept().await?;
         let conn = self
             .tls_acceptor
             .accept(conn)
             .await
             .map_err(|e| IoError::new(ErrorKind::Other, e.to_string()))?;
+        let tls_info = TlsInfo::from_rustls(conn.get_ref().1);
         Ok(Accepted {
             conn,
             local_addr,
             remote_addr,
             http_version,
             http_scheme,
+            tls_info: Some(tls_info),
         })
     }
 }
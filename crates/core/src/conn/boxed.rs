@@ -0,0 +1,187 @@
+//! Boxed acceptor, it is used to serve acceptors of different types with one server.
+use std::io::Result as IoResult;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::future::{select_all, BoxFuture};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::sync::CancellationToken;
+
+use crate::async_trait;
+use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder};
+use crate::http::HttpConnection;
+use crate::service::HyperHandler;
+
+trait DynConn: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+    fn serve_boxed(
+        self: Box<Self>,
+        handler: HyperHandler,
+        builder: Arc<HttpBuilder>,
+        server_shutdown_token: CancellationToken,
+        idle_connection_timeout: Option<Duration>,
+    ) -> BoxFuture<'static, IoResult<()>>;
+}
+impl<C> DynConn for C
+where
+    C: HttpConnection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    fn serve_boxed(
+        self: Box<Self>,
+        handler: HyperHandler,
+        builder: Arc<HttpBuilder>,
+        server_shutdown_token: CancellationToken,
+        idle_connection_timeout: Option<Duration>,
+    ) -> BoxFuture<'static, IoResult<()>> {
+        (*self).serve(handler, builder, server_shutdown_token, idle_connection_timeout)
+    }
+}
+
+/// Connection accepted by [`BoxedAcceptor`].
+pub struct BoxedConn(Box<dyn DynConn>);
+
+impl AsyncRead for BoxedConn {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut *self.0).poll_read(cx, buf)
+    }
+}
+impl AsyncWrite for BoxedConn {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
+        Pin::new(&mut *self.0).poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut *self.0).poll_flush(cx)
+    }
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut *self.0).poll_shutdown(cx)
+    }
+}
+
+#[async_trait]
+impl HttpConnection for BoxedConn {
+    async fn serve(
+        self,
+        handler: HyperHandler,
+        builder: Arc<HttpBuilder>,
+        server_shutdown_token: CancellationToken,
+        idle_connection_timeout: Option<Duration>,
+    ) -> IoResult<()> {
+        self.0
+            .serve_boxed(handler, builder, server_shutdown_token, idle_connection_timeout)
+            .await
+    }
+}
+
+trait DynAcceptor: Send + 'static {
+    fn accept_boxed(&mut self) -> BoxFuture<'_, IoResult<Accepted<BoxedConn>>>;
+}
+impl<A> DynAcceptor for A
+where
+    A: Acceptor + Send + 'static,
+{
+    fn accept_boxed(&mut self) -> BoxFuture<'_, IoResult<Accepted<BoxedConn>>> {
+        Box::pin(async move { Ok(self.accept().await?.map_conn(|conn| BoxedConn(Box::new(conn)))) })
+    }
+}
+
+/// `BoxedAcceptor` erases type of acceptors and accepts connections from all of them.
+///
+/// Unlike [`JoinedListener`](super::JoinedListener), acceptors can be added at runtime, so it is useful when
+/// listeners are decided by configuration.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::conn::BoxedAcceptor;
+/// use salvo_core::prelude::*;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut acceptor = BoxedAcceptor::new(TcpListener::new("127.0.0.1:5800").bind().await);
+/// for addr in ["127.0.0.1:5801", "127.0.0.1:5802"] {
+///     acceptor = acceptor.join(TcpListener::new(addr).bind().await);
+/// }
+/// # }
+/// ```
+pub struct BoxedAcceptor {
+    inner: Vec<Box<dyn DynAcceptor>>,
+    holdings: Vec<Holding>,
+}
+impl BoxedAcceptor {
+    /// Create a new `BoxedAcceptor`.
+    #[inline]
+    pub fn new<A>(acceptor: A) -> Self
+    where
+        A: Acceptor + Send + 'static,
+    {
+        let holdings = acceptor.holdings().to_vec();
+        Self {
+            inner: vec![Box::new(acceptor)],
+            holdings,
+        }
+    }
+    /// Add another acceptor.
+    #[inline]
+    pub fn join<A>(mut self, acceptor: A) -> Self
+    where
+        A: Acceptor + Send + 'static,
+    {
+        self.holdings.extend_from_slice(acceptor.holdings());
+        self.inner.push(Box::new(acceptor));
+        self
+    }
+}
+
+#[async_trait]
+impl Acceptor for BoxedAcceptor {
+    type Conn = BoxedConn;
+
+    #[inline]
+    fn holdings(&self) -> &[Holding] {
+        &self.holdings
+    }
+
+    #[inline]
+    async fn accept(&mut self) -> IoResult<Accepted<Self::Conn>> {
+        if self.inner.len() == 1 {
+            return self.inner[0].accept_boxed().await;
+        }
+        let (accepted, _, _) = select_all(self.inner.iter_mut().map(|acceptor| acceptor.accept_boxed())).await;
+        accepted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::conn::{Listener, TcpListener};
+
+    #[tokio::test]
+    async fn test_boxed_acceptor() {
+        let mut acceptor = BoxedAcceptor::new(TcpListener::new("127.0.0.1:0").bind().await)
+            .join(TcpListener::new("127.0.0.1:0").bind().await);
+        assert_eq!(acceptor.holdings().len(), 2);
+        let addrs = acceptor
+            .holdings()
+            .iter()
+            .map(|holding| holding.local_addr.clone().into_std().unwrap())
+            .collect::<Vec<_>>();
+
+        for addr in addrs {
+            tokio::spawn(async move {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                stream.write_i32(518).await.unwrap();
+            });
+            let mut conn = acceptor.accept().await.unwrap().conn;
+            assert_eq!(conn.read_i32().await.unwrap(), 518);
+        }
+    }
+}
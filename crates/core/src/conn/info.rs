@@ -0,0 +1,264 @@
+//! Information of the connection which a request is received from.
+//!
+//! TLS listeners record the negotiated [`TlsInfo`] of each connection after the handshake, it can be got with
+//! the addresses and the http version of the connection by [`Request::connection_info`].
+//!
+//! # Example
+//!
+//! Reject requests which are not sent over TLS 1.2 or later:
+//!
+//! ```
+//! use salvo_core::conn::TlsVersion;
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn require_tls12(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+//!     let info = req.connection_info();
+//!     if info.tls_version().map(|v| v < TlsVersion::Tls12).unwrap_or(true) {
+//!         tracing::warn!(remote_addr = %info.remote_addr, tls = ?info.tls, "tls 1.2 is required");
+//!         res.render(StatusError::forbidden().brief("TLS 1.2 or later is required."));
+//!         ctrl.skip_rest();
+//!     }
+//! }
+//!
+//! let router = Router::with_path("payments").hoop(require_tls12);
+//! ```
+use std::fmt::{self, Display, Formatter};
+
+use http::uri::Scheme;
+
+use crate::conn::SocketAddr;
+use crate::http::{Request, Version};
+
+/// Negotiated TLS protocol version.
+///
+/// Versions are ordered, so a minimum version can be checked by comparison.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[non_exhaustive]
+pub enum TlsVersion {
+    /// SSL 3.0.
+    Ssl3,
+    /// TLS 1.0.
+    Tls10,
+    /// TLS 1.1.
+    Tls11,
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3.
+    Tls13,
+}
+impl Display for TlsVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ssl3 => "SSLv3",
+            Self::Tls10 => "TLSv1.0",
+            Self::Tls11 => "TLSv1.1",
+            Self::Tls12 => "TLSv1.2",
+            Self::Tls13 => "TLSv1.3",
+        })
+    }
+}
+
+/// Information of a TLS connection negotiated by the handshake.
+///
+/// Fields are `None` if they are not negotiated, or are not exposed by the TLS library, such as the version and the
+/// cipher suite of `native-tls`.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct TlsInfo {
+    /// Protocol version.
+    pub version: Option<TlsVersion>,
+    /// Name of the cipher suite, such as `TLS13_AES_128_GCM_SHA256`, its format depends on the TLS library.
+    pub cipher_suite: Option<String>,
+    /// Protocol negotiated by ALPN, such as `h2` and `http/1.1`.
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// Server name sent by the client by SNI.
+    pub server_name: Option<String>,
+}
+impl TlsInfo {
+    /// Create a new empty `TlsInfo`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets protocol version.
+    #[inline]
+    pub fn version(mut self, version: impl Into<Option<TlsVersion>>) -> Self {
+        self.version = version.into();
+        self
+    }
+    /// Sets name of the cipher suite.
+    #[inline]
+    pub fn cipher_suite(mut self, cipher_suite: impl Into<Option<String>>) -> Self {
+        self.cipher_suite = cipher_suite.into();
+        self
+    }
+    /// Sets protocol negotiated by ALPN.
+    #[inline]
+    pub fn alpn_protocol(mut self, alpn_protocol: impl Into<Option<Vec<u8>>>) -> Self {
+        self.alpn_protocol = alpn_protocol.into();
+        self
+    }
+    /// Sets server name sent by SNI.
+    #[inline]
+    pub fn server_name(mut self, server_name: impl Into<Option<String>>) -> Self {
+        self.server_name = server_name.into();
+        self
+    }
+    /// Protocol negotiated by ALPN as a string, `None` if it is not negotiated or is not valid UTF-8.
+    #[inline]
+    pub fn alpn_protocol_str(&self) -> Option<&str> {
+        self.alpn_protocol.as_deref().and_then(|p| std::str::from_utf8(p).ok())
+    }
+}
+
+cfg_feature! {
+    #![any(feature = "rustls", feature = "acme")]
+    impl TlsInfo {
+        pub(crate) fn from_rustls(conn: &tokio_rustls::rustls::ServerConnection) -> Self {
+            use tokio_rustls::rustls::ProtocolVersion;
+            let version = conn.protocol_version().and_then(|version| match version {
+                ProtocolVersion::SSLv3 => Some(TlsVersion::Ssl3),
+                ProtocolVersion::TLSv1_0 => Some(TlsVersion::Tls10),
+                ProtocolVersion::TLSv1_1 => Some(TlsVersion::Tls11),
+                ProtocolVersion::TLSv1_2 => Some(TlsVersion::Tls12),
+                ProtocolVersion::TLSv1_3 => Some(TlsVersion::Tls13),
+                _ => None,
+            });
+            Self {
+                version,
+                cipher_suite: conn.negotiated_cipher_suite().map(|suite| format!("{:?}", suite.suite())),
+                alpn_protocol: conn.alpn_protocol().map(|p| p.to_vec()),
+                server_name: conn.server_name().map(|name| name.to_owned()),
+            }
+        }
+    }
+}
+cfg_feature! {
+    #![feature = "native-tls"]
+    impl TlsInfo {
+        pub(crate) fn from_native_tls<S>(stream: &tokio_native_tls::TlsStream<S>) -> Self
+        where
+            S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+        {
+            Self {
+                alpn_protocol: stream.get_ref().negotiated_alpn().ok().flatten(),
+                ..Default::default()
+            }
+        }
+    }
+}
+cfg_feature! {
+    #![feature = "openssl"]
+    impl TlsInfo {
+        pub(crate) fn from_openssl(ssl: &openssl::ssl::SslRef) -> Self {
+            let version = match ssl.version_str() {
+                "SSLv3" => Some(TlsVersion::Ssl3),
+                "TLSv1" => Some(TlsVersion::Tls10),
+                "TLSv1.1" => Some(TlsVersion::Tls11),
+                "TLSv1.2" => Some(TlsVersion::Tls12),
+                "TLSv1.3" => Some(TlsVersion::Tls13),
+                _ => None,
+            };
+            Self {
+                version,
+                cipher_suite: ssl.current_cipher().map(|cipher| cipher.name().to_owned()),
+                alpn_protocol: ssl.selected_alpn_protocol().map(|p| p.to_vec()),
+                server_name: ssl.servername(openssl::ssl::NameType::HOST_NAME).map(|name| name.to_owned()),
+            }
+        }
+    }
+}
+cfg_feature! {
+    #![feature = "quinn"]
+    impl TlsInfo {
+        pub(crate) fn from_quinn(conn: &quinn::Connection) -> Self {
+            let handshake = conn
+                .handshake_data()
+                .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok());
+            Self {
+                // QUIC always uses TLS 1.3.
+                version: Some(TlsVersion::Tls13),
+                cipher_suite: None,
+                alpn_protocol: handshake.as_ref().and_then(|data| data.protocol.clone()),
+                server_name: handshake.and_then(|data| data.server_name),
+            }
+        }
+    }
+}
+
+/// Information of the connection which a request is received from, it is got by [`Request::connection_info`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct ConnectionInfo<'a> {
+    /// Local address.
+    pub local_addr: &'a SocketAddr,
+    /// Remote address.
+    pub remote_addr: &'a SocketAddr,
+    /// Scheme of the listener.
+    pub scheme: &'a Scheme,
+    /// Http version of the request.
+    pub http_version: Version,
+    /// TLS information, `None` if the connection is not over TLS.
+    pub tls: Option<&'a TlsInfo>,
+}
+impl<'a> ConnectionInfo<'a> {
+    pub(crate) fn new(req: &'a Request) -> Self {
+        Self {
+            local_addr: req.local_addr(),
+            remote_addr: req.remote_addr(),
+            scheme: req.scheme(),
+            http_version: req.version(),
+            tls: req.tls_info(),
+        }
+    }
+    /// Returns `true` if the connection is over TLS.
+    #[inline]
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+    /// Negotiated TLS protocol version.
+    #[inline]
+    pub fn tls_version(&self) -> Option<TlsVersion> {
+        self.tls.and_then(|tls| tls.version)
+    }
+    /// Protocol negotiated by ALPN.
+    #[inline]
+    pub fn alpn_protocol(&self) -> Option<&'a [u8]> {
+        self.tls.and_then(|tls| tls.alpn_protocol.as_deref())
+    }
+    /// Server name sent by SNI.
+    #[inline]
+    pub fn server_name(&self) -> Option<&'a str> {
+        self.tls.and_then(|tls| tls.server_name.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_connection_info() {
+        let mut req = Request::new();
+        let info = req.connection_info();
+        assert!(!info.is_tls());
+        assert_eq!(info.tls_version(), None);
+
+        *req.tls_info_mut() = Some(Arc::new(
+            TlsInfo::new()
+                .version(TlsVersion::Tls13)
+                .alpn_protocol(b"h2".to_vec())
+                .server_name("salvo.rs".to_owned()),
+        ));
+        let info = req.connection_info();
+        assert!(info.is_tls());
+        assert!(info.tls_version().unwrap() >= TlsVersion::Tls12);
+        assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+        assert_eq!(info.server_name(), Some("salvo.rs"));
+        assert_eq!(info.tls.unwrap().alpn_protocol_str(), Some("h2"));
+        assert_eq!(TlsVersion::Tls12.to_string(), "TLSv1.2");
+    }
+}
@@ -55,12 +55,18 @@ cfg_feature! {
 pub mod addr;
 pub use addr::SocketAddr;
 
+pub mod info;
+pub use info::{ConnectionInfo, TlsInfo, TlsVersion};
+
 pub mod tcp;
 pub use tcp::TcpListener;
 
 mod joined;
 pub use joined::JoinedListener;
 
+pub mod boxed;
+pub use boxed::BoxedAcceptor;
+
 cfg_feature! {
     #![unix]
     pub use unix::UnixListener;
@@ -126,6 +132,8 @@ pub struct Accepted<C> {
     pub http_scheme: Scheme,
     /// Http version.
     pub http_version: Version,
+    /// TLS information negotiated by the handshake, `None` if the connection is not over TLS.
+    pub tls_info: Option<TlsInfo>,
 }
 
 impl<C> Accepted<C>
@@ -141,6 +149,7 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            tls_info,
         } = self;
         Accepted {
             conn: wrap_fn(conn),
@@ -148,6 +157,7 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            tls_info,
         }
     }
 }
@@ -14,7 +14,7 @@ use tokio_native_tls::TlsStream;
 use tokio_util::sync::CancellationToken;
 
 use crate::async_trait;
-use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder, IntoConfigStream, Listener};
+use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder, IntoConfigStream, Listener, TlsInfo};
 use crate::http::{HttpConnection, Version};
 use crate::service::HyperHandler;
 
@@ -181,17 +181,20 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            ..
         } = self.inner.accept().await?;
         let conn = tls_acceptor
             .accept(conn)
             .await
             .map_err(|e| IoError::new(ErrorKind::Other, e.to_string()))?;
+        let tls_info = TlsInfo::from_native_tls(&conn);
         Ok(Accepted {
             conn,
             local_addr,
             remote_addr,
             http_version,
             http_scheme,
+            tls_info: Some(tls_info),
         })
     }
 }
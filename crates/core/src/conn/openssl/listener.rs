@@ -18,7 +18,7 @@ use tokio_util::sync::CancellationToken;
 use super::SslAcceptorBuilder;
 
 use crate::async_trait;
-use crate::conn::{Accepted, Acceptor,HttpBuilder, Holding, IntoConfigStream, Listener};
+use crate::conn::{Accepted, Acceptor,HttpBuilder, Holding, IntoConfigStream, Listener, TlsInfo};
 use crate::http::{HttpConnection, Version};
 use crate::service::HyperHandler;
 
@@ -183,6 +183,7 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            ..
         } = self.inner.accept().await?;
         let ssl = Ssl::new(tls_acceptor.context()).map_err(|err| IoError::new(ErrorKind::Other, err.to_string()))?;
         let mut tls_stream =
@@ -192,12 +193,14 @@ where
             .accept()
             .await
             .map_err(|err| IoError::new(ErrorKind::Other, err.to_string()))?;
+        let tls_info = TlsInfo::from_openssl(tls_stream.ssl());
         Ok(Accepted {
             conn: tls_stream,
             local_addr,
             remote_addr,
             http_version,
             http_scheme,
+            tls_info: Some(tls_info),
         })
     }
 }
@@ -19,7 +19,7 @@ use crate::conn::{Holding, IntoConfigStream};
 use crate::http::Version;
 
 use super::H3Connection;
-use crate::conn::{Accepted, Acceptor, Listener};
+use crate::conn::{Accepted, Acceptor, Listener, TlsInfo};
 
 /// A wrapper of `Listener` with quinn.
 pub struct QuinnListener<S, C, T, E> {
@@ -145,6 +145,7 @@ where
             let remote_addr = new_conn.remote_address();
             match new_conn.await {
                 Ok(conn) => {
+                    let tls_info = TlsInfo::from_quinn(&conn);
                     let conn = http3_quinn::Connection::new(conn);
                     return Ok(Accepted {
                         conn: H3Connection(conn),
@@ -152,6 +153,7 @@ where
                         remote_addr: remote_addr.into(),
                         http_scheme: self.holdings[0].http_scheme.clone(),
                         http_version: Version::HTTP_3,
+                        tls_info: Some(tls_info),
                     });
                 }
                 Err(e) => return Err(IoError::new(ErrorKind::Other, e.to_string())),
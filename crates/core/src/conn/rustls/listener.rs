@@ -13,7 +13,7 @@ use tokio_rustls::server::TlsStream;
 
 use crate::async_trait;
 use crate::conn::Holding;
-use crate::conn::{Accepted, Acceptor, IntoConfigStream, Listener};
+use crate::conn::{Accepted, Acceptor, IntoConfigStream, Listener, TlsInfo};
 use crate::http::uri::Scheme;
 use crate::http::Version;
 
@@ -160,17 +160,20 @@ where
             remote_addr,
             http_version,
             http_scheme,
+            ..
         } = self.inner.accept().await?;
         let conn = tls_acceptor
             .accept(conn)
             .await
             .map_err(|e| IoError::new(ErrorKind::Other, e.to_string()))?;
+        let tls_info = TlsInfo::from_rustls(conn.get_ref().1);
         Ok(Accepted {
             conn,
             local_addr,
             remote_addr,
             http_version,
             http_scheme,
+            tls_info: Some(tls_info),
         })
     }
 }
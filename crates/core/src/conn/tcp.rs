@@ -162,6 +162,7 @@ impl Acceptor for TcpAcceptor {
             remote_addr: remote_addr.into(),
             http_version: Version::HTTP_11,
             http_scheme: Scheme::HTTP,
+            tls_info: None,
         })
     }
 }
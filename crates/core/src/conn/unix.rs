@@ -74,6 +74,7 @@ impl Acceptor for UnixAcceptor {
             remote_addr: remote_addr.into(),
             http_version: Version::HTTP_11,
             http_scheme: Scheme::HTTP,
+            tls_info: None,
         })
     }
 }
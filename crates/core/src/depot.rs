@@ -1,6 +1,8 @@
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// `Depot` is for store temp data of current request.
 ///
@@ -31,20 +33,122 @@ use std::fmt::{self, Formatter};
 #[derive(Default)]
 pub struct Depot {
     map: HashMap<String, Box<dyn Any + Send + Sync>>,
+    typed: HashMap<TypedKeyId, Box<dyn Any + Send + Sync>>,
 }
 
+/// Keys with same name and different value types do not conflict.
+type TypedKeyId = (TypeId, &'static str);
+
 #[inline]
 fn type_key<T: 'static>() -> String {
     format!("{:?}", TypeId::of::<T>())
 }
 
+/// Typed key used to store and get value in [`Depot`], the value type is checked at compile time.
+///
+/// Keys are usually defined by [`key!`](crate::key) as constants. Several values of the same type can be stored
+/// with different keys, and they can be got by [`State::from_typed`](crate::extract::State::from_typed) too.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::TypedKey;
+///
+/// const USER_ID: TypedKey<u64> = TypedKey::new("user_id");
+///
+/// let mut depot = Depot::new();
+/// depot.insert_typed(&USER_ID, 10);
+/// assert_eq!(depot.get_typed(&USER_ID), Some(&10));
+/// ```
+pub struct TypedKey<T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+impl<T> TypedKey<T> {
+    /// Create a new `TypedKey` with the given name.
+    #[inline]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+    /// Get the name of this key.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+impl<T: 'static> TypedKey<T> {
+    #[inline]
+    fn id(&self) -> TypedKeyId {
+        (TypeId::of::<T>(), self.name)
+    }
+}
+impl<T> Clone for TypedKey<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for TypedKey<T> {}
+impl<T> fmt::Debug for TypedKey<T> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedKey")
+            .field("name", &self.name)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Define [`TypedKey`] constants, the key name is prefixed with the module path so it is unique.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// struct User {
+///     name: String,
+/// }
+/// salvo_core::key! {
+///     /// Current login user.
+///     pub CURRENT_USER: User;
+///     REQUEST_COUNT: u32;
+/// }
+///
+/// #[handler]
+/// async fn auth(depot: &mut Depot) {
+///     depot.insert_typed(&CURRENT_USER, User { name: "chris".into() });
+/// }
+/// #[handler]
+/// async fn hello(depot: &mut Depot) -> String {
+///     format!("Hello {}", depot.get_typed(&CURRENT_USER).map(|u| &*u.name).unwrap_or("guest"))
+/// }
+/// ```
+#[macro_export]
+macro_rules! key {
+    ($($(#[$meta:meta])* $vis:vis $name:ident: $ty:ty;)+) => {
+        $(
+            $(#[$meta])*
+            $vis const $name: $crate::TypedKey<$ty> =
+                $crate::TypedKey::new(concat!(module_path!(), "::", stringify!($name)));
+        )+
+    };
+}
+
 impl Depot {
     /// Creates an empty `Depot`.
     ///
     /// The depot is initially created with a capacity of 0, so it will not allocate until it is first inserted into.
     #[inline]
     pub fn new() -> Depot {
-        Depot { map: HashMap::new() }
+        Depot {
+            map: HashMap::new(),
+            typed: HashMap::new(),
+        }
     }
 
     /// Get reference to depot inner map.
@@ -60,6 +164,7 @@ impl Depot {
     pub fn with_capacity(capacity: usize) -> Self {
         Depot {
             map: HashMap::with_capacity(capacity),
+            typed: HashMap::new(),
         }
     }
     /// Returns the number of elements the depot can hold without reallocating.
@@ -166,6 +271,52 @@ impl Depot {
         self.map.remove(key).is_some()
     }
 
+    /// Inserts a value into the depot with a typed key.
+    #[inline]
+    pub fn insert_typed<V: Any + Send + Sync>(&mut self, key: &TypedKey<V>, value: V) -> &mut Self {
+        self.typed.insert(key.id(), Box::new(value));
+        self
+    }
+
+    /// Check is there a value stored in depot with this typed key.
+    #[inline]
+    pub fn contains_typed<V: Any + Send + Sync>(&self, key: &TypedKey<V>) -> bool {
+        self.typed.contains_key(&key.id())
+    }
+
+    /// Immutably borrows value stored with the typed key from depot.
+    #[inline]
+    pub fn get_typed<V: Any + Send + Sync>(&self, key: &TypedKey<V>) -> Option<&V> {
+        self.typed.get(&key.id()).and_then(|value| value.downcast_ref::<V>())
+    }
+
+    /// Mutably borrows value stored with the typed key from depot.
+    #[inline]
+    pub fn get_typed_mut<V: Any + Send + Sync>(&mut self, key: &TypedKey<V>) -> Option<&mut V> {
+        self.typed
+            .get_mut(&key.id())
+            .and_then(|value| value.downcast_mut::<V>())
+    }
+
+    /// Remove value stored with the typed key from depot and returning it.
+    #[inline]
+    pub fn remove_typed<V: Any + Send + Sync>(&mut self, key: &TypedKey<V>) -> Option<V> {
+        self.typed
+            .remove(&key.id())
+            .and_then(|value| value.downcast::<V>().ok())
+            .map(|value| *value)
+    }
+
+    /// Create a builder to take a read-only [`DepotSnapshot`] of selected values.
+    #[inline]
+    pub fn snapshot(&self) -> DepotSnapshotBuilder<'_> {
+        DepotSnapshotBuilder {
+            depot: self,
+            map: HashMap::new(),
+            typed: HashMap::new(),
+        }
+    }
+
     /// Remove value from depot and returning the value if the type was previously in the depot.
     #[inline]
     pub fn scrape<T: Any + Send + Sync>(&mut self) -> Result<T, Option<Box<dyn Any + Send + Sync>>> {
@@ -173,10 +324,147 @@ impl Depot {
     }
 }
 
+/// Builder of [`DepotSnapshot`], created by [`Depot::snapshot`].
+///
+/// Only the selected values are cloned into the snapshot, values absent in depot are skipped.
+pub struct DepotSnapshotBuilder<'a> {
+    depot: &'a Depot,
+    map: HashMap<String, Box<dyn Any + Send + Sync>>,
+    typed: HashMap<TypedKeyId, Box<dyn Any + Send + Sync>>,
+}
+impl<'a> DepotSnapshotBuilder<'a> {
+    #[inline]
+    fn clone_value<V: Any + Send + Sync + Clone>(mut self, key: String) -> Self {
+        if let Ok(value) = self.depot.get::<V>(&key) {
+            let value = value.clone();
+            self.map.insert(key, Box::new(value));
+        }
+        self
+    }
+
+    /// Clone the value injected to the depot by type into the snapshot.
+    #[inline]
+    pub fn obtain<T: Any + Send + Sync + Clone>(self) -> Self {
+        self.clone_value::<T>(type_key::<T>())
+    }
+
+    /// Clone the value stored with the key into the snapshot.
+    #[inline]
+    pub fn get<V: Any + Send + Sync + Clone>(self, key: impl Into<String>) -> Self {
+        self.clone_value::<V>(key.into())
+    }
+
+    /// Clone the value stored with the typed key into the snapshot.
+    #[inline]
+    pub fn typed<V: Any + Send + Sync + Clone>(mut self, key: &TypedKey<V>) -> Self {
+        if let Some(value) = self.depot.get_typed(key) {
+            let value = value.clone();
+            self.typed.insert(key.id(), Box::new(value));
+        }
+        self
+    }
+
+    /// Build the snapshot.
+    #[inline]
+    pub fn build(self) -> DepotSnapshot {
+        DepotSnapshot {
+            map: Arc::new(self.map),
+            typed: Arc::new(self.typed),
+        }
+    }
+}
+
+/// Read-only snapshot of selected [`Depot`] values.
+///
+/// `DepotSnapshot` is cheap to clone and is `'static`, so it can be moved into tasks which keep running
+/// after the response is sent.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct Config {
+///     audit: bool,
+/// }
+///
+/// #[handler]
+/// async fn create(depot: &mut Depot) -> &'static str {
+///     let snapshot = depot.snapshot().obtain::<Config>().get::<String>("user").build();
+///     tokio::spawn(async move {
+///         if snapshot.obtain::<Config>().map(|c| c.audit).unwrap_or_default() {
+///             println!("created by {:?}", snapshot.get::<String>("user"));
+///         }
+///     });
+///     "created"
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct DepotSnapshot {
+    map: Arc<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    typed: Arc<HashMap<TypedKeyId, Box<dyn Any + Send + Sync>>>,
+}
+impl DepotSnapshot {
+    /// Returns the number of values in the snapshot.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len() + self.typed.len()
+    }
+
+    /// Returns `true` if the snapshot contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty() && self.typed.is_empty()
+    }
+
+    /// Check is there a value stored in snapshot with this key.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Obtain a reference to a value injected to the depot and cloned into the snapshot.
+    #[inline]
+    pub fn obtain<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.get(&type_key::<T>())
+    }
+
+    /// Immutably borrows value stored with the key.
+    #[inline]
+    pub fn get<V: Any + Send + Sync>(&self, key: &str) -> Option<&V> {
+        self.map.get(key).and_then(|value| value.downcast_ref::<V>())
+    }
+
+    /// Immutably borrows value stored with the typed key.
+    #[inline]
+    pub fn get_typed<V: Any + Send + Sync>(&self, key: &TypedKey<V>) -> Option<&V> {
+        self.typed.get(&key.id()).and_then(|value| value.downcast_ref::<V>())
+    }
+}
+impl fmt::Debug for DepotSnapshot {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepotSnapshot")
+            .field("keys", &self.map.keys())
+            .field(
+                "typed_keys",
+                &self.typed.keys().map(|(_, name)| *name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
 impl fmt::Debug for Depot {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Depot").field("keys", &self.map.keys()).finish()
+        f.debug_struct("Depot")
+            .field("keys", &self.map.keys())
+            .field(
+                "typed_keys",
+                &self.typed.keys().map(|(_, name)| *name).collect::<Vec<_>>(),
+            )
+            .finish()
     }
 }
 
@@ -199,6 +487,58 @@ mod test {
         assert_eq!(depot.get_mut::<String>("one").unwrap(), &mut "ONE".to_owned());
     }
 
+    #[test]
+    fn test_depot_typed_key() {
+        crate::key! {
+            NAME: String;
+            COUNT: u32;
+        }
+        const OTHER_NAME: TypedKey<u32> = TypedKey::new(NAME.name());
+
+        let mut depot = Depot::new();
+        depot.insert_typed(&NAME, "salvo".to_owned());
+        depot.insert_typed(&OTHER_NAME, 1);
+        assert!(depot.contains_typed(&NAME));
+        assert!(!depot.contains_typed(&COUNT));
+        assert_eq!(NAME.name(), "salvo_core::depot::test::NAME");
+        assert_eq!(depot.get_typed(&NAME).unwrap(), "salvo");
+        assert_eq!(depot.get_typed(&OTHER_NAME), Some(&1));
+
+        depot.get_typed_mut(&NAME).unwrap().push_str("-rs");
+        assert_eq!(depot.remove_typed(&NAME).as_deref(), Some("salvo-rs"));
+        assert!(depot.get_typed(&NAME).is_none());
+    }
+
+    #[test]
+    fn test_depot_snapshot() {
+        crate::key! {
+            COUNT: u32;
+        }
+        let mut depot = Depot::new();
+        depot.inject(1u8);
+        depot.insert("user", "chris".to_owned());
+        depot.insert_typed(&COUNT, 2);
+
+        let snapshot = depot
+            .snapshot()
+            .obtain::<u8>()
+            .get::<String>("user")
+            .get::<String>("missing")
+            .get::<u64>("user")
+            .typed(&COUNT)
+            .build();
+        depot.get_mut::<String>("user").unwrap().push_str(" lee");
+        let cloned = snapshot.clone();
+        let handle = std::thread::spawn(move || cloned.get::<String>("user").cloned());
+        assert_eq!(handle.join().unwrap().as_deref(), Some("chris"));
+
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot.obtain::<u8>(), Some(&1));
+        assert_eq!(snapshot.get_typed(&COUNT), Some(&2));
+        assert!(!snapshot.contains_key("missing"));
+        assert!(snapshot.get::<u64>("user").is_none());
+    }
+
     #[tokio::test]
     async fn test_middleware_use_depot() {
         #[handler]
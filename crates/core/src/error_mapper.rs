@@ -0,0 +1,378 @@
+//! [`ErrorMapper`] maps typed errors to responses in one place.
+//!
+//! Handlers return errors as [`Error`](crate::Error), or render them as the cause of a [`StatusError`], the
+//! [`ErrorMapper`] set on the [`Service`](crate::Service) finds the mapping registered for the type of the error
+//! and renders the response with it, so error types do not need to implement [`Writer`](crate::Writer).
+//!
+//! # Example
+//!
+//! ```
+//! use std::fmt::{self, Display, Formatter};
+//!
+//! use salvo_core::error_mapper::ErrorMapper;
+//! use salvo_core::prelude::*;
+//!
+//! #[derive(Debug)]
+//! enum UserError {
+//!     NotFound,
+//!     Banned,
+//! }
+//! impl Display for UserError {
+//!     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+//!         match self {
+//!             Self::NotFound => f.write_str("user not found"),
+//!             Self::Banned => f.write_str("user is banned"),
+//!         }
+//!     }
+//! }
+//! impl std::error::Error for UserError {}
+//! impl From<UserError> for salvo_core::Error {
+//!     fn from(e: UserError) -> Self {
+//!         salvo_core::Error::other(e)
+//!     }
+//! }
+//!
+//! #[handler]
+//! async fn user() -> salvo_core::Result<&'static str> {
+//!     Err(UserError::NotFound)?
+//! }
+//!
+//! let mapper = ErrorMapper::new().map(|e: &UserError| match e {
+//!     UserError::NotFound => StatusError::not_found().brief(e.to_string()),
+//!     UserError::Banned => StatusError::forbidden().brief(e.to_string()),
+//! });
+//! let service = Service::new(Router::new().get(user)).error_mapper(mapper);
+//! ```
+//!
+//! An error type which implements [`MappedError`] can be returned from handlers directly, such as
+//! `Result<Json<User>, UserError>`, without converting it to [`Error`](crate::Error) first.
+
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Formatter};
+use std::io::Error as IoError;
+
+use tracing::Level;
+
+use crate::http::{ResBody, Response, StatusError};
+use crate::{Error, Scribe};
+
+type MapFn = Box<dyn Fn(&(dyn StdError + 'static), &mut Response) -> bool + Send + Sync>;
+
+struct Mapping {
+    type_name: &'static str,
+    level: Option<Level>,
+    map: MapFn,
+}
+
+/// Marks an error type which is rendered by the [`ErrorMapper`] when it is returned from handlers.
+///
+/// The error is rendered as `500 Internal Server Error` with itself as the cause, the mapping registered for its type
+/// replaces the response if there is one.
+///
+/// ```
+/// # use std::fmt::{self, Display, Formatter};
+/// use salvo_core::error_mapper::{ErrorMapper, MappedError};
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::Json;
+///
+/// #[derive(Debug)]
+/// struct NotFound;
+/// # impl Display for NotFound {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         f.write_str("not found")
+/// #     }
+/// # }
+/// impl std::error::Error for NotFound {}
+/// impl MappedError for NotFound {}
+///
+/// #[handler]
+/// async fn user() -> Result<Json<&'static str>, NotFound> {
+///     Err(NotFound)
+/// }
+///
+/// let mapper = ErrorMapper::new().map(|_: &NotFound| StatusError::not_found());
+/// let service = Service::new(Router::new().get(user)).error_mapper(mapper);
+/// ```
+pub trait MappedError: StdError + Send + Sync + 'static {}
+impl<E> Scribe for E
+where
+    E: MappedError,
+{
+    #[inline]
+    fn render(self, res: &mut Response) {
+        res.render(StatusError::internal_server_error().cause(self));
+    }
+}
+
+/// Registry of renderers for typed errors.
+///
+/// Mappings are checked in the order they are registered, the first mapping matches the error, or one of its
+/// [`source`](StdError::source)s, is used. Errors without a matching mapping are rendered as usual.
+#[derive(Default)]
+pub struct ErrorMapper {
+    mappings: Vec<Mapping>,
+}
+impl Debug for ErrorMapper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorMapper")
+            .field(
+                "mappings",
+                &self
+                    .mappings
+                    .iter()
+                    .map(|mapping| mapping.type_name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+impl ErrorMapper {
+    /// Create a new `ErrorMapper` without mappings.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register mapping of error type `E` to [`StatusError`].
+    ///
+    /// The [`StatusError`] is rendered by the catcher, so the body is in the format accepted by the client.
+    /// Errors are logged with `ERROR` level if the status code is server error, client errors are not logged.
+    #[inline]
+    pub fn map<E, F>(self, f: F) -> Self
+    where
+        E: StdError + 'static,
+        F: Fn(&E) -> StatusError + Send + Sync + 'static,
+    {
+        self.push::<E>(None, move |e, res| {
+            let status_error = f(e);
+            if status_error.code.is_server_error() {
+                tracing::error!(error = ?e, "request failed");
+            }
+            res.render(status_error);
+        })
+    }
+
+    /// Register mapping of error type `E` which writes to [`Response`] directly, and logs the error with `level`.
+    ///
+    /// Use it when the response needs custom status code, headers or body format.
+    #[inline]
+    pub fn map_with<E, F>(self, level: impl Into<Option<Level>>, f: F) -> Self
+    where
+        E: StdError + 'static,
+        F: Fn(&E, &mut Response) + Send + Sync + 'static,
+    {
+        self.push::<E>(level.into(), f)
+    }
+
+    fn push<E>(mut self, level: Option<Level>, f: impl Fn(&E, &mut Response) + Send + Sync + 'static) -> Self
+    where
+        E: StdError + 'static,
+    {
+        self.mappings.push(Mapping {
+            type_name: std::any::type_name::<E>(),
+            level,
+            map: Box::new(move |e, res| match e.downcast_ref::<E>() {
+                Some(e) => {
+                    f(e, res);
+                    true
+                }
+                None => false,
+            }),
+        });
+        self
+    }
+
+    /// Returns `true` if no mapping is registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Try to render the error with registered mappings, returns `true` if a mapping matches it.
+    pub fn map_error(&self, error: &(dyn StdError + 'static), res: &mut Response) -> bool {
+        let mut current = Some(error);
+        while let Some(error) = current {
+            let error = unwrap_error(error);
+            for mapping in &self.mappings {
+                if (mapping.map)(error, res) {
+                    if let Some(level) = mapping.level {
+                        log_error(level, error);
+                    }
+                    return true;
+                }
+            }
+            current = error.source();
+        }
+        false
+    }
+
+    /// Render the error body of the response with registered mappings if its cause matches any of them.
+    pub(crate) fn map_response(&self, res: &mut Response) {
+        if !matches!(&res.body, ResBody::Error(StatusError { cause: Some(_), .. })) {
+            return;
+        }
+        let ResBody::Error(status_error) = res.take_body() else {
+            unreachable!()
+        };
+        let matched = match &status_error.cause {
+            Some(cause) => self.map_error(&**cause, res),
+            None => false,
+        };
+        if !matched {
+            res.body = ResBody::Error(status_error);
+        }
+    }
+}
+
+/// Get the inner error of wrappers like [`Error`] and [`IoError`], their `source` is not the inner error.
+fn unwrap_error<'a>(mut error: &'a (dyn StdError + 'static)) -> &'a (dyn StdError + 'static) {
+    loop {
+        let inner: &(dyn StdError + 'static) = if let Some(e) = error.downcast_ref::<Error>() {
+            match e {
+                Error::Other(e) => &**e,
+                Error::Io(e) => match e.get_ref() {
+                    Some(e) => e,
+                    None => return error,
+                },
+                #[cfg(feature = "anyhow")]
+                Error::Anyhow(e) => e.as_ref(),
+                #[cfg(feature = "eyre")]
+                Error::Eyre(e) => e.as_ref(),
+                _ => return error,
+            }
+        } else if let Some(e) = error.downcast_ref::<IoError>().and_then(|e| e.get_ref()) {
+            e
+        } else {
+            return error;
+        };
+        error = inner;
+    }
+}
+
+fn log_error(level: Level, error: &(dyn StdError + 'static)) {
+    match level {
+        Level::ERROR => tracing::error!(error = ?error, "request failed"),
+        Level::WARN => tracing::warn!(error = ?error, "request failed"),
+        Level::INFO => tracing::info!(error = ?error, "request failed"),
+        Level::DEBUG => tracing::debug!(error = ?error, "request failed"),
+        _ => tracing::trace!(error = ?error, "request failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display, Formatter};
+
+    use serde_json::json;
+
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+    use crate::writing::Json;
+
+    use super::*;
+
+    #[derive(Debug)]
+    enum UserError {
+        NotFound,
+        Locked,
+    }
+    impl Display for UserError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::NotFound => f.write_str("user not found"),
+                Self::Locked => f.write_str("user is locked"),
+            }
+        }
+    }
+    impl StdError for UserError {}
+    impl From<UserError> for Error {
+        fn from(e: UserError) -> Self {
+            Error::other(e)
+        }
+    }
+
+    #[derive(Debug)]
+    struct DbError;
+    impl Display for DbError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("database is down")
+        }
+    }
+    impl StdError for DbError {}
+
+    #[handler]
+    async fn not_found() -> crate::Result<&'static str> {
+        Err(UserError::NotFound)?
+    }
+    #[handler]
+    async fn locked() -> crate::Result<&'static str> {
+        Err(UserError::Locked)?
+    }
+    #[handler]
+    async fn db() -> Result<&'static str, StatusError> {
+        let e = std::io::Error::other(DbError);
+        Err(StatusError::internal_server_error().cause(e))
+    }
+    #[handler]
+    async fn unknown() -> crate::Result<&'static str> {
+        Err(Error::other("unknown"))
+    }
+
+    impl MappedError for DbError {}
+
+    #[handler]
+    async fn db_direct() -> Result<Json<&'static str>, DbError> {
+        Err(DbError)
+    }
+
+    #[tokio::test]
+    async fn test_mapped_error() {
+        let router = Router::with_path("db").get(db_direct);
+        let res = TestClient::get("http://127.0.0.1:5801/db").send(router).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+
+        let mapper = ErrorMapper::new().map(|_: &DbError| StatusError::service_unavailable());
+        let service = Service::new(Router::with_path("db").get(db_direct)).error_mapper(mapper);
+        let res = TestClient::get("http://127.0.0.1:5801/db").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[tokio::test]
+    async fn test_error_mapper() {
+        let mapper = ErrorMapper::new()
+            .map(|e: &UserError| match e {
+                UserError::NotFound => StatusError::not_found().brief(e.to_string()),
+                UserError::Locked => StatusError::locked().brief(e.to_string()),
+            })
+            .map_with(Level::WARN, |e: &DbError, res: &mut Response| {
+                res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+                res.render(Json(json!({"error": e.to_string()})));
+            });
+        let router = Router::new()
+            .push(Router::with_path("not_found").get(not_found))
+            .push(Router::with_path("locked").get(locked))
+            .push(Router::with_path("db").get(db))
+            .push(Router::with_path("unknown").get(unknown));
+        let service = Service::new(router).error_mapper(mapper);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/not_found")
+            .add_header("accept", "application/json", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        assert!(res.take_string().await.unwrap().contains("user not found"));
+
+        let res = TestClient::get("http://127.0.0.1:5801/locked").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::LOCKED));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/db").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(
+            res.take_json::<serde_json::Value>().await.unwrap(),
+            json!({"error": "database is down"})
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5801/unknown").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
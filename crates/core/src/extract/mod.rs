@@ -65,6 +65,8 @@
 /// Metadata types.
 pub mod metadata;
 pub use metadata::Metadata;
+mod state;
+pub use state::State;
 
 use async_trait::async_trait;
 use serde::Deserialize;
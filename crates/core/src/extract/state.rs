@@ -0,0 +1,125 @@
+//! Typed state which is injected to [`Depot`].
+use std::any::{type_name, Any};
+use std::ops::{Deref, DerefMut};
+
+use crate::http::StatusError;
+use crate::{Depot, TypedKey};
+
+/// State extractor which gets a value injected to [`Depot`], such as by `affix::inject` of `salvo-extra`.
+///
+/// `State<T>` can be used as a parameter of `#[handler]` functions, `500 Internal Server Error` is
+/// rendered if the value is not found in depot. Macros can not resolve imported names, so the parameter
+/// should be written with full path, such as `salvo::extract::State<T>`, or marked with `#[salvo(state)]`.
+///
+/// Several values of the same type can be stored with different [`TypedKey`]s, use [`State::from_typed`]
+/// to get them.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use salvo_core::prelude::*;
+/// use salvo_core::extract::State;
+///
+/// struct Config {
+///     name: String,
+/// }
+/// #[handler]
+/// async fn hello(#[salvo(state)] config: State<Arc<Config>>) -> String {
+///     format!("Hello {}", config.name)
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct State<T>(pub T);
+impl<T> State<T>
+where
+    T: Any + Send + Sync + Clone,
+{
+    /// Get the state from depot, used by `#[handler]` macro.
+    pub fn from_depot(depot: &Depot) -> Result<Self, StatusError> {
+        depot.obtain::<T>().map(|v| State(v.clone())).map_err(|_| {
+            StatusError::internal_server_error().brief(format!(
+                "State of type `{}` is not found in depot, it should be injected before the handler.",
+                type_name::<T>()
+            ))
+        })
+    }
+
+    /// Get the state stored with the typed key from depot.
+    pub fn from_typed(depot: &Depot, key: &TypedKey<T>) -> Result<Self, StatusError> {
+        depot.get_typed(key).map(|v| State(v.clone())).ok_or_else(|| {
+            StatusError::internal_server_error().brief(format!(
+                "State `{}` of type `{}` is not found in depot, it should be injected before the handler.",
+                key.name(),
+                type_name::<T>()
+            ))
+        })
+    }
+
+    /// Consumes self and returns the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T> DerefMut for State<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_state_extractor() {
+        const GREETING: TypedKey<&'static str> = TypedKey::new("greeting");
+
+        #[handler]
+        async fn inject(depot: &mut Depot) {
+            depot.inject(5u32);
+            depot.insert_typed(&GREETING, "hello");
+        }
+        #[handler]
+        async fn hello(#[salvo(state)] count: State<u32>, depot: &mut Depot) -> String {
+            format!("{} {}", *State::from_typed(depot, &GREETING).unwrap(), *count)
+        }
+        #[handler]
+        async fn missing(_value: salvo_core::extract::State<String>) -> &'static str {
+            "unreachable"
+        }
+
+        let router = Router::new()
+            .push(Router::with_path("hello").hoop(inject).get(hello))
+            .push(Router::with_path("missing").get(missing));
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/hello")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello 5");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/missing").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.take_string().await.unwrap().contains("alloc::string::String"));
+
+        let err = State::from_typed(&Depot::new(), &GREETING).unwrap_err();
+        assert!(err.brief.contains("`greeting`"));
+    }
+}
@@ -124,4 +124,69 @@ mod test {
             Some(&HeaderValue::from_static("attachment; filename=attach.file"))
         );
     }
+
+    #[tokio::test]
+    async fn test_named_file_byteranges() {
+        use crate::prelude::*;
+        use crate::test::{ResponseExt, TestClient};
+
+        #[handler]
+        async fn file(req: &mut Request, res: &mut Response) {
+            NamedFile::builder("Cargo.toml")
+                .content_type(Mime::from_str("text/plain").unwrap())
+                .send(req.headers(), res)
+                .await;
+        }
+        let content = std::fs::read_to_string("Cargo.toml").unwrap();
+        let service = Service::new(Router::new().get(file));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("range", "bytes=0-6,10-11", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        let content_type = res.content_type().unwrap();
+        assert_eq!(content_type.essence_str(), "multipart/byteranges");
+        let boundary = content_type.get_param("boundary").unwrap().to_string();
+        let content_length: usize = res.headers()["content-length"].to_str().unwrap().parse().unwrap();
+        let body = res.take_string().await.unwrap();
+        assert_eq!(body.len(), content_length);
+        assert_eq!(
+            body,
+            format!(
+                "\r\n--{boundary}\r\ncontent-type: text/plain\r\ncontent-range: bytes 0-6/{len}\r\n\r\n{}\
+                \r\n--{boundary}\r\ncontent-type: text/plain\r\ncontent-range: bytes 10-11/{len}\r\n\r\n{}\
+                \r\n--{boundary}--\r\n",
+                &content[0..7],
+                &content[10..12],
+                len = content.len()
+            )
+        );
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("range", "bytes=0-6", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.take_string().await.unwrap(), &content[0..7]);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("range", "bytes=4-9,0-6,10-11", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.headers()["content-range"], format!("bytes 0-11/{}", content.len()));
+        assert_eq!(res.take_string().await.unwrap(), &content[0..12]);
+
+        let ranges = (0..17)
+            .map(|i| format!("{}-{}", i * 3, i * 3))
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("range", format!("bytes={ranges}"), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), content);
+    }
 }
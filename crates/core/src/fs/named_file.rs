@@ -8,16 +8,22 @@ use std::time::{SystemTime, UNIX_EPOCH};
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
+use bytes::Bytes;
 use enumflags2::{bitflags, BitFlags};
+use futures_util::{future, stream, StreamExt};
 use headers::*;
+use textnonce::TextNonce;
 use tokio::fs::File;
 
 use super::{ChunkedFile, ChunkedState};
-use crate::http::header::{CONTENT_DISPOSITION, CONTENT_ENCODING, IF_NONE_MATCH, RANGE};
+use crate::http::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_TYPE, IF_NONE_MATCH, RANGE};
 use crate::http::{HttpRange, Mime, Request, Response, StatusCode, StatusError};
 use crate::{async_trait, Depot, Error, Result, Writer};
 
 const CHUNK_SIZE: u64 = 1024 * 1024;
+/// Requests with more ranges, after overlapping and adjacent ranges are coalesced, get the whole file with `200 OK`,
+/// so tiny ranges can not be abused to amplify responses.
+const MAX_RANGES: usize = 16;
 
 #[bitflags(default = Etag | LastModified | ContentDisposition)]
 #[repr(u8)]
@@ -465,14 +471,21 @@ impl NamedFile {
             res.headers_mut().insert(CONTENT_ENCODING, content_encoding.clone());
         }
         let mut offset = 0;
+        let mut ranges = Vec::new();
 
         // check for range header
         let range = req_headers.get(RANGE);
         if let Some(range) = range {
             if let Ok(range) = range.to_str() {
                 if let Ok(range) = HttpRange::parse(range, length) {
-                    length = range[0].length;
-                    offset = range[0].start;
+                    let range = coalesce_ranges(range);
+                    if range.len() <= MAX_RANGES {
+                        if let Some(first) = range.first() {
+                            length = first.length;
+                            offset = first.start;
+                        }
+                        ranges = range;
+                    }
                 } else {
                     res.headers_mut().typed_insert(ContentRange::unsatisfied_bytes(length));
                     res.status_code(StatusCode::RANGE_NOT_SATISFIABLE);
@@ -492,9 +505,11 @@ impl NamedFile {
             return;
         }
 
-        if offset != 0 || length != self.metadata.len() || range.is_some() {
+        if ranges.len() > 1 {
+            self.send_byteranges(&ranges, res).await;
+        } else if !ranges.is_empty() {
             res.status_code(StatusCode::PARTIAL_CONTENT);
-            match ContentRange::bytes(offset..offset + length - 1, self.metadata.len()) {
+            match ContentRange::bytes(offset..offset + length, self.metadata.len()) {
                 Ok(content_range) => {
                     res.headers_mut().typed_insert(content_range);
                 }
@@ -526,6 +541,60 @@ impl NamedFile {
     }
 }
 
+impl NamedFile {
+    /// Sends multiple ranges as a `multipart/byteranges` body.
+    async fn send_byteranges(self, ranges: &[HttpRange], res: &mut Response) {
+        let file = self.file.into_std().await;
+        let boundary = TextNonce::sized_urlsafe(32).unwrap().into_string();
+        let total_size = self.metadata.len();
+        let mut content_length = 0;
+        let mut parts = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let file = match file.try_clone() {
+                Ok(file) => file,
+                Err(e) => {
+                    tracing::error!(error = ?e, "clone file for byteranges failed");
+                    res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                    return;
+                }
+            };
+            let header = format!(
+                "\r\n--{boundary}\r\ncontent-type: {}\r\ncontent-range: bytes {}-{}/{total_size}\r\n\r\n",
+                self.content_type,
+                range.start,
+                range.start + range.length - 1
+            );
+            content_length += header.len() as u64 + range.length;
+            let reader = ChunkedFile {
+                offset: range.start,
+                total_size: range.length,
+                read_size: 0,
+                state: ChunkedState::File(Some(file)),
+                buffer_size: self.buffer_size,
+            };
+            parts.push(stream::once(future::ready(Ok(Bytes::from(header)))).chain(reader));
+        }
+        let end = format!("\r\n--{boundary}--\r\n");
+        content_length += end.len() as u64;
+
+        res.status_code(StatusCode::PARTIAL_CONTENT);
+        match HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}")) {
+            Ok(content_type) => {
+                res.headers_mut().insert(CONTENT_TYPE, content_type);
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "set byteranges content type failed");
+            }
+        }
+        res.headers_mut().typed_insert(ContentLength(content_length));
+        res.stream(
+            stream::iter(parts)
+                .flatten()
+                .chain(stream::once(future::ready(Ok(Bytes::from(end))))),
+        );
+    }
+}
+
 #[async_trait]
 impl Writer for NamedFile {
     async fn write(mut self, req: &mut Request, _depot: &mut Depot, res: &mut Response) {
@@ -547,6 +616,22 @@ impl DerefMut for NamedFile {
     }
 }
 
+/// Sorts ranges and merges overlapping and adjacent ones.
+fn coalesce_ranges(mut ranges: Vec<HttpRange>) -> Vec<HttpRange> {
+    ranges.sort_by_key(|range| range.start);
+    let mut coalesced: Vec<HttpRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match coalesced.last_mut() {
+            Some(last) if range.start <= last.start + last.length => {
+                let end = cmp::max(last.start + last.length, range.start + range.length);
+                last.length = end - last.start;
+            }
+            _ => coalesced.push(range),
+        }
+    }
+    coalesced
+}
+
 /// Returns true if `req_headers` has no `If-Match` header or one which matches `etag`.
 fn any_match(etag: Option<&ETag>, req_headers: &HeaderMap) -> bool {
     match req_headers.typed_get::<IfMatch>() {
@@ -34,7 +34,10 @@
 //! }
 //! ```
 
-use crate::http::StatusCode;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use crate::http::{Method, StatusCode};
 use crate::{async_trait, Depot, FlowCtrl, Request, Response};
 
 /// Handler
@@ -69,10 +72,12 @@ pub fn empty() -> EmptyHandler {
     EmptyHandler
 }
 
-#[doc(hidden)]
+/// Handler which only calls the inner handler when the filter returns `true`, it is created by [`when`].
 #[non_exhaustive]
 pub struct WhenHoop<H, F> {
+    /// The wrapped handler.
     pub inner: H,
+    /// The filter which decides whether the inner handler is called.
     pub filter: F,
 }
 #[async_trait]
@@ -88,6 +93,113 @@ where
     }
 }
 
+/// Wrap a handler so it is only called when `predicate` returns `true`, otherwise the request goes straight to the
+/// next handler.
+///
+/// It makes any hoop conditional, such as on a header, a path, or a flag set in [`Depot`] by an earlier hoop,
+/// without forking the router tree only to vary middlewares.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn trace() {}
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "hello"
+/// }
+///
+/// let router = Router::new()
+///     .hoop(handler::when(|req: &Request, _: &Depot| req.headers().contains_key("x-trace"), trace))
+///     .hoop(handler::when(|_: &Request, depot: &Depot| depot.get::<bool>("beta").copied().unwrap_or(false), trace))
+///     .get(hello);
+/// ```
+#[inline]
+pub fn when<F, H>(predicate: F, hoop: H) -> WhenHoop<H, F>
+where
+    F: Fn(&Request, &Depot) -> bool + Send + Sync + 'static,
+    H: Handler,
+{
+    WhenHoop {
+        inner: hoop,
+        filter: predicate,
+    }
+}
+
+/// Handler wrapped by hoops, the hoops are called in order before the inner handler, as if they are added to a
+/// router by [`Router::hoop`](crate::Router::hoop) and the inner handler is its goal.
+///
+/// It is created by `#[handler(wrap = [...])]`, or by [`hooped`] to keep the middlewares of a handler next to it.
+pub struct HoopedHandler {
+    hoops: Vec<Arc<dyn Handler>>,
+    inner: Arc<dyn Handler>,
+}
+impl Debug for HoopedHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HoopedHandler")
+            .field("hoops", &self.hoops.iter().map(|h| h.type_name()).collect::<Vec<_>>())
+            .field("inner", &self.inner.type_name())
+            .finish()
+    }
+}
+impl HoopedHandler {
+    /// Create a new `HoopedHandler` without hoops.
+    #[inline]
+    pub fn new(inner: impl Handler) -> Self {
+        Self {
+            hoops: vec![],
+            inner: Arc::new(inner),
+        }
+    }
+    /// Add a hoop, it is called after the hoops added before.
+    #[inline]
+    pub fn hoop(mut self, hoop: impl Handler) -> Self {
+        self.hoops.push(Arc::new(hoop));
+        self
+    }
+}
+#[async_trait]
+impl Handler for HoopedHandler {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let mut handlers = Vec::with_capacity(self.hoops.len() + 1);
+        handlers.extend(self.hoops.iter().cloned());
+        handlers.push(self.inner.clone());
+        let mut inner_ctrl = FlowCtrl::new(handlers);
+        inner_ctrl.call_next(req, depot, res).await;
+        if inner_ctrl.is_ceased() {
+            ctrl.cease();
+        }
+    }
+}
+
+/// Wrap `handler` by hoops which are added by [`HoopedHandler::hoop`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn auth(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+///     if req.header::<String>("authorization").is_none() {
+///         res.render(StatusError::unauthorized());
+///         ctrl.skip_rest();
+///     }
+/// }
+/// #[handler]
+/// async fn profile() -> &'static str {
+///     "profile"
+/// }
+///
+/// let router = Router::with_path("profile").get(handler::hooped(profile).hoop(auth));
+/// ```
+#[inline]
+pub fn hooped(handler: impl Handler) -> HoopedHandler {
+    HoopedHandler::new(handler)
+}
+
 /// `Skipper` is used in many middlewares.
 pub trait Skipper: Send + Sync + 'static {
     /// Check if the request should be skipped.
@@ -109,6 +221,110 @@ pub fn none_skipper(_req: &mut Request, _depot: &Depot) -> bool {
     false
 }
 
+type SkipPredicate = Box<dyn Fn(&Request, &Depot) -> bool + Send + Sync + 'static>;
+
+/// `Skipper` built from paths, methods and predicates, the request is skipped if any of them matches.
+///
+/// It is accepted by the `skipper` option of all first-party middlewares, so the same rules can be used to
+/// exempt endpoints like health checks from them.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::handler::RequestSkipper;
+/// use salvo_core::http::Method;
+///
+/// let skipper = RequestSkipper::new()
+///     .path("/healthz")
+///     .path_prefix("/metrics")
+///     .method(Method::OPTIONS)
+///     .when(|req, _depot| req.headers().contains_key("x-internal"));
+/// ```
+#[derive(Default)]
+pub struct RequestSkipper {
+    paths: Vec<String>,
+    path_prefixes: Vec<String>,
+    methods: Vec<Method>,
+    predicates: Vec<SkipPredicate>,
+}
+impl RequestSkipper {
+    /// Create a new `RequestSkipper` which skips nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Skip requests whose path is exactly `path`.
+    #[inline]
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+    /// Skip requests whose path is exactly one of `paths`.
+    #[inline]
+    pub fn paths<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+    /// Skip requests whose path is `prefix` or under it, `/metrics` matches `/metrics` and `/metrics/cpu`,
+    /// but not `/metricsx`.
+    #[inline]
+    pub fn path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        while prefix.len() > 1 && prefix.ends_with('/') {
+            prefix.pop();
+        }
+        self.path_prefixes.push(prefix);
+        self
+    }
+    /// Skip requests with `method`.
+    #[inline]
+    pub fn method(mut self, method: Method) -> Self {
+        self.methods.push(method);
+        self
+    }
+    /// Skip requests for which `predicate` returns `true`.
+    #[inline]
+    pub fn when<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Request, &Depot) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+}
+impl Skipper for RequestSkipper {
+    fn skipped(&self, req: &mut Request, depot: &Depot) -> bool {
+        let path = req.uri().path();
+        self.paths.iter().any(|p| p == path)
+            || self.path_prefixes.iter().any(|prefix| is_under_prefix(path, prefix))
+            || self.methods.contains(req.method())
+            || self.predicates.iter().any(|predicate| predicate(req, depot))
+    }
+}
+fn is_under_prefix(path: &str, prefix: &str) -> bool {
+    if prefix == "/" {
+        return true;
+    }
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+impl Debug for RequestSkipper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSkipper")
+            .field("paths", &self.paths)
+            .field("path_prefixes", &self.path_prefixes)
+            .field("methods", &self.methods)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
 macro_rules! handler_tuple_impls {
     ($(
         $Tuple:tt {
@@ -209,3 +425,123 @@ macro_rules! __for_each_tuple {
 
 __for_each_tuple!(handler_tuple_impls);
 __for_each_tuple!(skipper_tuple_impls);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestClient;
+
+    #[test]
+    fn test_request_skipper() {
+        let skipper = RequestSkipper::new()
+            .path("/healthz")
+            .path_prefix("/metrics/")
+            .method(Method::OPTIONS)
+            .when(|req, _depot| req.query::<String>("skip").is_some());
+        let depot = Depot::new();
+        let skipped = |method: Method, url: &str| {
+            let mut req = TestClient::get(url).build();
+            *req.method_mut() = method;
+            skipper.skipped(&mut req, &depot)
+        };
+        assert!(skipped(Method::GET, "http://127.0.0.1:5801/healthz"));
+        assert!(!skipped(Method::GET, "http://127.0.0.1:5801/healthz/db"));
+        assert!(skipped(Method::GET, "http://127.0.0.1:5801/metrics"));
+        assert!(skipped(Method::GET, "http://127.0.0.1:5801/metrics/cpu"));
+        assert!(!skipped(Method::GET, "http://127.0.0.1:5801/metricsx"));
+        assert!(skipped(Method::OPTIONS, "http://127.0.0.1:5801/users"));
+        assert!(skipped(Method::GET, "http://127.0.0.1:5801/users?skip=1"));
+        assert!(!skipped(Method::POST, "http://127.0.0.1:5801/users"));
+    }
+
+    #[tokio::test]
+    async fn test_when() {
+        use crate::prelude::*;
+        use crate::test::ResponseExt;
+
+        #[handler]
+        async fn mark(res: &mut Response) {
+            res.headers_mut().insert("x-marked", "1".parse().unwrap());
+        }
+        #[handler]
+        async fn beta(depot: &mut Depot) {
+            depot.insert("beta", true);
+        }
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let router = Router::new()
+            .hoop(when(
+                |req: &Request, _: &Depot| req.query::<String>("beta").is_some(),
+                beta,
+            ))
+            .hoop(when(
+                |req: &Request, _: &Depot| req.headers().contains_key("x-mark"),
+                mark,
+            ))
+            .hoop(when(
+                |_: &Request, depot: &Depot| depot.get::<bool>("beta").copied().unwrap_or(false),
+                mark,
+            ))
+            .get(hello);
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert!(res.headers().get("x-marked").is_none());
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-mark", "1", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()["x-marked"], "1");
+        let res = TestClient::get("http://127.0.0.1:5801/?beta=1").send(&service).await;
+        assert_eq!(res.headers()["x-marked"], "1");
+    }
+
+    #[tokio::test]
+    async fn test_hooped() {
+        use crate::prelude::*;
+        use crate::test::ResponseExt;
+
+        #[handler]
+        async fn mark(res: &mut Response) {
+            res.headers_mut().append("x-marked", "1".parse().unwrap());
+        }
+        #[handler]
+        async fn deny(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+            if req.query::<String>("deny").is_some() {
+                res.render(StatusError::forbidden());
+                ctrl.skip_rest();
+            }
+        }
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler(wrap = [mark, deny])]
+        async fn wrapped() -> &'static str {
+            "wrapped"
+        }
+        let router = Router::new()
+            .hoop(mark)
+            .push(Router::with_path("hooped").get(hooped(hello).hoop(deny)))
+            .push(Router::with_path("wrapped").get(wrapped));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hooped").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        let res = TestClient::get("http://127.0.0.1:5801/hooped?deny=1")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/wrapped").send(&service).await;
+        assert_eq!(res.headers().get_all("x-marked").iter().count(), 2);
+        assert_eq!(res.take_string().await.unwrap(), "wrapped");
+        let res = TestClient::get("http://127.0.0.1:5801/wrapped?deny=1")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+    }
+}
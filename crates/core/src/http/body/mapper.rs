@@ -0,0 +1,189 @@
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use hyper::body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+
+use crate::BoxedError;
+
+/// Transforms body data chunk by chunk while it is streamed, so the whole body is never buffered.
+///
+/// Closures `FnMut(Bytes) -> Bytes` implement this trait. Implement it for a type when it needs to keep
+/// state between chunks, for example a pattern may be split by chunk boundaries.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::body::BodyMapper;
+/// use salvo_core::hyper::body::Bytes;
+/// use salvo_core::BoxedError;
+///
+/// const SCRIPT: &[u8] = b"<script src=\"/live.js\"></script>";
+///
+/// /// Inject a script before `</body>`, the tag may be split into two chunks.
+/// #[derive(Default)]
+/// struct InjectScript {
+///     pending: Vec<u8>,
+///     injected: bool,
+/// }
+/// impl BodyMapper for InjectScript {
+///     fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+///         if self.injected {
+///             return Ok(chunk);
+///         }
+///         self.pending.extend_from_slice(&chunk);
+///         if let Some(pos) = self.pending.windows(7).position(|w| w == b"</body>") {
+///             self.injected = true;
+///             let mut data = std::mem::take(&mut self.pending);
+///             data.splice(pos..pos, SCRIPT.iter().copied());
+///             return Ok(data.into());
+///         }
+///         // Keep bytes that may be the beginning of `</body>`.
+///         let keep = self.pending.len().min(6);
+///         Ok(self.pending.drain(..self.pending.len() - keep).collect::<Vec<_>>().into())
+///     }
+///     fn finish(&mut self) -> Result<Bytes, BoxedError> {
+///         Ok(std::mem::take(&mut self.pending).into())
+///     }
+/// }
+/// ```
+pub trait BodyMapper: Send + Sync + 'static {
+    /// Maps a data chunk, returned empty bytes is skipped.
+    fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError>;
+
+    /// Called when the inner body ends, returned bytes are sent as the last data chunk.
+    #[inline]
+    fn finish(&mut self) -> Result<Bytes, BoxedError> {
+        Ok(Bytes::new())
+    }
+}
+impl<F> BodyMapper for F
+where
+    F: FnMut(Bytes) -> Bytes + Send + Sync + 'static,
+{
+    #[inline]
+    fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+        Ok(self(chunk))
+    }
+}
+
+/// Body whose data chunks are transformed by a [`BodyMapper`].
+#[pin_project]
+pub struct MappedBody<B, M> {
+    #[pin]
+    inner: B,
+    mapper: M,
+    trailers: Option<Frame<Bytes>>,
+    finished: bool,
+}
+impl<B, M> MappedBody<B, M> {
+    /// Create a new `MappedBody`.
+    #[inline]
+    pub fn new(inner: B, mapper: M) -> Self {
+        Self {
+            inner,
+            mapper,
+            trailers: None,
+            finished: false,
+        }
+    }
+}
+
+impl<B, M> Body for MappedBody<B, M>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxedError>,
+    M: BodyMapper,
+{
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if *this.finished {
+            return Poll::Ready(this.trailers.take().map(Ok));
+        }
+        loop {
+            match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => {
+                        let data = this.mapper.map(data)?;
+                        if !data.is_empty() {
+                            return Poll::Ready(Some(Ok(Frame::data(data))));
+                        }
+                    }
+                    Err(frame) => {
+                        // Data after mapping must be sent before trailers.
+                        *this.finished = true;
+                        let data = this.mapper.finish()?;
+                        if data.is_empty() {
+                            return Poll::Ready(Some(Ok(frame)));
+                        }
+                        *this.trailers = Some(frame);
+                        return Poll::Ready(Some(Ok(Frame::data(data))));
+                    }
+                },
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => {
+                    *this.finished = true;
+                    let data = this.mapper.finish()?;
+                    return Poll::Ready((!data.is_empty()).then(|| Ok(Frame::data(data))));
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.finished && self.trailers.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::stream::{self, StreamExt};
+
+    use crate::http::body::{BodyMapper, ResBody};
+    use crate::BoxedError;
+
+    struct Redact {
+        tail: Vec<u8>,
+    }
+    impl BodyMapper for Redact {
+        fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+            self.tail.extend_from_slice(&chunk);
+            let text = String::from_utf8(std::mem::take(&mut self.tail))?.replace("secret", "******");
+            let (ready, tail) = text.split_at(text.len().saturating_sub(5));
+            self.tail = tail.as_bytes().to_vec();
+            Ok(Bytes::copy_from_slice(ready.as_bytes()))
+        }
+        fn finish(&mut self) -> Result<Bytes, BoxedError> {
+            Ok(std::mem::take(&mut self.tail).into())
+        }
+    }
+
+    async fn read_string(mut body: ResBody) -> String {
+        let mut content = String::new();
+        while let Some(chunk) = body.next().await {
+            content.push_str(std::str::from_utf8(&chunk.unwrap().into_data().unwrap()).unwrap());
+        }
+        content
+    }
+
+    #[tokio::test]
+    async fn test_mapped_body() {
+        let chunks = vec!["my sec", "ret is ", "already secret"];
+        let body = ResBody::stream(stream::iter(chunks.into_iter().map(Ok::<_, BoxedError>)));
+        let body = body.map_with(Redact { tail: Vec::new() });
+        assert_eq!(read_string(body).await, "my ****** is already ******");
+
+        let body =
+            ResBody::Once(Bytes::from_static(b"hello")).map_with(|chunk: Bytes| chunk.to_ascii_uppercase().into());
+        assert_eq!(read_string(body).await, "HELLO");
+    }
+}
@@ -11,6 +11,10 @@ pub use hyper::body::Incoming as HyperBody;
 pub use res::ResBody;
 mod channel;
 pub use channel::{BodyReceiver, BodySender};
+mod mapper;
+pub use mapper::{BodyMapper, MappedBody};
+mod stats;
+pub use stats::BodyStats;
 
 use std::ops::{Deref, DerefMut};
 
@@ -10,6 +10,7 @@ use hyper::body::{Body, Frame, Incoming, SizeHint};
 
 use bytes::Bytes;
 
+use crate::http::body::{BodyMapper, MappedBody};
 use crate::BoxedError;
 
 /// Body for request.
@@ -53,6 +54,17 @@ impl ReqBody {
     pub fn take(&mut self) -> Self {
         std::mem::replace(self, Self::None)
     }
+
+    /// Transform data chunks of this body by `mapper` while it is streamed.
+    ///
+    /// `None` body is returned unchanged.
+    #[inline]
+    pub fn map_with(self, mapper: impl BodyMapper) -> Self {
+        match self {
+            Self::None => self,
+            body => Self::Boxed(Box::pin(MappedBody::new(body, mapper))),
+        }
+    }
 }
 
 impl Body for ReqBody {
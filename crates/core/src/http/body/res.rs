@@ -17,7 +17,7 @@ use sync_wrapper::SyncWrapper;
 use bytes::Bytes;
 
 use crate::error::BoxedError;
-use crate::http::body::{BodyReceiver, BodySender, BytesFrame};
+use crate::http::body::{BodyMapper, BodyReceiver, BodySender, BytesFrame, MappedBody};
 use crate::prelude::StatusError;
 
 /// Response body type.
@@ -131,6 +131,17 @@ impl ResBody {
     pub fn take(&mut self) -> Self {
         std::mem::replace(self, Self::None)
     }
+
+    /// Transform data chunks of this body by `mapper` while it is streamed.
+    ///
+    /// `None` and `Error` bodies are returned unchanged.
+    #[inline]
+    pub fn map_with(self, mapper: impl BodyMapper) -> Self {
+        match self {
+            Self::None | Self::Error(_) => self,
+            body => Self::Boxed(Box::pin(MappedBody::new(body, mapper))),
+        }
+    }
 }
 
 impl Body for ResBody {
@@ -0,0 +1,245 @@
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use hyper::body::{Body, Frame, SizeHint};
+use pin_project::{pin_project, pinned_drop};
+
+use crate::http::body::{ReqBody, ResBody};
+use crate::BoxedError;
+
+type FinishFn = Box<dyn FnOnce(&BodyStats) + Send + 'static>;
+
+struct Inner {
+    read: AtomicU64,
+    written: AtomicU64,
+    // `None` after the response body is finished.
+    on_finish: Mutex<Option<Vec<FinishFn>>>,
+}
+
+/// Counters of bytes read from the request body and bytes written to the response body of a request.
+///
+/// When it is enabled by [`Service::body_stats`](crate::Service::body_stats), the request body is counted while
+/// it is read by handlers, and the response body is counted while it is written to the connection, after all
+/// handlers and the catcher, so the written bytes are the final size on the wire, for example after compression.
+/// Headers are not counted.
+///
+/// It is injected into [`Depot`](crate::Depot), middlewares can get it by `depot.obtain::<BodyStats>()` and
+/// register [`BodyStats::on_finish`] to record the bytes when the response body is finished.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::body::BodyStats;
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn bandwidth(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+///     ctrl.call_next(req, depot, res).await;
+///     if let Ok(stats) = depot.obtain::<BodyStats>() {
+///         let route = req.matched_path().unwrap_or_default().to_owned();
+///         stats.on_finish(move |stats| {
+///             println!("{route}: read {} bytes, written {} bytes", stats.bytes_read(), stats.bytes_written());
+///         });
+///     }
+/// }
+///
+/// let service = Service::new(Router::new().hoop(bandwidth)).body_stats(true);
+/// ```
+#[derive(Clone)]
+pub struct BodyStats {
+    inner: Arc<Inner>,
+}
+impl Default for BodyStats {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for BodyStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStats")
+            .field("bytes_read", &self.bytes_read())
+            .field("bytes_written", &self.bytes_written())
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+impl BodyStats {
+    /// Create a new `BodyStats`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                read: AtomicU64::new(0),
+                written: AtomicU64::new(0),
+                on_finish: Mutex::new(Some(Vec::new())),
+            }),
+        }
+    }
+
+    /// Bytes of the request body read so far.
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.inner.read.load(Ordering::Acquire)
+    }
+
+    /// Bytes of the response body written so far.
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.written.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the response body is written completely, or it is dropped because the connection is
+    /// closed.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.inner.on_finish.lock().map(|fns| fns.is_none()).unwrap_or(true)
+    }
+
+    /// Register a function called when the response body is finished, it is called immediately if the response
+    /// body is already finished.
+    pub fn on_finish<F>(&self, f: F)
+    where
+        F: FnOnce(&BodyStats) + Send + 'static,
+    {
+        if let Ok(mut fns) = self.inner.on_finish.lock() {
+            if let Some(fns) = fns.as_mut() {
+                fns.push(Box::new(f));
+                return;
+            }
+        }
+        f(self);
+    }
+
+    /// Mark the response body as finished and call registered functions.
+    pub fn finish(&self) {
+        let fns = self.inner.on_finish.lock().ok().and_then(|mut fns| fns.take());
+        for f in fns.into_iter().flatten() {
+            f(self);
+        }
+    }
+
+    /// Wrap the request body, bytes are counted as read when handlers read them.
+    pub fn count_request(&self, body: ReqBody) -> ReqBody {
+        match body {
+            ReqBody::None => body,
+            body => ReqBody::Boxed(Box::pin(CountedBody::new(body, self.clone(), false))),
+        }
+    }
+
+    /// Wrap the response body, bytes are counted as written when they are written to the connection,
+    /// [`BodyStats::finish`] is called when the body ends or is dropped.
+    pub fn count_response(&self, body: ResBody) -> ResBody {
+        match body {
+            ResBody::None | ResBody::Error(_) => {
+                self.finish();
+                body
+            }
+            body => ResBody::Boxed(Box::pin(CountedBody::new(body, self.clone(), true))),
+        }
+    }
+}
+
+/// Body counts bytes of data frames polled from the inner body.
+#[pin_project(PinnedDrop)]
+struct CountedBody<B> {
+    #[pin]
+    inner: B,
+    stats: BodyStats,
+    response: bool,
+}
+impl<B> CountedBody<B> {
+    fn new(inner: B, stats: BodyStats, response: bool) -> Self {
+        Self { inner, stats, response }
+    }
+}
+#[pinned_drop]
+impl<B> PinnedDrop for CountedBody<B> {
+    fn drop(self: Pin<&mut Self>) {
+        if self.response {
+            self.stats.finish();
+        }
+    }
+}
+
+impl<B> Body for CountedBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxedError>,
+{
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        if let Some(Ok(frame)) = &frame {
+            if let Some(data) = frame.data_ref() {
+                let counter = if *this.response {
+                    &this.stats.inner.written
+                } else {
+                    &this.stats.inner.read
+                };
+                counter.fetch_add(data.len() as u64, Ordering::AcqRel);
+            }
+        }
+        Poll::Ready(frame.map(|frame| frame.map_err(Into::into)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    static RECORDED: Mutex<Vec<(u64, u64)>> = Mutex::new(Vec::new());
+
+    #[handler]
+    async fn record(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.call_next(req, depot, res).await;
+        depot.obtain::<BodyStats>().unwrap().on_finish(|stats| {
+            RECORDED
+                .lock()
+                .unwrap()
+                .push((stats.bytes_read(), stats.bytes_written()));
+        });
+    }
+
+    #[handler]
+    async fn echo(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        let body = req.payload().await.unwrap().clone();
+        let stats = depot.obtain::<BodyStats>().unwrap();
+        assert_eq!(stats.bytes_read(), body.len() as u64);
+        res.render(String::from_utf8(body.to_vec()).unwrap().repeat(2));
+    }
+
+    #[tokio::test]
+    async fn test_body_stats() {
+        let router = Router::new().hoop(record).post(echo);
+        let service = Service::new(router).body_stats(true);
+
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .text("hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.body.size_hint().exact(), Some(10));
+        assert!(RECORDED.lock().unwrap().is_empty());
+        assert_eq!(res.take_string().await.unwrap(), "hellohello");
+        drop(res);
+        assert_eq!(*RECORDED.lock().unwrap(), vec![(5, 10)]);
+    }
+}
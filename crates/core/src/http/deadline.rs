@@ -0,0 +1,197 @@
+//! Request deadline headers.
+//!
+//! A deadline tells a server how long the client is willing to wait, servers should give up on work which can
+//! not finish before it, and pass the remaining budget to services they call. [`DeadlineHeader`] parses the
+//! incoming header into an [`Instant`] and formats the remaining budget for outgoing requests.
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Header of gRPC timeouts.
+pub const GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+/// Header of deadlines as unix timestamps in milliseconds.
+pub const X_REQUEST_DEADLINE: HeaderName = HeaderName::from_static("x-request-deadline");
+
+/// Format of the deadline header value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum DeadlineFormat {
+    /// gRPC style timeout, a positive integer of at most 8 digits followed by a unit, `H` for hours, `M` for
+    /// minutes, `S` for seconds, `m` for milliseconds, `u` for microseconds and `n` for nanoseconds, e.g. `100m`.
+    GrpcTimeout,
+    /// Remaining time in milliseconds, e.g. `2500`.
+    TimeoutMillis,
+    /// Absolute deadline as a unix timestamp in milliseconds, it relies on synchronized clocks.
+    UnixMillis,
+}
+
+/// Header carrying the request deadline.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeadlineHeader {
+    name: HeaderName,
+    format: DeadlineFormat,
+    max_timeout: Option<Duration>,
+}
+
+impl DeadlineHeader {
+    /// Create a new `DeadlineHeader` with header name and format.
+    #[inline]
+    pub fn new(name: HeaderName, format: DeadlineFormat) -> Self {
+        Self {
+            name,
+            format,
+            max_timeout: None,
+        }
+    }
+
+    /// `grpc-timeout` header in [`DeadlineFormat::GrpcTimeout`].
+    #[inline]
+    pub fn grpc_timeout() -> Self {
+        Self::new(GRPC_TIMEOUT, DeadlineFormat::GrpcTimeout)
+    }
+
+    /// `x-request-deadline` header in [`DeadlineFormat::UnixMillis`].
+    #[inline]
+    pub fn x_request_deadline() -> Self {
+        Self::new(X_REQUEST_DEADLINE, DeadlineFormat::UnixMillis)
+    }
+
+    /// Sets the max timeout accepted from clients, longer timeouts are reduced to it.
+    #[inline]
+    pub fn max_timeout(mut self, max_timeout: Duration) -> Self {
+        self.max_timeout = Some(max_timeout);
+        self
+    }
+
+    /// Get the header name.
+    #[inline]
+    pub fn name(&self) -> &HeaderName {
+        &self.name
+    }
+
+    /// Get the header format.
+    #[inline]
+    pub fn format(&self) -> DeadlineFormat {
+        self.format
+    }
+
+    /// Parse the deadline from headers, returns `None` if the header is missing or malformed.
+    pub fn parse(&self, headers: &HeaderMap) -> Option<Instant> {
+        let value = headers.get(&self.name)?.to_str().ok()?.trim();
+        let timeout = match self.format {
+            DeadlineFormat::GrpcTimeout => {
+                let (digits, unit) = value.split_at(value.len().checked_sub(1)?);
+                if digits.is_empty() || digits.len() > 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                let amount: u64 = digits.parse().ok()?;
+                match unit {
+                    "H" => Duration::from_secs(amount * 3600),
+                    "M" => Duration::from_secs(amount * 60),
+                    "S" => Duration::from_secs(amount),
+                    "m" => Duration::from_millis(amount),
+                    "u" => Duration::from_micros(amount),
+                    "n" => Duration::from_nanos(amount),
+                    _ => return None,
+                }
+            }
+            DeadlineFormat::TimeoutMillis => Duration::from_millis(value.parse().ok()?),
+            DeadlineFormat::UnixMillis => {
+                let deadline = UNIX_EPOCH + Duration::from_millis(value.parse().ok()?);
+                deadline.duration_since(SystemTime::now()).unwrap_or_default()
+            }
+        };
+        let timeout = match self.max_timeout {
+            Some(max_timeout) => timeout.min(max_timeout),
+            None => timeout,
+        };
+        Some(Instant::now() + timeout)
+    }
+
+    /// Format the remaining time until `deadline` as a header value.
+    pub fn format_value(&self, deadline: Instant) -> HeaderValue {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let value = match self.format {
+            DeadlineFormat::GrpcTimeout => {
+                // Use the finest unit which fits in 8 digits.
+                let units = [
+                    (remaining.as_nanos(), 'n'),
+                    (remaining.as_micros(), 'u'),
+                    (remaining.as_millis(), 'm'),
+                    (u128::from(remaining.as_secs()), 'S'),
+                    (u128::from(remaining.as_secs() / 60), 'M'),
+                ];
+                match units.iter().find(|(amount, _)| *amount < 100_000_000) {
+                    Some((amount, unit)) => format!("{amount}{unit}"),
+                    None => format!("{}H", (remaining.as_secs() / 3600).min(99_999_999)),
+                }
+            }
+            DeadlineFormat::TimeoutMillis => remaining.as_millis().to_string(),
+            DeadlineFormat::UnixMillis => (SystemTime::now() + remaining)
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or_default()
+                .to_string(),
+        };
+        HeaderValue::from_str(&value).expect("deadline header value should be valid")
+    }
+
+    /// Set the remaining time until `deadline` to headers.
+    #[inline]
+    pub fn insert(&self, headers: &mut HeaderMap, deadline: Instant) {
+        headers.insert(self.name.clone(), self.format_value(deadline));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(header: &DeadlineHeader, value: &'static str) -> Option<Duration> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header.name().clone(), HeaderValue::from_static(value));
+        header
+            .parse(&headers)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        let header = DeadlineHeader::grpc_timeout();
+        let remaining = parse(&header, "2S").unwrap();
+        assert!(remaining > Duration::from_millis(1900) && remaining <= Duration::from_secs(2));
+        assert!(parse(&header, "100m").unwrap() <= Duration::from_millis(100));
+        assert!(parse(&header, "1H").unwrap() > Duration::from_secs(3500));
+        assert_eq!(parse(&header, "S"), None);
+        assert_eq!(parse(&header, "123456789S"), None);
+        assert_eq!(parse(&header, "10x"), None);
+
+        let header = DeadlineHeader::grpc_timeout().max_timeout(Duration::from_secs(1));
+        assert!(parse(&header, "1H").unwrap() <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_parse_millis() {
+        let header = DeadlineHeader::new(HeaderName::from_static("x-timeout-ms"), DeadlineFormat::TimeoutMillis);
+        assert!(parse(&header, "1500").unwrap() > Duration::from_millis(1400));
+        assert_eq!(parse(&header, "-1"), None);
+
+        let header = DeadlineHeader::x_request_deadline();
+        assert_eq!(parse(&header, "1000"), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_format_value() {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let value = DeadlineHeader::grpc_timeout().format_value(deadline);
+        let value = value.to_str().unwrap();
+        assert!(value.ends_with('u'));
+        let micros: u64 = value.trim_end_matches('u').parse().unwrap();
+        assert!(micros > 4_900_000 && micros <= 5_000_000);
+
+        let header = DeadlineHeader::new(HeaderName::from_static("x-timeout-ms"), DeadlineFormat::TimeoutMillis);
+        let mut headers = HeaderMap::new();
+        header.insert(&mut headers, Instant::now() - Duration::from_secs(1));
+        assert_eq!(headers["x-timeout-ms"], "0");
+    }
+}
@@ -158,6 +158,7 @@ impl FilePart {
             size += chunk.len() as u64;
             file.write_all(&chunk).await?;
         }
+        file.flush().await?;
         Ok(FilePart {
             name,
             headers: field.headers().to_owned(),
@@ -1,14 +1,17 @@
 //! The http related types and functions.
 
+pub mod deadline;
 pub mod errors;
 pub mod form;
 mod range;
 pub mod request;
 pub mod response;
+pub mod upgrade;
 cfg_feature! {
     #![feature = "cookie"]
     pub use cookie;
 }
+pub use deadline::{DeadlineFormat, DeadlineHeader};
 pub use errors::{ParseError, StatusError};
 pub use headers;
 pub use http::method::Method;
@@ -18,7 +21,7 @@ pub use range::HttpRange;
 pub use request::Request;
 pub mod body;
 pub use body::{Body, ReqBody, ResBody};
-pub use response::Response;
+pub use response::{NoBuffering, Response};
 
 pub use http::version::Version;
 
@@ -1,28 +1,33 @@
 //! Http request.
 
 use std::fmt::{self, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 #[cfg(feature = "cookie")]
 use cookie::{Cookie, CookieJar};
-use http::header::{AsHeaderName, HeaderMap, HeaderValue, IntoHeaderName, CONTENT_TYPE};
+use http::header::{AsHeaderName, HeaderMap, HeaderValue, IntoHeaderName, CONTENT_LENGTH, CONTENT_TYPE};
 use http::method::Method;
 pub use http::request::Parts;
 use http::uri::{Scheme, Uri};
 use http::{self, Extensions};
 use http_body_util::{BodyExt, Limited};
-use indexmap::IndexMap;
+use hyper::upgrade::OnUpgrade;
 use mime;
 use multimap::MultiMap;
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use serde::de::Deserialize;
+use tokio_util::sync::CancellationToken;
 
-use crate::conn::SocketAddr;
+use crate::conn::{ConnectionInfo, SocketAddr, TlsInfo};
 use crate::extract::{Extractible, Metadata};
-use crate::http::body::ReqBody;
+use crate::http::body::{BodyMapper, ReqBody};
 use crate::http::form::{FilePart, FormData};
-use crate::http::{Mime, ParseError, Version};
+use crate::http::upgrade::TakeOver;
+use crate::http::{Mime, ParseError, StatusError, Version};
+use crate::routing::{PathParams, RouteMetadata};
 use crate::serde::{from_request, from_str_map, from_str_multi_map, from_str_multi_val, from_str_val};
 use crate::Error;
 
@@ -59,18 +64,26 @@ pub struct Request {
     #[cfg(feature = "cookie")]
     pub(crate) cookies: CookieJar,
 
-    pub(crate) params: IndexMap<String, String>,
+    pub(crate) params: PathParams,
+    pub(crate) matched_path: Option<String>,
+    pub(crate) route_metadata: RouteMetadata,
 
     // accept: Option<Vec<Mime>>,
     pub(crate) queries: OnceCell<MultiMap<String, String>>,
     pub(crate) form_data: tokio::sync::OnceCell<FormData>,
     pub(crate) payload: tokio::sync::OnceCell<Bytes>,
+    pub(crate) buffered_body: Option<Bytes>,
 
     /// The version of the HTTP protocol used.
     pub(crate) version: Version,
     pub(crate) scheme: Scheme,
     pub(crate) local_addr: SocketAddr,
     pub(crate) remote_addr: SocketAddr,
+    pub(crate) tls_info: Option<Arc<TlsInfo>>,
+    pub(crate) cancellation_token: CancellationToken,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) received_at: Instant,
+    pub(crate) routed_at: Option<Instant>,
 }
 
 impl fmt::Debug for Request {
@@ -85,6 +98,7 @@ impl fmt::Debug for Request {
             .field("body", &self.body())
             .field("local_addr", &self.local_addr)
             .field("remote_addr", &self.remote_addr)
+            .field("tls_info", &self.tls_info)
             .finish()
     }
 }
@@ -108,14 +122,22 @@ impl Request {
             method: Method::default(),
             #[cfg(feature = "cookie")]
             cookies: CookieJar::default(),
-            params: IndexMap::new(),
+            params: PathParams::new(),
+            matched_path: None,
+            route_metadata: RouteMetadata::new(),
             queries: OnceCell::new(),
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
+            buffered_body: None,
             version: Version::default(),
             scheme: Scheme::HTTP,
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
+            tls_info: None,
+            cancellation_token: CancellationToken::new(),
+            deadline: None,
+            received_at: Instant::now(),
+            routed_at: None,
         }
     }
     /// Creates a new `Request` from [`hyper::Request`].
@@ -161,12 +183,20 @@ impl Request {
             #[cfg(feature = "cookie")]
             cookies,
             // accept: None,
-            params: IndexMap::new(),
+            params: PathParams::new(),
+            matched_path: None,
+            route_metadata: RouteMetadata::new(),
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
+            buffered_body: None,
             // multipart: OnceCell::new(),
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
+            tls_info: None,
+            cancellation_token: CancellationToken::new(),
+            deadline: None,
+            received_at: Instant::now(),
+            routed_at: None,
             version,
             scheme,
         }
@@ -315,6 +345,84 @@ impl Request {
         &mut self.remote_addr
     }
 
+    /// Get the TLS information of the connection, `None` if the request is not received over TLS.
+    #[inline]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.tls_info.as_deref()
+    }
+    /// Get mutable TLS information of the connection.
+    #[inline]
+    pub fn tls_info_mut(&mut self) -> &mut Option<Arc<TlsInfo>> {
+        &mut self.tls_info
+    }
+    /// Get the information of the connection, such as addresses, http version and negotiated TLS parameters.
+    #[inline]
+    pub fn connection_info(&self) -> ConnectionInfo<'_> {
+        ConnectionInfo::new(self)
+    }
+
+    /// Get the cancellation token of this request.
+    ///
+    /// The token is cancelled when the client disconnects before the response is returned, or the server
+    /// begins graceful shutdown. The handler future is dropped by the server when the client disconnects, so
+    /// the token is mostly useful for work spawned by handlers, which should be aborted together with the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn report(req: &mut Request) -> &'static str {
+    ///     let token = req.cancellation_token().clone();
+    ///     let task = tokio::spawn(async move {
+    ///         tokio::select! {
+    ///             _ = token.cancelled() => None,
+    ///             _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => Some("report"),
+    ///         }
+    ///     });
+    ///     task.await.ok().flatten().unwrap_or("cancelled")
+    /// }
+    /// ```
+    #[inline]
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Get the deadline of this request.
+    ///
+    /// The deadline is set by middlewares such as `Timeout`, it may come from a deadline header sent by the
+    /// client, see [`DeadlineHeader`](crate::http::DeadlineHeader). Handlers can use it to give up on work which
+    /// can not finish in time, and proxies forward the remaining budget to upstreams.
+    #[inline]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+    /// Set the deadline of this request.
+    #[inline]
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+    /// Get the time remaining until the deadline, returns `None` if there is no deadline.
+    #[inline]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Get the time when this request is received, it is the time when this `Request` is created.
+    #[inline]
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
+    /// Get the time when the route of this request is detected, returns `None` if no route is matched.
+    ///
+    /// If the request is rerouted, it is the time when the last route is detected.
+    #[inline]
+    pub fn routed_at(&self) -> Option<Instant> {
+        self.routed_at
+    }
+
     /// Get request remote address reference.
     #[inline]
     pub fn local_addr(&self) -> &SocketAddr {
@@ -402,16 +510,79 @@ impl Request {
         &mut self.body
     }
 
-    /// Sets body to a new value and returns old value.
+    /// Sets body to a new value and returns old value, the body buffered by [`Request::buffer_body`] is dropped.
     #[inline]
     pub fn replace_body(&mut self, body: ReqBody) -> ReqBody {
+        self.buffered_body = None;
         std::mem::replace(&mut self.body, body)
     }
 
     /// Take body form the request, and set the body to None in the request.
+    ///
+    /// If the body is buffered by [`Request::buffer_body`], a copy of the buffered body is returned and the body
+    /// is kept in the request.
     #[inline]
     pub fn take_body(&mut self) -> ReqBody {
-        self.replace_body(ReqBody::None)
+        match &self.buffered_body {
+            Some(bytes) => ReqBody::Once(bytes.clone()),
+            None => self.replace_body(ReqBody::None),
+        }
+    }
+
+    /// Take over the underlying connection after the response is sent, such as for a custom upgrade protocol or a
+    /// `CONNECT` tunnel.
+    ///
+    /// It returns `400 Bad Request` if the connection can not be taken over, such as it has been taken over or the
+    /// request is not from an HTTP/1 connection. See [`upgrade`](crate::http::upgrade) for details.
+    #[inline]
+    pub fn take_over(&mut self) -> Result<TakeOver, StatusError> {
+        self.extensions
+            .remove::<OnUpgrade>()
+            .map(TakeOver::new)
+            .ok_or_else(|| StatusError::bad_request().brief("The connection can not be taken over."))
+    }
+
+    /// Buffer the body up to `max_size`, so the body can be read multiple times.
+    ///
+    /// Reading the body takes it, so only the first reader gets it, such as an audit middleware, a signature
+    /// verification middleware and an extractor of the handler can not read the same body. After the body is
+    /// buffered, [`Request::take_body`] returns a copy of it, so every reader gets the whole body.
+    ///
+    /// It returns an error if the body is larger than `max_size` or reading the body fails, the body is consumed
+    /// then.
+    pub async fn buffer_body(&mut self, max_size: usize) -> Result<&Bytes, ParseError> {
+        if self.buffered_body.is_none() {
+            let body = self.replace_body(ReqBody::None);
+            let bytes = match (self.payload.get(), body) {
+                (Some(payload), _) => payload.clone(),
+                (None, ReqBody::Once(bytes)) if bytes.len() <= max_size => bytes,
+                (None, body) => Limited::new(body, max_size)
+                    .collect()
+                    .await
+                    .map_err(ParseError::other)?
+                    .to_bytes(),
+            };
+            self.body = ReqBody::Once(bytes.clone());
+            self.buffered_body = Some(bytes);
+        }
+        Ok(self.buffered_body.as_ref().expect("buffered body should be set"))
+    }
+    /// Get the body buffered by [`Request::buffer_body`].
+    #[inline]
+    pub fn buffered_body(&self) -> Option<&Bytes> {
+        self.buffered_body.as_ref()
+    }
+
+    /// Transform the body by `mapper` while it is read, the whole body is not buffered.
+    ///
+    /// It should be called before the body is parsed, it has no effect on already parsed payload or form data.
+    /// The `Content-Length` header is removed because the mapped body may have different size.
+    #[inline]
+    pub fn map_body(&mut self, mapper: impl BodyMapper) -> &mut Self {
+        self.headers.remove(CONTENT_LENGTH);
+        self.body = self.take_body().map_with(mapper);
+        self.buffered_body = None;
+        self
     }
 
     /// Returns a reference to the associated extensions.
@@ -537,14 +708,26 @@ impl Request {
             self.cookies.get(name.as_ref())
         }
     }
-    /// Get params reference.
+    /// Get the route path template of the matched router, such as `/users/<id>`.
+    ///
+    /// It is `None` if the request is not matched by any router.
+    #[inline]
+    pub fn matched_path(&self) -> Option<&str> {
+        self.matched_path.as_deref()
+    }
+    /// Get metadata of the matched routers, see [`Router::meta`](crate::Router::meta).
+    #[inline]
+    pub fn route_metadata(&self) -> &RouteMetadata {
+        &self.route_metadata
+    }
+    /// Get params reference, params are kept in the order they are matched.
     #[inline]
-    pub fn params(&self) -> &IndexMap<String, String> {
+    pub fn params(&self) -> &PathParams {
         &self.params
     }
     /// Get params mutable reference.
     #[inline]
-    pub fn params_mut(&mut self) -> &mut IndexMap<String, String> {
+    pub fn params_mut(&mut self) -> &mut PathParams {
         &mut self.params
     }
 
@@ -931,4 +1114,26 @@ file content\r\n\
         let files = req.files("file1").await.unwrap();
         assert_eq!(files[0].name().unwrap(), "err.txt");
     }
+
+    #[tokio::test]
+    async fn test_buffer_body() {
+        let mut req: Request = TestClient::post("http://127.0.0.1:5800/hello")
+            .raw_form("lover=dog&money=sh*t")
+            .build();
+        assert!(req.buffer_body(4).await.is_err());
+
+        let mut req: Request = TestClient::post("http://127.0.0.1:5800/hello")
+            .raw_form("lover=dog&money=sh*t")
+            .build();
+        assert_eq!(req.buffer_body(1024).await.unwrap(), "lover=dog&money=sh*t");
+        assert_eq!(req.payload().await.unwrap(), "lover=dog&money=sh*t");
+        assert_eq!(req.form::<String>("money").await.unwrap(), "sh*t");
+        assert_eq!(req.buffered_body().unwrap(), "lover=dog&money=sh*t");
+        let body = req.take_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "lover=dog&money=sh*t");
+
+        req.replace_body(ReqBody::None);
+        assert!(req.buffered_body().is_none());
+        assert!(req.take_body().is_none());
+    }
 }
@@ -5,8 +5,9 @@ use std::path::PathBuf;
 
 #[cfg(feature = "cookie")]
 use cookie::{Cookie, CookieJar};
-use futures_util::stream::Stream;
-use http::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use futures_channel::oneshot;
+use futures_util::stream::{Stream, StreamExt};
+use http::header::{HeaderMap, HeaderName, HeaderValue, IntoHeaderName, CONTENT_LENGTH};
 pub use http::response::Parts;
 use http::{version::Version, Extensions};
 use mime::Mime;
@@ -16,6 +17,7 @@ use crate::http::{StatusCode, StatusError};
 use crate::{BoxedError, Error, Scribe};
 use bytes::Bytes;
 
+use crate::http::body::{BodyMapper, BodyStats};
 pub use crate::http::body::{BodySender, BytesFrame, ResBody};
 
 /// Represents an HTTP response
@@ -33,6 +35,36 @@ pub struct Response {
     /// The HTTP body.
     pub body: ResBody,
     pub(crate) extensions: Extensions,
+    pub(crate) flusher: Option<Flusher>,
+}
+
+/// Route metadata disables response buffering, it is attached by `Router::meta(NoBuffering)`.
+///
+/// Middlewares which transform the whole body, such as compression, switch to streaming mode for routes with
+/// it, so data flushed by [`Response::flush`] reaches the client immediately. The `X-Accel-Buffering: no` header
+/// is also sent to tell reverse proxies not to buffer the response.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NoBuffering;
+
+type FlushHook = Box<dyn FnOnce(&mut Response) + Send + Sync + 'static>;
+
+/// Sends the head of the response to the connection when the response is flushed the first time, and the body
+/// data to the connection after that.
+pub(crate) struct Flusher {
+    head_tx: Option<oneshot::Sender<hyper::Response<ResBody>>>,
+    body_tx: Option<BodySender>,
+    hooks: Vec<FlushHook>,
+    pub(crate) body_stats: Option<BodyStats>,
+}
+impl Flusher {
+    pub(crate) fn new(head_tx: oneshot::Sender<hyper::Response<ResBody>>) -> Self {
+        Self {
+            head_tx: Some(head_tx),
+            body_tx: None,
+            hooks: Vec::new(),
+            body_stats: None,
+        }
+    }
 }
 impl Default for Response {
     #[inline]
@@ -80,6 +112,7 @@ where
             #[cfg(feature = "cookie")]
             cookies,
             extensions: Extensions::new(),
+            flusher: None,
         }
     }
 }
@@ -96,6 +129,7 @@ impl Response {
             #[cfg(feature = "cookie")]
             cookies: CookieJar::default(),
             extensions: Extensions::new(),
+            flusher: None,
         }
     }
 
@@ -110,6 +144,7 @@ impl Response {
             headers: HeaderMap::new(),
             cookies,
             extensions: Extensions::new(),
+            flusher: None,
         }
     }
 
@@ -128,6 +163,20 @@ impl Response {
     pub fn set_headers(&mut self, headers: HeaderMap) {
         self.headers = headers
     }
+    /// Insert headers into the response, the existing values of the same names are replaced.
+    ///
+    /// Headers are written into the response directly, so middleware adding several headers does not need to build
+    /// an intermediate `HeaderMap`, which allocates on every request.
+    #[inline]
+    pub fn insert_headers<I>(&mut self, headers: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (HeaderName, HeaderValue)>,
+    {
+        for (name, value) in headers {
+            self.headers.insert(name, value);
+        }
+        self
+    }
 
     /// Modify a header for this response.
     ///
@@ -149,6 +198,41 @@ impl Response {
         Ok(self)
     }
 
+    /// Add a `Link: <url>; rel=preload` header to preload the resource at `url`, `destination` is the `as`
+    /// attribute of the link, such as `style`, `script` or `font`.
+    ///
+    /// The link is sent as a header of the final response, browsers use it to preload resources while the document
+    /// is still loading. `103 Early Hints` informational responses are not implemented, because hyper can not send
+    /// informational responses before the final response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn index(res: &mut Response) {
+    ///     res.add_preload_link("/static/app.css", "style")
+    ///         .unwrap()
+    ///         .add_preload_link("/static/app.js", "script")
+    ///         .unwrap();
+    ///     res.render(Text::Html("<html>...</html>"));
+    /// }
+    /// ```
+    pub fn add_preload_link(&mut self, url: impl AsRef<str>, destination: impl AsRef<str>) -> crate::Result<&mut Self> {
+        let mut link = format!("<{}>; rel=preload", url.as_ref());
+        let destination = destination.as_ref();
+        if !destination.is_empty() {
+            link.push_str("; as=");
+            link.push_str(destination);
+            if destination == "font" {
+                // fonts are always fetched in cors mode.
+                link.push_str("; crossorigin");
+            }
+        }
+        self.add_header(http::header::LINK, link, false)
+    }
+
     /// Get version.
     #[inline]
     pub fn version(&self) -> Version {
@@ -184,6 +268,30 @@ impl Response {
         self.replace_body(ResBody::None)
     }
 
+    /// Transform the body by `mapper` while it is streamed to the client, the whole body is not buffered.
+    ///
+    /// It is usually called by a middleware after `FlowCtrl::call_next`. The `Content-Length` header is removed
+    /// because the mapped body may have different size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use salvo_core::hyper::body::Bytes;
+    ///
+    /// #[handler]
+    /// async fn shout(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+    ///     ctrl.call_next(req, depot, res).await;
+    ///     res.map_body(|chunk: Bytes| Bytes::from(chunk.to_ascii_uppercase()));
+    /// }
+    /// ```
+    #[inline]
+    pub fn map_body(&mut self, mapper: impl BodyMapper) -> &mut Self {
+        self.headers.remove(CONTENT_LENGTH);
+        self.body = self.take_body().map_with(mapper);
+        self
+    }
+
     // If return `true`, it means this response is ready for write back and the reset handlers should be skipped.
     #[doc(hidden)]
     #[inline]
@@ -462,6 +570,145 @@ impl Response {
         self.body = body;
         sender
     }
+
+    /// Send the response to the client before handlers are finished, it is used for progressive rendering.
+    ///
+    /// The status code and headers are sent by the first call, changes to them after that are ignored. The body
+    /// written so far is sent to the client, and the body written later is sent by next calls or when all
+    /// handlers are finished. The catcher is not called for flushed responses.
+    ///
+    /// It does nothing if the response is not served by a connection, for example in tests with `TestClient`,
+    /// the whole response is sent when handlers are finished as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::http::NoBuffering;
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn page(res: &mut Response) -> salvo_core::Result<()> {
+    ///     res.add_header("content-type", "text/html; charset=utf-8", true)?;
+    ///     res.write_body("<html><head><title>Report</title></head><body>")?;
+    ///     res.flush().await?;
+    ///     // Render the slow part of the page.
+    ///     res.write_body("<p>Done</p></body></html>")?;
+    ///     Ok(())
+    /// }
+    ///
+    /// let router = Router::with_path("report").meta(NoBuffering).get(page);
+    /// ```
+    pub async fn flush(&mut self) -> crate::Result<()> {
+        let Some(flusher) = &mut self.flusher else {
+            return Ok(());
+        };
+        if let Some(head_tx) = flusher.head_tx.take() {
+            let hooks = std::mem::take(&mut flusher.hooks);
+            let body_stats = flusher.body_stats.take();
+            let pending = self.take_body();
+            let (body_tx, body) = ResBody::channel();
+            self.body = body;
+            self.headers.remove(CONTENT_LENGTH);
+            for hook in hooks {
+                hook(self);
+            }
+            if let Some(body_stats) = body_stats {
+                self.body = body_stats.count_response(self.take_body());
+            }
+            let head = self.flushed_head();
+            if head_tx.send(head).is_err() {
+                return Err(Error::other("connection is closed before the response is flushed"));
+            }
+            self.body = pending;
+            if let Some(flusher) = &mut self.flusher {
+                flusher.body_tx = Some(body_tx);
+            }
+        }
+        let body = self.take_body();
+        if let Some(body_tx) = self.flusher.as_mut().and_then(|flusher| flusher.body_tx.as_mut()) {
+            forward_body(body, body_tx).await?;
+        }
+        Ok(())
+    }
+
+    /// Register a function called before the response is flushed the first time.
+    ///
+    /// Middlewares use it to set headers and transform the flushed body by [`Response::map_body`], because their
+    /// code after `FlowCtrl::call_next` runs after the response is flushed.
+    #[inline]
+    pub fn before_flush<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Response) + Send + Sync + 'static,
+    {
+        if let Some(flusher) = &mut self.flusher {
+            if flusher.head_tx.is_some() {
+                flusher.hooks.push(Box::new(f));
+            }
+        }
+    }
+
+    /// Returns `true` if the response has been flushed by [`Response::flush`].
+    #[inline]
+    pub fn is_flushed(&self) -> bool {
+        self.flusher
+            .as_ref()
+            .map(|flusher| flusher.body_tx.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Send the rest of the body of a flushed response and end it.
+    pub(crate) async fn finish_flush(&mut self) {
+        if let Err(e) = self.flush().await {
+            tracing::debug!(error = ?e, "send flushed response failed");
+        }
+        self.flusher = None;
+    }
+
+    fn flushed_head(&mut self) -> hyper::Response<ResBody> {
+        #[allow(unused_mut)]
+        let mut headers = self.headers.clone();
+        #[cfg(feature = "cookie")]
+        for cookie in self.cookies.delta() {
+            if let Ok(hv) = cookie.encoded().to_string().parse() {
+                headers.append(http::header::SET_COOKIE, hv);
+            }
+        }
+        let status_code = *self.status_code.get_or_insert(StatusCode::OK);
+        let mut head = hyper::Response::new(self.take_body());
+        *head.status_mut() = status_code;
+        *head.headers_mut() = headers;
+        *head.extensions_mut() = std::mem::take(&mut self.extensions);
+        head
+    }
+}
+
+async fn forward_body(body: ResBody, body_tx: &mut BodySender) -> crate::Result<()> {
+    match body {
+        ResBody::None | ResBody::Error(_) => {}
+        ResBody::Once(bytes) => {
+            if !bytes.is_empty() {
+                body_tx.send_data(bytes).await?;
+            }
+        }
+        ResBody::Chunks(chunks) => {
+            for chunk in chunks {
+                body_tx.send_data(chunk).await?;
+            }
+        }
+        mut body => {
+            while let Some(frame) = body.next().await {
+                match frame?.into_data() {
+                    Ok(data) => body_tx.send_data(data).await?,
+                    Err(frame) => {
+                        if let Ok(trailers) = frame.into_trailers() {
+                            body_tx.send_trailers(trailers).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 impl fmt::Debug for Response {
@@ -500,6 +747,42 @@ mod test {
         assert!(body.is_none());
     }
 
+    #[test]
+    fn test_insert_headers() {
+        let mut res = Response::new();
+        res.headers_mut()
+            .append(http::header::VARY, HeaderValue::from_static("accept"));
+        res.headers_mut()
+            .append(http::header::VARY, HeaderValue::from_static("origin"));
+        res.insert_headers([
+            (http::header::VARY, HeaderValue::from_static("cookie")),
+            (http::header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ]);
+        assert_eq!(
+            res.headers().get_all(http::header::VARY).iter().collect::<Vec<_>>(),
+            vec!["cookie"]
+        );
+        assert_eq!(res.headers()[http::header::CACHE_CONTROL], "no-store");
+    }
+
+    #[test]
+    fn test_add_preload_link() {
+        let mut res = Response::new();
+        res.add_preload_link("/app.css", "style")
+            .unwrap()
+            .add_preload_link("/font.woff2", "font")
+            .unwrap();
+        let links = res.headers().get_all(http::header::LINK).iter().collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                "</app.css>; rel=preload; as=style",
+                "</font.woff2>; rel=preload; as=font; crossorigin"
+            ]
+        );
+        assert!(res.add_preload_link("/bad\n", "").is_err());
+    }
+
     #[tokio::test]
     async fn test_body_stream1() {
         let mut body = ResBody::Once(Bytes::from("hello"));
@@ -526,4 +809,19 @@ mod test {
 
         assert_eq!("Hello World", &result)
     }
+
+    #[tokio::test]
+    async fn test_map_body() {
+        let mut res = Response::new();
+        res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from_static("5"));
+        res.body(ResBody::Once(Bytes::from("hello")))
+            .map_body(|chunk: Bytes| Bytes::from(chunk.to_ascii_uppercase()));
+        assert!(res.headers().get(CONTENT_LENGTH).is_none());
+
+        let mut result = bytes::BytesMut::new();
+        while let Some(Ok(data)) = res.body.next().await {
+            result.extend_from_slice(&data.into_data().unwrap_or_default())
+        }
+        assert_eq!("HELLO", &result)
+    }
 }
@@ -0,0 +1,202 @@
+//! Taking over the underlying connection of a request.
+//!
+//! A handler calls [`Request::take_over`], responds `101 Switching Protocols` for a custom upgrade protocol or a `2xx`
+//! status for `CONNECT`, and the connection is handed over as an [`Upgraded`] stream after the response is sent.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::http::header::{CONNECTION, UPGRADE};
+//! use salvo_core::prelude::*;
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! #[handler]
+//! async fn echo(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+//!     let take_over = req.take_over()?;
+//!     res.status_code(StatusCode::SWITCHING_PROTOCOLS);
+//!     res.add_header(CONNECTION, "upgrade", true).unwrap();
+//!     res.add_header(UPGRADE, "echo", true).unwrap();
+//!     take_over.spawn(|mut io| async move {
+//!         let mut buf = [0; 1024];
+//!         while let Ok(n @ 1..) = io.read(&mut buf).await {
+//!             if io.write_all(&buf[..n]).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!     });
+//!     Ok(())
+//! }
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::io::Result as IoResult;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hyper::upgrade::OnUpgrade;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::rt::tokio::TokioIo;
+use crate::Error;
+
+/// The connection of a request which is taken over, it can be awaited by [`TakeOver::upgraded`] after the response is
+/// sent.
+pub struct TakeOver {
+    on_upgrade: OnUpgrade,
+}
+impl Debug for TakeOver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TakeOver").finish()
+    }
+}
+impl TakeOver {
+    #[inline]
+    pub(crate) fn new(on_upgrade: OnUpgrade) -> Self {
+        Self { on_upgrade }
+    }
+
+    /// Wait for the response to be sent and get the connection.
+    ///
+    /// The connection is only handed over if the response status is `101 Switching Protocols`, or a `2xx` status of a
+    /// `CONNECT` request, otherwise an error is returned.
+    pub async fn upgraded(self) -> Result<Upgraded, Error> {
+        let upgraded = self.on_upgrade.await?;
+        Ok(Upgraded {
+            inner: TokioIo::new(upgraded),
+        })
+    }
+
+    /// Spawn a task which serves the connection by `callback` after the response is sent.
+    pub fn spawn<F, Fut>(self, callback: F)
+    where
+        F: FnOnce(Upgraded) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(async move {
+            match self.upgraded().await {
+                Ok(upgraded) => callback(upgraded).await,
+                Err(e) => tracing::debug!(error = ?e, "connection take over failed"),
+            }
+        });
+    }
+}
+
+/// The connection taken over from a request, it is a raw byte stream.
+///
+/// Bytes already read by the server after the request head, such as the first bytes of the new protocol, are returned
+/// by reading it first.
+pub struct Upgraded {
+    inner: TokioIo<hyper::upgrade::Upgraded>,
+}
+impl Debug for Upgraded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded").finish()
+    }
+}
+impl AsyncRead for Upgraded {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+impl AsyncWrite for Upgraded {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::server::conn::http1;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::http::header::{CONNECTION, UPGRADE};
+    use crate::http::uri::Scheme;
+    use crate::prelude::*;
+
+    #[handler]
+    async fn shout(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+        let take_over = req.take_over()?;
+        res.status_code(StatusCode::SWITCHING_PROTOCOLS);
+        res.add_header(CONNECTION, "upgrade", true).unwrap();
+        res.add_header(UPGRADE, "shout", true).unwrap();
+        take_over.spawn(|mut io| async move {
+            let mut buf = [0; 64];
+            while let Ok(n @ 1..) = io.read(&mut buf).await {
+                buf[..n].make_ascii_uppercase();
+                if io.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_take_over() {
+        let service = Service::new(Router::with_path("shout").goal(shout));
+        let handler = service.hyper_handler(
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5801)).into(),
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5802)).into(),
+            Scheme::HTTP,
+            None,
+        );
+        let (mut client, server) = tokio::io::duplex(1024);
+        tokio::spawn(
+            http1::Builder::new()
+                .serve_connection(TokioIo::new(server), handler)
+                .with_upgrades(),
+        );
+
+        // The bytes after the request head belong to the new protocol.
+        client
+            .write_all(b"GET /shout HTTP/1.1\r\nhost: localhost\r\nconnection: upgrade\r\nupgrade: shout\r\n\r\nhello")
+            .await
+            .unwrap();
+        let mut buf = Vec::new();
+        while !buf.ends_with(b"HELLO") {
+            let mut chunk = [0; 256];
+            let n = client.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        let response = String::from_utf8(buf).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(response.contains("upgrade: shout\r\n"));
+
+        client.write_all(b"again").await.unwrap();
+        let mut buf = [0; 5];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"AGAIN");
+    }
+
+    #[tokio::test]
+    async fn test_take_over_without_connection() {
+        let res = crate::test::TestClient::get("http://127.0.0.1:5801/shout")
+            .send(Router::with_path("shout").goal(shout))
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
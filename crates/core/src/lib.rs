@@ -22,8 +22,13 @@ mod cfg;
 
 pub mod catcher;
 pub mod conn;
+cfg_feature! {
+    #![feature = "config"]
+    pub mod config;
+}
 mod depot;
 mod error;
+pub mod error_mapper;
 pub mod extract;
 pub mod fs;
 pub mod handler;
@@ -51,7 +56,7 @@ cfg_feature! {
 }
 
 pub use self::conn::Listener;
-pub use self::depot::Depot;
+pub use self::depot::{Depot, DepotSnapshot, DepotSnapshotBuilder, TypedKey};
 pub use self::error::{BoxedError, Error};
 pub use self::extract::Extractible;
 pub use self::handler::Handler;
@@ -0,0 +1,173 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+
+use super::{join_matched_paths, PathState, Router};
+use crate::{Handler, Request};
+
+/// The ordered handlers which handle requests of a route, it is used to debug how middlewares are composed.
+///
+/// Hoops are listed in the order they are called, the hoops of parent routers come first. Code after
+/// `ctrl.call_next` in a hoop runs in the reverse order, so a hoop listed later changes the response before the
+/// ones listed earlier.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "hello"
+/// }
+///
+/// let service = Service::new(Router::new().push(Router::with_path("hello").get(hello)));
+/// for chain in service.hoop_chains() {
+///     println!("{chain}");
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct HoopChain {
+    /// The path template of the route, such as `/users/<id>`.
+    pub path: String,
+    /// Filters of the route other than path filters, such as `method:GET`.
+    pub filters: Vec<String>,
+    /// Type names of hoops in the order they are called.
+    pub hoops: Vec<&'static str>,
+    /// Type name of the goal.
+    pub goal: &'static str,
+}
+
+impl HoopChain {
+    fn new(path: String, filters: Vec<String>, hoops: &[Arc<dyn Handler>], goal: &Arc<dyn Handler>) -> Self {
+        Self {
+            path,
+            filters,
+            hoops: hoops.iter().map(|hoop| hoop.type_name()).collect(),
+            goal: goal.type_name(),
+        }
+    }
+}
+
+/// Returns the name of the crate which defines the type, such as `salvo_cors` for `salvo_cors::CorsHandler`.
+fn crate_name(type_name: &str) -> &str {
+    type_name.split("::").next().unwrap_or(type_name)
+}
+
+impl Display for HoopChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if !self.filters.is_empty() {
+            write!(f, " [{}]", self.filters.join(", "))?;
+        }
+        writeln!(f)?;
+        for (i, hoop) in self.hoops.iter().enumerate() {
+            writeln!(f, "  {}. {} ({})", i + 1, hoop, crate_name(hoop))?;
+        }
+        write!(f, "  -> {} ({})", self.goal, crate_name(self.goal))
+    }
+}
+
+impl Router {
+    /// Returns the hoop chains of all routes which have a goal, in the order they are detected.
+    pub fn hoop_chains(&self) -> Vec<HoopChain> {
+        let mut chains = Vec::new();
+        self.collect_hoop_chains(&mut Vec::new(), &mut Vec::new(), &mut Vec::new(), &mut chains);
+        chains
+    }
+    fn collect_hoop_chains(
+        &self,
+        paths: &mut Vec<String>,
+        filters: &mut Vec<String>,
+        hoops: &mut Vec<Arc<dyn Handler>>,
+        chains: &mut Vec<HoopChain>,
+    ) {
+        let (paths_len, filters_len, hoops_len) = (paths.len(), filters.len(), hoops.len());
+        for filter in &self.filters {
+            let info = format!("{filter:?}");
+            match info.strip_prefix("path:") {
+                Some(path) => paths.push(path.to_owned()),
+                None => filters.push(info),
+            }
+        }
+        hoops.extend(self.hoops.iter().cloned());
+        for router in &self.routers {
+            router.collect_hoop_chains(paths, filters, hoops, chains);
+        }
+        if let Some(goal) = &self.goal {
+            chains.push(HoopChain::new(join_matched_paths(paths), filters.clone(), hoops, goal));
+        }
+        paths.truncate(paths_len);
+        filters.truncate(filters_len);
+        hoops.truncate(hoops_len);
+    }
+
+    /// Returns the hoop chain of the route which handles the request, or `None` if no route is matched.
+    ///
+    /// Only filters of the router tree are checked, the request is not handled.
+    pub fn hoop_chain(&self, req: &mut Request) -> Option<HoopChain> {
+        let mut path_state = PathState::new(req.uri().path());
+        let matched = self.detect(req, &mut path_state)?;
+        Some(HoopChain::new(
+            path_state.matched_path(),
+            vec![format!("method:{:?}", req.method())],
+            &matched.hoops,
+            &matched.goal,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    #[handler]
+    async fn auth() {}
+    #[handler]
+    async fn cors() {}
+    #[handler]
+    async fn show_user() {}
+    #[handler]
+    async fn list_users() {}
+
+    fn router() -> Router {
+        Router::new().hoop(cors).push(
+            Router::with_path("users")
+                .get(list_users)
+                .push(Router::with_path("<id>").hoop(auth).get(show_user)),
+        )
+    }
+
+    #[test]
+    fn test_hoop_chains() {
+        let chains = router().hoop_chains();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].path, "/users");
+        assert_eq!(chains[0].hoops.len(), 1);
+        assert_eq!(chains[1].path, "/users/<id>");
+        assert_eq!(chains[1].filters, vec!["method:GET".to_owned()]);
+        assert_eq!(chains[1].hoops.len(), 2);
+        assert!(chains[1].hoops[0].ends_with("::cors"));
+        assert!(chains[1].hoops[1].ends_with("::auth"));
+        assert!(chains[1].goal.ends_with("::show_user"));
+
+        let dump = chains[1].to_string();
+        assert!(dump.starts_with("/users/<id> [method:GET]\n  1. "));
+        assert!(dump.contains("(salvo_core)"));
+        assert_eq!(crate_name("salvo_cors::CorsHandler"), "salvo_cors");
+    }
+
+    #[test]
+    fn test_hoop_chain_of_request() {
+        let router = router();
+        let mut req = TestClient::get("http://127.0.0.1:5801/users/12").build();
+        let chain = router.hoop_chain(&mut req).unwrap();
+        assert_eq!(chain.path, "/users/<id>");
+        assert_eq!(chain.hoops.len(), 2);
+
+        let mut req = TestClient::post("http://127.0.0.1:5801/users/12").build();
+        assert!(router.hoop_chain(&mut req).is_none());
+    }
+}
@@ -139,7 +139,7 @@ impl RegexWispBuilder {
 impl WispBuilder for RegexWispBuilder {
     fn build(&self, name: String, _sign: String, _args: Vec<String>) -> Result<WispKind, String> {
         Ok(RegexWisp {
-            name,
+            name: name.into(),
             regex: self.0.clone(),
         }
         .into())
@@ -162,7 +162,7 @@ impl WispBuilder for CharsWispBuilder {
     fn build(&self, name: String, _sign: String, args: Vec<String>) -> Result<WispKind, String> {
         if args.is_empty() {
             return Ok(CharsWisp {
-                name,
+                name: name.into(),
                 checker: self.0.clone(),
                 min_width: 1,
                 max_width: None,
@@ -214,7 +214,7 @@ impl WispBuilder for CharsWispBuilder {
             }
         };
         Ok(CharsWisp {
-            name,
+            name: name.into(),
             checker: self.0.clone(),
             min_width,
             max_width,
@@ -225,7 +225,7 @@ impl WispBuilder for CharsWispBuilder {
 
 /// Chars wisp match chars in url segement.
 pub struct CharsWisp {
-    name: String,
+    name: Arc<str>,
     checker: Arc<dyn Fn(char) -> bool + Send + Sync + 'static>,
     min_width: usize,
     max_width: Option<usize>,
@@ -242,44 +242,28 @@ impl fmt::Debug for CharsWisp {
 }
 impl PathWisp for CharsWisp {
     fn detect<'a>(&self, state: &mut PathState) -> bool {
-        let picked = state.pick();
-        if picked.is_none() {
-            return false;
-        }
-        let picked = picked.unwrap();
-        if let Some(max_width) = self.max_width {
-            let mut chars = Vec::with_capacity(max_width);
-            for ch in picked.chars() {
-                if (self.checker)(ch) {
-                    chars.push(ch);
-                }
-                if chars.len() == max_width {
-                    state.forward(max_width);
-                    state.params.insert(self.name.clone(), chars.into_iter().collect());
-                    return true;
-                }
+        let picked = match state.pick() {
+            Some(picked) => picked,
+            None => return false,
+        };
+        // Collect matched chars into the value directly, without a temporary `Vec<char>`.
+        let mut value = String::new();
+        let mut width = 0;
+        for ch in picked.chars() {
+            if (self.checker)(ch) {
+                value.push(ch);
+                width += 1;
             }
-            if chars.len() >= self.min_width {
-                state.forward(chars.len());
-                state.params.insert(self.name.clone(), chars.into_iter().collect());
-                true
-            } else {
-                false
+            if Some(width) == self.max_width {
+                break;
             }
+        }
+        if Some(width) == self.max_width || width >= self.min_width {
+            state.forward(width);
+            state.params.insert(self.name.clone(), value);
+            true
         } else {
-            let mut chars = Vec::with_capacity(16);
-            for ch in picked.chars() {
-                if (self.checker)(ch) {
-                    chars.push(ch);
-                }
-            }
-            if chars.len() >= self.min_width {
-                state.forward(chars.len());
-                state.params.insert(self.name.clone(), chars.into_iter().collect());
-                true
-            } else {
-                false
-            }
+            false
         }
     }
 }
@@ -384,7 +368,7 @@ impl PathWisp for CombWisp {
 
 /// Named wisp match part in url segment and give it a name.
 #[derive(Debug, Eq, PartialEq)]
-pub struct NamedWisp(pub String);
+pub struct NamedWisp(pub Arc<str>);
 impl PathWisp for NamedWisp {
     #[inline]
     fn detect<'a>(&self, state: &mut PathState) -> bool {
@@ -394,7 +378,7 @@ impl PathWisp for NamedWisp {
                 return false;
             }
             if !rest.is_empty() || !self.0.starts_with("*+") {
-                let rest = rest.to_string();
+                let rest = rest.into_owned();
                 state.params.insert(self.0.clone(), rest);
                 state.cursor.0 = state.parts.len();
                 true
@@ -419,14 +403,17 @@ impl PathWisp for NamedWisp {
 #[non_exhaustive]
 pub struct RegexWisp {
     /// The name of the wisp.
-    pub name: String,
+    pub name: Arc<str>,
     /// The regex pattern.
     pub regex: Regex,
 }
 impl RegexWisp {
     #[inline]
     fn new(name: String, regex: Regex) -> RegexWisp {
-        RegexWisp { name, regex }
+        RegexWisp {
+            name: name.into(),
+            regex,
+        }
     }
 }
 impl PartialEq for RegexWisp {
@@ -444,7 +431,7 @@ impl PathWisp for RegexWisp {
                 return false;
             }
             if !rest.is_empty() || !self.name.starts_with("*+") {
-                let cap = self.regex.captures(&rest).and_then(|caps| caps.get(0));
+                let cap = self.regex.find(&rest);
                 if let Some(cap) = cap {
                     let cap = cap.as_str().to_owned();
                     state.forward(cap.len());
@@ -462,7 +449,7 @@ impl PathWisp for RegexWisp {
                 return false;
             }
             let picked = picked.unwrap();
-            let cap = self.regex.captures(picked).and_then(|caps| caps.get(0));
+            let cap = self.regex.find(picked);
             if let Some(cap) = cap {
                 let cap = cap.as_str().to_owned();
                 state.forward(cap.len());
@@ -709,7 +696,7 @@ impl PathParser {
                         wisps.push(RegexWisp::new(name, regex).into());
                     }
                 } else if ch == '>' {
-                    wisps.push(NamedWisp(name).into());
+                    wisps.push(NamedWisp(name.into()).into());
                 }
                 if let Some(c) = self.curr() {
                     if c != '>' {
@@ -788,9 +775,9 @@ impl PathParser {
         }
         for (index, wisp) in wisps.iter().enumerate() {
             let name = match wisp {
-                WispKind::Named(wisp) => Some(&wisp.0),
-                WispKind::Chars(wisp) => Some(&wisp.name),
-                WispKind::Regex(wisp) => Some(&wisp.name),
+                WispKind::Named(wisp) => Some(&*wisp.0),
+                WispKind::Chars(wisp) => Some(&*wisp.name),
+                WispKind::Regex(wisp) => Some(&*wisp.name),
                 WispKind::Comb(comb) => {
                     comb.validate()?;
                     self.validate(&comb.0, all_names)?;
@@ -814,7 +801,7 @@ impl PathParser {
                         self.path.iter().collect::<String>()
                     ));
                 }
-                all_names.insert(name.clone());
+                all_names.insert(name.to_owned());
             }
         }
         let wild_names = all_names
@@ -843,7 +830,7 @@ impl PathParser {
 
 /// Filter request by it's path information.
 pub struct PathFilter {
-    raw_value: String,
+    raw_value: Arc<str>,
     path_wisps: Vec<WispKind>,
 }
 
@@ -855,7 +842,12 @@ impl fmt::Debug for PathFilter {
 impl Filter for PathFilter {
     #[inline]
     fn filter(&self, _req: &mut Request, state: &mut PathState) -> bool {
-        self.detect(state)
+        if self.detect(state) {
+            state.matched_paths.push(self.raw_value.clone());
+            true
+        } else {
+            false
+        }
     }
 }
 impl PathFilter {
@@ -875,7 +867,10 @@ impl PathFilter {
                 panic!("{}, raw_value: {}", e, raw_value);
             }
         };
-        PathFilter { raw_value, path_wisps }
+        PathFilter {
+            raw_value: raw_value.into(),
+            path_wisps,
+        }
     }
     /// Register new path wisp builder.
     #[inline]
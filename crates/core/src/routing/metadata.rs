@@ -0,0 +1,112 @@
+use std::any::Any;
+use std::fmt::{self, Formatter};
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+
+type Value = Arc<dyn Any + Send + Sync>;
+
+/// Typed values attached to routers by [`Router::meta`](super::Router::meta).
+///
+/// When a request is matched, metadata of all routers on the matched path are collected from the outermost
+/// router to the innermost router, and can be got by [`Request::route_metadata`](crate::Request::route_metadata).
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Role(&'static str);
+///
+/// #[handler]
+/// async fn hello(req: &mut Request) -> String {
+///     format!("{:?}", req.route_metadata().get::<Role>())
+/// }
+///
+/// let router = Router::with_path("admin").meta(Role("admin")).get(hello);
+/// ```
+#[derive(Clone, Default)]
+pub struct RouteMetadata {
+    // Values of every router on the matched path, from the innermost router to the outermost router. Values of a
+    // router are shared with the matched requests, so collecting them does not allocate for common nesting depth.
+    layers: SmallVec<[Arc<Vec<Value>>; 4]>,
+}
+impl RouteMetadata {
+    /// Create an empty `RouteMetadata`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a value.
+    #[inline]
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        let value: Value = Arc::new(value);
+        match self.layers.first_mut() {
+            Some(layer) => Arc::make_mut(layer).push(value),
+            None => self.layers.push(Arc::new(vec![value])),
+        }
+    }
+    /// Get the innermost value of type `T`.
+    #[inline]
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.layers
+            .iter()
+            .find_map(|layer| layer.iter().rev().find_map(|value| value.downcast_ref()))
+    }
+    /// Get all values of type `T`, from the outermost router to the innermost router.
+    #[inline]
+    pub fn get_all<T: Any + Send + Sync>(&self) -> impl Iterator<Item = &T> {
+        self.layers
+            .iter()
+            .rev()
+            .flat_map(|layer| layer.iter())
+            .filter_map(|value| value.downcast_ref())
+    }
+    /// Returns `true` if there is a value of type `T`.
+    #[inline]
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+    /// Returns the count of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.layers.iter().map(|layer| layer.len()).sum()
+    }
+    /// Returns `true` if there is no value.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|layer| layer.is_empty())
+    }
+    /// Append the values of outer router, they are shared instead of copied.
+    #[inline]
+    pub(crate) fn push_outer(&mut self, outer: &RouteMetadata) {
+        self.layers.extend(outer.layers.iter().cloned());
+    }
+}
+impl fmt::Debug for RouteMetadata {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteMetadata").field("len", &self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_metadata() {
+        let mut outer = RouteMetadata::new();
+        outer.insert("outer");
+        outer.insert(1u8);
+        let mut metadata = RouteMetadata::new();
+        metadata.insert("inner");
+        metadata.push_outer(&outer);
+        assert_eq!(metadata.len(), 3);
+        assert_eq!(metadata.get::<&str>(), Some(&"inner"));
+        assert_eq!(metadata.get_all::<&str>().collect::<Vec<_>>(), vec![&"outer", &"inner"]);
+        assert!(metadata.contains::<u8>());
+        assert!(!metadata.contains::<u16>());
+    }
+}
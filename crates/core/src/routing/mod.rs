@@ -1,29 +1,50 @@
 //! Routing and filters
 //! Router can route http requests to different handlers.
 
+mod chain;
 pub mod filters;
+mod metadata;
+mod params;
+mod rewriter;
 mod router;
+pub use chain::HoopChain;
 pub use filters::*;
+pub use metadata::RouteMetadata;
+pub use params::PathParams;
+pub use rewriter::Rewriter;
 pub use router::{DetectMatched, Router};
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use indexmap::IndexMap;
+use http::header::ALT_SVC;
+use http::uri::{PathAndQuery, Uri};
+use smallvec::SmallVec;
 
-use crate::http::{Request, Response};
-use crate::{Depot, Handler};
+use crate::http::{Request, ResBody, Response, StatusCode, StatusError};
+use crate::service::enter_route;
+use crate::{Depot, Error, Handler};
+
+/// Max times a request can be rerouted by [`FlowCtrl::reroute`], used to avoid infinite loop.
+const MAX_REROUTES: usize = 10;
+/// Max times the chain can be restarted by [`FlowCtrl::restart`] and [`FlowCtrl::jump_to`], used to avoid
+/// infinite loop.
+const MAX_RESTARTS: usize = 10;
 
-#[doc(hidden)]
-pub type PathParams = IndexMap<String, String>;
 #[doc(hidden)]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PathState {
-    pub(crate) parts: Vec<String>,
+    /// Decoded url path segments. The list is inline for most paths, but every segment is still an owned
+    /// `String`, because comb wisps rewrite the segment they are matching.
+    pub(crate) parts: SmallVec<[String; 8]>,
     /// (row, col), row is the index of parts, col is the index of char in the part.
     pub(crate) cursor: (usize, usize),
     pub(crate) params: PathParams,
     pub(crate) end_slash: bool, // For rest match, we want include the last slash.
+    /// Raw values of matched path filters, they are shared with the filters so matching does not copy them.
+    pub(crate) matched_paths: SmallVec<[Arc<str>; 8]>,
 }
 impl PathState {
     /// Create new `PathState`.
@@ -41,15 +62,22 @@ impl PathState {
                     None
                 }
             })
-            .collect::<Vec<_>>();
+            .collect();
         PathState {
             parts,
             cursor: (0, 0),
             params: PathParams::new(),
             end_slash,
+            matched_paths: SmallVec::new(),
         }
     }
 
+    /// Get the route path template joined by matched path filters, such as `/users/<id>`.
+    #[inline]
+    pub fn matched_path(&self) -> String {
+        join_matched_paths(&self.matched_paths)
+    }
+
     #[inline]
     pub fn pick(&self) -> Option<&str> {
         match self.parts.get(self.cursor.0) {
@@ -107,6 +135,21 @@ impl PathState {
     }
 }
 
+fn join_matched_paths<S: AsRef<str>>(matched_paths: &[S]) -> String {
+    let mut path = String::new();
+    for part in matched_paths {
+        let part = part.as_ref().trim_matches('/');
+        if !part.is_empty() {
+            path.push('/');
+            path.push_str(part);
+        }
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+    path
+}
+
 #[inline]
 fn decode_url_path_safely(path: &str) -> String {
     percent_encoding::percent_decode_str(path)
@@ -132,13 +175,22 @@ pub enum FlowCtrlStage {
 /// **NOTE**: When `Response`'s status code is set, and the status code `is_success()` is returns false,
 /// all rest handlers will skipped.
 ///
+/// Handlers can also restart the chain, jump back to a named checkpoint, or reroute the request to another
+/// path without issuing an external redirect.
+///
 /// [`Router`]: crate::routing::Router
 #[derive(Default)]
 pub struct FlowCtrl {
     catching: Option<bool>,
     is_ceased: bool,
+    reroutes: usize,
+    restarts: usize,
     cursor: usize,
+    checkpoints: HashMap<String, usize>,
+    goal_elapsed: Option<Duration>,
     pub(crate) handlers: Vec<Arc<dyn Handler>>,
+    /// Router used by [`FlowCtrl::reroute`], it is only set for the handlers called by service.
+    pub(crate) router: Option<Arc<Router>>,
 }
 
 impl FlowCtrl {
@@ -148,8 +200,13 @@ impl FlowCtrl {
         FlowCtrl {
             catching: None,
             is_ceased: false,
+            reroutes: 0,
+            restarts: 0,
             cursor: 0,
+            checkpoints: HashMap::new(),
+            goal_elapsed: None,
             handlers,
+            router: None,
         }
     }
     /// Has next handler.
@@ -176,7 +233,12 @@ impl FlowCtrl {
         } else {
             while let Some(h) = handler.take() {
                 self.cursor += 1;
+                // The goal is the last handler.
+                let goal_started = (self.cursor == self.handlers.len()).then(Instant::now);
                 h.handle(req, depot, res, self).await;
+                if let Some(started) = goal_started {
+                    self.goal_elapsed = Some(self.goal_elapsed.unwrap_or_default() + started.elapsed());
+                }
                 if !self.catching.unwrap_or_default() && res.is_stamped() {
                     self.skip_rest();
                     return true;
@@ -188,12 +250,136 @@ impl FlowCtrl {
         }
     }
 
+    /// Get the time spent by the goal handler, returns `None` if the goal has not been called.
+    ///
+    /// Middlewares use it to tell the time spent by the goal from the time spent by other middlewares.
+    #[inline]
+    pub fn goal_elapsed(&self) -> Option<Duration> {
+        self.goal_elapsed
+    }
+
     /// Skip all reset handlers.
     #[inline]
     pub fn skip_rest(&mut self) {
         self.cursor = self.handlers.len()
     }
 
+    /// Restart the chain, all handlers will be called again from the first one when current handler returns.
+    ///
+    /// Handlers which are still running are called again too, such as a hoop which calls this after
+    /// [`FlowCtrl::call_next`]. The chain can be restarted 10 times at most, including jumps by
+    /// [`FlowCtrl::jump_to`], returns `false` if the limit is reached.
+    #[inline]
+    pub fn restart(&mut self) -> bool {
+        if self.restarts >= MAX_RESTARTS {
+            return false;
+        }
+        self.restarts += 1;
+        self.cursor = 0;
+        true
+    }
+
+    /// Record a checkpoint with the given name at the next handler, [`FlowCtrl::jump_to`] can be used later to
+    /// call handlers from this position again.
+    #[inline]
+    pub fn checkpoint(&mut self, name: impl Into<String>) {
+        self.checkpoints.insert(name.into(), self.cursor);
+    }
+
+    /// Jump to the checkpoint with the given name, handlers after it will be called again when current handler
+    /// returns. Returns `false` if the checkpoint is not found or the limit of [`FlowCtrl::restart`] is reached.
+    #[inline]
+    pub fn jump_to(&mut self, name: &str) -> bool {
+        match self.checkpoints.get(name) {
+            Some(cursor) if self.restarts < MAX_RESTARTS => {
+                self.restarts += 1;
+                self.cursor = *cursor;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Rewrite the path of request and route it again, no external redirect is sent to the client.
+    ///
+    /// The response is reset, rest handlers are skipped and handlers of the new matched router will be called when
+    /// current handler returns. Hoops which have been called are not called again, so hoops shared by both
+    /// routers, such as hoops of the root router, are only called once. If no router matches the new path, the
+    /// response is `404 Not Found`. Automatic `HEAD` and `OPTIONS` handling of [`Service`](crate::Service) is
+    /// not applied to rerouted requests.
+    ///
+    /// The query of the original request is kept if `path` has no query.
+    ///
+    /// It returns an error if the path is invalid, the response has been flushed, or it is not called in the
+    /// handlers of service, such as in a [`Catcher`](crate::catcher::Catcher). A request can be rerouted 10 times
+    /// at most, the response is `500 Internal Server Error` after that.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn rewrite(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+    ///     if let Some(rest) = req.uri().path().strip_prefix("/old/") {
+    ///         let path = format!("/new/{rest}");
+    ///         ctrl.reroute(req, res, &path).unwrap();
+    ///     }
+    /// }
+    /// let router = Router::new()
+    ///     .hoop(rewrite)
+    ///     .push(Router::with_path("new/<id>").get(salvo_core::handler::empty()));
+    /// ```
+    pub fn reroute(&mut self, req: &mut Request, res: &mut Response, path: &str) -> crate::Result<()> {
+        let Some(router) = self.router.clone() else {
+            return Err(Error::other("reroute is only supported in the handlers of service"));
+        };
+        if res.is_flushed() {
+            return Err(Error::other("response has been flushed"));
+        }
+        let path_and_query = match (path.contains('?'), req.uri().query()) {
+            (false, Some(query)) => format!("{path}?{query}").parse::<PathAndQuery>(),
+            _ => path.parse::<PathAndQuery>(),
+        }
+        .map_err(Error::other)?;
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = Some(path_and_query);
+        req.set_uri(Uri::from_parts(parts).map_err(Error::other)?);
+
+        reset_response(req, res);
+        self.handlers.truncate(self.cursor);
+        self.checkpoints.retain(|_, cursor| *cursor <= self.handlers.len());
+        self.reroutes += 1;
+        if self.reroutes > MAX_REROUTES {
+            tracing::error!(uri = ?req.uri(), "too many reroutes");
+            res.render(StatusError::internal_server_error().brief("Too many reroutes."));
+            return Err(Error::other("too many reroutes"));
+        }
+
+        let mut path_state = PathState::new(req.uri().path());
+        let Some(dm) = router.detect(req, &mut path_state) else {
+            res.status_code(StatusCode::NOT_FOUND);
+            return Ok(());
+        };
+        let hoops_len = dm.hoops.len();
+        let handlers = enter_route(req, res, path_state, dm);
+        // Skip hoops which have been called, the goal is always called.
+        let called = self
+            .handlers
+            .iter()
+            .zip(&handlers[..hoops_len])
+            .take_while(|(called, hoop)| Arc::as_ptr(called) as *const () == Arc::as_ptr(hoop) as *const ())
+            .count();
+        self.handlers.extend(handlers.into_iter().skip(called));
+        Ok(())
+    }
+
+    /// Check is the request rerouted by [`FlowCtrl::reroute`].
+    #[inline]
+    pub fn is_rerouted(&self) -> bool {
+        self.reroutes > 0
+    }
+
     /// Check is `FlowCtrl` ceased.
     #[inline]
     pub fn is_ceased(&self) -> bool {
@@ -210,6 +396,23 @@ impl FlowCtrl {
     }
 }
 
+/// Reset the response written by handlers for a rerouted request, the `Alt-Svc` header set by service is kept.
+fn reset_response(req: &Request, res: &mut Response) {
+    res.status_code = None;
+    res.body = ResBody::None;
+    let alt_svc = res.headers.remove(ALT_SVC);
+    res.headers.clear();
+    if let Some(alt_svc) = alt_svc {
+        res.headers.insert(ALT_SVC, alt_svc);
+    }
+    #[cfg(feature = "cookie")]
+    {
+        res.cookies = req.cookies.clone();
+    }
+    #[cfg(not(feature = "cookie"))]
+    let _ = req;
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -242,4 +445,116 @@ mod tests {
         assert!(access(&service, "127.0.0.1").await.contains("404: Not Found"));
         assert_eq!(access(&service, "localhost").await, "Hello World");
     }
+
+    #[tokio::test]
+    async fn test_reroute() {
+        #[handler]
+        async fn count(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+            ctrl.call_next(req, depot, res).await;
+            let times = depot.get::<u32>("count").copied().unwrap_or_default() + 1;
+            depot.insert("count", times);
+            res.headers_mut().insert("x-count", times.into());
+        }
+        #[handler]
+        async fn mark(res: &mut Response) {
+            res.headers_mut().insert("x-old", "true".parse().unwrap());
+            res.render("stale");
+        }
+        #[handler]
+        async fn rewrite(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+            if let Some(rest) = req.uri().path().strip_prefix("/old/") {
+                let path = format!("/new/{rest}");
+                ctrl.reroute(req, res, &path).unwrap();
+            }
+        }
+        #[handler]
+        async fn show(req: &mut Request) -> String {
+            format!(
+                "{}:{}",
+                req.param::<String>("id").unwrap_or_default(),
+                req.query::<String>("q").unwrap_or_default()
+            )
+        }
+        #[handler]
+        async fn looping(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+            ctrl.reroute(req, res, "/loop").ok();
+        }
+        let router = Router::new()
+            .hoop(count)
+            .push(Router::with_path("old/<id>").hoop(mark).hoop(rewrite).get(show))
+            .push(Router::with_path("new/<id>").get(show))
+            .push(Router::with_path("loop").get(looping));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/old/7?q=x").send(&service).await;
+        assert_eq!(res.headers().get("x-count").unwrap(), "1");
+        assert!(res.headers().get("x-old").is_none());
+        assert_eq!(res.take_string().await.unwrap(), "7:x");
+
+        let res = TestClient::get("http://127.0.0.1:5801/loop").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+        assert_eq!(res.headers().get("x-count").unwrap(), "1");
+    }
+
+    #[tokio::test]
+    async fn test_restart_limit() {
+        #[handler]
+        async fn count(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+            let times = depot.get::<u32>("count").copied().unwrap_or_default() + 1;
+            depot.insert("count", times);
+            ctrl.call_next(req, depot, res).await;
+            ctrl.restart();
+        }
+        #[handler]
+        async fn hello(depot: &mut Depot) -> String {
+            depot.get::<u32>("count").unwrap().to_string()
+        }
+        let router = Router::new().hoop(count).get(hello);
+        let content = TestClient::get("http://127.0.0.1:5801")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "1234567891011");
+    }
+
+    #[tokio::test]
+    async fn test_restart_and_jump() {
+        #[handler]
+        async fn count(depot: &mut Depot, ctrl: &mut FlowCtrl) {
+            let times = depot.get::<u32>("count").copied().unwrap_or_default() + 1;
+            depot.insert("count", times);
+            if times < 3 {
+                ctrl.restart();
+            } else {
+                ctrl.checkpoint("retry");
+            }
+        }
+        #[handler]
+        async fn retry(depot: &mut Depot, ctrl: &mut FlowCtrl) {
+            let retries = depot.get::<u32>("retries").copied().unwrap_or_default() + 1;
+            depot.insert("retries", retries);
+            if retries < 2 {
+                assert!(ctrl.jump_to("retry"));
+            }
+            assert!(!ctrl.jump_to("missing"));
+        }
+        #[handler]
+        async fn hello(depot: &mut Depot) -> String {
+            format!(
+                "{} {}",
+                depot.get::<u32>("count").unwrap(),
+                depot.get::<u32>("retries").unwrap()
+            )
+        }
+        let router = Router::new().hoop(count).hoop(retry).get(hello);
+        let content = TestClient::get("http://127.0.0.1:5801")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "3 2");
+    }
 }
@@ -0,0 +1,174 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Index;
+use std::slice;
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+
+/// The params captured from the url path by path filters, such as `id` of `/users/<id>`.
+///
+/// Routes seldom have more than a few params, so they are stored inline in insertion order and looked up by a linear
+/// scan, which is faster than hashing for a few entries. Param names are shared with the path filters, so only the
+/// values are allocated when a request is matched.
+///
+/// It replaces the `IndexMap<String, String>` returned by [`Request::params`](crate::Request::params) before, the
+/// names are `&str` instead of `&String` when iterating.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct PathParams {
+    inner: SmallVec<[(Arc<str>, String); 4]>,
+}
+impl Debug for PathParams {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+impl PathParams {
+    /// Create a new empty `PathParams`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of params.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+    /// Returns `true` if there is no param.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+    /// Get the value of the param.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.inner.iter().find(|(n, _)| &**n == name).map(|(_, v)| v)
+    }
+    /// Get the mutable value of the param.
+    #[inline]
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut String> {
+        self.inner.iter_mut().find(|(n, _)| &**n == name).map(|(_, v)| v)
+    }
+    /// Returns `true` if the param exists.
+    #[inline]
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.inner.iter().any(|(n, _)| &**n == name)
+    }
+    /// Insert a param, the old value is replaced and returned if the param exists, and its position is kept.
+    #[inline]
+    pub fn insert(&mut self, name: impl Into<Arc<str>>, value: String) -> Option<String> {
+        let name = name.into();
+        match self.get_mut(&name) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.inner.push((name, value));
+                None
+            }
+        }
+    }
+    /// Remove a param and return its value, the order of other params is kept.
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.inner.iter().position(|(n, _)| &**n == name)?;
+        Some(self.inner.remove(index).1)
+    }
+    /// Remove all params.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+    /// Iterate the params in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.inner.iter())
+    }
+    /// Iterate the names of the params in insertion order.
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.inner.iter().map(|(n, _)| &**n)
+    }
+    /// Iterate the values of the params in insertion order.
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &String> {
+        self.inner.iter().map(|(_, v)| v)
+    }
+}
+impl Index<&str> for PathParams {
+    type Output = String;
+
+    /// Panics if the param does not exist.
+    #[inline]
+    fn index(&self, name: &str) -> &Self::Output {
+        self.get(name).expect("path param not found")
+    }
+}
+impl<'a> IntoIterator for &'a PathParams {
+    type Item = (&'a str, &'a String);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<N> FromIterator<(N, String)> for PathParams
+where
+    N: Into<Arc<str>>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (N, String)>>(iter: I) -> Self {
+        let mut params = PathParams::new();
+        for (name, value) in iter {
+            params.insert(name, value);
+        }
+        params
+    }
+}
+
+/// Iterator of [`PathParams`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a>(slice::Iter<'a, (Arc<str>, String)>);
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a String);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(n, v)| (&**n, v))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(n, v)| (&**n, v))
+    }
+}
+impl ExactSizeIterator for Iter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_params() {
+        let mut params = PathParams::new();
+        assert!(params.is_empty());
+        assert_eq!(params.insert("id", "1".into()), None);
+        assert_eq!(params.insert("name", "salvo".into()), None);
+        assert_eq!(params.insert("id", "2".into()), Some("1".into()));
+        assert_eq!(params.len(), 2);
+        assert_eq!(params["id"], "2");
+        assert_eq!(params.get("name").map(|s| &**s), Some("salvo"));
+        assert!(params.get("none").is_none());
+        assert_eq!(
+            params.iter().map(|(n, v)| format!("{n}={v}")).collect::<Vec<_>>(),
+            vec!["id=2", "name=salvo"]
+        );
+        assert_eq!(params.remove("id"), Some("2".into()));
+        assert_eq!(params.keys().collect::<Vec<_>>(), vec!["name"]);
+        assert!(!params.inner.spilled());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::http::Request;
+
+/// `Rewriter` changes requests before they are routed, it is added to [`Service`] by [`Service::rewriter`].
+///
+/// Hoops are only called for requests which match a route, so a path which only exists after it is rewritten,
+/// such as a path with a prefix added by an ingress, can not be changed by a hoop. Rewriters are called once for
+/// every request, before the router detects the route.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "hello"
+/// }
+///
+/// let service = Service::new(Router::with_path("hello").get(hello)).rewriter(|req: &mut Request| {
+///     if let Some(path) = req.uri().path().strip_prefix("/api") {
+///         if let Ok(uri) = path.parse() {
+///             req.set_uri(uri);
+///         }
+///     }
+/// });
+/// ```
+///
+/// [`Service`]: crate::Service
+/// [`Service::rewriter`]: crate::Service::rewriter
+pub trait Rewriter: Send + Sync + 'static {
+    /// Rewrite the request.
+    fn rewrite(&self, req: &mut Request);
+}
+
+impl<F> Rewriter for F
+where
+    F: Fn(&mut Request) + Send + Sync + 'static,
+{
+    #[inline]
+    fn rewrite(&self, req: &mut Request) {
+        (self)(req)
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt::{self, Formatter};
 use std::sync::Arc;
 
 use super::filters;
-use super::{Filter, FnFilter, PathFilter, PathState};
+use super::{Filter, FnFilter, PathFilter, PathState, RouteMetadata};
 use crate::handler::{Handler, WhenHoop};
 use crate::http::uri::Scheme;
 use crate::{Depot, Request};
@@ -93,11 +93,13 @@ pub struct Router {
     pub hoops: Vec<Arc<dyn Handler>>,
     /// The final handler to handle request of current router.
     pub goal: Option<Arc<dyn Handler>>,
+    metadata: RouteMetadata,
 }
 #[doc(hidden)]
 pub struct DetectMatched {
     pub hoops: Vec<Arc<dyn Handler>>,
     pub goal: Arc<dyn Handler>,
+    pub metadata: RouteMetadata,
 }
 
 impl Default for Router {
@@ -116,6 +118,7 @@ impl Router {
             filters: Vec::new(),
             hoops: Vec::new(),
             goal: None,
+            metadata: RouteMetadata::new(),
         }
     }
 
@@ -141,6 +144,17 @@ impl Router {
         &mut self.hoops
     }
 
+    /// Get current router's metadata reference.
+    #[inline]
+    pub fn metadata(&self) -> &RouteMetadata {
+        &self.metadata
+    }
+    /// Get current router's metadata mutable reference.
+    #[inline]
+    pub fn metadata_mut(&mut self) -> &mut RouteMetadata {
+        &mut self.metadata
+    }
+
     /// Get current router's filters reference.
     #[inline]
     pub fn filters(&self) -> &Vec<Box<dyn Filter>> {
@@ -154,6 +168,14 @@ impl Router {
 
     /// Detect current router is matched for current request.
     pub fn detect(&self, req: &mut Request, path_state: &mut PathState) -> Option<DetectMatched> {
+        let matched_len = path_state.matched_paths.len();
+        let matched = self.detect_inner(req, path_state);
+        if matched.is_none() {
+            path_state.matched_paths.truncate(matched_len);
+        }
+        matched
+    }
+    fn detect_inner(&self, req: &mut Request, path_state: &mut PathState) -> Option<DetectMatched> {
         for filter in &self.filters {
             if !filter.filter(req, path_state) {
                 return None;
@@ -163,9 +185,18 @@ impl Router {
             let original_cursor = path_state.cursor;
             for child in &self.routers {
                 if let Some(dm) = child.detect(req, path_state) {
+                    // Most routers have no hoops, reuse the hoops of the child then.
+                    let hoops = if self.hoops.is_empty() {
+                        dm.hoops
+                    } else {
+                        [&self.hoops[..], &dm.hoops[..]].concat()
+                    };
+                    let mut metadata = dm.metadata;
+                    metadata.push_outer(&self.metadata);
                     return Some(DetectMatched {
-                        hoops: [&self.hoops[..], &dm.hoops[..]].concat(),
-                        goal: dm.goal.clone(),
+                        hoops,
+                        goal: dm.goal,
+                        metadata,
                     });
                 } else {
                     path_state.cursor = original_cursor;
@@ -177,6 +208,7 @@ impl Router {
                 return Some(DetectMatched {
                     hoops: self.hoops.clone(),
                     goal,
+                    metadata: self.metadata.clone(),
                 });
             }
         }
@@ -247,6 +279,15 @@ impl Router {
         self
     }
 
+    /// Attach a typed metadata value to current router, it is effective for current router and it's descendants.
+    ///
+    /// Middlewares can read it by [`Request::route_metadata`] after the request is matched.
+    #[inline]
+    pub fn meta<T: std::any::Any + Send + Sync>(mut self, value: T) -> Self {
+        self.metadata.insert(value);
+        self
+    }
+
     /// Create a new router and set path filter.
     ///
     /// # Panics
@@ -691,4 +732,39 @@ mod tests {
         assert!(matched.is_some());
         assert_eq!(path_state.params["p"], "a/b/c");
     }
+
+    #[test]
+    fn test_router_detect_matched_path() {
+        let router = Router::default().push(
+            Router::with_path("users")
+                .push(Router::with_path("<id>/articles").get(fake_handler))
+                .push(Router::with_path("<id>").push(Router::with_path("emails").get(fake_handler))),
+        );
+        let mut req = TestClient::get("http://local.host/users/12/emails").build();
+        let mut path_state = PathState::new(req.uri().path());
+        let matched = router.detect(&mut req, &mut path_state);
+        assert!(matched.is_some());
+        assert_eq!(path_state.matched_path(), "/users/<id>/emails");
+    }
+
+    #[test]
+    fn test_router_detect_metadata() {
+        let router = Router::default().meta("root").push(
+            Router::with_path("users")
+                .meta(1u8)
+                .push(Router::with_path("list").get(fake_handler))
+                .push(Router::with_path("<id>").meta("user").get(fake_handler)),
+        );
+        let mut req = TestClient::get("http://local.host/users/12").build();
+        let mut path_state = PathState::new(req.uri().path());
+        let matched = router.detect(&mut req, &mut path_state).unwrap();
+        assert_eq!(matched.metadata.get::<&str>(), Some(&"user"));
+        assert_eq!(matched.metadata.get_all::<&str>().count(), 2);
+        assert_eq!(matched.metadata.get::<u8>(), Some(&1));
+
+        let mut req = TestClient::get("http://local.host/users/list").build();
+        let mut path_state = PathState::new(req.uri().path());
+        let matched = router.detect(&mut req, &mut path_state).unwrap();
+        assert_eq!(matched.metadata.get::<&str>(), Some(&"root"));
+    }
 }
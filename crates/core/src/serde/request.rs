@@ -2,7 +2,6 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::iter::Iterator;
 
-use indexmap::IndexMap;
 use multimap::MultiMap;
 use serde::de::value::Error as ValError;
 use serde::de::{self, Deserialize, Error as DeError, IntoDeserializer};
@@ -14,6 +13,7 @@ use crate::extract::Metadata;
 use crate::http::form::FormData;
 use crate::http::header::HeaderMap;
 use crate::http::ParseError;
+use crate::routing::PathParams;
 use crate::Request;
 
 use super::{CowValue, VecValue};
@@ -37,7 +37,7 @@ pub(crate) enum Payload<'a> {
 
 #[derive(Debug)]
 pub(crate) struct RequestDeserializer<'de> {
-    params: &'de IndexMap<String, String>,
+    params: &'de PathParams,
     queries: &'de MultiMap<String, String>,
     #[cfg(feature = "cookie")]
     cookies: &'de cookie::CookieJar,
@@ -165,10 +165,10 @@ impl<'de> RequestDeserializer<'de> {
                     return true;
                 }
                 SourceFrom::Param => {
-                    let mut value = self.params.get(&*field_name);
+                    let mut value = self.params.get(&field_name);
                     if value.is_none() {
                         for alias in &field.aliases {
-                            value = self.params.get(*alias);
+                            value = self.params.get(alias);
                             if value.is_some() {
                                 break;
                             }
@@ -513,9 +513,9 @@ mod tests {
             .query("q1", "q1v")
             .query("q2", "23")
             .build();
-        req.params.insert("param1".into(), "param1v".into());
-        req.params.insert("p2".into(), "921".into());
-        req.params.insert("p3".into(), "89785".into());
+        req.params.insert("param1", "param1v".into());
+        req.params.insert("p2", "921".into());
+        req.params.insert("p3", "89785".into());
         let data: RequestData = req.extract().await.unwrap();
         assert_eq!(
             data,
@@ -556,7 +556,7 @@ mod tests {
                 },
             ])
             .build();
-        req.params.insert("p2".into(), "921".into());
+        req.params.insert("p2", "921".into());
         let data: RequestData = req.extract().await.unwrap();
         assert_eq!(
             data,
@@ -589,7 +589,7 @@ mod tests {
         let mut req = TestClient::get("http://127.0.0.1:5800/test/1234/param2v")
             .json(&true)
             .build();
-        req.params.insert("p2".into(), "921".into());
+        req.params.insert("p2", "921".into());
         let data: RequestData = req.extract().await.unwrap();
         assert_eq!(data, RequestData { p2: "921", b: true });
     }
@@ -606,7 +606,7 @@ mod tests {
         let mut req = TestClient::get("http://127.0.0.1:5800/test/1234/param2v")
             .json(&"abcd-good")
             .build();
-        req.params.insert("p2".into(), "921".into());
+        req.params.insert("p2", "921".into());
         let data: RequestData = req.extract().await.unwrap();
         assert_eq!(
             data,
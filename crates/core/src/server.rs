@@ -4,6 +4,7 @@ use std::io::Result as IoResult;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use futures_util::future::BoxFuture;
 #[cfg(feature = "http1")]
 use hyper::server::conn::http1;
 #[cfg(feature = "http2")]
@@ -18,6 +19,9 @@ use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder};
 use crate::http::{HeaderValue, HttpConnection, Version};
 use crate::Service;
 
+type BoxedHook = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+type BoxedTask = Box<dyn FnOnce(CancellationToken) -> BoxFuture<'static, ()> + Send>;
+
 /// HTTP Server
 ///
 /// A `Server` is created to listen on a port, parse HTTP requests, and hand them off to a [`Service`].
@@ -25,6 +29,10 @@ pub struct Server<A> {
     acceptor: A,
     builder: HttpBuilder,
     idle_timeout: Option<Duration>,
+    start_hooks: Vec<BoxedHook>,
+    shutdown_hooks: Vec<BoxedHook>,
+    tasks: Vec<(String, BoxedTask)>,
+    task_timeout: Duration,
 }
 
 impl<A: Acceptor + Send> Server<A> {
@@ -54,6 +62,10 @@ impl<A: Acceptor + Send> Server<A> {
                 quinn: crate::conn::quinn::Builder::new(),
             },
             idle_timeout: None,
+            start_hooks: Vec::new(),
+            shutdown_hooks: Vec::new(),
+            tasks: Vec::new(),
+            task_timeout: Duration::from_secs(10),
         }
     }
 
@@ -95,6 +107,80 @@ impl<A: Acceptor + Send> Server<A> {
         self
     }
 
+    /// Add a hook which is called before the server starts accepting connections.
+    ///
+    /// Hooks are called in the order they are added.
+    #[must_use]
+    pub fn on_start<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.start_hooks.push(Box::new(move || Box::pin(hook())));
+        self
+    }
+
+    /// Add a hook which is called after all connections are closed and all background tasks are finished
+    /// during graceful shutdown.
+    ///
+    /// Hooks are called in the order they are added.
+    #[must_use]
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_hooks.push(Box::new(move || Box::pin(hook())));
+        self
+    }
+
+    /// Add a background task which is spawned after start hooks are called.
+    ///
+    /// The task receives a [`CancellationToken`] which is cancelled when graceful shutdown is initiated, and it
+    /// should finish soon after that. Tasks not finished in [`Server::background_task_timeout`] after all
+    /// connections are closed are aborted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use salvo_core::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+    /// Server::new(acceptor).background_task("cleanup", |token| async move {
+    ///     let mut interval = tokio::time::interval(Duration::from_secs(60));
+    ///     loop {
+    ///         tokio::select! {
+    ///             _ = token.cancelled() => break,
+    ///             _ = interval.tick() => {
+    ///                 // remove expired sessions...
+    ///             }
+    ///         }
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn background_task<F, Fut>(mut self, name: impl Into<String>, task: F) -> Self
+    where
+        F: FnOnce(CancellationToken) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let task: BoxedTask = Box::new(move |token| Box::pin(task(token)));
+        self.tasks.push((name.into(), task));
+        self
+    }
+
+    /// Specify how long to wait for background tasks to finish during graceful shutdown. Default is 10 seconds.
+    #[must_use]
+    pub fn background_task_timeout(mut self, timeout: Duration) -> Self {
+        self.task_timeout = timeout;
+        self
+    }
+
     /// Serve a [`Service`]
     #[inline]
     pub async fn serve<S>(self, service: S)
@@ -171,6 +257,10 @@ impl<A: Acceptor + Send> Server<A> {
             mut acceptor,
             builder,
             idle_timeout,
+            start_hooks,
+            shutdown_hooks,
+            tasks,
+            task_timeout,
         } = self;
         let alive_connections = Arc::new(AtomicUsize::new(0));
         let notify = Arc::new(Notify::new());
@@ -194,6 +284,14 @@ impl<A: Acceptor + Send> Server<A> {
             }
         }
 
+        for hook in start_hooks {
+            hook().await;
+        }
+        let tasks = tasks
+            .into_iter()
+            .map(|(name, task)| (name, tokio::spawn(task(server_shutdown_token.clone()))))
+            .collect::<Vec<_>>();
+
         let service = Arc::new(service.into());
         let builder = Arc::new(builder);
         loop {
@@ -218,13 +316,15 @@ impl<A: Acceptor + Send> Server<A> {
                 },
                  accepted = acceptor.accept() => {
                     match accepted {
-                        Ok(Accepted { conn, local_addr, remote_addr, http_scheme, ..}) => {
+                        Ok(Accepted { conn, local_addr, remote_addr, http_scheme, tls_info, ..}) => {
                             alive_connections.fetch_add(1, Ordering::Release);
 
                             let service = service.clone();
                             let alive_connections = alive_connections.clone();
                             let notify = notify.clone();
-                            let handler = service.hyper_handler(local_addr, remote_addr, http_scheme, alt_svc_h3.clone());
+                            let mut handler = service.hyper_handler(local_addr, remote_addr, http_scheme, alt_svc_h3.clone());
+                            handler.shutdown_token = server_shutdown_token.clone();
+                            handler.tls_info = tls_info.map(Arc::new);
                             let builder = builder.clone();
 
                             let timeout_token = timeout_token.clone();
@@ -261,15 +361,84 @@ impl<A: Acceptor + Send> Server<A> {
             notify.notified().await;
         }
 
+        if !tasks.is_empty() {
+            tracing::info!("wait for all background tasks to finish.");
+            let deadline = tokio::time::Instant::now() + task_timeout;
+            for (name, mut task) in tasks {
+                match tokio::time::timeout_at(deadline, &mut task).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        tracing::error!(name, error = ?e, "background task failed");
+                    }
+                    Err(_) => {
+                        tracing::warn!(name, "background task is aborted because it is not finished in time");
+                        task.abort();
+                    }
+                }
+            }
+        }
+        for hook in shutdown_hooks {
+            hook().await;
+        }
+
         tracing::info!("server stopped");
         Ok(())
     }
 }
 
+cfg_feature! {
+    #![feature = "config"]
+    impl Server<crate::conn::BoxedAcceptor> {
+        /// Create a `Server` listening on all addresses in config.
+        ///
+        /// Connection idle timeout and background tasks timeout are applied from config. Limits are process wide
+        /// and are applied by [`LimitsConfig::apply`](crate::config::LimitsConfig::apply). Compression and CORS
+        /// settings are used by their middlewares.
+        pub async fn from_config(config: &crate::config::ServerConfig) -> IoResult<Self> {
+            use std::io::{Error as IoError, ErrorKind};
+
+            use crate::conn::{BoxedAcceptor, Listener, TcpListener};
+
+            let mut acceptor: Option<BoxedAcceptor> = None;
+            for addr in &config.listen {
+                let listener = TcpListener::new(addr.clone());
+                let bound = match &config.tls {
+                    None => BoxedAcceptor::new(listener.try_bind().await?),
+                    #[cfg(feature = "rustls")]
+                    Some(tls) => {
+                        use crate::conn::rustls::{Keycert, RustlsConfig};
+                        let keycert = Keycert::new().cert_from_path(&tls.cert)?.key_from_path(&tls.key)?;
+                        BoxedAcceptor::new(listener.rustls(RustlsConfig::new(keycert)).try_bind().await?)
+                    }
+                    #[cfg(not(feature = "rustls"))]
+                    Some(_) => {
+                        return Err(IoError::new(ErrorKind::Unsupported, "tls in config requires `rustls` feature"));
+                    }
+                };
+                acceptor = Some(match acceptor {
+                    Some(acceptor) => acceptor.join(bound),
+                    None => bound,
+                });
+            }
+            let acceptor =
+                acceptor.ok_or_else(|| IoError::new(ErrorKind::InvalidInput, "no listen address in config"))?;
+
+            let mut server = Server::new(acceptor).background_task_timeout(config.timeouts.background_task);
+            if let Some(timeout) = config.timeouts.idle {
+                server = server.idle_timeout(timeout);
+            }
+            Ok(server)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use serde::Serialize;
 
+    use crate::conn::Acceptor;
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
 
@@ -339,4 +508,132 @@ mod tests {
             .unwrap();
         assert!(result.contains("<code>404</code>"));
     }
+
+    struct WaitCancelled(tokio::sync::mpsc::UnboundedSender<()>);
+    #[async_trait]
+    impl Handler for WaitCancelled {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let token = req.cancellation_token().clone();
+            let tx = self.0.clone();
+            tokio::spawn(async move {
+                token.cancelled().await;
+                tx.send(()).ok();
+            });
+            futures_util::future::pending::<()>().await;
+        }
+    }
+
+    async fn send_request(addr: std::net::SocketAddr) -> tokio::net::TcpStream {
+        use tokio::io::AsyncWriteExt;
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET / HTTP/1.1\r\nhost: {addr}\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        stream
+    }
+
+    #[tokio::test]
+    async fn test_request_cancelled_by_client() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(Router::new().get(WaitCancelled(tx))).await;
+        });
+
+        let stream = send_request(addr).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx.try_recv().is_err());
+        drop(stream);
+        let cancelled = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await;
+        assert_eq!(cancelled.unwrap(), Some(()));
+    }
+
+    #[tokio::test]
+    async fn test_request_cancelled_by_shutdown() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor)
+                .serve_with_graceful_shutdown(
+                    Router::new().get(WaitCancelled(tx)),
+                    async {
+                        stop_rx.await.ok();
+                    },
+                    Some(Duration::from_secs(2)),
+                )
+                .await;
+        });
+
+        let _stream = send_request(addr).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx.try_recv().is_err());
+        stop_tx.send(()).unwrap();
+        let cancelled = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await;
+        assert_eq!(cancelled.unwrap(), Some(()));
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_hooks() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let start_tx = tx.clone();
+        let task_tx = tx.clone();
+        let stuck_tx = tx.clone();
+        let server = Server::new(acceptor)
+            .on_start(move || async move {
+                start_tx.send("start").ok();
+            })
+            .on_shutdown(move || async move {
+                tx.send("shutdown").ok();
+            })
+            .background_task("job", move |token| async move {
+                task_tx.send("job started").ok();
+                token.cancelled().await;
+                task_tx.send("job stopped").ok();
+            })
+            .background_task("stuck", move |_| async move {
+                futures_util::future::pending::<()>().await;
+                stuck_tx.send("unreachable").ok();
+            })
+            .background_task_timeout(Duration::from_millis(100));
+        let server = tokio::spawn(server.serve_with_graceful_shutdown(
+            Router::new(),
+            async {
+                stop_rx.await.ok();
+            },
+            None,
+        ));
+
+        assert_eq!(rx.recv().await, Some("start"));
+        assert_eq!(rx.recv().await, Some("job started"));
+        stop_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), server)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rx.recv().await, Some("job stopped"));
+        assert_eq!(rx.recv().await, Some("shutdown"));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[cfg(feature = "config")]
+    #[tokio::test]
+    async fn test_server_from_config() {
+        let config: crate::config::ServerConfig = serde_json::from_value(serde_json::json!({
+            "listen": "127.0.0.1:0, 127.0.0.1:0",
+            "timeouts": { "idle": "30s" },
+        }))
+        .unwrap();
+        let server = Server::from_config(&config).await.unwrap();
+        assert_eq!(server.holdings().len(), 2);
+        assert_eq!(server.idle_timeout, Some(Duration::from_secs(30)));
+
+        let config: crate::config::ServerConfig = serde_json::from_value(serde_json::json!({ "listen": [] })).unwrap();
+        assert!(Server::from_config(&config).await.is_err());
+    }
 }
@@ -1,19 +1,35 @@
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
+use futures_channel::oneshot;
 use headers::HeaderValue;
-use http::header::{ALT_SVC, CONTENT_TYPE};
+use http::header::{HeaderName, ALLOW, ALT_SVC, CONTENT_LENGTH, CONTENT_TYPE};
 use http::uri::Scheme;
 use hyper::service::Service as HyperService;
 use hyper::{Method, Request as HyperRequest, Response as HyperResponse};
+use tokio_util::sync::CancellationToken;
 
 use crate::catcher::{write_error_default, Catcher};
-use crate::conn::SocketAddr;
-use crate::http::body::{ReqBody, ResBody};
-use crate::http::{Mime, Request, Response, StatusCode};
-use crate::routing::{FlowCtrl, PathState, Router};
-use crate::Depot;
+use crate::conn::{SocketAddr, TlsInfo};
+use crate::error_mapper::ErrorMapper;
+use crate::http::body::{BodyStats, ReqBody, ResBody};
+use crate::http::response::Flusher;
+use crate::http::{Mime, NoBuffering, Request, Response, StatusCode};
+use crate::routing::{DetectMatched, FlowCtrl, HoopChain, PathState, Rewriter, Router};
+use crate::writing::JsonOptions;
+use crate::{async_trait, Depot, Handler};
+
+/// Methods checked to build the `Allow` header of automatic `OPTIONS` responses.
+const ALLOW_METHODS: [Method; 6] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
 
 /// Service http request.
 #[non_exhaustive]
@@ -22,8 +38,20 @@ pub struct Service {
     pub router: Arc<Router>,
     /// The catcher of this service.
     pub catcher: Option<Arc<Catcher>>,
+    /// The error mapper of this service.
+    pub error_mapper: Option<Arc<ErrorMapper>>,
     /// The allowed media types of this service.
     pub allowed_media_types: Arc<Vec<Mime>>,
+    /// Whether body sizes of requests are counted by [`BodyStats`].
+    pub body_stats: bool,
+    /// Whether `OPTIONS` requests without a matched route are answered automatically.
+    pub auto_options: bool,
+    /// Whether `HEAD` requests without a matched route are handled by `GET` routes.
+    pub auto_head: bool,
+    /// The options used by [`Json`](crate::writing::Json) responses of this service.
+    pub json_options: Option<Arc<JsonOptions>>,
+    /// Rewriters called before requests are routed.
+    pub rewriters: Vec<Arc<dyn Rewriter>>,
 }
 
 impl Service {
@@ -36,7 +64,13 @@ impl Service {
         Service {
             router: router.into(),
             catcher: None,
+            error_mapper: None,
             allowed_media_types: Arc::new(vec![]),
+            body_stats: false,
+            auto_options: false,
+            auto_head: false,
+            json_options: None,
+            rewriters: vec![],
         }
     }
 
@@ -46,6 +80,33 @@ impl Service {
         self.router.clone()
     }
 
+    /// Get the ordered hoop chains of all routes, it can be printed at startup to check how middlewares are
+    /// composed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # #[handler]
+    /// # async fn hello() {}
+    /// let service = Service::new(Router::with_path("hello").get(hello));
+    /// for chain in service.hoop_chains() {
+    ///     tracing::debug!("{chain}");
+    /// }
+    /// ```
+    #[inline]
+    pub fn hoop_chains(&self) -> Vec<HoopChain> {
+        self.router.hoop_chains()
+    }
+
+    /// Get the ordered hoop chain which handles the request, or `None` if no route is matched.
+    ///
+    /// The request is only routed and not handled.
+    #[inline]
+    pub fn hoop_chain(&self, req: &mut Request) -> Option<HoopChain> {
+        self.router.hoop_chain(req)
+    }
+
     /// When the response code is 400-600 and the body is empty, capture and set the error page content.
     /// If catchers is not set, the default error page will be used.
     ///
@@ -74,6 +135,27 @@ impl Service {
         self
     }
 
+    /// Sets [`ErrorMapper`], errors rendered as the cause of [`StatusError`](crate::http::StatusError) are rendered
+    /// with its mappings before the catcher is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # use salvo_core::error_mapper::ErrorMapper;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mapper = ErrorMapper::new().map(|_: &std::num::ParseIntError| StatusError::bad_request());
+    /// let service = Service::new(Router::new()).error_mapper(mapper);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn error_mapper(mut self, error_mapper: impl Into<Arc<ErrorMapper>>) -> Self {
+        self.error_mapper = Some(error_mapper.into());
+        self
+    }
+
     /// Sets allowed media types list and returns `Self` for write code chained.
     ///
     /// # Example
@@ -95,6 +177,93 @@ impl Service {
         self
     }
 
+    /// Sets whether body sizes of requests are counted, the default is `false`.
+    ///
+    /// When it is `true`, a [`BodyStats`] is injected into the [`Depot`] of every request, it counts bytes of the
+    /// request body read by handlers and bytes of the final response body written to the connection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let service = Service::new(Router::new()).body_stats(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn body_stats(mut self, body_stats: bool) -> Self {
+        self.body_stats = body_stats;
+        self
+    }
+
+    /// Sets whether `OPTIONS` requests are answered automatically, the default is `false`.
+    ///
+    /// When it is `true` and no route matches an `OPTIONS` request, the request is handled by the hoops of the
+    /// route matching the same path with other methods, so middlewares such as CORS can answer preflight
+    /// requests, and `204 No Content` with the `Allow` header listing the methods of the path is responded if
+    /// hoops do not answer it. Routes registered by [`Router::options`] are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let service = Service::new(Router::new()).auto_options(true).auto_head(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn auto_options(mut self, auto_options: bool) -> Self {
+        self.auto_options = auto_options;
+        self
+    }
+
+    /// Sets whether `HEAD` requests are handled by `GET` routes, the default is `false`.
+    ///
+    /// When it is `true` and no route matches a `HEAD` request, the request is handled as a `GET` request, the
+    /// body of the response is discarded and the headers are kept, `Content-Length` is set if the size of the
+    /// body is known. Routes registered by [`Router::head`] are not affected.
+    #[inline]
+    pub fn auto_head(mut self, auto_head: bool) -> Self {
+        self.auto_head = auto_head;
+        self
+    }
+
+    /// Sets options used by [`Json`](crate::writing::Json) and [`JsonArray`](crate::writing::JsonArray)
+    /// responses of this service.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # use salvo_core::extract::metadata::RenameRule;
+    /// # use salvo_core::writing::JsonOptions;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let service = Service::new(Router::new())
+    ///     .json_options(JsonOptions::new().pretty(true).key_case(RenameRule::CamelCase));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn json_options(mut self, json_options: JsonOptions) -> Self {
+        self.json_options = Some(Arc::new(json_options));
+        self
+    }
+
+    /// Add a [`Rewriter`] which changes requests before they are routed.
+    ///
+    /// Rewriters are called in the order they are added, the path rerouted by [`FlowCtrl::reroute`] is not
+    /// rewritten again.
+    #[inline]
+    pub fn rewriter(mut self, rewriter: impl Rewriter) -> Self {
+        self.rewriters.push(Arc::new(rewriter));
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn hyper_handler(
@@ -110,8 +279,16 @@ impl Service {
             http_scheme,
             router: self.router.clone(),
             catcher: self.catcher.clone(),
+            error_mapper: self.error_mapper.clone(),
             allowed_media_types: self.allowed_media_types.clone(),
+            body_stats: self.body_stats,
+            auto_options: self.auto_options,
+            auto_head: self.auto_head,
+            json_options: self.json_options.clone(),
+            rewriters: self.rewriters.clone(),
             alt_svc_h3,
+            tls_info: None,
+            shutdown_token: CancellationToken::new(),
         }
     }
     /// Handle new request, this function only used for test.
@@ -143,34 +320,92 @@ pub struct HyperHandler {
     pub(crate) http_scheme: Scheme,
     pub(crate) router: Arc<Router>,
     pub(crate) catcher: Option<Arc<Catcher>>,
+    pub(crate) error_mapper: Option<Arc<ErrorMapper>>,
     pub(crate) allowed_media_types: Arc<Vec<Mime>>,
+    pub(crate) body_stats: bool,
+    pub(crate) auto_options: bool,
+    pub(crate) auto_head: bool,
+    pub(crate) json_options: Option<Arc<JsonOptions>>,
+    pub(crate) rewriters: Vec<Arc<dyn Rewriter>>,
     pub(crate) alt_svc_h3: Option<HeaderValue>,
+    /// TLS information of the connection.
+    pub(crate) tls_info: Option<Arc<TlsInfo>>,
+    /// Parent of cancellation tokens of requests, it is cancelled when server begins graceful shutdown.
+    pub(crate) shutdown_token: CancellationToken,
 }
 impl HyperHandler {
     /// Handle [`Request`] and returns [`Response`].
     #[inline]
-    pub fn handle(&self, mut req: Request) -> impl Future<Output = Response> {
+    pub fn handle(&self, req: Request) -> impl Future<Output = Response> {
+        self.handle_with(req, None)
+    }
+
+    /// Handle [`Request`], the head of the response is sent by `head_tx` if the response is flushed by handlers.
+    fn handle_with(
+        &self,
+        mut req: Request,
+        head_tx: Option<oneshot::Sender<HyperResponse<ResBody>>>,
+    ) -> impl Future<Output = Response> {
         let catcher = self.catcher.clone();
+        let error_mapper = self.error_mapper.clone();
         let allowed_media_types = self.allowed_media_types.clone();
         req.local_addr = self.local_addr.clone();
         req.remote_addr = self.remote_addr.clone();
+        req.tls_info = self.tls_info.clone();
         #[cfg(not(feature = "cookie"))]
         let mut res = Response::new();
         #[cfg(feature = "cookie")]
         let mut res = Response::with_cookies(req.cookies.clone());
+        res.flusher = head_tx.map(Flusher::new);
+        if let Some(json_options) = &self.json_options {
+            res.extensions.insert(json_options.clone());
+        }
         if let Some(alt_svc_h3) = &self.alt_svc_h3 {
             if !res.headers().contains_key(ALT_SVC) {
                 res.headers_mut().insert(ALT_SVC, alt_svc_h3.clone());
             }
         }
         let mut depot = Depot::new();
-        let mut path_state = PathState::new(req.uri().path());
+        let body_stats = self.body_stats.then(|| {
+            let stats = BodyStats::new();
+            let body = stats.count_request(req.take_body());
+            req.replace_body(body);
+            depot.inject(stats.clone());
+            if let Some(flusher) = &mut res.flusher {
+                flusher.body_stats = Some(stats.clone());
+            }
+            stats
+        });
+        for rewriter in &self.rewriters {
+            rewriter.rewrite(&mut req);
+        }
         let router = self.router.clone();
+        let auto_options = self.auto_options;
+        let auto_head = self.auto_head;
 
         async move {
-            if let Some(dm) = router.detect(&mut req, &mut path_state) {
-                req.params = path_state.params;
-                let mut ctrl = FlowCtrl::new([&dm.hoops[..], &[dm.goal]].concat());
+            let mut head_as_get = false;
+            let mut path_state = PathState::new(req.uri().path());
+            let mut detected = router.detect(&mut req, &mut path_state);
+            if detected.is_none() && auto_head && *req.method() == Method::HEAD {
+                *req.method_mut() = Method::GET;
+                path_state = PathState::new(req.uri().path());
+                detected = router.detect(&mut req, &mut path_state);
+                head_as_get = detected.is_some();
+                if !head_as_get {
+                    *req.method_mut() = Method::HEAD;
+                }
+            }
+            if detected.is_none() && auto_options && *req.method() == Method::OPTIONS {
+                if let Some((dm, state)) = detect_options(&router, &mut req, auto_head) {
+                    detected = Some(dm);
+                    path_state = state;
+                }
+            }
+            if let Some(dm) = detected {
+                let handlers = enter_route(&mut req, &mut res, path_state, dm);
+                let mut ctrl = FlowCtrl::new(handlers);
+                ctrl.router = Some(router);
                 ctrl.call_next(&mut req, &mut depot, &mut res).await;
                 if res.status_code.is_none() {
                     res.status_code = Some(StatusCode::OK);
@@ -178,7 +413,14 @@ impl HyperHandler {
             } else {
                 res.status_code(StatusCode::NOT_FOUND);
             }
+            if res.is_flushed() {
+                res.finish_flush().await;
+                return res;
+            }
 
+            if let Some(error_mapper) = error_mapper {
+                error_mapper.map_response(&mut res);
+            }
             let status = res.status_code.unwrap();
             let has_error = status.is_client_error() || status.is_server_error();
             if let Some(value) = res.headers().get(CONTENT_TYPE) {
@@ -221,6 +463,20 @@ impl HyperHandler {
                     write_error_default(&req, &mut res, None);
                 }
             }
+            if head_as_get {
+                *req.method_mut() = Method::HEAD;
+                let size = match &res.body {
+                    ResBody::Once(bytes) => Some(bytes.len()),
+                    ResBody::Chunks(chunks) => Some(chunks.iter().map(|chunk| chunk.len()).sum()),
+                    _ => None,
+                };
+                if let Some(size) = size {
+                    if !res.headers().contains_key(CONTENT_LENGTH) {
+                        res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from(size));
+                    }
+                }
+                res.body = ResBody::None;
+            }
             #[cfg(debug_assertions)]
             if let hyper::Method::HEAD = *req.method() {
                 if !res.body.is_none() {
@@ -250,11 +506,79 @@ impl HyperHandler {
                     res.extensions.insert(stream);
                 }
             }
+            if let Some(stats) = body_stats {
+                res.body = stats.count_response(res.take_body());
+            }
             res
         }
     }
 }
 
+/// Set the matched route to the request, and returns the handlers of it.
+pub(crate) fn enter_route(
+    req: &mut Request,
+    res: &mut Response,
+    path_state: PathState,
+    dm: DetectMatched,
+) -> Vec<Arc<dyn Handler>> {
+    req.routed_at = Some(Instant::now());
+    req.matched_path = Some(path_state.matched_path());
+    req.route_metadata = dm.metadata;
+    req.params = path_state.params;
+    if req.route_metadata.contains::<NoBuffering>() {
+        res.headers_mut().insert(
+            HeaderName::from_static("x-accel-buffering"),
+            HeaderValue::from_static("no"),
+        );
+    }
+    let mut handlers = dm.hoops;
+    handlers.push(dm.goal);
+    handlers
+}
+
+/// Detect the routes of the request path with other methods for an `OPTIONS` request.
+///
+/// Returns the hoops of the first matched route with a goal responds the `Allow` header, and the path state of it.
+fn detect_options(router: &Router, req: &mut Request, auto_head: bool) -> Option<(DetectMatched, PathState)> {
+    let mut allowed = Vec::new();
+    let mut first = None;
+    for method in ALLOW_METHODS {
+        *req.method_mut() = method.clone();
+        let mut path_state = PathState::new(req.uri().path());
+        if let Some(dm) = router.detect(req, &mut path_state) {
+            allowed.push(method);
+            if first.is_none() {
+                first = Some((dm, path_state));
+            }
+        }
+    }
+    *req.method_mut() = Method::OPTIONS;
+    let (mut dm, path_state) = first?;
+    if auto_head && allowed.contains(&Method::GET) && !allowed.contains(&Method::HEAD) {
+        allowed.insert(1, Method::HEAD);
+    }
+    allowed.push(Method::OPTIONS);
+    let allow = allowed
+        .iter()
+        .map(|method| method.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    dm.goal = Arc::new(AllowMethods(
+        HeaderValue::from_str(&allow).expect("methods should be valid header value"),
+    ));
+    Some((dm, path_state))
+}
+
+/// Goal of automatic `OPTIONS` responses.
+struct AllowMethods(HeaderValue);
+#[async_trait]
+impl Handler for AllowMethods {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        res.status_code(StatusCode::NO_CONTENT);
+        res.headers_mut().insert(ALLOW, self.0.clone());
+    }
+}
+
 impl<B> HyperService<HyperRequest<B>> for HyperHandler
 where
     B: Into<ReqBody>,
@@ -271,8 +595,9 @@ where
     ) -> Self::Future {
         let scheme = req.uri().scheme().cloned().unwrap_or_else(|| self.http_scheme.clone());
         // https://github.com/hyperium/hyper/issues/1310
+        // The authority-form target of `CONNECT` requests is kept as it is.
         #[cfg(feature = "fix-http1-request-uri")]
-        if req.uri().scheme().is_none() {
+        if req.uri().scheme().is_none() && req.uri().authority().is_none() {
             if let Some(host) = req
                 .headers()
                 .get(http::header::HOST)
@@ -287,14 +612,37 @@ where
                 }
             }
         }
-        let request = Request::from_hyper(req, scheme);
-        let response = self.handle(request);
-        Box::pin(async move { Ok(response.await.into_hyper()) })
+        let mut request = Request::from_hyper(req, scheme);
+        request.cancellation_token = self.shutdown_token.child_token();
+        // The future is dropped by hyper without completing if the client disconnects.
+        let guard = request.cancellation_token.clone().drop_guard();
+        let (head_tx, mut head_rx) = oneshot::channel();
+        let mut response = Box::pin(self.handle_with(request, Some(head_tx)));
+        Box::pin(async move {
+            tokio::select! {
+                biased;
+                Ok(head) = &mut head_rx => {
+                    // The response is flushed, the rest of handlers run in background and send the rest of body.
+                    tokio::spawn(async move {
+                        response.await;
+                        guard.disarm();
+                    });
+                    Ok(head)
+                }
+                response = &mut response => {
+                    guard.disarm();
+                    Ok(response.into_hyper())
+                }
+            }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use http::header::{ALLOW, CONTENT_LENGTH, CONTENT_TYPE};
+    use hyper::Method;
+
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
 
@@ -355,4 +703,125 @@ mod tests {
         let content = access(&service, "3").await;
         assert_eq!(content, "before1before2before3");
     }
+
+    #[tokio::test]
+    async fn test_auto_options_and_head() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler]
+        async fn preflight(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+            if req.method() == Method::OPTIONS && req.headers().contains_key("access-control-request-method") {
+                res.add_header("access-control-allow-origin", "*", true).unwrap();
+                res.status_code(StatusCode::OK);
+                ctrl.skip_rest();
+            } else {
+                ctrl.call_next(req, depot, res).await;
+            }
+        }
+        let router = Router::new()
+            .push(Router::with_path("hello").get(hello).post(hello))
+            .push(Router::with_path("cors").hoop(preflight).delete(hello));
+        let service = Service::new(router).auto_options(true).auto_head(true);
+
+        let res = TestClient::options("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(res.headers()[ALLOW], "GET, HEAD, POST, OPTIONS");
+
+        let res = TestClient::options("http://127.0.0.1:5801/cors")
+            .add_header("access-control-request-method", "DELETE", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers()["access-control-allow-origin"], "*");
+
+        let res = TestClient::options("http://127.0.0.1:5801/missing")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+
+        let mut res = TestClient::head("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers()[CONTENT_LENGTH], "5");
+        assert!(res.headers()[CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+        assert!(res.take_string().await.unwrap().is_empty());
+
+        let service = Service::new(Router::with_path("hello").get(hello));
+        let res = TestClient::head("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        let res = TestClient::options("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_flush() {
+        use std::sync::Mutex;
+
+        use futures_channel::oneshot;
+        use futures_util::StreamExt;
+        use http::uri::Scheme;
+        use hyper::service::Service as _;
+
+        use crate::conn::SocketAddr;
+        use crate::http::body::ReqBody;
+        use crate::http::NoBuffering;
+
+        static RELEASE: Mutex<Option<oneshot::Receiver<()>>> = Mutex::new(None);
+
+        #[handler]
+        async fn progressive(res: &mut Response) {
+            res.write_body("head;").unwrap();
+            res.flush().await.unwrap();
+            let release = RELEASE.lock().unwrap().take().unwrap();
+            release.await.unwrap();
+            res.write_body("body").unwrap();
+        }
+        let (tx, rx) = oneshot::channel();
+        *RELEASE.lock().unwrap() = Some(rx);
+
+        let service = Service::new(Router::new().meta(NoBuffering).get(progressive));
+        let handler = service.hyper_handler(SocketAddr::Unknown, SocketAddr::Unknown, Scheme::HTTP, None);
+        let req = hyper::Request::builder()
+            .uri("http://127.0.0.1:5801/")
+            .body(ReqBody::None)
+            .unwrap();
+        // The head is received while the handler is still waiting.
+        let res = handler.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["x-accel-buffering"], "no");
+        let mut body = res.into_body();
+        let chunk = body.next().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(chunk, "head;");
+
+        tx.send(()).unwrap();
+        let chunk = body.next().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(chunk, "body");
+        assert!(body.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rewriter() {
+        #[handler]
+        async fn hello(req: &mut Request) -> String {
+            format!("hello {}", req.uri().path_and_query().unwrap())
+        }
+        let service = Service::new(Router::with_path("hello").get(hello)).rewriter(|req: &mut Request| {
+            if let Some(path) = req.uri().path_and_query().and_then(|p| p.as_str().strip_prefix("/api")) {
+                let uri = path.parse().unwrap();
+                req.set_uri(uri);
+            }
+        });
+        let content = TestClient::get("http://127.0.0.1:5801/api/hello?a=1")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello /hello?a=1");
+        let res = TestClient::get("http://127.0.0.1:5801/other/hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
 }
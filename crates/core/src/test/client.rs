@@ -47,4 +47,16 @@ impl TestClient {
     pub fn trace(url: impl AsRef<str>) -> RequestBuilder {
         RequestBuilder::new(url, Method::TRACE)
     }
+
+    /// Create a new `RequestBuilder` with the GET method and WebSocket upgrade headers, use
+    /// [`RequestBuilder::connect`] to connect to a [`Service`](crate::Service).
+    #[cfg(feature = "http1")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http1")))]
+    pub fn websocket(url: impl AsRef<str>) -> RequestBuilder {
+        RequestBuilder::new(url, Method::GET)
+            .add_header("connection", "Upgrade", true)
+            .add_header("upgrade", "websocket", true)
+            .add_header("sec-websocket-version", "13", true)
+            .add_header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==", true)
+    }
 }
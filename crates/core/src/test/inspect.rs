@@ -0,0 +1,315 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::http::StatusCode;
+use crate::routing::{FlowCtrl, Router};
+use crate::{async_trait, Depot, Handler, Request, Response};
+
+/// Wraps all hoops and goals in the router tree, so every request handled by it records which handlers
+/// ran and what each of them contributed to the response.
+///
+/// The recorded [`HoopTrace`] can be read with [`ResponseExt::hoop_trace`](super::ResponseExt::hoop_trace).
+/// Handlers added to [`Catcher`](crate::catcher::Catcher) are not recorded.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::test::{inspect, ResponseExt, TestClient};
+///
+/// #[handler]
+/// async fn add_header(res: &mut Response) {
+///     res.headers_mut().insert("x-hoop", "1".parse().unwrap());
+/// }
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "hello"
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let service = Service::new(inspect(Router::new().hoop(add_header).get(hello)));
+///     let res = TestClient::get("http://127.0.0.1:5800").send(&service).await;
+///     let trace = res.hoop_trace().unwrap();
+///     assert_eq!(trace.position("add_header"), Some(0));
+///     assert!(trace.find("add_header").unwrap().header_change("x-hoop").is_some());
+/// }
+/// ```
+pub fn inspect(mut router: Router) -> Router {
+    wrap_router(&mut router);
+    router.hoops.insert(0, Arc::new(TraceRoot));
+    router
+}
+
+fn wrap_router(router: &mut Router) {
+    for hoop in router.hoops.iter_mut() {
+        *hoop = Arc::new(Traced(hoop.clone()));
+    }
+    if let Some(goal) = router.goal.as_mut() {
+        *goal = Arc::new(Traced(goal.clone()));
+    }
+    for child in router.routers.iter_mut() {
+        wrap_router(child);
+    }
+}
+
+/// Changes of a response header made by a handler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeaderChange {
+    /// Header name.
+    pub name: HeaderName,
+    /// Values before the handler changed it, it is empty if the header is added.
+    pub old: Vec<HeaderValue>,
+    /// Values after the handler changed it, it is empty if the header is removed.
+    pub new: Vec<HeaderValue>,
+}
+impl HeaderChange {
+    /// Returns `true` if the header is added by the handler.
+    #[inline]
+    pub fn is_added(&self) -> bool {
+        self.old.is_empty()
+    }
+    /// Returns `true` if the header is removed by the handler.
+    #[inline]
+    pub fn is_removed(&self) -> bool {
+        self.new.is_empty()
+    }
+}
+
+/// What a handler contributed to the response.
+///
+/// Changes made by handlers called inside it by [`FlowCtrl::call_next`] are not included.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct HoopRecord {
+    /// Type name of the handler.
+    pub name: &'static str,
+    /// Nesting depth, handlers called by `FlowCtrl::call_next` of another handler have bigger depth.
+    pub depth: usize,
+    /// Status code before and after the handler changed it.
+    pub status_change: Option<(Option<StatusCode>, Option<StatusCode>)>,
+    /// Changed response headers.
+    pub header_changes: Vec<HeaderChange>,
+}
+impl HoopRecord {
+    /// Get the change of the header with the given name.
+    pub fn header_change(&self, name: impl AsRef<str>) -> Option<&HeaderChange> {
+        let name = name.as_ref();
+        self.header_changes.iter().find(|c| c.name.as_str().eq_ignore_ascii_case(name))
+    }
+
+    fn apply(&mut self, before: &Snapshot, after: &Snapshot) {
+        if before.status_code != after.status_code {
+            let from = self.status_change.map(|(from, _)| from).unwrap_or(before.status_code);
+            self.status_change = Some((from, after.status_code));
+        }
+        let mut names = before.headers.keys().collect::<Vec<_>>();
+        names.extend(after.headers.keys().filter(|name| !before.headers.contains_key(*name)));
+        for name in names {
+            let old = before.headers.get_all(name).iter().cloned().collect::<Vec<_>>();
+            let new = after.headers.get_all(name).iter().cloned().collect::<Vec<_>>();
+            if old == new {
+                continue;
+            }
+            if let Some(change) = self.header_changes.iter_mut().find(|c| c.name == *name) {
+                change.new = new;
+            } else {
+                self.header_changes.push(HeaderChange {
+                    name: name.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+        self.header_changes.retain(|c| c.old != c.new);
+    }
+}
+
+/// Handlers ran for a request in calling order, created by [`inspect`].
+#[derive(Clone, Debug, Default)]
+pub struct HoopTrace {
+    records: Vec<HoopRecord>,
+}
+impl HoopTrace {
+    /// Get all records in calling order.
+    #[inline]
+    pub fn records(&self) -> &[HoopRecord] {
+        &self.records
+    }
+    /// Get type names of handlers in calling order.
+    #[inline]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.records.iter().map(|r| r.name).collect()
+    }
+    /// Get the position of first handler whose type name contains `name`.
+    #[inline]
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.records.iter().position(|r| r.name.contains(name))
+    }
+    /// Get the record of first handler whose type name contains `name`.
+    #[inline]
+    pub fn find(&self, name: &str) -> Option<&HoopRecord> {
+        self.records.iter().find(|r| r.name.contains(name))
+    }
+}
+impl Display for HoopTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for record in &self.records {
+            write!(f, "{}{}", "  ".repeat(record.depth), record.name)?;
+            if let Some((from, to)) = record.status_change {
+                write!(f, " status: {from:?} -> {to:?}")?;
+            }
+            for change in &record.header_changes {
+                write!(f, " {}: {:?} -> {:?}", change.name, change.old, change.new)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+struct Snapshot {
+    status_code: Option<StatusCode>,
+    headers: HeaderMap,
+}
+impl Snapshot {
+    fn of(res: &Response) -> Self {
+        Self {
+            status_code: res.status_code,
+            headers: res.headers().clone(),
+        }
+    }
+}
+
+struct Frame {
+    index: usize,
+    last: Snapshot,
+}
+
+#[derive(Default)]
+struct Recorder {
+    records: Vec<HoopRecord>,
+    stack: Vec<Frame>,
+}
+impl Recorder {
+    fn enter(&mut self, name: &'static str, res: &Response) {
+        let now = Snapshot::of(res);
+        if let Some(parent) = self.stack.last() {
+            self.records[parent.index].apply(&parent.last, &now);
+        }
+        self.records.push(HoopRecord {
+            name,
+            depth: self.stack.len(),
+            status_change: None,
+            header_changes: Vec::new(),
+        });
+        self.stack.push(Frame {
+            index: self.records.len() - 1,
+            last: now,
+        });
+    }
+    fn exit(&mut self, res: &Response) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let now = Snapshot::of(res);
+        self.records[frame.index].apply(&frame.last, &now);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.last = now;
+        }
+    }
+}
+
+struct TraceRoot;
+#[async_trait]
+impl Handler for TraceRoot {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        depot.inject(Recorder::default());
+        ctrl.call_next(req, depot, res).await;
+        if let Ok(recorder) = depot.scrape::<Recorder>() {
+            res.extensions.insert(HoopTrace {
+                records: recorder.records,
+            });
+        }
+    }
+}
+
+struct Traced(Arc<dyn Handler>);
+#[async_trait]
+impl Handler for Traced {
+    fn type_id(&self) -> std::any::TypeId {
+        self.0.type_id()
+    }
+    fn type_name(&self) -> &'static str {
+        self.0.type_name()
+    }
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if let Ok(recorder) = depot.obtain_mut::<Recorder>() {
+            recorder.enter(self.0.type_name(), res);
+        }
+        self.0.handle(req, depot, res, ctrl).await;
+        if let Ok(recorder) = depot.obtain_mut::<Recorder>() {
+            recorder.exit(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{inspect, ResponseExt, TestClient};
+
+    #[handler]
+    async fn outer(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        res.headers_mut().insert("x-outer", "before".parse().unwrap());
+        ctrl.call_next(req, depot, res).await;
+        res.headers_mut().insert("x-outer", "after".parse().unwrap());
+        res.headers_mut().remove("x-inner");
+    }
+    #[handler]
+    async fn inner(res: &mut Response) {
+        res.headers_mut().insert("x-inner", "1".parse().unwrap());
+    }
+    #[handler]
+    async fn teapot(res: &mut Response) {
+        res.status_code(StatusCode::IM_A_TEAPOT);
+        res.render("teapot");
+    }
+
+    #[tokio::test]
+    async fn test_inspect() {
+        let router = Router::new()
+            .hoop(outer)
+            .push(Router::with_path("tea").hoop(inner).get(teapot));
+        let service = Service::new(inspect(router));
+        let res = TestClient::get("http://127.0.0.1:5801/tea").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::IM_A_TEAPOT));
+
+        let trace = res.hoop_trace().unwrap();
+        assert_eq!(trace.records().len(), 3);
+        assert_eq!(trace.position("outer"), Some(0));
+        assert_eq!(trace.position("inner"), Some(1));
+        assert_eq!(trace.position("teapot"), Some(2));
+
+        let record = trace.find("outer").unwrap();
+        assert_eq!(record.depth, 0);
+        assert!(record.status_change.is_none());
+        let change = record.header_change("x-outer").unwrap();
+        assert!(change.is_added());
+        assert_eq!(change.new, vec!["after"]);
+        assert!(record.header_change("x-inner").unwrap().is_removed());
+
+        let record = trace.find("inner").unwrap();
+        assert_eq!(record.depth, 1);
+        assert!(record.header_change("x-inner").unwrap().is_added());
+        assert!(record.header_change("x-outer").is_none());
+
+        let record = trace.find("teapot").unwrap();
+        assert_eq!(record.status_change, Some((None, Some(StatusCode::IM_A_TEAPOT))));
+        assert!(record.header_change("content-type").unwrap().is_added());
+        assert!(trace.to_string().contains("teapot"));
+    }
+}
@@ -1,8 +1,15 @@
 //! Test utils for unit tests.
 
 mod client;
+mod inspect;
 mod request;
 mod response;
+#[cfg(feature = "http1")]
+mod websocket;
 pub use client::TestClient;
-pub use request::RequestBuilder;
-pub use response::ResponseExt;
+pub use inspect::{inspect, HeaderChange, HoopRecord, HoopTrace};
+pub use request::{MultipartForm, RequestBuilder};
+pub use response::{ResponseExt, SseEvent};
+#[cfg(feature = "http1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http1")))]
+pub use websocket::{WebSocketClient, WebSocketMessage};
@@ -8,6 +8,7 @@ use http::header::{self, HeaderMap, HeaderValue, IntoHeaderName};
 use http::uri::Scheme;
 use url::Url;
 
+use super::MultipartForm;
 use crate::http::body::ReqBody;
 use crate::http::Method;
 use crate::routing::{FlowCtrl, Router};
@@ -187,6 +188,14 @@ impl RequestBuilder {
             .or_insert(HeaderValue::from_static("application/x-www-form-urlencoded"));
         self.body(value.into())
     }
+    /// Sets the body of this request to be the given multipart form.
+    ///
+    /// The `Content-Type` header is always set to `multipart/form-data` with the boundary of the form.
+    pub fn multipart(mut self, form: MultipartForm) -> Self {
+        let content_type = HeaderValue::from_str(&form.content_type()).expect("invalid multipart boundary");
+        self.headers.insert(header::CONTENT_TYPE, content_type);
+        self.body(form.to_bytes())
+    }
     /// Modify a header for this response.
     ///
     /// When `overwrite` is set to `true`, If the header is already present, the value will be replaced.
@@ -239,7 +248,7 @@ impl RequestBuilder {
                 .filter_map(|c| c.encoded().to_string().parse().ok())
                 .collect::<Vec<_>>();
             for hv in values {
-                response.headers_mut().insert(header::SET_COOKIE, hv);
+                response.headers_mut().append(header::SET_COOKIE, hv);
             }
             response
         }
@@ -300,3 +309,30 @@ where
         SendTarget::call(handler, req).await
     }
 }
+
+#[cfg(all(test, feature = "cookie"))]
+mod tests {
+    use crate::http::cookie::Cookie;
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn test_send_keeps_all_set_cookies() {
+        #[handler]
+        async fn login(res: &mut Response) {
+            res.add_cookie(Cookie::new("session", "1"));
+            res.add_cookie(Cookie::new("remember", "2"));
+        }
+
+        let router = Router::new().goal(login);
+        let res = TestClient::get("http://127.0.0.1:5800/").send(router).await;
+        let mut cookies = res
+            .headers()
+            .get_all(http::header::SET_COOKIE)
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .collect::<Vec<_>>();
+        cookies.sort_unstable();
+        assert_eq!(cookies, vec!["remember=2", "session=1"]);
+    }
+}
@@ -1,3 +1,5 @@
-mod builder;
-
-pub use builder::RequestBuilder;
\ No newline at end of file
+mod builder;
+mod multipart;
+
+pub use builder::RequestBuilder;
+pub use multipart::MultipartForm;
@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use mime::Mime;
+
+/// Multipart form body used by [`RequestBuilder::multipart`](super::RequestBuilder::multipart).
+///
+/// # Example
+/// ```ignore
+/// let form = MultipartForm::new()
+///     .text("title", "avatar")
+///     .file("file", "avatar.png", std::fs::read("avatar.png").unwrap());
+/// TestClient::post("http://127.0.0.1:5800/upload").multipart(form);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultipartForm {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<Mime>,
+    data: Vec<u8>,
+}
+
+impl Default for MultipartForm {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultipartForm {
+    /// Create a new empty `MultipartForm` with a generated boundary.
+    pub fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let boundary = format!(
+            "----SalvoTestBoundary{:08x}{:08x}",
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        Self {
+            boundary,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Get the boundary of this form.
+    #[inline]
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Get the `Content-Type` header value of this form.
+    #[inline]
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Add a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: value.into().into_bytes(),
+        });
+        self
+    }
+
+    /// Add a file field, the content type is guessed from the file name.
+    pub fn file(self, name: impl Into<String>, file_name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        let file_name = file_name.into();
+        let content_type = mime_infer::from_path(&file_name).first_or_octet_stream();
+        self.file_with_type(name, file_name, content_type, data)
+    }
+
+    /// Add a file field with the given content type.
+    pub fn file_with_type(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: Mime,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Encode this form to body bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            body.extend_from_slice(
+                format!("content-disposition: form-data; name=\"{}\"", escape_quoted(&part.name)).as_bytes(),
+            );
+            if let Some(file_name) = &part.file_name {
+                body.extend_from_slice(format!("; filename=\"{}\"", escape_quoted(file_name)).as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            if let Some(content_type) = &part.content_type {
+                body.extend_from_slice(format!("content-type: {content_type}\r\n").as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body
+    }
+}
+
+/// Escape quoted string in `Content-Disposition` the same way as browsers.
+fn escape_quoted(value: &str) -> String {
+    value.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{MultipartForm, ResponseExt, TestClient};
+
+    #[tokio::test]
+    async fn test_multipart_form() {
+        #[handler]
+        async fn upload(req: &mut Request) -> String {
+            let title = req.form::<String>("title").await.unwrap();
+            let file = req.file("file").await.unwrap();
+            let content = std::fs::read_to_string(file.path()).unwrap();
+            format!(
+                "{title}|{}|{}|{content}",
+                file.name().unwrap(),
+                file.headers()[&crate::http::header::CONTENT_TYPE].to_str().unwrap()
+            )
+        }
+        let router = Router::new().post(upload);
+
+        let form = MultipartForm::new()
+            .text("title", "notes")
+            .file("file", "a \"b\".txt", "hello\r\nworld");
+        let content = TestClient::post("http://127.0.0.1:5801")
+            .multipart(form)
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "notes|a %22b%22.txt|text/plain|hello\r\nworld");
+    }
+}
@@ -1,7 +1,8 @@
 use std::borrow::Cow;
 use std::io::{self, Result as IoResult, Write};
+use std::time::Duration;
 
-use bytes::{Bytes, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use encoding_rs::{Encoding, UTF_8};
 use flate2::write::{GzDecoder, ZlibDecoder};
 use http_body_util::BodyExt;
@@ -13,6 +14,7 @@ use zstd::stream::write::Decoder as ZstdDecoder;
 use crate::catcher::status_error_bytes;
 use crate::http::header::{self, CONTENT_ENCODING};
 use crate::http::response::{ResBody, Response};
+use super::HoopTrace;
 use crate::{async_trait, Error};
 
 struct Writer {
@@ -42,12 +44,17 @@ impl io::Write for Writer {
     }
 }
 
+/// Max chars of body shown in the error message of `take_json`.
+const JSON_ERROR_BODY_LIMIT: usize = 256;
+
 /// More utils functions for response.
 #[async_trait]
 pub trait ResponseExt {
     /// Take body as `String` from response.
     async fn take_string(&mut self) -> crate::Result<String>;
     /// Take body as deserialize it to type `T` instance.
+    ///
+    /// The error message contains status code, content type and the body when deserialization fails.
     async fn take_json<T: DeserializeOwned>(&mut self) -> crate::Result<T>;
     /// Take body as `String` from response with charset.
     async fn take_string_with_charset(
@@ -58,6 +65,16 @@ pub trait ResponseExt {
     ) -> crate::Result<String>;
     /// Take all body bytes. If body is none, it will creates and returns a new [`Bytes`].
     async fn take_bytes(&mut self, content_type: Option<&Mime>) -> crate::Result<Bytes>;
+    /// Get handlers ran for the request, it is only available if the router is wrapped by [`inspect`](super::inspect).
+    fn hoop_trace(&self) -> Option<&HoopTrace>;
+    /// Take next data chunk of a streaming body, returns `None` if the body is ended.
+    ///
+    /// Returns [`ErrorKind::TimedOut`] error if no chunk is received in `timeout`.
+    async fn next_chunk(&mut self, timeout: Duration) -> crate::Result<Option<Bytes>>;
+    /// Take next server-sent event from the body, returns `None` if the body is ended.
+    ///
+    /// Returns [`ErrorKind::TimedOut`] error if no complete event is received in `timeout`.
+    async fn next_sse_event(&mut self, timeout: Duration) -> crate::Result<Option<SseEvent>>;
 }
 
 #[async_trait]
@@ -81,8 +98,26 @@ impl ResponseExt for Response {
             .await
     }
     async fn take_json<T: DeserializeOwned>(&mut self) -> crate::Result<T> {
+        let status_code = self.status_code;
+        let content_type = self
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_owned());
         let full = self.take_bytes(Some(&"application/json".parse().unwrap())).await?;
-        serde_json::from_slice(&full).map_err(Error::SerdeJson)
+        serde_json::from_slice(&full).map_err(|e| {
+            let body = String::from_utf8_lossy(&full);
+            let body = if body.chars().count() > JSON_ERROR_BODY_LIMIT {
+                format!("{}...", body.chars().take(JSON_ERROR_BODY_LIMIT).collect::<String>())
+            } else {
+                body.into_owned()
+            };
+            Error::other(format!(
+                "failed to deserialize response body as json: {e}, status code: {}, content type: {}, body: {body:?}",
+                status_code.map(|s| s.to_string()).unwrap_or_else(|| "none".into()),
+                content_type.as_deref().unwrap_or("none"),
+            ))
+        })
     }
     async fn take_string_with_charset(
         &mut self,
@@ -145,4 +180,184 @@ impl ResponseExt for Response {
         };
         Ok(bytes)
     }
+    fn hoop_trace(&self) -> Option<&HoopTrace> {
+        self.extensions.get::<HoopTrace>()
+    }
+    async fn next_chunk(&mut self, timeout: Duration) -> crate::Result<Option<Bytes>> {
+        let next = async {
+            while let Some(frame) = self.body.frame().await {
+                if let Ok(data) = frame?.into_data() {
+                    if !data.is_empty() {
+                        return Ok(Some(data));
+                    }
+                }
+            }
+            Ok(None)
+        };
+        tokio::time::timeout(timeout, next)
+            .await
+            .map_err(|_| IoError::new(ErrorKind::TimedOut, "timed out waiting for response chunk"))?
+    }
+    async fn next_sse_event(&mut self, timeout: Duration) -> crate::Result<Option<SseEvent>> {
+        let mut buffer = self.extensions.remove::<SseBuffer>().unwrap_or_default();
+        let next = async {
+            loop {
+                if let Some(event) = buffer.next_event() {
+                    return Ok(Some(event));
+                }
+                match self.body.frame().await {
+                    Some(frame) => {
+                        if let Ok(data) = frame?.into_data() {
+                            buffer.0.extend_from_slice(&data);
+                        }
+                    }
+                    None => return Ok(None),
+                }
+            }
+        };
+        let result = tokio::time::timeout(timeout, next)
+            .await
+            .map_err(|_| IoError::new(ErrorKind::TimedOut, "timed out waiting for server-sent event").into())
+            .and_then(|r| r);
+        self.extensions.insert(buffer);
+        result
+    }
+}
+
+/// Server-sent event received by [`ResponseExt::next_sse_event`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SseEvent {
+    /// Event id.
+    pub id: Option<String>,
+    /// Event name.
+    pub event: Option<String>,
+    /// Event data, lines of multiple `data` fields are joined by `\n`.
+    pub data: Option<String>,
+    /// Reconnection time.
+    pub retry: Option<Duration>,
+    /// Comment, lines of multiple comments are joined by `\n`.
+    pub comment: Option<String>,
+}
+impl SseEvent {
+    fn parse(block: &str) -> Self {
+        let mut event = SseEvent::default();
+        for line in block.lines() {
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "" => append_line(&mut event.comment, value),
+                "data" => append_line(&mut event.data, value),
+                "event" => event.event = Some(value.to_owned()),
+                "id" => event.id = Some(value.to_owned()),
+                "retry" => event.retry = value.parse().ok().map(Duration::from_millis),
+                _ => {}
+            }
+        }
+        event
+    }
+}
+fn append_line(target: &mut Option<String>, line: &str) {
+    match target {
+        Some(target) => {
+            target.push('\n');
+            target.push_str(line);
+        }
+        None => *target = Some(line.to_owned()),
+    }
+}
+
+/// Received bytes not parsed to server-sent event yet.
+#[derive(Clone, Default)]
+struct SseBuffer(BytesMut);
+impl SseBuffer {
+    fn next_event(&mut self) -> Option<SseEvent> {
+        loop {
+            let (end, delimiter) = [&b"\r\n\r\n"[..], b"\n\n", b"\r\r"]
+                .iter()
+                .filter_map(|d| self.0.windows(d.len()).position(|w| w == *d).map(|p| (p, d.len())))
+                .min_by_key(|(p, _)| *p)?;
+            let block = self.0.split_to(end);
+            self.0.advance(delimiter);
+            let block = String::from_utf8_lossy(&block);
+            if !block.trim().is_empty() {
+                return Some(SseEvent::parse(&block));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[handler]
+    async fn events(res: &mut Response) {
+        res.headers_mut()
+            .insert(crate::http::header::CONTENT_TYPE, "text/event-stream".parse().unwrap());
+        let mut tx = res.channel();
+        tokio::spawn(async move {
+            tx.send_data(": keep\n\nid: 1\nevent: greet\ndata: hel").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send_data("lo\ndata: world\nretry: 100\r\n\r\n").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            tx.send_data("data: done\n\n").await.unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_next_chunk() {
+        let mut res = TestClient::get("http://127.0.0.1:5801")
+            .send(Router::new().get(events))
+            .await;
+        let chunk = res.next_chunk(Duration::from_secs(1)).await.unwrap().unwrap();
+        assert!(chunk.starts_with(b": keep"));
+        let chunk = res.next_chunk(Duration::from_secs(1)).await.unwrap().unwrap();
+        assert!(chunk.starts_with(b"lo"));
+        let err = res.next_chunk(Duration::from_millis(50)).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(res.next_chunk(Duration::from_secs(1)).await.unwrap().is_some());
+        assert!(res.next_chunk(Duration::from_secs(1)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_next_sse_event() {
+        let mut res = TestClient::get("http://127.0.0.1:5801")
+            .send(Router::new().get(events))
+            .await;
+        let event = res.next_sse_event(Duration::from_secs(1)).await.unwrap().unwrap();
+        assert_eq!(event.comment.as_deref(), Some("keep"));
+        let event = res.next_sse_event(Duration::from_secs(1)).await.unwrap().unwrap();
+        assert_eq!(event.id.as_deref(), Some("1"));
+        assert_eq!(event.event.as_deref(), Some("greet"));
+        assert_eq!(event.data.as_deref(), Some("hello\nworld"));
+        assert_eq!(event.retry, Some(Duration::from_millis(100)));
+        assert!(res.next_sse_event(Duration::from_millis(50)).await.is_err());
+        let event = res.next_sse_event(Duration::from_secs(1)).await.unwrap().unwrap();
+        assert_eq!(event.data.as_deref(), Some("done"));
+        assert!(res.next_sse_event(Duration::from_secs(1)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_take_json_error() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "not json"
+        }
+        let err = TestClient::get("http://127.0.0.1:5801")
+            .send(Router::new().get(hello))
+            .await
+            .take_json::<Vec<u32>>()
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("status code: 200 OK"));
+        assert!(err.contains("content type: text/plain; charset=utf-8"));
+        assert!(err.contains("body: \"not json\""));
+    }
 }
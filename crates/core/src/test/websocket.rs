@@ -0,0 +1,387 @@
+//! WebSocket client used to test socket handlers without binding a real port.
+use bytes::{Buf, BufMut, BytesMut};
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::StatusCode;
+use hyper::server::conn::http1;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+use super::RequestBuilder;
+use crate::http::uri::Scheme;
+use crate::rt::tokio::TokioIo;
+use crate::{Error, Result, Service};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Message sent or received by [`WebSocketClient`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    /// Text message.
+    Text(String),
+    /// Binary message.
+    Binary(Vec<u8>),
+    /// Ping message.
+    Ping(Vec<u8>),
+    /// Pong message.
+    Pong(Vec<u8>),
+    /// Close message with optional close code and reason.
+    Close(Option<(u16, String)>),
+}
+
+/// WebSocket client connected to a [`Service`] in process.
+///
+/// It is created by [`RequestBuilder::connect`], the request is upgraded with a real HTTP/1 connection
+/// over an in-memory stream, so the socket handlers work as they do with a real server.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut client = TestClient::websocket("http://127.0.0.1:5801/ws").connect(&service).await.unwrap();
+/// client.send_text("hello").await.unwrap();
+/// assert_eq!(client.recv().await.unwrap().unwrap(), WebSocketMessage::Text("hello".into()));
+/// ```
+#[derive(Debug)]
+pub struct WebSocketClient {
+    io: DuplexStream,
+    read_buf: BytesMut,
+    status_code: StatusCode,
+    headers: HeaderMap,
+    closed: bool,
+}
+
+impl WebSocketClient {
+    /// Get the status code of the handshake response.
+    #[inline]
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Get headers of the handshake response.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Send a text message.
+    #[inline]
+    pub async fn send_text(&mut self, text: impl Into<String>) -> Result<()> {
+        self.send(WebSocketMessage::Text(text.into())).await
+    }
+
+    /// Send a binary message.
+    #[inline]
+    pub async fn send_binary(&mut self, data: impl Into<Vec<u8>>) -> Result<()> {
+        self.send(WebSocketMessage::Binary(data.into())).await
+    }
+
+    /// Send a message.
+    pub async fn send(&mut self, message: WebSocketMessage) -> Result<()> {
+        let (opcode, payload) = match message {
+            WebSocketMessage::Text(text) => (OPCODE_TEXT, text.into_bytes()),
+            WebSocketMessage::Binary(data) => (OPCODE_BINARY, data),
+            WebSocketMessage::Ping(data) => (OPCODE_PING, data),
+            WebSocketMessage::Pong(data) => (OPCODE_PONG, data),
+            WebSocketMessage::Close(frame) => {
+                let mut payload = Vec::new();
+                if let Some((code, reason)) = frame {
+                    payload.extend_from_slice(&code.to_be_bytes());
+                    payload.extend_from_slice(reason.as_bytes());
+                }
+                (OPCODE_CLOSE, payload)
+            }
+        };
+        self.io.write_all(&encode_frame(opcode, &payload)).await?;
+        self.io.flush().await?;
+        Ok(())
+    }
+
+    /// Receive next message, pings are not answered automatically.
+    ///
+    /// Returns `None` if the connection is closed.
+    pub async fn recv(&mut self) -> Option<Result<WebSocketMessage>> {
+        if self.closed {
+            return None;
+        }
+        let mut fragments: Option<(u8, Vec<u8>)> = None;
+        loop {
+            let (fin, opcode, payload) = match self.read_frame().await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    self.closed = true;
+                    return None;
+                }
+                Err(e) => return Some(Err(e)),
+            };
+            let (opcode, payload) = match (opcode, fragments.take()) {
+                (OPCODE_CONTINUATION, Some((first, mut data))) => {
+                    data.extend_from_slice(&payload);
+                    (first, data)
+                }
+                (OPCODE_CONTINUATION, None) => {
+                    return Some(Err(Error::other("unexpected websocket continuation frame")));
+                }
+                (opcode, _) => (opcode, payload),
+            };
+            if !fin {
+                fragments = Some((opcode, payload));
+                continue;
+            }
+            let message = match opcode {
+                OPCODE_TEXT => match String::from_utf8(payload) {
+                    Ok(text) => WebSocketMessage::Text(text),
+                    Err(e) => return Some(Err(Error::other(e))),
+                },
+                OPCODE_BINARY => WebSocketMessage::Binary(payload),
+                OPCODE_PING => WebSocketMessage::Ping(payload),
+                OPCODE_PONG => WebSocketMessage::Pong(payload),
+                OPCODE_CLOSE => {
+                    self.closed = true;
+                    // Reply the close frame to complete the closing handshake.
+                    self.io.write_all(&encode_frame(OPCODE_CLOSE, &payload)).await.ok();
+                    if payload.len() >= 2 {
+                        let code = u16::from_be_bytes([payload[0], payload[1]]);
+                        let reason = String::from_utf8_lossy(&payload[2..]).into_owned();
+                        WebSocketMessage::Close(Some((code, reason)))
+                    } else {
+                        WebSocketMessage::Close(None)
+                    }
+                }
+                opcode => return Some(Err(Error::other(format!("unknown websocket opcode: {opcode}")))),
+            };
+            return Some(Ok(message));
+        }
+    }
+
+    /// Send close message and wait for the close message from the server.
+    pub async fn close(mut self) -> Result<()> {
+        self.send(WebSocketMessage::Close(Some((1000, String::new())))).await?;
+        while let Some(message) = self.recv().await {
+            if let WebSocketMessage::Close(_) = message? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn read_frame(&mut self) -> Result<Option<(bool, u8, Vec<u8>)>> {
+        loop {
+            if let Some(frame) = decode_frame(&mut self.read_buf)? {
+                return Ok(Some(frame));
+            }
+            if self.io.read_buf(&mut self.read_buf).await? == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+impl RequestBuilder {
+    /// Connect to the [`Service`] and upgrade the request to WebSocket.
+    ///
+    /// Returns error if the service does not respond `101 Switching Protocols`.
+    pub async fn connect(self, service: &Service) -> Result<WebSocketClient> {
+        let req = self.build_hyper();
+        let (client_io, server_io) = tokio::io::duplex(BUFFER_SIZE);
+        let handler = service.hyper_handler(
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5801)).into(),
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5802)).into(),
+            Scheme::HTTP,
+            None,
+        );
+        tokio::spawn(async move {
+            let conn = http1::Builder::new()
+                .serve_connection(TokioIo::new(server_io), handler)
+                .with_upgrades();
+            if let Err(e) = conn.await {
+                tracing::debug!(error = ?e, "test websocket connection error");
+            }
+        });
+
+        let mut io = client_io;
+        let uri = req.uri();
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let mut head = format!("{} {} HTTP/1.1\r\n", req.method(), path);
+        if !req.headers().contains_key(http::header::HOST) {
+            head.push_str(&format!("host: {}\r\n", uri.authority().map(|a| a.as_str()).unwrap_or("localhost")));
+        }
+        for (name, value) in req.headers() {
+            head.push_str(name.as_str());
+            head.push_str(": ");
+            head.push_str(value.to_str().map_err(Error::other)?);
+            head.push_str("\r\n");
+        }
+        head.push_str("\r\n");
+        io.write_all(head.as_bytes()).await?;
+
+        let mut read_buf = BytesMut::with_capacity(BUFFER_SIZE);
+        let head_len = loop {
+            if let Some(pos) = read_buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if io.read_buf(&mut read_buf).await? == 0 {
+                return Err(Error::other("connection closed before websocket handshake completed"));
+            }
+        };
+        let head = read_buf.split_to(head_len);
+        let head = String::from_utf8_lossy(&head);
+        let mut lines = head.split("\r\n");
+        let status_code = lines
+            .next()
+            .and_then(|line| line.split(' ').nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .ok_or_else(|| Error::other("invalid websocket handshake response"))?;
+        let mut headers = HeaderMap::new();
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.trim().as_bytes()),
+                    HeaderValue::from_str(value.trim()),
+                ) {
+                    headers.append(name, value);
+                }
+            }
+        }
+        if status_code != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(Error::other(format!(
+                "websocket handshake failed with status: {status_code}"
+            )));
+        }
+        Ok(WebSocketClient {
+            io,
+            read_buf,
+            status_code,
+            headers,
+            closed: false,
+        })
+    }
+}
+
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    // Frames sent by client must be masked.
+    let mask = [0x37, 0xfa, 0x21, 0x3d];
+    let mut frame = BytesMut::with_capacity(payload.len() + 14);
+    frame.put_u8(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => frame.put_u8(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.put_u8(0x80 | 126);
+            frame.put_u16(len as u16);
+        }
+        len => {
+            frame.put_u8(0x80 | 127);
+            frame.put_u64(len as u64);
+        }
+    }
+    frame.put_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    frame.to_vec()
+}
+
+fn decode_frame(buf: &mut BytesMut) -> Result<Option<(bool, u8, Vec<u8>)>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let fin = buf[0] & 0x80 != 0;
+    let opcode = buf[0] & 0x0F;
+    let masked = buf[1] & 0x80 != 0;
+    let (len, mut offset) = match buf[1] & 0x7F {
+        126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as usize, 4),
+        127 if buf.len() >= 10 => {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&buf[2..10]);
+            let len = usize::try_from(u64::from_be_bytes(bytes)).map_err(Error::other)?;
+            (len, 10)
+        }
+        126 | 127 => return Ok(None),
+        len => (len as usize, 2),
+    };
+    let mask = if masked {
+        if buf.len() < offset + 4 {
+            return Ok(None);
+        }
+        let mask = [buf[offset], buf[offset + 1], buf[offset + 2], buf[offset + 3]];
+        offset += 4;
+        Some(mask)
+    } else {
+        None
+    };
+    if buf.len() < offset + len {
+        return Ok(None);
+    }
+    buf.advance(offset);
+    let mut payload = buf.split_to(len).to_vec();
+    if let Some(mask) = mask {
+        payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+    }
+    Ok(Some((fin, opcode, payload)))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::upgrade::OnUpgrade;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    // Echo server which handles the frames without a websocket library.
+    #[handler]
+    async fn echo(req: &mut Request, res: &mut Response) {
+        let on_upgrade = req.extensions_mut().remove::<OnUpgrade>().unwrap();
+        res.status_code(StatusCode::SWITCHING_PROTOCOLS);
+        res.add_header("connection", "upgrade", true).unwrap();
+        res.add_header("upgrade", "websocket", true).unwrap();
+        tokio::spawn(async move {
+            let mut io = TokioIo::new(on_upgrade.await.unwrap());
+            let mut buf = BytesMut::new();
+            loop {
+                while let Some((_, opcode, payload)) = decode_frame(&mut buf).unwrap() {
+                    let mut frame = vec![0x80 | opcode, payload.len() as u8];
+                    frame.extend_from_slice(&payload);
+                    io.write_all(&frame).await.unwrap();
+                    if opcode == OPCODE_CLOSE {
+                        return;
+                    }
+                }
+                if io.read_buf(&mut buf).await.unwrap() == 0 {
+                    return;
+                }
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client() {
+        let service = Service::new(Router::with_path("ws").goal(echo));
+        let mut client = TestClient::websocket("http://127.0.0.1:5801/ws")
+            .connect(&service)
+            .await
+            .unwrap();
+        assert_eq!(client.status_code(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(client.headers().get("upgrade").unwrap(), "websocket");
+
+        client.send_text("hello").await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("hello".into())
+        );
+        client.send(WebSocketMessage::Ping(b"ping".to_vec())).await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Ping(b"ping".to_vec())
+        );
+        client.close().await.unwrap();
+
+        let result = TestClient::websocket("http://127.0.0.1:5801/none")
+            .connect(&service)
+            .await;
+        assert!(result.unwrap_err().to_string().contains("404"));
+    }
+}
@@ -1,11 +1,144 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream;
+use serde::ser::{SerializeSeq, Serializer as _};
 use serde::Serialize;
+use serde_json::ser::{Formatter, Serializer};
+use serde_json::Value;
+use tokio::sync::mpsc;
 
 use super::Scribe;
+use crate::extract::metadata::RenameRule;
 use crate::http::header::{HeaderValue, CONTENT_TYPE};
 use crate::http::{Response, StatusError};
 
+/// Size of chunks sent by [`JsonArray`].
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Options of JSON serialization used by [`Json`] and [`JsonArray`].
+///
+/// They are set for a whole service by `Service::json_options`, responses of services without them use the
+/// default options: compact output, keys unchanged and `content-type` set to `application/json; charset=utf-8`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct JsonOptions {
+    /// Whether the output is pretty printed.
+    pub pretty: bool,
+    /// Rule used to rename keys of all objects in the output.
+    pub key_case: Option<RenameRule>,
+    /// Whether `charset=utf-8` is added to the `content-type` header.
+    pub charset: bool,
+}
+
+impl Default for JsonOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            key_case: None,
+            charset: true,
+        }
+    }
+}
+
+impl JsonOptions {
+    /// Create a new `JsonOptions` with default values.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets whether the output is pretty printed.
+    #[inline]
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Sets rule used to rename keys of all objects in the output, for example `RenameRule::CamelCase`.
+    #[inline]
+    pub fn key_case(mut self, key_case: impl Into<Option<RenameRule>>) -> Self {
+        self.key_case = key_case.into();
+        self
+    }
+
+    /// Sets whether `charset=utf-8` is added to the `content-type` header.
+    #[inline]
+    pub fn charset(mut self, charset: bool) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Get the options of the service which is serving the response.
+    pub(crate) fn of(res: &Response) -> Arc<JsonOptions> {
+        res.extensions
+            .get::<Arc<JsonOptions>>()
+            .cloned()
+            .unwrap_or_else(|| Arc::new(JsonOptions::default()))
+    }
+
+    fn content_type(&self) -> HeaderValue {
+        if self.charset {
+            HeaderValue::from_static("application/json; charset=utf-8")
+        } else {
+            HeaderValue::from_static("application/json")
+        }
+    }
+
+    /// Serialize `value` to `writer` with these options.
+    pub fn to_writer<W, T>(&self, writer: W, value: &T) -> serde_json::Result<()>
+    where
+        W: Write,
+        T: Serialize + ?Sized,
+    {
+        if self.pretty {
+            self.serialize(&mut Serializer::pretty(writer), value)
+        } else {
+            self.serialize(&mut Serializer::new(writer), value)
+        }
+    }
+
+    /// Serialize `value` to a byte vector with these options.
+    pub fn to_vec<T>(&self, value: &T) -> serde_json::Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut bytes = Vec::with_capacity(128);
+        self.to_writer(&mut bytes, value)?;
+        Ok(bytes)
+    }
+
+    fn serialize<W, F, T>(&self, serializer: &mut Serializer<W, F>, value: &T) -> serde_json::Result<()>
+    where
+        W: Write,
+        F: Formatter,
+        T: Serialize + ?Sized,
+    {
+        match self.key_case {
+            Some(rule) => rename_keys(serde_json::to_value(value)?, rule).serialize(serializer),
+            None => value.serialize(serializer),
+        }
+    }
+}
+
+fn rename_keys(value: Value, rule: RenameRule) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (rule.rename(key), rename_keys(value, rule)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(|item| rename_keys(item, rule)).collect()),
+        value => value,
+    }
+}
+
 /// Write serializable content to response as json content. It will set `content-type` to `application/json; charset=utf-8`.
+///
+/// The output is affected by the [`JsonOptions`] of the service.
 pub struct Json<T>(pub T);
 #[async_trait]
 impl<T> Scribe for Json<T>
@@ -14,12 +147,10 @@ where
 {
     #[inline]
     fn render(self, res: &mut Response) {
-        match serde_json::to_vec(&self.0) {
+        let options = JsonOptions::of(res);
+        match options.to_vec(&self.0) {
             Ok(bytes) => {
-                res.headers_mut().insert(
-                    CONTENT_TYPE,
-                    HeaderValue::from_static("application/json; charset=utf-8"),
-                );
+                res.headers_mut().insert(CONTENT_TYPE, options.content_type());
                 res.write_body(bytes).ok();
             }
             Err(e) => {
@@ -30,6 +161,86 @@ where
     }
 }
 
+/// Write items of an iterator to response as a json array, items are serialized incrementally on a blocking
+/// thread and sent in chunks, so large lists are never fully buffered in memory.
+///
+/// The output is affected by the [`JsonOptions`] of the service. Because the status code and headers are sent
+/// before serialization finishes, an error serializing an item aborts the response body.
+pub struct JsonArray<I>(pub I);
+impl<I> Scribe for JsonArray<I>
+where
+    I: IntoIterator + Send + 'static,
+    I::Item: Serialize,
+{
+    fn render(self, res: &mut Response) {
+        let options = JsonOptions::of(res);
+        let (tx, rx) = mpsc::channel::<io::Result<Bytes>>(2);
+        let items = self.0;
+        res.headers_mut().insert(CONTENT_TYPE, options.content_type());
+        tokio::task::spawn_blocking(move || {
+            let mut writer = ChunkWriter {
+                tx: tx.clone(),
+                buf: Vec::with_capacity(CHUNK_SIZE),
+            };
+            let result = if options.pretty {
+                write_seq(&mut Serializer::pretty(&mut writer), &options, items)
+            } else {
+                write_seq(&mut Serializer::new(&mut writer), &options, items)
+            };
+            let result = result.map_err(io::Error::from).and_then(|_| writer.flush());
+            if let Err(e) = result {
+                tracing::error!(error = ?e, "JsonArray write error");
+                tx.blocking_send(Err(e)).ok();
+            }
+        });
+        res.stream(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        }));
+    }
+}
+
+fn write_seq<W, F, I>(serializer: &mut Serializer<W, F>, options: &JsonOptions, items: I) -> serde_json::Result<()>
+where
+    W: Write,
+    F: Formatter,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    for item in items {
+        match options.key_case {
+            Some(rule) => seq.serialize_element(&rename_keys(serde_json::to_value(&item)?, rule))?,
+            None => seq.serialize_element(&item)?,
+        }
+    }
+    SerializeSeq::end(seq)
+}
+
+/// Buffers output of the serializer and sends it in chunks of [`CHUNK_SIZE`].
+struct ChunkWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    buf: Vec<u8>,
+}
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE)));
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body is closed"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -56,4 +267,42 @@ mod tests {
             "application/json; charset=utf-8"
         );
     }
+
+    #[tokio::test]
+    async fn test_write_json_with_options() {
+        #[derive(Serialize, Debug)]
+        struct User {
+            user_name: String,
+        }
+        #[handler]
+        async fn test() -> Json<User> {
+            Json(User {
+                user_name: "jobs".into(),
+            })
+        }
+
+        let router = Router::new().push(Router::with_path("test").get(test));
+        let service = Service::new(router).json_options(
+            JsonOptions::new()
+                .pretty(true)
+                .key_case(RenameRule::CamelCase)
+                .charset(false),
+        );
+        let mut res = TestClient::get("http://127.0.0.1:5800/test").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "{\n  \"userName\": \"jobs\"\n}");
+        assert_eq!(res.headers().get("content-type").unwrap(), "application/json");
+    }
+
+    #[tokio::test]
+    async fn test_write_json_array() {
+        #[handler]
+        async fn test() -> JsonArray<std::ops::Range<u32>> {
+            JsonArray(0..10_000)
+        }
+
+        let router = Router::new().push(Router::with_path("test").get(test));
+        let mut res = TestClient::get("http://127.0.0.1:5800/test").send(router).await;
+        let items: Vec<u32> = serde_json::from_str(&res.take_string().await.unwrap()).unwrap();
+        assert_eq!(items, (0..10_000).collect::<Vec<_>>());
+    }
 }
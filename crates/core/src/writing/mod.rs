@@ -6,12 +6,12 @@ mod seek;
 mod text;
 
 use http::StatusCode;
-pub use json::Json;
+pub use json::{Json, JsonArray, JsonOptions};
 pub use redirect::Redirect;
 pub use seek::ReadSeeker;
 pub use text::Text;
 
-use crate::http::header::{HeaderValue, CONTENT_TYPE};
+use crate::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use crate::{async_trait, Depot, Request, Response};
 
 /// `Writer` is a trait allows you to implement custom writing logic for different data types.
@@ -122,6 +122,47 @@ impl Scribe for std::convert::Infallible {
     fn render(self, _res: &mut Response) {}
 }
 
+/// Render `T` and then set the status code, so `(StatusCode::CREATED, Json(user))` can be returned from handlers.
+///
+/// The status code is not set if `T` renders an error, such as a serialization failure of [`Json`].
+impl<T> Scribe for (StatusCode, T)
+where
+    T: Scribe,
+{
+    #[inline]
+    fn render(self, res: &mut Response) {
+        let (status_code, scribe) = self;
+        scribe.render(res);
+        if !res.body.is_error() {
+            res.status_code(status_code);
+        }
+    }
+}
+/// Render `T` and then insert the headers, existing headers with the same names are replaced.
+impl<T> Scribe for (HeaderMap, T)
+where
+    T: Scribe,
+{
+    #[inline]
+    fn render(self, res: &mut Response) {
+        let (headers, scribe) = self;
+        scribe.render(res);
+        res.headers_mut().extend(headers);
+    }
+}
+/// Render `T` and then set the status code and insert the headers.
+impl<T> Scribe for (StatusCode, HeaderMap, T)
+where
+    T: Scribe,
+{
+    #[inline]
+    fn render(self, res: &mut Response) {
+        let (status_code, headers, scribe) = self;
+        (status_code, scribe).render(res);
+        res.headers_mut().extend(headers);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -154,4 +195,35 @@ mod tests {
         assert_eq!(res.take_string().await.unwrap(), "hello");
         assert_eq!(res.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
     }
+
+    #[tokio::test]
+    async fn test_write_tuple() {
+        use crate::http::header::{HeaderMap, LOCATION};
+        use crate::writing::Json;
+
+        #[handler]
+        async fn create() -> Result<(StatusCode, HeaderMap, Json<&'static str>), StatusError> {
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, "/users/1".parse().unwrap());
+            Ok((StatusCode::CREATED, headers, Json("created")))
+        }
+        #[handler]
+        async fn conflict() -> Result<Json<&'static str>, (StatusCode, &'static str)> {
+            Err((StatusCode::CONFLICT, "user exists"))
+        }
+
+        let router = Router::new()
+            .push(Router::with_path("create").post(create))
+            .push(Router::with_path("conflict").post(conflict));
+        let service = Service::new(router);
+
+        let mut res = TestClient::post("http://127.0.0.1:5800/create").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "/users/1");
+        assert_eq!(res.take_string().await.unwrap(), r#""created""#);
+
+        let mut res = TestClient::post("http://127.0.0.1:5800/conflict").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+        assert_eq!(res.take_string().await.unwrap(), "user exists");
+    }
 }
@@ -36,8 +36,12 @@
 #![warn(clippy::future_not_send)]
 #![warn(rustdoc::broken_intra_doc_links)]
 
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
 use bytes::{BufMut, BytesMut};
-use salvo_core::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::{self, HeaderName, HeaderValue};
 use salvo_core::http::{Method, Request, Response, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
@@ -84,7 +88,7 @@ where
 /// [`Cors`] middleware which adds headers for [CORS][mdn].
 ///
 /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Cors {
     allow_credentials: AllowCredentials,
     allow_headers: AllowHeaders,
@@ -93,6 +97,20 @@ pub struct Cors {
     expose_headers: ExposeHeaders,
     max_age: MaxAge,
     vary: Vary,
+    skipper: Arc<dyn Skipper>,
+}
+impl Debug for Cors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cors")
+            .field("allow_credentials", &self.allow_credentials)
+            .field("allow_headers", &self.allow_headers)
+            .field("allow_methods", &self.allow_methods)
+            .field("allow_origin", &self.allow_origin)
+            .field("expose_headers", &self.expose_headers)
+            .field("max_age", &self.max_age)
+            .field("vary", &self.vary)
+            .finish()
+    }
 }
 impl Default for Cors {
     #[inline]
@@ -113,6 +131,7 @@ impl Cors {
             expose_headers: Default::default(),
             max_age: Default::default(),
             vary: Default::default(),
+            skipper: Arc::new(none_skipper),
         }
     }
 
@@ -233,6 +252,82 @@ impl Cors {
         self
     }
 
+    /// Sets skipper, no CORS headers are added to responses when it returns `true`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Arc::new(skipper);
+        self
+    }
+
+    /// Create a new `Cors` from config, `enabled` in config is not checked.
+    ///
+    /// An error is returned if `allow_credentials` is combined with `*` in origins, methods or headers, which is not
+    /// allowed by the CORS protocol.
+    #[cfg(feature = "config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_config(config: &salvo_core::config::CorsConfig) -> salvo_core::Result<Self> {
+        use std::str::FromStr;
+
+        fn invalid(kind: &str, value: &str) -> salvo_core::Error {
+            salvo_core::Error::other(format!("invalid cors {kind} `{value}` in config"))
+        }
+        fn header_names(values: &[String]) -> salvo_core::Result<Vec<HeaderName>> {
+            values
+                .iter()
+                .map(|v| HeaderName::from_str(v).map_err(|_| invalid("header", v)))
+                .collect()
+        }
+
+        if config.allow_credentials {
+            let wildcards = [
+                ("origins", &config.allow_origins),
+                ("methods", &config.allow_methods),
+                ("headers", &config.allow_headers),
+                ("expose headers", &config.expose_headers),
+            ];
+            if let Some((kind, _)) = wildcards.iter().find(|(_, values)| values.iter().any(|v| v == "*")) {
+                return Err(salvo_core::Error::other(format!(
+                    "cors `allow_credentials` can not be combined with `*` {kind} in config"
+                )));
+            }
+        }
+        let mut cors = Self::new().allow_credentials(config.allow_credentials);
+        if config.allow_origins.iter().any(|v| v == "*") {
+            cors = cors.allow_origin(Any);
+        } else if !config.allow_origins.is_empty() {
+            let origins = config
+                .allow_origins
+                .iter()
+                .map(|v| HeaderValue::from_str(v).map_err(|_| invalid("origin", v)))
+                .collect::<salvo_core::Result<Vec<_>>>()?;
+            cors = cors.allow_origin(origins);
+        }
+        if config.allow_methods.iter().any(|v| v == "*") {
+            cors = cors.allow_methods(Any);
+        } else if !config.allow_methods.is_empty() {
+            let methods = config
+                .allow_methods
+                .iter()
+                .map(|v| Method::from_str(&v.to_uppercase()).map_err(|_| invalid("method", v)))
+                .collect::<salvo_core::Result<Vec<_>>>()?;
+            cors = cors.allow_methods(methods);
+        }
+        if config.allow_headers.iter().any(|v| v == "*") {
+            cors = cors.allow_headers(Any);
+        } else if !config.allow_headers.is_empty() {
+            cors = cors.allow_headers(header_names(&config.allow_headers)?);
+        }
+        if config.expose_headers.iter().any(|v| v == "*") {
+            cors = cors.expose_headers(Any);
+        } else if !config.expose_headers.is_empty() {
+            cors = cors.expose_headers(header_names(&config.expose_headers)?);
+        }
+        if let Some(max_age) = config.max_age {
+            cors = cors.max_age(max_age);
+        }
+        Ok(cors)
+    }
+
     /// Returns a new `CorsHandler` using current cors settings.
     pub fn into_handler(self) -> CorsHandler {
         self.ensure_usable_cors_rules();
@@ -275,42 +370,48 @@ pub struct CorsHandler(Cors);
 #[async_trait]
 impl Handler for CorsHandler {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.0.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
         let origin = req.headers().get(&header::ORIGIN);
 
-        let mut headers = HeaderMap::new();
-
-        // These headers are applied to both preflight and subsequent regular CORS requests:
+        // Headers are written into the response directly instead of an intermediate `HeaderMap`, so no map is
+        // allocated per request. These headers are applied to both preflight and subsequent regular CORS requests:
         // https://fetch.spec.whatwg.org/#http-responses
-
-        headers.extend(self.0.allow_origin.to_header(origin, req, depot));
-        headers.extend(self.0.allow_credentials.to_header(origin, req, depot));
+        res.insert_headers(
+            self.0
+                .allow_origin
+                .to_header(origin, req, depot)
+                .into_iter()
+                .chain(self.0.allow_credentials.to_header(origin, req, depot)),
+        );
 
         let mut vary_headers = self.0.vary.values();
         if let Some(first) = vary_headers.next() {
-            let mut header = match headers.entry(header::VARY) {
-                header::Entry::Occupied(_) => {
-                    unreachable!("no vary header inserted up to this point")
-                }
-                header::Entry::Vacant(v) => v.insert_entry(first),
-            };
-
+            let headers = res.headers_mut();
+            headers.insert(header::VARY, first);
             for val in vary_headers {
-                header.append(val);
+                headers.append(header::VARY, val);
             }
         }
 
         // Return results immediately upon preflight request
         if req.method() == Method::OPTIONS {
             // These headers are applied only to preflight requests
-            headers.extend(self.0.allow_methods.to_header(origin, req, depot));
-            headers.extend(self.0.allow_headers.to_header(origin, req, depot));
-            headers.extend(self.0.max_age.to_header(origin, req, depot));
+            res.insert_headers(
+                self.0
+                    .allow_methods
+                    .to_header(origin, req, depot)
+                    .into_iter()
+                    .chain(self.0.allow_headers.to_header(origin, req, depot))
+                    .chain(self.0.max_age.to_header(origin, req, depot)),
+            );
             res.status_code = Some(StatusCode::NO_CONTENT);
         } else {
             // This header is applied only to non-preflight requests
-            headers.extend(self.0.expose_headers.to_header(origin, req, depot));
+            res.insert_headers(self.0.expose_headers.to_header(origin, req, depot));
         }
-        res.headers_mut().extend(headers);
         ctrl.call_next(req, depot, res).await;
     }
 }
@@ -329,6 +430,7 @@ pub fn preflight_request_headers() -> impl Iterator<Item = HeaderName> {
 
 #[cfg(test)]
 mod tests {
+    use salvo_core::handler::RequestSkipper;
     use salvo_core::http::header::*;
     use salvo_core::prelude::*;
     use salvo_core::test::TestClient;
@@ -384,4 +486,62 @@ mod tests {
         );
         assert!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).is_none());
     }
+
+    #[tokio::test]
+    async fn test_cors_skipper() {
+        let cors_handler = Cors::new()
+            .allow_origin("https://salvo.rs")
+            .skipper(RequestSkipper::new().path("/healthz"))
+            .into_handler();
+        let router = Router::new()
+            .hoop(cors_handler)
+            .push(Router::with_path("healthz").get(handler::empty()))
+            .push(Router::with_path("hello").get(handler::empty()));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header("Origin", "https://salvo.rs", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "https://salvo.rs");
+        let res = TestClient::get("http://127.0.0.1:5801/healthz")
+            .add_header("Origin", "https://salvo.rs", true)
+            .send(&service)
+            .await;
+        assert!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[cfg(feature = "config")]
+    #[tokio::test]
+    async fn test_cors_from_config() {
+        let config: salvo_core::config::CorsConfig = serde_json::from_value(serde_json::json!({
+            "allow_origins": "https://salvo.rs",
+            "allow_methods": ["get", "post"],
+            "allow_headers": "content-type",
+            "max_age": "10m",
+        }))
+        .unwrap();
+        let cors_handler = Cors::from_config(&config).unwrap().into_handler();
+        let router = Router::new().hoop(cors_handler).options(handler::empty());
+        let res = TestClient::options("https://salvo.rs")
+            .add_header("Origin", "https://salvo.rs", true)
+            .add_header("Access-Control-Request-Method", "POST", true)
+            .send(router)
+            .await;
+        let headers = res.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://salvo.rs");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], "GET,POST");
+        assert_eq!(headers[ACCESS_CONTROL_MAX_AGE], "600");
+
+        let config: salvo_core::config::CorsConfig =
+            serde_json::from_value(serde_json::json!({ "allow_headers": "bad header" })).unwrap();
+        assert!(Cors::from_config(&config).is_err());
+
+        let config: salvo_core::config::CorsConfig = serde_json::from_value(serde_json::json!({
+            "allow_origins": "*",
+            "allow_credentials": true,
+        }))
+        .unwrap();
+        assert!(Cors::from_config(&config).is_err());
+    }
 }
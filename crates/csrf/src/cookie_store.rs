@@ -19,6 +19,10 @@ pub struct CookieStore {
     pub path: String,
     /// CSRF cookie domain.
     pub domain: Option<String>,
+    /// CSRF cookie same site policy.
+    pub same_site: SameSite,
+    /// Whether the CSRF cookie is http only.
+    pub http_only: bool,
 }
 impl Default for CookieStore {
     #[inline]
@@ -35,6 +39,8 @@ impl CookieStore {
             name: "salvo.csrf".into(),
             path: "/".into(),
             domain: None,
+            same_site: SameSite::Strict,
+            http_only: true,
         }
     }
     /// Sets cookie name.
@@ -60,6 +66,20 @@ impl CookieStore {
         self.domain = Some(domain.into());
         self
     }
+
+    /// Sets cookie same site policy, the default is `SameSite::Strict`.
+    ///
+    /// If `SameSite::None` is used, the cookie is always marked as secure, as browsers require.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Sets whether the cookie is http only, the default is `true`.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
 }
 #[async_trait]
 impl CsrfStore for CookieStore {
@@ -83,11 +103,11 @@ impl CsrfStore for CookieStore {
         token: &str,
         proof: &str,
     ) -> Result<(), Self::Error> {
-        let secure = req.uri().scheme() == Some(&Scheme::HTTPS);
+        let secure = req.uri().scheme() == Some(&Scheme::HTTPS) || self.same_site == SameSite::None;
         let expires = cookie::time::OffsetDateTime::now_utc() + self.ttl;
         let cookie_builder = Cookie::build((self.name.clone(), format!("{token}.{proof}")))
-            .http_only(true)
-            .same_site(SameSite::Strict)
+            .http_only(self.http_only)
+            .same_site(self.same_site)
             .path(self.path.clone())
             .secure(secure)
             .expires(Expiration::DateTime(expires));
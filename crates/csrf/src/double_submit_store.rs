@@ -0,0 +1,129 @@
+use cookie::time::Duration;
+use cookie::{Cookie, Expiration, SameSite};
+use salvo_core::http::uri::Scheme;
+use salvo_core::{async_trait, Depot, Error, Request, Response};
+
+use super::{CsrfCipher, CsrfStore};
+
+/// A stateless `CsrfStore` implementation for the double submit cookie pattern.
+///
+/// The token is stored in a cookie which can be read by javascript, and the proof is stored
+/// in another http only cookie. Browser clients, such as single page applications, read the token
+/// from the token cookie and submit it back in a header or a form field, the request is verified
+/// by the proof cookie, so it is not needed to store anything in the server side.
+///
+/// Use a keyed cipher, such as [`HmacCipher`](crate::HmacCipher), to avoid forged cookie pairs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DoubleSubmitCookieStore {
+    /// CSRF cookie ttl.
+    pub ttl: Duration,
+    /// The name of cookie contains token, it can be read by javascript.
+    pub token_name: String,
+    /// The name of http only cookie contains proof.
+    pub proof_name: String,
+    /// CSRF cookie path.
+    pub path: String,
+    /// CSRF cookie domain.
+    pub domain: Option<String>,
+    /// CSRF cookie same site policy.
+    pub same_site: SameSite,
+}
+impl Default for DoubleSubmitCookieStore {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DoubleSubmitCookieStore {
+    /// Create a new `DoubleSubmitCookieStore`.
+    pub fn new() -> Self {
+        Self {
+            ttl: Duration::days(1),
+            token_name: "salvo.csrf.token".into(),
+            proof_name: "salvo.csrf.proof".into(),
+            path: "/".into(),
+            domain: None,
+            same_site: SameSite::Strict,
+        }
+    }
+    /// Sets the name of cookie contains token.
+    pub fn token_name(mut self, name: impl Into<String>) -> Self {
+        self.token_name = name.into();
+        self
+    }
+
+    /// Sets the name of cookie contains proof.
+    pub fn proof_name(mut self, name: impl Into<String>) -> Self {
+        self.proof_name = name.into();
+        self
+    }
+
+    /// Sets cookie ttl.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets cookie path.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets cookie domain.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets cookie same site policy, the default is `SameSite::Strict`.
+    ///
+    /// If `SameSite::None` is used, the cookies are always marked as secure, as browsers require.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    fn build_cookie(&self, name: &str, value: &str, secure: bool, http_only: bool) -> Cookie<'static> {
+        let expires = cookie::time::OffsetDateTime::now_utc() + self.ttl;
+        let cookie_builder = Cookie::build((name.to_owned(), value.to_owned()))
+            .http_only(http_only)
+            .same_site(self.same_site)
+            .path(self.path.clone())
+            .secure(secure)
+            .expires(Expiration::DateTime(expires));
+        if let Some(domain) = &self.domain {
+            cookie_builder.domain(domain.clone()).build()
+        } else {
+            cookie_builder.build()
+        }
+    }
+}
+#[async_trait]
+impl CsrfStore for DoubleSubmitCookieStore {
+    type Error = Error;
+    async fn load<C: CsrfCipher>(&self, req: &mut Request, _depot: &mut Depot, cipher: &C) -> Option<(String, String)> {
+        let token = req.cookie(&self.token_name)?.value().to_owned();
+        let proof = req.cookie(&self.proof_name)?.value().to_owned();
+        if cipher.verify(&token, &proof) {
+            Some((token, proof))
+        } else {
+            None
+        }
+    }
+    async fn save(
+        &self,
+        req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        token: &str,
+        proof: &str,
+    ) -> Result<(), Self::Error> {
+        let secure = req.uri().scheme() == Some(&Scheme::HTTPS) || self.same_site == SameSite::None;
+        res.add_cookie(self.build_cookie(&self.token_name, token, secure, false));
+        res.add_cookie(self.build_cookie(&self.proof_name, proof, secure, true));
+        Ok(())
+    }
+}
@@ -24,6 +24,13 @@ impl HeaderFinder {
         }
     }
 }
+impl Default for HeaderFinder {
+    /// Create new `HeaderFinder` with header name `x-csrf-token`.
+    #[inline]
+    fn default() -> Self {
+        Self::new("x-csrf-token")
+    }
+}
 #[async_trait]
 impl CsrfTokenFinder for HeaderFinder {
     #[inline]
@@ -46,6 +53,13 @@ impl FormFinder {
         }
     }
 }
+impl Default for FormFinder {
+    /// Create new `FormFinder` with field name `csrf_token`.
+    #[inline]
+    fn default() -> Self {
+        Self::new("csrf_token")
+    }
+}
 #[async_trait]
 impl CsrfTokenFinder for FormFinder {
     #[inline]
@@ -68,6 +82,13 @@ impl JsonFinder {
         }
     }
 }
+impl Default for JsonFinder {
+    /// Create new `JsonFinder` with field name `csrf_token`.
+    #[inline]
+    fn default() -> Self {
+        Self::new("csrf_token")
+    }
+}
 #[async_trait]
 impl CsrfTokenFinder for JsonFinder {
     #[inline]
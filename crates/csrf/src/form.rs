@@ -0,0 +1,157 @@
+use std::fmt::{self, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::ParseError;
+use salvo_core::{async_trait, Request};
+use serde::{Deserialize, Deserializer};
+
+use crate::CsrfCipher;
+
+/// Default name of the form field carries csrf token, it is the same as [`FormFinder`](crate::FormFinder)'s default.
+pub const CSRF_FIELD_NAME: &str = "csrf_token";
+
+/// Render a hidden input carries csrf `token`, the field name is [`CSRF_FIELD_NAME`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_csrf::csrf_field;
+///
+/// assert_eq!(csrf_field("abc"), r#"<input type="hidden" name="csrf_token" value="abc">"#);
+/// ```
+#[inline]
+pub fn csrf_field(token: &str) -> String {
+    csrf_field_named(CSRF_FIELD_NAME, token)
+}
+
+/// Render a hidden input carries csrf `token` with the given field name.
+pub fn csrf_field_named(name: &str, token: &str) -> String {
+    format!(
+        r#"<input type="hidden" name="{}" value="{}">"#,
+        escape_attr(name),
+        escape_attr(token)
+    )
+}
+
+fn escape_attr(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Verifies tokens against the proof of current request, inserted into request extensions by [`Csrf`](crate::Csrf).
+#[derive(Clone)]
+pub(crate) struct CsrfVerifier {
+    pub(crate) proof: String,
+    pub(crate) cipher: Arc<dyn CsrfCipher>,
+}
+impl CsrfVerifier {
+    pub(crate) fn verify(&self, token: &str) -> bool {
+        self.cipher.verify(token, &self.proof)
+    }
+}
+
+/// Extract form data as `T` after validating csrf token in field [`CSRF_FIELD_NAME`].
+///
+/// The token is validated even if the request is skipped by [`Csrf`](crate::Csrf), so it can be used to protect
+/// individual handlers. Extraction fails if [`Csrf`](crate::Csrf) is not used before the handler or the token is
+/// missing or invalid.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_csrf::CsrfForm;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Comment {
+///     content: String,
+/// }
+///
+/// #[handler]
+/// async fn create_comment(comment: CsrfForm<Comment>) -> String {
+///     comment.into_inner().content
+/// }
+/// ```
+pub struct CsrfForm<T>(pub T);
+impl<T> CsrfForm<T> {
+    /// Consumes self and returns the form data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for CsrfForm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T> DerefMut for CsrfForm<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl<T> fmt::Debug for CsrfForm<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for CsrfForm<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(CsrfForm)
+    }
+}
+
+#[async_trait]
+impl<'de, T> Extractible<'de> for CsrfForm<T>
+where
+    T: Deserialize<'de> + Send,
+{
+    fn metadata() -> &'de Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    async fn extract(req: &'de mut Request) -> Result<Self, ParseError> {
+        let verifier = req
+            .extensions()
+            .get::<CsrfVerifier>()
+            .cloned()
+            .ok_or_else(|| ParseError::other("`Csrf` middleware should be used before extracting `CsrfForm`"))?;
+        let token = req
+            .form::<String>(CSRF_FIELD_NAME)
+            .await
+            .ok_or_else(|| ParseError::other("csrf token is missing"))?;
+        if !verifier.verify(&token) {
+            tracing::debug!("rejecting form due to invalid or expired CSRF token");
+            return Err(ParseError::other("csrf token is invalid"));
+        }
+        req.parse_form().await.map(CsrfForm)
+    }
+    async fn extract_with_arg(req: &'de mut Request, _arg: &str) -> Result<Self, ParseError> {
+        Self::extract(req).await
+    }
+}
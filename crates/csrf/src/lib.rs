@@ -5,6 +5,14 @@
 //! Data can be saved in Cookies via [`CookieStore`](struct.CookieStore.html) or in session
 //! via [`SessionStore`](struct.SessionStore.html). [`SessionStore`](struct.SessionStore.html) need to work with `salvo-session` crate.
 //!
+//! [`DoubleSubmitCookieStore`](struct.DoubleSubmitCookieStore.html) implements the stateless double submit
+//! cookie pattern, the token cookie can be read by javascript and submitted back in a header, and
+//! [`CsrfTokenHandler`] can be used to issue tokens as json to single page applications.
+//!
+//! For server rendered forms, [`CsrfDepotExt::csrf_field`] renders the hidden input carries the token and
+//! [`CsrfForm`] extracts the form data after validating the token. With feature `template`, the token and the
+//! hidden input are also available in templates rendered by `salvo-template` as `csrf_token` and `csrf_field`.
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -16,15 +24,21 @@
 #![warn(rustdoc::broken_intra_doc_links)]
 
 use std::error::Error as StdError;
+use std::sync::Arc;
 
 mod finder;
+mod form;
 
 pub use finder::{CsrfTokenFinder, FormFinder, HeaderFinder, JsonFinder};
+use form::CsrfVerifier;
+pub use form::{csrf_field, csrf_field_named, CsrfForm, CSRF_FIELD_NAME};
 
 use rand::distributions::Standard;
 use rand::Rng;
 use salvo_core::handler::Skipper;
-use salvo_core::http::{Method, StatusCode};
+use salvo_core::http::header::{HeaderValue, CACHE_CONTROL};
+use salvo_core::http::{Method, StatusCode, StatusError};
+use salvo_core::writing::Json;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 
 #[macro_use]
@@ -35,11 +49,19 @@ cfg_feature! {
 
     mod cookie_store;
     pub use cookie_store::CookieStore;
+    mod double_submit_store;
+    pub use double_submit_store::DoubleSubmitCookieStore;
+    pub use cookie::SameSite;
 
     /// Helper function to create a `CookieStore`.
     pub fn cookie_store<>() -> CookieStore {
         CookieStore::new()
     }
+
+    /// Helper function to create a `DoubleSubmitCookieStore`.
+    pub fn double_submit_cookie_store() -> DoubleSubmitCookieStore {
+        DoubleSubmitCookieStore::new()
+    }
 }
 cfg_feature! {
     #![feature = "session-store"]
@@ -198,6 +220,11 @@ pub trait CsrfCipher: Send + Sync + 'static {
 pub trait CsrfDepotExt {
     /// Get csrf token reference from depot.
     fn csrf_token(&self) -> Option<&String>;
+    /// Render a hidden input carries csrf token for html forms, see [`csrf_field`].
+    #[inline]
+    fn csrf_field(&self) -> Option<String> {
+        self.csrf_token().map(|token| csrf_field(token))
+    }
 }
 
 impl CsrfDepotExt for Depot {
@@ -209,7 +236,7 @@ impl CsrfDepotExt for Depot {
 
 /// Cross-Site Request Forgery (CSRF) protection middleware.
 pub struct Csrf<C, S> {
-    cipher: C,
+    cipher: Arc<C>,
     store: S,
     skipper: Box<dyn Skipper>,
     finders: Vec<Box<dyn CsrfTokenFinder>>,
@@ -220,7 +247,7 @@ impl<C: CsrfCipher, S: CsrfStore> Csrf<C, S> {
     #[inline]
     pub fn new(cipher: C, store: S, finder: impl CsrfTokenFinder) -> Self {
         Self {
-            cipher,
+            cipher: Arc::new(cipher),
             store,
             skipper: Box::new(default_skipper),
             finders: vec![Box::new(finder)],
@@ -258,12 +285,73 @@ impl<C: CsrfCipher, S: CsrfStore> Csrf<C, S> {
     }
 }
 
+/// Handler to issue csrf token as json, such as `{"token": "..."}`.
+///
+/// It is useful for single page applications, they can request the token from this handler
+/// and send it back in the header. This handler should be used after [`Csrf`] middleware.
+#[derive(Clone, Debug)]
+pub struct CsrfTokenHandler {
+    field_name: String,
+}
+impl Default for CsrfTokenHandler {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl CsrfTokenHandler {
+    /// Create a new `CsrfTokenHandler`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            field_name: "token".into(),
+        }
+    }
+    /// Sets the field name of token in json, the default is `token`.
+    #[inline]
+    pub fn field_name(mut self, field_name: impl Into<String>) -> Self {
+        self.field_name = field_name.into();
+        self
+    }
+}
+#[async_trait]
+impl Handler for CsrfTokenHandler {
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        match depot.csrf_token() {
+            Some(token) => {
+                let mut data = serde_json::Map::new();
+                data.insert(self.field_name.clone(), token.clone().into());
+                res.headers_mut()
+                    .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+                res.render(Json(data));
+            }
+            None => {
+                tracing::error!(
+                    "csrf token not found in depot, `Csrf` middleware should be used before `CsrfTokenHandler`"
+                );
+                res.render(StatusError::internal_server_error().brief("CSRF token not found."));
+            }
+        }
+    }
+}
+
+fn insert_token(depot: &mut Depot, token: String) {
+    #[cfg(feature = "template")]
+    {
+        use salvo_template::TemplateDepotExt;
+        let context = depot.template_context_mut();
+        context.insert("csrf_field".into(), csrf_field(&token).into());
+        context.insert("csrf_token".into(), token.clone().into());
+    }
+    depot.insert(CSRF_TOKEN_KEY, token);
+}
+
 #[async_trait]
 impl<C: CsrfCipher, S: CsrfStore> Handler for Csrf<C, S> {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        match self.store.load(req, depot, &self.cipher).await {
+        match self.store.load(req, depot, &*self.cipher).await {
             Some((token, proof)) => {
-                depot.insert(CSRF_TOKEN_KEY, token);
+                insert_token(depot, token);
 
                 if !self.skipper.skipped(req, depot) {
                     if let Some(token) = &self.find_token(req).await {
@@ -283,6 +371,10 @@ impl<C: CsrfCipher, S: CsrfStore> Handler for Csrf<C, S> {
                         return;
                     }
                 }
+                req.extensions_mut().insert(CsrfVerifier {
+                    proof,
+                    cipher: self.cipher.clone(),
+                });
                 ctrl.call_next(req, depot, res).await;
             }
             None => {
@@ -296,7 +388,11 @@ impl<C: CsrfCipher, S: CsrfStore> Handler for Csrf<C, S> {
                         tracing::error!(error = ?e, "salvo csrf token failed");
                     }
                     tracing::debug!("new token: {:?}", token);
-                    depot.insert(CSRF_TOKEN_KEY, token);
+                    insert_token(depot, token);
+                    req.extensions_mut().insert(CsrfVerifier {
+                        proof,
+                        cipher: self.cipher.clone(),
+                    });
                     ctrl.call_next(req, depot, res).await;
                 }
             }
@@ -590,4 +686,154 @@ mod tests {
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::FORBIDDEN);
     }
+
+    #[cfg(feature = "hmac-cipher")]
+    #[tokio::test]
+    async fn test_double_submit_cookie() {
+        let csrf = Csrf::new(
+            HmacCipher::new(*b"01234567012345670123456701234567"),
+            DoubleSubmitCookieStore::new().same_site(SameSite::Lax),
+            HeaderFinder::default(),
+        );
+        let router = Router::new()
+            .hoop(csrf)
+            .post(post_index)
+            .push(Router::with_path("token").get(CsrfTokenHandler::new()));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/token").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.headers().get(CACHE_CONTROL).unwrap(), "no-store");
+        let token_cookie = res.cookie("salvo.csrf.token").unwrap().clone();
+        let proof_cookie = res.cookie("salvo.csrf.proof").unwrap().clone();
+        assert_eq!(token_cookie.http_only(), Some(false));
+        assert_eq!(proof_cookie.http_only(), Some(true));
+        assert_eq!(token_cookie.same_site(), Some(SameSite::Lax));
+        let data = res
+            .take_json::<std::collections::HashMap<String, String>>()
+            .await
+            .unwrap();
+        assert_eq!(data.get("token").unwrap(), token_cookie.value());
+
+        let cookies = format!("{}; {}", token_cookie.stripped(), proof_cookie.stripped());
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("cookie", &cookies, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::FORBIDDEN);
+
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("x-csrf-token", "aGVsbG8", true)
+            .add_header("cookie", &cookies, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::FORBIDDEN);
+
+        let mut res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("x-csrf-token", token_cookie.value(), true)
+            .add_header("cookie", &cookies, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.take_string().await.unwrap(), "POST");
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Comment {
+        content: String,
+    }
+    #[handler]
+    async fn post_comment(comment: CsrfForm<Comment>) -> String {
+        comment.into_inner().content
+    }
+    #[handler]
+    async fn get_field(depot: &mut Depot) -> String {
+        depot.csrf_field().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_csrf_form() {
+        let csrf = Csrf::new(
+            BcryptCipher::new(),
+            CookieStore::new(),
+            HeaderFinder::new("x-csrf-token"),
+        );
+        let router = Router::new().hoop(csrf).get(get_field).post(post_comment);
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801").send(&service).await;
+        let field = res.take_string().await.unwrap();
+        let cookie = res.cookie("salvo.csrf").unwrap();
+        let token = field
+            .strip_prefix(r#"<input type="hidden" name="csrf_token" value=""#)
+            .and_then(|field| field.strip_suffix(r#"">"#))
+            .unwrap();
+
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("x-csrf-token", token, true)
+            .add_header("cookie", cookie.to_string(), true)
+            .form(&[("content", "hello")])
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::BAD_REQUEST);
+
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("x-csrf-token", token, true)
+            .add_header("cookie", cookie.to_string(), true)
+            .form(&[("content", "hello"), ("csrf_token", "invalid")])
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::BAD_REQUEST);
+
+        let mut res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("x-csrf-token", token, true)
+            .add_header("cookie", cookie.to_string(), true)
+            .form(&[("content", "hello"), ("csrf_token", token)])
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+    }
+
+    #[cfg(feature = "template")]
+    #[tokio::test]
+    async fn test_template_context() {
+        use salvo_template::{Template, Templates};
+
+        #[handler]
+        async fn page() -> Template {
+            Template::new("page")
+        }
+        let templates = Templates::new(|_: &str, context: &serde_json::Value| {
+            Ok(format!(
+                "{}|{}",
+                context["csrf_token"].as_str().unwrap(),
+                context["csrf_field"].as_str().unwrap()
+            ))
+        });
+        let csrf = Csrf::new(
+            BcryptCipher::new(),
+            CookieStore::new(),
+            HeaderFinder::new("x-csrf-token"),
+        );
+        let router = Router::new().hoop(csrf).hoop(templates).get(page);
+        let mut res = TestClient::get("http://127.0.0.1:5801").send(router).await;
+        let content = res.take_string().await.unwrap();
+        let (token, field) = content.split_once('|').unwrap();
+        assert_eq!(field, csrf_field(token));
+    }
+
+    #[tokio::test]
+    async fn test_token_handler_without_csrf() {
+        let router = Router::new().get(CsrfTokenHandler::new());
+        let res = TestClient::get("http://127.0.0.1:5801").send(router).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_cookie_store_options() {
+        let store = CookieStore::new().same_site(SameSite::None).http_only(false);
+        assert_eq!(store.same_site, SameSite::None);
+        assert!(!store.http_only);
+    }
 }
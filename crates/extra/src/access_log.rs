@@ -0,0 +1,490 @@
+//! Structured access log middleware.
+//!
+//! Read more: <https://salvo.rs>
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use salvo_core::http::body::Body;
+use salvo_core::http::header::{HeaderName, CONTENT_LENGTH, REFERER, USER_AGENT};
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+pub use crate::trusted_proxies::TrustedProxies;
+
+/// A record of a handled request.
+#[derive(Serialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct AccessRecord {
+    /// The time when the request is received.
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    /// Request id.
+    pub request_id: Option<String>,
+    /// Client ip, resolved by trusted proxies.
+    pub client_ip: Option<IpAddr>,
+    /// Request method.
+    pub method: String,
+    /// Request uri path and query.
+    pub uri: String,
+    /// Route path template of the matched router, such as `/users/<id>`.
+    pub route: Option<String>,
+    /// Http version.
+    pub version: String,
+    /// Response status code.
+    pub status: u16,
+    /// Time spent by handlers.
+    #[serde(serialize_with = "serialize_latency")]
+    pub latency: Duration,
+    /// Request body size, `None` if it is unknown.
+    pub bytes_in: Option<u64>,
+    /// Response body size, `None` if it is unknown, such as streaming body.
+    pub bytes_out: Option<u64>,
+    /// Referer header.
+    pub referer: Option<String>,
+    /// User agent header.
+    pub user_agent: Option<String>,
+}
+
+fn serialize_latency<S: serde::Serializer>(latency: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(latency.as_secs_f64() * 1000.0)
+}
+
+/// Format of access log lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessLogFormat {
+    /// Json object per line, contains all fields of [`AccessRecord`].
+    #[default]
+    Json,
+    /// Common log format.
+    Common,
+    /// Combined log format, it is common log format with referer and user agent.
+    Combined,
+}
+impl AccessLogFormat {
+    /// Format the record to a line, without line break.
+    pub fn format(&self, record: &AccessRecord) -> String {
+        fn or_dash<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_else(|| "-".into())
+        }
+        let common = || {
+            let time = record
+                .time
+                .format(format_description!(
+                    "[day]/[month repr:short]/[year]:[hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+                ))
+                .unwrap_or_default();
+            format!(
+                "{} - - [{}] \"{} {} {}\" {} {}",
+                or_dash(record.client_ip),
+                time,
+                record.method,
+                record.uri,
+                record.version,
+                record.status,
+                or_dash(record.bytes_out),
+            )
+        };
+        match self {
+            Self::Json => serde_json::to_string(record).unwrap_or_default(),
+            Self::Common => common(),
+            Self::Combined => format!(
+                "{} \"{}\" \"{}\"",
+                common(),
+                record.referer.as_deref().unwrap_or("-"),
+                record.user_agent.as_deref().unwrap_or("-")
+            ),
+        }
+    }
+}
+
+/// Sink receives formatted access log lines.
+pub trait AccessLogSink: Send + Sync + 'static {
+    /// Write a line, the line does not contain line break.
+    fn write(&self, record: &AccessRecord, line: &str);
+}
+impl<F> AccessLogSink for F
+where
+    F: Fn(&AccessRecord, &str) + Send + Sync + 'static,
+{
+    #[inline]
+    fn write(&self, record: &AccessRecord, line: &str) {
+        (self)(record, line)
+    }
+}
+
+/// Write access log lines to stdout.
+#[derive(Default, Debug)]
+pub struct StdoutSink;
+impl AccessLogSink for StdoutSink {
+    #[inline]
+    fn write(&self, _record: &AccessRecord, line: &str) {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{line}").ok();
+    }
+}
+
+/// Emit access log lines as `tracing` events with target `salvo::access_log`.
+#[derive(Default, Debug)]
+pub struct TracingSink;
+impl AccessLogSink for TracingSink {
+    #[inline]
+    fn write(&self, record: &AccessRecord, line: &str) {
+        if record.status >= 500 {
+            tracing::error!(target: "salvo::access_log", "{line}");
+        } else {
+            tracing::info!(target: "salvo::access_log", "{line}");
+        }
+    }
+}
+
+/// Write access log lines to file, the file is rotated when its size exceeds `max_size`.
+///
+/// Rotated files are renamed to `<path>.1`, `<path>.2` ... and at most `max_files` rotated files are kept.
+///
+/// Lines are written by a background thread, so requests are not blocked by file io. Lines are dropped with a
+/// warning if the writer falls behind by more than `buffer_size` lines.
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    buffer_size: usize,
+    sender: Mutex<Option<SyncSender<String>>>,
+}
+impl FileSink {
+    /// Create new `FileSink`, the default `max_size` is 100 MiB and the default `max_files` is 5.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_size: 100 * 1024 * 1024,
+            max_files: 5,
+            buffer_size: 4096,
+            sender: Mutex::new(None),
+        }
+    }
+    /// Sets max size of file in bytes.
+    #[inline]
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+    /// Sets max number of rotated files.
+    #[inline]
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+    /// Sets max number of lines waiting to be written, default is 4096.
+    #[inline]
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    fn writer(&self) -> FileWriter {
+        FileWriter {
+            path: self.path.clone(),
+            max_size: self.max_size,
+            max_files: self.max_files,
+            file: None,
+        }
+    }
+
+    /// Starts the writer thread on first use.
+    fn spawn_writer(&self) -> io::Result<SyncSender<String>> {
+        let (sender, receiver) = mpsc::sync_channel::<String>(self.buffer_size);
+        let mut writer = self.writer();
+        thread::Builder::new().name("salvo-access-log".into()).spawn(move || {
+            for line in receiver {
+                if let Err(e) = writer.write_line(&line) {
+                    tracing::error!(error = ?e, path = ?writer.path, "write access log failed");
+                }
+            }
+        })?;
+        Ok(sender)
+    }
+}
+impl AccessLogSink for FileSink {
+    fn write(&self, _record: &AccessRecord, line: &str) {
+        let mut sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+        if sender.is_none() {
+            match self.spawn_writer() {
+                Ok(spawned) => *sender = Some(spawned),
+                Err(e) => {
+                    tracing::error!(error = ?e, path = ?self.path, "spawn access log writer failed");
+                    return;
+                }
+            }
+        }
+        if let Some(tx) = &*sender {
+            match tx.try_send(line.to_owned()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    tracing::warn!(path = ?self.path, "access log writer falls behind, line is dropped");
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    tracing::error!(path = ?self.path, "access log writer stopped");
+                    *sender = None;
+                }
+            }
+        }
+    }
+}
+
+/// Writes lines to file and rotates it, owned by the writer thread of [`FileSink`].
+struct FileWriter {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: Option<(File, u64)>,
+}
+impl FileWriter {
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+        fs::remove_file(self.rotated_path(self.max_files)).ok();
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if let Some((_, size)) = &self.file {
+            if *size > 0 && *size + line.len() as u64 + 1 > self.max_size {
+                self.file = None;
+                self.rotate()?;
+            }
+        }
+        if self.file.is_none() {
+            let opened = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            let size = opened.metadata()?.len();
+            self.file = Some((opened, size));
+        }
+        if let Some((opened, size)) = &mut self.file {
+            writeln!(opened, "{line}")?;
+            *size += line.len() as u64 + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Structured access log middleware.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::access_log::{AccessLog, AccessLogFormat, FileSink};
+///
+/// let access_log = AccessLog::new()
+///     .format(AccessLogFormat::Combined)
+///     .sink(FileSink::new("access.log").max_size(10 * 1024 * 1024));
+/// let router = Router::new().hoop(access_log);
+/// ```
+pub struct AccessLog {
+    format: AccessLogFormat,
+    sink: Box<dyn AccessLogSink>,
+    trusted_proxies: TrustedProxies,
+    request_id_header: HeaderName,
+}
+impl Default for AccessLog {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AccessLog {
+    /// Create new `AccessLog` middleware, it writes json lines to [`TracingSink`] by default.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            format: AccessLogFormat::Json,
+            sink: Box::new(TracingSink),
+            trusted_proxies: TrustedProxies::default(),
+            request_id_header: HeaderName::from_static("x-request-id"),
+        }
+    }
+    /// Sets log format.
+    #[inline]
+    pub fn format(mut self, format: AccessLogFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Sets log sink.
+    #[inline]
+    pub fn sink(mut self, sink: impl AccessLogSink) -> Self {
+        self.sink = Box::new(sink);
+        self
+    }
+    /// Sets trusted proxies used to resolve client ip.
+    #[inline]
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+    /// Sets the header name of request id, the default is `x-request-id`, which is same as `RequestId` middleware.
+    #[inline]
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for AccessLog {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let time = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let now = Instant::now();
+        let bytes_in = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok()?.parse().ok())
+            .or_else(|| req.body().size_hint().exact());
+        ctrl.call_next(req, depot, res).await;
+        let latency = now.elapsed();
+
+        let header = |req: &Request, name| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let record = AccessRecord {
+            time,
+            request_id: header(req, &self.request_id_header).or_else(|| {
+                res.headers()
+                    .get(&self.request_id_header)
+                    .and_then(|v| v.to_str().ok())
+                    .map(ToOwned::to_owned)
+            }),
+            client_ip: self.trusted_proxies.client_ip(req),
+            method: req.method().to_string(),
+            uri: req
+                .uri()
+                .path_and_query()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "/".into()),
+            route: req.matched_path().map(ToOwned::to_owned),
+            version: format!("{:?}", req.version()),
+            status: res.status_code.unwrap_or(StatusCode::OK).as_u16(),
+            latency,
+            bytes_in,
+            bytes_out: res
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok()?.parse().ok())
+                .or_else(|| res.body_mut().size_hint().exact()),
+            referer: header(req, &REFERER),
+            user_agent: header(req, &USER_AGENT),
+        };
+        self.sink.write(&record, &self.format.format(&record));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_access_log() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let records = lines.clone();
+        let router = Router::new()
+            .hoop(
+                AccessLog::new()
+                    .trusted_proxies(TrustedProxies::new(["127.0.0.1".parse().unwrap()]))
+                    .sink(move |_: &AccessRecord, line: &str| records.lock().unwrap().push(line.to_owned())),
+            )
+            .push(Router::with_path("users/<id>").get(hello));
+
+        TestClient::get("http://127.0.0.1:5801/users/12?q=1")
+            .add_header("x-request-id", "abc", true)
+            .add_header("x-forwarded-for", "10.0.0.1, 127.0.0.1", true)
+            .send(router)
+            .await;
+        let line = lines.lock().unwrap().pop().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["request_id"], "abc");
+        assert_eq!(value["route"], "/users/<id>");
+        assert_eq!(value["uri"], "/users/12?q=1");
+        assert_eq!(value["status"], 200);
+        assert_eq!(value["bytes_out"], 5);
+        assert_eq!(value["method"], "GET");
+        assert!(value["latency"].is_number());
+    }
+
+    #[test]
+    fn test_common_format() {
+        let record = AccessRecord {
+            time: OffsetDateTime::from_unix_timestamp(971186136).unwrap(),
+            request_id: None,
+            client_ip: Some("10.0.0.1".parse().unwrap()),
+            method: "GET".into(),
+            uri: "/index.html".into(),
+            route: None,
+            version: "HTTP/1.1".into(),
+            status: 200,
+            latency: Duration::from_millis(3),
+            bytes_in: None,
+            bytes_out: Some(2326),
+            referer: Some("http://example.com".into()),
+            user_agent: None,
+        };
+        assert_eq!(
+            AccessLogFormat::Common.format(&record),
+            r#"10.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /index.html HTTP/1.1" 200 2326"#
+        );
+        assert_eq!(
+            AccessLogFormat::Combined.format(&record),
+            r#"10.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /index.html HTTP/1.1" 200 2326 "http://example.com" "-""#
+        );
+    }
+
+    #[test]
+    fn test_file_sink_rotation() {
+        let dir = std::env::temp_dir().join(format!("salvo-access-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("access.log");
+        let mut writer = FileSink::new(&path).max_size(10).max_files(2).writer();
+        for line in ["line-1", "line-2", "line-3", "line-4"] {
+            writer.write_line(line).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line-4\n");
+        assert_eq!(fs::read_to_string(dir.join("access.log.1")).unwrap(), "line-3\n");
+        assert_eq!(fs::read_to_string(dir.join("access.log.2")).unwrap(), "line-2\n");
+        assert!(!dir.join("access.log.3").exists());
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -1,11 +1,15 @@
 //! affix middleware is used to add any data to depot.
 //!
+//! Values injected by affix can be got by [`State`](salvo_core::extract::State) extractor in handlers,
+//! and several values of the same type can be injected with different [`TypedKey`]s.
+//!
 //! Read more: <https://salvo.rs>
 
 use std::any::TypeId;
 
 use salvo_core::handler;
 use salvo_core::prelude::*;
+use salvo_core::TypedKey;
 
 trait Affix {
     fn attach(&self, depot: &mut Depot);
@@ -25,12 +29,31 @@ where
     }
 }
 
+struct TypedAffixCell<V> {
+    key: TypedKey<V>,
+    value: V,
+}
+impl<T> Affix for TypedAffixCell<T>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    fn attach(&self, depot: &mut Depot) {
+        depot.insert_typed(&self.key, self.value.clone());
+    }
+}
+
 /// Inject a value into depot.
 #[inline]
 pub fn inject<V: Send + Sync + Clone + 'static>(value: V) -> AffixList {
     insert(format!("{:?}", TypeId::of::<V>()), value)
 }
 
+/// Inject a value into depot with typed key.
+#[inline]
+pub fn inject_keyed<V: Send + Sync + Clone + 'static>(key: TypedKey<V>, value: V) -> AffixList {
+    AffixList::new().inject_keyed(key, value)
+}
+
 /// Insert a key-value pair into depot.
 #[inline]
 pub fn insert<K, V>(key: K, value: V) -> AffixList
@@ -56,6 +79,12 @@ impl AffixList {
         self.insert(format!("{:?}", TypeId::of::<V>()), value)
     }
 
+    /// Inject a value into depot with typed key, the value can be got by [`Depot::get_typed`].
+    pub fn inject_keyed<V: Send + Sync + Clone + 'static>(mut self, key: TypedKey<V>, value: V) -> Self {
+        self.0.push(Box::new(TypedAffixCell { key, value }));
+        self
+    }
+
     /// Insert a key-value pair into depot.
     pub fn insert<K, V>(mut self, key: K, value: V) -> Self
     where
@@ -106,4 +135,35 @@ mod tests {
             .await;
         assert_eq!(content.unwrap(), "salvo:powerful");
     }
+
+    #[tokio::test]
+    async fn test_affix_state() {
+        const PRIMARY: TypedKey<&'static str> = TypedKey::new("primary");
+        const REPLICA: TypedKey<&'static str> = TypedKey::new("replica");
+
+        #[handler]
+        async fn hello(user: salvo_core::extract::State<Arc<User>>, depot: &mut Depot) -> String {
+            format!(
+                "{}:{}:{}",
+                user.name,
+                depot.get_typed(&PRIMARY).unwrap(),
+                depot.get_typed(&REPLICA).unwrap()
+            )
+        }
+        let user = User {
+            name: "salvo".to_string(),
+        };
+        let router = Router::with_hoop(
+            inject(Arc::new(user))
+                .inject_keyed(PRIMARY, "db1")
+                .inject_keyed(REPLICA, "db2"),
+        )
+        .goal(hello);
+        let content = TestClient::get("http://127.0.0.1:5800/")
+            .send(router)
+            .await
+            .take_string()
+            .await;
+        assert_eq!(content.unwrap(), "salvo:db1:db2");
+    }
 }
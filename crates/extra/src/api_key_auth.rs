@@ -0,0 +1,417 @@
+//! API key auth middleware.
+//!
+//! [`ApiKeyAuth`] finds the API key in request header, query or cookie and resolves it to a principal and its
+//! scopes with an [`ApiKeyResolver`]. Missing keys are rejected with `401 Unauthorized`, invalid keys and keys
+//! without required scopes are rejected with `403 Forbidden`, the same as `JwtAuth`.
+//!
+//! Keys should be saved hashed with [`hash_api_key`], so leaked storage does not leak usable keys.
+//!
+//! Read more: <https://salvo.rs>
+use std::collections::HashMap;
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::HeaderName;
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::{Digest, Sha256};
+
+/// key used to insert api key principal to depot.
+pub const API_KEY_PRINCIPAL_KEY: &str = "::salvo::api_key_auth::principal";
+/// key used to insert api key scopes to depot.
+pub const API_KEY_SCOPES_KEY: &str = "::salvo::api_key_auth::scopes";
+/// key used to insert api key auth state to depot.
+pub const API_KEY_STATE_KEY: &str = "::salvo::api_key_auth::state";
+
+/// Hash API key with SHA256 and returns it as lowercase hex string.
+///
+/// Save hashed keys instead of the keys themselves, and look up resolved keys by their hash.
+#[inline]
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Where to find the API key in request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApiKeySource {
+    /// Find the key in request header.
+    Header(HeaderName),
+    /// Find the key in query parameter.
+    Query(String),
+    /// Find the key in cookie.
+    Cookie(String),
+}
+impl ApiKeySource {
+    fn find(&self, req: &Request) -> Option<String> {
+        let key = match self {
+            Self::Header(name) => req.header::<String>(name),
+            Self::Query(name) => req.query::<String>(name),
+            Self::Cookie(name) => req.cookie(name).map(|c| c.value().to_owned()),
+        };
+        key.filter(|key| !key.is_empty())
+    }
+}
+
+/// The resolved principal of API key and its scopes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiKeyIdentity<P> {
+    /// The principal the key belongs to.
+    pub principal: P,
+    /// Scopes granted to the key.
+    pub scopes: Vec<String>,
+}
+impl<P> ApiKeyIdentity<P> {
+    /// Create a new `ApiKeyIdentity`.
+    #[inline]
+    pub fn new<I, S>(principal: P, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            principal,
+            scopes: scopes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// ApiKeyResolver resolves API key to its identity.
+#[async_trait]
+pub trait ApiKeyResolver: Send + Sync {
+    /// The principal type.
+    type Principal: Send + Sync + 'static;
+    /// Resolve `key`, returns `None` if the key is not valid.
+    async fn resolve(&self, key: &str, depot: &mut Depot) -> Option<ApiKeyIdentity<Self::Principal>>;
+}
+
+/// [`ApiKeyResolver`] with a fixed set of keys, keys are saved hashed.
+#[derive(Clone, Debug)]
+pub struct StaticApiKeys<P> {
+    keys: HashMap<String, ApiKeyIdentity<P>>,
+}
+impl<P> Default for StaticApiKeys<P> {
+    #[inline]
+    fn default() -> Self {
+        Self { keys: HashMap::new() }
+    }
+}
+impl<P> StaticApiKeys<P>
+where
+    P: Clone + Send + Sync + 'static,
+{
+    /// Create a new empty `StaticApiKeys`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a plain key.
+    #[inline]
+    pub fn add(self, key: &str, identity: ApiKeyIdentity<P>) -> Self {
+        self.add_hashed(hash_api_key(key), identity)
+    }
+    /// Add a key hashed by [`hash_api_key`].
+    #[inline]
+    pub fn add_hashed(mut self, hash: impl Into<String>, identity: ApiKeyIdentity<P>) -> Self {
+        self.keys.insert(hash.into().to_ascii_lowercase(), identity);
+        self
+    }
+}
+#[async_trait]
+impl<P> ApiKeyResolver for StaticApiKeys<P>
+where
+    P: Clone + Send + Sync + 'static,
+{
+    type Principal = P;
+    async fn resolve(&self, key: &str, _depot: &mut Depot) -> Option<ApiKeyIdentity<P>> {
+        self.keys.get(&hash_api_key(key)).cloned()
+    }
+}
+
+/// ApiKeyAuthState
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ApiKeyAuthState {
+    /// Authorized.
+    Authorized,
+    /// Unauthorized, no key is found.
+    Unauthorized,
+    /// Forbidden, the key is invalid or has no required scopes.
+    Forbidden,
+}
+
+/// ApiKeyAuthDepotExt
+pub trait ApiKeyAuthDepotExt {
+    /// Get api key auth state from depot.
+    fn api_key_auth_state(&self) -> ApiKeyAuthState;
+    /// Get api key principal reference from depot.
+    fn api_key_principal<P: Send + Sync + 'static>(&self) -> Option<&P>;
+    /// Get api key scopes from depot.
+    fn api_key_scopes(&self) -> &[String];
+    /// Returns `true` if the api key has `scope`.
+    #[inline]
+    fn has_api_key_scope(&self, scope: &str) -> bool {
+        self.api_key_scopes().iter().any(|s| s == scope)
+    }
+}
+impl ApiKeyAuthDepotExt for Depot {
+    #[inline]
+    fn api_key_auth_state(&self) -> ApiKeyAuthState {
+        self.get(API_KEY_STATE_KEY)
+            .ok()
+            .cloned()
+            .unwrap_or(ApiKeyAuthState::Unauthorized)
+    }
+    #[inline]
+    fn api_key_principal<P: Send + Sync + 'static>(&self) -> Option<&P> {
+        self.get(API_KEY_PRINCIPAL_KEY).ok()
+    }
+    #[inline]
+    fn api_key_scopes(&self) -> &[String] {
+        self.get::<Vec<String>>(API_KEY_SCOPES_KEY)
+            .map(|scopes| &scopes[..])
+            .unwrap_or_default()
+    }
+}
+
+/// ApiKeyAuth, used as middleware.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::api_key_auth::{hash_api_key, ApiKeyAuth, ApiKeyAuthDepotExt, ApiKeyIdentity, StaticApiKeys};
+///
+/// #[handler]
+/// async fn hello(depot: &mut Depot) -> String {
+///     format!("Hello, {}", depot.api_key_principal::<String>().unwrap())
+/// }
+///
+/// let keys = StaticApiKeys::new().add_hashed(
+///     hash_api_key("secret-key"),
+///     ApiKeyIdentity::new("alice".to_owned(), ["read"]),
+/// );
+/// let auth = ApiKeyAuth::new(keys).query("api_key").required_scopes(["read"]);
+/// let router = Router::new().hoop(auth).get(hello);
+/// ```
+#[non_exhaustive]
+pub struct ApiKeyAuth<R> {
+    /// Only write auth state to depot when set to `true`.
+    ///
+    /// **Note**: If you set to `true`, you must handle auth state in next middlewares or handler.
+    pub force_passed: bool,
+    /// The resolver.
+    pub resolver: R,
+    /// Sources the key is found in, they are checked in order.
+    pub sources: Vec<ApiKeySource>,
+    /// Scopes the key must have.
+    pub required_scopes: Vec<String>,
+    /// Requests are passed without api key when skipper returns `true`.
+    pub skipper: Box<dyn Skipper>,
+}
+impl<R> ApiKeyAuth<R>
+where
+    R: ApiKeyResolver,
+{
+    /// Create new `ApiKeyAuth`, the key is found in `x-api-key` header by default.
+    #[inline]
+    pub fn new(resolver: R) -> Self {
+        Self {
+            force_passed: false,
+            resolver,
+            sources: vec![ApiKeySource::Header(HeaderName::from_static("x-api-key"))],
+            required_scopes: vec![],
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Sets force_passed value and return Self.
+    #[inline]
+    pub fn force_passed(mut self, force_passed: bool) -> Self {
+        self.force_passed = force_passed;
+        self
+    }
+    /// Sets sources list with new value and return Self.
+    #[inline]
+    pub fn sources(mut self, sources: Vec<ApiKeySource>) -> Self {
+        self.sources = sources;
+        self
+    }
+    /// Add a header source.
+    #[inline]
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.sources.push(ApiKeySource::Header(name));
+        self
+    }
+    /// Add a query parameter source.
+    #[inline]
+    pub fn query(mut self, name: impl Into<String>) -> Self {
+        self.sources.push(ApiKeySource::Query(name.into()));
+        self
+    }
+    /// Add a cookie source.
+    #[inline]
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        self.sources.push(ApiKeySource::Cookie(name.into()));
+        self
+    }
+    /// Sets scopes the key must have.
+    #[inline]
+    pub fn required_scopes<I, S>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Sets skipper and return Self.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
+    fn find_key(&self, req: &Request) -> Option<String> {
+        self.sources.iter().find_map(|source| source.find(req))
+    }
+}
+
+#[async_trait]
+impl<R> Handler for ApiKeyAuth<R>
+where
+    R: ApiKeyResolver + 'static,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let Some(key) = self.find_key(req) else {
+            depot.insert(API_KEY_STATE_KEY, ApiKeyAuthState::Unauthorized);
+            if !self.force_passed {
+                res.render(StatusError::unauthorized());
+                ctrl.skip_rest();
+            }
+            return;
+        };
+        let state = match self.resolver.resolve(&key, depot).await {
+            Some(identity) => {
+                let granted = self
+                    .required_scopes
+                    .iter()
+                    .all(|required| identity.scopes.contains(required));
+                depot.insert(API_KEY_PRINCIPAL_KEY, identity.principal);
+                depot.insert(API_KEY_SCOPES_KEY, identity.scopes);
+                if granted {
+                    ApiKeyAuthState::Authorized
+                } else {
+                    tracing::info!("api key has no required scopes");
+                    ApiKeyAuthState::Forbidden
+                }
+            }
+            None => {
+                tracing::info!("invalid api key");
+                ApiKeyAuthState::Forbidden
+            }
+        };
+        depot.insert(API_KEY_STATE_KEY, state);
+        if state == ApiKeyAuthState::Forbidden && !self.force_passed {
+            res.render(StatusError::forbidden());
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::COOKIE;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        format!(
+            "{} {}",
+            depot.api_key_principal::<String>().unwrap(),
+            depot.has_api_key_scope("write")
+        )
+    }
+
+    #[test]
+    fn test_hash_api_key() {
+        assert_eq!(
+            hash_api_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth() {
+        let keys = StaticApiKeys::new()
+            .add("reader-key", ApiKeyIdentity::new("reader".to_owned(), ["read"]))
+            .add_hashed(
+                hash_api_key("writer-key").to_uppercase(),
+                ApiKeyIdentity::new("writer".to_owned(), ["read", "write"]),
+            )
+            .add("guest-key", ApiKeyIdentity::new("guest".to_owned(), Vec::<String>::new()));
+        let auth = ApiKeyAuth::new(keys)
+            .query("api_key")
+            .cookie("api_key")
+            .required_scopes(["read"]);
+        let service = Service::new(Router::new().hoop(auth).goal(hello));
+
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-key", "wrong", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+
+        let res = TestClient::get("http://127.0.0.1:5801/?api_key=guest-key")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-key", "reader-key", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "reader false");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/?api_key=writer-key")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "writer true");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(COOKIE, "api_key=writer-key", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "writer true");
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth_force_passed() {
+        #[handler]
+        async fn state(depot: &mut Depot) -> String {
+            format!("{:?}", depot.api_key_auth_state())
+        }
+        let keys = StaticApiKeys::new().add("key", ApiKeyIdentity::new((), ["read"]));
+        let auth = ApiKeyAuth::new(keys).force_passed(true);
+        let service = Service::new(Router::new().hoop(auth).goal(state));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "Unauthorized");
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-key", "bad", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "Forbidden");
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-key", "key", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "Authorized");
+    }
+}
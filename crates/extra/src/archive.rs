@@ -0,0 +1,685 @@
+//! Stream ZIP or TAR archives assembled on the fly.
+//!
+//! [`ArchiveStream`] reads entries from a stream of `(name, reader)` pairs or [`ArchiveEntry`] values and writes
+//! the archive to the response while the entries are read, nothing is buffered on disk. The response has no
+//! content length, so it is sent with chunked encoding on HTTP/1.1.
+//!
+//! ZIP archives use data descriptors, since sizes and checksums are only known after an entry is read. ZIP64 is
+//! not supported, so entries and the whole archive must be smaller than 4GB. TAR entries need their size in the
+//! header, entries without a known size are read into memory before they are written.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::stream;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::archive::ArchiveStream;
+//!
+//! #[handler]
+//! async fn download_all(res: &mut Response) {
+//!     let mut entries = Vec::new();
+//!     for name in ["a.txt", "b.txt"] {
+//!         if let Ok(file) = tokio::fs::File::open(format!("attachments/{name}")).await {
+//!             entries.push((name, file));
+//!         }
+//!     }
+//!     res.render(ArchiveStream::zip(stream::iter(entries)).file_name("attachments.zip"));
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("attachments.zip").get(download_all);
+//!     let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::pin::Pin;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use salvo_core::http::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+use salvo_core::http::Response;
+use salvo_core::Scribe;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+const TAR_BLOCK_SIZE: usize = 512;
+
+/// Compression method of ZIP entries.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ZipCompression {
+    /// Entries are stored without compression.
+    Stored,
+    /// Entries are compressed with deflate.
+    #[default]
+    Deflated,
+}
+
+/// Format of the archive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchiveFormat {
+    /// ZIP archive with the given compression method.
+    Zip(ZipCompression),
+    /// Uncompressed TAR archive.
+    Tar,
+}
+
+impl ArchiveFormat {
+    /// Content type of the archive.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Zip(_) => "application/zip",
+            Self::Tar => "application/x-tar",
+        }
+    }
+}
+
+/// An entry of the archive.
+pub struct ArchiveEntry {
+    name: String,
+    reader: Pin<Box<dyn AsyncRead + Send>>,
+    size: Option<u64>,
+    modified: Option<SystemTime>,
+}
+
+impl ArchiveEntry {
+    /// Create a new `ArchiveEntry` with the path of the entry in the archive and the reader of its content.
+    pub fn new(name: impl Into<String>, reader: impl AsyncRead + Send + 'static) -> Self {
+        Self {
+            name: name.into(),
+            reader: Box::pin(reader),
+            size: None,
+            modified: None,
+        }
+    }
+
+    /// Sets the size of the content, TAR entries with a known size are streamed instead of read into memory.
+    ///
+    /// Writing the archive fails if the reader does not return exactly this number of bytes.
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Sets the modification time of the entry, default is the time the entry is written.
+    pub fn modified(mut self, modified: SystemTime) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+}
+
+impl Debug for ArchiveEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveEntry")
+            .field("name", &self.name)
+            .field("size", &self.size)
+            .field("modified", &self.modified)
+            .finish()
+    }
+}
+
+impl<N, R> From<(N, R)> for ArchiveEntry
+where
+    N: Into<String>,
+    R: AsyncRead + Send + 'static,
+{
+    fn from((name, reader): (N, R)) -> Self {
+        Self::new(name, reader)
+    }
+}
+
+/// An archive written to the response while its entries are read.
+pub struct ArchiveStream {
+    format: ArchiveFormat,
+    entries: BoxStream<'static, ArchiveEntry>,
+    file_name: Option<String>,
+}
+
+impl ArchiveStream {
+    /// Create a new `ArchiveStream` with the given format and entries.
+    pub fn new<S, E>(format: ArchiveFormat, entries: S) -> Self
+    where
+        S: Stream<Item = E> + Send + 'static,
+        E: Into<ArchiveEntry> + 'static,
+    {
+        Self {
+            format,
+            entries: entries.map(Into::into).boxed(),
+            file_name: None,
+        }
+    }
+
+    /// Create a new ZIP archive, entries are compressed with deflate.
+    pub fn zip<S, E>(entries: S) -> Self
+    where
+        S: Stream<Item = E> + Send + 'static,
+        E: Into<ArchiveEntry> + 'static,
+    {
+        Self::new(ArchiveFormat::Zip(ZipCompression::Deflated), entries)
+    }
+
+    /// Create a new TAR archive.
+    pub fn tar<S, E>(entries: S) -> Self
+    where
+        S: Stream<Item = E> + Send + 'static,
+        E: Into<ArchiveEntry> + 'static,
+    {
+        Self::new(ArchiveFormat::Tar, entries)
+    }
+
+    /// Sets the file name, the response is sent as an attachment with this name.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Convert the archive into a stream of bytes.
+    pub fn into_stream(self) -> impl Stream<Item = IoResult<Vec<u8>>> + Send + 'static {
+        let writer = ArchiveWriter {
+            format: self.format,
+            entries: self.entries,
+            current: None,
+            offset: 0,
+            central: Vec::new(),
+            count: 0,
+            buffer: vec![0; READ_BUFFER_SIZE],
+            finished: false,
+            done: false,
+        };
+        stream::unfold(writer, |mut writer| async move {
+            let chunk = writer.next_chunk().await?;
+            Some((chunk, writer))
+        })
+    }
+}
+
+impl Debug for ArchiveStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveStream")
+            .field("format", &self.format)
+            .field("file_name", &self.file_name)
+            .finish()
+    }
+}
+
+impl Scribe for ArchiveStream {
+    fn render(mut self, res: &mut Response) {
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(self.format.content_type()));
+        if let Some(file_name) = self.file_name.take() {
+            match HeaderValue::from_str(&format!("attachment; filename=\"{}\"", file_name.replace('"', "\\\""))) {
+                Ok(value) => {
+                    res.headers_mut().insert(CONTENT_DISPOSITION, value);
+                }
+                Err(e) => {
+                    tracing::warn!(error = ?e, %file_name, "invalid archive file name");
+                }
+            }
+        }
+        res.stream(self.into_stream().map(|chunk| {
+            chunk.map_err(|e| {
+                tracing::error!(error = ?e, "write archive failed");
+                e
+            })
+        }));
+    }
+}
+
+struct CurrentEntry {
+    reader: Pin<Box<dyn AsyncRead + Send>>,
+    name: String,
+    header_offset: u64,
+    dos_time: (u16, u16),
+    crc: Crc,
+    size: u64,
+    compressed: u64,
+    encoder: Option<DeflateEncoder<Vec<u8>>>,
+    expected_size: Option<u64>,
+}
+
+struct ArchiveWriter {
+    format: ArchiveFormat,
+    entries: BoxStream<'static, ArchiveEntry>,
+    current: Option<CurrentEntry>,
+    // Bytes written so far, used for offsets in the ZIP central directory.
+    offset: u64,
+    central: Vec<u8>,
+    count: usize,
+    buffer: Vec<u8>,
+    finished: bool,
+    done: bool,
+}
+
+impl ArchiveWriter {
+    async fn next_chunk(&mut self) -> Option<IoResult<Vec<u8>>> {
+        if self.done {
+            return None;
+        }
+        match self.advance().await {
+            Ok(Some(chunk)) => {
+                self.offset += chunk.len() as u64;
+                Some(Ok(chunk))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    async fn advance(&mut self) -> IoResult<Option<Vec<u8>>> {
+        loop {
+            if let Some(current) = &mut self.current {
+                let len = current.reader.read(&mut self.buffer).await?;
+                if len == 0 {
+                    let current = self.current.take().expect("current entry should exist");
+                    let chunk = self.finish_entry(current)?;
+                    if chunk.is_empty() {
+                        continue;
+                    }
+                    return Ok(Some(chunk));
+                }
+                let data = &self.buffer[..len];
+                current.size += len as u64;
+                let chunk = match &mut current.encoder {
+                    Some(encoder) => {
+                        encoder.write_all(data)?;
+                        std::mem::take(encoder.get_mut())
+                    }
+                    None => data.to_vec(),
+                };
+                current.crc.update(data);
+                current.compressed += chunk.len() as u64;
+                if matches!(current.expected_size, Some(expected) if current.size > expected) {
+                    return Err(IoError::new(
+                        ErrorKind::InvalidData,
+                        format!("archive entry `{}` is larger than its size", current.name),
+                    ));
+                }
+                if chunk.is_empty() {
+                    continue;
+                }
+                return Ok(Some(chunk));
+            }
+            if self.finished {
+                return Ok(None);
+            }
+            match self.entries.next().await {
+                Some(entry) => return self.start_entry(entry).await.map(Some),
+                None => {
+                    self.finished = true;
+                    return self.trailer().map(Some);
+                }
+            }
+        }
+    }
+
+    async fn start_entry(&mut self, mut entry: ArchiveEntry) -> IoResult<Vec<u8>> {
+        let name = entry.name.trim_start_matches('/').replace('\\', "/");
+        let modified = entry.modified.unwrap_or_else(SystemTime::now);
+        self.count += 1;
+        match self.format {
+            ArchiveFormat::Zip(compression) => {
+                let dos_time = dos_date_time(modified);
+                let header = zip_local_header(&name, compression, dos_time);
+                self.current = Some(CurrentEntry {
+                    reader: entry.reader,
+                    name,
+                    header_offset: self.offset,
+                    dos_time,
+                    crc: Crc::new(),
+                    size: 0,
+                    compressed: 0,
+                    encoder: (compression == ZipCompression::Deflated)
+                        .then(|| DeflateEncoder::new(Vec::new(), Compression::default())),
+                    expected_size: entry.size,
+                });
+                Ok(header)
+            }
+            ArchiveFormat::Tar => {
+                let mtime = modified.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+                match entry.size {
+                    Some(size) => {
+                        self.current = Some(CurrentEntry {
+                            reader: entry.reader,
+                            name: name.clone(),
+                            header_offset: self.offset,
+                            dos_time: (0, 0),
+                            crc: Crc::new(),
+                            size: 0,
+                            compressed: 0,
+                            encoder: None,
+                            expected_size: Some(size),
+                        });
+                        Ok(tar_headers(&name, size, mtime))
+                    }
+                    None => {
+                        let mut data = Vec::new();
+                        entry.reader.read_to_end(&mut data).await?;
+                        let mut chunk = tar_headers(&name, data.len() as u64, mtime);
+                        chunk.extend_from_slice(&data);
+                        pad_tar_block(&mut chunk, data.len() as u64);
+                        Ok(chunk)
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish_entry(&mut self, mut current: CurrentEntry) -> IoResult<Vec<u8>> {
+        if let Some(expected) = current.expected_size {
+            if current.size != expected {
+                return Err(IoError::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("archive entry `{}` is smaller than its size", current.name),
+                ));
+            }
+        }
+        match self.format {
+            ArchiveFormat::Zip(compression) => {
+                let mut chunk = match current.encoder.take() {
+                    Some(encoder) => encoder.finish()?,
+                    None => Vec::new(),
+                };
+                current.compressed += chunk.len() as u64;
+                let crc = current.crc.sum();
+                let size = zip32(current.size)?;
+                let compressed = zip32(current.compressed)?;
+                let header_offset = zip32(current.header_offset)?;
+
+                // Data descriptor.
+                chunk.extend_from_slice(&0x08074b50u32.to_le_bytes());
+                chunk.extend_from_slice(&crc.to_le_bytes());
+                chunk.extend_from_slice(&compressed.to_le_bytes());
+                chunk.extend_from_slice(&size.to_le_bytes());
+
+                // Central directory header.
+                let central = &mut self.central;
+                central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+                central.extend_from_slice(&20u16.to_le_bytes());
+                central.extend_from_slice(&20u16.to_le_bytes());
+                central.extend_from_slice(&ZIP_FLAGS.to_le_bytes());
+                central.extend_from_slice(&zip_method(compression).to_le_bytes());
+                central.extend_from_slice(&current.dos_time.0.to_le_bytes());
+                central.extend_from_slice(&current.dos_time.1.to_le_bytes());
+                central.extend_from_slice(&crc.to_le_bytes());
+                central.extend_from_slice(&compressed.to_le_bytes());
+                central.extend_from_slice(&size.to_le_bytes());
+                central.extend_from_slice(&(current.name.len() as u16).to_le_bytes());
+                // Extra field length, comment length, disk number, internal and external attributes.
+                central.extend_from_slice(&[0; 12]);
+                central.extend_from_slice(&header_offset.to_le_bytes());
+                central.extend_from_slice(current.name.as_bytes());
+                Ok(chunk)
+            }
+            ArchiveFormat::Tar => {
+                let mut chunk = Vec::new();
+                pad_tar_block(&mut chunk, current.size);
+                Ok(chunk)
+            }
+        }
+    }
+
+    fn trailer(&mut self) -> IoResult<Vec<u8>> {
+        match self.format {
+            ArchiveFormat::Zip(_) => {
+                let count = u16::try_from(self.count)
+                    .map_err(|_| IoError::other("too many entries for a zip archive"))?;
+                let central_offset = zip32(self.offset)?;
+                let mut chunk = std::mem::take(&mut self.central);
+                let central_size = zip32(chunk.len() as u64)?;
+                chunk.extend_from_slice(&0x06054b50u32.to_le_bytes());
+                chunk.extend_from_slice(&[0; 4]);
+                chunk.extend_from_slice(&count.to_le_bytes());
+                chunk.extend_from_slice(&count.to_le_bytes());
+                chunk.extend_from_slice(&central_size.to_le_bytes());
+                chunk.extend_from_slice(&central_offset.to_le_bytes());
+                chunk.extend_from_slice(&[0; 2]);
+                Ok(chunk)
+            }
+            ArchiveFormat::Tar => Ok(vec![0; TAR_BLOCK_SIZE * 2]),
+        }
+    }
+}
+
+// Sizes and checksums are written in the data descriptor, file names are encoded in UTF-8.
+const ZIP_FLAGS: u16 = 0x0008 | 0x0800;
+
+fn zip_method(compression: ZipCompression) -> u16 {
+    match compression {
+        ZipCompression::Stored => 0,
+        ZipCompression::Deflated => 8,
+    }
+}
+
+fn zip32(value: u64) -> IoResult<u32> {
+    u32::try_from(value).map_err(|_| IoError::other("zip64 archives are not supported"))
+}
+
+fn zip_local_header(name: &str, compression: ZipCompression, dos_time: (u16, u16)) -> Vec<u8> {
+    let mut header = Vec::with_capacity(30 + name.len());
+    header.extend_from_slice(&0x04034b50u32.to_le_bytes());
+    header.extend_from_slice(&20u16.to_le_bytes());
+    header.extend_from_slice(&ZIP_FLAGS.to_le_bytes());
+    header.extend_from_slice(&zip_method(compression).to_le_bytes());
+    header.extend_from_slice(&dos_time.0.to_le_bytes());
+    header.extend_from_slice(&dos_time.1.to_le_bytes());
+    // Checksum, compressed size and size are written in the data descriptor.
+    header.extend_from_slice(&[0; 12]);
+    header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes());
+    header.extend_from_slice(name.as_bytes());
+    header
+}
+
+/// Convert time to MS-DOS time and date in UTC.
+fn dos_date_time(time: SystemTime) -> (u16, u16) {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let year = year.min(2107) as u16;
+    let secs = secs % 86400;
+    let time = ((secs / 3600) as u16) << 11 | (((secs % 3600) / 60) as u16) << 5 | ((secs % 60) / 2) as u16;
+    let date = (year - 1980) << 9 | (month as u16) << 5 | day as u16;
+    (time, date)
+}
+
+/// Convert days since 1970-01-01 to year, month and day in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn tar_headers(name: &str, size: u64, mtime: u64) -> Vec<u8> {
+    let mut headers = Vec::with_capacity(TAR_BLOCK_SIZE);
+    if name.len() > 100 {
+        // GNU long name entry.
+        let mut long_name = name.as_bytes().to_vec();
+        long_name.push(0);
+        headers.extend_from_slice(&tar_header(b"././@LongLink", long_name.len() as u64, 0, b'L'));
+        let len = long_name.len() as u64;
+        headers.extend_from_slice(&long_name);
+        pad_tar_block(&mut headers, len);
+    }
+    headers.extend_from_slice(&tar_header(&name.as_bytes()[..name.len().min(100)], size, mtime, b'0'));
+    headers
+}
+
+fn tar_header(name: &[u8], size: u64, mtime: u64, kind: u8) -> [u8; TAR_BLOCK_SIZE] {
+    let mut header = [0; TAR_BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name);
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    if size < 0o77_777_777_777 {
+        write_octal(&mut header[124..136], size);
+    } else {
+        // Base-256 encoding for sizes which do not fit in 11 octal digits.
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    write_octal(&mut header[136..148], mtime.min(0o77_777_777_777));
+    header[156] = kind;
+    header[257..265].copy_from_slice(b"ustar  \0");
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|b| u32::from(*b)).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    header
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    field[..width].copy_from_slice(format!("{value:0width$o}").as_bytes());
+    field[width] = 0;
+}
+
+fn pad_tar_block(data: &mut Vec<u8>, len: u64) {
+    let rem = (len % TAR_BLOCK_SIZE as u64) as usize;
+    if rem != 0 {
+        data.resize(data.len() + TAR_BLOCK_SIZE - rem, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::DeflateDecoder;
+    use futures_util::stream;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    fn read_u16(data: &[u8], pos: usize) -> usize {
+        u16::from_le_bytes([data[pos], data[pos + 1]]) as usize
+    }
+    fn read_u32(data: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+    }
+
+    // Read entries through the central directory, like unzip does.
+    fn read_zip(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = data.len() - 22;
+        assert_eq!(read_u32(data, end), 0x06054b50);
+        let count = read_u16(data, end + 10);
+        let mut pos = read_u32(data, end + 16) as usize;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            assert_eq!(read_u32(data, pos), 0x02014b50);
+            let method = read_u16(data, pos + 10);
+            let crc = read_u32(data, pos + 16);
+            let compressed = read_u32(data, pos + 20) as usize;
+            let name_len = read_u16(data, pos + 28);
+            let offset = read_u32(data, pos + 42) as usize;
+            let name = String::from_utf8(data[pos + 46..pos + 46 + name_len].to_vec()).unwrap();
+            pos += 46 + name_len;
+
+            assert_eq!(read_u32(data, offset), 0x04034b50);
+            let start = offset + 30 + read_u16(data, offset + 26);
+            let raw = &data[start..start + compressed];
+            let content = if method == 8 {
+                let mut content = Vec::new();
+                DeflateDecoder::new(raw).read_to_end(&mut content).unwrap();
+                content
+            } else {
+                raw.to_vec()
+            };
+            let mut checksum = Crc::new();
+            checksum.update(&content);
+            assert_eq!(checksum.sum(), crc);
+            entries.push((name, content));
+        }
+        entries
+    }
+
+    fn entries() -> impl Stream<Item = (&'static str, &'static [u8])> + Send {
+        stream::iter(vec![
+            ("a.txt", &b"hello world"[..]),
+            ("dir/b.txt", &b"hello hello hello hello"[..]),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_zip_stream() {
+        #[handler]
+        async fn download(req: &mut Request, res: &mut Response) {
+            let compression = if req.query::<bool>("stored").unwrap_or_default() {
+                ZipCompression::Stored
+            } else {
+                ZipCompression::Deflated
+            };
+            res.render(ArchiveStream::new(ArchiveFormat::Zip(compression), entries()).file_name("all.zip"));
+        }
+        let service = Service::new(Router::new().get(download));
+        for url in ["http://127.0.0.1:5800/?stored=true", "http://127.0.0.1:5800/"] {
+            let mut res = TestClient::get(url).send(&service).await;
+            assert_eq!(res.headers()[CONTENT_TYPE], "application/zip");
+            assert_eq!(res.headers()[CONTENT_DISPOSITION], "attachment; filename=\"all.zip\"");
+            let data = res.take_bytes(None).await.unwrap();
+            let entries = read_zip(&data);
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0], ("a.txt".to_owned(), b"hello world".to_vec()));
+            assert_eq!(
+                entries[1],
+                ("dir/b.txt".to_owned(), b"hello hello hello hello".to_vec())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tar_stream() {
+        let long_name = format!("{}/c.txt", "d".repeat(120));
+        let entries = stream::iter(vec![
+            ArchiveEntry::new("a.txt", &b"hello world"[..]),
+            ArchiveEntry::new(long_name.clone(), &b"sized"[..]).size(5),
+        ]);
+        let data: Vec<u8> = ArchiveStream::tar(entries)
+            .into_stream()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        assert_eq!(data.len() % TAR_BLOCK_SIZE, 0);
+        assert_eq!(&data[..5], b"a.txt");
+        assert_eq!(&data[124..135], b"00000000013");
+        assert_eq!(&data[512..523], b"hello world");
+        assert_eq!(data[1024 + 156], b'L');
+        assert_eq!(&data[1536..1536 + long_name.len()], long_name.as_bytes());
+        assert_eq!(&data[2048 + 124..2048 + 135], b"00000000005");
+        assert_eq!(&data[2560..2565], b"sized");
+        assert!(data[3072..].iter().all(|b| *b == 0));
+        assert_eq!(data.len(), 3072 + TAR_BLOCK_SIZE * 2);
+
+        let entries = stream::iter(vec![ArchiveEntry::new("a.txt", &b"too long"[..]).size(3)]);
+        let results: Vec<_> = ArchiveStream::tar(entries).into_stream().collect().await;
+        assert!(results.last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_dos_date_time() {
+        // 2024-02-29 13:45:30 UTC
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_709_214_330);
+        let (time, date) = dos_date_time(time);
+        assert_eq!(date, (44 << 9) | (2 << 5) | 29);
+        assert_eq!(time, (13 << 11) | (45 << 5) | 15);
+    }
+}
@@ -0,0 +1,485 @@
+//! Audit log middleware.
+//!
+//! [`Audit`] records method, path, principal, selected headers and optionally bodies of requests and responses,
+//! and writes the records to an [`AuditSink`], such as file, message queue or database.
+//!
+//! Read more: <https://salvo.rs>
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use time::OffsetDateTime;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use salvo_core::http::body::Body;
+use salvo_core::http::header::{HeaderName, CONTENT_TYPE};
+use salvo_core::http::{mime, HeaderMap, Request, ResBody, Response, StatusCode};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// The value used to replace redacted headers and fields.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Recorded body of a request or response.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "content", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AuditBody {
+    /// Json body, redaction rules are applied to its fields.
+    Json(Value),
+    /// Text body, invalid UTF-8 sequences are replaced.
+    Text(String),
+    /// The body is larger than the size cap, or it is a stream which can not be captured.
+    Omitted {
+        /// Size of the body if it is known.
+        size: Option<u64>,
+    },
+}
+
+/// A record of an audited request.
+#[derive(Serialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct AuditRecord {
+    /// The time when the request is received.
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    /// Request id from `x-request-id` header.
+    pub request_id: Option<String>,
+    /// The principal made the request, resolved by the principal getter.
+    pub principal: Option<String>,
+    /// Remote address of the connection.
+    pub remote_addr: String,
+    /// Request method.
+    pub method: String,
+    /// Request uri path and query.
+    pub uri: String,
+    /// Route path template of the matched router, such as `/users/<id>`.
+    pub route: Option<String>,
+    /// Response status code.
+    pub status: u16,
+    /// Time spent by handlers.
+    #[serde(serialize_with = "serialize_latency")]
+    pub latency: Duration,
+    /// Selected request headers.
+    pub headers: BTreeMap<String, String>,
+    /// Request body, only recorded when it is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<AuditBody>,
+    /// Response body, only recorded when it is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<AuditBody>,
+}
+
+fn serialize_latency<S: serde::Serializer>(latency: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(latency.as_secs_f64() * 1000.0)
+}
+
+/// Sink receives audit records.
+///
+/// Async closures taking [`AuditRecord`] implement this trait.
+#[async_trait]
+pub trait AuditSink: Send + Sync + 'static {
+    /// Write a record.
+    async fn write(&self, record: AuditRecord);
+}
+#[async_trait]
+impl<F, Fut> AuditSink for F
+where
+    F: Fn(AuditRecord) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    #[inline]
+    async fn write(&self, record: AuditRecord) {
+        (self)(record).await
+    }
+}
+
+/// Emit audit records as json `tracing` events with target `salvo::audit`.
+#[derive(Default, Debug)]
+pub struct TracingSink;
+#[async_trait]
+impl AuditSink for TracingSink {
+    #[inline]
+    async fn write(&self, record: AuditRecord) {
+        tracing::info!(target: "salvo::audit", "{}", serde_json::to_string(&record).unwrap_or_default());
+    }
+}
+
+/// Append audit records to file as json lines.
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+impl FileSink {
+    /// Create new `FileSink`, the file is created if it does not exist.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: Mutex::new(None),
+        }
+    }
+
+    async fn write_line(&self, line: &str) -> std::io::Result<()> {
+        let mut file = self.file.lock().await;
+        if file.is_none() {
+            *file = Some(OpenOptions::new().create(true).append(true).open(&self.path).await?);
+        }
+        if let Some(file) = &mut *file {
+            file.write_all(format!("{line}\n").as_bytes()).await?;
+            file.flush().await?;
+        }
+        Ok(())
+    }
+}
+#[async_trait]
+impl AuditSink for FileSink {
+    async fn write(&self, record: AuditRecord) {
+        let line = serde_json::to_string(&record).unwrap_or_default();
+        if let Err(e) = self.write_line(&line).await {
+            tracing::error!(error = ?e, path = ?self.path, "write audit log failed");
+        }
+    }
+}
+
+/// Principal getter resolves the principal of the request, such as user id set by authentication middlewares.
+pub type PrincipalGetter = Box<dyn Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static>;
+
+/// Audit log middleware.
+///
+/// Records are written to the sink after the request is handled, and the response is sent after the record is
+/// written, so a slow sink slows down the audited endpoints.
+///
+/// Bodies are only recorded when they are enabled by [`Audit::request_body`] or [`Audit::response_body`], a
+/// body larger than the size cap, a request body without known size, or a streaming response body, is recorded
+/// as [`AuditBody::Omitted`]. Recorded request bodies are buffered by [`Request::buffer_body`]. Json
+/// fields named in [`Audit::redact_field`] are replaced with [`REDACTED`] at any depth.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::audit::{Audit, FileSink};
+///
+/// let audit = Audit::new(FileSink::new("audit.log"))
+///     .principal_getter(|_req, depot| depot.get::<String>("user_id").ok().cloned())
+///     .header("x-forwarded-for")
+///     .request_body(16 * 1024)
+///     .redact_field("password");
+/// let router = Router::with_path("admin").hoop(audit);
+/// ```
+pub struct Audit {
+    sink: Box<dyn AuditSink>,
+    principal_getter: Option<PrincipalGetter>,
+    headers: Vec<HeaderName>,
+    redacted_headers: HashSet<HeaderName>,
+    redacted_fields: HashSet<String>,
+    request_body: Option<usize>,
+    response_body: Option<usize>,
+}
+impl std::fmt::Debug for Audit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Audit")
+            .field("headers", &self.headers)
+            .field("redacted_headers", &self.redacted_headers)
+            .field("redacted_fields", &self.redacted_fields)
+            .field("request_body", &self.request_body)
+            .field("response_body", &self.response_body)
+            .finish()
+    }
+}
+impl Audit {
+    /// Create new `Audit` middleware with sink.
+    ///
+    /// `authorization`, `cookie` and `proxy-authorization` headers are redacted by default.
+    #[inline]
+    pub fn new(sink: impl AuditSink) -> Self {
+        Self {
+            sink: Box::new(sink),
+            principal_getter: None,
+            headers: Vec::new(),
+            redacted_headers: ["authorization", "cookie", "proxy-authorization"]
+                .into_iter()
+                .map(HeaderName::from_static)
+                .collect(),
+            redacted_fields: HashSet::new(),
+            request_body: None,
+            response_body: None,
+        }
+    }
+
+    /// Sets principal getter.
+    #[inline]
+    pub fn principal_getter<G>(mut self, principal_getter: G) -> Self
+    where
+        G: Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static,
+    {
+        self.principal_getter = Some(Box::new(principal_getter));
+        self
+    }
+
+    /// Record the request header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    #[inline]
+    pub fn header(mut self, name: &str) -> Self {
+        self.headers
+            .push(HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"));
+        self
+    }
+
+    /// Redact the value of the request header when it is recorded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not a valid header name.
+    #[inline]
+    pub fn redact_header(mut self, name: &str) -> Self {
+        self.redacted_headers
+            .insert(HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"));
+        self
+    }
+
+    /// Redact the json field with the name in recorded bodies.
+    #[inline]
+    pub fn redact_field(mut self, name: impl Into<String>) -> Self {
+        self.redacted_fields.insert(name.into());
+        self
+    }
+
+    /// Record request bodies not larger than `max_size`.
+    #[inline]
+    pub fn request_body(mut self, max_size: usize) -> Self {
+        self.request_body = Some(max_size);
+        self
+    }
+
+    /// Record response bodies not larger than `max_size`.
+    #[inline]
+    pub fn response_body(mut self, max_size: usize) -> Self {
+        self.response_body = Some(max_size);
+        self
+    }
+
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.redacted_fields.contains(key) {
+                        *value = Value::String(REDACTED.into());
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.redact(value)),
+            _ => {}
+        }
+    }
+
+    fn record_body(&self, headers: &HeaderMap, data: &[u8]) -> AuditBody {
+        let is_json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<mime::Mime>().ok())
+            .map(|ctype| ctype.subtype() == mime::JSON || ctype.suffix() == Some(mime::JSON))
+            .unwrap_or(false);
+        if is_json {
+            if let Ok(mut value) = serde_json::from_slice::<Value>(data) {
+                self.redact(&mut value);
+                return AuditBody::Json(value);
+            }
+        }
+        AuditBody::Text(String::from_utf8_lossy(data).into_owned())
+    }
+
+    /// Buffer the request body up to `max_size` by [`Request::buffer_body`], so it can still be read by the next
+    /// handlers. Bodies which may be larger than `max_size`, including bodies without known size, are not read.
+    async fn capture_request_body(&self, req: &mut Request, max_size: usize) -> AuditBody {
+        if req.buffered_body().is_none() {
+            let body = req.body();
+            if body.is_end_stream() {
+                return AuditBody::Text(String::new());
+            }
+            let size_hint = body.size_hint();
+            if size_hint.upper().map(|upper| upper > max_size as u64).unwrap_or(true) {
+                return AuditBody::Omitted {
+                    size: size_hint.exact(),
+                };
+            }
+        }
+        match req.buffer_body(max_size).await {
+            Ok(bytes) if bytes.len() <= max_size => {
+                let bytes = bytes.clone();
+                self.record_body(req.headers(), &bytes)
+            }
+            Ok(bytes) => AuditBody::Omitted {
+                size: Some(bytes.len() as u64),
+            },
+            Err(e) => {
+                tracing::warn!(error = ?e, "read request body failed");
+                AuditBody::Omitted { size: None }
+            }
+        }
+    }
+
+    fn capture_response_body(&self, res: &Response, max_size: usize) -> AuditBody {
+        let data = match &res.body {
+            ResBody::None | ResBody::Error(_) => return AuditBody::Text(String::new()),
+            ResBody::Once(bytes) => bytes.to_vec(),
+            ResBody::Chunks(chunks) => chunks.iter().flat_map(|chunk| chunk.iter().copied()).collect(),
+            body => {
+                return AuditBody::Omitted {
+                    size: body.size_hint().exact(),
+                }
+            }
+        };
+        if data.len() > max_size {
+            return AuditBody::Omitted {
+                size: Some(data.len() as u64),
+            };
+        }
+        self.record_body(res.headers(), &data)
+    }
+}
+
+#[async_trait]
+impl Handler for Audit {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let time = OffsetDateTime::now_utc();
+        let now = Instant::now();
+        let request_body = match self.request_body {
+            Some(max_size) => Some(self.capture_request_body(req, max_size).await),
+            None => None,
+        };
+        ctrl.call_next(req, depot, res).await;
+        let latency = now.elapsed();
+
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|name| {
+                let value = req.headers().get(name)?;
+                let value = if self.redacted_headers.contains(name) {
+                    REDACTED.to_owned()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                Some((name.as_str().to_owned(), value))
+            })
+            .collect();
+        let record = AuditRecord {
+            time,
+            request_id: req
+                .headers()
+                .get("x-request-id")
+                .or_else(|| res.headers().get("x-request-id"))
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned),
+            principal: self.principal_getter.as_ref().and_then(|getter| getter(req, depot)),
+            remote_addr: req.remote_addr().to_string(),
+            method: req.method().to_string(),
+            uri: req
+                .uri()
+                .path_and_query()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "/".into()),
+            route: req.matched_path().map(ToOwned::to_owned),
+            status: res.status_code.unwrap_or(StatusCode::OK).as_u16(),
+            latency,
+            headers,
+            request_body,
+            response_body: self
+                .response_body
+                .map(|max_size| self.capture_response_body(res, max_size)),
+        };
+        self.sink.write(record).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audit() {
+        #[handler]
+        async fn update(req: &mut Request, depot: &mut Depot) -> Json<Value> {
+            depot.insert("user_id", "alice".to_owned());
+            let body: Value = req.parse_json().await.unwrap();
+            Json(json!({"name": body["name"], "token": "secret"}))
+        }
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = {
+            let records = records.clone();
+            move |record: AuditRecord| {
+                records.lock().unwrap().push(record);
+                async {}
+            }
+        };
+        let router = Router::with_path("users/<id>")
+            .hoop(
+                Audit::new(sink)
+                    .principal_getter(|_req, depot| depot.get::<String>("user_id").ok().cloned())
+                    .header("authorization")
+                    .header("x-custom")
+                    .request_body(1024)
+                    .response_body(16)
+                    .redact_field("password"),
+            )
+            .post(update);
+        let service = Service::new(router);
+
+        let mut res = TestClient::post("http://127.0.0.1:5801/users/1?q=1")
+            .add_header("authorization", "Bearer abc", true)
+            .add_header("x-custom", "value", true)
+            .json(&json!({"name": "alice", "profile": {"password": "123"}}))
+            .send(&service)
+            .await;
+        assert_eq!(res.take_json::<Value>().await.unwrap()["name"], "alice");
+
+        let record = records.lock().unwrap().pop().unwrap();
+        assert_eq!(record.principal.as_deref(), Some("alice"));
+        assert_eq!(record.method, "POST");
+        assert_eq!(record.uri, "/users/1?q=1");
+        assert_eq!(record.route.as_deref(), Some("/users/<id>"));
+        assert_eq!(record.status, 200);
+        assert_eq!(record.headers["authorization"], REDACTED);
+        assert_eq!(record.headers["x-custom"], "value");
+        assert_eq!(
+            record.request_body,
+            Some(AuditBody::Json(
+                json!({"name": "alice", "profile": {"password": REDACTED}})
+            ))
+        );
+        assert!(matches!(
+            record.response_body,
+            Some(AuditBody::Omitted { size: Some(_) })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_file_sink() {
+        let dir = std::env::temp_dir().join(format!("salvo-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+        let sink = FileSink::new(&path);
+        sink.write_line("line-1").await.unwrap();
+        sink.write_line("line-2").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "line-1\nline-2\n");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
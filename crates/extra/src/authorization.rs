@@ -0,0 +1,359 @@
+//! Authorization middleware driven by route metadata.
+//!
+//! Attach [`Requires`] to routers with [`Router::meta`](salvo_core::Router::meta), and add [`Authorization`]
+//! as a middleware. Requirements of all routers on the matched path are checked against the [`Grants`] of the
+//! authenticated principal in depot by a [`Policy`]. Requests without grants are rejected with
+//! `401 Unauthorized` and requests not allowed by the policy are rejected with `403 Forbidden`.
+//!
+//! Read more: <https://salvo.rs>
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// key used to insert grants to depot.
+pub const GRANTS_KEY: &str = "::salvo::authorization::grants";
+
+/// Roles, scopes and permissions granted to the authenticated principal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Grants {
+    /// Granted roles.
+    pub roles: Vec<String>,
+    /// Granted scopes.
+    pub scopes: Vec<String>,
+    /// Granted permissions.
+    pub permissions: Vec<String>,
+}
+impl Grants {
+    /// Create empty `Grants`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a role.
+    #[inline]
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+    /// Add a scope.
+    #[inline]
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+    /// Add a permission.
+    #[inline]
+    pub fn permission(mut self, permission: impl Into<String>) -> Self {
+        self.permissions.push(permission.into());
+        self
+    }
+    /// Returns `true` if `role` is granted.
+    #[inline]
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+    /// Returns `true` if `scope` is granted.
+    #[inline]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+    /// Returns `true` if `permission` is granted.
+    #[inline]
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+}
+
+/// Requirements of a route, attached with [`Router::meta`](salvo_core::Router::meta).
+///
+/// All roles, scopes and permissions listed are required. Empty `Requires` only requires an authenticated
+/// principal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Requires {
+    /// Required roles.
+    pub roles: Vec<String>,
+    /// Required scopes.
+    pub scopes: Vec<String>,
+    /// Required permissions.
+    pub permissions: Vec<String>,
+}
+impl Requires {
+    /// Requires an authenticated principal only.
+    #[inline]
+    pub fn authenticated() -> Self {
+        Self::default()
+    }
+    /// Requires `role`.
+    #[inline]
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+    /// Requires `scope`.
+    #[inline]
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+    /// Requires `permission`.
+    #[inline]
+    pub fn permission(mut self, permission: impl Into<String>) -> Self {
+        self.permissions.push(permission.into());
+        self
+    }
+    /// Returns `true` if `grants` meet the requirements.
+    #[inline]
+    pub fn is_met(&self, grants: &Grants) -> bool {
+        self.roles.iter().all(|r| grants.has_role(r))
+            && self.scopes.iter().all(|s| grants.has_scope(s))
+            && self.permissions.iter().all(|p| grants.has_permission(p))
+    }
+}
+
+/// Route metadata skips all requirements of the route, attach it to public routes nested in protected routers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllowAnonymous;
+
+/// AuthorizationDepotExt
+pub trait AuthorizationDepotExt {
+    /// Sets grants of the authenticated principal, it is usually called by authentication middlewares.
+    fn set_grants(&mut self, grants: Grants) -> &mut Self;
+    /// Get grants reference.
+    fn grants(&self) -> Option<&Grants>;
+}
+impl AuthorizationDepotExt for Depot {
+    #[inline]
+    fn set_grants(&mut self, grants: Grants) -> &mut Self {
+        self.insert(GRANTS_KEY, grants);
+        self
+    }
+    #[inline]
+    fn grants(&self) -> Option<&Grants> {
+        self.get(GRANTS_KEY).ok()
+    }
+}
+
+/// Policy decides whether the request is allowed.
+///
+/// Implement it to delegate decisions to a policy engine, such as Casbin or OPA.
+#[async_trait]
+pub trait Policy: Send + Sync + 'static {
+    /// Returns `true` if the request is allowed.
+    ///
+    /// `requires` contains requirements of all routers on the matched path, from the outermost router to the
+    /// innermost router.
+    async fn authorize(&self, grants: &Grants, requires: &[&Requires], req: &Request, depot: &Depot) -> bool;
+}
+
+/// Default policy allows the request if all requirements are met.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPolicy;
+#[async_trait]
+impl Policy for DefaultPolicy {
+    async fn authorize(&self, grants: &Grants, requires: &[&Requires], _req: &Request, _depot: &Depot) -> bool {
+        requires.iter().all(|requires| requires.is_met(grants))
+    }
+}
+
+type GrantsFn = dyn Fn(&Depot) -> Option<Grants> + Send + Sync;
+
+/// Authorization middleware.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::authorization::{Authorization, AuthorizationDepotExt, Grants, Requires};
+///
+/// #[handler]
+/// async fn authenticate(depot: &mut Depot) {
+///     // Verify credentials and load grants here.
+///     depot.set_grants(Grants::new().role("admin"));
+/// }
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "Hello"
+/// }
+///
+/// let router = Router::new()
+///     .hoop(authenticate)
+///     .hoop(Authorization::new())
+///     .push(Router::with_path("admin").meta(Requires::authenticated().role("admin")).get(hello))
+///     .push(Router::with_path("public").get(hello));
+/// ```
+pub struct Authorization<P = DefaultPolicy> {
+    policy: P,
+    grants: Box<GrantsFn>,
+}
+impl Default for Authorization {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Authorization {
+    /// Create a new `Authorization` with [`DefaultPolicy`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_policy(DefaultPolicy)
+    }
+}
+impl<P> Authorization<P>
+where
+    P: Policy,
+{
+    /// Create a new `Authorization` with `policy`.
+    #[inline]
+    pub fn with_policy(policy: P) -> Self {
+        Self {
+            policy,
+            grants: Box::new(default_grants),
+        }
+    }
+    /// Sets how grants are read from depot.
+    ///
+    /// By default, grants set by [`AuthorizationDepotExt::set_grants`] are used.
+    #[inline]
+    pub fn grants_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Depot) -> Option<Grants> + Send + Sync + 'static,
+    {
+        self.grants = Box::new(f);
+        self
+    }
+}
+
+fn default_grants(depot: &Depot) -> Option<Grants> {
+    if let Some(grants) = depot.grants() {
+        return Some(grants.clone());
+    }
+    #[cfg(feature = "api-key-auth")]
+    {
+        use crate::api_key_auth::{ApiKeyAuthDepotExt, ApiKeyAuthState};
+        if depot.api_key_auth_state() == ApiKeyAuthState::Authorized {
+            return Some(Grants {
+                scopes: depot.api_key_scopes().to_vec(),
+                ..Default::default()
+            });
+        }
+    }
+    None
+}
+
+#[async_trait]
+impl<P> Handler for Authorization<P>
+where
+    P: Policy,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let metadata = req.route_metadata();
+        if metadata.contains::<AllowAnonymous>() {
+            return;
+        }
+        let requires = metadata.get_all::<Requires>().collect::<Vec<_>>();
+        if requires.is_empty() {
+            return;
+        }
+        let Some(grants) = (self.grants)(depot) else {
+            res.render(StatusError::unauthorized());
+            ctrl.skip_rest();
+            return;
+        };
+        if !self.policy.authorize(&grants, &requires, req, depot).await {
+            res.render(StatusError::forbidden());
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn authenticate(req: &mut Request, depot: &mut Depot) {
+        if let Some(roles) = req.header::<String>("x-roles") {
+            let grants = roles.split(',').fold(Grants::new(), |grants, role| grants.role(role));
+            depot.set_grants(grants.permission("posts:read"));
+        }
+    }
+    #[handler]
+    async fn hello() -> &'static str {
+        "Hello"
+    }
+
+    fn router<P: Policy>(authorization: Authorization<P>) -> Router {
+        Router::new()
+            .hoop(authenticate)
+            .hoop(authorization)
+            .push(
+                Router::with_path("admin")
+                    .meta(Requires::authenticated().role("admin"))
+                    .push(
+                        Router::with_path("posts")
+                            .meta(Requires::authenticated().role("editor"))
+                            .get(hello),
+                    )
+                    .push(Router::with_path("login").meta(AllowAnonymous).get(hello))
+                    .get(hello),
+            )
+            .push(Router::with_path("public").get(hello))
+    }
+
+    async fn status(service: &Service, path: &str, roles: Option<&str>) -> Option<StatusCode> {
+        let mut client = TestClient::get(format!("http://127.0.0.1:5801{path}"));
+        if let Some(roles) = roles {
+            client = client.add_header("x-roles", roles, true);
+        }
+        client.send(service).await.status_code
+    }
+
+    #[tokio::test]
+    async fn test_authorization() {
+        let service = Service::new(router(Authorization::new()));
+        assert_eq!(status(&service, "/public", None).await, Some(StatusCode::OK));
+        assert_eq!(status(&service, "/admin", None).await, Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(
+            status(&service, "/admin", Some("user")).await,
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(status(&service, "/admin", Some("admin")).await, Some(StatusCode::OK));
+        assert_eq!(
+            status(&service, "/admin/posts", Some("admin")).await,
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            status(&service, "/admin/posts", Some("admin,editor")).await,
+            Some(StatusCode::OK)
+        );
+        assert_eq!(status(&service, "/admin/login", None).await, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_authorization_policy() {
+        struct MethodPolicy;
+        #[async_trait]
+        impl Policy for MethodPolicy {
+            async fn authorize(&self, grants: &Grants, _requires: &[&Requires], req: &Request, _depot: &Depot) -> bool {
+                req.method() == salvo_core::http::Method::GET && grants.has_permission("posts:read")
+            }
+        }
+        let service = Service::new(router(Authorization::with_policy(MethodPolicy)));
+        assert_eq!(
+            status(&service, "/admin/posts", Some("user")).await,
+            Some(StatusCode::OK)
+        );
+        assert_eq!(
+            status(&service, "/admin/posts", None).await,
+            Some(StatusCode::UNAUTHORIZED)
+        );
+
+        let service = Service::new(router(
+            Authorization::new().grants_with(|_| Some(Grants::new().role("admin"))),
+        ));
+        let mut res = TestClient::get("http://127.0.0.1:5801/admin").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "Hello");
+    }
+}
@@ -1,6 +1,7 @@
 //! basic auth middleware.
 //!
 //! Read more: <https://salvo.rs>
+use salvo_core::handler::{none_skipper, Skipper};
 use salvo_core::http::header::{HeaderName, PROXY_AUTHORIZATION, AUTHORIZATION};
 use salvo_core::http::{Request, Response, StatusCode};
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
@@ -32,6 +33,7 @@ pub struct BasicAuth<V: BasicAuthValidator> {
     realm: String,
     header_names: Vec<HeaderName>,
     validator: V,
+    skipper: Box<dyn Skipper>,
 }
 
 impl<V> BasicAuth<V>
@@ -45,9 +47,17 @@ where
             realm: "realm".to_owned(),
             header_names: vec![AUTHORIZATION, PROXY_AUTHORIZATION],
             validator,
+            skipper: Box::new(none_skipper),
         }
     }
 
+    /// Sets skipper, requests are passed without credentials when it returns `true`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn set_header_names(mut self, header_names: impl Into<Vec<HeaderName>>) -> Self {
@@ -122,6 +132,10 @@ where
     V: BasicAuthValidator + 'static,
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
         if let Ok((username, password)) = self.parse_credentials(req) {
             if self.validator.validate(&username, &password, depot).await {
                 depot.insert(USERNAME_KEY, username);
@@ -136,6 +150,7 @@ where
 
 #[cfg(test)]
 mod tests {
+    use salvo_core::handler::RequestSkipper;
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
 
@@ -178,4 +193,24 @@ mod tests {
             .unwrap();
         assert!(content.contains("Unauthorized"));
     }
+
+    #[tokio::test]
+    async fn test_basic_auth_skipper() {
+        let auth_handler = BasicAuth::new(Validator).skipper(RequestSkipper::new().path("/healthz"));
+        let router = Router::with_hoop(auth_handler)
+            .push(Router::with_path("healthz").get(hello))
+            .push(Router::with_path("hello").get(hello));
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5800/healthz")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains("Hello"));
+
+        let res = TestClient::get("http://127.0.0.1:5800/hello").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -0,0 +1,765 @@
+//! Bot detection and crawler policy middleware.
+//!
+//! [`BotDetector`] classifies requests by user agent into [`BotClass`]es. Requests claiming to be known crawlers,
+//! such as Googlebot, are verified by reverse DNS when a [`CrawlerVerifier`] is set, so spoofed crawlers can be
+//! told from real ones. The classification is injected into [`Depot`] as [`BotInfo`], and a [`BotPolicy`] can be
+//! applied to every class:
+//!
+//! - [`BotPolicy::Block`] rejects requests with `403 Forbidden`.
+//! - [`BotPolicy::Throttle`] limits the count of requests of every crawler or client in a period.
+//! - [`BotPolicy::Hoop`] runs a hoop for the class, such as a cache to serve crawlers cached pages.
+//!
+//! [`RobotsTxt`] is a handler which serves `robots.txt`, rules are added manually or collected from routers
+//! marked by [`Crawl`] with [`Router::meta`](salvo_core::Router::meta).
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::bot_detection::{BotClass, BotDetector, BotPolicy, Crawl, RobotsTxt};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! let detector = BotDetector::new()
+//!     .policy(BotClass::FakeCrawler, BotPolicy::Block)
+//!     .policy(BotClass::Bot, BotPolicy::throttle(60, Duration::from_secs(60)));
+//! let site = Router::new()
+//!     .push(Router::with_path("admin").meta(Crawl::Disallow).get(hello))
+//!     .push(Router::with_path("hello").get(hello));
+//! let robots = RobotsTxt::new().router(&site).sitemap("https://example.com/sitemap.xml");
+//! let router = Router::new()
+//!     .hoop(detector)
+//!     .push(Router::with_path("robots.txt").get(robots))
+//!     .push(site);
+//! ```
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Formatter, Write};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::USER_AGENT;
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::writing::Text;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Router};
+
+use crate::trusted_proxies::TrustedProxies;
+
+/// Maximum count of entries kept in caches before expired entries are removed.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Substrings of user agents of automated clients other than known crawlers.
+const BOT_PATTERNS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "slurp",
+    "curl/",
+    "wget/",
+    "httpie/",
+    "python-requests",
+    "python-urllib",
+    "aiohttp",
+    "go-http-client",
+    "java/",
+    "okhttp",
+    "libwww-perl",
+    "scrapy",
+    "headlesschrome",
+    "phantomjs",
+];
+
+/// Class of a request.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum BotClass {
+    /// Not detected as a bot.
+    Human,
+    /// An automated client which is not a known crawler, such as a http library or a headless browser. Requests
+    /// without user agent are bots too.
+    Bot,
+    /// A known crawler which is not verified, because it has no verification domains or no verifier is set.
+    Crawler,
+    /// A known crawler verified by reverse DNS.
+    VerifiedCrawler,
+    /// A request claiming to be a known crawler, but failed to be verified.
+    FakeCrawler,
+}
+impl BotClass {
+    /// Returns `true` if the class is not [`BotClass::Human`].
+    #[inline]
+    pub fn is_bot(&self) -> bool {
+        *self != BotClass::Human
+    }
+}
+
+/// Classification of a request, it is injected into [`Depot`] by [`BotDetector`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BotInfo {
+    /// Class of the request.
+    pub class: BotClass,
+    /// Name of the known crawler the request claims to be.
+    pub name: Option<String>,
+}
+
+/// Get the classification of the request, returns `None` if no [`BotDetector`] handled the request.
+#[inline]
+pub fn bot_info(depot: &Depot) -> Option<&BotInfo> {
+    depot.obtain::<BotInfo>().ok()
+}
+
+/// A known crawler.
+#[derive(Clone, Debug)]
+pub struct KnownCrawler {
+    name: String,
+    pattern: String,
+    domains: Vec<String>,
+}
+impl KnownCrawler {
+    /// Create a new `KnownCrawler`, requests with user agents containing `pattern` claim to be it. Patterns are
+    /// matched case insensitively.
+    #[inline]
+    pub fn new(name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into().to_lowercase(),
+            domains: Vec::new(),
+        }
+    }
+    /// Add a domain which hosts of the crawler belong to, such as `googlebot.com`, it is used to verify the
+    /// crawler by reverse DNS.
+    #[inline]
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domains.push(domain.into().trim_matches('.').to_lowercase());
+        self
+    }
+    /// Get the name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get the verification domains.
+    #[inline]
+    pub fn domains(&self) -> &[String] {
+        &self.domains
+    }
+
+    /// Major search engine crawlers with their documented verification domains.
+    pub fn defaults() -> Vec<KnownCrawler> {
+        vec![
+            Self::new("Googlebot", "googlebot")
+                .domain("googlebot.com")
+                .domain("google.com")
+                .domain("googleusercontent.com"),
+            Self::new("Bingbot", "bingbot").domain("search.msn.com"),
+            Self::new("Applebot", "applebot").domain("applebot.apple.com"),
+            Self::new("YandexBot", "yandex")
+                .domain("yandex.ru")
+                .domain("yandex.net")
+                .domain("yandex.com"),
+            Self::new("Baiduspider", "baiduspider")
+                .domain("baidu.com")
+                .domain("baidu.jp"),
+            Self::new("DuckDuckBot", "duckduckbot"),
+        ]
+    }
+}
+
+/// Verifies that a request claiming to be a known crawler is sent by it.
+#[async_trait]
+pub trait CrawlerVerifier: Send + Sync + 'static {
+    /// Returns `true` if the ip belongs to one of the domains of the crawler.
+    async fn verify(&self, ip: IpAddr, crawler: &KnownCrawler) -> bool;
+}
+
+/// DNS resolver used by [`ReverseDnsVerifier`], it is implemented with a DNS client such as `hickory-resolver`.
+#[async_trait]
+pub trait DnsResolver: Send + Sync + 'static {
+    /// Error type for DnsResolver.
+    type Error: StdError + Sync + Send + 'static;
+    /// Get host names of the ip by its PTR records.
+    async fn reverse(&self, ip: IpAddr) -> Result<Vec<String>, Self::Error>;
+    /// Get ips of the host name.
+    async fn forward(&self, host: &str) -> Result<Vec<IpAddr>, Self::Error>;
+}
+
+/// Verifies crawlers by reverse DNS, as documented by search engines.
+///
+/// The host name of the ip must belong to a domain of the crawler, and must resolve back to the ip. Results are
+/// cached for every ip.
+pub struct ReverseDnsVerifier<R> {
+    resolver: R,
+    ttl: Duration,
+    cache: Mutex<HashMap<(IpAddr, String), (bool, Instant)>>,
+}
+impl<R: Debug> Debug for ReverseDnsVerifier<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReverseDnsVerifier")
+            .field("resolver", &self.resolver)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+impl<R: DnsResolver> ReverseDnsVerifier<R> {
+    /// Create a new `ReverseDnsVerifier`, results are cached for one hour.
+    #[inline]
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver,
+            ttl: Duration::from_secs(3600),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Sets how long results are cached.
+    #[inline]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    async fn lookup(&self, ip: IpAddr, crawler: &KnownCrawler) -> Result<bool, R::Error> {
+        for host in self.resolver.reverse(ip).await? {
+            let host = host.trim_end_matches('.').to_lowercase();
+            let belongs = crawler
+                .domains
+                .iter()
+                .any(|domain| host == *domain || host.ends_with(&format!(".{domain}")));
+            if belongs && self.resolver.forward(&host).await?.contains(&ip) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+#[async_trait]
+impl<R: DnsResolver> CrawlerVerifier for ReverseDnsVerifier<R> {
+    async fn verify(&self, ip: IpAddr, crawler: &KnownCrawler) -> bool {
+        let key = (ip, crawler.name.clone());
+        let cached = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key).copied();
+        if let Some((verified, at)) = cached {
+            if at.elapsed() < self.ttl {
+                return verified;
+            }
+        }
+        let verified = match self.lookup(ip, crawler).await {
+            Ok(verified) => verified,
+            Err(e) => {
+                // Do not cache failures of the resolver.
+                tracing::warn!(error = ?e, ip = %ip, "verify crawler failed");
+                return false;
+            }
+        };
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= MAX_ENTRIES {
+            let ttl = self.ttl;
+            cache.retain(|_, (_, at)| at.elapsed() < ttl);
+        }
+        cache.insert(key, (verified, Instant::now()));
+        verified
+    }
+}
+
+/// Policy applied to a [`BotClass`].
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum BotPolicy {
+    /// Let requests through.
+    Allow,
+    /// Reject requests with `403 Forbidden`.
+    Block,
+    /// Reject requests with `429 Too Many Requests` if a crawler, or a client of other classes, sends more than
+    /// `limit` requests in `period`.
+    Throttle {
+        /// Maximum count of requests in a period.
+        limit: usize,
+        /// Length of a period.
+        period: Duration,
+    },
+    /// Handle requests with a hoop, such as a cache.
+    Hoop(Arc<dyn Handler>),
+}
+impl BotPolicy {
+    /// Create a [`BotPolicy::Throttle`].
+    #[inline]
+    pub fn throttle(limit: usize, period: Duration) -> Self {
+        Self::Throttle { limit, period }
+    }
+    /// Create a [`BotPolicy::Hoop`].
+    #[inline]
+    pub fn hoop(hoop: impl Handler) -> Self {
+        Self::Hoop(Arc::new(hoop))
+    }
+}
+impl Debug for BotPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allow => f.write_str("Allow"),
+            Self::Block => f.write_str("Block"),
+            Self::Throttle { limit, period } => f
+                .debug_struct("Throttle")
+                .field("limit", limit)
+                .field("period", period)
+                .finish(),
+            Self::Hoop(_) => f.write_str("Hoop"),
+        }
+    }
+}
+
+/// Middleware which classifies requests and applies policies to bots.
+///
+/// Requests are classified by the user agent, known crawlers are verified if a verifier is set. All classes are
+/// allowed by default.
+pub struct BotDetector {
+    crawlers: Vec<KnownCrawler>,
+    bot_patterns: Vec<String>,
+    verifier: Option<Box<dyn CrawlerVerifier>>,
+    policies: HashMap<BotClass, BotPolicy>,
+    trusted_proxies: TrustedProxies,
+    throttles: Mutex<HashMap<(BotClass, String), (usize, Instant)>>,
+    skipper: Box<dyn Skipper>,
+}
+impl Debug for BotDetector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BotDetector")
+            .field("crawlers", &self.crawlers)
+            .field("bot_patterns", &self.bot_patterns)
+            .field("policies", &self.policies)
+            .finish()
+    }
+}
+impl Default for BotDetector {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl BotDetector {
+    /// Create a new `BotDetector` with [default crawlers](KnownCrawler::defaults) and common bot user agents.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            crawlers: KnownCrawler::defaults(),
+            bot_patterns: BOT_PATTERNS.iter().map(|p| (*p).to_owned()).collect(),
+            verifier: None,
+            policies: HashMap::new(),
+            trusted_proxies: TrustedProxies::default(),
+            throttles: Mutex::new(HashMap::new()),
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Add a known crawler, crawlers added later are matched first.
+    #[inline]
+    pub fn crawler(mut self, crawler: KnownCrawler) -> Self {
+        self.crawlers.insert(0, crawler);
+        self
+    }
+    /// Sets known crawlers, it replaces the default crawlers.
+    #[inline]
+    pub fn crawlers(mut self, crawlers: impl IntoIterator<Item = KnownCrawler>) -> Self {
+        self.crawlers = crawlers.into_iter().collect();
+        self
+    }
+    /// Add a substring of user agents of bots, patterns are matched case insensitively.
+    #[inline]
+    pub fn bot_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.bot_patterns.push(pattern.into().to_lowercase());
+        self
+    }
+    /// Sets the verifier of known crawlers.
+    #[inline]
+    pub fn verifier(mut self, verifier: impl CrawlerVerifier) -> Self {
+        self.verifier = Some(Box::new(verifier));
+        self
+    }
+    /// Sets the policy of a class.
+    #[inline]
+    pub fn policy(mut self, class: BotClass, policy: BotPolicy) -> Self {
+        self.policies.insert(class, policy);
+        self
+    }
+    /// Sets trusted proxies used to resolve client ips from `X-Forwarded-For` header, client ips are the remote
+    /// address of the connection by default.
+    #[inline]
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+    /// Sets skipper.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
+    /// Classify a request by its user agent and client ip.
+    pub async fn classify(&self, user_agent: Option<&str>, ip: Option<IpAddr>) -> BotInfo {
+        let Some(user_agent) = user_agent.map(str::to_lowercase).filter(|ua| !ua.trim().is_empty()) else {
+            return BotInfo {
+                class: BotClass::Bot,
+                name: None,
+            };
+        };
+        if let Some(crawler) = self.crawlers.iter().find(|c| user_agent.contains(&c.pattern)) {
+            let class = match (&self.verifier, ip) {
+                (Some(verifier), Some(ip)) if !crawler.domains.is_empty() => {
+                    if verifier.verify(ip, crawler).await {
+                        BotClass::VerifiedCrawler
+                    } else {
+                        BotClass::FakeCrawler
+                    }
+                }
+                _ => BotClass::Crawler,
+            };
+            return BotInfo {
+                class,
+                name: Some(crawler.name.clone()),
+            };
+        }
+        let class = if self.bot_patterns.iter().any(|p| user_agent.contains(p.as_str())) {
+            BotClass::Bot
+        } else {
+            BotClass::Human
+        };
+        BotInfo { class, name: None }
+    }
+
+    /// Counts a request in the current period, returns `false` if the limit is exceeded.
+    fn take(&self, class: BotClass, key: String, limit: usize, period: Duration) -> bool {
+        let mut throttles = self.throttles.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if throttles.len() >= MAX_ENTRIES {
+            throttles.retain(|_, (_, started)| now.duration_since(*started) < period);
+        }
+        let (count, started) = throttles.entry((class, key)).or_insert((0, now));
+        if now.duration_since(*started) >= period {
+            (*count, *started) = (0, now);
+        }
+        *count += 1;
+        *count <= limit
+    }
+}
+
+#[async_trait]
+impl Handler for BotDetector {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let ip = self.trusted_proxies.client_ip(req);
+        let user_agent = req.headers().get(USER_AGENT).and_then(|v| v.to_str().ok());
+        let info = self.classify(user_agent, ip).await;
+        let user_agent = user_agent.unwrap_or_default().to_owned();
+        let policy = self.policies.get(&info.class).cloned();
+        let class = info.class;
+        let key = info
+            .name
+            .clone()
+            .or_else(|| ip.map(|ip| ip.to_string()))
+            .unwrap_or(user_agent);
+        depot.inject(info);
+        match policy {
+            None | Some(BotPolicy::Allow) => {}
+            Some(BotPolicy::Block) => {
+                res.render(StatusError::forbidden());
+                ctrl.skip_rest();
+            }
+            Some(BotPolicy::Throttle { limit, period }) => {
+                if !self.take(class, key, limit, period) {
+                    res.render(StatusError::too_many_requests());
+                    ctrl.skip_rest();
+                }
+            }
+            Some(BotPolicy::Hoop(hoop)) => {
+                hoop.handle(req, depot, res, ctrl).await;
+            }
+        }
+    }
+}
+
+/// Marks whether crawlers may crawl a router and its children, it is read by [`RobotsTxt::router`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Crawl {
+    /// Crawlers may crawl the router.
+    Allow,
+    /// Crawlers may not crawl the router.
+    Disallow,
+}
+
+/// A group of `robots.txt` rules for user agents.
+#[derive(Clone, Default, Debug)]
+pub struct RobotsGroup {
+    user_agents: Vec<String>,
+    allows: Vec<String>,
+    disallows: Vec<String>,
+    crawl_delay: Option<u64>,
+}
+impl RobotsGroup {
+    /// Create a new `RobotsGroup` for the user agent, `*` is for all crawlers.
+    #[inline]
+    pub fn new(user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agents: vec![user_agent.into()],
+            ..Default::default()
+        }
+    }
+    /// Add a user agent.
+    #[inline]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agents.push(user_agent.into());
+        self
+    }
+    /// Allow a path prefix.
+    #[inline]
+    pub fn allow(mut self, path: impl Into<String>) -> Self {
+        self.allows.push(path.into());
+        self
+    }
+    /// Disallow a path prefix.
+    #[inline]
+    pub fn disallow(mut self, path: impl Into<String>) -> Self {
+        self.disallows.push(path.into());
+        self
+    }
+    /// Sets crawl delay in seconds.
+    #[inline]
+    pub fn crawl_delay(mut self, seconds: u64) -> Self {
+        self.crawl_delay = Some(seconds);
+        self
+    }
+}
+
+/// Handler which serves `robots.txt`.
+#[derive(Clone, Default, Debug)]
+pub struct RobotsTxt {
+    groups: Vec<RobotsGroup>,
+    sitemaps: Vec<String>,
+}
+impl RobotsTxt {
+    /// Create a new `RobotsTxt` without rules, it allows all crawlers to crawl everything.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a group of rules.
+    #[inline]
+    pub fn group(mut self, group: RobotsGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+    /// Add a sitemap url.
+    #[inline]
+    pub fn sitemap(mut self, url: impl Into<String>) -> Self {
+        self.sitemaps.push(url.into());
+        self
+    }
+    /// Add rules of routers marked by [`Crawl`] to the group for all crawlers.
+    ///
+    /// Path params are replaced by `*`, and paths are cut before a trailing rest param, such as
+    /// `/users/<id>/private` is `/users/*/private` and `/admin/<**rest>` is `/admin/`.
+    pub fn router(mut self, router: &Router) -> Self {
+        let index = match self
+            .groups
+            .iter()
+            .position(|g| g.user_agents.iter().any(|ua| ua == "*"))
+        {
+            Some(index) => index,
+            None => {
+                self.groups.push(RobotsGroup::new("*"));
+                self.groups.len() - 1
+            }
+        };
+        collect_rules(router, &mut Vec::new(), &mut self.groups[index]);
+        self
+    }
+
+    /// Render `robots.txt`.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for group in &self.groups {
+            for user_agent in &group.user_agents {
+                let _ = writeln!(text, "User-agent: {user_agent}");
+            }
+            for path in &group.allows {
+                let _ = writeln!(text, "Allow: {path}");
+            }
+            for path in &group.disallows {
+                let _ = writeln!(text, "Disallow: {path}");
+            }
+            if group.allows.is_empty() && group.disallows.is_empty() {
+                text.push_str("Disallow:\n");
+            }
+            if let Some(delay) = group.crawl_delay {
+                let _ = writeln!(text, "Crawl-delay: {delay}");
+            }
+            text.push('\n');
+        }
+        for url in &self.sitemaps {
+            let _ = writeln!(text, "Sitemap: {url}");
+        }
+        text
+    }
+}
+
+fn collect_rules(router: &Router, paths: &mut Vec<String>, group: &mut RobotsGroup) {
+    let len = paths.len();
+    for filter in router.filters() {
+        if let Some(path) = format!("{filter:?}").strip_prefix("path:") {
+            paths.push(path.to_owned());
+        }
+    }
+    if let Some(crawl) = router.metadata().get::<Crawl>() {
+        let path = robots_path(&paths[..]);
+        let rules = match crawl {
+            Crawl::Allow => &mut group.allows,
+            Crawl::Disallow => &mut group.disallows,
+        };
+        if !rules.contains(&path) {
+            rules.push(path);
+        }
+    }
+    for router in router.routers() {
+        collect_rules(router, paths, group);
+    }
+    paths.truncate(len);
+}
+
+fn robots_path(paths: &[String]) -> String {
+    let mut path = String::from("/");
+    let segments = paths
+        .iter()
+        .flat_map(|p| p.split('/'))
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.starts_with("<*") {
+            break;
+        }
+        if segment.contains('<') {
+            path.push('*');
+        } else {
+            path.push_str(segment);
+        }
+        if i + 1 < segments.len() {
+            path.push('/');
+        }
+    }
+    path
+}
+
+#[async_trait]
+impl Handler for RobotsTxt {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        res.render(Text::Plain(self.render()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    struct FakeResolver;
+    #[async_trait]
+    impl DnsResolver for FakeResolver {
+        type Error = Infallible;
+        async fn reverse(&self, ip: IpAddr) -> Result<Vec<String>, Self::Error> {
+            match ip.to_string().as_str() {
+                "66.249.66.1" => Ok(vec!["crawl-66-249-66-1.googlebot.com.".into()]),
+                "192.0.2.1" => Ok(vec!["crawl.googlebot.com.evil.example".into()]),
+                _ => Ok(vec![]),
+            }
+        }
+        async fn forward(&self, host: &str) -> Result<Vec<IpAddr>, Self::Error> {
+            match host {
+                "crawl-66-249-66-1.googlebot.com" => Ok(vec!["66.249.66.1".parse().unwrap()]),
+                _ => Ok(vec![]),
+            }
+        }
+    }
+
+    /// Pretends requests are forwarded by a trusted proxy at `127.0.0.1`.
+    #[handler]
+    async fn from_proxy(req: &mut Request) {
+        *req.remote_addr_mut() = std::net::SocketAddr::from(([127, 0, 0, 1], 80)).into();
+    }
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        format!("{:?}", bot_info(depot).unwrap().class)
+    }
+
+    #[tokio::test]
+    async fn test_bot_detector() {
+        let detector = BotDetector::new()
+            .verifier(ReverseDnsVerifier::new(FakeResolver))
+            .trusted_proxies(TrustedProxies::new(["127.0.0.1".parse().unwrap()]))
+            .policy(BotClass::FakeCrawler, BotPolicy::Block)
+            .policy(BotClass::Bot, BotPolicy::throttle(1, Duration::from_secs(60)));
+        let service = Service::new(Router::with_hoop(from_proxy).hoop(detector).get(hello));
+
+        let googlebot = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+        let send = |user_agent: &str, ip: &str| {
+            TestClient::get("http://127.0.0.1:5801/")
+                .add_header("user-agent", user_agent, true)
+                .add_header("x-forwarded-for", ip, true)
+        };
+
+        let mut res = send(googlebot, "66.249.66.1").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "VerifiedCrawler");
+        let res = send(googlebot, "192.0.2.1").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+        let mut res = send("DuckDuckBot/1.1", "192.0.2.2").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "Crawler");
+        let mut res = send("Mozilla/5.0 (X11; Linux x86_64) Firefox/118.0", "192.0.2.3")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "Human");
+
+        let mut res = send("curl/8.0.1", "192.0.2.4").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "Bot");
+        let res = send("curl/8.0.1", "192.0.2.4").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+        let res = send("curl/8.0.1", "192.0.2.5").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt() {
+        let site = Router::new()
+            .push(Router::with_path("admin").meta(Crawl::Disallow).get(hello))
+            .push(
+                Router::with_path("users/<id>")
+                    .meta(Crawl::Disallow)
+                    .push(Router::with_path("profile").meta(Crawl::Allow).get(hello)),
+            )
+            .push(Router::with_path("files/<**rest>").meta(Crawl::Disallow).get(hello));
+        let robots = RobotsTxt::new()
+            .group(RobotsGroup::new("BadBot").disallow("/"))
+            .router(&site)
+            .sitemap("https://example.com/sitemap.xml");
+        let router = Router::new().push(Router::with_path("robots.txt").get(robots));
+
+        let content = TestClient::get("http://127.0.0.1:5801/robots.txt")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(
+            content,
+            "User-agent: BadBot\nDisallow: /\n\n\
+             User-agent: *\nAllow: /users/*/profile\nDisallow: /admin\nDisallow: /users/*\nDisallow: /files/\n\n\
+             Sitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+}
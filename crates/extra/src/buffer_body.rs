@@ -0,0 +1,110 @@
+//! Middleware which buffers request bodies so they can be read multiple times.
+//!
+//! Reading the body of a request takes it, so an audit middleware, a signature verification middleware and the
+//! extractor of the handler can not all read it, and hoops must be ordered carefully. [`BufferBody`] buffers the
+//! body by [`Request::buffer_body`], every reader after it gets the whole body.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::buffer_body::BufferBody;
+//!
+//! #[handler]
+//! async fn verify(req: &mut Request) {
+//!     let _signed = req.payload().await;
+//! }
+//! #[handler]
+//! async fn create(req: &mut Request) -> String {
+//!     req.form::<String>("name").await.unwrap_or_default()
+//! }
+//!
+//! let router = Router::new().hoop(BufferBody::new(64 * 1024)).hoop(verify).post(create);
+//! ```
+use std::fmt::{self, Debug, Formatter};
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Middleware which buffers request bodies up to a size limit.
+///
+/// Requests with larger bodies are rejected with `413 Payload Too Large`.
+pub struct BufferBody {
+    max_size: usize,
+    skipper: Box<dyn Skipper>,
+}
+impl Debug for BufferBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferBody").field("max_size", &self.max_size).finish()
+    }
+}
+impl BufferBody {
+    /// Create a new `BufferBody` which buffers bodies up to `max_size` bytes.
+    #[inline]
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Sets skipper, bodies of skipped requests are not buffered.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for BufferBody {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        if req.buffer_body(self.max_size).await.is_err() {
+            res.render(StatusError::payload_too_large());
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn audit(req: &mut Request, depot: &mut Depot) {
+        let body = req.payload().await.unwrap().clone();
+        depot.insert("audited", String::from_utf8(body.to_vec()).unwrap());
+    }
+    #[handler]
+    async fn create(req: &mut Request, depot: &mut Depot) -> String {
+        let name = req.form::<String>("name").await.unwrap_or_default();
+        format!("{name}|{}", depot.get::<String>("audited").unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_buffer_body() {
+        let router = Router::new().hoop(BufferBody::new(16)).hoop(audit).post(create);
+        let service = Service::new(router);
+
+        let content = TestClient::post("http://127.0.0.1:5801/")
+            .raw_form("name=salvo")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "salvo|name=salvo");
+
+        let res = TestClient::post("http://127.0.0.1:5801/")
+            .raw_form("name=salvo&description=web+framework")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+}
@@ -0,0 +1,387 @@
+//! Cache-Control policy middleware.
+//!
+//! [`CacheControlPolicy`] sets `Cache-Control` header of responses by rules matching request path or response
+//! content type, so cache headers are configured in one place instead of being scattered across handlers.
+//!
+//! Read more: <https://salvo.rs>
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use salvo_core::http::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Builder of `Cache-Control` header value.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use salvo_extra::cache_control::CacheDirectives;
+///
+/// let directives = CacheDirectives::new().public().max_age(Duration::from_secs(60)).s_maxage(Duration::from_secs(3600));
+/// assert_eq!(directives.to_string(), "public, max-age=60, s-maxage=3600");
+/// assert_eq!(CacheDirectives::immutable_asset().to_string(), "public, max-age=31536000, immutable");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheDirectives {
+    public: bool,
+    private: bool,
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    must_revalidate: bool,
+    proxy_revalidate: bool,
+    immutable: bool,
+    max_age: Option<Duration>,
+    s_maxage: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+}
+impl CacheDirectives {
+    /// Create empty directives.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Directives for fingerprinted assets which never change: `public, max-age=31536000, immutable`.
+    #[inline]
+    pub fn immutable_asset() -> Self {
+        Self::new().public().max_age(Duration::from_secs(31_536_000)).immutable()
+    }
+    /// Directives for responses cached by CDNs for `s_maxage`, but revalidated by browsers:
+    /// `public, max-age=0, s-maxage=...`.
+    #[inline]
+    pub fn cdn(s_maxage: Duration) -> Self {
+        Self::new().public().max_age(Duration::ZERO).s_maxage(s_maxage)
+    }
+
+    /// Sets `public` directive.
+    #[inline]
+    pub fn public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+    /// Sets `private` directive.
+    #[inline]
+    pub fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+    /// Sets `no-cache` directive.
+    #[inline]
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+    /// Sets `no-store` directive.
+    #[inline]
+    pub fn no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+    /// Sets `no-transform` directive.
+    #[inline]
+    pub fn no_transform(mut self) -> Self {
+        self.no_transform = true;
+        self
+    }
+    /// Sets `must-revalidate` directive.
+    #[inline]
+    pub fn must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+    /// Sets `proxy-revalidate` directive.
+    #[inline]
+    pub fn proxy_revalidate(mut self) -> Self {
+        self.proxy_revalidate = true;
+        self
+    }
+    /// Sets `immutable` directive.
+    #[inline]
+    pub fn immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+    /// Sets `max-age` directive.
+    #[inline]
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    /// Sets `s-maxage` directive, it is used by shared caches such as CDNs.
+    #[inline]
+    pub fn s_maxage(mut self, s_maxage: Duration) -> Self {
+        self.s_maxage = Some(s_maxage);
+        self
+    }
+    /// Sets `stale-while-revalidate` directive.
+    #[inline]
+    pub fn stale_while_revalidate(mut self, duration: Duration) -> Self {
+        self.stale_while_revalidate = Some(duration);
+        self
+    }
+    /// Sets `stale-if-error` directive.
+    #[inline]
+    pub fn stale_if_error(mut self, duration: Duration) -> Self {
+        self.stale_if_error = Some(duration);
+        self
+    }
+
+    /// Convert to header value.
+    #[inline]
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("cache directives should be a valid header value")
+    }
+}
+impl Display for CacheDirectives {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        let mut write = |f: &mut Formatter<'_>, directive: &str, value: Option<u64>| -> fmt::Result {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            f.write_str(directive)?;
+            if let Some(value) = value {
+                write!(f, "={value}")?;
+            }
+            Ok(())
+        };
+        let flags = [
+            (self.public, "public"),
+            (self.private, "private"),
+            (self.no_cache, "no-cache"),
+            (self.no_store, "no-store"),
+            (self.no_transform, "no-transform"),
+            (self.must_revalidate, "must-revalidate"),
+            (self.proxy_revalidate, "proxy-revalidate"),
+        ];
+        for (enabled, directive) in flags {
+            if enabled {
+                write(f, directive, None)?;
+            }
+        }
+        let durations = [
+            (self.max_age, "max-age"),
+            (self.s_maxage, "s-maxage"),
+            (self.stale_while_revalidate, "stale-while-revalidate"),
+            (self.stale_if_error, "stale-if-error"),
+        ];
+        for (duration, directive) in durations {
+            if let Some(duration) = duration {
+                write(f, directive, Some(duration.as_secs()))?;
+            }
+        }
+        if self.immutable {
+            write(f, "immutable", None)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Matcher {
+    Path(String),
+    ContentType(String),
+}
+impl Matcher {
+    fn matches(&self, path: &str, content_type: Option<&str>) -> bool {
+        match self {
+            Matcher::Path(pattern) => glob_match(pattern, path),
+            Matcher::ContentType(pattern) => content_type.is_some_and(|content_type| {
+                let essence = content_type.split(';').next().unwrap_or_default().trim();
+                match pattern.strip_suffix("/*") {
+                    Some(main) => essence
+                        .split_once('/')
+                        .is_some_and(|(ty, _)| ty.eq_ignore_ascii_case(main)),
+                    None => essence.eq_ignore_ascii_case(pattern),
+                }
+            }),
+        }
+    }
+}
+
+/// Match `path` with `pattern`, `*` matches any characters except `/` and `**` matches any characters.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**") {
+        return (0..=path.len())
+            .filter(|i| path.is_char_boundary(*i))
+            .any(|i| glob_match(rest, &path[i..]));
+    }
+    if let Some(rest) = pattern.strip_prefix('*') {
+        let end = path.find('/').unwrap_or(path.len());
+        return (0..=end)
+            .filter(|i| path.is_char_boundary(*i))
+            .any(|i| glob_match(rest, &path[i..]));
+    }
+    match (pattern.chars().next(), path.chars().next()) {
+        (Some(p), Some(c)) if p == c => glob_match(&pattern[p.len_utf8()..], &path[c.len_utf8()..]),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Middleware sets `Cache-Control` header by rules.
+///
+/// Rules are checked in the order they are added after the handler is run, the first matched rule is used and
+/// the fallback directives are used if no rule matches. Only successful and `304 Not Modified` responses are
+/// affected, and `Cache-Control` set by handlers is kept unless [`CacheControlPolicy::overwrite`] is enabled.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use salvo_core::prelude::*;
+/// use salvo_extra::cache_control::{CacheControlPolicy, CacheDirectives};
+///
+/// let policy = CacheControlPolicy::new()
+///     .path("/assets/**", CacheDirectives::immutable_asset())
+///     .content_type("application/json", CacheDirectives::new().no_store())
+///     .content_type("text/html", CacheDirectives::cdn(Duration::from_secs(300)))
+///     .fallback(CacheDirectives::new().no_cache());
+/// let router = Router::new().hoop(policy);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CacheControlPolicy {
+    rules: Vec<(Matcher, HeaderValue)>,
+    fallback: Option<HeaderValue>,
+    overwrite: bool,
+}
+impl CacheControlPolicy {
+    /// Create a new `CacheControlPolicy` without rules.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a rule for request paths matched by `pattern`.
+    ///
+    /// In pattern, `*` matches any characters except `/` and `**` matches any characters, such as `/assets/**`
+    /// and `/*.js`.
+    #[inline]
+    pub fn path(mut self, pattern: impl Into<String>, directives: CacheDirectives) -> Self {
+        self.rules
+            .push((Matcher::Path(pattern.into()), directives.to_header_value()));
+        self
+    }
+    /// Add a rule for response content type, such as `application/json` or `image/*`.
+    #[inline]
+    pub fn content_type(mut self, pattern: impl Into<String>, directives: CacheDirectives) -> Self {
+        self.rules
+            .push((Matcher::ContentType(pattern.into()), directives.to_header_value()));
+        self
+    }
+    /// Sets directives used when no rule matches.
+    #[inline]
+    pub fn fallback(mut self, directives: CacheDirectives) -> Self {
+        self.fallback = Some(directives.to_header_value());
+        self
+    }
+    /// Sets whether to overwrite `Cache-Control` header set by handlers. Default is `false`.
+    #[inline]
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    fn find(&self, path: &str, content_type: Option<&str>) -> Option<&HeaderValue> {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(path, content_type))
+            .map(|(_, value)| value)
+            .or(self.fallback.as_ref())
+    }
+}
+
+#[async_trait]
+impl Handler for CacheControlPolicy {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.call_next(req, depot, res).await;
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        if !(status.is_success() || status == StatusCode::NOT_MODIFIED) {
+            return;
+        }
+        if !self.overwrite && res.headers().contains_key(CACHE_CONTROL) {
+            return;
+        }
+        let content_type = res.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+        if let Some(value) = self.find(req.uri().path(), content_type) {
+            let value = value.clone();
+            res.headers_mut().insert(CACHE_CONTROL, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+    use salvo_core::writing::{Json, Text};
+
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/assets/**", "/assets/js/app.1234.js"));
+        assert!(glob_match("/*.js", "/app.js"));
+        assert!(!glob_match("/*.js", "/js/app.js"));
+        assert!(glob_match("/**/*.css", "/a/b/site.css"));
+        assert!(!glob_match("/assets/*", "/assets/a/b"));
+        assert!(glob_match("/exact", "/exact"));
+        assert!(!glob_match("/exact", "/exact/"));
+    }
+
+    #[handler]
+    async fn asset() -> &'static str {
+        "asset"
+    }
+    #[handler]
+    async fn api() -> Json<&'static str> {
+        Json("api")
+    }
+    #[handler]
+    async fn page() -> Text<&'static str> {
+        Text::Html("<p>page</p>")
+    }
+    #[handler]
+    async fn custom(res: &mut Response) {
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("private"));
+    }
+    #[handler]
+    async fn missing(res: &mut Response) {
+        res.status_code(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cache_control_policy() {
+        let policy = CacheControlPolicy::new()
+            .path("/assets/**", CacheDirectives::immutable_asset())
+            .content_type("application/json", CacheDirectives::new().no_store())
+            .fallback(CacheDirectives::new().no_cache());
+        let router = Router::new()
+            .hoop(policy)
+            .push(Router::with_path("assets/<**>").get(asset))
+            .push(Router::with_path("api").get(api))
+            .push(Router::with_path("page").get(page))
+            .push(Router::with_path("custom").get(custom))
+            .push(Router::with_path("missing").get(missing));
+        let service = Service::new(router);
+
+        async fn cache_control(service: &Service, url: &str) -> Option<String> {
+            let res = TestClient::get(url).send(service).await;
+            res.headers()
+                .get(CACHE_CONTROL)
+                .map(|v| v.to_str().unwrap().to_owned())
+        }
+        assert_eq!(
+            cache_control(&service, "http://127.0.0.1:5801/assets/app.js").await.unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(cache_control(&service, "http://127.0.0.1:5801/api").await.unwrap(), "no-store");
+        assert_eq!(cache_control(&service, "http://127.0.0.1:5801/page").await.unwrap(), "no-cache");
+        assert_eq!(cache_control(&service, "http://127.0.0.1:5801/custom").await.unwrap(), "private");
+        assert!(cache_control(&service, "http://127.0.0.1:5801/missing").await.is_none());
+    }
+}
@@ -1,16 +1,19 @@
 /*!
 # Salvo handlers for etag and last-modified-since headers.
 This crate provides three handlers: [`ETag`], [`Modified`], and
-[`CachingHeaders`].
+[`CachingHeaders`]. [`AutoETag`] is a faster etag handler for polled
+apis, it can be configured for every route by [`ETagMode`].
 Unless you are sure that you _don't_ want either etag or last-modified
 behavior, please use the combined [`CachingHeaders`] handler.
  */
 
 use etag::EntityTag;
-use salvo_core::http::header::{ETAG, IF_NONE_MATCH};
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::{HeaderValue, ETAG, IF_NONE_MATCH};
 use salvo_core::http::headers::{self, HeaderMapExt};
-use salvo_core::http::{ResBody, StatusCode};
+use salvo_core::http::{Method, ResBody, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use xxhash_rust::xxh3::Xxh3;
 
 /**
 # Etag and If-None-Match header handler
@@ -187,6 +190,160 @@ impl Handler for CachingHeaders {
     }
 }
 
+/// Mode of etags set by [`AutoETag`].
+///
+/// Attach it to routers with [`Router::meta`](salvo_core::Router::meta) to override the mode of [`AutoETag`]
+/// for their routes, the innermost one is used.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ETagMode {
+    /// Etags are not set.
+    Off,
+    /// Weak etags are set, such as `W/"0123456789abcdef0123456789abcdef"`.
+    #[default]
+    Weak,
+    /// Strong etags are set, they should only be used if bodies of the same etag are byte for byte identical.
+    Strong,
+}
+
+/**
+Handler that sets an etag from the hash of the response body and answers `If-None-Match`.
+
+It handles `GET` and `HEAD` requests with successful responses, the body is hashed chunk by chunk by xxh3, so
+the body is not copied. Streamed bodies are not hashed. If a handler sets an etag, it is used instead.
+Etags are compared by the weak comparison function as required for `If-None-Match`, requests with a matched
+etag get `304 Not Modified` without body.
+
+# Example
+
+```
+use salvo_core::prelude::*;
+use salvo_extra::caching_headers::{AutoETag, ETagMode};
+
+#[handler]
+async fn orders() -> &'static str {
+    r#"[{"id": 1}]"#
+}
+
+let router = Router::new()
+    .hoop(AutoETag::new())
+    .push(Router::with_path("orders").get(orders))
+    .push(Router::with_path("events").meta(ETagMode::Off).get(orders));
+```
+*/
+pub struct AutoETag {
+    mode: ETagMode,
+    skipper: Box<dyn Skipper>,
+}
+impl std::fmt::Debug for AutoETag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoETag").field("mode", &self.mode).finish()
+    }
+}
+impl Default for AutoETag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AutoETag {
+    /// Constructs a new `AutoETag` handler which sets weak etags.
+    pub fn new() -> Self {
+        Self {
+            mode: ETagMode::Weak,
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Sets the default mode for routes without [`ETagMode`] metadata.
+    pub fn mode(mut self, mode: ETagMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets skipper.
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+/// Hash the body chunk by chunk, returns `None` for bodies which can not be hashed without reading them.
+fn hash_body(body: &ResBody) -> Option<u128> {
+    let mut hasher = Xxh3::new();
+    match body {
+        ResBody::Once(bytes) => hasher.update(bytes),
+        ResBody::Chunks(chunks) => {
+            for chunk in chunks {
+                hasher.update(chunk);
+            }
+        }
+        _ => return None,
+    }
+    Some(hasher.digest128())
+}
+
+/// Returns `true` if the etag matches `If-None-Match` by the weak comparison function.
+fn matches_if_none_match(etag: &str, if_none_match: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+#[async_trait]
+impl Handler for AutoETag {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        ctrl.call_next(req, depot, res).await;
+        if ctrl.is_ceased() || !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return;
+        }
+        if !res.status_code.map(|s| s.is_success()).unwrap_or(true) {
+            return;
+        }
+        let mode = req.route_metadata().get::<ETagMode>().copied().unwrap_or(self.mode);
+        if mode == ETagMode::Off {
+            return;
+        }
+
+        let etag = match res.headers().get(ETAG).and_then(|etag| etag.to_str().ok()) {
+            Some(etag) => etag.to_owned(),
+            None => {
+                let Some(hash) = hash_body(&res.body) else {
+                    return;
+                };
+                let etag = match mode {
+                    ETagMode::Strong => format!("\"{hash:032x}\""),
+                    _ => format!("W/\"{hash:032x}\""),
+                };
+                match HeaderValue::from_str(&etag) {
+                    Ok(value) => {
+                        res.headers_mut().insert(ETAG, value);
+                    }
+                    Err(e) => {
+                        tracing::error!(error = ?e, "failed to set etag");
+                        return;
+                    }
+                }
+                etag
+            }
+        };
+
+        let matched = req
+            .headers()
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| matches_if_none_match(&etag, value));
+        if matched {
+            res.body(ResBody::None);
+            res.status_code(StatusCode::NOT_MODIFIED);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use salvo_core::http::header::*;
@@ -216,4 +373,44 @@ mod tests {
         assert_eq!(respone.status_code, Some(StatusCode::NOT_MODIFIED));
         assert!(respone.body.is_none());
     }
+
+    #[handler]
+    async fn orders() -> &'static str {
+        r#"[{"id": 1}]"#
+    }
+    #[handler]
+    async fn create() -> &'static str {
+        "created"
+    }
+
+    #[tokio::test]
+    async fn test_auto_etag() {
+        let router = Router::with_hoop(AutoETag::new())
+            .push(Router::with_path("orders").get(orders).post(create))
+            .push(Router::with_path("strong").meta(ETagMode::Strong).get(orders))
+            .push(Router::with_path("off").meta(ETagMode::Off).get(orders));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5800/orders").send(&service).await;
+        let etag = res.headers().get(ETAG).unwrap().to_str().unwrap().to_owned();
+        assert!(etag.starts_with("W/\""));
+        let res = TestClient::get("http://127.0.0.1:5800/orders")
+            .add_header(IF_NONE_MATCH, format!("\"other\", {etag}"), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert!(res.body.is_none());
+
+        let res = TestClient::get("http://127.0.0.1:5800/strong")
+            .add_header(IF_NONE_MATCH, etag.trim_start_matches("W/"), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert_eq!(res.headers().get(ETAG).unwrap(), etag.trim_start_matches("W/"));
+
+        let res = TestClient::post("http://127.0.0.1:5800/orders").send(&service).await;
+        assert!(res.headers().get(ETAG).is_none());
+        let res = TestClient::get("http://127.0.0.1:5800/off").send(&service).await;
+        assert!(res.headers().get(ETAG).is_none());
+    }
 }
@@ -1,41 +1,195 @@
 //! Catch panic middleware.
 //!
 //! Read more: <https://salvo.rs>
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
 use std::panic::AssertUnwindSafe;
+use std::sync::Once;
 
+use futures_util::future::poll_fn;
 use futures_util::FutureExt;
 
-use salvo_core::http::{Request, Response, StatusError};
-use salvo_core::{async_trait, Depot, FlowCtrl, Error, Handler};
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::http::{Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
+
+thread_local! {
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+    static CAPTURE_BACKTRACE: Cell<bool> = const { Cell::new(false) };
+}
+static INSTALL_HOOK: Once = Once::new();
+
+/// Installs a panic hook which captures the backtrace of panics, the previous hook is still called.
+///
+/// Backtraces are only captured while a [`CaptureGuard`] is alive on the panicking thread, other panics in the
+/// process are not affected.
+fn install_backtrace_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CAPTURE_BACKTRACE.with(|capture| capture.get()) {
+                BACKTRACE.with(|bt| *bt.borrow_mut() = Some(Backtrace::force_capture()));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Enables backtrace capturing on current thread until it is dropped.
+struct CaptureGuard(bool);
+impl CaptureGuard {
+    fn enter() -> Self {
+        Self(CAPTURE_BACKTRACE.with(|capture| capture.replace(true)))
+    }
+}
+impl Drop for CaptureGuard {
+    fn drop(&mut self) {
+        CAPTURE_BACKTRACE.with(|capture| capture.set(self.0));
+    }
+}
+
+/// Information about a panic caught by [`CatchPanic`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PanicReport {
+    /// The panic message.
+    pub message: String,
+    /// The backtrace captured when panic occurred, it is only available if backtrace capturing is enabled.
+    pub backtrace: Option<String>,
+}
+impl PanicReport {
+    fn new(payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(msg) = payload.downcast_ref::<&str>() {
+            (*msg).to_owned()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic payload".to_owned()
+        };
+        let backtrace = BACKTRACE.with(|bt| bt.borrow_mut().take()).map(|bt| bt.to_string());
+        Self { message, backtrace }
+    }
+}
+
+/// Hook called when a panic is caught, it can be used to report panics to services like Sentry.
+pub type PanicHook = Box<dyn Fn(&Request, &PanicReport) + Send + Sync + 'static>;
 
 /// This middleware catches panics and write `500 INTERNAL SERVER ERROR`
 /// into response. This middleware should be used as the first middleware.
-#[derive(Default, Debug)]
-pub struct CatchPanic {}
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::catch_panic::CatchPanic;
+///
+/// let catch_panic = CatchPanic::new()
+///     .backtrace(true)
+///     .problem_details(true)
+///     .hook(|req, report| eprintln!("panic on {}: {}", req.uri(), report.message));
+/// let router = Router::new().hoop(catch_panic);
+/// ```
+#[derive(Default)]
+pub struct CatchPanic {
+    backtrace: bool,
+    problem_details: bool,
+    hook: Option<PanicHook>,
+}
 impl CatchPanic {
     /// Create new `CatchPanic` middleware.
     #[inline]
     pub fn new() -> Self {
-        CatchPanic {}
+        CatchPanic::default()
+    }
+
+    /// Sets whether backtraces of panics are captured, default is `false`.
+    ///
+    /// A process wide panic hook is installed when it is enabled, the previous panic hook is still called. Backtraces
+    /// are only captured for panics in handlers called by this middleware.
+    #[inline]
+    pub fn backtrace(mut self, backtrace: bool) -> Self {
+        if backtrace {
+            install_backtrace_hook();
+        }
+        self.backtrace = backtrace;
+        self
+    }
+
+    /// Sets whether the response is rendered as Problem Details ([RFC 7807](https://www.rfc-editor.org/rfc/rfc7807)).
+    #[inline]
+    pub fn problem_details(mut self, problem_details: bool) -> Self {
+        self.problem_details = problem_details;
+        self
+    }
+
+    /// Sets the hook called when a panic is caught.
+    #[inline]
+    pub fn hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Request, &PanicReport) + Send + Sync + 'static,
+    {
+        self.hook = Some(Box::new(hook));
+        self
     }
 }
 
 #[async_trait]
 impl Handler for CatchPanic {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        if let Err(e) = AssertUnwindSafe(ctrl.call_next(req, depot, res)).catch_unwind().await {
-            tracing::error!(error = ?e, "panic occurred");
-            res.render(
-                StatusError::internal_server_error()
-                    .brief("panic occurred on server")
-                    .cause(Error::other(format!("{e:#?}"))),
-            );
+        if self.backtrace {
+            BACKTRACE.with(|bt| bt.borrow_mut().take());
+        }
+        let result = {
+            let next = AssertUnwindSafe(ctrl.call_next(req, depot, res)).catch_unwind();
+            futures_util::pin_mut!(next);
+            if self.backtrace {
+                // Tasks may be moved between threads, so capturing is enabled for each poll.
+                poll_fn(|cx| {
+                    let _guard = CaptureGuard::enter();
+                    next.as_mut().poll(cx)
+                })
+                .await
+            } else {
+                next.await
+            }
+        };
+        if let Err(e) = result {
+            let report = PanicReport::new(&*e);
+            tracing::error!(message = %report.message, backtrace = ?report.backtrace, "panic occurred");
+            if let Some(hook) = &self.hook {
+                hook(req, &report);
+            }
+            ctrl.skip_rest();
+            if self.problem_details {
+                let problem = serde_json::json!({
+                    "type": "about:blank",
+                    "title": "Internal Server Error",
+                    "status": 500,
+                    "detail": "panic occurred on server",
+                    "instance": req.uri().path(),
+                });
+                res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+                // Replace the partial body written before the panic.
+                res.body(problem.to_string().into());
+            } else {
+                res.render(
+                    StatusError::internal_server_error()
+                        .brief("panic occurred on server")
+                        .cause(Error::other(report.message)),
+                );
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
     use tracing_test::traced_test;
@@ -62,4 +216,42 @@ mod tests {
             .unwrap();
         assert!(logs_contain("panic occurred"));
     }
+
+    #[tokio::test]
+    async fn test_catch_panic_report() {
+        #[handler]
+        async fn hello(res: &mut Response) {
+            res.write_body("partial").unwrap();
+            panic!("panic error!");
+        }
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports2 = reports.clone();
+        let router = Router::new()
+            .hoop(
+                CatchPanic::new()
+                    .backtrace(true)
+                    .problem_details(true)
+                    .hook(move |req, report| {
+                        reports2.lock().unwrap().push((
+                            req.uri().path().to_owned(),
+                            report.message.clone(),
+                            report.backtrace.is_some(),
+                        ));
+                    }),
+            )
+            .push(Router::with_path("hello").get(hello));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(router).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/problem+json");
+        let content = res.take_string().await.unwrap();
+        assert!(content.starts_with('{'));
+        assert!(content.contains(r#""status":500"#));
+        assert!(content.contains(r#""instance":"/hello""#));
+        assert_eq!(
+            *reports.lock().unwrap(),
+            vec![("/hello".to_owned(), "panic error!".to_owned(), true)]
+        );
+    }
 }
@@ -2,7 +2,12 @@
 //!
 //! Read more: <https://salvo.rs>
 
-use tokio::sync::Semaphore;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 use salvo_core::http::StatusError;
 use salvo_core::http::{Request, Response};
@@ -37,3 +42,234 @@ pub fn max_concurrency(size: usize) -> MaxConcurrency {
         semaphore: Semaphore::new(size),
     }
 }
+
+/// Key getter identifies the group of a request, requests in the same group share the same concurrency limit.
+pub type KeyGetter = Box<dyn Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static>;
+
+struct Slot {
+    semaphore: Arc<Semaphore>,
+    waiting: AtomicUsize,
+}
+
+/// Middleware limits the number of requests processed simultaneously, with a bounded wait queue.
+///
+/// At most `max_concurrency` requests are processed at the same time, up to `queue_size` requests wait for a
+/// permit, other requests are responded with `503 Service Unavailable`. Waiting requests are also responded with
+/// `503 Service Unavailable` if they wait longer than `queue_timeout`.
+///
+/// Every `ConcurrencyLimiter` has its own limit, so it can be added to a route to limit the route. When a key
+/// getter is set, the limit is applied to every key separately, and requests without a key are not limited.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use salvo_core::prelude::*;
+/// use salvo_extra::concurrency_limiter::ConcurrencyLimiter;
+///
+/// #[handler]
+/// async fn render() -> &'static str {
+///     "rendered"
+/// }
+///
+/// let router = Router::with_path("render").hoop(
+///     ConcurrencyLimiter::new(4)
+///         .queue_size(16)
+///         .queue_timeout(Duration::from_secs(10)),
+/// ).get(render);
+/// ```
+pub struct ConcurrencyLimiter {
+    max_concurrency: usize,
+    queue_size: usize,
+    queue_timeout: Option<Duration>,
+    key_getter: Option<KeyGetter>,
+    slots: Mutex<HashMap<String, Arc<Slot>>>,
+}
+impl std::fmt::Debug for ConcurrencyLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConcurrencyLimiter")
+            .field("max_concurrency", &self.max_concurrency)
+            .field("queue_size", &self.queue_size)
+            .field("queue_timeout", &self.queue_timeout)
+            .finish()
+    }
+}
+impl ConcurrencyLimiter {
+    /// Create a new `ConcurrencyLimiter` with max concurrency, the wait queue is empty by default.
+    #[inline]
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency,
+            queue_size: 0,
+            queue_timeout: None,
+            key_getter: None,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets max number of requests waiting for a permit.
+    #[inline]
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Sets max duration a request waits for a permit.
+    #[inline]
+    pub fn queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = Some(queue_timeout);
+        self
+    }
+
+    /// Sets key getter, the limit is applied to every key separately.
+    #[inline]
+    pub fn key_getter<G>(mut self, key_getter: G) -> Self
+    where
+        G: Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key_getter = Some(Box::new(key_getter));
+        self
+    }
+
+    fn slot(&self, key: &str) -> Arc<Slot> {
+        let mut slots = self.slots.lock().unwrap();
+        slots
+            .entry(key.to_owned())
+            .or_insert_with(|| {
+                Arc::new(Slot {
+                    semaphore: Arc::new(Semaphore::new(self.max_concurrency)),
+                    waiting: AtomicUsize::new(0),
+                })
+            })
+            .clone()
+    }
+
+    /// Remove the slot if it is not used by any request.
+    fn release(&self, key: &str, slot: Arc<Slot>) {
+        let mut slots = self.slots.lock().unwrap();
+        // slots are only cloned with the lock held, so the count can not be increased now.
+        if Arc::strong_count(&slot) == 2 && slot.semaphore.available_permits() == self.max_concurrency {
+            slots.remove(key);
+        }
+    }
+
+    async fn acquire(&self, slot: &Slot) -> Option<OwnedSemaphorePermit> {
+        if let Ok(permit) = slot.semaphore.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+        if slot.waiting.fetch_add(1, Ordering::AcqRel) >= self.queue_size {
+            slot.waiting.fetch_sub(1, Ordering::AcqRel);
+            return None;
+        }
+        let acquire = slot.semaphore.clone().acquire_owned();
+        let permit = match self.queue_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, acquire).await.ok().and_then(Result::ok),
+            None => acquire.await.ok(),
+        };
+        slot.waiting.fetch_sub(1, Ordering::AcqRel);
+        permit
+    }
+}
+
+#[async_trait]
+impl Handler for ConcurrencyLimiter {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let key = match &self.key_getter {
+            Some(key_getter) => match key_getter(req, depot) {
+                Some(key) => key,
+                None => {
+                    ctrl.call_next(req, depot, res).await;
+                    return;
+                }
+            },
+            None => String::new(),
+        };
+        let slot = self.slot(&key);
+        match self.acquire(&slot).await {
+            Some(permit) => {
+                ctrl.call_next(req, depot, res).await;
+                drop(permit);
+            }
+            None => {
+                tracing::debug!(key = %key, "concurrency limit reached");
+                res.render(StatusError::service_unavailable().brief("Too many concurrent requests."));
+                ctrl.skip_rest();
+            }
+        }
+        if self.key_getter.is_some() {
+            self.release(&key, slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "done"
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limiter() {
+        let router = Router::new().push(
+            Router::with_path("<key>")
+                .hoop(
+                    ConcurrencyLimiter::new(1)
+                        .queue_size(1)
+                        .key_getter(|req, _| req.param::<String>("key")),
+                )
+                .get(slow),
+        );
+        let service = Arc::new(Service::new(router));
+
+        let mut tasks = Vec::new();
+        for key in ["a", "a", "a", "b"] {
+            let service = service.clone();
+            tasks.push(tokio::spawn(async move {
+                TestClient::get(format!("http://127.0.0.1:5801/{key}"))
+                    .send(&*service)
+                    .await
+                    .status_code
+                    .unwrap()
+            }));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let mut statuses = Vec::new();
+        for task in tasks {
+            statuses.push(task.await.unwrap());
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::OK
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limiter_queue_timeout() {
+        let limiter = ConcurrencyLimiter::new(1)
+            .queue_size(1)
+            .queue_timeout(Duration::from_millis(50));
+        let service = Arc::new(Service::new(Router::new().hoop(limiter).get(slow)));
+
+        let first = tokio::spawn({
+            let service = service.clone();
+            async move { TestClient::get("http://127.0.0.1:5801/").send(&*service).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&*service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(first.await.unwrap().status_code.unwrap(), StatusCode::OK);
+    }
+}
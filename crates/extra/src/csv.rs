@@ -0,0 +1,378 @@
+//! CSV streaming responder and extractor.
+//!
+//! [`CsvStream`] renders a stream of serializable rows as `text/csv`, the header row is derived from the field
+//! names of the first row. [`CsvBody`] parses an uploaded CSV body into typed records, rows which can not be
+//! parsed are collected as [`CsvRowError`] instead of failing the whole request.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::stream;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::csv::{CsvBody, CsvStream};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! #[handler]
+//! async fn export(res: &mut Response) {
+//!     let users = stream::iter((0..1000).map(|id| User { id, name: format!("user {id}") }));
+//!     res.render(CsvStream::new(users).file_name("users.csv"));
+//! }
+//!
+//! #[handler]
+//! async fn import(body: CsvBody<User>, res: &mut Response) {
+//!     if body.is_valid() {
+//!         res.render(format!("imported {} users", body.records().len()));
+//!     } else {
+//!         res.status_code(StatusCode::UNPROCESSABLE_ENTITY);
+//!         res.render(Json(body.errors()));
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("users").get(export).post(import);
+//!     let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Display, Formatter};
+
+use ::csv::{ReaderBuilder, WriterBuilder};
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+use salvo_core::http::{ParseError, Request, Response};
+use salvo_core::{async_trait, BoxedError, Scribe};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Content type used when rendering [`CsvStream`].
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Max number of rows written into one chunk of the response body.
+const ROWS_PER_CHUNK: usize = 128;
+
+/// A stream of rows rendered as CSV.
+pub struct CsvStream<T> {
+    inner: BoxStream<'static, T>,
+    delimiter: u8,
+    has_headers: bool,
+    file_name: Option<String>,
+}
+
+impl<T> CsvStream<T> {
+    /// Create a new `CsvStream` from a stream of rows.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        Self {
+            inner: stream.boxed(),
+            delimiter: b',',
+            has_headers: true,
+            file_name: None,
+        }
+    }
+
+    /// Sets the field delimiter, default is `,`.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether a header row is written before the first row, default is `true`.
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Sets the file name, the response is sent as an attachment with this name.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+}
+
+impl<T> Debug for CsvStream<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsvStream")
+            .field("delimiter", &self.delimiter)
+            .field("has_headers", &self.has_headers)
+            .field("file_name", &self.file_name)
+            .finish()
+    }
+}
+
+impl<T> Scribe for CsvStream<T>
+where
+    T: Serialize + Send + 'static,
+{
+    fn render(self, res: &mut Response) {
+        let Self {
+            inner,
+            delimiter,
+            has_headers,
+            file_name,
+        } = self;
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(CSV_CONTENT_TYPE));
+        if let Some(file_name) = file_name {
+            match HeaderValue::from_str(&format!("attachment; filename=\"{}\"", file_name.replace('"', "\\\""))) {
+                Ok(value) => {
+                    res.headers_mut().insert(CONTENT_DISPOSITION, value);
+                }
+                Err(e) => {
+                    tracing::warn!(error = ?e, %file_name, "invalid csv file name");
+                }
+            }
+        }
+        let mut first = true;
+        let chunks = inner.ready_chunks(ROWS_PER_CHUNK).map(move |rows| {
+            // The header row is only written by the writer of the first chunk.
+            let mut writer = WriterBuilder::new()
+                .delimiter(delimiter)
+                .has_headers(has_headers && first)
+                .from_writer(Vec::new());
+            first = false;
+            for row in rows {
+                if let Err(e) = writer.serialize(row) {
+                    tracing::error!(error = ?e, "csv serialize error");
+                    return Err(BoxedError::from(e));
+                }
+            }
+            writer.into_inner().map_err(|e| BoxedError::from(e.into_error()))
+        });
+        res.stream(chunks);
+    }
+}
+
+/// An error of a row which can not be parsed.
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct CsvRowError {
+    /// Line number of the row in the uploaded file, starting from `1`.
+    pub line: u64,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl Display for CsvRowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl StdError for CsvRowError {}
+
+impl CsvRowError {
+    fn new(error: ::csv::Error, line: u64) -> Self {
+        let line = error.position().map(|pos| pos.line()).unwrap_or(line);
+        let message = match error.kind() {
+            ::csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+            _ => error.to_string(),
+        };
+        Self { line, message }
+    }
+}
+
+/// Records parsed from an uploaded CSV body.
+///
+/// The first row is treated as the header row, and fields are matched to records by name. The request must have no
+/// content type, or one of `text/csv` and `application/csv`, `text/tab-separated-values` is parsed with tab as
+/// the delimiter.
+pub struct CsvBody<T> {
+    records: Vec<T>,
+    errors: Vec<CsvRowError>,
+}
+
+impl<T> CsvBody<T>
+where
+    T: DeserializeOwned,
+{
+    /// Parse CSV data with the given field delimiter.
+    pub fn parse(data: &[u8], delimiter: u8) -> Self {
+        let mut reader = ReaderBuilder::new().delimiter(delimiter).from_reader(data);
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        let mut line = 1;
+        for result in reader.deserialize::<T>() {
+            line += 1;
+            match result {
+                Ok(record) => records.push(record),
+                Err(e) => errors.push(CsvRowError::new(e, line)),
+            }
+        }
+        Self { records, errors }
+    }
+}
+
+impl<T> CsvBody<T> {
+    /// Records which are parsed successfully.
+    pub fn records(&self) -> &[T] {
+        &self.records
+    }
+
+    /// Errors of rows which can not be parsed.
+    pub fn errors(&self) -> &[CsvRowError] {
+        &self.errors
+    }
+
+    /// Returns `true` if all rows are parsed successfully.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Consumes self and returns the parsed records and row errors.
+    pub fn into_parts(self) -> (Vec<T>, Vec<CsvRowError>) {
+        (self.records, self.errors)
+    }
+
+    /// Consumes self and returns the records if all rows are parsed successfully, otherwise the row errors.
+    pub fn into_result(self) -> Result<Vec<T>, Vec<CsvRowError>> {
+        if self.errors.is_empty() {
+            Ok(self.records)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+impl<T> Debug for CsvBody<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsvBody")
+            .field("records", &self.records)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<'de, T> Extractible<'de> for CsvBody<T>
+where
+    T: DeserializeOwned + Send,
+{
+    fn metadata() -> &'de Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    async fn extract(req: &'de mut Request) -> Result<Self, ParseError> {
+        let delimiter = match req.content_type() {
+            None => b',',
+            Some(ctype) => match ctype.essence_str() {
+                "text/csv" | "application/csv" => b',',
+                "text/tab-separated-values" => b'\t',
+                _ => return Err(ParseError::InvalidContentType),
+            },
+        };
+        let payload = req.payload().await?;
+        if payload.is_empty() {
+            return Err(ParseError::EmptyBody);
+        }
+        Ok(Self::parse(payload, delimiter))
+    }
+}
+
+impl<'de, T> Deserialize<'de> for CsvBody<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<T>::deserialize(deserializer).map(|records| Self {
+            records,
+            errors: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct User {
+        id: u64,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_csv_render() {
+        #[handler]
+        async fn export(res: &mut Response) {
+            let users = stream::iter(vec![
+                User {
+                    id: 1,
+                    name: "Smith, John".into(),
+                },
+                User {
+                    id: 2,
+                    name: "say \"hi\"".into(),
+                },
+            ]);
+            res.render(CsvStream::new(users).file_name("users.csv"));
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .send(Router::new().get(export))
+            .await;
+        assert_eq!(res.headers()[CONTENT_TYPE], CSV_CONTENT_TYPE);
+        assert_eq!(res.headers()[CONTENT_DISPOSITION], "attachment; filename=\"users.csv\"");
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            "id,name\n1,\"Smith, John\"\n2,\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_csv_extract() {
+        #[handler]
+        async fn import(body: CsvBody<User>) -> String {
+            let errors: Vec<_> = body.errors().iter().map(ToString::to_string).collect();
+            format!("{}|{}", body.records().len(), errors.join(";"))
+        }
+
+        let service = Service::new(Router::new().post(import));
+        let content = TestClient::post("http://127.0.0.1:5800/")
+            .bytes(b"id,name\n1,alice\nx,bob\n3,carol\n4\n".to_vec())
+            .add_header(CONTENT_TYPE, "text/csv", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        let (count, errors) = content.split_once('|').unwrap();
+        assert_eq!(count, "2");
+        let errors: Vec<_> = errors.split(';').collect();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("line 3:"));
+        assert!(errors[1].starts_with("line 5:"));
+    }
+
+    #[test]
+    fn test_csv_parse() {
+        let body = CsvBody::<User>::parse(b"id;name\n1;alice\n", b';');
+        assert_eq!(
+            body.into_result().unwrap(),
+            vec![User {
+                id: 1,
+                name: "alice".into()
+            }]
+        );
+    }
+}
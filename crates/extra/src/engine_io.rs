@@ -0,0 +1,885 @@
+//! Engine.io compatible endpoint for Salvo web framework.
+//!
+//! Clients connect with HTTP long polling and upgrade to WebSocket, or connect with WebSocket directly,
+//! following the version 4 of the engine.io protocol which is used by Socket.IO 3 and later. Socket.IO
+//! packets are delivered as text messages, so they can be parsed by the application.
+//!
+//! Read more: <https://socket.io/docs/v4/engine-io-protocol/>
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use futures_util::sink::SinkExt;
+use futures_util::stream::StreamExt;
+use rand::RngCore;
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::http::{Method, StatusCode};
+use salvo_core::writing::Text;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
+use tokio::sync::{mpsc, Notify};
+
+use crate::websocket::rooms::RoomRegistry;
+use crate::websocket::{Message, WebSocket, WebSocketUpgrade};
+
+/// Separator of packets in a long polling payload.
+const SEPARATOR: char = '\x1e';
+
+/// Id of a session registered in [`Hub`], it is the `sid` of the engine.io protocol.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct SocketId(Arc<str>);
+impl SocketId {
+    fn generate() -> Self {
+        let mut bytes = [0; 15];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(URL_SAFE_NO_PAD.encode(bytes).into())
+    }
+    /// Get the id as a string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl Display for SocketId {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Packet of the engine.io protocol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Packet {
+    Open(String),
+    Close,
+    Ping(String),
+    Pong(String),
+    Message(Message),
+    Upgrade,
+    Noop,
+}
+impl Packet {
+    fn decode(text: &str) -> Option<Self> {
+        if let Some(data) = text.strip_prefix('b') {
+            return STANDARD
+                .decode(data)
+                .ok()
+                .map(|data| Packet::Message(Message::binary(data)));
+        }
+        let mut chars = text.chars();
+        let kind = chars.next()?;
+        let data = chars.as_str();
+        match kind {
+            '0' => Some(Packet::Open(data.into())),
+            '1' => Some(Packet::Close),
+            '2' => Some(Packet::Ping(data.into())),
+            '3' => Some(Packet::Pong(data.into())),
+            '4' => Some(Packet::Message(Message::text(data))),
+            '5' => Some(Packet::Upgrade),
+            '6' => Some(Packet::Noop),
+            _ => None,
+        }
+    }
+    fn decode_payload(payload: &str) -> Option<Vec<Self>> {
+        payload.split(SEPARATOR).map(Packet::decode).collect()
+    }
+    fn decode_frame(msg: &Message) -> Option<Self> {
+        if msg.is_binary() {
+            Some(Packet::Message(msg.clone()))
+        } else {
+            msg.to_str().ok().and_then(Packet::decode)
+        }
+    }
+
+    /// Encodes the packet for long polling, binary messages are encoded with base64.
+    fn encode(&self) -> String {
+        match self {
+            Packet::Open(data) => format!("0{data}"),
+            Packet::Close => "1".into(),
+            Packet::Ping(data) => format!("2{data}"),
+            Packet::Pong(data) => format!("3{data}"),
+            Packet::Message(msg) if msg.is_binary() => format!("b{}", STANDARD.encode(msg.as_bytes())),
+            Packet::Message(msg) => format!("4{}", msg.to_str().unwrap_or_default()),
+            Packet::Upgrade => "5".into(),
+            Packet::Noop => "6".into(),
+        }
+    }
+    /// Encodes the packet as a WebSocket frame, binary messages are sent as binary frames.
+    fn encode_frame(&self) -> Message {
+        match self {
+            Packet::Message(msg) if msg.is_binary() => msg.clone(),
+            packet => Message::text(packet.encode()),
+        }
+    }
+}
+
+struct Session {
+    outgoing: mpsc::Sender<Packet>,
+    pending: Arc<tokio::sync::Mutex<mpsc::Receiver<Packet>>>,
+    incoming: Mutex<Option<mpsc::Sender<Message>>>,
+    upgraded: AtomicBool,
+    pong: Notify,
+}
+impl Session {
+    fn incoming(&self) -> Option<mpsc::Sender<Message>> {
+        self.incoming.lock().unwrap().clone()
+    }
+}
+
+struct HubInner {
+    queue_size: usize,
+    registry: RoomRegistry<SocketId, Arc<Session>>,
+}
+
+/// Hub manages engine.io sessions with named rooms.
+///
+/// It works like the [`Hub`](crate::websocket::hub::Hub) of WebSocket: every session has a bounded send
+/// queue, [`Hub::send_to`] waits if the queue is full, and broadcasting closes sessions whose queue is full.
+/// Messages are queued regardless of the transport the client is using, they are written by the pending long
+/// polling request or by the WebSocket after the client is upgraded.
+#[derive(Clone)]
+pub struct Hub {
+    inner: Arc<HubInner>,
+}
+impl Default for Hub {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl fmt::Debug for Hub {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hub")
+            .field("queue_size", &self.inner.queue_size)
+            .field("connections", &self.connection_count())
+            .finish()
+    }
+}
+impl Hub {
+    /// Create new `Hub`, the send queue size of every session is 64.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_queue_size(64)
+    }
+
+    /// Create new `Hub` with send queue size of every session.
+    pub fn with_queue_size(queue_size: usize) -> Self {
+        Self {
+            inner: Arc::new(HubInner {
+                queue_size: queue_size.max(1),
+                registry: RoomRegistry::default(),
+            }),
+        }
+    }
+
+    fn register(&self, upgraded: bool) -> (Socket, Arc<Session>) {
+        let id = SocketId::generate();
+        // One more slot for the close packet.
+        let (outgoing, pending) = mpsc::channel(self.inner.queue_size + 1);
+        let (incoming, receiver) = mpsc::channel(self.inner.queue_size);
+        let session = Arc::new(Session {
+            outgoing,
+            pending: Arc::new(tokio::sync::Mutex::new(pending)),
+            incoming: Mutex::new(Some(incoming)),
+            upgraded: AtomicBool::new(upgraded),
+            pong: Notify::new(),
+        });
+        self.inner.registry.insert(id.clone(), session.clone());
+        let socket = Socket {
+            id,
+            hub: self.clone(),
+            receiver,
+        };
+        (socket, session)
+    }
+
+    fn session(&self, id: &SocketId) -> Option<Arc<Session>> {
+        self.inner.registry.get(id)
+    }
+
+    /// Close a session and remove it from the hub and all rooms.
+    pub fn remove(&self, id: &SocketId) {
+        if let Some(session) = self.inner.registry.remove(id) {
+            session.incoming.lock().unwrap().take();
+            session.outgoing.try_send(Packet::Close).ok();
+        }
+    }
+
+    /// Add a session to a room.
+    pub fn join(&self, id: &SocketId, room: impl Into<String>) {
+        self.inner.registry.join(id, room.into());
+    }
+
+    /// Remove a session from a room, empty rooms are removed.
+    pub fn leave(&self, id: &SocketId, room: &str) {
+        self.inner.registry.leave(id, room);
+    }
+
+    /// Send a message to a session, it waits if the send queue of the session is full.
+    ///
+    /// Only text and binary messages can be sent, other messages are ignored.
+    pub async fn send_to(&self, id: &SocketId, msg: Message) -> Result<(), Error> {
+        let session = self
+            .session(id)
+            .ok_or_else(|| Error::other(format!("session {id} is not found")))?;
+        if !msg.is_text() && !msg.is_binary() {
+            return Ok(());
+        }
+        session
+            .outgoing
+            .send(Packet::Message(msg))
+            .await
+            .map_err(|_| Error::other(format!("session {id} is closed")))
+    }
+
+    /// Send a message to all sessions, returns the number of sessions which the message is queued to.
+    pub fn broadcast(&self, msg: Message) -> usize {
+        self.deliver(self.inner.registry.targets(None), msg)
+    }
+
+    /// Send a message to all sessions in a room, returns the number of sessions which the message is queued to.
+    pub fn broadcast_to(&self, room: &str, msg: Message) -> usize {
+        self.deliver(self.inner.registry.targets(Some(room)), msg)
+    }
+
+    fn deliver(&self, targets: Vec<(SocketId, Arc<Session>)>, msg: Message) -> usize {
+        if !msg.is_text() && !msg.is_binary() {
+            return 0;
+        }
+        let mut count = 0;
+        for (id, session) in targets {
+            // Keeps the last slot for the close packet.
+            if session.outgoing.capacity() <= 1 {
+                tracing::warn!(sid = %id, "engine.io send queue is full, session is closed");
+                self.remove(&id);
+            } else if session.outgoing.try_send(Packet::Message(msg.clone())).is_ok() {
+                count += 1;
+            } else {
+                self.remove(&id);
+            }
+        }
+        count
+    }
+
+    /// Get the number of sessions.
+    pub fn connection_count(&self) -> usize {
+        self.inner.registry.len()
+    }
+
+    /// Get names of all rooms.
+    pub fn rooms(&self) -> Vec<String> {
+        self.inner.registry.rooms()
+    }
+
+    /// Get ids of sessions in a room.
+    pub fn room_members(&self, room: &str) -> Vec<SocketId> {
+        self.inner.registry.room_members(room)
+    }
+}
+
+/// A session registered in [`Hub`], it is closed and removed from the hub when dropped.
+pub struct Socket {
+    id: SocketId,
+    hub: Hub,
+    receiver: mpsc::Receiver<Message>,
+}
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socket").field("id", &self.id).finish()
+    }
+}
+impl Socket {
+    /// Get the id of this session.
+    #[inline]
+    pub fn id(&self) -> &SocketId {
+        &self.id
+    }
+
+    /// Get the hub of this session.
+    #[inline]
+    pub fn hub(&self) -> &Hub {
+        &self.hub
+    }
+
+    /// Receive another message from the client.
+    ///
+    /// Returns `None` if the session has closed.
+    #[inline]
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.receiver.recv().await
+    }
+
+    /// Send a message to this session.
+    #[inline]
+    pub async fn send(&self, msg: Message) -> Result<(), Error> {
+        self.hub.send_to(&self.id, msg).await
+    }
+
+    /// Join a room.
+    #[inline]
+    pub fn join(&self, room: impl Into<String>) {
+        self.hub.join(&self.id, room);
+    }
+
+    /// Leave a room.
+    #[inline]
+    pub fn leave(&self, room: &str) {
+        self.hub.leave(&self.id, room);
+    }
+
+    /// Close this session.
+    #[inline]
+    pub fn close(self) {}
+}
+impl Drop for Socket {
+    fn drop(&mut self) {
+        self.hub.remove(&self.id);
+    }
+}
+
+/// Errors of the engine.io protocol, they are rendered as JSON with status code `400 Bad Request`.
+#[derive(Clone, Copy, Debug)]
+enum ProtocolError {
+    UnknownTransport = 0,
+    UnknownSid = 1,
+    BadHandshakeMethod = 2,
+    BadRequest = 3,
+    UnsupportedProtocolVersion = 5,
+}
+impl ProtocolError {
+    fn render(self, res: &mut Response) {
+        let message = match self {
+            ProtocolError::UnknownTransport => "Transport unknown",
+            ProtocolError::UnknownSid => "Session ID unknown",
+            ProtocolError::BadHandshakeMethod => "Bad handshake method",
+            ProtocolError::BadRequest => "Bad request",
+            ProtocolError::UnsupportedProtocolVersion => "Unsupported protocol version",
+        };
+        res.status_code(StatusCode::BAD_REQUEST);
+        res.render(Text::Json(format!(
+            r#"{{"code":{},"message":"{message}"}}"#,
+            self as u8
+        )));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Config {
+    ping_interval: Duration,
+    ping_timeout: Duration,
+    max_payload: usize,
+    allow_upgrades: bool,
+}
+
+/// Engine.io endpoint handler.
+///
+/// A new session is registered to the [`Hub`] for every connected client, and the callback is called with
+/// its [`Socket`] in a spawned task. The router of this handler should match all requests under its path,
+/// clients use `/engine.io/` by default and `/socket.io/` for Socket.IO.
+///
+/// The server sends ping packets every `ping_interval`, the session is closed if the client does not respond
+/// within `ping_timeout`.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::engine_io::{EngineIo, Hub, Socket};
+///
+/// let hub = Hub::new();
+/// let router = Router::with_path("engine.io/<**>").goal(EngineIo::new(hub, |mut socket: Socket| async move {
+///     socket.join("lobby");
+///     while let Some(msg) = socket.recv().await {
+///         socket.hub().broadcast_to("lobby", msg);
+///     }
+/// }));
+/// ```
+pub struct EngineIo<F> {
+    hub: Hub,
+    config: Config,
+    callback: Arc<F>,
+}
+impl<F> fmt::Debug for EngineIo<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineIo")
+            .field("hub", &self.hub)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+impl<F, Fut> EngineIo<F>
+where
+    F: Fn(Socket) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    /// Create new `EngineIo`, the callback is called with every connected session.
+    #[inline]
+    pub fn new(hub: Hub, callback: F) -> Self {
+        Self {
+            hub,
+            config: Config {
+                ping_interval: Duration::from_secs(25),
+                ping_timeout: Duration::from_secs(20),
+                max_payload: 1_000_000,
+                allow_upgrades: true,
+            },
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// Get the hub of this endpoint.
+    #[inline]
+    pub fn hub(&self) -> &Hub {
+        &self.hub
+    }
+
+    /// Sets the interval of ping packets, default is 25 seconds.
+    #[inline]
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.config.ping_interval = ping_interval;
+        self
+    }
+
+    /// Sets how long to wait for the pong packet before the session is closed, default is 20 seconds.
+    #[inline]
+    pub fn ping_timeout(mut self, ping_timeout: Duration) -> Self {
+        self.config.ping_timeout = ping_timeout;
+        self
+    }
+
+    /// Sets the max size of a long polling payload in bytes, default is 1 MB.
+    #[inline]
+    pub fn max_payload(mut self, max_payload: usize) -> Self {
+        self.config.max_payload = max_payload;
+        self
+    }
+
+    /// Sets whether WebSocket is allowed, default is `true`.
+    ///
+    /// If it is `false`, clients can only use long polling.
+    #[inline]
+    pub fn allow_upgrades(mut self, allow_upgrades: bool) -> Self {
+        self.config.allow_upgrades = allow_upgrades;
+        self
+    }
+
+    fn open(&self, upgraded: bool) -> (SocketId, Arc<Session>, String) {
+        let (socket, session) = self.hub.register(upgraded);
+        let id = socket.id.clone();
+        let upgrades = if self.config.allow_upgrades && !upgraded {
+            r#"["websocket"]"#
+        } else {
+            "[]"
+        };
+        let handshake = format!(
+            r#"{{"sid":"{id}","upgrades":{upgrades},"pingInterval":{},"pingTimeout":{},"maxPayload":{}}}"#,
+            self.config.ping_interval.as_millis(),
+            self.config.ping_timeout.as_millis(),
+            self.config.max_payload
+        );
+        tokio::spawn(heartbeat(self.hub.clone(), id.clone(), session.clone(), self.config));
+        tokio::spawn((self.callback)(socket));
+        (id, session, Packet::Open(handshake).encode())
+    }
+
+    async fn poll(&self, session: Arc<Session>, res: &mut Response) {
+        let Ok(mut pending) = session.pending.try_lock() else {
+            tracing::debug!("overlapping long polling requests");
+            return ProtocolError::BadRequest.render(res);
+        };
+        let mut payload = match pending.recv().await {
+            Some(packet) => packet.encode(),
+            None => Packet::Close.encode(),
+        };
+        while payload.len() < self.config.max_payload {
+            let Ok(packet) = pending.try_recv() else {
+                break;
+            };
+            payload.push(SEPARATOR);
+            payload.push_str(&packet.encode());
+            if packet == Packet::Close {
+                break;
+            }
+        }
+        render_payload(res, payload);
+    }
+
+    async fn post(&self, id: &SocketId, session: Arc<Session>, req: &mut Request, res: &mut Response) {
+        let payload = match req.payload_with_max_size(self.config.max_payload).await {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::debug!(error = ?e, "read long polling payload failed");
+                res.status_code(StatusCode::PAYLOAD_TOO_LARGE);
+                return;
+            }
+        };
+        let Some(packets) = std::str::from_utf8(payload).ok().and_then(Packet::decode_payload) else {
+            self.hub.remove(id);
+            return ProtocolError::BadRequest.render(res);
+        };
+        for packet in packets {
+            if !receive(&self.hub, id, &session, packet).await {
+                break;
+            }
+        }
+        render_payload(res, "ok".into());
+    }
+}
+
+fn render_payload(res: &mut Response, payload: String) {
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=UTF-8"));
+    res.render(payload);
+}
+
+/// Handles a packet from the client, returns `false` if the session is closed.
+async fn receive(hub: &Hub, id: &SocketId, session: &Session, packet: Packet) -> bool {
+    match packet {
+        Packet::Pong(_) => session.pong.notify_one(),
+        Packet::Message(msg) => {
+            if let Some(incoming) = session.incoming() {
+                incoming.send(msg).await.ok();
+            }
+        }
+        Packet::Close => {
+            hub.remove(id);
+            return false;
+        }
+        _ => {}
+    }
+    true
+}
+
+async fn heartbeat(hub: Hub, id: SocketId, session: Arc<Session>, config: Config) {
+    loop {
+        tokio::time::sleep(config.ping_interval).await;
+        if hub.session(&id).is_none() || session.outgoing.try_send(Packet::Ping(String::new())).is_err() {
+            break;
+        }
+        if tokio::time::timeout(config.ping_timeout, session.pong.notified())
+            .await
+            .is_err()
+        {
+            tracing::debug!(sid = %id, "engine.io client is not responding");
+            hub.remove(&id);
+            break;
+        }
+    }
+}
+
+/// Upgrades a long polling session to WebSocket.
+async fn upgrade(mut ws: WebSocket, hub: Hub, id: SocketId, session: Arc<Session>) {
+    loop {
+        let packet = match ws.recv().await {
+            Some(Ok(msg)) => Packet::decode_frame(&msg),
+            _ => return,
+        };
+        match packet {
+            Some(Packet::Ping(data)) if data == "probe" => {
+                if ws.send(Message::text(Packet::Pong(data).encode())).await.is_err() {
+                    return;
+                }
+                // Completes the pending long polling request.
+                session.outgoing.try_send(Packet::Noop).ok();
+            }
+            Some(Packet::Upgrade) => break,
+            _ => {
+                tracing::debug!(sid = %id, "unexpected packet during upgrade");
+                ws.close().await.ok();
+                return;
+            }
+        }
+    }
+    session.upgraded.store(true, Ordering::Release);
+    serve(ws, hub, id, session).await;
+}
+
+async fn serve(ws: WebSocket, hub: Hub, id: SocketId, session: Arc<Session>) {
+    let mut pending = session.pending.clone().lock_owned().await;
+    let (mut sink, mut stream) = ws.split();
+    loop {
+        tokio::select! {
+            packet = pending.recv() => {
+                let packet = packet.unwrap_or(Packet::Close);
+                if let Err(e) = sink.send(packet.encode_frame()).await {
+                    tracing::debug!(error = ?e, sid = %id, "engine.io send failed");
+                    break;
+                }
+                if packet == Packet::Close {
+                    break;
+                }
+            }
+            msg = stream.next() => {
+                let Some(Ok(msg)) = msg else {
+                    break;
+                };
+                if msg.is_close() {
+                    break;
+                }
+                match Packet::decode_frame(&msg) {
+                    Some(packet) => {
+                        if !receive(&hub, &id, &session, packet).await {
+                            break;
+                        }
+                    }
+                    None => continue,
+                }
+            }
+        }
+    }
+    hub.remove(&id);
+    sink.close().await.ok();
+}
+
+#[async_trait]
+impl<F, Fut> Handler for EngineIo<F>
+where
+    F: Fn(Socket) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.skip_rest();
+        if req.query::<String>("EIO").as_deref() != Some("4") {
+            return ProtocolError::UnsupportedProtocolVersion.render(res);
+        }
+        let websocket = match req.query::<String>("transport").as_deref() {
+            Some("polling") => false,
+            Some("websocket") if self.config.allow_upgrades => true,
+            _ => return ProtocolError::UnknownTransport.render(res),
+        };
+        let Some(id) = req.query::<String>("sid").map(|sid| SocketId(sid.into())) else {
+            if websocket {
+                let (id, session, open) = self.open(true);
+                let hub = self.hub.clone();
+                let sid = id.clone();
+                let result = WebSocketUpgrade::new()
+                    .upgrade(req, res, move |mut ws| async move {
+                        if ws.send(Message::text(open)).await.is_ok() {
+                            serve(ws, hub, id, session).await;
+                        } else {
+                            hub.remove(&id);
+                        }
+                    })
+                    .await;
+                if let Err(e) = result {
+                    self.hub.remove(&sid);
+                    res.render(e);
+                }
+            } else if req.method() == Method::GET {
+                let (_, _, open) = self.open(false);
+                render_payload(res, open);
+            } else {
+                ProtocolError::BadHandshakeMethod.render(res);
+            }
+            return;
+        };
+        let Some(session) = self.hub.session(&id) else {
+            return ProtocolError::UnknownSid.render(res);
+        };
+        if websocket {
+            if session.upgraded.load(Ordering::Acquire) {
+                return ProtocolError::BadRequest.render(res);
+            }
+            let hub = self.hub.clone();
+            let result = WebSocketUpgrade::new()
+                .upgrade(req, res, move |ws| upgrade(ws, hub, id, session))
+                .await;
+            if let Err(e) = result {
+                res.render(e);
+            }
+        } else if session.upgraded.load(Ordering::Acquire) {
+            ProtocolError::BadRequest.render(res);
+        } else if req.method() == Method::GET {
+            self.poll(session, res).await;
+        } else if req.method() == Method::POST {
+            self.post(&id, session, req, res).await;
+        } else {
+            ProtocolError::BadRequest.render(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient, WebSocketMessage};
+
+    use super::*;
+
+    fn service(hub: Hub) -> Service {
+        let engine = EngineIo::new(hub, |mut socket: Socket| async move {
+            socket.join("lobby");
+            while let Some(msg) = socket.recv().await {
+                socket.hub().broadcast_to("lobby", msg);
+            }
+        });
+        Service::new(Router::with_path("engine.io/<**>").goal(engine))
+    }
+
+    async fn handshake(service: &Service) -> String {
+        let mut res = TestClient::get("http://127.0.0.1:5801/engine.io/?EIO=4&transport=polling")
+            .send(service)
+            .await;
+        let body = res.take_string().await.unwrap();
+        let sid = body
+            .strip_prefix(r#"0{"sid":""#)
+            .and_then(|s| s.split('"').next())
+            .unwrap();
+        assert_eq!(
+            body,
+            format!(
+                r#"0{{"sid":"{sid}","upgrades":["websocket"],"pingInterval":25000,"pingTimeout":20000,"maxPayload":1000000}}"#
+            )
+        );
+        sid.to_owned()
+    }
+
+    #[test]
+    fn test_packet() {
+        assert_eq!(
+            Packet::decode_payload("4hello\x1ebAQI=\x1e3").unwrap(),
+            vec![
+                Packet::Message(Message::text("hello")),
+                Packet::Message(Message::binary(vec![1, 2])),
+                Packet::Pong(String::new())
+            ]
+        );
+        assert!(Packet::decode_payload("4a\x1e9").is_none());
+        assert_eq!(Packet::Message(Message::binary(vec![1, 2])).encode(), "bAQI=");
+        assert_eq!(Packet::Ping("probe".into()).encode(), "2probe");
+    }
+
+    #[tokio::test]
+    async fn test_engine_io_polling() {
+        let hub = Hub::new();
+        let service = service(hub.clone());
+        let sid = handshake(&service).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(hub.connection_count(), 1);
+        assert_eq!(hub.room_members("lobby").len(), 1);
+
+        let url = format!("http://127.0.0.1:5801/engine.io/?EIO=4&transport=polling&sid={sid}");
+        let mut res = TestClient::post(&url).text("4hello\x1ebAQI=").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "ok");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut res = TestClient::get(&url).send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "4hello\x1ebAQI=");
+
+        assert_eq!(hub.broadcast(Message::text("all")), 1);
+        let mut res = TestClient::get(&url).send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "4all");
+
+        let mut res = TestClient::post(&url).text("1").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "ok");
+        assert_eq!(hub.connection_count(), 0);
+        let mut res = TestClient::get(&url).send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            r#"{"code":1,"message":"Session ID unknown"}"#
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5801/engine.io/?EIO=3&transport=polling")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_engine_io_upgrade() {
+        let hub = Hub::new();
+        let service = service(hub.clone());
+        let sid = handshake(&service).await;
+        let url = format!("http://127.0.0.1:5801/engine.io/?EIO=4&transport=polling&sid={sid}");
+
+        let service = Arc::new(service);
+        let poll = {
+            let service = service.clone();
+            let url = url.clone();
+            tokio::spawn(async move { TestClient::get(url).send(&*service).await.take_string().await.unwrap() })
+        };
+        let mut client = TestClient::websocket(format!(
+            "http://127.0.0.1:5801/engine.io/?EIO=4&transport=websocket&sid={sid}"
+        ))
+        .connect(&service)
+        .await
+        .unwrap();
+        client.send_text("2probe").await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("3probe".into())
+        );
+        assert_eq!(poll.await.unwrap(), "6");
+        client.send_text("5").await.unwrap();
+
+        client.send_text("4hello").await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("4hello".into())
+        );
+        client.send_binary(vec![1, 2]).await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Binary(vec![1, 2])
+        );
+        let res = TestClient::get(&url).send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+
+        hub.remove(&SocketId(sid.into()));
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("1".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_engine_io_websocket() {
+        let hub = Hub::new();
+        let service = service(hub.clone());
+        let mut client = TestClient::websocket("http://127.0.0.1:5801/engine.io/?EIO=4&transport=websocket")
+            .connect(&service)
+            .await
+            .unwrap();
+        let WebSocketMessage::Text(open) = client.recv().await.unwrap().unwrap() else {
+            panic!("open packet is expected");
+        };
+        assert!(open.starts_with(r#"0{"sid":""#));
+        assert!(open.contains(r#""upgrades":[]"#));
+        client.send_text("4hello").await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("4hello".into())
+        );
+        client.send_text("1").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(hub.connection_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_engine_io_heartbeat() {
+        let hub = Hub::new();
+        let engine = EngineIo::new(hub.clone(), |mut socket: Socket| async move {
+            while socket.recv().await.is_some() {}
+        })
+        .ping_interval(Duration::from_millis(50))
+        .ping_timeout(Duration::from_millis(50));
+        let service = Service::new(Router::with_path("engine.io/<**>").goal(engine));
+        let mut res = TestClient::get("http://127.0.0.1:5801/engine.io/?EIO=4&transport=polling")
+            .send(&service)
+            .await;
+        let body = res.take_string().await.unwrap();
+        let sid = body[9..].split('"').next().unwrap().to_owned();
+        let url = format!("http://127.0.0.1:5801/engine.io/?EIO=4&transport=polling&sid={sid}");
+
+        let mut res = TestClient::get(&url).send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "2");
+        TestClient::post(&url).text("3").send(&service).await;
+        let mut res = TestClient::get(&url).send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "2");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(hub.connection_count(), 0);
+    }
+}
@@ -0,0 +1,293 @@
+//! Feature flags middleware.
+//!
+//! [`FeatureFlags`] resolves the flags of every request by a [`FlagProvider`] and injects them into [`Depot`] as
+//! [`Flags`], so handlers and middlewares read the same flags during a gradual rollout:
+//!
+//! - [`feature`] is a guard which hides routes of disabled features.
+//! - [`enabled`] is a predicate for [`handler::when`] and [`Router::hoop_when`], it makes hoops conditional.
+//! - Handlers read flags by [`is_enabled`].
+//!
+//! Services such as Unleash or LaunchDarkly are integrated by implementing [`FlagProvider`] with their SDKs,
+//! [`Flags`] itself is a provider which returns fixed flags.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::feature_flags::{self, feature, FeatureFlags, Flags};
+//!
+//! #[handler]
+//! async fn checkout(depot: &mut Depot) -> &'static str {
+//!     if feature_flags::is_enabled(depot, "express_shipping") {
+//!         "new checkout with express shipping"
+//!     } else {
+//!         "new checkout"
+//!     }
+//! }
+//!
+//! let router = Router::new()
+//!     .hoop(FeatureFlags::new(Flags::new().enable("new_checkout")))
+//!     .push(Router::with_path("checkout").hoop(feature("new_checkout")).get(checkout));
+//! ```
+//!
+//! [`handler::when`]: salvo_core::handler::when
+//! [`Router::hoop_when`]: salvo_core::Router::hoop_when
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error as StdError;
+
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Flags resolved for a request.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Flags {
+    values: HashMap<String, bool>,
+}
+impl Flags {
+    /// Create a new `Flags` without any flag, all flags are disabled.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Enable a flag.
+    #[inline]
+    pub fn enable(self, name: impl Into<String>) -> Self {
+        self.set(name, true)
+    }
+
+    /// Disable a flag.
+    #[inline]
+    pub fn disable(self, name: impl Into<String>) -> Self {
+        self.set(name, false)
+    }
+
+    /// Sets whether a flag is enabled.
+    #[inline]
+    pub fn set(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.insert(name, enabled);
+        self
+    }
+
+    /// Inserts a flag, returns the previous value of it.
+    #[inline]
+    pub fn insert(&mut self, name: impl Into<String>, enabled: bool) -> Option<bool> {
+        self.values.insert(name.into(), enabled)
+    }
+
+    /// Returns `true` if the flag is enabled, unknown flags are disabled.
+    #[inline]
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.values.get(name).copied().unwrap_or(false)
+    }
+
+    /// Get the value of a flag, returns `None` if the flag is unknown.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.values.get(name).copied()
+    }
+
+    /// Iterate over all flags.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.values.iter().map(|(name, enabled)| (name.as_str(), *enabled))
+    }
+}
+
+/// Provider of feature flags.
+#[async_trait]
+pub trait FlagProvider: Send + Sync + 'static {
+    /// Error type for FlagProvider.
+    type Error: StdError + Sync + Send + 'static;
+    /// Resolves the flags of a request, such as by the user set in `depot` by an authentication middleware.
+    async fn flags(&self, req: &mut Request, depot: &Depot) -> Result<Flags, Self::Error>;
+}
+
+#[async_trait]
+impl FlagProvider for Flags {
+    type Error = Infallible;
+    async fn flags(&self, _req: &mut Request, _depot: &Depot) -> Result<Flags, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+/// Returns `true` if the flag is enabled for the request, it is `false` if no [`FeatureFlags`] handled the request.
+#[inline]
+pub fn is_enabled(depot: &Depot, name: &str) -> bool {
+    match depot.obtain::<Flags>() {
+        Ok(flags) => flags.is_enabled(name),
+        Err(_) => false,
+    }
+}
+
+/// Returns a predicate which checks if the flag is enabled, it is used with [`handler::when`] and
+/// [`Router::hoop_when`].
+///
+/// [`handler::when`]: salvo_core::handler::when
+/// [`Router::hoop_when`]: salvo_core::Router::hoop_when
+pub fn enabled(name: impl Into<String>) -> impl Fn(&Request, &Depot) -> bool + Send + Sync + 'static {
+    let name = name.into();
+    move |_req, depot| is_enabled(depot, &name)
+}
+
+/// Middleware which resolves flags of requests and injects them into [`Depot`].
+///
+/// Flags resolved by nested `FeatureFlags` are merged, the inner ones override the outer ones.
+#[derive(Debug)]
+pub struct FeatureFlags<P> {
+    provider: P,
+    fallback: Flags,
+}
+impl<P: FlagProvider> FeatureFlags<P> {
+    /// Create a new `FeatureFlags` with a provider.
+    #[inline]
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            fallback: Flags::new(),
+        }
+    }
+
+    /// Sets flags used when the provider fails, default is no flag, so all flags are disabled.
+    #[inline]
+    pub fn fallback(mut self, fallback: Flags) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+#[async_trait]
+impl<P: FlagProvider> Handler for FeatureFlags<P> {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let flags = match self.provider.flags(req, depot).await {
+            Ok(flags) => flags,
+            Err(e) => {
+                tracing::error!(error = ?e, "resolve feature flags failed");
+                self.fallback.clone()
+            }
+        };
+        match depot.obtain_mut::<Flags>() {
+            Ok(outer) => outer.values.extend(flags.values),
+            Err(_) => {
+                depot.inject(flags);
+            }
+        }
+        ctrl.call_next(req, depot, res).await;
+    }
+}
+
+/// Guard which only lets requests through when a feature is enabled, it is created by [`feature`].
+#[derive(Clone, Debug)]
+pub struct FeatureGuard {
+    name: String,
+    status: StatusCode,
+}
+impl FeatureGuard {
+    /// Sets the status code of responses when the feature is disabled, default is `404 Not Found`, so
+    /// unreleased routes look like they do not exist.
+    #[inline]
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Create a [`FeatureGuard`] which only lets requests through when the flag is enabled.
+#[inline]
+pub fn feature(name: impl Into<String>) -> FeatureGuard {
+    FeatureGuard {
+        name: name.into(),
+        status: StatusCode::NOT_FOUND,
+    }
+}
+
+#[async_trait]
+impl Handler for FeatureGuard {
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if !is_enabled(depot, &self.name) {
+            res.status_code(self.status);
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Unavailable;
+    impl fmt::Display for Unavailable {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("unavailable")
+        }
+    }
+    impl StdError for Unavailable {}
+
+    /// Enables `beta` for requests with the `x-beta` header.
+    struct HeaderProvider;
+    #[async_trait]
+    impl FlagProvider for HeaderProvider {
+        type Error = Unavailable;
+        async fn flags(&self, req: &mut Request, _depot: &Depot) -> Result<Flags, Self::Error> {
+            match req.header::<String>("x-beta").as_deref() {
+                Some("fail") => Err(Unavailable),
+                Some(_) => Ok(Flags::new().enable("beta")),
+                None => Ok(Flags::new().disable("beta")),
+            }
+        }
+    }
+
+    #[handler]
+    async fn mark(res: &mut Response) {
+        res.headers_mut().insert("x-beta", "1".parse().unwrap());
+    }
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        format!(
+            "hello beta={} old={}",
+            is_enabled(depot, "beta"),
+            is_enabled(depot, "old")
+        )
+    }
+
+    #[tokio::test]
+    async fn test_feature_flags() {
+        let router = Router::new()
+            .hoop(FeatureFlags::new(Flags::new().enable("old").enable("beta")))
+            .hoop(FeatureFlags::new(HeaderProvider).fallback(Flags::new().enable("beta")))
+            .hoop(handler::when(enabled("beta"), mark))
+            .push(Router::with_path("hello").get(hello))
+            .push(
+                Router::with_path("beta")
+                    .hoop(feature("beta").status(StatusCode::FORBIDDEN))
+                    .get(hello),
+            );
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+        assert!(res.headers().get("x-beta").is_none());
+        assert_eq!(res.take_string().await.unwrap(), "hello beta=false old=true");
+        let res = TestClient::get("http://127.0.0.1:5801/beta").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/beta")
+            .add_header("x-beta", "1", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()["x-beta"], "1");
+        assert_eq!(res.take_string().await.unwrap(), "hello beta=true old=true");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/beta")
+            .add_header("x-beta", "fail", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello beta=true old=true");
+    }
+}
@@ -0,0 +1,612 @@
+//! Firewall middleware.
+//!
+//! [`Firewall`] rejects requests by allow and deny lists of client ips, ip ranges in CIDR notation and user agents,
+//! and by a ban list of ips and keys such as api keys. Lists are shared with a [`FirewallHandle`], so they are
+//! updated at runtime, such as by an admin api, without restarting the server.
+//!
+//! With [`AutoBan`], clients which receive too many responses with abuse status codes, `429 Too Many Requests`
+//! set by the rate limiter by default, are banned for a cooldown period. Banned clients are rejected before the
+//! rate limiter, so they do not cost the rate limiter store anything.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::firewall::{AutoBan, Firewall};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! let firewall = Firewall::new()
+//!     .allow_ip("10.0.0.0/8".parse().unwrap())
+//!     .deny_ip("203.0.113.0/24".parse().unwrap())
+//!     .deny_user_agent("sqlmap")
+//!     .auto_ban(AutoBan::new(20, Duration::from_secs(60), Duration::from_secs(600)));
+//! let handle = firewall.handle();
+//! handle.ban_ip("198.51.100.7".parse().unwrap(), None);
+//!
+//! // The rate limiter should be added after the firewall.
+//! let router = Router::new().hoop(firewall).get(hello);
+//! ```
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
+use std::net::{AddrParseError, IpAddr};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::USER_AGENT;
+use salvo_core::http::{Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+use crate::trusted_proxies::TrustedProxies;
+
+type KeyIssuer = Box<dyn Fn(&Request, &Depot) -> Option<String> + Send + Sync>;
+
+/// Maximum number of clients with strikes kept before expired strikes are removed.
+const MAX_STRIKES: usize = 1024;
+
+/// Error returned when parsing an [`IpNet`] fails.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IpNetError {
+    /// The address is invalid.
+    Addr(AddrParseError),
+    /// The prefix length is invalid.
+    Prefix,
+}
+impl Display for IpNetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Addr(e) => write!(f, "invalid ip address: {e}"),
+            Self::Prefix => f.write_str("invalid prefix length"),
+        }
+    }
+}
+impl std::error::Error for IpNetError {}
+
+/// An ip address or an ip range in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+impl IpNet {
+    /// Create a new `IpNet`, returns an error if the prefix length is longer than the address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, IpNetError> {
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > max {
+            return Err(IpNetError::Prefix);
+        }
+        Ok(Self { addr, prefix })
+    }
+    /// Get the address.
+    #[inline]
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+    /// Get the prefix length.
+    #[inline]
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+    /// Returns `true` if the ip is in the range, IPv4-mapped IPv6 addresses are matched as IPv4 addresses.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, canonical(*ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+impl From<IpAddr> for IpNet {
+    #[inline]
+    fn from(addr: IpAddr) -> Self {
+        let addr = canonical(addr);
+        let prefix = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix }
+    }
+}
+impl FromStr for IpNet {
+    type Err = IpNetError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((addr, prefix)) => {
+                let addr = canonical(addr.trim().parse().map_err(IpNetError::Addr)?);
+                let prefix = prefix.trim().parse().map_err(|_| IpNetError::Prefix)?;
+                Self::new(addr, prefix)
+            }
+            None => Ok(s.trim().parse::<IpAddr>().map_err(IpNetError::Addr)?.into()),
+        }
+    }
+}
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+/// Bans clients which receive too many responses with abuse status codes in a time window.
+#[derive(Clone, Debug)]
+pub struct AutoBan {
+    statuses: Vec<StatusCode>,
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+}
+impl AutoBan {
+    /// Create a new `AutoBan`, the client ip is banned for `cooldown` after it receives `threshold` abuse
+    /// responses in `window`. The abuse status code is `429 Too Many Requests` by default.
+    #[inline]
+    pub fn new(threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            statuses: vec![StatusCode::TOO_MANY_REQUESTS],
+            threshold: threshold.max(1),
+            window,
+            cooldown,
+        }
+    }
+    /// Sets the status codes counted as abuse, such as `401 Unauthorized` to stop credential stuffing.
+    #[inline]
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Strikes {
+    count: usize,
+    started: Instant,
+}
+
+#[derive(Default, Debug)]
+struct Rules {
+    allowed_ips: Vec<IpNet>,
+    denied_ips: Vec<IpNet>,
+    allowed_user_agents: Vec<String>,
+    denied_user_agents: Vec<String>,
+    banned_ips: HashMap<IpAddr, Option<Instant>>,
+    banned_keys: HashMap<String, Option<Instant>>,
+    strikes: HashMap<IpAddr, Strikes>,
+}
+
+fn is_banned<K, Q>(bans: &HashMap<K, Option<Instant>>, key: &Q) -> bool
+where
+    K: Borrow<Q> + Hash + Eq,
+    Q: Hash + Eq + ?Sized,
+{
+    match bans.get(key) {
+        Some(Some(until)) => *until > Instant::now(),
+        Some(None) => true,
+        None => false,
+    }
+}
+
+fn matches_user_agent(patterns: &[String], user_agent: &str) -> bool {
+    patterns.iter().any(|pattern| user_agent.contains(pattern.as_str()))
+}
+
+/// Handle to update the lists of a [`Firewall`] at runtime, it is cheap to clone.
+#[derive(Clone, Default, Debug)]
+pub struct FirewallHandle {
+    rules: Arc<RwLock<Rules>>,
+}
+impl FirewallHandle {
+    fn read(&self) -> RwLockReadGuard<'_, Rules> {
+        self.rules.read().unwrap_or_else(|e| e.into_inner())
+    }
+    fn write(&self) -> RwLockWriteGuard<'_, Rules> {
+        self.rules.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Allow an ip range, allowed clients are never rejected or banned.
+    pub fn allow_ip(&self, net: IpNet) {
+        let mut rules = self.write();
+        if !rules.allowed_ips.contains(&net) {
+            rules.allowed_ips.push(net);
+        }
+    }
+    /// Deny an ip range.
+    pub fn deny_ip(&self, net: IpNet) {
+        let mut rules = self.write();
+        if !rules.denied_ips.contains(&net) {
+            rules.denied_ips.push(net);
+        }
+    }
+    /// Remove an ip range from both the allow list and the deny list.
+    pub fn remove_ip(&self, net: &IpNet) {
+        let mut rules = self.write();
+        rules.allowed_ips.retain(|n| n != net);
+        rules.denied_ips.retain(|n| n != net);
+    }
+    /// Allow user agents containing the pattern, such as the user agent of an uptime monitor. Patterns are
+    /// matched case insensitively.
+    pub fn allow_user_agent(&self, pattern: impl Into<String>) {
+        let pattern = pattern.into().to_lowercase();
+        let mut rules = self.write();
+        if !rules.allowed_user_agents.contains(&pattern) {
+            rules.allowed_user_agents.push(pattern);
+        }
+    }
+    /// Deny user agents containing the pattern, patterns are matched case insensitively.
+    pub fn deny_user_agent(&self, pattern: impl Into<String>) {
+        let pattern = pattern.into().to_lowercase();
+        let mut rules = self.write();
+        if !rules.denied_user_agents.contains(&pattern) {
+            rules.denied_user_agents.push(pattern);
+        }
+    }
+    /// Remove a user agent pattern from both the allow list and the deny list.
+    pub fn remove_user_agent(&self, pattern: &str) {
+        let pattern = pattern.to_lowercase();
+        let mut rules = self.write();
+        rules.allowed_user_agents.retain(|p| *p != pattern);
+        rules.denied_user_agents.retain(|p| *p != pattern);
+    }
+
+    /// Ban an ip for a duration, it is banned forever if the duration is `None`.
+    pub fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>) {
+        let until = duration.map(|duration| Instant::now() + duration);
+        self.write().banned_ips.insert(canonical(ip), until);
+    }
+    /// Unban an ip, returns `true` if it was banned.
+    pub fn unban_ip(&self, ip: &IpAddr) -> bool {
+        let mut rules = self.write();
+        rules.strikes.remove(&canonical(*ip));
+        rules.banned_ips.remove(&canonical(*ip)).is_some()
+    }
+    /// Returns `true` if the ip is banned.
+    pub fn is_ip_banned(&self, ip: &IpAddr) -> bool {
+        is_banned(&self.read().banned_ips, &canonical(*ip))
+    }
+    /// Ban a key issued by [`Firewall::key_issuer`] for a duration, it is banned forever if the duration is `None`.
+    pub fn ban_key(&self, key: impl Into<String>, duration: Option<Duration>) {
+        let until = duration.map(|duration| Instant::now() + duration);
+        self.write().banned_keys.insert(key.into(), until);
+    }
+    /// Unban a key, returns `true` if it was banned.
+    pub fn unban_key(&self, key: &str) -> bool {
+        self.write().banned_keys.remove(key).is_some()
+    }
+    /// Returns `true` if the key is banned.
+    pub fn is_key_banned(&self, key: &str) -> bool {
+        is_banned(&self.read().banned_keys, key)
+    }
+    /// Get all banned ips and keys which are not expired.
+    pub fn banned(&self) -> (Vec<IpAddr>, Vec<String>) {
+        let rules = self.read();
+        let ips = rules
+            .banned_ips
+            .keys()
+            .filter(|ip| is_banned(&rules.banned_ips, *ip))
+            .copied()
+            .collect();
+        let keys = rules
+            .banned_keys
+            .keys()
+            .filter(|key| is_banned(&rules.banned_keys, *key))
+            .cloned()
+            .collect();
+        (ips, keys)
+    }
+
+    /// Counts an abuse response of the ip, returns `true` if the ip is banned by it.
+    fn strike(&self, ip: IpAddr, auto_ban: &AutoBan) -> bool {
+        let mut rules = self.write();
+        let now = Instant::now();
+        if rules.strikes.len() >= MAX_STRIKES {
+            rules
+                .strikes
+                .retain(|_, strikes| now.duration_since(strikes.started) < auto_ban.window);
+            rules
+                .banned_ips
+                .retain(|_, until| until.map(|until| until > now).unwrap_or(true));
+        }
+        let strikes = rules.strikes.entry(ip).or_insert(Strikes { count: 0, started: now });
+        if now.duration_since(strikes.started) >= auto_ban.window {
+            *strikes = Strikes { count: 0, started: now };
+        }
+        strikes.count += 1;
+        if strikes.count < auto_ban.threshold {
+            return false;
+        }
+        rules.strikes.remove(&ip);
+        rules.banned_ips.insert(ip, Some(now + auto_ban.cooldown));
+        true
+    }
+}
+
+/// Decision of the firewall for a request.
+enum Verdict {
+    Allow,
+    Check,
+    Reject,
+}
+
+/// Middleware which rejects requests by allow, deny and ban lists.
+///
+/// Allowed clients always pass. Other clients are rejected if their ip or user agent is denied, or their ip or key
+/// is banned. Client ips are the remote address of the connection by default, use [`Firewall::trusted_proxies`] to
+/// resolve them from headers set by trusted proxies. Ip rules are not checked for requests without a client ip.
+pub struct Firewall {
+    handle: FirewallHandle,
+    trusted_proxies: TrustedProxies,
+    key_issuer: Option<KeyIssuer>,
+    auto_ban: Option<AutoBan>,
+    status: StatusCode,
+    skipper: Box<dyn Skipper>,
+}
+impl Debug for Firewall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Firewall")
+            .field("handle", &self.handle)
+            .field("auto_ban", &self.auto_ban)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+impl Default for Firewall {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Firewall {
+    /// Create a new `Firewall` with empty lists, it lets all requests through.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_handle(FirewallHandle::default())
+    }
+    /// Create a new `Firewall` sharing lists with the handle, firewalls of different routers can share lists.
+    #[inline]
+    pub fn with_handle(handle: FirewallHandle) -> Self {
+        Self {
+            handle,
+            trusted_proxies: TrustedProxies::default(),
+            key_issuer: None,
+            auto_ban: None,
+            status: StatusCode::FORBIDDEN,
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Get the handle to update lists at runtime.
+    #[inline]
+    pub fn handle(&self) -> FirewallHandle {
+        self.handle.clone()
+    }
+
+    /// Allow an ip range, see [`FirewallHandle::allow_ip`].
+    #[inline]
+    pub fn allow_ip(self, net: IpNet) -> Self {
+        self.handle.allow_ip(net);
+        self
+    }
+    /// Deny an ip range, see [`FirewallHandle::deny_ip`].
+    #[inline]
+    pub fn deny_ip(self, net: IpNet) -> Self {
+        self.handle.deny_ip(net);
+        self
+    }
+    /// Allow user agents containing the pattern, see [`FirewallHandle::allow_user_agent`].
+    #[inline]
+    pub fn allow_user_agent(self, pattern: impl Into<String>) -> Self {
+        self.handle.allow_user_agent(pattern);
+        self
+    }
+    /// Deny user agents containing the pattern, see [`FirewallHandle::deny_user_agent`].
+    #[inline]
+    pub fn deny_user_agent(self, pattern: impl Into<String>) -> Self {
+        self.handle.deny_user_agent(pattern);
+        self
+    }
+    /// Sets trusted proxies used to resolve client ips from `X-Forwarded-For` header.
+    #[inline]
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+    /// Sets the function which issues keys of requests, such as api keys, requests with banned keys are rejected.
+    #[inline]
+    pub fn key_issuer(
+        mut self,
+        key_issuer: impl Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.key_issuer = Some(Box::new(key_issuer));
+        self
+    }
+    /// Sets the rule to ban abusing clients automatically.
+    #[inline]
+    pub fn auto_ban(mut self, auto_ban: AutoBan) -> Self {
+        self.auto_ban = Some(auto_ban);
+        self
+    }
+    /// Sets the status code of rejected requests, default is `403 Forbidden`.
+    #[inline]
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+    /// Sets skipper.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
+    fn verdict(&self, ip: Option<IpAddr>, req: &Request, depot: &Depot) -> Verdict {
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_lowercase);
+        let key = self.key_issuer.as_ref().and_then(|issuer| issuer(req, depot));
+        let rules = self.handle.read();
+        let ip_allowed = ip.map(|ip| rules.allowed_ips.iter().any(|net| net.contains(&ip)));
+        let ua_allowed = user_agent
+            .as_deref()
+            .map(|ua| matches_user_agent(&rules.allowed_user_agents, ua));
+        if ip_allowed.unwrap_or(false) || ua_allowed.unwrap_or(false) {
+            return Verdict::Allow;
+        }
+        if let Some(ip) = ip {
+            if rules.denied_ips.iter().any(|net| net.contains(&ip)) || is_banned(&rules.banned_ips, &ip) {
+                return Verdict::Reject;
+            }
+        }
+        if let Some(ua) = user_agent.as_deref() {
+            if matches_user_agent(&rules.denied_user_agents, ua) {
+                return Verdict::Reject;
+            }
+        }
+        if let Some(key) = key {
+            if is_banned(&rules.banned_keys, &key) {
+                return Verdict::Reject;
+            }
+        }
+        Verdict::Check
+    }
+}
+
+#[async_trait]
+impl Handler for Firewall {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let ip = self.trusted_proxies.client_ip(req).map(canonical);
+        match self.verdict(ip, req, depot) {
+            Verdict::Allow => {}
+            Verdict::Reject => {
+                tracing::debug!(ip = ?ip, "request rejected by firewall");
+                res.render(StatusError::from_code(self.status).unwrap_or_else(StatusError::forbidden));
+                ctrl.skip_rest();
+            }
+            Verdict::Check => {
+                let (Some(auto_ban), Some(ip)) = (&self.auto_ban, ip) else {
+                    return;
+                };
+                ctrl.call_next(req, depot, res).await;
+                let abused = res.status_code.map(|s| auto_ban.statuses.contains(&s)).unwrap_or(false);
+                if abused && self.handle.strike(ip, auto_ban) {
+                    tracing::warn!(ip = %ip, cooldown = ?auto_ban.cooldown, "client banned by firewall");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    /// Pretends requests are forwarded by a trusted proxy at `127.0.0.1`.
+    #[handler]
+    async fn from_proxy(req: &mut Request) {
+        *req.remote_addr_mut() = std::net::SocketAddr::from(([127, 0, 0, 1], 80)).into();
+    }
+
+    #[handler]
+    async fn hello(req: &mut Request, res: &mut Response) {
+        if req.query::<bool>("limited").unwrap_or(false) {
+            res.status_code(StatusCode::TOO_MANY_REQUESTS);
+        } else {
+            res.render("hello");
+        }
+    }
+
+    #[test]
+    fn test_ip_net() {
+        let net: IpNet = "10.1.0.0/16".parse().unwrap();
+        assert!(net.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(net.contains(&"::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!net.contains(&"10.2.0.1".parse().unwrap()));
+        let net: IpNet = "2001:db8::/32".parse().unwrap();
+        assert!(net.contains(&"2001:db8:1::1".parse().unwrap()));
+        assert!(!net.contains(&"2001:db9::1".parse().unwrap()));
+        let all: IpNet = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains(&"192.0.2.1".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+        assert_eq!("192.0.2.1".parse::<IpNet>().unwrap().to_string(), "192.0.2.1/32");
+    }
+
+    #[tokio::test]
+    async fn test_firewall() {
+        let firewall = Firewall::new()
+            .trusted_proxies(TrustedProxies::new(["127.0.0.1".parse().unwrap()]))
+            .key_issuer(|req, _depot| req.header("x-api-key"))
+            .allow_ip("10.0.0.0/8".parse().unwrap())
+            .deny_ip("192.0.2.0/24".parse().unwrap())
+            .deny_user_agent("SQLMap")
+            .auto_ban(AutoBan::new(2, Duration::from_secs(60), Duration::from_secs(60)));
+        let handle = firewall.handle();
+        let service = Service::new(Router::with_hoop(from_proxy).hoop(firewall).get(hello));
+
+        let send = |ip: &str, query: &str| {
+            TestClient::get(format!("http://127.0.0.1:5801/{query}")).add_header("x-forwarded-for", ip, true)
+        };
+        let status = |res: Response| res.status_code.unwrap();
+
+        assert_eq!(status(send("198.51.100.1", "").send(&service).await), StatusCode::OK);
+        assert_eq!(
+            status(send("192.0.2.9", "").send(&service).await),
+            StatusCode::FORBIDDEN
+        );
+        let res = send("198.51.100.1", "")
+            .add_header("user-agent", "sqlmap/1.7", true)
+            .send(&service)
+            .await;
+        assert_eq!(status(res), StatusCode::FORBIDDEN);
+
+        handle.ban_key("leaked", None);
+        let res = send("198.51.100.1", "")
+            .add_header("x-api-key", "leaked", true)
+            .send(&service)
+            .await;
+        assert_eq!(status(res), StatusCode::FORBIDDEN);
+
+        for _ in 0..2 {
+            let res = send("198.51.100.2", "?limited=true").send(&service).await;
+            assert_eq!(status(res), StatusCode::TOO_MANY_REQUESTS);
+        }
+        assert!(handle.is_ip_banned(&"198.51.100.2".parse().unwrap()));
+        assert_eq!(
+            status(send("198.51.100.2", "").send(&service).await),
+            StatusCode::FORBIDDEN
+        );
+
+        for _ in 0..3 {
+            let res = send("10.0.0.1", "?limited=true").send(&service).await;
+            assert_eq!(status(res), StatusCode::TOO_MANY_REQUESTS);
+        }
+        assert!(!handle.is_ip_banned(&"10.0.0.1".parse().unwrap()));
+
+        assert!(handle.unban_ip(&"198.51.100.2".parse().unwrap()));
+        assert_eq!(status(send("198.51.100.2", "").send(&service).await), StatusCode::OK);
+        assert_eq!(handle.banned(), (vec![], vec!["leaked".to_owned()]));
+    }
+}
@@ -0,0 +1,338 @@
+//! GeoIP enrichment middleware.
+//!
+//! [`GeoIp`] resolves the client ip of every request to its country, region, city and autonomous system, and
+//! injects the result into [`Depot`] as [`GeoInfo`]. Rate limiters, access logs and handlers read it by
+//! [`geo_info`], and [`GeoIp`] itself can reject requests from countries which can not be served for compliance
+//! reasons with `451 Unavailable For Legal Reasons`.
+//!
+//! Ips are resolved by a [`GeoLookup`], [`MaxMindLookup`] reads MaxMind databases, such as GeoLite2 or GeoIP2.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::geoip::{self, GeoIp, MaxMindLookup};
+//!
+//! #[handler]
+//! async fn hello(depot: &mut Depot) -> String {
+//!     let country = geoip::geo_info(depot).and_then(|info| info.country_code.as_deref());
+//!     format!("hello from {}", country.unwrap_or("unknown"))
+//! }
+//!
+//! let lookup = MaxMindLookup::open("GeoLite2-City.mmdb")
+//!     .unwrap()
+//!     .asn_db("GeoLite2-ASN.mmdb")
+//!     .unwrap();
+//! let geoip = GeoIp::new(lookup).deny_country("KP");
+//! let router = Router::new().hoop(geoip).get(hello);
+//! ```
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::net::IpAddr;
+use std::path::Path;
+
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::{Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+use crate::trusted_proxies::TrustedProxies;
+
+/// Geographical information of an ip.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct GeoInfo {
+    /// The resolved ip.
+    pub ip: Option<IpAddr>,
+    /// ISO 3166-1 alpha-2 country code, such as `US`.
+    pub country_code: Option<String>,
+    /// English name of the country.
+    pub country_name: Option<String>,
+    /// ISO 3166-2 code of the region, such as `CA` for California, without the country code.
+    pub region_code: Option<String>,
+    /// English name of the region.
+    pub region_name: Option<String>,
+    /// English name of the city.
+    pub city: Option<String>,
+    /// Autonomous system number.
+    pub asn: Option<u32>,
+    /// Organization of the autonomous system.
+    pub as_org: Option<String>,
+}
+impl GeoInfo {
+    /// Create a new `GeoInfo` of the ip without any information.
+    #[inline]
+    pub fn new(ip: IpAddr) -> Self {
+        Self {
+            ip: Some(ip),
+            ..Default::default()
+        }
+    }
+    /// Sets the country code.
+    #[inline]
+    pub fn country_code(mut self, code: impl Into<String>) -> Self {
+        self.country_code = Some(code.into());
+        self
+    }
+    /// Sets the region code.
+    #[inline]
+    pub fn region_code(mut self, code: impl Into<String>) -> Self {
+        self.region_code = Some(code.into());
+        self
+    }
+    /// Sets the autonomous system number.
+    #[inline]
+    pub fn asn(mut self, asn: u32) -> Self {
+        self.asn = Some(asn);
+        self
+    }
+}
+
+/// Get the geographical information of the request, returns `None` if no [`GeoIp`] handled the request.
+#[inline]
+pub fn geo_info(depot: &Depot) -> Option<&GeoInfo> {
+    depot.obtain::<GeoInfo>().ok()
+}
+
+/// Resolves ips to geographical information.
+pub trait GeoLookup: Send + Sync + 'static {
+    /// Resolve the ip, returns `None` if the ip is not found.
+    fn lookup(&self, ip: IpAddr) -> Option<GeoInfo>;
+}
+impl<F> GeoLookup for F
+where
+    F: Fn(IpAddr) -> Option<GeoInfo> + Send + Sync + 'static,
+{
+    #[inline]
+    fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        (self)(ip)
+    }
+}
+
+/// [`GeoLookup`] which reads MaxMind databases.
+///
+/// The geo database is a City or Country database, an ASN database can be added to resolve autonomous systems.
+pub struct MaxMindLookup {
+    geo: Option<Reader<Vec<u8>>>,
+    asn: Option<Reader<Vec<u8>>>,
+}
+impl Debug for MaxMindLookup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let database_type =
+            |reader: &Option<Reader<Vec<u8>>>| reader.as_ref().map(|r| r.metadata.database_type.clone());
+        f.debug_struct("MaxMindLookup")
+            .field("geo", &database_type(&self.geo))
+            .field("asn", &database_type(&self.asn))
+            .finish()
+    }
+}
+impl MaxMindLookup {
+    /// Open a City or Country database.
+    #[inline]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MaxMindDBError> {
+        Ok(Self::from_readers(Some(Reader::open_readfile(path)?), None))
+    }
+    /// Create a new `MaxMindLookup` with readers of a City or Country database and an ASN database.
+    #[inline]
+    pub fn from_readers(geo: Option<Reader<Vec<u8>>>, asn: Option<Reader<Vec<u8>>>) -> Self {
+        Self { geo, asn }
+    }
+    /// Open an ASN database.
+    #[inline]
+    pub fn asn_db(mut self, path: impl AsRef<Path>) -> Result<Self, MaxMindDBError> {
+        self.asn = Some(Reader::open_readfile(path)?);
+        Ok(self)
+    }
+}
+impl GeoLookup for MaxMindLookup {
+    fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        let mut info = GeoInfo::new(ip);
+        let mut found = false;
+        if let Some(reader) = &self.geo {
+            match reader.lookup::<geoip2::City>(ip) {
+                Ok(city) => {
+                    found = true;
+                    let english = |names: Option<BTreeMap<&str, &str>>| {
+                        names.and_then(|names| names.get("en").map(|name| (*name).to_owned()))
+                    };
+                    if let Some(country) = city.country {
+                        info.country_code = country.iso_code.map(ToOwned::to_owned);
+                        info.country_name = english(country.names);
+                    }
+                    if let Some(region) = city.subdivisions.and_then(|s| s.into_iter().next()) {
+                        info.region_code = region.iso_code.map(ToOwned::to_owned);
+                        info.region_name = english(region.names);
+                    }
+                    info.city = city.city.and_then(|city| english(city.names));
+                }
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                Err(e) => tracing::warn!(error = ?e, ip = %ip, "lookup geo database failed"),
+            }
+        }
+        if let Some(reader) = &self.asn {
+            match reader.lookup::<geoip2::Asn>(ip) {
+                Ok(asn) => {
+                    found = true;
+                    info.asn = asn.autonomous_system_number;
+                    info.as_org = asn.autonomous_system_organization.map(ToOwned::to_owned);
+                }
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                Err(e) => tracing::warn!(error = ?e, ip = %ip, "lookup asn database failed"),
+            }
+        }
+        found.then_some(info)
+    }
+}
+
+/// Middleware which resolves client ips and injects [`GeoInfo`] into [`Depot`].
+///
+/// Requests whose ip can not be resolved get a [`GeoInfo`] without information, so handlers can tell them from
+/// requests not handled by `GeoIp`.
+pub struct GeoIp<L> {
+    lookup: L,
+    trusted_proxies: TrustedProxies,
+    allowed_countries: Vec<String>,
+    denied_countries: Vec<String>,
+    status: StatusCode,
+    skipper: Box<dyn Skipper>,
+}
+impl<L> Debug for GeoIp<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeoIp")
+            .field("allowed_countries", &self.allowed_countries)
+            .field("denied_countries", &self.denied_countries)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+impl<L: GeoLookup> GeoIp<L> {
+    /// Create a new `GeoIp`, client ips are the remote address of the connection by default, see
+    /// [`GeoIp::trusted_proxies`].
+    #[inline]
+    pub fn new(lookup: L) -> Self {
+        Self {
+            lookup,
+            trusted_proxies: TrustedProxies::default(),
+            allowed_countries: Vec::new(),
+            denied_countries: Vec::new(),
+            status: StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Sets trusted proxies used to resolve client ips from `X-Forwarded-For` header.
+    #[inline]
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+    /// Only allow requests from the country, requests from unknown countries are rejected if any country is
+    /// allowed.
+    #[inline]
+    pub fn allow_country(mut self, code: impl Into<String>) -> Self {
+        self.allowed_countries.push(code.into().to_uppercase());
+        self
+    }
+    /// Reject requests from the country.
+    #[inline]
+    pub fn deny_country(mut self, code: impl Into<String>) -> Self {
+        self.denied_countries.push(code.into().to_uppercase());
+        self
+    }
+    /// Sets the status code of rejected requests, default is `451 Unavailable For Legal Reasons`.
+    #[inline]
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+    /// Sets skipper.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
+    fn is_allowed(&self, info: &GeoInfo) -> bool {
+        match info.country_code.as_deref() {
+            Some(code) => {
+                !self.denied_countries.iter().any(|c| c.eq_ignore_ascii_case(code))
+                    && (self.allowed_countries.is_empty()
+                        || self.allowed_countries.iter().any(|c| c.eq_ignore_ascii_case(code)))
+            }
+            None => self.allowed_countries.is_empty(),
+        }
+    }
+}
+
+#[async_trait]
+impl<L: GeoLookup> Handler for GeoIp<L> {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let info = match self.trusted_proxies.client_ip(req) {
+            Some(ip) => self.lookup.lookup(ip).unwrap_or_else(|| GeoInfo::new(ip)),
+            None => GeoInfo::default(),
+        };
+        let allowed = self.is_allowed(&info);
+        depot.inject(info);
+        if !allowed {
+            res.render(StatusError::from_code(self.status).unwrap_or_else(StatusError::forbidden));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    fn lookup(ip: IpAddr) -> Option<GeoInfo> {
+        match ip.to_string().as_str() {
+            "192.0.2.1" => Some(GeoInfo::new(ip).country_code("US").region_code("CA").asn(64496)),
+            "192.0.2.2" => Some(GeoInfo::new(ip).country_code("KP")),
+            _ => None,
+        }
+    }
+
+    /// Pretends requests are forwarded by a trusted proxy at `127.0.0.1`.
+    #[handler]
+    async fn from_proxy(req: &mut Request) {
+        *req.remote_addr_mut() = std::net::SocketAddr::from(([127, 0, 0, 1], 80)).into();
+    }
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        let info = geo_info(depot).unwrap();
+        format!(
+            "{}/{}/{}",
+            info.country_code.as_deref().unwrap_or("-"),
+            info.region_code.as_deref().unwrap_or("-"),
+            info.asn.unwrap_or_default()
+        )
+    }
+
+    #[tokio::test]
+    async fn test_geoip() {
+        let proxies = TrustedProxies::new(["127.0.0.1".parse().unwrap()]);
+        let geoip = GeoIp::new(lookup).trusted_proxies(proxies.clone()).deny_country("kp");
+        let service = Service::new(Router::with_hoop(from_proxy).hoop(geoip).get(hello));
+        let send = |ip: &str| TestClient::get("http://127.0.0.1:5801/").add_header("x-forwarded-for", ip, true);
+
+        let mut res = send("192.0.2.1").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "US/CA/64496");
+        let res = send("192.0.2.2").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS));
+        let mut res = send("198.51.100.1").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "-/-/0");
+
+        let geoip = GeoIp::new(lookup).trusted_proxies(proxies).allow_country("US");
+        let service = Service::new(Router::with_hoop(from_proxy).hoop(geoip).get(hello));
+        let res = send("192.0.2.1").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let res = send("198.51.100.1").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS));
+    }
+}
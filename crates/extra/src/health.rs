@@ -0,0 +1,373 @@
+//! Health and readiness checking.
+//!
+//! [`Health`] serves `/healthz` and `/readyz` endpoints, every registered [`HealthIndicator`] is checked when
+//! the endpoints are requested. When any indicator is down, the response status code is `503 Service Unavailable`.
+//!
+//! Read more: <https://salvo.rs>
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Router};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Health status.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// The component is working.
+    Up,
+    /// The component is not working.
+    Down,
+}
+
+/// Result of a [`HealthIndicator`] check.
+#[derive(Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct Indication {
+    /// Status of the checked component.
+    pub status: HealthStatus,
+    /// Details of the checked component, such as queue depth or error message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+impl Indication {
+    /// Create an indication with `Up` status.
+    #[inline]
+    pub fn up() -> Self {
+        Self {
+            status: HealthStatus::Up,
+            details: None,
+        }
+    }
+    /// Create an indication with `Down` status.
+    #[inline]
+    pub fn down() -> Self {
+        Self {
+            status: HealthStatus::Down,
+            details: None,
+        }
+    }
+    /// Sets details.
+    #[inline]
+    pub fn details(mut self, details: impl Into<Value>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+impl<E: std::fmt::Display> From<Result<(), E>> for Indication {
+    #[inline]
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(_) => Self::up(),
+            Err(e) => Self::down().details(e.to_string()),
+        }
+    }
+}
+
+/// Check health of a component, such as database connection or message queue.
+///
+/// Async closures returning [`Indication`] implement this trait.
+#[async_trait]
+pub trait HealthIndicator: Send + Sync + 'static {
+    /// Check the component.
+    async fn check(&self) -> Indication;
+}
+#[async_trait]
+impl<F, Fut> HealthIndicator for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Indication> + Send,
+{
+    #[inline]
+    async fn check(&self) -> Indication {
+        self().await
+    }
+}
+
+/// Health report rendered as JSON by health endpoints.
+#[derive(Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct HealthReport {
+    /// Overall status, it is `Down` if any indicator is down or the server is draining.
+    pub status: HealthStatus,
+    /// Whether the server is draining for shutdown, only reported by readiness endpoint.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub draining: bool,
+    /// Results of all indicators.
+    pub checks: BTreeMap<String, Indication>,
+}
+
+struct Inner {
+    indicators: Vec<(String, Box<dyn HealthIndicator>)>,
+    timeout: Duration,
+    ready: AtomicBool,
+}
+
+/// Health endpoints driven by registered [`HealthIndicator`]s.
+///
+/// `Health` is cheap to clone, all clones share the same indicators and readiness.
+///
+/// # Example
+///
+/// ```no_run
+/// # use tokio::sync::oneshot;
+/// use std::time::Duration;
+///
+/// use salvo_core::prelude::*;
+/// use salvo_extra::health::{Health, Indication};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, rx) = oneshot::channel::<()>();
+///     let health = Health::new().indicator("db", || async { Indication::up() });
+///     let router = Router::new().push(health.router());
+///     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+///     let signal = health.shutdown_signal(async { rx.await.ok(); }, Duration::from_secs(5));
+///     let server = Server::new(acceptor).serve_with_graceful_shutdown(router, signal, None);
+///     tokio::task::spawn(server);
+///
+///     // Later, flip readiness and start the shutdown after 5 seconds.
+///     let _ = tx.send(());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Health {
+    inner: Arc<Inner>,
+}
+impl Default for Health {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl std::fmt::Debug for Health {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Health")
+            .field("indicators", &self.inner.indicators.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("timeout", &self.inner.timeout)
+            .field("ready", &self.is_ready())
+            .finish()
+    }
+}
+impl Health {
+    /// Create a new `Health` without indicators.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                indicators: Vec::new(),
+                timeout: Duration::from_secs(5),
+                ready: AtomicBool::new(true),
+            }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("`Health` can not be configured after it is cloned")
+    }
+
+    /// Register an indicator with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Health` has been cloned.
+    #[inline]
+    pub fn indicator(mut self, name: impl Into<String>, indicator: impl HealthIndicator) -> Self {
+        self.inner_mut().indicators.push((name.into(), Box::new(indicator)));
+        self
+    }
+
+    /// Sets timeout of every indicator check, indicators not finished in time are reported as down. Default is 5
+    /// seconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Health` has been cloned.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner_mut().timeout = timeout;
+        self
+    }
+
+    /// Sets whether the server is ready to receive traffic.
+    #[inline]
+    pub fn set_ready(&self, ready: bool) {
+        self.inner.ready.store(ready, Ordering::Release);
+    }
+    /// Returns `true` if the server is ready to receive traffic.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.inner.ready.load(Ordering::Acquire)
+    }
+
+    /// Wrap the graceful shutdown `signal` passed to `Server::serve_with_graceful_shutdown`.
+    ///
+    /// When `signal` is fired, readiness is flipped to not ready, then it waits `drain` duration for load
+    /// balancers to stop sending traffic before the server stops accepting connections.
+    pub fn shutdown_signal<G>(&self, signal: G, drain: Duration) -> impl Future<Output = ()> + Send + 'static
+    where
+        G: Future<Output = ()> + Send + 'static,
+    {
+        let health = self.clone();
+        async move {
+            signal.await;
+            tracing::info!(drain_in_seconds = drain.as_secs_f32(), "server is draining");
+            health.set_ready(false);
+            tokio::time::sleep(drain).await;
+        }
+    }
+
+    /// Check all indicators.
+    pub async fn check(&self) -> HealthReport {
+        let timeout = self.inner.timeout;
+        let indications = join_all(self.inner.indicators.iter().map(|(_, indicator)| async move {
+            tokio::time::timeout(timeout, indicator.check())
+                .await
+                .unwrap_or_else(|_| Indication::down().details("check timed out"))
+        }))
+        .await;
+        let status = if indications.iter().all(|i| i.status == HealthStatus::Up) {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        };
+        let checks = self
+            .inner
+            .indicators
+            .iter()
+            .map(|(name, _)| name.clone())
+            .zip(indications)
+            .collect();
+        HealthReport {
+            status,
+            draining: false,
+            checks,
+        }
+    }
+
+    /// Get handler for liveness endpoint.
+    #[inline]
+    pub fn healthz(&self) -> HealthHandler {
+        HealthHandler {
+            health: self.clone(),
+            readiness: false,
+        }
+    }
+    /// Get handler for readiness endpoint, it also reports down when the server is not ready.
+    #[inline]
+    pub fn readyz(&self) -> HealthHandler {
+        HealthHandler {
+            health: self.clone(),
+            readiness: true,
+        }
+    }
+    /// Create a router serves `healthz` and `readyz` endpoints.
+    #[inline]
+    pub fn router(&self) -> Router {
+        Router::new()
+            .push(Router::with_path("healthz").get(self.healthz()))
+            .push(Router::with_path("readyz").get(self.readyz()))
+    }
+}
+
+/// Handler renders [`HealthReport`], created by [`Health::healthz`] or [`Health::readyz`].
+#[derive(Clone, Debug)]
+pub struct HealthHandler {
+    health: Health,
+    readiness: bool,
+}
+#[async_trait]
+impl Handler for HealthHandler {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let mut report = self.health.check().await;
+        if self.readiness && !self.health.is_ready() {
+            report.draining = true;
+            report.status = HealthStatus::Down;
+        }
+        match report.status {
+            HealthStatus::Up => res.status_code(StatusCode::OK),
+            HealthStatus::Down => res.status_code(StatusCode::SERVICE_UNAVAILABLE),
+        };
+        res.render(Json(report));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health() {
+        let health = Health::new()
+            .timeout(Duration::from_millis(50))
+            .indicator("db", || async { Indication::up() })
+            .indicator("queue", || async { Indication::up().details(json!({"depth": 3})) });
+        let service = Service::new(health.router());
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/healthz").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(
+            res.take_json::<Value>().await.unwrap(),
+            json!({"status": "up", "checks": {"db": {"status": "up"}, "queue": {"status": "up", "details": {"depth": 3}}}})
+        );
+
+        health.set_ready(false);
+        let mut res = TestClient::get("http://127.0.0.1:5801/readyz").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        let report = res.take_json::<Value>().await.unwrap();
+        assert_eq!(report["status"], "down");
+        assert_eq!(report["draining"], true);
+        let res = TestClient::get("http://127.0.0.1:5801/healthz").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_health_down() {
+        let health = Health::new()
+            .timeout(Duration::from_millis(50))
+            .indicator("db", || async { Indication::from(Err::<(), _>("connection refused")) })
+            .indicator("slow", || async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Indication::up()
+            });
+        let mut res = TestClient::get("http://127.0.0.1:5801/readyz")
+            .send(health.router())
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(
+            res.take_json::<Value>().await.unwrap(),
+            json!({"status": "down", "checks": {
+                "db": {"status": "down", "details": "connection refused"},
+                "slow": {"status": "down", "details": "check timed out"},
+            }})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_signal() {
+        let health = Health::new();
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let signal = health.shutdown_signal(
+            async {
+                rx.await.ok();
+            },
+            Duration::from_millis(10),
+        );
+        let task = tokio::spawn(signal);
+        assert!(health.is_ready());
+        tx.send(()).unwrap();
+        task.await.unwrap();
+        assert!(!health.is_ready());
+    }
+}
@@ -0,0 +1,297 @@
+//! Outbound http client which propagates the context of the current request.
+//!
+//! Service to service calls should carry the correlation data of the request which triggers them. [`HttpClient`]
+//! wraps a [`reqwest::Client`] and adds them to every outbound request, so handlers do not copy headers manually:
+//!
+//! - The request id set by [`RequestId`](crate::request_id::RequestId).
+//! - Trace context headers of the incoming request, `traceparent`, `tracestate` and `baggage`.
+//! - The remaining time until the deadline of the request, it is used as the timeout of the outbound request and
+//!   optionally sent by a [`DeadlineHeader`].
+//!
+//! Clients other than reqwest, such as a hyper client, can use [`HttpClient::headers`] to get the headers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::http_client::HttpClient;
+//! use salvo_extra::request_id::RequestId;
+//!
+//! #[handler]
+//! async fn profile(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+//!     let client = HttpClient::new();
+//!     match client.scoped(req, depot).get("http://users.internal/me").send().await {
+//!         Ok(upstream) => res.render(upstream.text().await.unwrap_or_default()),
+//!         Err(_) => {
+//!             res.status_code(StatusCode::BAD_GATEWAY);
+//!         }
+//!     }
+//! }
+//!
+//! let router = Router::new().hoop(RequestId::new()).get(profile);
+//! ```
+use std::time::{Duration, Instant};
+
+use reqwest::{IntoUrl, RequestBuilder};
+use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue};
+use salvo_core::http::{DeadlineHeader, Method, Request};
+use salvo_core::Depot;
+
+use crate::request_id::RequestIdDepotExt;
+
+/// Header of W3C trace context.
+pub const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+/// Header of vendor specific W3C trace context.
+pub const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+/// Header of W3C baggage.
+pub const BAGGAGE: HeaderName = HeaderName::from_static("baggage");
+
+/// Context of the current request which is propagated to outbound requests.
+#[derive(Clone, Default, Debug)]
+#[non_exhaustive]
+pub struct OutboundContext {
+    /// Request id of the current request.
+    pub request_id: Option<String>,
+    /// Trace context headers of the current request.
+    pub trace_headers: HeaderMap,
+    /// Deadline of the current request.
+    pub deadline: Option<Instant>,
+}
+impl OutboundContext {
+    /// Capture the context of a request.
+    ///
+    /// The request id is read from depot, or from the `request_id_header` of the request if no
+    /// [`RequestId`](crate::request_id::RequestId) handled it.
+    pub fn capture(req: &Request, depot: &Depot, request_id_header: &HeaderName) -> Self {
+        let request_id = depot.request_id().map(ToOwned::to_owned).or_else(|| {
+            req.headers()
+                .get(request_id_header)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned)
+        });
+        let mut trace_headers = HeaderMap::new();
+        for name in [TRACEPARENT, TRACESTATE, BAGGAGE] {
+            for value in req.headers().get_all(&name) {
+                trace_headers.append(name.clone(), value.clone());
+            }
+        }
+        Self {
+            request_id,
+            trace_headers,
+            deadline: req.deadline(),
+        }
+    }
+
+    /// Get the time remaining until the deadline, returns `None` if there is no deadline.
+    #[inline]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Http client which propagates the context of the current request to outbound requests.
+///
+/// `HttpClient` is cheap to clone, the connection pool of the inner client is shared by all clones.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    request_id_header: HeaderName,
+    deadline_header: Option<DeadlineHeader>,
+}
+impl Default for HttpClient {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl HttpClient {
+    /// Create new `HttpClient`, the request id is sent in the `x-request-id` header and no deadline header is sent.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            request_id_header: HeaderName::from_static("x-request-id"),
+            deadline_header: None,
+        }
+    }
+    /// Sets the inner http client.
+    #[inline]
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+    /// Sets the header name of the request id, it should be the same as the one used by
+    /// [`RequestId`](crate::request_id::RequestId).
+    #[inline]
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
+    }
+    /// Sets the header which sends the remaining time to upstreams.
+    #[inline]
+    pub fn deadline_header(mut self, header: DeadlineHeader) -> Self {
+        self.deadline_header = Some(header);
+        self
+    }
+
+    /// Get the inner http client.
+    #[inline]
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Returns the headers which propagate the context.
+    pub fn headers(&self, context: &OutboundContext) -> HeaderMap {
+        let mut headers = context.trace_headers.clone();
+        if let Some(value) = context
+            .request_id
+            .as_deref()
+            .and_then(|id| HeaderValue::from_str(id).ok())
+        {
+            headers.insert(self.request_id_header.clone(), value);
+        }
+        if let (Some(header), Some(deadline)) = (&self.deadline_header, context.deadline) {
+            header.insert(&mut headers, deadline);
+        }
+        headers
+    }
+
+    /// Returns a client which propagates the context of the request.
+    #[inline]
+    pub fn scoped(&self, req: &Request, depot: &Depot) -> ScopedClient<'_> {
+        ScopedClient {
+            client: self,
+            context: OutboundContext::capture(req, depot, &self.request_id_header),
+        }
+    }
+}
+
+/// Http client bound to the context of a request, it is created by [`HttpClient::scoped`].
+#[derive(Debug)]
+pub struct ScopedClient<'a> {
+    client: &'a HttpClient,
+    context: OutboundContext,
+}
+impl ScopedClient<'_> {
+    /// Get the propagated context.
+    #[inline]
+    pub fn context(&self) -> &OutboundContext {
+        &self.context
+    }
+
+    /// Start building a request with the method and url, the headers of the context are set and the timeout is
+    /// the remaining time until the deadline.
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        let builder = self
+            .client
+            .client
+            .request(method, url)
+            .headers(self.client.headers(&self.context));
+        match self.context.remaining_time() {
+            Some(remaining) => builder.timeout(remaining),
+            None => builder,
+        }
+    }
+    /// Start building a `GET` request.
+    #[inline]
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+    /// Start building a `POST` request.
+    #[inline]
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+    /// Start building a `PUT` request.
+    #[inline]
+    pub fn put<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+    /// Start building a `PATCH` request.
+    #[inline]
+    pub fn patch<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::PATCH, url)
+    }
+    /// Start building a `DELETE` request.
+    #[inline]
+    pub fn delete<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::conn::TcpListener;
+    use salvo_core::http::DeadlineFormat;
+    use salvo_core::conn::Acceptor;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::request_id::RequestId;
+
+    const X_TIMEOUT_MS: HeaderName = HeaderName::from_static("x-timeout-ms");
+
+    #[handler]
+    async fn echo(req: &mut Request) -> String {
+        let header = |name: &str| req.header::<String>(name).unwrap_or_default();
+        let timeout: u64 = header("x-timeout-ms").parse().unwrap_or_default();
+        format!(
+            "{}|{}|{}",
+            header("x-request-id"),
+            header("traceparent"),
+            timeout > 4000 && timeout <= 5000
+        )
+    }
+
+    async fn serve_upstream() -> String {
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(Router::new().get(echo)).await;
+        });
+        format!("http://{addr}/")
+    }
+
+    struct Profile {
+        client: HttpClient,
+        upstream: String,
+    }
+    #[async_trait]
+    impl Handler for Profile {
+        async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let scoped = self.client.scoped(req, depot);
+            assert!(scoped.context().remaining_time().unwrap() > Duration::from_secs(4));
+            let upstream = scoped.get(&self.upstream).send().await.unwrap();
+            res.render(upstream.text().await.unwrap());
+        }
+    }
+
+    #[handler]
+    async fn deadline(req: &mut Request) {
+        req.set_deadline(Some(Instant::now() + Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn test_http_client() {
+        let client = HttpClient::new()
+            .http_client(reqwest::Client::builder().no_proxy().build().unwrap())
+            .deadline_header(DeadlineHeader::new(X_TIMEOUT_MS, DeadlineFormat::TimeoutMillis));
+        let profile = Profile {
+            client,
+            upstream: serve_upstream().await,
+        };
+        let router = Router::new().hoop(RequestId::new()).hoop(deadline).get(profile);
+        let service = Service::new(router);
+
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("traceparent", traceparent, true)
+            .send(&service)
+            .await;
+        let request_id = res.headers()["x-request-id"].to_str().unwrap().to_owned();
+        let content = res.take_string().await.unwrap();
+        assert_eq!(content, format!("{request_id}|{traceparent}|true"));
+    }
+}
@@ -0,0 +1,594 @@
+//! Idempotency middleware.
+//!
+//! For unsafe requests carrying an `Idempotency-Key` header, the first response is saved in an
+//! [`IdempotencyStore`] and replayed for duplicate requests within the TTL. While the first request is still
+//! being processed, duplicates are rejected with `409 Conflict`. Reusing a key with a different request is
+//! rejected with `422 Unprocessable Entity`.
+//!
+//! Keys are scoped by the client, so different clients never share responses. By default, the client is
+//! identified by the credentials in `Authorization` header, or the client ip if it is absent, use
+//! [`Idempotency::scope_with`] to identify it by the authenticated user.
+//!
+//! Responses with server errors or streaming bodies are not saved, so the request can be retried.
+//!
+//! Read more: <https://datatracker.ietf.org/doc/draft-ietf-httpapi-idempotency-key-header/>
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use salvo_core::http::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use salvo_core::http::request::secure_max_size;
+use salvo_core::http::{HeaderMap, Method, Request, ResBody, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::{Digest, Sha256};
+
+/// Default header name of idempotency key.
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+/// Header name added to replayed responses.
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Saved response.
+#[derive(Clone, Debug)]
+pub struct IdempotentResponse {
+    /// Response status code.
+    pub status: Option<StatusCode>,
+    /// Response headers.
+    pub headers: HeaderMap,
+    /// Response body.
+    pub body: Bytes,
+}
+impl IdempotentResponse {
+    fn from_response(res: &Response) -> Option<Self> {
+        let body = match &res.body {
+            ResBody::None => Bytes::new(),
+            ResBody::Once(bytes) => bytes.clone(),
+            ResBody::Chunks(chunks) => chunks.iter().flatten().copied().collect::<Vec<u8>>().into(),
+            _ => return None,
+        };
+        Some(Self {
+            status: res.status_code,
+            headers: res.headers().clone(),
+            body,
+        })
+    }
+}
+
+/// Result of [`IdempotencyStore::begin`].
+#[derive(Clone, Debug)]
+pub enum Begin {
+    /// The key is acquired, the request should be processed.
+    ///
+    /// The fence token must be passed to [`IdempotencyStore::complete`] or [`IdempotencyStore::release`].
+    Acquired(u64),
+    /// Another request with the same key is being processed.
+    InFlight {
+        /// Fingerprint of the request being processed.
+        fingerprint: String,
+    },
+    /// The request has been processed.
+    Completed {
+        /// Fingerprint of the processed request.
+        fingerprint: String,
+        /// Saved response.
+        response: IdempotentResponse,
+    },
+}
+
+/// Store of idempotency keys.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Error type for IdempotencyStore.
+    type Error: StdError + Sync + Send + 'static;
+    /// Acquires `key` for a request with `fingerprint` if it is absent or its lock is older than `lock_timeout`.
+    ///
+    /// Every acquisition must return a new fence token.
+    async fn begin(&self, key: &str, fingerprint: &str, lock_timeout: Duration) -> Result<Begin, Self::Error>;
+    /// Saves the response of `key` for `ttl`, only if `fence` still holds the key.
+    ///
+    /// Returns `false` if the key has been acquired by another request.
+    async fn complete(
+        &self,
+        key: &str,
+        fence: u64,
+        response: IdempotentResponse,
+        ttl: Duration,
+    ) -> Result<bool, Self::Error>;
+    /// Removes `key`, only if `fence` still holds the key, so the request can be retried.
+    async fn release(&self, key: &str, fence: u64) -> Result<(), Self::Error>;
+}
+
+enum Record {
+    Pending {
+        fence: u64,
+        fingerprint: String,
+        locked_at: Instant,
+        lock_timeout: Duration,
+    },
+    Completed {
+        fingerprint: String,
+        response: IdempotentResponse,
+        expires_at: Instant,
+    },
+}
+
+/// In-memory [`IdempotencyStore`], it is only suitable for a single process.
+#[derive(Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, Record>>,
+    fence: AtomicU64,
+}
+impl MemoryStore {
+    /// Create a new `MemoryStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl fmt::Debug for MemoryStore {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryStore").finish()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryStore {
+    type Error = std::convert::Infallible;
+
+    async fn begin(&self, key: &str, fingerprint: &str, lock_timeout: Duration) -> Result<Begin, Self::Error> {
+        let now = Instant::now();
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.retain(|_, record| match record {
+            Record::Pending {
+                locked_at,
+                lock_timeout,
+                ..
+            } => now.duration_since(*locked_at) < *lock_timeout,
+            Record::Completed { expires_at, .. } => *expires_at > now,
+        });
+        match records.get(key) {
+            Some(Record::Pending { fingerprint, .. }) => Ok(Begin::InFlight {
+                fingerprint: fingerprint.clone(),
+            }),
+            Some(Record::Completed {
+                fingerprint, response, ..
+            }) => Ok(Begin::Completed {
+                fingerprint: fingerprint.clone(),
+                response: response.clone(),
+            }),
+            None => {
+                let fence = self.fence.fetch_add(1, Ordering::Relaxed) + 1;
+                records.insert(
+                    key.to_owned(),
+                    Record::Pending {
+                        fence,
+                        fingerprint: fingerprint.to_owned(),
+                        locked_at: now,
+                        lock_timeout,
+                    },
+                );
+                Ok(Begin::Acquired(fence))
+            }
+        }
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        fence: u64,
+        response: IdempotentResponse,
+        ttl: Duration,
+    ) -> Result<bool, Self::Error> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(record) = records.get_mut(key) {
+            if let Record::Pending {
+                fence: current,
+                fingerprint,
+                ..
+            } = record
+            {
+                if *current == fence {
+                    let fingerprint = std::mem::take(fingerprint);
+                    *record = Record::Completed {
+                        fingerprint,
+                        response,
+                        expires_at: Instant::now() + ttl,
+                    };
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    async fn release(&self, key: &str, fence: u64) -> Result<(), Self::Error> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(records.get(key), Some(Record::Pending { fence: current, .. }) if *current == fence) {
+            records.remove(key);
+        }
+        Ok(())
+    }
+}
+
+type ScopeFn = dyn Fn(&Request, &Depot) -> Option<String> + Send + Sync;
+
+/// Default scope of keys, the credentials in `Authorization` header, or the client ip if it is absent.
+fn default_scope(req: &Request, _depot: &Depot) -> Option<String> {
+    if let Some(credentials) = req.headers().get(AUTHORIZATION) {
+        return Some(format!("auth-{}", hex::encode(Sha256::digest(credentials.as_bytes()))));
+    }
+    let remote_addr = req.remote_addr();
+    match remote_addr.clone().into_std() {
+        Some(addr) => Some(format!("ip-{}", addr.ip())),
+        None => Some(format!("addr-{remote_addr}")),
+    }
+}
+
+/// Idempotency middleware.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::idempotency::{Idempotency, MemoryStore};
+///
+/// #[handler]
+/// async fn create_payment() -> &'static str {
+///     "created"
+/// }
+///
+/// let router = Router::with_path("payments")
+///     .hoop(Idempotency::new(MemoryStore::new()))
+///     .post(create_payment);
+/// ```
+pub struct Idempotency<S> {
+    store: S,
+    header_name: HeaderName,
+    methods: Vec<Method>,
+    ttl: Duration,
+    lock_timeout: Duration,
+    required: bool,
+    scope: Box<ScopeFn>,
+}
+impl<S> fmt::Debug for Idempotency<S> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Idempotency")
+            .field("header_name", &self.header_name)
+            .field("methods", &self.methods)
+            .field("ttl", &self.ttl)
+            .field("lock_timeout", &self.lock_timeout)
+            .field("required", &self.required)
+            .finish()
+    }
+}
+impl<S> Idempotency<S>
+where
+    S: IdempotencyStore,
+{
+    /// Create a new `Idempotency`.
+    ///
+    /// By default, `POST` and `PATCH` requests are handled, responses are kept for 24 hours and locks of
+    /// unfinished requests expire after 60 seconds. Keys are scoped by the credentials in `Authorization` header,
+    /// or the client ip if it is absent.
+    #[inline]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            header_name: HeaderName::from_static(IDEMPOTENCY_KEY),
+            methods: vec![Method::POST, Method::PATCH],
+            ttl: Duration::from_secs(24 * 60 * 60),
+            lock_timeout: Duration::from_secs(60),
+            required: false,
+            scope: Box::new(default_scope),
+        }
+    }
+    /// Sets header name of idempotency key.
+    #[inline]
+    pub fn header_name(mut self, name: HeaderName) -> Self {
+        self.header_name = name;
+        self
+    }
+    /// Sets methods handled by this middleware.
+    #[inline]
+    pub fn methods(mut self, methods: impl Into<Vec<Method>>) -> Self {
+        self.methods = methods.into();
+        self
+    }
+    /// Sets how long responses are kept.
+    #[inline]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    /// Sets how long a key is locked by an unfinished request, after which the request can be retried.
+    #[inline]
+    pub fn lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+    /// Rejects requests without idempotency key with `400 Bad Request` if `required` is `true`.
+    #[inline]
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+    /// Sets scope of keys, such as the authenticated user, so different clients can not share responses.
+    ///
+    /// Requests are not handled if it returns `None`. If the scope is not behind an authentication, clients can
+    /// share responses by the same key, they may contain private data such as `Set-Cookie` headers.
+    #[inline]
+    pub fn scope_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static,
+    {
+        self.scope = Box::new(f);
+        self
+    }
+}
+
+#[async_trait]
+impl<S> Handler for Idempotency<S>
+where
+    S: IdempotencyStore,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if !self.methods.contains(req.method()) {
+            return;
+        }
+        let key = match req.headers().get(&self.header_name).map(|v| v.to_str()) {
+            Some(Ok(key)) if !key.is_empty() && key.len() <= 255 => key.to_owned(),
+            None if !self.required => return,
+            _ => {
+                res.render(StatusError::bad_request().brief("Invalid or missing idempotency key."));
+                ctrl.skip_rest();
+                return;
+            }
+        };
+        let key = match (self.scope)(req, depot) {
+            Some(scope) => format!("{scope}:{key}"),
+            None => return,
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(req.method().as_str());
+        hasher.update([0]);
+        hasher.update(req.uri().path());
+        hasher.update([0]);
+        hasher.update(req.uri().query().unwrap_or_default());
+        hasher.update([0]);
+        // The body is buffered, so the next handlers can still read it.
+        match req.buffer_body(secure_max_size()).await {
+            Ok(payload) => hasher.update(payload),
+            Err(e) => {
+                res.render(e);
+                ctrl.skip_rest();
+                return;
+            }
+        }
+        let fingerprint = hex::encode(hasher.finalize());
+
+        let fence = match self.store.begin(&key, &fingerprint, self.lock_timeout).await {
+            Ok(Begin::Acquired(fence)) => fence,
+            Ok(Begin::InFlight { fingerprint: existing })
+            | Ok(Begin::Completed {
+                fingerprint: existing, ..
+            }) if existing != fingerprint => {
+                res.render(
+                    StatusError::unprocessable_entity().brief("Idempotency key is reused with a different request."),
+                );
+                ctrl.skip_rest();
+                return;
+            }
+            Ok(Begin::InFlight { .. }) => {
+                res.render(StatusError::conflict().brief("A request with the same idempotency key is in progress."));
+                ctrl.skip_rest();
+                return;
+            }
+            Ok(Begin::Completed { response, .. }) => {
+                let IdempotentResponse { status, headers, body } = response;
+                if let Some(status) = status {
+                    res.status_code(status);
+                }
+                *res.headers_mut() = headers;
+                res.headers_mut()
+                    .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+                *res.body_mut() = ResBody::Once(body);
+                ctrl.skip_rest();
+                return;
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "idempotency store failed");
+                res.render(StatusError::internal_server_error());
+                ctrl.skip_rest();
+                return;
+            }
+        };
+
+        ctrl.call_next(req, depot, res).await;
+
+        let is_server_error = res.status_code.map(|s| s.is_server_error()).unwrap_or(false);
+        let response = if is_server_error {
+            None
+        } else {
+            IdempotentResponse::from_response(res)
+        };
+        let result = match response {
+            Some(response) => match self.store.complete(&key, fence, response, self.ttl).await {
+                Ok(true) => Ok(()),
+                Ok(false) => {
+                    tracing::warn!(key, "idempotency key lock is lost, response is not saved");
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
+            None => self.store.release(&key, fence).await,
+        };
+        if let Err(e) = result {
+            tracing::error!(error = ?e, "idempotency store failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{RequestBuilder, ResponseExt, TestClient};
+
+    use super::*;
+
+    struct CreatePayment {
+        count: Arc<AtomicUsize>,
+    }
+    #[async_trait]
+    impl Handler for CreatePayment {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            if req.query::<bool>("fail").unwrap_or(false) {
+                res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+                return;
+            }
+            if req.query::<bool>("slow").unwrap_or(false) {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            res.status_code(StatusCode::CREATED);
+            res.render(format!("payment {count}"));
+        }
+    }
+
+    fn service(count: Arc<AtomicUsize>) -> Service {
+        let router = Router::with_path("payments")
+            .hoop(Idempotency::new(MemoryStore::new()))
+            .post(CreatePayment { count });
+        Service::new(router)
+    }
+
+    fn post(key: &str, query: &str, body: &str) -> RequestBuilder {
+        TestClient::post(format!("http://127.0.0.1:5801/payments{query}"))
+            .add_header(IDEMPOTENCY_KEY, key, true)
+            .text(body.to_owned())
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_replay() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let service = service(count.clone());
+
+        let mut res = post("a", "", "amount=1").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        assert!(res.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        assert_eq!(res.take_string().await.unwrap(), "payment 1");
+
+        let mut res = post("a", "", "amount=1").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        assert_eq!(res.headers().get(IDEMPOTENT_REPLAYED).unwrap(), "true");
+        assert_eq!(res.take_string().await.unwrap(), "payment 1");
+
+        let res = post("a", "", "amount=2").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNPROCESSABLE_ENTITY));
+
+        let mut res = post("b", "", "amount=1").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "payment 2");
+
+        let mut res = TestClient::post("http://127.0.0.1:5801/payments").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "payment 3");
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_in_flight_and_failure() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let service = Arc::new(service(count.clone()));
+
+        let first = tokio::spawn({
+            let service = service.clone();
+            async move { post("a", "?slow=true", "").send(&*service).await.status_code }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let res = post("a", "?slow=true", "").send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+        assert_eq!(first.await.unwrap(), Some(StatusCode::CREATED));
+
+        let res = post("b", "?fail=true", "").send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        let res = post("b", "?fail=true", "").send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_scope_query_and_body() {
+        #[handler]
+        async fn echo(req: &mut Request) -> String {
+            let payload = req.payload().await.unwrap();
+            String::from_utf8_lossy(payload).into_owned()
+        }
+        let count = Arc::new(AtomicUsize::new(0));
+        let service = service(count.clone());
+
+        let mut res = post("a", "", "amount=1")
+            .add_header(AUTHORIZATION, "Bearer alice", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "payment 1");
+        let mut res = post("a", "", "amount=1")
+            .add_header(AUTHORIZATION, "Bearer bob", true)
+            .send(&service)
+            .await;
+        assert!(res.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        assert_eq!(res.take_string().await.unwrap(), "payment 2");
+
+        let res = post("a", "?currency=eur", "amount=1")
+            .add_header(AUTHORIZATION, "Bearer alice", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNPROCESSABLE_ENTITY));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        let router = Router::with_path("echo")
+            .hoop(Idempotency::new(MemoryStore::new()))
+            .post(echo);
+        let mut res = TestClient::post("http://127.0.0.1:5801/echo")
+            .add_header(IDEMPOTENCY_KEY, "a", true)
+            .text("amount=1")
+            .send(router)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "amount=1");
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_fencing() {
+        let store = MemoryStore::new();
+        let response = IdempotentResponse {
+            status: Some(StatusCode::OK),
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"ok"),
+        };
+        let ttl = Duration::from_secs(60);
+        let Begin::Acquired(stale) = store.begin("a", "fp", Duration::ZERO).await.unwrap() else {
+            panic!("key should be acquired");
+        };
+        let Begin::Acquired(fence) = store.begin("a", "fp", ttl).await.unwrap() else {
+            panic!("expired lock should be acquired again");
+        };
+        assert_ne!(stale, fence);
+        assert!(!store.complete("a", stale, response.clone(), ttl).await.unwrap());
+        store.release("a", stale).await.unwrap();
+        assert!(matches!(
+            store.begin("a", "fp", ttl).await.unwrap(),
+            Begin::InFlight { .. }
+        ));
+        assert!(store.complete("a", fence, response, ttl).await.unwrap());
+        assert!(matches!(
+            store.begin("a", "fp", ttl).await.unwrap(),
+            Begin::Completed { .. }
+        ));
+    }
+}
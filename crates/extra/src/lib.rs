@@ -11,6 +11,16 @@
 #[macro_use]
 mod cfg;
 
+cfg_feature! {
+    #![any(feature = "access-log", feature = "bot-detection", feature = "firewall", feature = "geoip")]
+    pub mod trusted_proxies;
+}
+
+cfg_feature! {
+    #![feature = "access-log"]
+    pub mod access_log;
+}
+
 cfg_feature! {
     #![feature = "basic-auth"]
     pub mod basic_auth;
@@ -20,6 +30,22 @@ cfg_feature! {
     #![feature = "affix"]
     pub mod affix;
 }
+cfg_feature! {
+    #![feature = "archive"]
+    pub mod archive;
+}
+cfg_feature! {
+    #![feature = "audit"]
+    pub mod audit;
+}
+cfg_feature! {
+    #![feature = "api-key-auth"]
+    pub mod api_key_auth;
+}
+cfg_feature! {
+    #![feature = "authorization"]
+    pub mod authorization;
+}
 
 cfg_feature! {
     #![feature = "force-https"]
@@ -40,10 +66,18 @@ cfg_feature! {
     #![feature = "compression"]
     pub mod compression;
 }
+cfg_feature! {
+    #![feature = "csv"]
+    pub mod csv;
+}
 cfg_feature! {
     #![feature = "logging"]
     pub mod logging;
 }
+cfg_feature! {
+    #![feature = "ndjson"]
+    pub mod ndjson;
+}
 cfg_feature! {
     #![feature = "sse"]
     pub mod sse;
@@ -68,11 +102,95 @@ cfg_feature! {
     #![feature = "timeout"]
     pub mod timeout;
 }
+cfg_feature! {
+    #![feature = "cache-control"]
+    pub mod cache_control;
+}
 cfg_feature! {
     #![feature = "caching-headers"]
     pub mod caching_headers;
 }
+cfg_feature! {
+    #![feature = "redirects"]
+    pub mod redirects;
+}
 cfg_feature! {
     #![feature = "request-id"]
     pub mod request_id;
 }
+cfg_feature! {
+    #![feature = "response-headers"]
+    pub mod response_headers;
+}
+cfg_feature! {
+    #![feature = "rewrite"]
+    pub mod rewrite;
+}
+cfg_feature! {
+    #![feature = "secure-headers"]
+    pub mod secure_headers;
+}
+cfg_feature! {
+    #![feature = "server-timing"]
+    pub mod server_timing;
+}
+cfg_feature! {
+    #![feature = "signed-url"]
+    pub mod signed_url;
+}
+cfg_feature! {
+    #![feature = "health"]
+    pub mod health;
+}
+cfg_feature! {
+    #![feature = "idempotency"]
+    pub mod idempotency;
+}
+cfg_feature! {
+    #![feature = "engine-io"]
+    pub mod engine_io;
+}
+cfg_feature! {
+    #![feature = "maintenance"]
+    pub mod maintenance;
+}
+cfg_feature! {
+    #![feature = "webhook"]
+    pub mod webhook;
+}
+cfg_feature! {
+    #![feature = "feature-flags"]
+    pub mod feature_flags;
+}
+cfg_feature! {
+    #![feature = "http-client"]
+    pub mod http_client;
+}
+cfg_feature! {
+    #![feature = "slow-request"]
+    pub mod slow_request;
+}
+cfg_feature! {
+    #![feature = "firewall"]
+    pub mod firewall;
+}
+cfg_feature! {
+    #![feature = "bot-detection"]
+    pub mod bot_detection;
+}
+cfg_feature! {
+    #![feature = "geoip"]
+    pub mod geoip;
+}
+cfg_feature! {
+    #![feature = "buffer-body"]
+    pub mod buffer_body;
+}
+cfg_feature! {
+    #![feature = "load-shedder"]
+    pub mod load_shedder;
+}
+cfg_feature! {
+    #![feature = "query-filter"]
+    pub mod query_filter;
+}
@@ -0,0 +1,461 @@
+//! Adaptive load shedding middleware.
+//!
+//! [`ConcurrencyLimiter`](crate::concurrency_limiter::ConcurrencyLimiter) has a fixed limit, so when a downstream
+//! service gets slow, requests pile up in its queue and the latency of every request grows. [`LoadShedder`] keeps
+//! the latency bounded under overload:
+//!
+//! * Requests beyond the concurrency limit wait in a bounded queue, requests are rejected at once when the queue is
+//!   full.
+//! * The queue is managed like CoDel: when the time requests spend in the queue stays above `target_delay` for a
+//!   whole `interval`, the server is overloaded, requests leaving the queue late are rejected and the queue timeout
+//!   is cut to `target_delay` until the queue drains.
+//! * If a `target_latency` is set, the concurrency limit adapts to the latency of handlers, it decreases when
+//!   requests are slower than the target and slowly increases back otherwise.
+//!
+//! Rejected requests are responded with `503 Service Unavailable` and the hooks can be used to export metrics.
+//!
+//! Read more: <https://salvo.rs>
+use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::header::{HeaderValue, RETRY_AFTER};
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Why a request is rejected by [`LoadShedder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ShedReason {
+    /// The queue is full.
+    QueueFull,
+    /// The request waited in the queue longer than the queue timeout.
+    QueueTimeout,
+    /// The request left the queue late while the server is overloaded.
+    Overloaded,
+}
+
+/// Snapshot of the state of a [`LoadShedder`], passed to the hooks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LoadStats {
+    /// Number of requests being processed.
+    pub in_flight: usize,
+    /// Number of requests waiting in the queue.
+    pub waiting: usize,
+    /// Current concurrency limit.
+    pub limit: usize,
+    /// Whether the server is considered overloaded.
+    pub overloaded: bool,
+}
+
+type ShedHook = Box<dyn Fn(ShedReason, LoadStats) + Send + Sync>;
+type CompleteHook = Box<dyn Fn(Duration, Duration, LoadStats) + Send + Sync>;
+
+struct State {
+    in_flight: usize,
+    limit: f64,
+    /// When the queue delay will be considered too long for a whole interval.
+    first_above: Option<Instant>,
+    overloaded: bool,
+    last_decrease: Option<Instant>,
+}
+
+/// Middleware which sheds load adaptively by concurrency and latency.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use salvo_core::prelude::*;
+/// use salvo_extra::load_shedder::LoadShedder;
+///
+/// #[handler]
+/// async fn search() -> &'static str {
+///     "results"
+/// }
+///
+/// let shedder = LoadShedder::new(64)
+///     .queue_size(256)
+///     .target_latency(Duration::from_millis(200))
+///     .on_shed(|reason, stats| tracing::warn!(?reason, ?stats, "request shed"));
+/// let router = Router::new().hoop(shedder).push(Router::with_path("search").get(search));
+/// ```
+pub struct LoadShedder {
+    max_concurrency: usize,
+    min_concurrency: usize,
+    queue_size: usize,
+    queue_timeout: Duration,
+    target_delay: Duration,
+    interval: Duration,
+    target_latency: Option<Duration>,
+    retry_after: Option<Duration>,
+    skipper: Box<dyn Skipper>,
+    on_shed: Option<ShedHook>,
+    on_complete: Option<CompleteHook>,
+    state: Mutex<State>,
+    waiting: AtomicUsize,
+    notify: Notify,
+}
+impl Debug for LoadShedder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadShedder")
+            .field("max_concurrency", &self.max_concurrency)
+            .field("min_concurrency", &self.min_concurrency)
+            .field("queue_size", &self.queue_size)
+            .field("queue_timeout", &self.queue_timeout)
+            .field("target_delay", &self.target_delay)
+            .field("interval", &self.interval)
+            .field("target_latency", &self.target_latency)
+            .field("retry_after", &self.retry_after)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+impl LoadShedder {
+    /// Create a new `LoadShedder` with max concurrency.
+    ///
+    /// By default the queue is as large as `max_concurrency`, requests wait at most 1 second, the target queue delay
+    /// is 5 milliseconds, the interval is 100 milliseconds and the concurrency limit does not adapt to latency.
+    #[inline]
+    pub fn new(max_concurrency: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            max_concurrency,
+            min_concurrency: 1,
+            queue_size: max_concurrency,
+            queue_timeout: Duration::from_secs(1),
+            target_delay: Duration::from_millis(5),
+            interval: Duration::from_millis(100),
+            target_latency: None,
+            retry_after: None,
+            skipper: Box::new(none_skipper),
+            on_shed: None,
+            on_complete: None,
+            state: Mutex::new(State {
+                in_flight: 0,
+                limit: max_concurrency as f64,
+                first_above: None,
+                overloaded: false,
+                last_decrease: None,
+            }),
+            waiting: AtomicUsize::new(0),
+            notify: Notify::new(),
+        }
+    }
+    /// Sets max number of requests waiting in the queue.
+    #[inline]
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+    /// Sets max duration a request waits in the queue when the server is not overloaded.
+    #[inline]
+    pub fn queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+    /// Sets acceptable queue delay, requests are rejected when the queue delay stays above it for an interval.
+    #[inline]
+    pub fn target_delay(mut self, target_delay: Duration) -> Self {
+        self.target_delay = target_delay;
+        self
+    }
+    /// Sets the interval the queue delay may stay above `target_delay` before the server is overloaded.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    /// Sets target latency of handlers, the concurrency limit adapts to keep the latency below it.
+    #[inline]
+    pub fn target_latency(mut self, target_latency: Duration) -> Self {
+        self.target_latency = Some(target_latency);
+        self
+    }
+    /// Sets the lowest concurrency limit when the limit adapts to latency, default is 1.
+    #[inline]
+    pub fn min_concurrency(mut self, min_concurrency: usize) -> Self {
+        self.min_concurrency = min_concurrency.clamp(1, self.max_concurrency);
+        self
+    }
+    /// Sets `Retry-After` header of rejected responses.
+    #[inline]
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+    /// Sets skipper, skipped requests are not limited, such as health checks.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+    /// Sets a hook called when a request is rejected.
+    #[inline]
+    pub fn on_shed(mut self, hook: impl Fn(ShedReason, LoadStats) + Send + Sync + 'static) -> Self {
+        self.on_shed = Some(Box::new(hook));
+        self
+    }
+    /// Sets a hook called when a request is completed, with its queue delay and its latency.
+    #[inline]
+    pub fn on_complete(mut self, hook: impl Fn(Duration, Duration, LoadStats) + Send + Sync + 'static) -> Self {
+        self.on_complete = Some(Box::new(hook));
+        self
+    }
+
+    /// Get the current stats.
+    pub fn stats(&self) -> LoadStats {
+        self.stats_of(&self.state())
+    }
+
+    fn stats_of(&self, state: &State) -> LoadStats {
+        LoadStats {
+            in_flight: state.in_flight,
+            waiting: self.waiting.load(Ordering::Acquire),
+            limit: state.limit as usize,
+            overloaded: state.overloaded,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut state = self.state();
+        if state.in_flight < state.limit as usize {
+            state.in_flight += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Update the CoDel state by the queue delay of a request, returns `true` if the request should be rejected.
+    fn check_delay(&self, delay: Duration, now: Instant) -> bool {
+        let mut state = self.state();
+        if delay < self.target_delay {
+            state.first_above = None;
+            state.overloaded = false;
+            return false;
+        }
+        match state.first_above {
+            None => {
+                state.first_above = Some(now + self.interval);
+                false
+            }
+            Some(first_above) if now >= first_above => {
+                state.overloaded = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn acquire(&self) -> Result<Duration, ShedReason> {
+        let enqueued_at = Instant::now();
+        // Requests only bypass the queue when it is empty, so the queue delay is measured fairly.
+        if self.waiting.load(Ordering::Acquire) == 0 && self.try_acquire() {
+            self.check_delay(Duration::ZERO, enqueued_at);
+            return Ok(Duration::ZERO);
+        }
+        let waiting = Waiting::new(&self.waiting);
+        if waiting.position >= self.queue_size {
+            return Err(ShedReason::QueueFull);
+        }
+        let timeout = if self.state().overloaded {
+            self.target_delay
+        } else {
+            self.queue_timeout
+        };
+        let deadline = tokio::time::Instant::from_std(enqueued_at + timeout);
+        loop {
+            if self.try_acquire() {
+                break;
+            }
+            if tokio::time::timeout_at(deadline, self.notify.notified()).await.is_err() {
+                if self.try_acquire() {
+                    break;
+                }
+                return Err(ShedReason::QueueTimeout);
+            }
+        }
+        let now = Instant::now();
+        let delay = now - enqueued_at;
+        if self.check_delay(delay, now) {
+            self.release(None);
+            return Err(ShedReason::Overloaded);
+        }
+        Ok(delay)
+    }
+
+    fn release(&self, latency: Option<Duration>) -> LoadStats {
+        let mut state = self.state();
+        state.in_flight -= 1;
+        if let (Some(target), Some(latency)) = (self.target_latency, latency) {
+            let now = Instant::now();
+            if latency > target {
+                // Decrease at most once per interval, requests started before a decrease should not decrease again.
+                if state.last_decrease.map(|at| now - at >= self.interval).unwrap_or(true) {
+                    state.limit = (state.limit * 0.9).max(self.min_concurrency as f64);
+                    state.last_decrease = Some(now);
+                }
+            } else {
+                state.limit = (state.limit + 1.0 / state.limit).min(self.max_concurrency as f64);
+            }
+        }
+        let stats = self.stats_of(&state);
+        drop(state);
+        self.notify.notify_one();
+        stats
+    }
+}
+
+/// Counts a request in the queue until it leaves.
+struct Waiting<'a> {
+    waiting: &'a AtomicUsize,
+    position: usize,
+}
+impl<'a> Waiting<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        let position = waiting.fetch_add(1, Ordering::AcqRel);
+        Self { waiting, position }
+    }
+}
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.waiting.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Releases the permit of a request even if it is cancelled.
+struct Permit<'a> {
+    shedder: &'a LoadShedder,
+    delay: Duration,
+    started_at: Instant,
+}
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let latency = self.started_at.elapsed();
+        let stats = self.shedder.release(Some(latency));
+        if let Some(hook) = &self.shedder.on_complete {
+            hook(self.delay, latency, stats);
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for LoadShedder {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        match self.acquire().await {
+            Ok(delay) => {
+                let _permit = Permit {
+                    shedder: self,
+                    delay,
+                    started_at: Instant::now(),
+                };
+                ctrl.call_next(req, depot, res).await;
+            }
+            Err(reason) => {
+                let stats = self.stats();
+                tracing::debug!(?reason, ?stats, "request shed");
+                if let Some(hook) = &self.on_shed {
+                    hook(reason, stats);
+                }
+                if let Some(retry_after) = self.retry_after {
+                    res.headers_mut()
+                        .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs().max(1)));
+                }
+                res.render(StatusError::service_unavailable().brief("Server is overloaded."));
+                ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        "done"
+    }
+
+    async fn send_all(service: Arc<Service>, count: usize) -> Vec<StatusCode> {
+        let mut tasks = Vec::new();
+        for _ in 0..count {
+            let service = service.clone();
+            tasks.push(tokio::spawn(async move {
+                TestClient::get("http://127.0.0.1:5801/")
+                    .send(&*service)
+                    .await
+                    .status_code
+                    .unwrap()
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut statuses = Vec::new();
+        for task in tasks {
+            statuses.push(task.await.unwrap());
+        }
+        statuses
+    }
+
+    #[tokio::test]
+    async fn test_load_shedder_queue_full() {
+        let shedder = LoadShedder::new(1).queue_size(1).retry_after(Duration::from_secs(5));
+        let service = Arc::new(Service::new(Router::new().hoop(shedder).get(slow)));
+
+        let statuses = send_all(service.clone(), 3).await;
+        assert_eq!(
+            statuses,
+            vec![StatusCode::OK, StatusCode::OK, StatusCode::SERVICE_UNAVAILABLE]
+        );
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_load_shedder_overloaded() {
+        let shed = Arc::new(Mutex::new(Vec::new()));
+        let shedder = LoadShedder::new(1)
+            .queue_size(10)
+            .target_delay(Duration::from_millis(20))
+            .interval(Duration::from_millis(50))
+            .on_shed({
+                let shed = shed.clone();
+                move |reason, _| shed.lock().unwrap().push(reason)
+            });
+        let service = Arc::new(Service::new(Router::new().hoop(shedder).get(slow)));
+
+        // The second request waits about 90ms and starts the interval, the third and the fourth ones leave the queue
+        // after the interval while the delay is still above the target, so they are rejected.
+        let statuses = send_all(service, 4).await;
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::SERVICE_UNAVAILABLE
+            ]
+        );
+        assert_eq!(shed.lock().unwrap()[0], ShedReason::Overloaded);
+    }
+}
@@ -1,27 +1,54 @@
 //! Simple logging middleware.
 //!
 //! Read more: <https://salvo.rs>
+use std::fmt::{self, Debug, Formatter};
 use std::time::Instant;
 
 use tracing::{Instrument, Level};
 
+use salvo_core::handler::{none_skipper, Skipper};
 use salvo_core::http::{Request, Response, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
 /// A simple logger middleware.
-#[derive(Default, Debug)]
-pub struct Logger {}
+pub struct Logger {
+    skipper: Box<dyn Skipper>,
+}
+impl Default for Logger {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl Logger {
     /// Create new `Logger` middleware.
     #[inline]
     pub fn new() -> Self {
-        Logger {}
+        Logger {
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Sets skipper, requests are not logged when it returns `true`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+impl Debug for Logger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger").finish()
     }
 }
 
 #[async_trait]
 impl Handler for Logger {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
         let span = tracing::span!(
             Level::INFO,
             "Request",
@@ -50,6 +77,7 @@ impl Handler for Logger {
 
 #[cfg(test)]
 mod tests {
+    use salvo_core::handler::RequestSkipper;
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
     use tracing_test::traced_test;
@@ -76,4 +104,26 @@ mod tests {
             .unwrap();
         assert!(logs_contain("duration"));
     }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_log_skipper() {
+        #[handler]
+        async fn healthz() -> &'static str {
+            "ok"
+        }
+
+        let router = Router::new()
+            .hoop(Logger::new().skipper(RequestSkipper::new().path("/healthz")))
+            .push(Router::with_path("healthz").get(healthz));
+
+        let content = TestClient::get("http://127.0.0.1:5801/healthz")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "ok");
+        assert!(!logs_contain("duration"));
+    }
 }
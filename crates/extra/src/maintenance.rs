@@ -0,0 +1,323 @@
+//! Maintenance mode middleware.
+//!
+//! [`MaintenanceMode`] can be switched on and off at runtime. When it is on, all requests except the requests
+//! from allowlisted ips or to allowlisted paths are responded with `503 Service Unavailable` and a
+//! `Retry-After` header, so traffic can be drained during migrations.
+//!
+//! Read more: <https://salvo.rs>
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use salvo_core::http::header::{HeaderValue, RETRY_AFTER};
+use salvo_core::http::{mime, Request, Response, StatusCode, StatusError};
+use salvo_core::writing::{Json, Text};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use serde_json::Value;
+
+struct Inner {
+    enabled: AtomicBool,
+    retry_after: Option<Duration>,
+    allowed_ips: Vec<IpAddr>,
+    allowed_paths: Vec<String>,
+    html: Option<String>,
+    json: Option<Value>,
+}
+
+/// Middleware responds `503 Service Unavailable` to all traffic when maintenance mode is on.
+///
+/// `MaintenanceMode` is cheap to clone, all clones share the same switch, so a clone can be kept to turn
+/// maintenance mode on or off at runtime.
+///
+/// When no custom page is set, the response is rendered by the catcher. A custom HTML page is set by
+/// [`MaintenanceMode::html`] and a custom JSON body is set by [`MaintenanceMode::json`], the JSON body is used
+/// when the client prefers `application/json`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use salvo_core::prelude::*;
+/// use salvo_extra::maintenance::MaintenanceMode;
+///
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "Hello World"
+/// }
+///
+/// let maintenance = MaintenanceMode::new()
+///     .retry_after(Duration::from_secs(300))
+///     .allow_path("/healthz")
+///     .allow_ip([127, 0, 0, 1].into());
+/// let router = Router::new().hoop(maintenance.clone()).get(hello);
+///
+/// // Later, during a migration.
+/// maintenance.enable();
+/// ```
+#[derive(Clone)]
+pub struct MaintenanceMode {
+    inner: Arc<Inner>,
+}
+impl Default for MaintenanceMode {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl std::fmt::Debug for MaintenanceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaintenanceMode")
+            .field("enabled", &self.is_enabled())
+            .field("retry_after", &self.inner.retry_after)
+            .field("allowed_ips", &self.inner.allowed_ips)
+            .field("allowed_paths", &self.inner.allowed_paths)
+            .finish()
+    }
+}
+impl MaintenanceMode {
+    /// Create a new `MaintenanceMode`, it is off by default and `Retry-After` is 60 seconds.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                enabled: AtomicBool::new(false),
+                retry_after: Some(Duration::from_secs(60)),
+                allowed_ips: Vec::new(),
+                allowed_paths: Vec::new(),
+                html: None,
+                json: None,
+            }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("`MaintenanceMode` can not be configured after it is cloned")
+    }
+
+    /// Sets whether maintenance mode is on when it is created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        *self.inner_mut().enabled.get_mut() = enabled;
+        self
+    }
+
+    /// Sets the `Retry-After` header, `None` means the header is not sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn retry_after(mut self, retry_after: impl Into<Option<Duration>>) -> Self {
+        self.inner_mut().retry_after = retry_after.into();
+        self
+    }
+
+    /// Allow requests from the ip when maintenance mode is on.
+    ///
+    /// The ip is matched against the remote address of the connection, behind proxies it is the address of the
+    /// proxy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn allow_ip(mut self, ip: IpAddr) -> Self {
+        self.inner_mut().allowed_ips.push(ip);
+        self
+    }
+
+    /// Allow requests to the path and its sub paths when maintenance mode is on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn allow_path(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        self.inner_mut()
+            .allowed_paths
+            .push(format!("/{}", path.trim_matches('/')));
+        self
+    }
+
+    /// Sets custom HTML page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.inner_mut().html = Some(html.into());
+        self
+    }
+
+    /// Sets custom JSON body.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `MaintenanceMode` has been cloned.
+    #[inline]
+    pub fn json(mut self, json: impl Into<Value>) -> Self {
+        self.inner_mut().json = Some(json.into());
+        self
+    }
+
+    /// Turn maintenance mode on.
+    #[inline]
+    pub fn enable(&self) {
+        self.set_enabled(true);
+    }
+    /// Turn maintenance mode off.
+    #[inline]
+    pub fn disable(&self) {
+        self.set_enabled(false);
+    }
+    /// Sets whether maintenance mode is on.
+    #[inline]
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Release);
+    }
+    /// Returns `true` if maintenance mode is on.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the request is allowed when maintenance mode is on.
+    pub fn is_allowed(&self, req: &Request) -> bool {
+        let path = req.uri().path();
+        if self.inner.allowed_paths.iter().any(|allowed| {
+            allowed == "/"
+                || path
+                    .strip_prefix(allowed.as_str())
+                    .map(|rest| rest.is_empty() || rest.starts_with('/'))
+                    .unwrap_or(false)
+        }) {
+            return true;
+        }
+        if self.inner.allowed_ips.is_empty() {
+            return false;
+        }
+        req.remote_addr()
+            .clone()
+            .into_std()
+            .map(|addr| self.inner.allowed_ips.contains(&addr.ip()))
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait]
+impl Handler for MaintenanceMode {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if !self.is_enabled() || self.is_allowed(req) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+        res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+        if let Some(retry_after) = self.inner.retry_after {
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+        }
+        let prefers_json = req
+            .first_accept()
+            .map(|mime| mime.subtype() == mime::JSON)
+            .unwrap_or(false);
+        match (&self.inner.json, &self.inner.html) {
+            (Some(json), _) if prefers_json || self.inner.html.is_none() => res.render(Json(json)),
+            (_, Some(html)) => res.render(Text::Html(html.clone())),
+            _ => res.render(StatusError::service_unavailable().brief("The service is under maintenance.")),
+        }
+        ctrl.skip_rest();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[handler]
+    async fn hello() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_mode() {
+        let maintenance = MaintenanceMode::new()
+            .retry_after(Duration::from_secs(120))
+            .allow_path("admin/")
+            .json(json!({"message": "maintenance"}));
+        let router = Router::new()
+            .hoop(maintenance.clone())
+            .push(Router::with_path("<**>").get(hello));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        maintenance.enable();
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(res.headers()[RETRY_AFTER], "120");
+        assert_eq!(
+            res.take_json::<Value>().await.unwrap(),
+            json!({"message": "maintenance"})
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5801/admin/users")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let res = TestClient::get("http://127.0.0.1:5801/administrator")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        maintenance.disable();
+        let res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_mode_page() {
+        let maintenance = MaintenanceMode::new()
+            .enabled(true)
+            .retry_after(None)
+            .html("<h1>Maintenance</h1>")
+            .json(json!({"message": "maintenance"}));
+        let service = Service::new(Router::new().hoop(maintenance).get(hello));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("accept", "text/html", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(res.headers().get(RETRY_AFTER).is_none());
+        assert_eq!(res.take_string().await.unwrap(), "<h1>Maintenance</h1>");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("accept", "application/json", true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            res.take_json::<Value>().await.unwrap(),
+            json!({"message": "maintenance"})
+        );
+
+        let maintenance = MaintenanceMode::new().enabled(true);
+        let service = Service::new(Router::new().hoop(maintenance).get(hello));
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(res.headers()[RETRY_AFTER], "60");
+    }
+}
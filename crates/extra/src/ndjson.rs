@@ -0,0 +1,319 @@
+//! Newline-delimited JSON (NDJSON, also known as JSON Lines) streaming.
+//!
+//! [`NdJsonStream`] can be rendered to a response, each item is serialized to one line of JSON, or created from
+//! a request to deserialize the lines of the request body while it is read.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::{stream, StreamExt};
+//! use salvo_core::prelude::*;
+//! use salvo_extra::ndjson::NdJsonStream;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Record {
+//!     id: u64,
+//! }
+//!
+//! #[handler]
+//! async fn export(res: &mut Response) {
+//!     let records = stream::iter((0..1000).map(|id| Ok::<_, std::io::Error>(Record { id })));
+//!     res.render(NdJsonStream::new(records).flush_lines(100));
+//! }
+//!
+//! #[handler]
+//! async fn import(req: &mut Request) -> Result<String, StatusError> {
+//!     let mut records = NdJsonStream::<Record>::from_request(req).map_err(|_| StatusError::bad_request())?;
+//!     let mut count = 0;
+//!     while let Some(record) = records.next().await {
+//!         record.map_err(|_| StatusError::bad_request())?;
+//!         count += 1;
+//!     }
+//!     Ok(format!("imported {count} records"))
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("records").get(export).post(import);
+//!     let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::http::{ParseError, ReqBody, Request, Response};
+use salvo_core::{BoxedError, Scribe};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Content type used when rendering [`NdJsonStream`].
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Default max size of a line when reading newline-delimited JSON, 1MB.
+pub const DEFAULT_MAX_LINE_SIZE: usize = 1024 * 1024;
+
+const ACCEPTED_CONTENT_TYPES: [&str; 4] = [
+    "application/x-ndjson",
+    "application/ndjson",
+    "application/jsonl",
+    "application/x-jsonlines",
+];
+
+/// A stream of values which are written or read as newline-delimited JSON.
+pub struct NdJsonStream<T> {
+    inner: BoxStream<'static, Result<T, ParseError>>,
+    flush_lines: usize,
+}
+
+impl<T> NdJsonStream<T> {
+    /// Create a new `NdJsonStream` from a stream of values, it is used to write a response.
+    pub fn new<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        E: Into<BoxedError> + 'static,
+    {
+        Self {
+            inner: stream.map(|item| item.map_err(ParseError::other)).boxed(),
+            flush_lines: 1,
+        }
+    }
+
+    /// Sets how many lines are collected before they are sent to the client, default is `1`.
+    ///
+    /// Lines are also sent when the source stream is pending, so a slow source never delays a line.
+    pub fn flush_lines(mut self, lines: usize) -> Self {
+        self.flush_lines = lines.max(1);
+        self
+    }
+}
+
+impl<T> NdJsonStream<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    /// Read newline-delimited JSON values from the request body, lines longer than [`DEFAULT_MAX_LINE_SIZE`] are
+    /// rejected.
+    pub fn from_request(req: &mut Request) -> Result<Self, ParseError> {
+        Self::from_request_with_max_line_size(req, DEFAULT_MAX_LINE_SIZE)
+    }
+
+    /// Read newline-delimited JSON values from the request body, lines longer than `max_line_size` are rejected.
+    ///
+    /// The request must have no content type or one of `application/x-ndjson`, `application/ndjson`,
+    /// `application/jsonl` and `application/x-jsonlines`. Empty lines are skipped, and the stream ends after the
+    /// first error.
+    pub fn from_request_with_max_line_size(req: &mut Request, max_line_size: usize) -> Result<Self, ParseError> {
+        if let Some(ctype) = req.content_type() {
+            if !ACCEPTED_CONTENT_TYPES.contains(&ctype.essence_str()) {
+                return Err(ParseError::InvalidContentType);
+            }
+        }
+        Ok(Self::from_body(req.take_body(), max_line_size))
+    }
+
+    /// Read newline-delimited JSON values from a request body.
+    pub fn from_body(body: ReqBody, max_line_size: usize) -> Self {
+        let reader = LineReader {
+            body,
+            buffer: Vec::new(),
+            scanned: 0,
+            max_line_size,
+            finished: false,
+            failed: false,
+        };
+        let inner = stream::unfold(reader, |mut reader| async move {
+            let line = reader.next_line().await?;
+            let item = line.and_then(|line| serde_json::from_slice::<T>(&line).map_err(ParseError::from));
+            if item.is_err() {
+                reader.failed = true;
+            }
+            Some((item, reader))
+        });
+        Self {
+            inner: inner.boxed(),
+            flush_lines: 1,
+        }
+    }
+}
+
+struct LineReader {
+    body: ReqBody,
+    buffer: Vec<u8>,
+    scanned: usize,
+    max_line_size: usize,
+    finished: bool,
+    failed: bool,
+}
+
+impl LineReader {
+    async fn next_line(&mut self) -> Option<Result<Vec<u8>, ParseError>> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            if let Some(pos) = self.buffer[self.scanned..].iter().position(|b| *b == b'\n') {
+                let end = self.scanned + pos;
+                let mut line: Vec<u8> = self.buffer.drain(..=end).collect();
+                self.scanned = 0;
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                if line.len() > self.max_line_size {
+                    self.failed = true;
+                    return Some(Err(line_too_long(self.max_line_size)));
+                }
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                return Some(Ok(line));
+            }
+            self.scanned = self.buffer.len();
+            if self.scanned > self.max_line_size {
+                self.failed = true;
+                return Some(Err(line_too_long(self.max_line_size)));
+            }
+            if self.finished {
+                // The last line may not be terminated by a newline.
+                let line = std::mem::take(&mut self.buffer);
+                self.scanned = 0;
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    return None;
+                }
+                return Some(Ok(line));
+            }
+            match self.body.next().await {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        self.buffer.extend_from_slice(&data);
+                    }
+                }
+                Some(Err(e)) => {
+                    self.failed = true;
+                    return Some(Err(ParseError::Io(e)));
+                }
+                None => {
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+fn line_too_long(max_line_size: usize) -> ParseError {
+    ParseError::other(format!("ndjson line exceeds the max size of {max_line_size} bytes"))
+}
+
+impl<T> Stream for NdJsonStream<T> {
+    type Item = Result<T, ParseError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<T> Debug for NdJsonStream<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NdJsonStream")
+            .field("flush_lines", &self.flush_lines)
+            .finish()
+    }
+}
+
+impl<T> Scribe for NdJsonStream<T>
+where
+    T: Serialize + Send + 'static,
+{
+    fn render(self, res: &mut Response) {
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(NDJSON_CONTENT_TYPE));
+        let lines = self.inner.ready_chunks(self.flush_lines).map(|items| {
+            let mut chunk = Vec::new();
+            for item in items {
+                let item = item.map_err(|e| {
+                    tracing::error!(error = ?e, "ndjson stream item error");
+                    BoxedError::from(e)
+                })?;
+                if let Err(e) = serde_json::to_writer(&mut chunk, &item) {
+                    tracing::error!(error = ?e, "ndjson serialize error");
+                    return Err(BoxedError::from(e));
+                }
+                chunk.push(b'\n');
+            }
+            Ok::<_, BoxedError>(chunk)
+        });
+        res.stream(lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Record {
+        id: u64,
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_render() {
+        #[handler]
+        async fn export(res: &mut Response) {
+            let records = stream::iter((0..3).map(|id| Ok::<_, std::io::Error>(Record { id })));
+            res.render(NdJsonStream::new(records).flush_lines(2));
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .send(Router::new().get(export))
+            .await;
+        assert_eq!(res.headers()[CONTENT_TYPE], NDJSON_CONTENT_TYPE);
+        assert_eq!(res.take_string().await.unwrap(), "{\"id\":0}\n{\"id\":1}\n{\"id\":2}\n");
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_read() {
+        let mut req = TestClient::post("http://127.0.0.1:5800/")
+            .bytes(b"{\"id\":1}\r\n\n{\"id\":2}\n{\"id\":3}".to_vec())
+            .add_header(CONTENT_TYPE, "application/x-ndjson", true)
+            .build();
+        let records: Vec<_> = NdJsonStream::<Record>::from_request(&mut req)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(records, vec![Record { id: 1 }, Record { id: 2 }, Record { id: 3 }]);
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_read_errors() {
+        let mut req = TestClient::post("http://127.0.0.1:5800/")
+            .bytes(b"{\"id\":1}\n{\"id\":12345678}\n{\"id\":3}\n".to_vec())
+            .add_header(CONTENT_TYPE, "application/x-ndjson", true)
+            .build();
+        let records: Vec<_> = NdJsonStream::<Record>::from_request_with_max_line_size(&mut req, 10)
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_ok());
+        assert!(records[1].is_err());
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/")
+            .json(&Record { id: 1 })
+            .build();
+        assert!(matches!(
+            NdJsonStream::<Record>::from_request(&mut req),
+            Err(ParseError::InvalidContentType)
+        ));
+    }
+}
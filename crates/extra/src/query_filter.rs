@@ -0,0 +1,755 @@
+//! Extractor of structured filters from query params.
+//!
+//! [`QueryFilter`] parses a small filter language from the `filter` query param, such as
+//! `filter=age>30 AND name~"jo"`, into a typed [`Filter`] tree. Only the fields listed by the [`FilterSchema`] are
+//! accepted, and every value is checked against the kind of its field, so a handler can translate the tree into a
+//! database query without validating it again.
+//!
+//! # Syntax
+//!
+//! - A condition is a field name, an operator and a value: `age >= 18`.
+//! - Operators are `=`, `!=`, `>`, `>=`, `<`, `<=` and `~` (contains, only for string fields).
+//! - Values are integers, numbers, double quoted strings with `\"` and `\\` escapes, `true` and `false`.
+//! - Conditions are combined by `AND`, `OR` and `NOT`, and grouped by parentheses. `NOT` binds tighter than `AND`,
+//!   which binds tighter than `OR`. Keywords are case insensitive.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::query_filter::{FilterField, FilterSchema, QueryFilter};
+//!
+//! struct UserFilter;
+//! impl FilterSchema for UserFilter {
+//!     const FIELDS: &'static [FilterField] = &[
+//!         FilterField::int("age"),
+//!         FilterField::string("name"),
+//!         FilterField::bool("active"),
+//!     ];
+//! }
+//!
+//! #[handler]
+//! async fn list_users(filter: QueryFilter<UserFilter>) -> String {
+//!     match filter.filter() {
+//!         Some(filter) => format!("users where {filter}"),
+//!         None => "all users".into(),
+//!     }
+//! }
+//!
+//! let router = Router::with_path("users").get(list_users);
+//! ```
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::str::CharIndices;
+
+use salvo_core::async_trait;
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::{ParseError, Request};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer};
+
+/// Kind of the values of a filter field.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum FieldKind {
+    /// Integer values, such as `42`.
+    Int,
+    /// Number values, such as `4.2` and `42`.
+    Float,
+    /// Double quoted string values, such as `"jo"`.
+    String,
+    /// Boolean values, `true` or `false`.
+    Bool,
+}
+impl FieldKind {
+    /// Returns `true` if the operator can be used on fields of this kind.
+    pub fn supports(self, op: FilterOp) -> bool {
+        match self {
+            Self::Int | Self::Float => op != FilterOp::Contains,
+            Self::String => true,
+            Self::Bool => matches!(op, FilterOp::Eq | FilterOp::Ne),
+        }
+    }
+    fn value(self, token: Token) -> Option<FilterValue> {
+        match (self, token) {
+            (Self::Int, Token::Number(n)) => n.parse().ok().map(FilterValue::Int),
+            (Self::Float, Token::Number(n)) => n.parse::<f64>().ok().filter(|n| n.is_finite()).map(FilterValue::Float),
+            (Self::String, Token::Str(s)) => Some(FilterValue::String(s)),
+            (Self::Bool, Token::Ident(s)) if s.eq_ignore_ascii_case("true") => Some(FilterValue::Bool(true)),
+            (Self::Bool, Token::Ident(s)) if s.eq_ignore_ascii_case("false") => Some(FilterValue::Bool(false)),
+            _ => None,
+        }
+    }
+}
+impl Display for FieldKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Int => "integer",
+            Self::Float => "number",
+            Self::String => "string",
+            Self::Bool => "boolean",
+        })
+    }
+}
+
+/// A field which can be filtered.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FilterField {
+    /// Name of the field used in filters.
+    pub name: &'static str,
+    /// Kind of the values of the field.
+    pub kind: FieldKind,
+}
+impl FilterField {
+    /// Create a new `FilterField`.
+    #[inline]
+    pub const fn new(name: &'static str, kind: FieldKind) -> Self {
+        Self { name, kind }
+    }
+    /// Create a new integer field.
+    #[inline]
+    pub const fn int(name: &'static str) -> Self {
+        Self::new(name, FieldKind::Int)
+    }
+    /// Create a new number field.
+    #[inline]
+    pub const fn float(name: &'static str) -> Self {
+        Self::new(name, FieldKind::Float)
+    }
+    /// Create a new string field.
+    #[inline]
+    pub const fn string(name: &'static str) -> Self {
+        Self::new(name, FieldKind::String)
+    }
+    /// Create a new boolean field.
+    #[inline]
+    pub const fn bool(name: &'static str) -> Self {
+        Self::new(name, FieldKind::Bool)
+    }
+}
+
+/// Comparison operator of a [`Condition`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FilterOp {
+    /// `=`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `~`, the field contains the value.
+    Contains,
+}
+impl Display for FilterOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Contains => "~",
+        })
+    }
+}
+
+/// Value of a [`Condition`], its variant always matches the [`FieldKind`] of the field.
+#[derive(Clone, PartialEq, Debug)]
+pub enum FilterValue {
+    /// Value of an integer field.
+    Int(i64),
+    /// Value of a number field.
+    Float(f64),
+    /// Value of a string field.
+    String(String),
+    /// Value of a boolean field.
+    Bool(bool),
+}
+impl Display for FilterValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{n}"),
+            Self::Float(n) => write!(f, "{n:?}"),
+            Self::String(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+/// A comparison of a field with a value, such as `age > 30`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Condition {
+    /// Name of the field, it is always one of the allowed fields.
+    pub field: &'static str,
+    /// Comparison operator.
+    pub op: FilterOp,
+    /// Value compared with.
+    pub value: FilterValue,
+}
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op, self.value)
+    }
+}
+
+/// A parsed filter.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Filter {
+    /// A single condition.
+    Condition(Condition),
+    /// All of the filters match, it has at least two filters.
+    And(Vec<Filter>),
+    /// Any of the filters matches, it has at least two filters.
+    Or(Vec<Filter>),
+    /// The filter does not match.
+    Not(Box<Filter>),
+}
+impl Filter {
+    /// Parse a filter with the allowed fields and the default limits of [`FilterParser`].
+    #[inline]
+    pub fn parse(input: &str, fields: &[FilterField]) -> Result<Self, FilterError> {
+        FilterParser::new(fields).parse(input)
+    }
+
+    /// Evaluate the filter by checking each condition with `check`.
+    ///
+    /// This is useful to filter items in memory, a database backed handler usually translates the tree into a query
+    /// instead.
+    pub fn eval<F>(&self, check: &mut F) -> bool
+    where
+        F: FnMut(&Condition) -> bool,
+    {
+        match self {
+            Self::Condition(condition) => check(condition),
+            Self::And(filters) => filters.iter().all(|filter| filter.eval(check)),
+            Self::Or(filters) => filters.iter().any(|filter| filter.eval(check)),
+            Self::Not(filter) => !filter.eval(check),
+        }
+    }
+
+    /// Returns all conditions in the filter from left to right.
+    pub fn conditions(&self) -> Vec<&Condition> {
+        let mut conditions = Vec::new();
+        self.collect_conditions(&mut conditions);
+        conditions
+    }
+    fn collect_conditions<'a>(&'a self, conditions: &mut Vec<&'a Condition>) {
+        match self {
+            Self::Condition(condition) => conditions.push(condition),
+            Self::And(filters) | Self::Or(filters) => {
+                for filter in filters {
+                    filter.collect_conditions(conditions);
+                }
+            }
+            Self::Not(filter) => filter.collect_conditions(conditions),
+        }
+    }
+}
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Condition(condition) => Display::fmt(condition, f),
+            Self::And(filters) => {
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" AND ")?;
+                    }
+                    if matches!(filter, Self::Or(_)) {
+                        write!(f, "({filter})")?;
+                    } else {
+                        Display::fmt(filter, f)?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Or(filters) => {
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" OR ")?;
+                    }
+                    Display::fmt(filter, f)?;
+                }
+                Ok(())
+            }
+            Self::Not(filter) => match **filter {
+                Self::Condition(_) | Self::Not(_) => write!(f, "NOT {filter}"),
+                _ => write!(f, "NOT ({filter})"),
+            },
+        }
+    }
+}
+
+/// An error of a filter which can not be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FilterError {
+    /// Byte offset in the filter where the error is found.
+    pub position: usize,
+    /// Description of the error.
+    pub message: String,
+}
+impl FilterError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+impl Display for FilterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter at {}: {}", self.position, self.message)
+    }
+}
+impl StdError for FilterError {}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(String),
+    Op(FilterOp),
+    LParen,
+    RParen,
+}
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(s) | Self::Number(s) => f.write_str(s),
+            Self::Str(s) => write!(f, "\"{s}\""),
+            Self::Op(op) => Display::fmt(op, f),
+            Self::LParen => f.write_str("("),
+            Self::RParen => f.write_str(")"),
+        }
+    }
+}
+
+fn take_while(chars: &mut Peekable<CharIndices<'_>>, value: &mut String, f: impl Fn(char) -> bool) {
+    while let Some((_, c)) = chars.next_if(|&(_, c)| f(c)) {
+        value.push(c);
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\'))) => value.push(c),
+                            Some((pos, _)) => return Err(FilterError::new(pos, "invalid escape in string")),
+                            None => return Err(FilterError::new(input.len(), "unterminated string")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(FilterError::new(pos, "unterminated string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            '=' | '!' | '>' | '<' | '~' => {
+                let eq = chars.next_if(|&(_, c)| c == '=').is_some();
+                Token::Op(match (c, eq) {
+                    // `==` is accepted as `=`.
+                    ('=', _) => FilterOp::Eq,
+                    ('!', true) => FilterOp::Ne,
+                    ('>', false) => FilterOp::Gt,
+                    ('>', true) => FilterOp::Ge,
+                    ('<', false) => FilterOp::Lt,
+                    ('<', true) => FilterOp::Le,
+                    ('~', false) => FilterOp::Contains,
+                    _ => return Err(FilterError::new(pos, "invalid operator")),
+                })
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut value = c.to_string();
+                take_while(&mut chars, &mut value, |c| c.is_ascii_digit() || c == '.');
+                Token::Number(value)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut value = c.to_string();
+                take_while(&mut chars, &mut value, |c| c.is_alphanumeric() || c == '_' || c == '.');
+                Token::Ident(value)
+            }
+            c => return Err(FilterError::new(pos, format!("unexpected character `{c}`"))),
+        };
+        tokens.push((pos, token));
+    }
+    Ok(tokens)
+}
+
+/// Parser of filters, with the allowed fields and the limits of the input.
+#[derive(Clone, Copy, Debug)]
+pub struct FilterParser<'a> {
+    fields: &'a [FilterField],
+    max_length: usize,
+    max_depth: usize,
+}
+impl<'a> FilterParser<'a> {
+    /// Create a new `FilterParser` which only accepts the given fields.
+    #[inline]
+    pub fn new(fields: &'a [FilterField]) -> Self {
+        Self {
+            fields,
+            max_length: 1024,
+            max_depth: 8,
+        }
+    }
+    /// Sets the max length of the filter in bytes, default is `1024`.
+    #[inline]
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+    /// Sets the max nesting depth of `NOT` and parentheses, default is `8`.
+    #[inline]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Parse a filter.
+    pub fn parse(&self, input: &str) -> Result<Filter, FilterError> {
+        if input.len() > self.max_length {
+            return Err(FilterError::new(
+                self.max_length,
+                format!("filter is longer than {} bytes", self.max_length),
+            ));
+        }
+        let mut state = ParseState {
+            parser: self,
+            tokens: tokenize(input)?.into_iter().peekable(),
+            end: input.len(),
+        };
+        let filter = state.parse_or(0)?;
+        match state.tokens.next() {
+            None => Ok(filter),
+            Some((pos, token)) => Err(FilterError::new(pos, format!("unexpected `{token}`"))),
+        }
+    }
+}
+
+struct ParseState<'p, 'a> {
+    parser: &'p FilterParser<'a>,
+    tokens: Peekable<std::vec::IntoIter<(usize, Token)>>,
+    end: usize,
+}
+impl ParseState<'_, '_> {
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.tokens
+            .next_if(|(_, token)| matches!(token, Token::Ident(s) if s.eq_ignore_ascii_case(keyword)))
+            .is_some()
+    }
+    fn next(&mut self, expected: &str) -> Result<(usize, Token), FilterError> {
+        self.tokens
+            .next()
+            .ok_or_else(|| FilterError::new(self.end, format!("expected {expected}, found end of filter")))
+    }
+
+    fn parse_or(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.parse_and(depth)?];
+        while self.eat_keyword("OR") {
+            filters.push(self.parse_and(depth)?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::Or(filters)
+        })
+    }
+    fn parse_and(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.parse_unary(depth)?];
+        while self.eat_keyword("AND") {
+            filters.push(self.parse_unary(depth)?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::And(filters)
+        })
+    }
+    fn parse_unary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if depth > self.parser.max_depth {
+            let pos = self.tokens.peek().map(|(pos, _)| *pos).unwrap_or(self.end);
+            return Err(FilterError::new(pos, "filter is nested too deeply"));
+        }
+        if self.eat_keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.parse_unary(depth + 1)?)));
+        }
+        if self.tokens.next_if(|(_, token)| *token == Token::LParen).is_some() {
+            let filter = self.parse_or(depth + 1)?;
+            return match self.next("`)`")? {
+                (_, Token::RParen) => Ok(filter),
+                (pos, token) => Err(FilterError::new(pos, format!("expected `)`, found `{token}`"))),
+            };
+        }
+        self.parse_condition()
+    }
+    fn parse_condition(&mut self) -> Result<Filter, FilterError> {
+        let field = match self.next("a field name")? {
+            (pos, Token::Ident(name)) => self
+                .parser
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .ok_or_else(|| FilterError::new(pos, format!("field `{name}` can not be filtered")))?,
+            (pos, token) => return Err(FilterError::new(pos, format!("expected a field name, found `{token}`"))),
+        };
+        let op = match self.next("an operator")? {
+            (pos, Token::Op(op)) if !field.kind.supports(op) => {
+                return Err(FilterError::new(
+                    pos,
+                    format!(
+                        "operator `{op}` can not be used on {} field `{}`",
+                        field.kind, field.name
+                    ),
+                ));
+            }
+            (_, Token::Op(op)) => op,
+            (pos, token) => return Err(FilterError::new(pos, format!("expected an operator, found `{token}`"))),
+        };
+        let (pos, token) = self.next("a value")?;
+        let value = field.kind.value(token).ok_or_else(|| {
+            FilterError::new(pos, format!("expected {} value for field `{}`", field.kind, field.name))
+        })?;
+        Ok(Filter::Condition(Condition {
+            field: field.name,
+            op,
+            value,
+        }))
+    }
+}
+
+/// Allowed fields and limits of a [`QueryFilter`].
+pub trait FilterSchema: Send + 'static {
+    /// Fields which can be filtered.
+    const FIELDS: &'static [FilterField];
+    /// Name of the query param, default is `filter`.
+    const PARAM: &'static str = "filter";
+    /// Max length of the filter in bytes, default is `1024`.
+    const MAX_LENGTH: usize = 1024;
+    /// Max nesting depth of `NOT` and parentheses, default is `8`.
+    const MAX_DEPTH: usize = 8;
+
+    /// Parse a filter with the fields and limits of this schema.
+    fn parse(input: &str) -> Result<Filter, FilterError> {
+        FilterParser::new(Self::FIELDS)
+            .max_length(Self::MAX_LENGTH)
+            .max_depth(Self::MAX_DEPTH)
+            .parse(input)
+    }
+}
+
+/// Filter extracted from the query param of the [`FilterSchema`].
+///
+/// The filter is `None` if the query param is missing or blank. Requests with an invalid filter are rejected with
+/// `400 Bad Request`.
+pub struct QueryFilter<S> {
+    filter: Option<Filter>,
+    _schema: PhantomData<fn() -> S>,
+}
+impl<S> QueryFilter<S> {
+    /// Create a new `QueryFilter` with the parsed filter.
+    #[inline]
+    pub fn new(filter: Option<Filter>) -> Self {
+        Self {
+            filter,
+            _schema: PhantomData,
+        }
+    }
+    /// Get the filter.
+    #[inline]
+    pub fn filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+    /// Consumes self and returns the filter.
+    #[inline]
+    pub fn into_inner(self) -> Option<Filter> {
+        self.filter
+    }
+}
+impl<S: FilterSchema> QueryFilter<S> {
+    /// Parse the filter, a blank filter is parsed as `None`.
+    pub fn parse(input: &str) -> Result<Self, FilterError> {
+        if input.trim().is_empty() {
+            return Ok(Self::new(None));
+        }
+        S::parse(input).map(|filter| Self::new(Some(filter)))
+    }
+}
+impl<S> Debug for QueryFilter<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryFilter").field("filter", &self.filter).finish()
+    }
+}
+
+#[async_trait]
+impl<'de, S> Extractible<'de> for QueryFilter<S>
+where
+    S: FilterSchema,
+{
+    fn metadata() -> &'de Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    async fn extract(req: &'de mut Request) -> Result<Self, ParseError> {
+        match req.queries().get(S::PARAM) {
+            Some(input) => Self::parse(input).map_err(ParseError::other),
+            None => Ok(Self::new(None)),
+        }
+    }
+}
+
+impl<'de, S> Deserialize<'de> for QueryFilter<S>
+where
+    S: FilterSchema,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(input) => Self::parse(&input).map_err(D::Error::custom),
+            None => Ok(Self::new(None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    struct UserFilter;
+    impl FilterSchema for UserFilter {
+        const FIELDS: &'static [FilterField] = &[
+            FilterField::int("age"),
+            FilterField::float("score"),
+            FilterField::string("name"),
+            FilterField::bool("active"),
+        ];
+        const MAX_DEPTH: usize = 2;
+    }
+
+    fn condition(field: &'static str, op: FilterOp, value: FilterValue) -> Filter {
+        Filter::Condition(Condition { field, op, value })
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        let filter = UserFilter::parse(r#"age>30 AND name~"jo" OR NOT (active = false or score <= -1.5)"#).unwrap();
+        assert_eq!(
+            filter,
+            Filter::Or(vec![
+                Filter::And(vec![
+                    condition("age", FilterOp::Gt, FilterValue::Int(30)),
+                    condition("name", FilterOp::Contains, FilterValue::String("jo".into())),
+                ]),
+                Filter::Not(Box::new(Filter::Or(vec![
+                    condition("active", FilterOp::Eq, FilterValue::Bool(false)),
+                    condition("score", FilterOp::Le, FilterValue::Float(-1.5)),
+                ]))),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            r#"age > 30 AND name ~ "jo" OR NOT (active = false OR score <= -1.5)"#
+        );
+        assert_eq!(UserFilter::parse(&filter.to_string()).unwrap(), filter);
+        assert_eq!(filter.conditions().len(), 4);
+
+        let filter = UserFilter::parse("age > 30 and not age >= 40").unwrap();
+        let matched: Vec<_> = [25, 35, 45]
+            .into_iter()
+            .filter(|age| {
+                filter.eval(&mut |condition| match (condition.op, &condition.value) {
+                    (FilterOp::Gt, FilterValue::Int(n)) => age > n,
+                    (FilterOp::Ge, FilterValue::Int(n)) => age >= n,
+                    _ => false,
+                })
+            })
+            .collect();
+        assert_eq!(matched, vec![35]);
+
+        let filter = UserFilter::parse(r#"name = "say \"hi\"""#).unwrap();
+        assert_eq!(
+            filter,
+            condition("name", FilterOp::Eq, FilterValue::String("say \"hi\"".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_errors() {
+        for (input, position) in [
+            ("password = \"x\"", 0),
+            ("age > \"30\"", 6),
+            ("age > 3.5", 6),
+            ("age ~ 3", 4),
+            ("active > true", 7),
+            ("name = \"jo", 7),
+            ("age > 30 AND", 12),
+            ("age > 30 name = \"jo\"", 9),
+            ("(age > 30", 9),
+            ("age # 30", 4),
+            ("NOT NOT NOT age > 30", 12),
+        ] {
+            let error = UserFilter::parse(input).unwrap_err();
+            assert_eq!(error.position, position, "{input}: {error}");
+        }
+        let error = FilterParser::new(UserFilter::FIELDS)
+            .max_length(8)
+            .parse("age > 30 AND age < 40")
+            .unwrap_err();
+        assert_eq!(error.position, 8);
+    }
+
+    #[tokio::test]
+    async fn test_query_filter_extract() {
+        #[handler]
+        async fn list_users(filter: QueryFilter<UserFilter>) -> String {
+            match filter.filter() {
+                Some(filter) => filter.to_string(),
+                None => "all".into(),
+            }
+        }
+
+        let service = Service::new(Router::new().get(list_users));
+        let content = TestClient::get("http://127.0.0.1:5800/?filter=age%3E30%20AND%20name~%22jo%22")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, r#"age > 30 AND name ~ "jo""#);
+
+        let content = TestClient::get("http://127.0.0.1:5800/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "all");
+
+        let res = TestClient::get("http://127.0.0.1:5800/?filter=password%3D%22x%22")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,574 @@
+//! Redirect mapping middleware.
+//!
+//! [`Redirects`] redirects requests by a table of rules, such as `/blog/<slug>` to
+//! `https://blog.example.com/<slug>`, so a large legacy URL migration does not need a route for every old URL.
+//! Requests which match no rule are passed to the next handlers.
+//!
+//! Rules can be loaded from a file and reloaded at runtime, see [`Redirects::from_file`].
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::redirects::{RedirectRule, Redirects};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! let redirects = Redirects::new()
+//!     .rule(RedirectRule::new("/blog/<slug>", "https://blog.example.com/<slug>"))
+//!     .rule(RedirectRule::new("/docs/<**rest>", "/manual/<rest>").status(StatusCode::TEMPORARY_REDIRECT));
+//! let router = Router::new().hoop(redirects).get(hello);
+//! ```
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::writing::Redirect;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
+use serde::Deserialize;
+
+/// A rule which redirects requests matching a path pattern to a target.
+///
+/// The pattern is matched against the request path segment by segment. `<name>` matches one segment and
+/// `<**name>` matches all the remaining segments, it must be the last segment of the pattern. Captured values
+/// are substituted for placeholders with the same name in the target.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RedirectRule {
+    /// Path pattern, such as `/blog/<slug>`.
+    pub from: String,
+    /// Target of the redirect, such as `https://blog.example.com/<slug>`.
+    pub to: String,
+    /// Status code of the redirect, default is `301 Moved Permanently`.
+    pub status: StatusCode,
+}
+impl RedirectRule {
+    /// Create a new `RedirectRule` with status `301 Moved Permanently`.
+    #[inline]
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            status: StatusCode::MOVED_PERMANENTLY,
+        }
+    }
+
+    /// Sets the status code, it must be one of `301`, `302`, `303`, `307` and `308`.
+    #[inline]
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest(String),
+}
+
+#[derive(Clone, Debug)]
+struct CompiledRule {
+    segments: Vec<Segment>,
+    to: String,
+    status: StatusCode,
+}
+impl CompiledRule {
+    fn compile(rule: RedirectRule) -> Result<Self, Error> {
+        if !matches!(rule.status.as_u16(), 301 | 302 | 303 | 307 | 308) {
+            return Err(Error::other(format!("invalid redirect status `{}`", rule.status)));
+        }
+        let parts = split_path(&rule.from);
+        let mut segments = Vec::with_capacity(parts.len());
+        for (i, part) in parts.iter().enumerate() {
+            let segment = match part.strip_prefix('<').and_then(|p| p.strip_suffix('>')) {
+                Some(name) => match name.strip_prefix("**") {
+                    Some(name) if i == parts.len() - 1 => Segment::Rest(name.to_owned()),
+                    Some(_) => {
+                        return Err(Error::other(format!(
+                            "rest segment must be the last segment in `{}`",
+                            rule.from
+                        )))
+                    }
+                    None => Segment::Param(name.to_owned()),
+                },
+                None => Segment::Literal((*part).to_owned()),
+            };
+            if let Segment::Param(name) | Segment::Rest(name) = &segment {
+                if name.is_empty() {
+                    return Err(Error::other(format!("empty param name in `{}`", rule.from)));
+                }
+            }
+            segments.push(segment);
+        }
+        let compiled = Self {
+            segments,
+            to: rule.to,
+            status: rule.status,
+        };
+        let mut missing = None;
+        compiled.render(|name| {
+            let known = compiled
+                .segments
+                .iter()
+                .any(|s| matches!(s, Segment::Param(n) | Segment::Rest(n) if n == name));
+            if !known && missing.is_none() {
+                missing = Some(name.to_owned());
+            }
+            Some("")
+        });
+        if let Some(name) = missing {
+            return Err(Error::other(format!("unknown param `{name}` in `{}`", compiled.to)));
+        }
+        Ok(compiled)
+    }
+
+    fn is_exact(&self) -> bool {
+        self.segments.iter().all(|s| matches!(s, Segment::Literal(_)))
+    }
+
+    fn capture(&self, parts: &[&str]) -> Option<Vec<(&str, String)>> {
+        let mut captures = Vec::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Literal(literal) => {
+                    if parts.get(i) != Some(&literal.as_str()) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => captures.push((name.as_str(), (*parts.get(i)?).to_owned())),
+                Segment::Rest(name) => {
+                    captures.push((name.as_str(), parts[i..].join("/")));
+                    return Some(captures);
+                }
+            }
+        }
+        (parts.len() == self.segments.len()).then_some(captures)
+    }
+
+    /// Render the target, placeholders `<name>` and `<**name>` are replaced by `value(name)`.
+    fn render<'a>(&self, mut value: impl FnMut(&str) -> Option<&'a str>) -> String {
+        let mut target = String::with_capacity(self.to.len());
+        let mut rest = self.to.as_str();
+        while let Some(start) = rest.find('<') {
+            let Some(end) = rest[start..].find('>') else {
+                break;
+            };
+            let name = rest[start + 1..start + end].trim_start_matches("**");
+            target.push_str(&rest[..start]);
+            match value(name) {
+                Some(v) => target.push_str(v),
+                None => target.push_str(&rest[start..=start + end]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        target.push_str(rest);
+        target
+    }
+}
+
+#[derive(Default, Debug)]
+struct RuleTable {
+    exact: HashMap<String, CompiledRule>,
+    patterns: Vec<CompiledRule>,
+}
+impl RuleTable {
+    fn insert(&mut self, rule: CompiledRule) {
+        if rule.is_exact() {
+            let key = rule
+                .segments
+                .iter()
+                .map(|s| match s {
+                    Segment::Literal(literal) => literal.as_str(),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            self.exact.entry(key).or_insert(rule);
+        } else {
+            self.patterns.push(rule);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.exact.len() + self.patterns.len()
+    }
+
+    fn find(&self, path: &str) -> Option<(String, StatusCode)> {
+        let parts = split_path(path);
+        if let Some(rule) = self.exact.get(&parts.join("/")) {
+            return Some((rule.render(|_| None), rule.status));
+        }
+        self.patterns.iter().find_map(|rule| {
+            let captures = rule.capture(&parts)?;
+            let target = rule.render(|name| captures.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str()));
+            Some((target, rule.status))
+        })
+    }
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.split('/').filter(|p| !p.is_empty()).collect()
+}
+
+#[derive(Deserialize)]
+struct RuleConfig {
+    from: String,
+    to: String,
+    #[serde(default = "default_status")]
+    status: u16,
+}
+fn default_status() -> u16 {
+    301
+}
+
+/// Parse rules from the content of a rules file.
+///
+/// Files with `json` extension contain an array of objects with `from`, `to` and optional `status` fields,
+/// other files contain one rule per line, such as `/blog/<slug> https://blog.example.com/<slug> 308`, the status is
+/// optional, empty lines and lines starting with `#` are ignored.
+fn parse_rules(path: &Path, content: &str) -> Result<Vec<RedirectRule>, Error> {
+    let configs = if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+        serde_json::from_str::<Vec<RuleConfig>>(content)
+            .map_err(|e| Error::other(format!("invalid redirect rules in `{}`: {e}", path.display())))?
+    } else {
+        let mut configs = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let status = match fields.get(2) {
+                Some(status) => status.parse().ok(),
+                None => Some(default_status()),
+            };
+            match (fields.len(), status) {
+                (2 | 3, Some(status)) => configs.push(RuleConfig {
+                    from: fields[0].to_owned(),
+                    to: fields[1].to_owned(),
+                    status,
+                }),
+                _ => {
+                    return Err(Error::other(format!(
+                        "invalid redirect rule at line {} in `{}`",
+                        i + 1,
+                        path.display()
+                    )))
+                }
+            }
+        }
+        configs
+    };
+    configs
+        .into_iter()
+        .map(|config| {
+            let status = StatusCode::from_u16(config.status).map_err(Error::other)?;
+            Ok(RedirectRule::new(config.from, config.to).status(status))
+        })
+        .collect()
+}
+
+struct Inner {
+    table: RwLock<Arc<RuleTable>>,
+    file: Option<PathBuf>,
+    reload_interval: Option<Duration>,
+    /// Time of the last check and modified time of the file when it was loaded.
+    reload_state: Mutex<(Instant, Option<SystemTime>)>,
+    preserve_query: bool,
+}
+
+/// Middleware redirects requests by a table of [`RedirectRule`]s.
+///
+/// Rules without params are looked up by path first, then the other rules are checked in the order they are
+/// added, the first matched rule is used.
+///
+/// `Redirects` is cheap to clone, all clones share the same rules, so a clone can be kept to reload rules at
+/// runtime.
+#[derive(Clone)]
+pub struct Redirects {
+    inner: Arc<Inner>,
+}
+impl Default for Redirects {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for Redirects {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redirects")
+            .field("rules", &self.len())
+            .field("file", &self.inner.file)
+            .field("reload_interval", &self.inner.reload_interval)
+            .field("preserve_query", &self.inner.preserve_query)
+            .finish()
+    }
+}
+impl Redirects {
+    /// Create a new `Redirects` without any rule.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                table: RwLock::new(Arc::new(RuleTable::default())),
+                file: None,
+                reload_interval: None,
+                reload_state: Mutex::new((Instant::now(), None)),
+                preserve_query: true,
+            }),
+        }
+    }
+
+    /// Create a new `Redirects` with rules loaded from a file.
+    ///
+    /// Files with `json` extension contain an array of objects with `from`, `to` and optional `status` fields:
+    ///
+    /// ```json
+    /// [{ "from": "/blog/<slug>", "to": "https://blog.example.com/<slug>", "status": 308 }]
+    /// ```
+    ///
+    /// Other files contain one rule per line, the pattern, the target and an optional status are separated by
+    /// whitespaces, empty lines and lines starting with `#` are ignored:
+    ///
+    /// ```text
+    /// # Blog is moved to its own domain.
+    /// /blog/<slug> https://blog.example.com/<slug> 308
+    /// /about /company
+    /// ```
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let mut redirects = Self::new();
+        let inner = redirects.inner_mut();
+        inner.file = Some(path.into());
+        redirects.reload()?;
+        Ok(redirects)
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("`Redirects` can not be configured after it is cloned")
+    }
+
+    /// Add a rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule is invalid or this `Redirects` has been cloned.
+    pub fn rule(mut self, rule: RedirectRule) -> Self {
+        let rule = CompiledRule::compile(rule).expect("invalid redirect rule");
+        let table = self.inner_mut().table.get_mut().unwrap();
+        Arc::get_mut(table)
+            .expect("`Redirects` can not be configured after it is cloned")
+            .insert(rule);
+        self
+    }
+
+    /// Sets how often the rules file is checked for changes, it is reloaded when its modified time changes.
+    ///
+    /// The file is checked when a request is handled, if it can not be loaded, the error is logged and the
+    /// previous rules are kept. Without this, the file is only reloaded by [`Redirects::reload`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Redirects` has been cloned.
+    #[inline]
+    pub fn reload_interval(mut self, interval: Duration) -> Self {
+        self.inner_mut().reload_interval = Some(interval);
+        self
+    }
+
+    /// Sets whether the query of the request is appended to the target, default is `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Redirects` has been cloned.
+    #[inline]
+    pub fn preserve_query(mut self, preserve_query: bool) -> Self {
+        self.inner_mut().preserve_query = preserve_query;
+        self
+    }
+
+    /// Get the number of rules.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.table.read().unwrap().len()
+    }
+
+    /// Returns `true` if there is no rule.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reload rules from the file, the rules are replaced only if all of them are valid.
+    ///
+    /// It does nothing if this `Redirects` is not created by [`Redirects::from_file`].
+    pub fn reload(&self) -> Result<(), Error> {
+        let Some(path) = &self.inner.file else {
+            return Ok(());
+        };
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        let content = std::fs::read_to_string(path)?;
+        let mut table = RuleTable::default();
+        for rule in parse_rules(path, &content)? {
+            table.insert(CompiledRule::compile(rule)?);
+        }
+        *self.inner.table.write().unwrap() = Arc::new(table);
+        *self.inner.reload_state.lock().unwrap() = (Instant::now(), modified);
+        Ok(())
+    }
+
+    fn reload_if_changed(&self) {
+        let (Some(path), Some(interval)) = (&self.inner.file, self.inner.reload_interval) else {
+            return;
+        };
+        let loaded_modified = {
+            let mut state = self.inner.reload_state.lock().unwrap();
+            if state.0.elapsed() < interval {
+                return;
+            }
+            state.0 = Instant::now();
+            state.1
+        };
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        if modified != loaded_modified {
+            if let Err(e) = self.reload() {
+                tracing::error!(error = ?e, path = %path.display(), "reload redirect rules failed");
+            }
+        }
+    }
+
+    /// Find the target and status code of the redirect for a path.
+    pub fn find(&self, path: &str) -> Option<(String, StatusCode)> {
+        let table = self.inner.table.read().unwrap().clone();
+        table.find(path)
+    }
+}
+
+#[async_trait]
+impl Handler for Redirects {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        self.reload_if_changed();
+        let Some((mut target, status)) = self.find(req.uri().path()) else {
+            return;
+        };
+        if self.inner.preserve_query {
+            if let Some(query) = req.uri().query() {
+                target.push(if target.contains('?') { '&' } else { '?' });
+                target.push_str(query);
+            }
+        }
+        match Redirect::with_status_code(status, target.as_str()) {
+            Ok(redirect) => {
+                res.render(redirect);
+                ctrl.skip_rest();
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, %target, "invalid redirect target");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::LOCATION;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let redirects = Redirects::new()
+            .rule(RedirectRule::new("/blog/<slug>", "https://blog.example.com/<slug>"))
+            .rule(RedirectRule::new("/docs/<**rest>", "/manual/<rest>").status(StatusCode::PERMANENT_REDIRECT))
+            .rule(RedirectRule::new("/about/", "/company"));
+        assert_eq!(redirects.len(), 3);
+        assert_eq!(
+            redirects.find("/blog/hello-world"),
+            Some((
+                "https://blog.example.com/hello-world".to_owned(),
+                StatusCode::MOVED_PERMANENTLY
+            ))
+        );
+        assert_eq!(redirects.find("/blog/a/b"), None);
+        assert_eq!(
+            redirects.find("/docs/guide/intro"),
+            Some(("/manual/guide/intro".to_owned(), StatusCode::PERMANENT_REDIRECT))
+        );
+        assert_eq!(
+            redirects.find("/docs"),
+            Some(("/manual/".to_owned(), StatusCode::PERMANENT_REDIRECT))
+        );
+        assert_eq!(
+            redirects.find("/about"),
+            Some(("/company".to_owned(), StatusCode::MOVED_PERMANENTLY))
+        );
+
+        assert!(CompiledRule::compile(RedirectRule::new("/a/<id>", "/b/<name>")).is_err());
+        assert!(CompiledRule::compile(RedirectRule::new("/a/<**rest>/b", "/b")).is_err());
+        assert!(CompiledRule::compile(RedirectRule::new("/a", "/b").status(StatusCode::OK)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redirects() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+
+        let redirects = Redirects::new().rule(RedirectRule::new("/old/<id>", "/new/<id>?from=old"));
+        let service = Service::new(Router::new().hoop(redirects).push(Router::with_path("<**>").get(hello)));
+
+        let res = TestClient::get("http://127.0.0.1:5801/old/12?page=2")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[LOCATION], "/new/12?from=old&page=2");
+
+        let content = TestClient::get("http://127.0.0.1:5801/other")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    #[test]
+    fn test_reload_from_file() {
+        let path = std::env::temp_dir().join(format!("salvo-redirects-{}.txt", std::process::id()));
+        std::fs::write(&path, "# legacy urls\n/a /b\n/c/<id> /d/<id> 307\n").unwrap();
+        let redirects = Redirects::from_file(&path).unwrap();
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(
+            redirects.find("/c/1"),
+            Some(("/d/1".to_owned(), StatusCode::TEMPORARY_REDIRECT))
+        );
+
+        std::fs::write(&path, "/a /e\n/bad\n").unwrap();
+        assert!(redirects.reload().is_err());
+        assert_eq!(redirects.len(), 2);
+
+        std::fs::write(&path, "/a /e\n").unwrap();
+        redirects.reload().unwrap();
+        assert_eq!(
+            redirects.find("/a"),
+            Some(("/e".to_owned(), StatusCode::MOVED_PERMANENTLY))
+        );
+        assert_eq!(redirects.find("/c/1"), None);
+        std::fs::remove_file(&path).ok();
+
+        let path = std::env::temp_dir().join(format!("salvo-redirects-{}.json", std::process::id()));
+        std::fs::write(&path, r#"[{"from": "/x/<id>", "to": "/y/<id>", "status": 302}]"#).unwrap();
+        let redirects = Redirects::from_file(&path).unwrap();
+        assert_eq!(redirects.find("/x/1"), Some(("/y/1".to_owned(), StatusCode::FOUND)));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -3,13 +3,35 @@
 //! Read more: <https://salvo.rs>
 use ulid::Ulid;
 
-use salvo_core::http::{header::HeaderName, Request, Response};
+use salvo_core::http::header::{HeaderName, HeaderValue};
+use salvo_core::http::{Request, Response};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
-/// Key for incoming flash messages in depot.
+/// Key for request id in depot.
 pub const REQUST_ID_KEY: &str = "::salvo::request_id";
 
+/// Validator for incoming request id.
+pub type IdValidator = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
 /// A middleware for generate request id.
+///
+/// The request id is stored in depot, and set to both request and response headers, so it can be used by
+/// `AccessLog` middleware, and it is rendered in the default error pages.
+///
+/// If `overwrite` is `false`, the incoming request id is kept if it is valid, otherwise a new one is generated.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::request_id::{RequestId, RequestIdDepotExt};
+///
+/// #[handler]
+/// async fn hello(depot: &mut Depot) -> String {
+///     format!("request id: {}", depot.request_id().unwrap_or_default())
+/// }
+/// let router = Router::new().hoop(RequestId::new().overwrite(false)).get(hello);
+/// ```
 #[non_exhaustive]
 pub struct RequestId {
     /// The header name for request id.
@@ -18,15 +40,18 @@ pub struct RequestId {
     pub overwrite: bool,
     /// The generator for request id.
     pub generator: Box<dyn IdGenerator + Send + Sync>,
+    /// The validator for incoming request id.
+    pub validator: IdValidator,
 }
 
 impl RequestId {
-    /// Create new `CatchPanic` middleware.
+    /// Create new `RequestId` middleware.
     pub fn new() -> Self {
         Self {
             header_name: HeaderName::from_static("x-request-id"),
             overwrite: true,
             generator: Box::new(UlidGenerator::new()),
+            validator: Box::new(is_valid_id),
         }
     }
 
@@ -47,6 +72,14 @@ impl RequestId {
         self.generator = Box::new(generator);
         self
     }
+
+    /// Set the validator for incoming request id.
+    ///
+    /// The default validator accepts 1 to 128 characters of ASCII letters, digits, `-`, `_`, `.` and `:`.
+    pub fn validator(mut self, validator: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.validator = Box::new(validator);
+        self
+    }
 }
 
 impl Default for RequestId {
@@ -55,6 +88,14 @@ impl Default for RequestId {
     }
 }
 
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
 /// A trait for generate request id.
 pub trait IdGenerator {
     /// Generate a new request id.
@@ -72,8 +113,8 @@ where
 
 /// A generator for generate request id with ulid.
 #[derive(Default, Debug)]
-pub struct UlidGenerator{}
-impl UlidGenerator{
+pub struct UlidGenerator {}
+impl UlidGenerator {
     /// Create new `UlidGenerator`.
     pub fn new() -> Self {
         Self {}
@@ -85,14 +126,88 @@ impl IdGenerator for UlidGenerator {
     }
 }
 
+/// Extension trait for getting request id from depot.
+pub trait RequestIdDepotExt {
+    /// Get request id reference.
+    fn request_id(&self) -> Option<&str>;
+}
+
+impl RequestIdDepotExt for Depot {
+    #[inline]
+    fn request_id(&self) -> Option<&str> {
+        self.get::<String>(REQUST_ID_KEY).map(|v| v.as_str()).ok()
+    }
+}
+
 #[async_trait]
 impl Handler for RequestId {
-    async fn handle(&self, req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
-        if !self.overwrite && req.headers().contains_key(REQUST_ID_KEY) {
-            return;
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let incoming = if self.overwrite {
+            None
+        } else {
+            req.headers()
+                .get(&self.header_name)
+                .and_then(|v| v.to_str().ok())
+                .filter(|id| (self.validator)(id))
+                .map(ToOwned::to_owned)
+        };
+        let id = match incoming {
+            Some(id) => id,
+            None => self.generator.generate(req, depot),
+        };
+        if let Ok(value) = HeaderValue::from_str(&id) {
+            req.headers_mut().insert(self.header_name.clone(), value.clone());
+            res.headers_mut().insert(self.header_name.clone(), value);
         }
-        let id = self.generator.generate(req, depot);
-        req.add_header(self.header_name.clone(), &id, true).ok();
         depot.insert(REQUST_ID_KEY, id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_id() {
+        #[handler]
+        async fn hello(depot: &mut Depot) -> String {
+            depot.request_id().unwrap().to_owned()
+        }
+        #[handler]
+        async fn fail() -> Result<(), StatusError> {
+            Err(StatusError::bad_request())
+        }
+
+        let router = Router::new()
+            .hoop(RequestId::new().overwrite(false))
+            .push(Router::with_path("hello").get(hello))
+            .push(Router::with_path("fail").get(fail));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header("x-request-id", "abc-123", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get("x-request-id").unwrap(), "abc-123");
+        assert_eq!(res.take_string().await.unwrap(), "abc-123");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header("x-request-id", "<script>", true)
+            .send(&service)
+            .await;
+        let id = res.headers().get("x-request-id").unwrap().to_str().unwrap().to_owned();
+        assert_eq!(id.len(), 26);
+        assert_eq!(res.take_string().await.unwrap(), id);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/fail")
+            .add_header("accept", "application/json", true)
+            .add_header("x-request-id", "abc-123", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::BAD_REQUEST);
+        assert!(res.take_string().await.unwrap().contains(r#""request_id":"abc-123""#));
+    }
+}
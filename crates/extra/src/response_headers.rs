@@ -0,0 +1,239 @@
+//! Middlewares for setting default headers of responses and removing unwanted ones.
+//!
+//! [`DefaultHeaders`] sets headers on every response unless they are already present, and [`RemoveHeaders`]
+//! strips headers such as `Server` and `X-Powered-By`. They can be added to routers at any level, the
+//! middlewares of inner routers override outer ones for requests handled by them:
+//!
+//! - An inner `DefaultHeaders` replaces the default value of an outer one, and keeps the header if an outer
+//!   `RemoveHeaders` removes it.
+//! - An inner `RemoveHeaders` removes the header even if an outer `DefaultHeaders` sets it.
+//!
+//! Headers are changed after handlers are called, or before the response is flushed.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::http::header::{SERVER, X_FRAME_OPTIONS};
+//! use salvo_core::prelude::*;
+//! use salvo_extra::response_headers::{DefaultHeaders, RemoveHeaders};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! let router = Router::new()
+//!     .hoop(DefaultHeaders::new().header(SERVER, "salvo").header(X_FRAME_OPTIONS, "DENY"))
+//!     .push(Router::with_path("hello").get(hello))
+//!     .push(
+//!         Router::with_path("widget")
+//!             .hoop(RemoveHeaders::new().header(X_FRAME_OPTIONS))
+//!             .get(hello),
+//!     );
+//! ```
+use std::sync::{Arc, Mutex};
+
+use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue, SERVER};
+use salvo_core::http::{Request, Response};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+const X_POWERED_BY: HeaderName = HeaderName::from_static("x-powered-by");
+
+/// Headers changes merged from all `DefaultHeaders` and `RemoveHeaders` which handle a request.
+#[derive(Default, Debug)]
+struct HeaderPolicy {
+    defaults: HeaderMap,
+    removals: Vec<HeaderName>,
+}
+impl HeaderPolicy {
+    fn set_default(&mut self, name: &HeaderName, value: &HeaderValue) {
+        self.removals.retain(|n| n != name);
+        self.defaults.insert(name.clone(), value.clone());
+    }
+    fn set_removal(&mut self, name: &HeaderName) {
+        self.defaults.remove(name);
+        if !self.removals.contains(name) {
+            self.removals.push(name.clone());
+        }
+    }
+    fn apply(&self, headers: &mut HeaderMap) {
+        for name in &self.removals {
+            headers.remove(name);
+        }
+        for (name, value) in &self.defaults {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Policy shared by nested middlewares, only the outermost one applies it to the response.
+#[derive(Clone)]
+struct SharedPolicy(Arc<Mutex<HeaderPolicy>>);
+
+async fn handle_policy<F>(depot: &mut Depot, req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl, merge: F)
+where
+    F: FnOnce(&mut HeaderPolicy),
+{
+    if let Ok(SharedPolicy(policy)) = depot.obtain::<SharedPolicy>() {
+        merge(&mut policy.lock().unwrap());
+        ctrl.call_next(req, depot, res).await;
+        return;
+    }
+    let policy = Arc::new(Mutex::new(HeaderPolicy::default()));
+    merge(&mut policy.lock().unwrap());
+    depot.inject(SharedPolicy(policy.clone()));
+    let flush_policy = policy.clone();
+    res.before_flush(move |res| flush_policy.lock().unwrap().apply(res.headers_mut()));
+    ctrl.call_next(req, depot, res).await;
+    if !res.is_flushed() {
+        policy.lock().unwrap().apply(res.headers_mut());
+    }
+}
+
+/// Middleware which sets headers on every response unless they are already present.
+#[derive(Clone, Default, Debug)]
+pub struct DefaultHeaders {
+    headers: HeaderMap,
+}
+impl DefaultHeaders {
+    /// Create a new `DefaultHeaders` without any header.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets a header which is added to responses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn header(mut self, name: HeaderName, value: impl AsRef<str>) -> Self {
+        let value = HeaderValue::from_str(value.as_ref()).expect("invalid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Get headers which are added to responses.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+#[async_trait]
+impl Handler for DefaultHeaders {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        handle_policy(depot, req, res, ctrl, |policy| {
+            for (name, value) in &self.headers {
+                policy.set_default(name, value);
+            }
+        })
+        .await;
+    }
+}
+
+/// Middleware which removes headers from every response, including the ones set by handlers.
+#[derive(Clone, Default, Debug)]
+pub struct RemoveHeaders {
+    names: Vec<HeaderName>,
+}
+impl RemoveHeaders {
+    /// Create a new `RemoveHeaders` which removes nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new `RemoveHeaders` which removes `Server` and `X-Powered-By` headers.
+    #[inline]
+    pub fn server_info() -> Self {
+        Self::new().header(SERVER).header(X_POWERED_BY)
+    }
+
+    /// Add a header which is removed from responses.
+    #[inline]
+    pub fn header(mut self, name: HeaderName) -> Self {
+        if !self.names.contains(&name) {
+            self.names.push(name);
+        }
+        self
+    }
+
+    /// Get names of headers which are removed from responses.
+    #[inline]
+    pub fn names(&self) -> &[HeaderName] {
+        &self.names
+    }
+}
+
+#[async_trait]
+impl Handler for RemoveHeaders {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        handle_policy(depot, req, res, ctrl, |policy| {
+            for name in &self.names {
+                policy.set_removal(name);
+            }
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::X_FRAME_OPTIONS;
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn hello(res: &mut Response) {
+        res.headers_mut()
+            .insert(X_POWERED_BY, HeaderValue::from_static("salvo"));
+        res.render("hello");
+    }
+    #[handler]
+    async fn custom(res: &mut Response) {
+        res.headers_mut().insert(SERVER, HeaderValue::from_static("custom"));
+        res.render("custom");
+    }
+
+    #[tokio::test]
+    async fn test_response_headers() {
+        let router = Router::new()
+            .hoop(
+                DefaultHeaders::new()
+                    .header(SERVER, "salvo")
+                    .header(X_FRAME_OPTIONS, "DENY"),
+            )
+            .hoop(RemoveHeaders::server_info())
+            .push(Router::with_path("hello").get(hello))
+            .push(
+                Router::with_path("custom")
+                    .hoop(DefaultHeaders::new().header(SERVER, "route"))
+                    .get(custom),
+            )
+            .push(
+                Router::with_path("widget")
+                    .hoop(RemoveHeaders::new().header(X_FRAME_OPTIONS))
+                    .hoop(DefaultHeaders::new().header(SERVER, "widget"))
+                    .get(hello),
+            );
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+        assert!(res.headers().get(SERVER).is_none());
+        assert!(res.headers().get(X_POWERED_BY).is_none());
+        assert_eq!(res.headers()[X_FRAME_OPTIONS], "DENY");
+
+        let res = TestClient::get("http://127.0.0.1:5801/custom").send(&service).await;
+        assert_eq!(res.headers()[SERVER], "custom");
+
+        let res = TestClient::get("http://127.0.0.1:5801/widget").send(&service).await;
+        assert_eq!(res.headers()[SERVER], "widget");
+        assert!(res.headers().get(X_FRAME_OPTIONS).is_none());
+        assert!(res.headers().get(X_POWERED_BY).is_none());
+    }
+}
@@ -0,0 +1,436 @@
+//! URL rewrite middleware.
+//!
+//! [`Rewrite`] changes the path of requests by rules, such as stripping the `/api` prefix added by an ingress or
+//! mapping legacy `.php` URLs to new routes. Unlike redirects, the client does not see the new URL.
+//!
+//! Hoops are only called for requests which match a route, so `Rewrite` is usually added to [`Service`] by
+//! [`Service::rewriter`], then requests are rewritten before the router detects the route. It can also be used as
+//! a hoop, requests handled by it are rerouted by [`FlowCtrl::reroute`].
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::rewrite::{Rewrite, RewriteRule};
+//!
+//! #[handler]
+//! async fn article() -> &'static str {
+//!     "article"
+//! }
+//!
+//! let rewrite = Rewrite::new()
+//!     .strip_prefix("/api")
+//!     .rule(RewriteRule::new("/<page>.php", "/<page>"))
+//!     .rule(RewriteRule::new("/news/<id>.html", "/articles/<id>?legacy=1"));
+//! let service = Service::new(Router::with_path("articles/<id>").get(article)).rewriter(rewrite);
+//! ```
+//!
+//! [`Service`]: salvo_core::Service
+//! [`Service::rewriter`]: salvo_core::Service::rewriter
+use salvo_core::http::uri::{PathAndQuery, Uri};
+use salvo_core::http::{Request, Response};
+use salvo_core::routing::Rewriter;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
+
+/// A rule which rewrites paths matching a pattern to a target.
+///
+/// The pattern is matched against the request path segment by segment. `<name>` matches a non-empty part of a
+/// segment, so `<page>.php` matches `index.php`, and `<**name>` matches all the remaining segments, it must be the
+/// last segment of the pattern. Captured values are substituted for placeholders with the same name in the target.
+///
+/// The target can contain a query, it is merged with the query of the request.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RewriteRule {
+    /// Path pattern, such as `/<page>.php`.
+    pub from: String,
+    /// Target path, such as `/<page>`.
+    pub to: String,
+}
+impl RewriteRule {
+    /// Create a new `RewriteRule`.
+    #[inline]
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Token {
+    Literal(String),
+    Param(String),
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Tokens(Vec<Token>),
+    Rest(String),
+}
+impl Segment {
+    fn parse(part: &str, is_last: bool, pattern: &str) -> Result<Self, Error> {
+        if let Some(name) = part.strip_prefix("<**").and_then(|p| p.strip_suffix('>')) {
+            return match (is_last, name.is_empty()) {
+                (true, false) => Ok(Segment::Rest(name.to_owned())),
+                (false, _) => Err(Error::other(format!(
+                    "rest segment must be the last segment in `{pattern}`"
+                ))),
+                (true, true) => Err(Error::other(format!("empty param name in `{pattern}`"))),
+            };
+        }
+        let mut tokens = Vec::new();
+        let mut rest = part;
+        while !rest.is_empty() {
+            let Some(start) = rest.find('<') else {
+                tokens.push(Token::Literal(rest.to_owned()));
+                break;
+            };
+            let end = rest[start..]
+                .find('>')
+                .ok_or_else(|| Error::other(format!("unclosed param in `{pattern}`")))?;
+            let name = &rest[start + 1..start + end];
+            if name.is_empty() || name.contains('<') || name.starts_with("**") {
+                return Err(Error::other(format!("invalid param `{name}` in `{pattern}`")));
+            }
+            if start > 0 {
+                tokens.push(Token::Literal(rest[..start].to_owned()));
+            } else if matches!(tokens.last(), Some(Token::Param(_))) {
+                return Err(Error::other(format!("adjacent params in `{pattern}`")));
+            }
+            tokens.push(Token::Param(name.to_owned()));
+            rest = &rest[start + end + 1..];
+        }
+        Ok(Segment::Tokens(tokens))
+    }
+
+    /// Match a segment of the path, a param followed by a literal takes the shortest value, or the longest one if
+    /// the literal ends the segment.
+    fn capture<'a>(tokens: &'a [Token], mut part: &str, captures: &mut Vec<(&'a str, String)>) -> bool {
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Literal(literal) => match part.strip_prefix(literal.as_str()) {
+                    Some(rest) => part = rest,
+                    None => return false,
+                },
+                Token::Param(name) => {
+                    let end = match tokens.get(i + 1) {
+                        Some(Token::Literal(next)) if i + 2 == tokens.len() => part.rfind(next.as_str()),
+                        Some(Token::Literal(next)) => part.find(next.as_str()),
+                        _ => Some(part.len()),
+                    };
+                    match end {
+                        Some(end) if end > 0 => {
+                            captures.push((name.as_str(), part[..end].to_owned()));
+                            part = &part[end..];
+                        }
+                        _ => return false,
+                    }
+                }
+            }
+        }
+        part.is_empty()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CompiledRule {
+    segments: Vec<Segment>,
+    to: String,
+}
+impl CompiledRule {
+    fn compile(rule: RewriteRule) -> Result<Self, Error> {
+        let parts = split_path(&rule.from);
+        let segments = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| Segment::parse(part, i == parts.len() - 1, &rule.from))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !rule.to.starts_with('/') {
+            return Err(Error::other(format!(
+                "rewrite target `{}` must start with `/`",
+                rule.to
+            )));
+        }
+        let compiled = Self { segments, to: rule.to };
+        let mut missing = None;
+        compiled.render(|name| {
+            if !compiled.has_param(name) && missing.is_none() {
+                missing = Some(name.to_owned());
+            }
+            Some("")
+        });
+        if let Some(name) = missing {
+            return Err(Error::other(format!("unknown param `{name}` in `{}`", compiled.to)));
+        }
+        Ok(compiled)
+    }
+
+    fn has_param(&self, name: &str) -> bool {
+        self.segments.iter().any(|segment| match segment {
+            Segment::Tokens(tokens) => tokens.iter().any(|t| matches!(t, Token::Param(n) if n == name)),
+            Segment::Rest(n) => n == name,
+        })
+    }
+
+    fn capture(&self, parts: &[&str]) -> Option<Vec<(&str, String)>> {
+        let mut captures = Vec::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Tokens(tokens) => {
+                    if !Segment::capture(tokens, parts.get(i)?, &mut captures) {
+                        return None;
+                    }
+                }
+                Segment::Rest(name) => {
+                    captures.push((name.as_str(), parts[i..].join("/")));
+                    return Some(captures);
+                }
+            }
+        }
+        (parts.len() == self.segments.len()).then_some(captures)
+    }
+
+    /// Render the target, placeholders `<name>` and `<**name>` are replaced by `value(name)`.
+    fn render<'a>(&self, mut value: impl FnMut(&str) -> Option<&'a str>) -> String {
+        let mut target = String::with_capacity(self.to.len());
+        let mut rest = self.to.as_str();
+        while let Some(start) = rest.find('<') {
+            let Some(end) = rest[start..].find('>') else {
+                break;
+            };
+            let name = rest[start + 1..start + end].trim_start_matches("**");
+            target.push_str(&rest[..start]);
+            match value(name) {
+                Some(v) => target.push_str(v),
+                None => target.push_str(&rest[start..=start + end]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        target.push_str(rest);
+        target
+    }
+
+    fn rewrite(&self, path: &str) -> Option<String> {
+        let captures = self.capture(&split_path(path))?;
+        Some(self.render(|name| captures.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())))
+    }
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.split('/').filter(|p| !p.is_empty()).collect()
+}
+
+fn push_query(query: &mut String, other: &str) {
+    if !other.is_empty() {
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str(other);
+    }
+}
+
+/// Middleware and [`Rewriter`] which rewrites request paths by [`RewriteRule`]s.
+///
+/// Rules are checked in the order they are added, every matched rule rewrites the path produced by the previous
+/// rules, so `strip_prefix("/api")` followed by a `.php` rule also rewrites `/api/index.php`.
+#[derive(Clone, Default, Debug)]
+pub struct Rewrite {
+    rules: Vec<CompiledRule>,
+}
+impl Rewrite {
+    /// Create a new `Rewrite` without any rule.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule is invalid.
+    pub fn rule(mut self, rule: RewriteRule) -> Self {
+        self.rules
+            .push(CompiledRule::compile(rule).expect("invalid rewrite rule"));
+        self
+    }
+
+    /// Add a rule which strips a path prefix, such as `/api`, `/api/users` is rewritten to `/users` and `/api`
+    /// to `/`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix contains params.
+    pub fn strip_prefix(self, prefix: impl AsRef<str>) -> Self {
+        let prefix = prefix.as_ref().trim_end_matches('/');
+        if prefix.contains('<') {
+            panic!("rewrite prefix `{prefix}` can not contain params");
+        }
+        self.rule(RewriteRule::new(format!("{prefix}/<**path>"), "/<path>"))
+    }
+
+    /// Get the number of rules.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if there is no rule.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Find the rewritten path for a path, it contains a query if targets of the matched rules contain one.
+    pub fn find(&self, path: &str) -> Option<String> {
+        let mut rewritten: Option<(String, String)> = None;
+        for rule in &self.rules {
+            let current = rewritten.as_ref().map(|(path, _)| path.as_str()).unwrap_or(path);
+            let Some(target) = rule.rewrite(current) else {
+                continue;
+            };
+            let (target_path, target_query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+            let mut query = rewritten.map(|(_, query)| query).unwrap_or_default();
+            push_query(&mut query, target_query);
+            rewritten = Some((target_path.to_owned(), query));
+        }
+        rewritten.map(|(path, query)| {
+            if query.is_empty() {
+                path
+            } else {
+                format!("{path}?{query}")
+            }
+        })
+    }
+
+    /// Find the rewritten path and query for a request, the query of the request is kept.
+    fn find_for(&self, req: &Request) -> Option<String> {
+        let target = self.find(req.uri().path())?;
+        let (path, target_query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+        let mut query = target_query.to_owned();
+        push_query(&mut query, req.uri().query().unwrap_or_default());
+        if query.is_empty() {
+            Some(path.to_owned())
+        } else {
+            Some(format!("{path}?{query}"))
+        }
+    }
+}
+
+impl Rewriter for Rewrite {
+    fn rewrite(&self, req: &mut Request) {
+        let Some(target) = self.find_for(req) else {
+            return;
+        };
+        let uri = target
+            .parse::<PathAndQuery>()
+            .map_err(Error::other)
+            .and_then(|path_and_query| {
+                let mut parts = req.uri().clone().into_parts();
+                parts.path_and_query = Some(path_and_query);
+                Uri::from_parts(parts).map_err(Error::other)
+            });
+        match uri {
+            Ok(uri) => req.set_uri(uri),
+            Err(e) => tracing::error!(error = ?e, %target, "invalid rewrite target"),
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Rewrite {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let Some(target) = self.find_for(req) else {
+            return;
+        };
+        if let Err(e) = ctrl.reroute(req, res, &target) {
+            tracing::error!(error = ?e, %target, "invalid rewrite target");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let rewrite = Rewrite::new()
+            .strip_prefix("/api/")
+            .rule(RewriteRule::new("/<page>.php", "/<page>"))
+            .rule(RewriteRule::new("/news/<id>.html", "/articles/<id>?legacy=1"))
+            .rule(RewriteRule::new(
+                "/v<version>-<name>/<**rest>",
+                "/<name>/<version>/<rest>",
+            ));
+        assert_eq!(rewrite.len(), 4);
+        assert_eq!(rewrite.find("/api/users/1"), Some("/users/1".to_owned()));
+        assert_eq!(rewrite.find("/api"), Some("/".to_owned()));
+        assert_eq!(rewrite.find("/apiary"), None);
+        assert_eq!(rewrite.find("/api/index.php"), Some("/index".to_owned()));
+        assert_eq!(rewrite.find("/a.b.php"), Some("/a.b".to_owned()));
+        assert_eq!(rewrite.find("/.php"), None);
+        assert_eq!(rewrite.find("/news/12.html"), Some("/articles/12?legacy=1".to_owned()));
+        assert_eq!(rewrite.find("/v2-shop/a/b"), Some("/shop/2/a/b".to_owned()));
+        assert_eq!(rewrite.find("/other"), None);
+
+        assert!(CompiledRule::compile(RewriteRule::new("/a/<id>", "/b/<name>")).is_err());
+        assert!(CompiledRule::compile(RewriteRule::new("/a/<**rest>/b", "/b")).is_err());
+        assert!(CompiledRule::compile(RewriteRule::new("/<a><b>", "/b")).is_err());
+        assert!(CompiledRule::compile(RewriteRule::new("/<a", "/b")).is_err());
+        assert!(CompiledRule::compile(RewriteRule::new("/a", "b")).is_err());
+    }
+
+    #[handler]
+    async fn show(req: &mut Request) -> String {
+        let path = req.uri().path_and_query().map(|p| p.as_str()).unwrap_or_default();
+        format!("{} {path}", req.param::<String>("id").unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_rewriter() {
+        let rewrite = Rewrite::new()
+            .strip_prefix("/api")
+            .rule(RewriteRule::new("/item.php", "/items/0"))
+            .rule(RewriteRule::new("/<id>.php", "/items/<id>?legacy=1"));
+        let service = Service::new(Router::with_path("items/<id>").get(show)).rewriter(rewrite);
+
+        let content = TestClient::get("http://127.0.0.1:5801/api/items/1?a=1")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "1 /items/1?a=1");
+        let content = TestClient::get("http://127.0.0.1:5801/api/7.php?a=1")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "7 /items/7?legacy=1&a=1");
+        let res = TestClient::get("http://127.0.0.1:5801/other").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_hoop() {
+        let router = Router::new().push(Router::with_path("items/<id>").get(show)).push(
+            Router::with_path("legacy/<**>")
+                .hoop(Rewrite::new().rule(RewriteRule::new("/legacy/<id>.php", "/items/<id>")))
+                .goal(salvo_core::handler::empty()),
+        );
+        let service = Service::new(router);
+        let content = TestClient::get("http://127.0.0.1:5801/legacy/3.php")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "3 /items/3");
+    }
+}
@@ -0,0 +1,350 @@
+//! Security headers middleware.
+//!
+//! Read more: <https://salvo.rs>
+use base64::engine::{general_purpose, Engine};
+use rand::RngCore;
+
+use salvo_core::http::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY_REPORT_ONLY,
+    REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+use salvo_core::http::{Request, Response};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Key for csp nonce in depot.
+pub const CSP_NONCE_KEY: &str = "::salvo::secure_headers::csp_nonce";
+
+const PERMISSIONS_POLICY: HeaderName = HeaderName::from_static("permissions-policy");
+const CROSS_ORIGIN_OPENER_POLICY: HeaderName = HeaderName::from_static("cross-origin-opener-policy");
+const CROSS_ORIGIN_EMBEDDER_POLICY: HeaderName = HeaderName::from_static("cross-origin-embedder-policy");
+const CROSS_ORIGIN_RESOURCE_POLICY: HeaderName = HeaderName::from_static("cross-origin-resource-policy");
+
+/// SecureHeadersDepotExt
+pub trait SecureHeadersDepotExt {
+    /// Get the nonce of `Content-Security-Policy` for current request.
+    fn csp_nonce(&self) -> Option<&str>;
+}
+
+impl SecureHeadersDepotExt for Depot {
+    #[inline]
+    fn csp_nonce(&self) -> Option<&str> {
+        self.get::<String>(CSP_NONCE_KEY).map(|v| v.as_str()).ok()
+    }
+}
+
+/// Builder of `Content-Security-Policy` header.
+///
+/// # Example
+///
+/// ```
+/// use salvo_extra::secure_headers::ContentSecurityPolicy;
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .directive("default-src", ["'self'"])
+///     .directive("img-src", ["'self'", "data:"])
+///     .nonce(true);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+    nonce: bool,
+    report_only: bool,
+}
+impl ContentSecurityPolicy {
+    /// Create an empty `ContentSecurityPolicy`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a strict `ContentSecurityPolicy` which only allows resources from same origin,
+    /// and inline scripts and styles with nonce.
+    pub fn strict() -> Self {
+        Self::new()
+            .directive("default-src", ["'self'"])
+            .directive("script-src", ["'self'"])
+            .directive("style-src", ["'self'"])
+            .directive("object-src", ["'none'"])
+            .directive("base-uri", ["'self'"])
+            .directive("frame-ancestors", ["'none'"])
+            .nonce(true)
+    }
+
+    /// Sets the sources of a directive, the previous sources of the directive are replaced.
+    pub fn directive<I, S>(mut self, name: impl Into<String>, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let name = name.into();
+        let sources = sources.into_iter().map(Into::into).collect();
+        if let Some((_, values)) = self.directives.iter_mut().find(|(n, _)| *n == name) {
+            *values = sources;
+        } else {
+            self.directives.push((name, sources));
+        }
+        self
+    }
+
+    /// Sets whether a nonce is generated for every request.
+    ///
+    /// The nonce is added to `script-src` and `style-src` directives, and it can be got by
+    /// [`SecureHeadersDepotExt::csp_nonce`] for templates.
+    #[inline]
+    pub fn nonce(mut self, nonce: bool) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Sets whether `Content-Security-Policy-Report-Only` header is used instead.
+    #[inline]
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    /// Render the header value with the nonce.
+    pub fn to_header_value(&self, nonce: Option<&str>) -> String {
+        let mut parts = Vec::with_capacity(self.directives.len());
+        for (name, sources) in &self.directives {
+            let mut part = name.clone();
+            for source in sources {
+                part.push(' ');
+                part.push_str(source);
+            }
+            if let Some(nonce) = nonce {
+                if name == "script-src" || name == "style-src" {
+                    part.push_str(&format!(" 'nonce-{nonce}'"));
+                }
+            }
+            parts.push(part);
+        }
+        parts.join("; ")
+    }
+}
+
+/// Middleware for adding security related headers to responses.
+///
+/// `SecureHeaders::new()` uses sane defaults which is safe for most applications:
+///
+/// - `X-Content-Type-Options: nosniff`
+/// - `X-Frame-Options: SAMEORIGIN`
+/// - `Referrer-Policy: strict-origin-when-cross-origin`
+/// - `Cross-Origin-Opener-Policy: same-origin`
+/// - `Cross-Origin-Resource-Policy: same-origin`
+///
+/// `SecureHeaders::strict()` also adds a strict `Content-Security-Policy` with nonce, and other strict headers.
+///
+/// Headers are added before handlers are called, so handlers can override them.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::secure_headers::{SecureHeaders, SecureHeadersDepotExt};
+///
+/// #[handler]
+/// async fn index(depot: &mut Depot, res: &mut Response) {
+///     let nonce = depot.csp_nonce().unwrap_or_default();
+///     res.render(Text::Html(format!(r#"<script nonce="{nonce}">alert(1)</script>"#)));
+/// }
+/// let router = Router::new().hoop(SecureHeaders::strict()).get(index);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecureHeaders {
+    headers: HeaderMap,
+    csp: Option<ContentSecurityPolicy>,
+}
+impl Default for SecureHeaders {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl SecureHeaders {
+    /// Create `SecureHeaders` with sane defaults.
+    pub fn new() -> Self {
+        Self::none()
+            .x_content_type_options(true)
+            .x_frame_options("SAMEORIGIN")
+            .referrer_policy("strict-origin-when-cross-origin")
+            .cross_origin_opener_policy("same-origin")
+            .cross_origin_resource_policy("same-origin")
+    }
+
+    /// Create `SecureHeaders` with strict headers.
+    pub fn strict() -> Self {
+        Self::new()
+            .content_security_policy(ContentSecurityPolicy::strict())
+            .x_frame_options("DENY")
+            .referrer_policy("no-referrer")
+            .cross_origin_embedder_policy("require-corp")
+            .permissions_policy("camera=(), microphone=(), geolocation=(), payment=(), usb=()")
+    }
+
+    /// Create `SecureHeaders` without any header.
+    #[inline]
+    pub fn none() -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            csp: None,
+        }
+    }
+
+    /// Sets `Content-Security-Policy` header.
+    #[inline]
+    pub fn content_security_policy(mut self, csp: ContentSecurityPolicy) -> Self {
+        self.csp = Some(csp);
+        self
+    }
+
+    /// Sets whether `X-Content-Type-Options: nosniff` header is added.
+    #[inline]
+    pub fn x_content_type_options(self, nosniff: bool) -> Self {
+        if nosniff {
+            self.header(X_CONTENT_TYPE_OPTIONS, "nosniff")
+        } else {
+            self.remove(X_CONTENT_TYPE_OPTIONS)
+        }
+    }
+
+    /// Sets `X-Frame-Options` header, such as `DENY` or `SAMEORIGIN`.
+    #[inline]
+    pub fn x_frame_options(self, value: impl AsRef<str>) -> Self {
+        self.header(X_FRAME_OPTIONS, value)
+    }
+
+    /// Sets `Referrer-Policy` header.
+    #[inline]
+    pub fn referrer_policy(self, value: impl AsRef<str>) -> Self {
+        self.header(REFERRER_POLICY, value)
+    }
+
+    /// Sets `Permissions-Policy` header, such as `camera=(), geolocation=(self)`.
+    #[inline]
+    pub fn permissions_policy(self, value: impl AsRef<str>) -> Self {
+        self.header(PERMISSIONS_POLICY, value)
+    }
+
+    /// Sets `Cross-Origin-Opener-Policy` header.
+    #[inline]
+    pub fn cross_origin_opener_policy(self, value: impl AsRef<str>) -> Self {
+        self.header(CROSS_ORIGIN_OPENER_POLICY, value)
+    }
+
+    /// Sets `Cross-Origin-Embedder-Policy` header.
+    #[inline]
+    pub fn cross_origin_embedder_policy(self, value: impl AsRef<str>) -> Self {
+        self.header(CROSS_ORIGIN_EMBEDDER_POLICY, value)
+    }
+
+    /// Sets `Cross-Origin-Resource-Policy` header.
+    #[inline]
+    pub fn cross_origin_resource_policy(self, value: impl AsRef<str>) -> Self {
+        self.header(CROSS_ORIGIN_RESOURCE_POLICY, value)
+    }
+
+    /// Sets a header which is added to responses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn header(mut self, name: HeaderName, value: impl AsRef<str>) -> Self {
+        let value = HeaderValue::from_str(value.as_ref()).expect("invalid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Removes a header, `Content-Security-Policy` is removed if the name is `Content-Security-Policy`.
+    pub fn remove(mut self, name: HeaderName) -> Self {
+        if name == CONTENT_SECURITY_POLICY || name == CONTENT_SECURITY_POLICY_REPORT_ONLY {
+            self.csp = None;
+        }
+        self.headers.remove(name);
+        self
+    }
+}
+
+fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    general_purpose::STANDARD.encode(bytes)
+}
+
+#[async_trait]
+impl Handler for SecureHeaders {
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let headers = res.headers_mut();
+        for (name, value) in &self.headers {
+            headers.insert(name.clone(), value.clone());
+        }
+        if let Some(csp) = &self.csp {
+            let nonce = csp.nonce.then(generate_nonce);
+            let name = if csp.report_only {
+                CONTENT_SECURITY_POLICY_REPORT_ONLY
+            } else {
+                CONTENT_SECURITY_POLICY
+            };
+            if let Ok(value) = HeaderValue::from_str(&csp.to_header_value(nonce.as_deref())) {
+                headers.insert(name, value);
+            }
+            if let Some(nonce) = nonce {
+                depot.insert(CSP_NONCE_KEY, nonce);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_csp_header_value() {
+        let csp = ContentSecurityPolicy::new()
+            .directive("default-src", ["'self'"])
+            .directive("script-src", ["'self'", "cdn.example.com"])
+            .directive("default-src", ["'none'"]);
+        assert_eq!(
+            csp.to_header_value(Some("abc")),
+            "default-src 'none'; script-src 'self' cdn.example.com 'nonce-abc'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secure_headers() {
+        #[handler]
+        async fn index(depot: &mut Depot) -> String {
+            depot.csp_nonce().unwrap_or_default().to_owned()
+        }
+
+        let service = Service::new(Router::new().hoop(SecureHeaders::new()).get(index));
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.headers().get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(res.headers().get(X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+        assert!(res.headers().get(CONTENT_SECURITY_POLICY).is_none());
+        assert_eq!(res.take_string().await.unwrap(), "");
+
+        let service = Service::new(
+            Router::new()
+                .hoop(SecureHeaders::strict().remove(X_FRAME_OPTIONS))
+                .get(index),
+        );
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert!(res.headers().get(X_FRAME_OPTIONS).is_none());
+        assert_eq!(res.headers().get(CROSS_ORIGIN_EMBEDDER_POLICY).unwrap(), "require-corp");
+        let csp = res
+            .headers()
+            .get(CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let nonce = res.take_string().await.unwrap();
+        assert_eq!(nonce.len(), 24);
+        assert!(csp.contains(&format!("script-src 'self' 'nonce-{nonce}'")));
+    }
+}
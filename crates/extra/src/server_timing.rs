@@ -0,0 +1,288 @@
+//! Server-Timing middleware.
+//!
+//! Read more: <https://salvo.rs>
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use salvo_core::http::header::{HeaderName, HeaderValue};
+use salvo_core::http::{Request, Response};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Key for server timing metrics in depot.
+pub const SERVER_TIMING_KEY: &str = "::salvo::server_timing";
+
+const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// A metric in `Server-Timing` header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metric {
+    name: String,
+    duration: Option<Duration>,
+    description: Option<String>,
+}
+impl Metric {
+    /// Create a new `Metric`.
+    ///
+    /// Characters not allowed in header token are replaced by `_`.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name
+            .into()
+            .chars()
+            .map(|c| if is_token_char(c) { c } else { '_' })
+            .collect();
+        Self {
+            name,
+            duration: None,
+            description: None,
+        }
+    }
+    /// Sets the duration of this metric.
+    #[inline]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+    /// Sets the description of this metric.
+    #[inline]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+    /// Get the name of this metric.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to(&self, out: &mut String) {
+        out.push_str(&self.name);
+        if let Some(duration) = self.duration {
+            write!(out, ";dur={:.1}", duration.as_secs_f64() * 1000.0).ok();
+        }
+        if let Some(description) = &self.description {
+            out.push_str(";desc=\"");
+            for c in description.chars().filter(|c| !c.is_control()) {
+                if c == '"' || c == '\\' {
+                    out.push('\\');
+                }
+                out.push(c);
+            }
+            out.push('"');
+        }
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Measures the duration of a metric, created by [`TimingSpan::start`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::server_timing::TimingSpan;
+///
+/// #[handler]
+/// async fn list_users(depot: &mut Depot) -> &'static str {
+///     let span = TimingSpan::start("db").description("query users");
+///     // query users...
+///     span.end(depot);
+///     "users"
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TimingSpan {
+    metric: Metric,
+    started: Instant,
+}
+impl TimingSpan {
+    /// Start measuring a metric with the given name.
+    #[inline]
+    pub fn start(name: impl Into<String>) -> Self {
+        Self {
+            metric: Metric::new(name),
+            started: Instant::now(),
+        }
+    }
+    /// Sets the description of the metric.
+    #[inline]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.metric = self.metric.description(description);
+        self
+    }
+    /// End measuring and record the metric into depot.
+    #[inline]
+    pub fn end(self, depot: &mut Depot) {
+        let duration = self.started.elapsed();
+        depot.add_timing(self.metric.duration(duration));
+    }
+}
+
+/// Extension trait for recording server timing metrics into depot.
+pub trait ServerTimingDepotExt {
+    /// Add a metric.
+    fn add_timing(&mut self, metric: Metric);
+    /// Add a metric with the given name and duration.
+    #[inline]
+    fn record_timing(&mut self, name: impl Into<String>, duration: Duration) {
+        self.add_timing(Metric::new(name).duration(duration));
+    }
+    /// Get recorded metrics.
+    fn timings(&self) -> &[Metric];
+}
+
+impl ServerTimingDepotExt for Depot {
+    #[inline]
+    fn add_timing(&mut self, metric: Metric) {
+        if let Ok(metrics) = self.get_mut::<Vec<Metric>>(SERVER_TIMING_KEY) {
+            metrics.push(metric);
+        } else {
+            self.insert(SERVER_TIMING_KEY, vec![metric]);
+        }
+    }
+    #[inline]
+    fn timings(&self) -> &[Metric] {
+        self.get::<Vec<Metric>>(SERVER_TIMING_KEY)
+            .map(|metrics| &metrics[..])
+            .unwrap_or_default()
+    }
+}
+
+/// A middleware emits metrics recorded by [`ServerTimingDepotExt`] as `Server-Timing` header, browser devtools
+/// can show them as backend breakdowns.
+///
+/// A `total` metric of the time spent by all following handlers is also added by default. Use
+/// [`ServerTiming::allowlist`] to only expose some metrics in production.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use salvo_core::prelude::*;
+/// use salvo_extra::server_timing::{ServerTiming, ServerTimingDepotExt};
+///
+/// #[handler]
+/// async fn hello(depot: &mut Depot) -> &'static str {
+///     depot.record_timing("cache", Duration::from_millis(2));
+///     "hello"
+/// }
+/// let router = Router::new()
+///     .hoop(ServerTiming::new().allowlist(["total", "cache"]))
+///     .get(hello);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServerTiming {
+    total: bool,
+    allowlist: Option<HashSet<String>>,
+}
+impl Default for ServerTiming {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ServerTiming {
+    /// Create a new `ServerTiming`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            total: true,
+            allowlist: None,
+        }
+    }
+    /// Sets whether a `total` metric is added. Default is `true`.
+    #[inline]
+    pub fn total(mut self, total: bool) -> Self {
+        self.total = total;
+        self
+    }
+    /// Only metrics with these names are emitted, other metrics are dropped.
+    #[inline]
+    pub fn allowlist<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowlist = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowlist.as_ref().map(|list| list.contains(name)).unwrap_or(true)
+    }
+}
+
+#[async_trait]
+impl Handler for ServerTiming {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let started = Instant::now();
+        ctrl.call_next(req, depot, res).await;
+        let mut metrics = depot.remove::<Vec<Metric>>(SERVER_TIMING_KEY).unwrap_or_default();
+        if self.total {
+            metrics.push(Metric::new("total").duration(started.elapsed()));
+        }
+        let mut timing = String::new();
+        for metric in metrics.iter().filter(|m| self.is_allowed(&m.name)) {
+            if !timing.is_empty() {
+                timing.push_str(", ");
+            }
+            metric.write_to(&mut timing);
+        }
+        if timing.is_empty() {
+            return;
+        }
+        match HeaderValue::from_str(&timing) {
+            Ok(value) => {
+                res.headers_mut().append(SERVER_TIMING, value);
+            }
+            Err(_) => {
+                tracing::error!(timing, "invalid server timing header value");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> &'static str {
+        depot.record_timing("db", Duration::from_millis(12));
+        depot.add_timing(Metric::new("cache hit").description("from \"redis\""));
+        TimingSpan::start("render").end(depot);
+        assert_eq!(depot.timings().len(), 3);
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn test_server_timing() {
+        let res = TestClient::get("http://127.0.0.1:5801")
+            .send(Router::new().hoop(ServerTiming::new()).get(hello))
+            .await;
+        let timing = res.headers().get(SERVER_TIMING).unwrap().to_str().unwrap();
+        assert!(timing.starts_with(r#"db;dur=12.0, cache_hit;desc="from \"redis\"", render;dur="#));
+        assert!(timing.contains(", total;dur="));
+
+        let res = TestClient::get("http://127.0.0.1:5801")
+            .send(
+                Router::new()
+                    .hoop(ServerTiming::new().total(false).allowlist(["db"]))
+                    .get(hello),
+            )
+            .await;
+        assert_eq!(res.headers().get(SERVER_TIMING).unwrap(), "db;dur=12.0");
+
+        let res = TestClient::get("http://127.0.0.1:5801")
+            .send(Router::new().hoop(ServerTiming::new().allowlist(["none"])).get(hello))
+            .await;
+        assert!(res.headers().get(SERVER_TIMING).is_none());
+    }
+}
@@ -0,0 +1,451 @@
+//! Signed URL generation and validation.
+//!
+//! [`UrlSigner`] issues time-limited links, for example to download or upload a file without a session, and
+//! validates them when it is used as middleware. A signed URL carries these query parameters:
+//!
+//! - `expires`: unix timestamp after which the link is rejected.
+//! - `kid`: id of the key used to sign the link, so keys can be rotated without breaking issued links.
+//! - `sig`: base64url encoded HMAC-SHA256 over the method, the path and all other query parameters.
+//!
+//! Claims are added as extra query parameters, they are covered by the signature so they can not be changed or
+//! removed, and no parameter can be added to a signed URL.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use salvo_core::http::Method;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::signed_url::{SignedUrlDepotExt, UrlSigner};
+//!
+//! #[handler]
+//! async fn download(depot: &mut Depot) -> String {
+//!     let user = depot.signed_url_claims().and_then(|claims| claims.get("user")).unwrap_or_default();
+//!     format!("file for {user}")
+//! }
+//!
+//! let signer = UrlSigner::new("2024-06", "new secret").fallback_key("2024-01", "old secret");
+//! let url = signer
+//!     .url(Method::GET, "/files/report.pdf")
+//!     .expires_in(Duration::from_secs(600))
+//!     .claim("user", "42")
+//!     .sign();
+//! let router = Router::with_path("files/<name>").hoop(signer).get(download);
+//! ```
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use salvo_core::http::{Method, Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::Sha256;
+
+/// Key used when insert verified claims into depot.
+pub const SIGNED_URL_CLAIMS_KEY: &str = "::salvo::signed_url::claims";
+
+/// Query parameter of the expiry timestamp.
+pub const EXPIRES_PARAM: &str = "expires";
+/// Query parameter of the key id.
+pub const KEY_ID_PARAM: &str = "kid";
+/// Query parameter of the signature.
+pub const SIGNATURE_PARAM: &str = "sig";
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Error of signed URL validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignedUrlError {
+    /// The signature, key id or expiry parameter is missing or malformed.
+    MissingSignature,
+    /// The URL is signed with a key which is not known.
+    UnknownKey,
+    /// The signature does not match.
+    InvalidSignature,
+    /// The link is expired.
+    Expired,
+}
+impl Display for SignedUrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSignature => f.write_str("url signature is missing"),
+            Self::UnknownKey => f.write_str("url is signed with an unknown key"),
+            Self::InvalidSignature => f.write_str("url signature is invalid"),
+            Self::Expired => f.write_str("url is expired"),
+        }
+    }
+}
+impl StdError for SignedUrlError {}
+
+/// Claims of a validated signed URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SignedClaims {
+    /// Id of the key the URL is signed with.
+    pub key_id: String,
+    /// Unix timestamp after which the URL is rejected.
+    pub expires_at: u64,
+    /// Claims of the URL, they are all query parameters except `expires`, `kid` and `sig`.
+    pub claims: BTreeMap<String, String>,
+}
+impl SignedClaims {
+    /// Get the value of a claim.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).map(String::as_str)
+    }
+}
+
+/// SignedUrlDepotExt
+pub trait SignedUrlDepotExt {
+    /// Get claims of the validated signed URL.
+    fn signed_url_claims(&self) -> Option<&SignedClaims>;
+}
+impl SignedUrlDepotExt for Depot {
+    #[inline]
+    fn signed_url_claims(&self) -> Option<&SignedClaims> {
+        self.get(SIGNED_URL_CLAIMS_KEY).ok()
+    }
+}
+
+#[derive(Clone)]
+struct SigningKey {
+    id: String,
+    secret: Vec<u8>,
+}
+
+/// Signs URLs, and validates them when it is used as middleware.
+///
+/// URLs are signed with the key given in [`UrlSigner::new`], keys added by [`UrlSigner::fallback_key`] are only
+/// used to validate links issued before a key rotation. A `HEAD` request is accepted by a URL signed for `GET`.
+#[derive(Clone)]
+pub struct UrlSigner {
+    keys: Vec<SigningKey>,
+    default_ttl: Duration,
+}
+impl UrlSigner {
+    /// Create new `UrlSigner` with the id and secret of the key used to sign URLs, links expire in 1 hour by
+    /// default.
+    #[inline]
+    pub fn new(key_id: impl Into<String>, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            keys: vec![SigningKey {
+                id: key_id.into(),
+                secret: secret.as_ref().to_vec(),
+            }],
+            default_ttl: Duration::from_secs(3600),
+        }
+    }
+    /// Add a key which is accepted when validating URLs, but not used to sign new ones.
+    #[inline]
+    pub fn fallback_key(mut self, key_id: impl Into<String>, secret: impl AsRef<[u8]>) -> Self {
+        self.keys.push(SigningKey {
+            id: key_id.into(),
+            secret: secret.as_ref().to_vec(),
+        });
+        self
+    }
+    /// Sets how long links are valid when no expiry is given to the builder.
+    #[inline]
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Start building a signed URL for `method` and `path`.
+    ///
+    /// The path must be percent-encoded as it is sent by clients, it may contain a query, whose parameters become
+    /// claims. Prepend the scheme and host to the result to make an absolute URL.
+    #[inline]
+    pub fn url(&self, method: Method, path: impl Into<String>) -> SignedUrlBuilder<'_> {
+        SignedUrlBuilder {
+            signer: self,
+            method,
+            path: path.into(),
+            expires_at: None,
+            claims: Vec::new(),
+        }
+    }
+
+    /// Validate a signed URL by the method, path and query of a request.
+    pub fn verify(&self, method: &Method, path: &str, query: Option<&str>) -> Result<SignedClaims, SignedUrlError> {
+        let mut signature = None;
+        let mut key_id = None;
+        let mut expires_at = None;
+        let mut params = Vec::new();
+        for (name, value) in form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+            match &*name {
+                SIGNATURE_PARAM => {
+                    signature = URL_SAFE_NO_PAD.decode(value.as_bytes()).ok();
+                    continue;
+                }
+                KEY_ID_PARAM => key_id = Some(value.to_string()),
+                EXPIRES_PARAM => expires_at = value.parse::<u64>().ok(),
+                _ => {}
+            }
+            params.push((name.into_owned(), value.into_owned()));
+        }
+        let (Some(signature), Some(key_id), Some(expires_at)) = (signature, key_id, expires_at) else {
+            return Err(SignedUrlError::MissingSignature);
+        };
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.id == key_id)
+            .ok_or(SignedUrlError::UnknownKey)?;
+        let method = if *method == Method::HEAD {
+            Method::GET
+        } else {
+            method.clone()
+        };
+        if mac(&key.secret, &method, path, &mut params)
+            .verify_slice(&signature)
+            .is_err()
+        {
+            return Err(SignedUrlError::InvalidSignature);
+        }
+        if now() > expires_at {
+            return Err(SignedUrlError::Expired);
+        }
+        let claims = params
+            .into_iter()
+            .filter(|(name, _)| name != EXPIRES_PARAM && name != KEY_ID_PARAM)
+            .collect();
+        Ok(SignedClaims {
+            key_id,
+            expires_at,
+            claims,
+        })
+    }
+}
+impl Debug for UrlSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlSigner")
+            .field("key_ids", &self.keys.iter().map(|key| &key.id).collect::<Vec<_>>())
+            .field("default_ttl", &self.default_ttl)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Handler for UrlSigner {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        match self.verify(req.method(), req.uri().path(), req.uri().query()) {
+            Ok(claims) => {
+                depot.insert(SIGNED_URL_CLAIMS_KEY, claims);
+            }
+            Err(SignedUrlError::MissingSignature) => {
+                res.render(StatusError::unauthorized().brief("Url signature is missing."));
+                ctrl.skip_rest();
+            }
+            Err(SignedUrlError::Expired) => {
+                res.render(StatusError::forbidden().brief("Url is expired."));
+                ctrl.skip_rest();
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "signed url validation failed");
+                res.render(StatusError::forbidden().brief("Url signature is invalid."));
+                ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+/// Builder of a signed URL, created by [`UrlSigner::url`].
+#[derive(Debug)]
+pub struct SignedUrlBuilder<'a> {
+    signer: &'a UrlSigner,
+    method: Method,
+    path: String,
+    expires_at: Option<u64>,
+    claims: Vec<(String, String)>,
+}
+impl SignedUrlBuilder<'_> {
+    /// Sets the time after which the URL is rejected.
+    #[inline]
+    pub fn expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(
+            expires_at
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        );
+        self
+    }
+    /// Sets how long the URL is valid from now.
+    #[inline]
+    pub fn expires_in(mut self, ttl: Duration) -> Self {
+        self.expires_at = Some(now() + ttl.as_secs());
+        self
+    }
+    /// Add a claim, it is sent as a query parameter covered by the signature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is one of the reserved parameters `expires`, `kid` and `sig`.
+    #[inline]
+    pub fn claim(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(
+            ![EXPIRES_PARAM, KEY_ID_PARAM, SIGNATURE_PARAM].contains(&&*name),
+            "`{name}` is reserved by signed url"
+        );
+        self.claims.push((name, value.into()));
+        self
+    }
+
+    /// Sign the URL, returns the path with the signed query.
+    pub fn sign(self) -> String {
+        let Self {
+            signer,
+            method,
+            path,
+            expires_at,
+            mut claims,
+        } = self;
+        let key = &signer.keys[0];
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path.to_owned(), Some(query.to_owned())),
+            None => (path, None),
+        };
+        let mut params: Vec<(String, String)> = form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+        params.append(&mut claims);
+        let expires_at = expires_at.unwrap_or_else(|| now() + signer.default_ttl.as_secs());
+        params.push((EXPIRES_PARAM.into(), expires_at.to_string()));
+        params.push((KEY_ID_PARAM.into(), key.id.clone()));
+        let signature = mac(&key.secret, &method, &path, &mut params).finalize().into_bytes();
+
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        serializer.extend_pairs(&params);
+        serializer.append_pair(SIGNATURE_PARAM, &URL_SAFE_NO_PAD.encode(signature));
+        format!("{path}?{}", serializer.finish())
+    }
+}
+
+/// Compute the signature of `METHOD\npath\nquery`, the query is canonicalized by sorting its parameters.
+fn mac(secret: &[u8], method: &Method, path: &str, params: &mut [(String, String)]) -> Hmac<Sha256> {
+    params.sort();
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    serializer.extend_pairs(params.iter());
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts keys of any size");
+    mac.update(format!("{method}\n{path}\n{}", serializer.finish()).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    fn split(url: &str) -> (&str, Option<&str>) {
+        match url.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (url, None),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = UrlSigner::new("k2", "secret2").fallback_key("k1", "secret1");
+        let url = signer
+            .url(Method::GET, "/files/a.pdf?download=1")
+            .claim("user", "alice bob")
+            .sign();
+        let (path, query) = split(&url);
+        let claims = signer.verify(&Method::GET, path, query).unwrap();
+        assert_eq!(claims.key_id, "k2");
+        assert_eq!(claims.get("user"), Some("alice bob"));
+        assert_eq!(claims.get("download"), Some("1"));
+        assert!(signer.verify(&Method::HEAD, path, query).is_ok());
+
+        assert_eq!(
+            signer.verify(&Method::PUT, path, query),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify(&Method::GET, "/files/b.pdf", query),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        let tampered = query.unwrap().replace("alice", "carol");
+        assert_eq!(
+            signer.verify(&Method::GET, path, Some(&tampered)),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        let added = format!("{}&admin=1", query.unwrap());
+        assert_eq!(
+            signer.verify(&Method::GET, path, Some(&added)),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify(&Method::GET, path, None),
+            Err(SignedUrlError::MissingSignature)
+        );
+    }
+
+    #[test]
+    fn test_key_rotation_and_expiry() {
+        let old = UrlSigner::new("k1", "secret1");
+        let url = old.url(Method::GET, "/a").sign();
+        let (path, query) = split(&url);
+
+        let rotated = UrlSigner::new("k2", "secret2").fallback_key("k1", "secret1");
+        assert!(rotated.verify(&Method::GET, path, query).is_ok());
+        let removed = UrlSigner::new("k2", "secret2");
+        assert_eq!(
+            removed.verify(&Method::GET, path, query),
+            Err(SignedUrlError::UnknownKey)
+        );
+
+        let url = old
+            .url(Method::GET, "/a")
+            .expires_at(UNIX_EPOCH + Duration::from_secs(1000))
+            .sign();
+        let (path, query) = split(&url);
+        assert_eq!(old.verify(&Method::GET, path, query), Err(SignedUrlError::Expired));
+    }
+
+    #[handler]
+    async fn download(depot: &mut Depot) -> String {
+        let claims = depot.signed_url_claims().unwrap();
+        format!("hello {}", claims.get("user").unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_signed_url_middleware() {
+        let signer = UrlSigner::new("k1", "secret");
+        let url = signer.url(Method::GET, "/files/a.pdf").claim("user", "alice").sign();
+        let service = Service::new(Router::with_path("files/<name>").hoop(signer).get(download));
+
+        let content = TestClient::get(format!("http://127.0.0.1:5801{url}"))
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello alice");
+
+        let res = TestClient::get("http://127.0.0.1:5801/files/a.pdf")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+
+        let res = TestClient::get(format!("http://127.0.0.1:5801{}", url.replace("a.pdf", "b.pdf")))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+    }
+}
@@ -1,19 +1,57 @@
 //! size limiter middleware.
 //!
+//! [`MaxSize`] limits the size of request bodies, and [`MaxResponseSize`] limits the size of response bodies.
+//!
 //! Read more: <https://salvo.rs>
+use std::sync::{Arc, Mutex};
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::body::{BodyMapper, ResBody};
+use salvo_core::http::header::{HeaderName, HeaderValue, CONTENT_LENGTH};
 use salvo_core::http::StatusError;
 use salvo_core::http::{Body, Request, Response};
-use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use salvo_core::hyper::body::Bytes;
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
+
+/// Header set on responses truncated by [`MaxResponseSize`], its value is the size of the original body.
+pub const X_RESPONSE_TRUNCATED: HeaderName = HeaderName::from_static("x-response-truncated");
 
 /// MaxSize
-pub struct MaxSize(pub u64);
+#[non_exhaustive]
+pub struct MaxSize {
+    /// Max size of request body in bytes.
+    pub size: u64,
+    /// Request body size is not limited when skipper returns `true`.
+    pub skipper: Box<dyn Skipper>,
+}
+impl MaxSize {
+    /// Create a new `MaxSize`.
+    #[inline]
+    pub fn new(size: u64) -> Self {
+        MaxSize {
+            size,
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Sets skipper and returns new `MaxSize`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
 #[async_trait]
 impl Handler for MaxSize {
     #[inline]
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
         let size_hint = req.body().size_hint().upper();
         if let Some(upper) = size_hint {
-            if upper > self.0 {
+            if upper > self.size {
                 res.render(StatusError::payload_too_large());
                 ctrl.skip_rest();
             } else {
@@ -28,11 +66,215 @@ impl Handler for MaxSize {
 /// Create a new `MaxSize`.
 #[inline]
 pub fn max_size(size: u64) -> MaxSize {
-    MaxSize(size)
+    MaxSize::new(size)
+}
+
+/// What [`MaxResponseSize`] does with response bodies larger than the limit.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[non_exhaustive]
+pub enum Overflow {
+    /// Responds `500 Internal Server Error` instead, or aborts the body if it is streamed and its size is not
+    /// known before it is sent.
+    #[default]
+    Abort,
+    /// Sends the body up to the limit, the [`X_RESPONSE_TRUNCATED`] header is set if the size is known before
+    /// the body is sent.
+    Truncate,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ResponseLimit {
+    size: u64,
+    overflow: Overflow,
+}
+impl ResponseLimit {
+    fn apply(self, res: &mut Response) {
+        let content_length = res
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        let size = match res.body_mut() {
+            ResBody::None | ResBody::Error(_) => return,
+            ResBody::Once(bytes) => Some(bytes.len() as u64),
+            ResBody::Chunks(chunks) => Some(chunks.iter().map(|chunk| chunk.len() as u64).sum()),
+            _ => content_length,
+        };
+        match size {
+            Some(size) if size <= self.size => {}
+            Some(size) => {
+                tracing::warn!(size, limit = self.size, overflow = ?self.overflow, "response body is too large");
+                match self.overflow {
+                    Overflow::Abort => {
+                        res.headers_mut().remove(CONTENT_LENGTH);
+                        res.render(StatusError::internal_server_error().brief("Response body is too large."));
+                    }
+                    Overflow::Truncate => {
+                        let body = match res.take_body() {
+                            ResBody::Once(bytes) => ResBody::Once(bytes.slice(..self.size as usize)),
+                            ResBody::Chunks(chunks) => {
+                                let mut limiter = BodyLimiter::new(self);
+                                ResBody::Chunks(
+                                    chunks
+                                        .into_iter()
+                                        .filter_map(|chunk| limiter.map(chunk).ok())
+                                        .filter(|chunk| !chunk.is_empty())
+                                        .collect(),
+                                )
+                            }
+                            body => body.map_with(BodyLimiter::new(self)),
+                        };
+                        res.replace_body(body);
+                        res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from(self.size));
+                        res.headers_mut().insert(X_RESPONSE_TRUNCATED, HeaderValue::from(size));
+                    }
+                }
+            }
+            None => {
+                res.map_body(BodyLimiter::new(self));
+            }
+        }
+    }
+}
+
+/// Limits the size of a body chunk by chunk while it is streamed.
+struct BodyLimiter {
+    remaining: u64,
+    limit: ResponseLimit,
+}
+impl BodyLimiter {
+    fn new(limit: ResponseLimit) -> Self {
+        Self {
+            remaining: limit.size,
+            limit,
+        }
+    }
+}
+impl BodyMapper for BodyLimiter {
+    fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+        let len = chunk.len() as u64;
+        if len <= self.remaining {
+            self.remaining -= len;
+            return Ok(chunk);
+        }
+        match self.limit.overflow {
+            Overflow::Abort => {
+                tracing::warn!(limit = self.limit.size, "response body is too large, abort it");
+                Err("response body is too large".into())
+            }
+            Overflow::Truncate => {
+                let keep = self.remaining as usize;
+                self.remaining = 0;
+                Ok(chunk.slice(..keep))
+            }
+        }
+    }
+}
+
+/// Limit shared by nested `MaxResponseSize` middlewares, the innermost one overrides outer ones.
+#[derive(Clone)]
+struct SharedResponseLimit(Arc<Mutex<ResponseLimit>>);
+
+/// Middleware which limits the size of response bodies, it protects the process from accidentally rendering huge
+/// bodies, such as the serialization of an unbounded query result.
+///
+/// If `MaxResponseSize` is added to a router whose parent router also has a `MaxResponseSize`, the inner one
+/// overrides the outer one for requests handled by it, so a route can have a larger limit than others.
+///
+/// Bodies whose size is known, including streams with `Content-Length`, are checked before they are sent,
+/// other streams are checked while they are sent.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::size_limiter::{MaxResponseSize, Overflow};
+///
+/// #[handler]
+/// async fn export() -> &'static str {
+///     "export"
+/// }
+/// let router = Router::new()
+///     .hoop(MaxResponseSize::new(8 * 1024 * 1024))
+///     .push(
+///         Router::with_path("export")
+///             .hoop(MaxResponseSize::new(1024 * 1024 * 1024).overflow(Overflow::Truncate))
+///             .get(export),
+///     );
+/// ```
+#[non_exhaustive]
+pub struct MaxResponseSize {
+    /// Max size of response body in bytes.
+    pub size: u64,
+    /// What to do with larger response bodies, default is [`Overflow::Abort`].
+    pub overflow: Overflow,
+    /// Response body size is not limited by this middleware when skipper returns `true`.
+    pub skipper: Box<dyn Skipper>,
+}
+impl MaxResponseSize {
+    /// Create a new `MaxResponseSize`.
+    #[inline]
+    pub fn new(size: u64) -> Self {
+        MaxResponseSize {
+            size,
+            overflow: Overflow::Abort,
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Sets what to do with larger response bodies.
+    #[inline]
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Sets skipper and returns new `MaxResponseSize`.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+#[async_trait]
+impl Handler for MaxResponseSize {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+        let limit = ResponseLimit {
+            size: self.size,
+            overflow: self.overflow,
+        };
+        if let Ok(SharedResponseLimit(shared)) = depot.obtain::<SharedResponseLimit>() {
+            *shared.lock().unwrap() = limit;
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+        let shared = Arc::new(Mutex::new(limit));
+        depot.inject(SharedResponseLimit(shared.clone()));
+        let flush_limit = shared.clone();
+        res.before_flush(move |res| {
+            let limit = *flush_limit.lock().unwrap();
+            limit.apply(res);
+        });
+        ctrl.call_next(req, depot, res).await;
+        if !res.is_flushed() {
+            let limit = *shared.lock().unwrap();
+            limit.apply(res);
+        }
+    }
+}
+/// Create a new `MaxResponseSize`.
+#[inline]
+pub fn max_response_size(size: u64) -> MaxResponseSize {
+    MaxResponseSize::new(size)
 }
 
 #[cfg(test)]
 mod tests {
+    use salvo_core::handler::RequestSkipper;
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
 
@@ -45,7 +287,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_size_limiter() {
-        let limit_handler = MaxSize(32);
+        let limit_handler = MaxSize::new(32);
         let router = Router::new()
             .hoop(limit_handler)
             .push(Router::with_path("hello").post(hello));
@@ -66,4 +308,72 @@ mod tests {
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
     }
+
+    #[tokio::test]
+    async fn test_size_limiter_skipper() {
+        let limit_handler = max_size(4).skipper(RequestSkipper::new().path_prefix("/upload"));
+        let router = Router::new()
+            .hoop(limit_handler)
+            .push(Router::with_path("hello").post(hello))
+            .push(Router::with_path("upload").post(hello));
+        let service = Service::new(router);
+
+        let res = TestClient::post("http://127.0.0.1:5801/hello")
+            .text("abcdefgh")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
+        let content = TestClient::post("http://127.0.0.1:5801/upload")
+            .text("abcdefgh")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    #[handler]
+    async fn large() -> String {
+        "a".repeat(100)
+    }
+    #[handler]
+    async fn large_stream(res: &mut Response) {
+        let chunks = (0..10).map(|_| Ok::<_, std::io::Error>("aaaaaaaaaa"));
+        res.stream(tokio_stream::iter(chunks));
+    }
+
+    #[tokio::test]
+    async fn test_max_response_size() {
+        let router = Router::new()
+            .hoop(max_response_size(20))
+            .push(Router::with_path("large").get(large))
+            .push(Router::with_path("stream").get(large_stream))
+            .push(
+                Router::with_path("truncate")
+                    .hoop(max_response_size(15).overflow(Overflow::Truncate))
+                    .push(Router::with_path("large").get(large))
+                    .push(Router::with_path("stream").get(large_stream)),
+            )
+            .push(Router::with_path("export").hoop(max_response_size(1000)).get(large));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/large").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::INTERNAL_SERVER_ERROR);
+        let mut res = TestClient::get("http://127.0.0.1:5801/export").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap().len(), 100);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/truncate/large")
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[X_RESPONSE_TRUNCATED], "100");
+        assert_eq!(res.take_string().await.unwrap(), "a".repeat(15));
+        let mut res = TestClient::get("http://127.0.0.1:5801/truncate/stream")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "a".repeat(15));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/stream").send(&service).await;
+        assert!(res.take_string().await.is_err());
+    }
 }
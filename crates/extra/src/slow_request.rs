@@ -0,0 +1,305 @@
+//! Slow request logging middleware.
+//!
+//! [`SlowRequestLogger`] records requests which take longer than a threshold, with the matched route, redacted
+//! params and the time spent by every phase, so tail latency offenders can be found without sampling every
+//! request by tracing:
+//!
+//! - `matching`: from receiving the request until its route is detected.
+//! - `middleware`: time spent by hoops after `SlowRequestLogger`.
+//! - `handler`: time spent by the goal handler.
+//! - `write`: from handlers returning until the body is written.
+//!
+//! Slow requests are logged by `tracing` as warnings, and passed to [`SlowRequestLogger::on_slow`] to feed
+//! metrics.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::slow_request::SlowRequestLogger;
+//!
+//! #[handler]
+//! async fn show_user() -> &'static str {
+//!     "user"
+//! }
+//!
+//! let logger = SlowRequestLogger::new(Duration::from_millis(500))
+//!     .show_param("id")
+//!     .on_slow(|slow| println!("slow request {} takes {:?}", slow.path, slow.total));
+//! let router = Router::new().hoop(logger).push(Router::with_path("users/<id>").get(show_user));
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::body::{BodyMapper, ResBody};
+use salvo_core::http::{Method, Request, Response, StatusCode};
+use salvo_core::hyper::body::Bytes;
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
+
+/// Value of params which are not shown.
+pub const REDACTED: &str = "[redacted]";
+
+/// Callback called with slow requests.
+pub type SlowRequestCallback = Arc<dyn Fn(&SlowRequest) + Send + Sync + 'static>;
+
+/// A request which takes longer than the threshold.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SlowRequest {
+    /// Method of the request.
+    pub method: Method,
+    /// Path of the request.
+    pub path: String,
+    /// Path template of the matched route, such as `/users/<id>`.
+    pub route: Option<String>,
+    /// Path params, values of params which are not shown are [`REDACTED`].
+    pub params: Vec<(String, String)>,
+    /// Status code of the response.
+    pub status: Option<StatusCode>,
+    /// Total time from receiving the request.
+    pub total: Duration,
+    /// Time spent on detecting the route.
+    pub matching: Duration,
+    /// Time spent by hoops.
+    pub middleware: Duration,
+    /// Time spent by the goal handler.
+    pub handler: Duration,
+    /// Time spent on writing the body, `None` if it is not measured.
+    pub write: Option<Duration>,
+}
+
+/// Middleware which logs requests taking longer than a threshold.
+///
+/// It should be the first hoop of the root router, hoops added before it are counted as matching. Measuring the
+/// write phase turns the response body into a streamed body, use [`SlowRequestLogger::measure_write`] to
+/// disable it.
+pub struct SlowRequestLogger {
+    threshold: Duration,
+    shown_params: Vec<String>,
+    measure_write: bool,
+    on_slow: Option<SlowRequestCallback>,
+    skipper: Box<dyn Skipper>,
+}
+impl Debug for SlowRequestLogger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestLogger")
+            .field("threshold", &self.threshold)
+            .field("shown_params", &self.shown_params)
+            .field("measure_write", &self.measure_write)
+            .finish()
+    }
+}
+impl SlowRequestLogger {
+    /// Create a new `SlowRequestLogger` which logs requests taking longer than `threshold`.
+    #[inline]
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            shown_params: Vec::new(),
+            measure_write: true,
+            on_slow: None,
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Show the value of a path param, values of params are redacted by default.
+    #[inline]
+    pub fn show_param(mut self, name: impl Into<String>) -> Self {
+        self.shown_params.push(name.into());
+        self
+    }
+    /// Sets whether the time spent on writing the body is measured, default is `true`.
+    #[inline]
+    pub fn measure_write(mut self, measure_write: bool) -> Self {
+        self.measure_write = measure_write;
+        self
+    }
+    /// Sets a callback called with slow requests, such as recording them in metrics.
+    #[inline]
+    pub fn on_slow(mut self, on_slow: impl Fn(&SlowRequest) + Send + Sync + 'static) -> Self {
+        self.on_slow = Some(Arc::new(on_slow));
+        self
+    }
+    /// Sets skipper, skipped requests are not measured.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+/// Reports a slow request when it is dropped, if the body takes the request over the threshold.
+struct Report {
+    slow: SlowRequest,
+    handled_at: Instant,
+    threshold: Duration,
+    on_slow: Option<SlowRequestCallback>,
+}
+impl Report {
+    fn finish(&mut self, write: Option<Duration>) {
+        if let Some(write) = write {
+            self.slow.write = Some(write);
+            self.slow.total += write;
+        }
+        if self.slow.total < self.threshold {
+            return;
+        }
+        let slow = &self.slow;
+        tracing::warn!(
+            method = %slow.method,
+            path = %slow.path,
+            route = slow.route.as_deref().unwrap_or_default(),
+            params = ?slow.params,
+            status = slow.status.map(|s| s.as_u16()).unwrap_or_default(),
+            total = ?slow.total,
+            matching = ?slow.matching,
+            middleware = ?slow.middleware,
+            handler = ?slow.handler,
+            write = ?slow.write,
+            "slow request"
+        );
+        if let Some(on_slow) = &self.on_slow {
+            on_slow(slow);
+        }
+    }
+}
+
+/// Passes the body through and reports the request when the body is written or dropped.
+struct WriteTimer(Report);
+impl BodyMapper for WriteTimer {
+    #[inline]
+    fn map(&mut self, chunk: Bytes) -> Result<Bytes, BoxedError> {
+        Ok(chunk)
+    }
+}
+impl Drop for WriteTimer {
+    fn drop(&mut self) {
+        let write = self.0.handled_at.elapsed();
+        self.0.finish(Some(write));
+    }
+}
+
+#[async_trait]
+impl Handler for SlowRequestLogger {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+        let started = Instant::now();
+        let matching = req
+            .routed_at()
+            .unwrap_or(started)
+            .saturating_duration_since(req.received_at());
+        ctrl.call_next(req, depot, res).await;
+        let handled_at = Instant::now();
+        let handler = ctrl.goal_elapsed().unwrap_or_default();
+        let slow = SlowRequest {
+            method: req.method().clone(),
+            path: req.uri().path().to_owned(),
+            route: req.matched_path().map(ToOwned::to_owned),
+            params: req
+                .params()
+                .iter()
+                .map(|(name, value)| {
+                    let value = if self.shown_params.iter().any(|shown| shown == name) {
+                        value.clone()
+                    } else {
+                        REDACTED.to_owned()
+                    };
+                    (name.to_owned(), value)
+                })
+                .collect(),
+            // the service responds `200 OK` when handlers of the matched route do not set a status code.
+            status: Some(res.status_code.unwrap_or(StatusCode::OK)),
+            total: handled_at.saturating_duration_since(req.received_at()),
+            matching,
+            middleware: (handled_at - started).saturating_sub(handler),
+            handler,
+            write: None,
+        };
+        let mut report = Report {
+            slow,
+            handled_at,
+            threshold: self.threshold,
+            on_slow: self.on_slow.clone(),
+        };
+        let measure_write =
+            self.measure_write && !res.is_flushed() && !matches!(res.body_mut(), ResBody::None | ResBody::Error(_));
+        if measure_write {
+            let body = res.take_body().map_with(WriteTimer(report));
+            res.replace_body(body);
+        } else {
+            report.finish(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn slow_user() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        "user"
+    }
+    #[handler]
+    async fn fast() -> &'static str {
+        "fast"
+    }
+    #[handler]
+    async fn auth() {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_logger() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let logger = SlowRequestLogger::new(Duration::from_millis(50))
+            .show_param("id")
+            .on_slow({
+                let records = records.clone();
+                move |slow| records.lock().unwrap().push(slow.clone())
+            });
+        let router = Router::new()
+            .hoop(logger)
+            .push(Router::with_path("users/<id>/<token>").hoop(auth).get(slow_user))
+            .push(Router::with_path("fast").get(fast));
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/users/12/secret")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "user");
+        TestClient::get("http://127.0.0.1:5801/fast").send(&service).await;
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        let slow = &records[0];
+        assert_eq!(slow.path, "/users/12/secret");
+        assert_eq!(slow.route.as_deref(), Some("/users/<id>/<token>"));
+        assert_eq!(
+            slow.params,
+            vec![
+                ("id".to_owned(), "12".to_owned()),
+                ("token".to_owned(), REDACTED.to_owned())
+            ]
+        );
+        assert_eq!(slow.status, Some(StatusCode::OK));
+        assert!(slow.handler >= Duration::from_millis(60));
+        assert!(slow.middleware >= Duration::from_millis(30));
+        assert!(slow.write.is_some());
+        assert!(slow.total >= slow.handler + slow.middleware);
+    }
+}
@@ -1,35 +1,283 @@
-//! basic auth middleware.
+//! Timeout middleware.
 //!
 //! Read more: <https://salvo.rs>
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::time::Duration;
 
-use salvo_core::http::{Request, Response, StatusError};
-use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::body::{Body, Frame, ReqBody, SizeHint};
+use salvo_core::http::{DeadlineHeader, Request, Response, StatusError};
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
+use tokio::sync::Notify;
+use tokio::time::{Instant, Sleep};
 
-/// Timeout
+use crate::server_timing::{Metric, ServerTimingDepotExt};
+
+/// Handler called when the request is timeout.
+pub type TimeoutResponder = Arc<dyn Fn(&Request, &mut Response) + Send + Sync + 'static>;
+
+/// Settings of the innermost `Timeout`, the outermost one waits for the deadline and responds with them.
+struct Effective {
+    expires_at: Instant,
+    responder: TimeoutResponder,
+    server_timing: bool,
+}
+
+/// Settings shared by nested `Timeout` middlewares, the innermost one overrides outer ones and notifies the
+/// outermost one to wait for the new deadline.
+struct Shared {
+    effective: Mutex<Effective>,
+    changed: Notify,
+}
+
+#[derive(Clone)]
+struct SharedTimeout(Arc<Shared>);
+
+/// Timeout middleware.
+///
+/// The handler timeout can be configured per router, if `Timeout` is added to a router whose parent router
+/// also has a `Timeout`, the inner one overrides the outer one for requests handled by it. The responder and
+/// [`Timeout::server_timing`] of the inner one override the outer ones too if they are set.
+///
+/// The deadline of the request is set to [`Request::deadline`], so handlers can check the remaining time, and
+/// proxies can forward it to upstreams. With [`Timeout::deadline_header`], a shorter deadline sent by the client
+/// is honored as well.
+///
+/// `Timeout` is about the time spent by handlers. Body read timeout aborts reading request body which is
+/// not completed in time, and upstream timeout of proxies should be configured in the proxy itself,
+/// such as `Proxy::timeout` in `salvo-proxy`, which responds `504 Gateway Timeout`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use salvo_core::prelude::*;
+/// use salvo_extra::timeout::Timeout;
+///
+/// #[handler]
+/// async fn report() -> &'static str {
+///     "report"
+/// }
+/// let router = Router::new()
+///     .hoop(Timeout::new(Duration::from_secs(5)))
+///     .push(Router::with_path("report").hoop(Timeout::new(Duration::from_secs(60))).get(report));
+/// ```
 pub struct Timeout {
     value: Duration,
+    body_read_timeout: Option<Duration>,
+    deadline_header: Option<DeadlineHeader>,
+    server_timing: Option<bool>,
+    responder: Option<TimeoutResponder>,
+    skipper: Box<dyn Skipper>,
 }
 impl Timeout {
     /// Create a new `Timeout`.
     #[inline]
     pub fn new(value: Duration) -> Self {
-        Timeout { value }
+        Timeout {
+            value,
+            body_read_timeout: None,
+            deadline_header: None,
+            server_timing: None,
+            responder: None,
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Sets the timeout of reading request body, the body reading is aborted with error if no data is
+    /// received in this duration.
+    #[inline]
+    pub fn body_read_timeout(mut self, value: Duration) -> Self {
+        self.body_read_timeout = Some(value);
+        self
+    }
+
+    /// Sets the header of deadlines sent by clients, the request times out at the incoming deadline if it is
+    /// earlier than the timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use salvo_core::http::DeadlineHeader;
+    /// use salvo_extra::timeout::Timeout;
+    ///
+    /// let timeout = Timeout::new(Duration::from_secs(30)).deadline_header(DeadlineHeader::grpc_timeout());
+    /// ```
+    #[inline]
+    pub fn deadline_header(mut self, header: DeadlineHeader) -> Self {
+        self.deadline_header = Some(header);
+        self
     }
+
+    /// Sets whether the time spent by handlers is recorded as a `handler` metric of
+    /// [`ServerTiming`](crate::server_timing::ServerTiming), which should be added before this middleware.
+    /// Default is `false`.
+    #[inline]
+    pub fn server_timing(mut self, server_timing: bool) -> Self {
+        self.server_timing = Some(server_timing);
+        self
+    }
+
+    /// Sets the responder called when handlers are timeout.
+    ///
+    /// The default responder renders `500 Internal Server Error`.
+    #[inline]
+    pub fn responder<F>(mut self, responder: F) -> Self
+    where
+        F: Fn(&Request, &mut Response) + Send + Sync + 'static,
+    {
+        self.responder = Some(Arc::new(responder));
+        self
+    }
+
+    /// Sets skipper, handlers are not limited by this timeout when it returns `true`.
+    ///
+    /// Skipped requests are still limited by the timeouts of outer routers.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+fn default_responder(_req: &Request, res: &mut Response) {
+    res.render(StatusError::internal_server_error().brief("Server process the request timeout."));
 }
+
 #[async_trait]
 impl Handler for Timeout {
     #[inline]
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        tokio::select! {
-            _ = ctrl.call_next(req, depot, res) => {},
-            _ = tokio::time::sleep(self.value) => {
-                res.render(StatusError::internal_server_error().brief("Server process the request timeout."));
-                ctrl.skip_rest();
+        if self.skipper.skipped(req, depot) {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+        if let Some(value) = self.body_read_timeout {
+            let body = req.take_body();
+            req.replace_body(ReqBody::Boxed(Box::pin(TimeoutBody::new(body, value))));
+        }
+        let started = Instant::now();
+        let mut expires_at = started + self.value;
+        if let Some(incoming) = self
+            .deadline_header
+            .as_ref()
+            .and_then(|header| header.parse(req.headers()))
+        {
+            expires_at = expires_at.min(Instant::from_std(incoming));
+        }
+        req.set_deadline(Some(expires_at.into_std()));
+        // nested timeout only overrides the settings of outer one, the outer one waits for the deadline.
+        if let Ok(SharedTimeout(shared)) = depot.obtain::<SharedTimeout>() {
+            {
+                let mut effective = shared.effective.lock().unwrap();
+                effective.expires_at = expires_at;
+                if let Some(responder) = &self.responder {
+                    effective.responder = responder.clone();
+                }
+                if let Some(server_timing) = self.server_timing {
+                    effective.server_timing = server_timing;
+                }
+            }
+            shared.changed.notify_one();
+            ctrl.call_next(req, depot, res).await;
+            return;
+        }
+
+        let shared = Arc::new(Shared {
+            effective: Mutex::new(Effective {
+                expires_at,
+                responder: self.responder.clone().unwrap_or_else(|| Arc::new(default_responder)),
+                server_timing: self.server_timing.unwrap_or(false),
+            }),
+            changed: Notify::new(),
+        });
+        depot.inject(SharedTimeout(shared.clone()));
+        let timed_out = {
+            let next = ctrl.call_next(req, depot, res);
+            tokio::pin!(next);
+            loop {
+                let current = shared.effective.lock().unwrap().expires_at;
+                tokio::select! {
+                    _ = &mut next => break false,
+                    // a nested timeout changed the deadline, wait for the new one.
+                    _ = shared.changed.notified() => {}
+                    _ = tokio::time::sleep_until(current) => {
+                        if shared.effective.lock().unwrap().expires_at <= Instant::now() {
+                            break true;
+                        }
+                    }
+                }
             }
+        };
+        let (responder, server_timing) = {
+            let effective = shared.effective.lock().unwrap();
+            (effective.responder.clone(), effective.server_timing)
+        };
+        if timed_out {
+            (*responder)(req, res);
+            ctrl.skip_rest();
+        }
+        if server_timing {
+            let mut metric = Metric::new("handler").duration(started.elapsed());
+            if timed_out {
+                metric = metric.description("timeout");
+            }
+            depot.add_timing(metric);
+        }
+    }
+}
+
+/// Request body which returns error if no data is received in timeout duration.
+struct TimeoutBody {
+    inner: ReqBody,
+    timeout: Duration,
+    sleep: Pin<Box<Sleep>>,
+}
+impl TimeoutBody {
+    fn new(inner: ReqBody, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            sleep: Box::pin(tokio::time::sleep(timeout)),
         }
     }
 }
+impl Body for TimeoutBody {
+    type Data = <ReqBody as Body>::Data;
+    type Error = BoxedError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.inner).poll_frame(cx) {
+            Poll::Ready(frame) => {
+                let deadline = Instant::now() + this.timeout;
+                this.sleep.as_mut().reset(deadline);
+                Poll::Ready(frame.map(|frame| frame.map_err(Into::into)))
+            }
+            Poll::Pending => match this.sleep.as_mut().poll(cx) {
+                Poll::Ready(_) => Poll::Ready(Some(Err(StatusError::request_timeout()
+                    .brief("Read request body timeout.")
+                    .into()))),
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -37,6 +285,7 @@ mod tests {
     use salvo_core::test::{ResponseExt, TestClient};
 
     use super::*;
+    use crate::server_timing::ServerTiming;
 
     #[tokio::test]
     async fn test_timeout_handler() {
@@ -72,4 +321,148 @@ mod tests {
             .unwrap();
         assert!(content.contains("hello"));
     }
+
+    #[tokio::test]
+    async fn test_timeout_per_router() {
+        #[handler]
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "hello"
+        }
+
+        let router = Router::new()
+            .hoop(ServerTiming::new().total(false))
+            .hoop(
+                Timeout::new(Duration::from_millis(100))
+                    .server_timing(true)
+                    .responder(|_req, res| {
+                        res.status_code(StatusCode::GATEWAY_TIMEOUT);
+                        res.render("custom timeout");
+                    }),
+            )
+            .push(Router::with_path("slow").get(slow))
+            .push(
+                Router::with_path("report")
+                    .hoop(Timeout::new(Duration::from_millis(500)))
+                    .get(slow),
+            )
+            .push(
+                Router::with_path("strict")
+                    .hoop(
+                        Timeout::new(Duration::from_millis(50))
+                            .server_timing(false)
+                            .responder(|_req, res| {
+                                res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+                                res.render("strict timeout");
+                            }),
+                    )
+                    .get(slow),
+            );
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/slow").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::GATEWAY_TIMEOUT);
+        let timing = res.headers().get("server-timing").unwrap().to_str().unwrap();
+        assert!(timing.starts_with("handler;dur=") && timing.ends_with(";desc=\"timeout\""));
+        assert_eq!(res.take_string().await.unwrap(), "custom timeout");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/report").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        let timing = res.headers().get("server-timing").unwrap().to_str().unwrap();
+        assert!(timing.starts_with("handler;dur=") && !timing.contains("timeout"));
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/strict").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(res.headers().get("server-timing").is_none());
+        assert_eq!(res.take_string().await.unwrap(), "strict timeout");
+    }
+
+    #[tokio::test]
+    async fn test_nested_timeout_shorter_than_outer() {
+        #[handler]
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            "hello"
+        }
+
+        let router = Router::new().hoop(Timeout::new(Duration::from_secs(5))).push(
+            Router::with_path("slow")
+                .hoop(Timeout::new(Duration::from_millis(100)))
+                .get(slow),
+        );
+        let service = Service::new(router);
+
+        let started = Instant::now();
+        let res = TestClient::get("http://127.0.0.1:5801/slow").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_body_read_timeout() {
+        #[handler]
+        async fn upload(req: &mut Request) -> String {
+            match req.payload().await {
+                Ok(payload) => format!("received {}", payload.len()),
+                Err(e) => format!("error {e}"),
+            }
+        }
+
+        let router = Router::new()
+            .hoop(Timeout::new(Duration::from_secs(5)).body_read_timeout(Duration::from_millis(50)))
+            .post(upload);
+        let service = Service::new(router);
+
+        let content = TestClient::post("http://127.0.0.1:5801/")
+            .body("hello")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "received 5");
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<Frame<<ReqBody as Body>::Data>, BoxedError>>(1);
+        let body = http_body_util::StreamBody::new(tokio_stream::wrappers::ReceiverStream::new(rx));
+        let mut req = TestClient::post("http://127.0.0.1:5801/").build();
+        req.replace_body(ReqBody::Boxed(Box::pin(body)));
+        let content = service.handle(req).await.take_string().await.unwrap();
+        drop(tx);
+        assert!(content.contains("error"));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_header() {
+        #[handler]
+        async fn slow(req: &mut Request) -> String {
+            let remaining = req.remaining_time().unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            format!("{}", remaining.as_millis())
+        }
+
+        let router = Router::new()
+            .hoop(Timeout::new(Duration::from_secs(5)).deadline_header(DeadlineHeader::grpc_timeout()))
+            .get(slow);
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("grpc-timeout", "100m", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains("timeout"));
+
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("grpc-timeout", "10S", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        let remaining: u64 = content.parse().unwrap();
+        assert!(remaining > 4000 && remaining <= 5000);
+    }
 }
@@ -0,0 +1,77 @@
+//! Resolve client ips of requests sent through trusted proxies.
+//!
+//! [`TrustedProxies`] is shared by middlewares which check or record client ips, such as access log, firewall, bot
+//! detection and geoip, so forwarded headers are trusted by the same rules everywhere.
+use std::net::IpAddr;
+
+use salvo_core::http::Request;
+
+/// Resolve client ip from `X-Forwarded-For` header, only when the request is sent by trusted proxies.
+#[derive(Default, Clone, Debug)]
+pub struct TrustedProxies {
+    proxies: Vec<IpAddr>,
+}
+impl TrustedProxies {
+    /// Create new `TrustedProxies`.
+    #[inline]
+    pub fn new(proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self {
+            proxies: proxies.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if the ip is a trusted proxy.
+    #[inline]
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.proxies.contains(ip)
+    }
+
+    /// Resolve client ip of the request.
+    ///
+    /// Addresses in `X-Forwarded-For` are checked from right to left, the first one which is not a trusted
+    /// proxy is the client ip.
+    pub fn client_ip(&self, req: &Request) -> Option<IpAddr> {
+        let remote_ip = req.remote_addr().clone().into_std().map(|addr| addr.ip())?;
+        if !self.is_trusted(&remote_ip) {
+            return Some(remote_ip);
+        }
+        let mut client_ip = remote_ip;
+        for value in req.headers().get_all("x-forwarded-for").iter().rev() {
+            let Ok(value) = value.to_str() else {
+                return Some(client_ip);
+            };
+            for ip in value.rsplit(',') {
+                match ip.trim().parse::<IpAddr>() {
+                    Ok(ip) => {
+                        client_ip = ip;
+                        if !self.is_trusted(&ip) {
+                            return Some(ip);
+                        }
+                    }
+                    Err(_) => return Some(client_ip),
+                }
+            }
+        }
+        Some(client_ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[test]
+    fn test_trusted_proxies() {
+        let proxies = TrustedProxies::new(["127.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()]);
+        let mut req = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-forwarded-for", "1.1.1.1, 2.2.2.2, 10.0.0.2", true)
+            .build();
+        assert_eq!(proxies.client_ip(&req), None);
+        *req.remote_addr_mut() = std::net::SocketAddr::from(([127, 0, 0, 1], 5801)).into();
+        assert_eq!(proxies.client_ip(&req), Some("2.2.2.2".parse().unwrap()));
+        let proxies = TrustedProxies::default();
+        assert_eq!(proxies.client_ip(&req), Some("127.0.0.1".parse().unwrap()));
+    }
+}
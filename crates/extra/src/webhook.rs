@@ -0,0 +1,671 @@
+//! Webhook verification middleware and delivery helper.
+//!
+//! [`WebhookVerifier`] verifies signatures of inbound webhooks, and [`WebhookSender`] signs outbound webhooks
+//! and delivers them with retries. Both sides support the common HMAC-SHA256 schemes of [`Scheme`].
+//!
+//! Read more: <https://www.standardwebhooks.com>
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use salvo_core::http::{HeaderMap, Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::Sha256;
+
+/// Header of Stripe signatures.
+pub const STRIPE_SIGNATURE: &str = "stripe-signature";
+/// Header of GitHub signatures.
+pub const X_HUB_SIGNATURE_256: &str = "x-hub-signature-256";
+/// Header of Standard Webhooks message ids.
+pub const WEBHOOK_ID: &str = "webhook-id";
+/// Header of Standard Webhooks timestamps.
+pub const WEBHOOK_TIMESTAMP: &str = "webhook-timestamp";
+/// Header of Standard Webhooks signatures.
+pub const WEBHOOK_SIGNATURE: &str = "webhook-signature";
+
+/// Signature scheme of webhooks, all of them sign with HMAC-SHA256.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Scheme {
+    /// `Stripe-Signature: t=<timestamp>,v1=<hex>`, the signed content is `<timestamp>.<body>`.
+    Stripe,
+    /// `X-Hub-Signature-256: sha256=<hex>`, the signed content is the body, there is no timestamp.
+    GitHub,
+    /// `webhook-id`, `webhook-timestamp` and `webhook-signature: v1,<base64>` headers, the signed content is
+    /// `<id>.<timestamp>.<body>`. Secrets prefixed with `whsec_` are decoded from base64.
+    StandardWebhooks,
+}
+impl Scheme {
+    fn key(self, secret: &[u8]) -> Vec<u8> {
+        if self == Scheme::StandardWebhooks {
+            if let Some(encoded) = secret.strip_prefix(b"whsec_") {
+                if let Ok(key) = STANDARD.decode(encoded) {
+                    return key;
+                }
+            }
+        }
+        secret.to_vec()
+    }
+    fn signed_content(self, id: &str, timestamp: u64, body: &[u8]) -> Vec<u8> {
+        let prefix = match self {
+            Scheme::Stripe => format!("{timestamp}."),
+            Scheme::GitHub => String::new(),
+            Scheme::StandardWebhooks => format!("{id}.{timestamp}."),
+        };
+        let mut content = prefix.into_bytes();
+        content.extend_from_slice(body);
+        content
+    }
+}
+
+fn mac(key: &[u8], content: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any size");
+    mac.update(content);
+    mac
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Error of webhook signature verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// Signature headers are missing or malformed.
+    MissingSignature,
+    /// No signature matches any secret.
+    InvalidSignature,
+    /// The timestamp is not within the tolerance.
+    Expired,
+}
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSignature => f.write_str("webhook signature is missing"),
+            Self::InvalidSignature => f.write_str("webhook signature is invalid"),
+            Self::Expired => f.write_str("webhook timestamp is not within the tolerance"),
+        }
+    }
+}
+impl StdError for VerifyError {}
+
+/// Signature headers of a request parsed by scheme.
+struct Signatures {
+    id: String,
+    timestamp: Option<u64>,
+    signatures: Vec<Vec<u8>>,
+}
+impl Signatures {
+    fn parse(scheme: Scheme, headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        match scheme {
+            Scheme::Stripe => {
+                let mut timestamp = None;
+                let mut signatures = Vec::new();
+                for item in header(STRIPE_SIGNATURE)?.split(',') {
+                    match item.trim().split_once('=') {
+                        Some(("t", t)) => timestamp = Some(t.parse().ok()?),
+                        Some(("v1", sig)) => signatures.extend(hex::decode(sig).ok()),
+                        _ => {}
+                    }
+                }
+                Some(Self {
+                    id: String::new(),
+                    timestamp: Some(timestamp?),
+                    signatures,
+                })
+            }
+            Scheme::GitHub => {
+                let signature = hex::decode(header(X_HUB_SIGNATURE_256)?.strip_prefix("sha256=")?).ok()?;
+                Some(Self {
+                    id: String::new(),
+                    timestamp: None,
+                    signatures: vec![signature],
+                })
+            }
+            Scheme::StandardWebhooks => Some(Self {
+                id: header(WEBHOOK_ID)?.to_owned(),
+                timestamp: Some(header(WEBHOOK_TIMESTAMP)?.parse().ok()?),
+                signatures: header(WEBHOOK_SIGNATURE)?
+                    .split(' ')
+                    .filter_map(|sig| sig.strip_prefix("v1,"))
+                    .filter_map(|sig| STANDARD.decode(sig).ok())
+                    .collect(),
+            }),
+        }
+    }
+}
+
+/// Middleware verifying signatures of inbound webhooks.
+///
+/// Requests without signature are rejected with `401 Unauthorized`, requests with invalid signature or expired
+/// timestamp are rejected with `403 Forbidden`. The body is read with [`Request::payload_with_max_size`], so it
+/// can still be parsed by the handler.
+///
+/// More than one secret can be added to rotate secrets without downtime.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::webhook::{Scheme, WebhookVerifier};
+///
+/// #[handler]
+/// async fn payment(req: &mut Request) -> &'static str {
+///     let _event = req.payload().await;
+///     "ok"
+/// }
+///
+/// let router = Router::with_path("webhooks/stripe")
+///     .hoop(WebhookVerifier::new(Scheme::Stripe, "whsec_test"))
+///     .post(payment);
+/// ```
+#[derive(Clone, Debug)]
+pub struct WebhookVerifier {
+    scheme: Scheme,
+    keys: Vec<Vec<u8>>,
+    tolerance: Duration,
+    max_size: usize,
+}
+impl WebhookVerifier {
+    /// Create new `WebhookVerifier`, the timestamp tolerance is 5 minutes and the max body size is 1 MiB.
+    #[inline]
+    pub fn new(scheme: Scheme, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            scheme,
+            keys: vec![scheme.key(secret.as_ref())],
+            tolerance: Duration::from_secs(300),
+            max_size: 1024 * 1024,
+        }
+    }
+    /// Add another accepted secret.
+    #[inline]
+    pub fn secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.keys.push(self.scheme.key(secret.as_ref()));
+        self
+    }
+    /// Sets the tolerance between the signed timestamp and now, it is ignored by schemes without timestamp.
+    #[inline]
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+    /// Sets the max body size.
+    #[inline]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Verify the signature of a webhook with its headers and body.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), VerifyError> {
+        let Some(Signatures {
+            id,
+            timestamp,
+            signatures,
+        }) = Signatures::parse(self.scheme, headers)
+        else {
+            return Err(VerifyError::MissingSignature);
+        };
+        if let Some(timestamp) = timestamp {
+            if now().abs_diff(timestamp) > self.tolerance.as_secs() {
+                return Err(VerifyError::Expired);
+            }
+        }
+        let content = self.scheme.signed_content(&id, timestamp.unwrap_or_default(), body);
+        let matched = self.keys.iter().any(|key| {
+            signatures
+                .iter()
+                .any(|signature| mac(key, &content).verify_slice(signature).is_ok())
+        });
+        if matched {
+            Ok(())
+        } else {
+            Err(VerifyError::InvalidSignature)
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for WebhookVerifier {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let body = match req.payload_with_max_size(self.max_size).await {
+            Ok(body) => body.clone(),
+            Err(e) => {
+                tracing::debug!(error = ?e, "read webhook body failed");
+                res.render(StatusError::payload_too_large());
+                ctrl.skip_rest();
+                return;
+            }
+        };
+        match self.verify(req.headers(), &body) {
+            Ok(()) => {}
+            Err(VerifyError::MissingSignature) => {
+                res.render(StatusError::unauthorized().brief("Webhook signature is missing."));
+                ctrl.skip_rest();
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "webhook verification failed");
+                res.render(StatusError::forbidden().brief("Webhook signature is invalid."));
+                ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+/// Result of a successful webhook delivery.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Delivery {
+    /// Id of the message, it is the same for all attempts.
+    pub id: String,
+    /// Number of attempts.
+    pub attempts: usize,
+    /// Status code of the successful response.
+    pub status: StatusCode,
+}
+
+/// Error of webhook delivery.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeliveryError {
+    /// The endpoint responded with a status code which is not retried, such as `400 Bad Request`.
+    Rejected {
+        /// Number of attempts.
+        attempts: usize,
+        /// Status code of the response.
+        status: StatusCode,
+    },
+    /// All attempts failed.
+    Exhausted {
+        /// Number of attempts.
+        attempts: usize,
+        /// Status code of the last response, `None` if the last request failed.
+        status: Option<StatusCode>,
+        /// Error of the last request.
+        error: Option<reqwest::Error>,
+    },
+}
+impl Display for DeliveryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected { attempts, status } => {
+                write!(f, "webhook is rejected with status {status} after {attempts} attempts")
+            }
+            Self::Exhausted {
+                attempts,
+                status: Some(status),
+                ..
+            } => write!(
+                f,
+                "webhook delivery failed with status {status} after {attempts} attempts"
+            ),
+            Self::Exhausted { attempts, error, .. } => match error {
+                Some(e) => write!(f, "webhook delivery failed after {attempts} attempts: {e}"),
+                None => write!(f, "webhook delivery failed after {attempts} attempts"),
+            },
+        }
+    }
+}
+impl StdError for DeliveryError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Exhausted { error: Some(e), .. } => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Signs outbound webhooks and delivers them with retries.
+///
+/// Requests failed with network errors, timeouts, `408 Request Timeout`, `429 Too Many Requests` or `5xx`
+/// status codes are retried with exponential backoff and jitter, `Retry-After` of the response is respected.
+/// Every attempt is signed with a new timestamp, so it is not rejected by the tolerance of the receiver.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_extra::webhook::{Scheme, WebhookSender};
+///
+/// # async fn deliver() {
+/// let sender = WebhookSender::new(Scheme::StandardWebhooks, "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw");
+/// let delivery = sender
+///     .send("https://example.com/webhooks", r#"{"type":"user.created"}"#)
+///     .await
+///     .unwrap();
+/// println!("delivered {} after {} attempts", delivery.id, delivery.attempts);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WebhookSender {
+    scheme: Scheme,
+    key: Vec<u8>,
+    client: reqwest::Client,
+    content_type: HeaderValue,
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+}
+impl WebhookSender {
+    /// Create new `WebhookSender`.
+    ///
+    /// By default the content type is `application/json`, it tries 5 times with backoff starting from 1 second
+    /// up to 5 minutes, and every attempt times out after 15 seconds.
+    #[inline]
+    pub fn new(scheme: Scheme, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            scheme,
+            key: scheme.key(secret.as_ref()),
+            client: reqwest::Client::new(),
+            content_type: HeaderValue::from_static("application/json"),
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            timeout: Duration::from_secs(15),
+        }
+    }
+    /// Sets the http client.
+    #[inline]
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+    /// Sets the content type of webhooks.
+    #[inline]
+    pub fn content_type(mut self, content_type: HeaderValue) -> Self {
+        self.content_type = content_type;
+        self
+    }
+    /// Sets the max number of attempts, including the first one.
+    #[inline]
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+    /// Sets the backoff before the first retry, it is doubled for every retry up to `max_backoff`.
+    #[inline]
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+    /// Sets the max backoff between retries.
+    #[inline]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+    /// Sets the timeout of every attempt.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the signature headers of a webhook.
+    pub fn sign(&self, id: &str, timestamp: u64, body: &[u8]) -> HeaderMap {
+        let content = self.scheme.signed_content(id, timestamp, body);
+        let signature = mac(&self.key, &content).finalize().into_bytes();
+        let mut headers = HeaderMap::new();
+        let value = |value: String| HeaderValue::from_str(&value).expect("signature is a valid header value");
+        match self.scheme {
+            Scheme::Stripe => {
+                headers.insert(
+                    STRIPE_SIGNATURE,
+                    value(format!("t={timestamp},v1={}", hex::encode(signature))),
+                );
+            }
+            Scheme::GitHub => {
+                headers.insert(X_HUB_SIGNATURE_256, value(format!("sha256={}", hex::encode(signature))));
+            }
+            Scheme::StandardWebhooks => {
+                if let Ok(id) = HeaderValue::from_str(id) {
+                    headers.insert(WEBHOOK_ID, id);
+                }
+                headers.insert(WEBHOOK_TIMESTAMP, value(timestamp.to_string()));
+                headers.insert(WEBHOOK_SIGNATURE, value(format!("v1,{}", STANDARD.encode(signature))));
+            }
+        }
+        headers
+    }
+
+    /// Send a webhook with a generated message id.
+    #[inline]
+    pub async fn send(&self, url: &str, body: impl Into<Bytes>) -> Result<Delivery, DeliveryError> {
+        let id = format!("msg_{}", hex::encode(rand::thread_rng().gen::<[u8; 12]>()));
+        self.send_with_id(url, id, body).await
+    }
+
+    /// Send a webhook with the message id, the id should be unique so receivers can deduplicate retries.
+    pub async fn send_with_id(
+        &self,
+        url: &str,
+        id: impl Into<String>,
+        body: impl Into<Bytes>,
+    ) -> Result<Delivery, DeliveryError> {
+        let id = id.into();
+        let body = body.into();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut headers = self.sign(&id, now(), &body);
+            headers.insert(CONTENT_TYPE, self.content_type.clone());
+            let result = self
+                .client
+                .post(url)
+                .headers(headers)
+                .timeout(self.timeout)
+                .body(body.clone())
+                .send()
+                .await;
+            let (status, retry_after, error) = match result {
+                Ok(res) => {
+                    let status = StatusCode::from_u16(res.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+                    if status.is_success() {
+                        return Ok(Delivery { id, attempts, status });
+                    }
+                    if !retryable(status) {
+                        return Err(DeliveryError::Rejected { attempts, status });
+                    }
+                    let retry_after = res
+                        .headers()
+                        .get(RETRY_AFTER.as_str())
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    (Some(status), retry_after, None)
+                }
+                Err(e) => (None, None, Some(e)),
+            };
+            if attempts >= self.max_attempts {
+                return Err(DeliveryError::Exhausted {
+                    attempts,
+                    status,
+                    error,
+                });
+            }
+            let backoff = retry_after
+                .unwrap_or_else(|| self.backoff(attempts))
+                .min(self.max_backoff);
+            tracing::debug!(id, attempts, ?status, ?backoff, "webhook delivery failed, retrying");
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    /// Backoff after the attempt, half of it is random.
+    fn backoff(&self, attempts: usize) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << (attempts - 1).min(16) as u32)
+            .min(self.max_backoff);
+        backoff / 2 + backoff.mul_f64(rand::thread_rng().gen::<f64>() / 2.0)
+    }
+}
+
+fn retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use salvo_core::conn::{Acceptor, Listener};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        for scheme in [Scheme::Stripe, Scheme::GitHub, Scheme::StandardWebhooks] {
+            let sender = WebhookSender::new(scheme, "whsec_c2VjcmV0");
+            let verifier = WebhookVerifier::new(scheme, "old").secret("whsec_c2VjcmV0");
+            let headers = sender.sign("msg_1", now(), b"{}");
+            assert_eq!(verifier.verify(&headers, b"{}"), Ok(()));
+            assert_eq!(verifier.verify(&headers, b"{ }"), Err(VerifyError::InvalidSignature));
+            assert_eq!(
+                verifier.verify(&HeaderMap::new(), b"{}"),
+                Err(VerifyError::MissingSignature)
+            );
+
+            let headers = sender.sign("msg_1", now() - 600, b"{}");
+            let expected = if scheme == Scheme::GitHub {
+                Ok(())
+            } else {
+                Err(VerifyError::Expired)
+            };
+            assert_eq!(verifier.verify(&headers, b"{}"), expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_github_vector() {
+        // Example from the GitHub documentation.
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_HUB_SIGNATURE_256,
+            HeaderValue::from_static("sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"),
+        );
+        let verifier = WebhookVerifier::new(Scheme::GitHub, "It's a Secret to Everybody");
+        assert_eq!(verifier.verify(&headers, b"Hello, World!"), Ok(()));
+    }
+
+    #[handler]
+    async fn hello(req: &mut Request) -> String {
+        String::from_utf8(req.payload().await.unwrap().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_webhook_verifier() {
+        let router = Router::new()
+            .hoop(WebhookVerifier::new(Scheme::Stripe, "secret"))
+            .post(hello);
+        let service = Service::new(router);
+        let sender = WebhookSender::new(Scheme::Stripe, "secret");
+
+        let mut req = TestClient::post("http://127.0.0.1:5801/").text("hello");
+        for (name, value) in &sender.sign("", now(), b"hello") {
+            req = req.add_header(name, value, true);
+        }
+        let mut res = req.send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let mut req = TestClient::post("http://127.0.0.1:5801/").text("hello");
+        for (name, value) in &WebhookSender::new(Scheme::Stripe, "other").sign("", now(), b"hello") {
+            req = req.add_header(name, value, true);
+        }
+        let res = req.send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+
+        let res = TestClient::post("http://127.0.0.1:5801/")
+            .text("hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+    }
+
+    struct Flaky {
+        count: Arc<AtomicUsize>,
+        failures: usize,
+        status: StatusCode,
+    }
+    #[async_trait]
+    impl Handler for Flaky {
+        async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            if self.count.fetch_add(1, Ordering::SeqCst) < self.failures {
+                res.status_code(self.status);
+            } else {
+                res.render("ok");
+            }
+        }
+    }
+
+    async fn serve(failures: usize, status: StatusCode) -> (String, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let router = Router::new()
+            .hoop(WebhookVerifier::new(Scheme::StandardWebhooks, "whsec_c2VjcmV0"))
+            .post(Flaky {
+                count: count.clone(),
+                failures,
+                status,
+            });
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+        (format!("http://{addr}/"), count)
+    }
+
+    fn sender() -> WebhookSender {
+        WebhookSender::new(Scheme::StandardWebhooks, "whsec_c2VjcmV0")
+            .http_client(reqwest::Client::builder().no_proxy().build().unwrap())
+            .initial_backoff(Duration::from_millis(10))
+            .max_attempts(3)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sender() {
+        let (url, count) = serve(2, StatusCode::SERVICE_UNAVAILABLE).await;
+        let delivery = sender().send_with_id(&url, "msg_1", "{}").await.unwrap();
+        assert_eq!(delivery.id, "msg_1");
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status, StatusCode::OK);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        let (url, count) = serve(3, StatusCode::INTERNAL_SERVER_ERROR).await;
+        let err = sender().send(&url, "{}").await.unwrap_err();
+        assert!(matches!(
+            err,
+            DeliveryError::Exhausted {
+                attempts: 3,
+                status: Some(StatusCode::INTERNAL_SERVER_ERROR),
+                ..
+            }
+        ));
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        let (url, count) = serve(1, StatusCode::BAD_REQUEST).await;
+        let err = sender().send(&url, "{}").await.unwrap_err();
+        assert!(matches!(
+            err,
+            DeliveryError::Rejected {
+                attempts: 1,
+                status: StatusCode::BAD_REQUEST
+            }
+        ));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,331 @@
+//! Hub for managing WebSocket connections, rooms and broadcasting.
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures_util::sink::SinkExt;
+use futures_util::stream::{SplitStream, StreamExt};
+use salvo_core::Error;
+use tokio::sync::mpsc;
+
+use super::rooms::RoomRegistry;
+use super::{Message, WebSocket};
+
+/// Id of a connection registered in [`Hub`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct ConnId(u64);
+impl Display for ConnId {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct HubInner {
+    next_id: AtomicU64,
+    queue_size: usize,
+    registry: RoomRegistry<ConnId, mpsc::Sender<Message>>,
+}
+
+/// Hub manages WebSocket connections with named rooms.
+///
+/// Every registered connection has a bounded send queue, [`Hub::send_to`] waits if the queue is full, and
+/// broadcasting disconnects connections whose queue is full, so a slow client never blocks others.
+/// Connections are removed from the hub and all rooms when [`HubConnection`] is dropped or the client
+/// is disconnected.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::websocket::hub::Hub;
+/// use salvo_extra::websocket::{Message, WebSocketUpgrade};
+///
+/// struct Chat {
+///     hub: Hub,
+/// }
+/// #[async_trait]
+/// impl Handler for Chat {
+///     async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+///         let hub = self.hub.clone();
+///         WebSocketUpgrade::new()
+///             .upgrade(req, res, |ws| async move {
+///                 let mut conn = hub.register(ws);
+///                 conn.join("lobby");
+///                 while let Some(Ok(msg)) = conn.recv().await {
+///                     hub.broadcast_to("lobby", msg);
+///                 }
+///             })
+///             .await
+///             .ok();
+///     }
+/// }
+/// let router = Router::with_path("chat").goal(Chat { hub: Hub::new() });
+/// ```
+#[derive(Clone)]
+pub struct Hub {
+    inner: Arc<HubInner>,
+}
+impl Default for Hub {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl fmt::Debug for Hub {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hub")
+            .field("queue_size", &self.inner.queue_size)
+            .field("connections", &self.connection_count())
+            .finish()
+    }
+}
+impl Hub {
+    /// Create new `Hub`, the send queue size of every connection is 64.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_queue_size(64)
+    }
+
+    /// Create new `Hub` with send queue size of every connection.
+    pub fn with_queue_size(queue_size: usize) -> Self {
+        Self {
+            inner: Arc::new(HubInner {
+                next_id: AtomicU64::new(1),
+                queue_size: queue_size.max(1),
+                registry: RoomRegistry::default(),
+            }),
+        }
+    }
+
+    /// Register a WebSocket to the hub.
+    ///
+    /// Messages sent by the hub are written to the socket by a spawned task, and messages received
+    /// from the socket are read by [`HubConnection::recv`].
+    pub fn register(&self, ws: WebSocket) -> HubConnection {
+        let id = ConnId(self.inner.next_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, mut rx) = mpsc::channel::<Message>(self.inner.queue_size);
+        let (mut sink, stream) = ws.split();
+        self.inner.registry.insert(id, tx);
+
+        let hub = self.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if let Err(e) = sink.send(msg).await {
+                    tracing::debug!(error = ?e, conn_id = %id, "websocket send failed");
+                    break;
+                }
+            }
+            hub.remove(id);
+            sink.close().await.ok();
+        });
+        HubConnection {
+            id,
+            hub: self.clone(),
+            stream,
+        }
+    }
+
+    /// Remove a connection from the hub and all rooms, its socket is closed after queued messages are sent.
+    pub fn remove(&self, id: ConnId) {
+        self.inner.registry.remove(&id);
+    }
+
+    /// Add a connection to a room.
+    pub fn join(&self, id: ConnId, room: impl Into<String>) {
+        self.inner.registry.join(&id, room.into());
+    }
+
+    /// Remove a connection from a room, empty rooms are removed.
+    pub fn leave(&self, id: ConnId, room: &str) {
+        self.inner.registry.leave(&id, room);
+    }
+
+    /// Send a message to a connection, it waits if the send queue of the connection is full.
+    pub async fn send_to(&self, id: ConnId, msg: Message) -> Result<(), Error> {
+        match self.inner.registry.get(&id) {
+            Some(tx) => tx
+                .send(msg)
+                .await
+                .map_err(|_| Error::other(format!("connection {id} is closed"))),
+            None => Err(Error::other(format!("connection {id} is not found"))),
+        }
+    }
+
+    /// Send a message to all connections, returns the number of connections which the message is queued to.
+    pub fn broadcast(&self, msg: Message) -> usize {
+        self.deliver(self.inner.registry.targets(None), msg)
+    }
+
+    /// Send a message to all connections in a room, returns the number of connections which the message
+    /// is queued to.
+    pub fn broadcast_to(&self, room: &str, msg: Message) -> usize {
+        self.deliver(self.inner.registry.targets(Some(room)), msg)
+    }
+
+    fn deliver(&self, targets: Vec<(ConnId, mpsc::Sender<Message>)>, msg: Message) -> usize {
+        let mut count = 0;
+        for (id, tx) in targets {
+            match tx.try_send(msg.clone()) {
+                Ok(()) => count += 1,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!(conn_id = %id, "websocket send queue is full, connection is removed");
+                    self.remove(id);
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => self.remove(id),
+            }
+        }
+        count
+    }
+
+    /// Get the number of connections.
+    pub fn connection_count(&self) -> usize {
+        self.inner.registry.len()
+    }
+
+    /// Get names of all rooms.
+    pub fn rooms(&self) -> Vec<String> {
+        self.inner.registry.rooms()
+    }
+
+    /// Get ids of connections in a room.
+    pub fn room_members(&self, room: &str) -> Vec<ConnId> {
+        self.inner.registry.room_members(room)
+    }
+}
+
+/// A connection registered in [`Hub`], it is removed from the hub when dropped.
+pub struct HubConnection {
+    id: ConnId,
+    hub: Hub,
+    stream: SplitStream<WebSocket>,
+}
+impl fmt::Debug for HubConnection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HubConnection").field("id", &self.id).finish()
+    }
+}
+impl HubConnection {
+    /// Get the id of this connection.
+    #[inline]
+    pub fn id(&self) -> ConnId {
+        self.id
+    }
+
+    /// Get the hub of this connection.
+    #[inline]
+    pub fn hub(&self) -> &Hub {
+        &self.hub
+    }
+
+    /// Receive another message from the client.
+    ///
+    /// Returns `None` if the stream has closed.
+    #[inline]
+    pub async fn recv(&mut self) -> Option<Result<Message, Error>> {
+        self.stream.next().await
+    }
+
+    /// Send a message to this connection.
+    #[inline]
+    pub async fn send(&self, msg: Message) -> Result<(), Error> {
+        self.hub.send_to(self.id, msg).await
+    }
+
+    /// Join a room.
+    #[inline]
+    pub fn join(&self, room: impl Into<String>) {
+        self.hub.join(self.id, room);
+    }
+
+    /// Leave a room.
+    #[inline]
+    pub fn leave(&self, room: &str) {
+        self.hub.leave(self.id, room);
+    }
+}
+impl Drop for HubConnection {
+    fn drop(&mut self) {
+        self.hub.remove(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use salvo_core::prelude::*;
+    use tokio_tungstenite::tungstenite::protocol;
+
+    use super::*;
+    use crate::websocket::tests::connect_client;
+    use crate::websocket::WebSocketUpgrade;
+
+    struct Chat {
+        hub: Hub,
+    }
+    #[async_trait]
+    impl Handler for Chat {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let hub = self.hub.clone();
+            WebSocketUpgrade::new()
+                .upgrade(req, res, |ws| async move {
+                    let mut conn = hub.register(ws);
+                    let mut room = String::new();
+                    while let Some(Ok(msg)) = conn.recv().await {
+                        let Ok(text) = msg.to_str() else {
+                            continue;
+                        };
+                        if let Some(name) = text.strip_prefix("join:") {
+                            room = name.to_owned();
+                            conn.join(name);
+                            conn.send(Message::text("joined")).await.unwrap();
+                        } else {
+                            hub.broadcast_to(&room, Message::text(text));
+                        }
+                    }
+                })
+                .await
+                .ok();
+        }
+    }
+
+    async fn next_text<S>(client: &mut tokio_tungstenite::WebSocketStream<S>) -> Option<String>
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        match tokio::time::timeout(Duration::from_millis(200), client.next()).await {
+            Ok(Some(Ok(msg))) => msg.into_text().ok(),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hub_rooms() {
+        let hub = Hub::new();
+        let mut client1 = connect_client(Router::new().goal(Chat { hub: hub.clone() })).await;
+        let mut client2 = connect_client(Router::new().goal(Chat { hub: hub.clone() })).await;
+        let mut client3 = connect_client(Router::new().goal(Chat { hub: hub.clone() })).await;
+        for (client, room) in [(&mut client1, "a"), (&mut client2, "a"), (&mut client3, "b")] {
+            client
+                .send(protocol::Message::text(format!("join:{room}")))
+                .await
+                .unwrap();
+            assert_eq!(next_text(client).await.unwrap(), "joined");
+        }
+        assert_eq!(hub.connection_count(), 3);
+        assert_eq!(hub.room_members("a").len(), 2);
+
+        client1.send(protocol::Message::text("hello")).await.unwrap();
+        assert_eq!(next_text(&mut client1).await.unwrap(), "hello");
+        assert_eq!(next_text(&mut client2).await.unwrap(), "hello");
+        assert!(next_text(&mut client3).await.is_none());
+
+        drop(client2);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(hub.connection_count(), 2);
+        assert_eq!(hub.room_members("a").len(), 1);
+        assert_eq!(hub.broadcast(Message::text("all")), 2);
+    }
+}
@@ -10,7 +10,8 @@ use std::borrow::Cow;
 use std::fmt::{self, Formatter};
 use std::future::Future;
 use std::pin::Pin;
-use std::task::{Context, Poll, ready};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
 
 use futures_util::sink::{Sink, SinkExt};
 use futures_util::stream::{Stream, StreamExt};
@@ -21,11 +22,15 @@ use salvo_core::http::headers::{Connection, HeaderMapExt, SecWebsocketAccept, Se
 use salvo_core::http::{StatusCode, StatusError};
 use salvo_core::rt::tokio::TokioIo;
 use salvo_core::{Error, Request, Response};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
 use tokio_tungstenite::{
     tungstenite::protocol::{self, WebSocketConfig},
     WebSocketStream,
 };
 
+pub mod hub;
+pub(crate) mod rooms;
+
 /// Creates a WebSocket Handler.
 /// Request:
 /// - Method must be `GET`
@@ -39,9 +44,13 @@ use tokio_tungstenite::{
 /// - Header `connection: upgrade`
 /// - Header `upgrade: websocket`
 /// - Header `sec-websocket-accept` with the hash value of the received key.
+///
+/// The `permessage-deflate` extension is not implemented yet, the underlying `tungstenite` does not support
+/// it, so it is never negotiated and clients fall back to uncompressed messages.
 #[allow(missing_debug_implementations)]
 pub struct WebSocketUpgrade {
     config: Option<WebSocketConfig>,
+    keepalive: Option<(Duration, Duration)>,
 }
 
 impl Default for WebSocketUpgrade {
@@ -55,13 +64,30 @@ impl WebSocketUpgrade {
     /// Create new `WebSocketUpgrade`.
     #[inline]
     pub fn new() -> Self {
-        WebSocketUpgrade { config: None }
+        WebSocketUpgrade {
+            config: None,
+            keepalive: None,
+        }
     }
 
     /// Create new `WebSocketUpgrade` with config.
     #[inline]
     pub fn with_config(config: WebSocketConfig) -> Self {
-        WebSocketUpgrade { config: Some(config) }
+        WebSocketUpgrade {
+            config: Some(config),
+            keepalive: None,
+        }
+    }
+
+    /// Sends ping messages to the client every `interval`, and closes the socket with error if nothing
+    /// is received from the client in `timeout`, pong messages replied by the client are also counted.
+    ///
+    /// Pings are sent by a timer while the socket is received from or sent to, pong messages are only read
+    /// while receiving, so the socket should be kept polled by [`WebSocket::recv`] or as a `Stream`.
+    #[inline]
+    pub fn keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some((interval, timeout));
+        self
     }
 
     /// The target minimum size of the write buffer to reach before writing the data
@@ -124,7 +150,6 @@ impl WebSocketUpgrade {
         self
     }
 
-
     /// Upgrade websocket request.
     #[inline]
     pub async fn upgrade<F, Fut>(&self, req: &mut Request, res: &mut Response, callback: F) -> Result<(), StatusError>
@@ -174,14 +199,18 @@ impl WebSocketUpgrade {
 
         if let Some(on_upgrade) = req.extensions_mut().remove::<OnUpgrade>() {
             let config = self.config;
+            let keepalive = self.keepalive;
             tokio::spawn(async move {
-                let socket = on_upgrade
+                let mut socket = on_upgrade
                     .and_then(move |upgraded| {
                         tracing::debug!("websocket upgrade complete");
                         WebSocket::from_raw_socket(upgraded, protocol::Role::Server, config).map(Ok)
                     })
                     .await
                     .expect("connection upgrade failed");
+                if let Some((interval, timeout)) = keepalive {
+                    socket.keepalive = Some(KeepAlive::new(interval, timeout));
+                }
                 callback(socket).await;
             });
             Ok(())
@@ -199,6 +228,30 @@ impl WebSocketUpgrade {
 /// `WebSocket`.
 pub struct WebSocket {
     inner: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
+    keepalive: Option<KeepAlive>,
+}
+
+struct KeepAlive {
+    interval: Interval,
+    timeout: Duration,
+    last_seen: Instant,
+    ping_pending: bool,
+    flush_pending: bool,
+    dead: bool,
+}
+impl KeepAlive {
+    fn new(interval: Duration, timeout: Duration) -> Self {
+        let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            interval: ticker,
+            timeout,
+            last_seen: Instant::now(),
+            ping_pending: false,
+            flush_pending: false,
+            dead: false,
+        }
+    }
 }
 
 impl WebSocket {
@@ -209,7 +262,7 @@ impl WebSocket {
         config: Option<protocol::WebSocketConfig>,
     ) -> Self {
         WebSocketStream::from_raw_socket(TokioIo::new(upgraded), role, config)
-            .map(|inner| WebSocket { inner })
+            .map(|inner| WebSocket { inner, keepalive: None })
             .await
     }
 
@@ -222,7 +275,7 @@ impl WebSocket {
 
     /// Send a message.
     pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
-        self.inner.send(msg.inner).await.map_err(Error::other)
+        SinkExt::send(self, msg).await
     }
 
     /// Gracefully close this websocket.
@@ -230,6 +283,42 @@ impl WebSocket {
     pub async fn close(mut self) -> Result<(), Error> {
         future::poll_fn(|cx| Pin::new(&mut self).poll_close(cx)).await
     }
+
+    /// Sends ping when the keepalive timer is ticked, and checks whether the peer is still alive.
+    ///
+    /// The timer registers the waker of current task, so a pending `recv` or `send` is woken to send pings.
+    fn poll_keepalive(&mut self, cx: &mut Context<'_>) -> Result<(), Error> {
+        let Some(keepalive) = &mut self.keepalive else {
+            return Ok(());
+        };
+        if keepalive.dead {
+            return Err(Error::other("websocket peer is not responding"));
+        }
+        while keepalive.interval.poll_tick(cx).is_ready() {
+            keepalive.ping_pending = true;
+        }
+        if keepalive.last_seen.elapsed() > keepalive.timeout {
+            tracing::debug!("websocket peer is not responding");
+            keepalive.dead = true;
+            return Err(Error::other("websocket peer is not responding"));
+        }
+        if keepalive.ping_pending {
+            if let Poll::Ready(ready) = Pin::new(&mut self.inner).poll_ready(cx) {
+                keepalive.ping_pending = false;
+                ready
+                    .and_then(|_| Pin::new(&mut self.inner).start_send(protocol::Message::Ping(vec![])))
+                    .map_err(Error::other)?;
+                keepalive.flush_pending = true;
+            }
+        }
+        if keepalive.flush_pending {
+            if let Poll::Ready(result) = Pin::new(&mut self.inner).poll_flush(cx) {
+                keepalive.flush_pending = false;
+                result.map_err(Error::other)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Stream for WebSocket {
@@ -237,7 +326,18 @@ impl Stream for WebSocket {
 
     #[inline]
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+        let this = &mut *self;
+        if this.keepalive.as_ref().map(|keepalive| keepalive.dead).unwrap_or(false) {
+            return Poll::Ready(None);
+        }
+        if let Err(e) = this.poll_keepalive(cx) {
+            return Poll::Ready(Some(Err(e)));
+        }
+        let item = ready!(Pin::new(&mut this.inner).poll_next(cx));
+        if let (Some(keepalive), Some(Ok(_))) = (&mut this.keepalive, &item) {
+            keepalive.last_seen = Instant::now();
+        }
+        match item {
             Some(Ok(item)) => Poll::Ready(Some(Ok(Message { inner: item }))),
             Some(Err(e)) => {
                 tracing::debug!("websocket poll error: {}", e);
@@ -256,6 +356,7 @@ impl Sink<Message> for WebSocket {
 
     #[inline]
     fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_keepalive(cx)?;
         Pin::new(&mut self.inner).poll_ready(cx).map_err(Error::other)
     }
 
@@ -266,6 +367,7 @@ impl Sink<Message> for WebSocket {
 
     #[inline]
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.poll_keepalive(cx)?;
         Pin::new(&mut self.inner).poll_flush(cx).map_err(Error::other)
     }
 
@@ -428,7 +530,7 @@ impl Into<Vec<u8>> for Message {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use salvo_core::conn::{Acceptor, Listener};
     use salvo_core::http::header::*;
     use salvo_core::prelude::*;
@@ -488,4 +590,132 @@ mod tests {
 
         assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
     }
+
+    #[tokio::test]
+    async fn test_websocket_with_test_client() {
+        use salvo_core::test::{TestClient, WebSocketMessage};
+
+        let service = Service::new(Router::new().goal(connect));
+        let mut client = TestClient::websocket("http://127.0.0.1:5801/")
+            .connect(&service)
+            .await
+            .unwrap();
+        assert_eq!(client.status_code(), StatusCode::SWITCHING_PROTOCOLS);
+        client.send_text("hello").await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap().unwrap(),
+            WebSocketMessage::Text("hello".into())
+        );
+        client.close().await.unwrap();
+    }
+
+    pub(crate) async fn connect_client(router: Router) -> WebSocketStream<tokio::net::TcpStream> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                format!(
+                    "GET / HTTP/1.1\r\nhost: {addr}\r\nupgrade: websocket\r\nconnection: Upgrade\r\n\
+                     sec-websocket-key: 6D69KGBOr4Re+Nj6zx9aQA==\r\nsec-websocket-version: 13\r\n\r\n"
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        // read response head byte by byte, so websocket frames are not consumed.
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        assert!(head.starts_with(b"HTTP/1.1 101"));
+        WebSocketStream::from_raw_socket(stream, protocol::Role::Client, None).await
+    }
+
+    struct Probe {
+        upgrade: WebSocketUpgrade,
+        tx: std::sync::Mutex<Option<tokio::sync::oneshot::Sender<bool>>>,
+    }
+    #[async_trait]
+    impl Handler for Probe {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let tx = self.tx.lock().unwrap().take();
+            self.upgrade
+                .upgrade(req, res, |mut ws| async move {
+                    let mut failed = false;
+                    while let Some(msg) = ws.recv().await {
+                        if msg.is_err() {
+                            failed = true;
+                            break;
+                        }
+                    }
+                    tx.unwrap().send(failed).ok();
+                })
+                .await
+                .ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_keepalive() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let upgrade = WebSocketUpgrade::new().keepalive(Duration::from_millis(50), Duration::from_millis(200));
+        let router = Router::new().goal(Probe {
+            upgrade,
+            tx: std::sync::Mutex::new(Some(tx)),
+        });
+        let mut client = connect_client(router).await;
+        let msg = client.next().await.unwrap().unwrap();
+        assert!(msg.is_ping());
+        // stop reading, so pings are not replied.
+        assert!(tokio::time::timeout(Duration::from_secs(2), rx).await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_keepalive_while_sending() {
+        #[handler]
+        async fn sender(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+            WebSocketUpgrade::new()
+                .keepalive(Duration::from_millis(50), Duration::from_secs(5))
+                .upgrade(req, res, |mut ws| async move {
+                    for _ in 0..10 {
+                        if ws.send(Message::text("tick")).await.is_err() {
+                            break;
+                        }
+                        tokio::time::sleep(Duration::from_millis(30)).await;
+                    }
+                })
+                .await
+        }
+        let mut client = connect_client(Router::new().goal(sender)).await;
+        let mut pinged = false;
+        while let Some(Ok(msg)) = client.next().await {
+            if msg.is_ping() {
+                pinged = true;
+                break;
+            }
+        }
+        assert!(pinged);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_max_message_size() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let router = Router::new().goal(Probe {
+            upgrade: WebSocketUpgrade::new().max_message_size(8),
+            tx: std::sync::Mutex::new(Some(tx)),
+        });
+        let mut client = connect_client(router).await;
+        client
+            .send(protocol::Message::text("message is too long"))
+            .await
+            .unwrap();
+        assert!(tokio::time::timeout(Duration::from_secs(2), rx).await.unwrap().unwrap());
+    }
 }
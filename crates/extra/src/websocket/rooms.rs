@@ -0,0 +1,111 @@
+//! Registry of connections with named rooms, it is shared by the hubs of WebSocket and engine.io.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::Mutex;
+
+struct State<K, V> {
+    members: HashMap<K, V>,
+    rooms: HashMap<String, HashSet<K>>,
+}
+
+/// Registry of connections with named rooms.
+///
+/// Values are the send queues of connections, so hubs can deliver messages in their own way. Connections are
+/// removed from all rooms when they are removed, and empty rooms are removed.
+pub(crate) struct RoomRegistry<K, V> {
+    state: Mutex<State<K, V>>,
+}
+impl<K, V> Default for RoomRegistry<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(State {
+                members: HashMap::new(),
+                rooms: HashMap::new(),
+            }),
+        }
+    }
+}
+impl<K, V> RoomRegistry<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    /// Register a connection.
+    pub(crate) fn insert(&self, id: K, value: V) {
+        self.state.lock().unwrap().members.insert(id, value);
+    }
+
+    /// Get the send queue of a connection.
+    pub(crate) fn get(&self, id: &K) -> Option<V> {
+        self.state.lock().unwrap().members.get(id).cloned()
+    }
+
+    /// Remove a connection from the registry and all rooms.
+    pub(crate) fn remove(&self, id: &K) -> Option<V> {
+        let mut state = self.state.lock().unwrap();
+        state.rooms.retain(|_, members| {
+            members.remove(id);
+            !members.is_empty()
+        });
+        state.members.remove(id)
+    }
+
+    /// Add a connection to a room, it is ignored if the connection is not registered.
+    pub(crate) fn join(&self, id: &K, room: String) {
+        let mut state = self.state.lock().unwrap();
+        if state.members.contains_key(id) {
+            state.rooms.entry(room).or_default().insert(id.clone());
+        }
+    }
+
+    /// Remove a connection from a room, empty rooms are removed.
+    pub(crate) fn leave(&self, id: &K, room: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(members) = state.rooms.get_mut(room) {
+            members.remove(id);
+            if members.is_empty() {
+                state.rooms.remove(room);
+            }
+        }
+    }
+
+    /// Get all connections, or connections in a room if `room` is `Some`, the lock is released before messages
+    /// are delivered to them.
+    pub(crate) fn targets(&self, room: Option<&str>) -> Vec<(K, V)> {
+        let state = self.state.lock().unwrap();
+        match room {
+            None => state.members.iter().map(|(id, v)| (id.clone(), v.clone())).collect(),
+            Some(room) => state
+                .rooms
+                .get(room)
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| state.members.get(id).map(|v| (id.clone(), v.clone())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Get the number of connections.
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().unwrap().members.len()
+    }
+
+    /// Get names of all rooms.
+    pub(crate) fn rooms(&self) -> Vec<String> {
+        self.state.lock().unwrap().rooms.keys().cloned().collect()
+    }
+
+    /// Get ids of connections in a room.
+    pub(crate) fn room_members(&self, room: &str) -> Vec<K> {
+        self.state
+            .lock()
+            .unwrap()
+            .rooms
+            .get(room)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
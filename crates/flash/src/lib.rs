@@ -195,9 +195,12 @@ pub trait FlashStore: Debug + Send + Sync + 'static {
 }
 
 /// A trait for `Depot` to get flash messages.
+///
+/// Incoming flash is the messages set by the previous request, and outgoing flash is the messages which will
+/// be available in the next request, flash messages only live for one request.
 pub trait FlashDepotExt {
     /// Get incoming flash.
-    fn incoming_flash(&mut self) -> Option<&Flash>;
+    fn incoming_flash(&self) -> Option<&Flash>;
     /// Get outgoing flash.
     fn outgoing_flash(&self) -> &Flash;
     /// Get mutable outgoing flash.
@@ -206,7 +209,7 @@ pub trait FlashDepotExt {
 
 impl FlashDepotExt for Depot {
     #[inline]
-    fn incoming_flash(&mut self) -> Option<&Flash> {
+    fn incoming_flash(&self) -> Option<&Flash> {
         self.get::<Flash>(INCOMING_FLASH_KEY).ok()
     }
 
@@ -242,7 +245,7 @@ impl<S> FlashHandler<S> {
 
     /// Sets the minimum level of messages to be displayed.
     #[inline]
-    pub fn minimum_level(&mut self, level: impl Into<Option<FlashLevel>>) -> &mut Self {
+    pub fn minimum_level(mut self, level: impl Into<Option<FlashLevel>>) -> Self {
         self.minimum_level = level.into();
         self
     }
@@ -260,8 +263,11 @@ where
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
         let mut has_incoming = false;
-        if let Some(flash) = self.store.load_flash(req, depot).await {
+        if let Some(mut flash) = self.store.load_flash(req, depot).await {
             has_incoming = !flash.is_empty();
+            if let Some(min_level) = self.minimum_level {
+                flash.0.retain(|msg| msg.level >= min_level);
+            }
             depot.insert(INCOMING_FLASH_KEY, flash);
         }
         depot.insert(OUTGOING_FLASH_KEY, Flash(vec![]));
@@ -344,6 +350,38 @@ mod tests {
         assert!(respone.take_string().await.unwrap().is_empty());
     }
 
+    #[cfg(feature = "cookie-store")]
+    #[tokio::test]
+    async fn test_cookie_store_minimum_level() {
+        #[handler]
+        pub async fn set_levels(depot: &mut Depot, res: &mut Response) {
+            depot
+                .outgoing_flash_mut()
+                .debug("Debug; message")
+                .warning("Saved; \"quoted\", done")
+                .error("Failed");
+            res.render(Redirect::other("/get"));
+        }
+        let router = Router::new()
+            .hoop(CookieStore::new().into_handler().minimum_level(FlashLevel::Warning))
+            .push(Router::with_path("get").get(get_flash))
+            .push(Router::with_path("set").get(set_levels));
+        let service = Service::new(router);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/set").send(&service).await;
+        let cookie = respone.headers().get(SET_COOKIE).unwrap().to_str().unwrap();
+        let cookie = cookie.split(';').next().unwrap().to_owned();
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/get")
+            .add_header(COOKIE, cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            respone.take_string().await.unwrap(),
+            "Saved; \"quoted\", done - warning\nFailed - error\n"
+        );
+    }
+
     #[cfg(feature = "session-store")]
     #[tokio::test]
     async fn test_session_store() {
@@ -41,15 +41,17 @@ impl FlashStore for SessionStore {
         depot.session().and_then(|s| s.get::<Flash>(&self.name))
     }
     async fn save_flash(&self, _req: &mut Request, depot: &mut Depot, _res: &mut Response, flash: Flash) {
-        if let Err(e) = depot
-            .session_mut()
-            .expect("session must be exist")
-            .insert(&self.name, flash)
-        {
+        let Some(session) = depot.session_mut() else {
+            tracing::error!("session not found, `SessionHandler` should be added before `FlashHandler`");
+            return;
+        };
+        if let Err(e) = session.insert(&self.name, flash) {
             tracing::error!(error = ?e, "save flash to session failed");
         }
     }
     async fn clear_flash(&self, depot: &mut Depot, _res: &mut Response) {
-        depot.session_mut().expect("session must be exist").remove(&self.name);
+        if let Some(session) = depot.session_mut() {
+            session.remove(&self.name);
+        }
     }
 }
@@ -0,0 +1,158 @@
+use std::io::Error as IoError;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::{BufMut, Bytes, BytesMut};
+use salvo_core::http::body::{Body, Frame};
+use salvo_core::http::{HeaderMap, ResBody};
+use salvo_core::BoxedError;
+
+use crate::connect;
+
+/// Flag of gRPC-web trailers frame.
+pub(crate) const TRAILERS_FLAG: u8 = 0x80;
+/// Flag of Connect end-stream message.
+pub(crate) const END_STREAM_FLAG: u8 = 0x02;
+
+/// Encodes a gRPC length-prefixed message.
+pub(crate) fn encode_message(flag: u8, payload: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(payload.len() + 5);
+    buf.put_u8(flag);
+    buf.put_u32(payload.len() as u32);
+    buf.put_slice(payload);
+    buf.freeze()
+}
+
+/// Decodes the first gRPC length-prefixed message, returns the flag and the payload.
+pub(crate) fn decode_message(buf: &[u8]) -> Option<(u8, &[u8])> {
+    if buf.len() < 5 {
+        return None;
+    }
+    let len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+    buf.get(5..5 + len).map(|payload| (buf[0], payload))
+}
+
+/// Encodes trailers as a gRPC-web trailers frame.
+pub(crate) fn encode_web_trailers(trailers: &HeaderMap) -> Bytes {
+    let mut block = Vec::new();
+    for (name, value) in trailers {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b": ");
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+    encode_message(TRAILERS_FLAG, &block)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    GrpcWeb,
+    GrpcWebText,
+    ConnectStream,
+}
+
+/// Response body which moves trailers of a gRPC response into the body.
+pub(crate) struct TranslatedBody {
+    inner: ResBody,
+    encoding: Encoding,
+    /// Trailers of trailers-only responses, they are sent in headers by gRPC.
+    trailers: Option<HeaderMap>,
+    finished: bool,
+}
+impl TranslatedBody {
+    pub(crate) fn new(inner: ResBody, encoding: Encoding, trailers: Option<HeaderMap>) -> Self {
+        Self {
+            inner,
+            encoding,
+            trailers,
+            finished: false,
+        }
+    }
+    fn encode_data(&self, data: Bytes) -> Bytes {
+        match self.encoding {
+            Encoding::GrpcWebText => STANDARD.encode(data).into(),
+            _ => data,
+        }
+    }
+    fn encode_trailers(&self, trailers: Option<&HeaderMap>) -> Option<Bytes> {
+        match self.encoding {
+            Encoding::GrpcWeb => trailers.map(encode_web_trailers),
+            Encoding::GrpcWebText => trailers.map(|trailers| STANDARD.encode(encode_web_trailers(trailers)).into()),
+            Encoding::ConnectStream => {
+                let end_stream = connect::end_stream(trailers.unwrap_or(&HeaderMap::new()));
+                Some(encode_message(END_STREAM_FLAG, end_stream.as_bytes()))
+            }
+        }
+    }
+}
+impl Body for TranslatedBody {
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        match ready!(Pin::new(&mut self.inner).poll_frame(cx)) {
+            Some(Ok(frame)) => match frame.into_data() {
+                Ok(data) => Poll::Ready(Some(Ok(Frame::data(self.encode_data(data))))),
+                Err(frame) => {
+                    self.finished = true;
+                    let trailers = frame.into_trailers().ok();
+                    let data = self.encode_trailers(trailers.as_ref()).unwrap_or_default();
+                    Poll::Ready(Some(Ok(Frame::data(data))))
+                }
+            },
+            Some(Err(e)) => Poll::Ready(Some(Err(e.into()))),
+            None => {
+                self.finished = true;
+                let trailers = self.trailers.take();
+                Poll::Ready(
+                    self.encode_trailers(trailers.as_ref())
+                        .map(|data| Ok(Frame::data(data))),
+                )
+            }
+        }
+    }
+}
+
+/// Collects a unary gRPC response body, returns the data and the trailers.
+pub(crate) async fn collect(mut body: ResBody) -> Result<(Bytes, Option<HeaderMap>), IoError> {
+    use http_body_util::BodyExt;
+    let mut data = BytesMut::new();
+    let mut trailers = None;
+    while let Some(frame) = body.frame().await {
+        match frame?.into_data() {
+            Ok(chunk) => data.extend_from_slice(&chunk),
+            Err(frame) => {
+                trailers = frame.into_trailers().ok();
+            }
+        }
+    }
+    Ok((data.freeze(), trailers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        let message = encode_message(0, b"hello");
+        assert_eq!(&message[..], b"\x00\x00\x00\x00\x05hello");
+        assert_eq!(decode_message(&message), Some((0, &b"hello"[..])));
+        assert_eq!(decode_message(&message[..6]), None);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        assert_eq!(
+            &encode_web_trailers(&trailers)[..],
+            b"\x80\x00\x00\x00\x10grpc-status: 0\r\n"
+        );
+    }
+}
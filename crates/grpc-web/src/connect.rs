@@ -0,0 +1,131 @@
+use percent_encoding::percent_decode_str;
+use salvo_core::http::{HeaderMap, StatusCode};
+use serde_json::{json, Map, Value};
+
+pub(crate) const GRPC_STATUS: &str = "grpc-status";
+pub(crate) const GRPC_MESSAGE: &str = "grpc-message";
+pub(crate) const GRPC_STATUS_DETAILS: &str = "grpc-status-details-bin";
+
+/// Names of gRPC status codes used by Connect, indexed by code.
+const CODES: [&str; 17] = [
+    "ok",
+    "canceled",
+    "unknown",
+    "invalid_argument",
+    "deadline_exceeded",
+    "not_found",
+    "already_exists",
+    "permission_denied",
+    "resource_exhausted",
+    "failed_precondition",
+    "aborted",
+    "out_of_range",
+    "unimplemented",
+    "internal",
+    "unavailable",
+    "data_loss",
+    "unauthenticated",
+];
+
+/// Returns the Connect name of a gRPC status code.
+pub(crate) fn code_name(code: u16) -> &'static str {
+    CODES.get(code as usize).copied().unwrap_or("unknown")
+}
+
+/// Returns the HTTP status code of a Connect unary error.
+pub(crate) fn http_status(code: u16) -> StatusCode {
+    match code {
+        0 => StatusCode::OK,
+        1 => StatusCode::from_u16(499).expect("499 is a valid status code"),
+        3 | 9 | 11 => StatusCode::BAD_REQUEST,
+        4 => StatusCode::GATEWAY_TIMEOUT,
+        5 => StatusCode::NOT_FOUND,
+        6 | 10 => StatusCode::CONFLICT,
+        7 => StatusCode::FORBIDDEN,
+        8 => StatusCode::TOO_MANY_REQUESTS,
+        12 => StatusCode::NOT_IMPLEMENTED,
+        14 => StatusCode::SERVICE_UNAVAILABLE,
+        16 => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Returns the gRPC status code and the decoded message of trailers.
+pub(crate) fn status(trailers: &HeaderMap) -> (u16, Option<String>) {
+    let code = trailers
+        .get(GRPC_STATUS)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(2);
+    let message = trailers
+        .get(GRPC_MESSAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| percent_decode_str(v).decode_utf8_lossy().into_owned());
+    (code, message)
+}
+
+/// Returns `true` if the header is a gRPC status header, which is translated instead of forwarded.
+pub(crate) fn is_status_header(name: &str) -> bool {
+    name == GRPC_STATUS || name == GRPC_MESSAGE || name == GRPC_STATUS_DETAILS
+}
+
+/// Returns the JSON error of a non-ok status, `None` if the status is ok.
+pub(crate) fn error(trailers: &HeaderMap) -> Option<Value> {
+    let (code, message) = status(trailers);
+    if code == 0 {
+        return None;
+    }
+    let mut error = Map::new();
+    error.insert("code".into(), code_name(code).into());
+    if let Some(message) = message {
+        error.insert("message".into(), message.into());
+    }
+    Some(Value::Object(error))
+}
+
+/// Returns the JSON end-stream message of Connect streaming responses.
+pub(crate) fn end_stream(trailers: &HeaderMap) -> String {
+    let mut end_stream = Map::new();
+    if let Some(error) = error(trailers) {
+        end_stream.insert("error".into(), error);
+    }
+    let mut metadata = Map::new();
+    for name in trailers.keys() {
+        if is_status_header(name.as_str()) {
+            continue;
+        }
+        let values = trailers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .map(|v| json!(v))
+            .collect::<Vec<_>>();
+        metadata.insert(name.as_str().into(), Value::Array(values));
+    }
+    if !metadata.is_empty() {
+        end_stream.insert("metadata".into(), Value::Object(metadata));
+    }
+    Value::Object(end_stream).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_stream() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(GRPC_STATUS, "5".parse().unwrap());
+        trailers.insert(GRPC_MESSAGE, "user%20not%20found".parse().unwrap());
+        trailers.insert("x-request-id", "1".parse().unwrap());
+        assert_eq!(
+            end_stream(&trailers),
+            r#"{"error":{"code":"not_found","message":"user not found"},"metadata":{"x-request-id":["1"]}}"#
+        );
+        assert_eq!(http_status(5), StatusCode::NOT_FOUND);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert(GRPC_STATUS, "0".parse().unwrap());
+        assert_eq!(end_stream(&trailers), "{}");
+    }
+}
@@ -0,0 +1,463 @@
+//! gRPC-web and Connect protocol bridge for Salvo web framework.
+//!
+//! [`GrpcWeb`] translates `application/grpc-web`, `application/grpc-web-text` and Connect protocol requests into
+//! plain gRPC requests, calls the gRPC service mounted after it, and translates the response back, so browser
+//! gRPC clients can talk to a Salvo server directly. Trailers of the gRPC response are moved into the body, as
+//! browsers can not read HTTP trailers.
+//!
+//! The gRPC service can be a Salvo handler, or a tower service converted by
+//! [`TowerServiceCompat`](salvo_core::tower_compat::TowerServiceCompat).
+//!
+//! Connect unary `GET` requests and compressed Connect unary requests are not supported.
+//!
+//! # CORS
+//!
+//! Browser clients send preflight requests, so [`Cors`](https://docs.rs/salvo-cors) should be added before
+//! `GrpcWeb`, allowing [`ALLOW_HEADERS`] and exposing [`EXPOSE_HEADERS`].
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_grpc_web::GrpcWeb;
+//!
+//! #[handler]
+//! async fn greeter(res: &mut Response) {
+//!     // A gRPC service, such as a tonic service converted by `compat()`.
+//! }
+//!
+//! let router = Router::with_path("helloworld.Greeter/<**>")
+//!     .hoop(GrpcWeb::new())
+//!     .post(greeter);
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Limited};
+use salvo_core::http::header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, TE};
+use salvo_core::http::{HeaderMap, Method, ReqBody, ResBody, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+mod body;
+mod connect;
+
+use body::{Encoding, TranslatedBody};
+
+/// Request headers used by gRPC-web and Connect clients, which should be allowed by CORS.
+pub const ALLOW_HEADERS: &[&str] = &[
+    "content-type",
+    "x-grpc-web",
+    "x-user-agent",
+    "grpc-timeout",
+    "connect-protocol-version",
+    "connect-timeout-ms",
+    "connect-content-encoding",
+    "connect-accept-encoding",
+];
+/// Response headers read by gRPC-web and Connect clients, which should be exposed by CORS.
+pub const EXPOSE_HEADERS: &[&str] = &[
+    "grpc-status",
+    "grpc-message",
+    "grpc-status-details-bin",
+    "grpc-encoding",
+    "connect-content-encoding",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Protocol {
+    GrpcWeb { text: bool, codec: String },
+    ConnectUnary { codec: String },
+    ConnectStream { codec: String },
+}
+impl Protocol {
+    fn detect(req: &Request) -> Option<Self> {
+        if req.method() != Method::POST {
+            return None;
+        }
+        let content_type = req.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+        let content_type = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        let subtype = content_type.strip_prefix("application/")?;
+        let (kind, codec) = match subtype.split_once('+') {
+            Some((kind, codec)) => (kind, codec.to_owned()),
+            None => (subtype, "proto".to_owned()),
+        };
+        match kind {
+            "grpc-web" => Some(Self::GrpcWeb { text: false, codec }),
+            "grpc-web-text" => Some(Self::GrpcWeb { text: true, codec }),
+            "connect" => Some(Self::ConnectStream { codec }),
+            "proto" | "json" if req.headers().contains_key("connect-protocol-version") => {
+                Some(Self::ConnectUnary { codec: kind.to_owned() })
+            }
+            _ => None,
+        }
+    }
+    fn codec(&self) -> &str {
+        match self {
+            Self::GrpcWeb { codec, .. } | Self::ConnectUnary { codec } | Self::ConnectStream { codec } => codec,
+        }
+    }
+}
+
+/// Decodes gRPC-web-text, which may be concatenated base64 chunks padded independently.
+fn decode_text(input: &[u8]) -> Result<Vec<u8>, base64::DecodeError> {
+    let input = input
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut rest = &input[..];
+    while !rest.is_empty() {
+        let end = match rest.iter().position(|b| *b == b'=') {
+            Some(start) => start + rest[start..].iter().take_while(|b| **b == b'=').count(),
+            None => rest.len(),
+        };
+        STANDARD.decode_vec(&rest[..end], &mut output)?;
+        rest = &rest[end..];
+    }
+    Ok(output)
+}
+
+/// gRPC-web and Connect protocol bridge middleware.
+#[derive(Clone, Debug)]
+pub struct GrpcWeb {
+    grpc_web: bool,
+    connect: bool,
+    max_request_size: usize,
+}
+impl Default for GrpcWeb {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl GrpcWeb {
+    /// Create a new `GrpcWeb` which bridges both gRPC-web and Connect protocol.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            grpc_web: true,
+            connect: true,
+            max_request_size: 4 * 1024 * 1024,
+        }
+    }
+    /// Sets whether to bridge gRPC-web requests.
+    #[inline]
+    pub fn grpc_web(mut self, value: bool) -> Self {
+        self.grpc_web = value;
+        self
+    }
+    /// Sets whether to bridge Connect protocol requests.
+    #[inline]
+    pub fn connect(mut self, value: bool) -> Self {
+        self.connect = value;
+        self
+    }
+    /// Sets max size of request bodies which need to be buffered, they are gRPC-web-text and Connect unary
+    /// requests. Default is 4 MiB.
+    #[inline]
+    pub fn max_request_size(mut self, size: usize) -> Self {
+        self.max_request_size = size;
+        self
+    }
+
+    async fn read_body(&self, req: &mut Request) -> Result<Bytes, StatusError> {
+        let body = Limited::new(req.take_body(), self.max_request_size);
+        match body.collect().await {
+            Ok(body) => Ok(body.to_bytes()),
+            Err(e) => Err(StatusError::payload_too_large().cause(e)),
+        }
+    }
+
+    async fn translate_request(&self, req: &mut Request, protocol: &Protocol) -> Result<(), StatusError> {
+        match protocol {
+            Protocol::GrpcWeb { text: true, .. } => {
+                let body = self.read_body(req).await?;
+                let body = decode_text(&body).map_err(|e| StatusError::bad_request().cause(e))?;
+                req.replace_body(ReqBody::Once(body.into()));
+            }
+            Protocol::GrpcWeb { text: false, .. } => {}
+            Protocol::ConnectUnary { .. } => {
+                if req.headers().contains_key("content-encoding") {
+                    return Err(StatusError::unsupported_media_type().brief("Compressed requests are not supported."));
+                }
+                let body = self.read_body(req).await?;
+                req.replace_body(ReqBody::Once(body::encode_message(0, &body)));
+            }
+            Protocol::ConnectStream { .. } => {
+                if let Some(encoding) = req.headers_mut().remove("connect-content-encoding") {
+                    req.headers_mut().insert("grpc-encoding", encoding);
+                }
+                if let Some(encoding) = req.headers_mut().remove("connect-accept-encoding") {
+                    req.headers_mut().insert("grpc-accept-encoding", encoding);
+                }
+            }
+        }
+        let headers = req.headers_mut();
+        if let Some(timeout) = headers.remove("connect-timeout-ms") {
+            if let Ok(timeout) = HeaderValue::from_str(&format!("{}m", timeout.to_str().unwrap_or_default())) {
+                headers.insert("grpc-timeout", timeout);
+            }
+        }
+        let content_type = format!("application/grpc+{}", protocol.codec());
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_str(&content_type).map_err(|e| StatusError::bad_request().cause(e))?,
+        );
+        headers.insert(TE, HeaderValue::from_static("trailers"));
+        headers.remove(CONTENT_LENGTH);
+        Ok(())
+    }
+
+    async fn translate_unary_response(res: &mut Response, codec: &str) {
+        let (data, trailers) = match body::collect(res.take_body()).await {
+            Ok(collected) => collected,
+            Err(e) => {
+                tracing::error!(error = ?e, "read gRPC response failed");
+                res.render(StatusError::bad_gateway());
+                return;
+            }
+        };
+        let mut headers = std::mem::take(res.headers_mut());
+        let trailers = trailers.unwrap_or_else(|| {
+            // Trailers-only response.
+            let mut trailers = HeaderMap::new();
+            for name in [
+                connect::GRPC_STATUS,
+                connect::GRPC_MESSAGE,
+                connect::GRPC_STATUS_DETAILS,
+            ] {
+                if let Some(value) = headers.remove(name) {
+                    trailers.insert(name, value);
+                }
+            }
+            trailers
+        });
+        headers.remove(CONTENT_TYPE);
+        headers.remove(CONTENT_LENGTH);
+        if let Some(encoding) = headers.remove("grpc-encoding") {
+            headers.insert("content-encoding", encoding);
+        }
+        for (name, value) in &trailers {
+            if connect::is_status_header(name.as_str()) {
+                continue;
+            }
+            if let Ok(name) = HeaderName::from_bytes(format!("trailer-{name}").as_bytes()) {
+                headers.append(name, value.clone());
+            }
+        }
+        *res.headers_mut() = headers;
+
+        let (code, _) = connect::status(&trailers);
+        match connect::error(&trailers) {
+            None => {
+                let Some((_, message)) = body::decode_message(&data) else {
+                    res.render(StatusError::bad_gateway().brief("Invalid gRPC response."));
+                    return;
+                };
+                let content_type = format!("application/{codec}");
+                res.status_code(StatusCode::OK);
+                if let Ok(content_type) = HeaderValue::from_str(&content_type) {
+                    res.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+                *res.body_mut() = ResBody::Once(Bytes::copy_from_slice(message));
+            }
+            Some(error) => {
+                res.status_code(connect::http_status(code));
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                *res.body_mut() = ResBody::Once(error.to_string().into());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for GrpcWeb {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let Some(protocol) = Protocol::detect(req) else {
+            return;
+        };
+        let enabled = match protocol {
+            Protocol::GrpcWeb { .. } => self.grpc_web,
+            Protocol::ConnectUnary { .. } | Protocol::ConnectStream { .. } => self.connect,
+        };
+        if !enabled {
+            return;
+        }
+        if let Err(e) = self.translate_request(req, &protocol).await {
+            res.render(e);
+            ctrl.skip_rest();
+            return;
+        }
+
+        ctrl.call_next(req, depot, res).await;
+
+        let is_grpc = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.starts_with("application/grpc"))
+            .unwrap_or(false);
+        if !is_grpc || res.body.is_error() {
+            return;
+        }
+        res.headers_mut().remove(CONTENT_LENGTH);
+        match protocol {
+            Protocol::GrpcWeb { text, codec } => {
+                let content_type = if text {
+                    format!("application/grpc-web-text+{codec}")
+                } else {
+                    format!("application/grpc-web+{codec}")
+                };
+                if let Ok(content_type) = HeaderValue::from_str(&content_type) {
+                    res.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+                let encoding = if text { Encoding::GrpcWebText } else { Encoding::GrpcWeb };
+                let body = TranslatedBody::new(res.take_body(), encoding, None);
+                *res.body_mut() = ResBody::Boxed(Box::pin(body));
+            }
+            Protocol::ConnectStream { codec } => {
+                let mut trailers = HeaderMap::new();
+                for name in [
+                    connect::GRPC_STATUS,
+                    connect::GRPC_MESSAGE,
+                    connect::GRPC_STATUS_DETAILS,
+                ] {
+                    if let Some(value) = res.headers_mut().remove(name) {
+                        trailers.insert(name, value);
+                    }
+                }
+                if let Some(encoding) = res.headers_mut().remove("grpc-encoding") {
+                    res.headers_mut().insert("connect-content-encoding", encoding);
+                }
+                if let Ok(content_type) = HeaderValue::from_str(&format!("application/connect+{codec}")) {
+                    res.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+                let trailers = (!trailers.is_empty()).then_some(trailers);
+                let body = TranslatedBody::new(res.take_body(), Encoding::ConnectStream, trailers);
+                *res.body_mut() = ResBody::Boxed(Box::pin(body));
+            }
+            Protocol::ConnectUnary { codec } => {
+                Self::translate_unary_response(res, &codec).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::body::{BytesFrame, Frame};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    /// A gRPC echo service, it fails with `NOT_FOUND` if the message is empty.
+    #[handler]
+    async fn echo(req: &mut Request, res: &mut Response) {
+        assert_eq!(req.header::<String>("content-type").unwrap(), "application/grpc+proto");
+        let payload = req.payload().await.unwrap().clone();
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        let (_, message) = body::decode_message(&payload).unwrap();
+        if message.is_empty() {
+            res.headers_mut().insert("grpc-status", HeaderValue::from_static("5"));
+            res.headers_mut()
+                .insert("grpc-message", HeaderValue::from_static("empty%20message"));
+            return;
+        }
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        trailers.insert("x-echo", HeaderValue::from_static("yes"));
+        let frames = vec![
+            Ok::<_, std::io::Error>(BytesFrame::from(body::encode_message(0, message))),
+            Ok(BytesFrame(Frame::trailers(trailers))),
+        ];
+        res.stream(futures_util::stream::iter(frames));
+    }
+
+    fn service() -> Service {
+        Service::new(Router::new().hoop(GrpcWeb::new()).post(echo))
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web() {
+        let service = service();
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .add_header("content-type", "application/grpc-web+proto", true)
+            .bytes(body::encode_message(0, b"hello").to_vec())
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/grpc-web+proto");
+        let body = res.take_bytes(None).await.unwrap();
+        let mut expected = body::encode_message(0, b"hello").to_vec();
+        expected.extend_from_slice(b"\x80\x00\x00\x00\x1dgrpc-status: 0\r\nx-echo: yes\r\n");
+        assert_eq!(&body[..], &expected[..]);
+
+        let text = STANDARD.encode(body::encode_message(0, b"hello"));
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .add_header("content-type", "application/grpc-web-text", true)
+            .bytes(text.into_bytes())
+            .send(&service)
+            .await;
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/grpc-web-text+proto"
+        );
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(decode_text(&body).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_connect_unary() {
+        let service = service();
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .add_header("content-type", "application/proto", true)
+            .add_header("connect-protocol-version", "1", true)
+            .bytes(b"hello".to_vec())
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/proto");
+        assert_eq!(res.headers().get("trailer-x-echo").unwrap(), "yes");
+        assert_eq!(&res.take_bytes(None).await.unwrap()[..], b"hello");
+
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .add_header("content-type", "application/proto", true)
+            .add_header("connect-protocol-version", "1", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            r#"{"code":"not_found","message":"empty message"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_stream() {
+        let service = service();
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .add_header("content-type", "application/connect+proto", true)
+            .bytes(body::encode_message(0, b"hello").to_vec())
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/connect+proto");
+        let body = res.take_bytes(None).await.unwrap();
+        let (flag, message) = body::decode_message(&body).unwrap();
+        assert_eq!((flag, message), (0, &b"hello"[..]));
+        let (flag, end_stream) = body::decode_message(&body[10..]).unwrap();
+        assert_eq!(flag, body::END_STREAM_FLAG);
+        assert_eq!(end_stream, br#"{"metadata":{"x-echo":["yes"]}}"#);
+    }
+}
@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Value of a translation argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArgValue {
+    /// String value.
+    String(String),
+    /// Number value, backends may use it to select plural forms.
+    Number(f64),
+}
+impl Display for ArgValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => f.write_str(value),
+            Self::Number(value) => write!(f, "{value}"),
+        }
+    }
+}
+impl From<String> for ArgValue {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+impl From<&str> for ArgValue {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+macro_rules! impl_number_arg {
+    ($($ty:ty),+) => {
+        $(
+            impl From<$ty> for ArgValue {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    Self::Number(value as f64)
+                }
+            }
+        )+
+    };
+}
+impl_number_arg!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Named arguments of a translation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Args(Vec<(String, ArgValue)>);
+impl Args {
+    /// Create empty `Args`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets an argument.
+    #[inline]
+    pub fn set(mut self, name: impl Into<String>, value: impl Into<ArgValue>) -> Self {
+        let name = name.into();
+        let value = value.into();
+        if let Some(arg) = self.0.iter_mut().find(|(n, _)| *n == name) {
+            arg.1 = value;
+        } else {
+            self.0.push((name, value));
+        }
+        self
+    }
+    /// Get an argument.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+    /// Iterate all arguments.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ArgValue)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v))
+    }
+    /// Returns `true` if there is no argument.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Translation backend.
+pub trait Translator: Send + Sync + 'static {
+    /// Locales which have translations.
+    fn locales(&self) -> &[String];
+    /// Translate message `key` to `locale`, returns `None` if the message is not found.
+    fn translate(&self, locale: &str, key: &str, args: &Args) -> Option<String>;
+}
+
+/// A simple in-memory translation backend.
+///
+/// Messages are plain strings, `{name}` in message is replaced by argument `name`, use `{{` and `}}` for literal braces.
+///
+/// # Example
+///
+/// ```
+/// use salvo_i18n::{Args, MemoryCatalog, Translator};
+///
+/// let catalog = MemoryCatalog::new()
+///     .add("en", "hello", "Hello, {name}!")
+///     .add("zh-CN", "hello", "你好，{name}！");
+/// let args = Args::new().set("name", "Salvo");
+/// assert_eq!(catalog.translate("en", "hello", &args).unwrap(), "Hello, Salvo!");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryCatalog {
+    locales: Vec<String>,
+    messages: HashMap<String, HashMap<String, String>>,
+}
+impl MemoryCatalog {
+    /// Create an empty `MemoryCatalog`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a message for a locale.
+    pub fn add(mut self, locale: impl Into<String>, key: impl Into<String>, message: impl Into<String>) -> Self {
+        let locale = locale.into();
+        if !self.locales.contains(&locale) {
+            self.locales.push(locale.clone());
+        }
+        self.messages
+            .entry(locale)
+            .or_default()
+            .insert(key.into(), message.into());
+        self
+    }
+}
+impl Translator for MemoryCatalog {
+    #[inline]
+    fn locales(&self) -> &[String] {
+        &self.locales
+    }
+    fn translate(&self, locale: &str, key: &str, args: &Args) -> Option<String> {
+        let message = self.messages.get(locale)?.get(key)?;
+        Some(interpolate(message, args).into_owned())
+    }
+}
+
+fn interpolate<'a>(message: &'a str, args: &Args) -> Cow<'a, str> {
+    if !message.contains(['{', '}']) {
+        return Cow::Borrowed(message);
+    }
+    let mut output = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(pos) = rest.find(['{', '}']) {
+        output.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if let (true, Some(end)) = (tail.starts_with('{'), tail.find('}')) {
+            let name = tail[1..end].trim();
+            match args.get(name) {
+                Some(value) => output.push_str(&value.to_string()),
+                None => output.push_str(&tail[..=end]),
+            }
+            rest = &tail[end + 1..];
+        } else {
+            output.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+    }
+    output.push_str(rest);
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate() {
+        let args = Args::new().set("name", "Bob").set("count", 3);
+        assert_eq!(interpolate("plain", &args), "plain");
+        assert_eq!(interpolate("{name} has {count} apples", &args), "Bob has 3 apples");
+        assert_eq!(interpolate("{{name}} {missing} }", &args), "{name} {missing} }");
+    }
+
+    #[test]
+    fn test_memory_catalog() {
+        let catalog = MemoryCatalog::new()
+            .add("en", "a", "A")
+            .add("fr", "a", "À")
+            .add("en", "b", "B");
+        assert_eq!(catalog.locales(), ["en", "fr"]);
+        assert_eq!(catalog.translate("fr", "a", &Args::new()).as_deref(), Some("À"));
+        assert!(catalog.translate("fr", "b", &Args::new()).is_none());
+    }
+}
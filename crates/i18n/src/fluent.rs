@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::Path;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+use crate::{ArgValue, Args, Translator};
+
+/// Translation backend using [Fluent](https://projectfluent.org/).
+///
+/// Message attributes can be translated with key `message.attribute`.
+///
+/// # Example
+///
+/// ```
+/// use salvo_i18n::{Args, FluentCatalog, Translator};
+///
+/// let catalog = FluentCatalog::new()
+///     .add_resource("en", "hello = Hello, { $name }!\n    .title = Greeting")
+///     .unwrap();
+/// let args = Args::new().set("name", "Salvo");
+/// assert_eq!(catalog.translate("en", "hello", &args).unwrap(), "Hello, Salvo!");
+/// assert_eq!(catalog.translate("en", "hello.title", &args).unwrap(), "Greeting");
+/// ```
+#[derive(Default)]
+pub struct FluentCatalog {
+    locales: Vec<String>,
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+}
+impl Debug for FluentCatalog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FluentCatalog").field("locales", &self.locales).finish()
+    }
+}
+impl FluentCatalog {
+    /// Create an empty `FluentCatalog`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load translations from a directory laid out as `{dir}/{locale}/*.ftl`.
+    pub fn load_dir(dir: impl AsRef<Path>) -> IoResult<Self> {
+        let mut catalog = Self::new();
+        let mut entries = std::fs::read_dir(dir)?.collect::<IoResult<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let Some(locale) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                continue;
+            };
+            let mut files = std::fs::read_dir(entry.path())?.collect::<IoResult<Vec<_>>>()?;
+            files.sort_by_key(|file| file.file_name());
+            for file in files {
+                let path = file.path();
+                if path.extension().is_some_and(|ext| ext == "ftl") {
+                    tracing::debug!(locale, path = %path.display(), "load fluent resource");
+                    catalog = catalog.add_resource(&locale, std::fs::read_to_string(&path)?)?;
+                }
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Add a fluent resource for a locale, resources of the same locale are merged.
+    pub fn add_resource(mut self, locale: &str, source: impl Into<String>) -> IoResult<Self> {
+        let resource = FluentResource::try_new(source.into()).map_err(|(_, errors)| {
+            IoError::new(
+                ErrorKind::InvalidData,
+                format!("invalid fluent resource for `{locale}`: {errors:?}"),
+            )
+        })?;
+        if !self.bundles.contains_key(locale) {
+            let langid = locale
+                .parse::<LanguageIdentifier>()
+                .map_err(|e| IoError::new(ErrorKind::InvalidInput, format!("invalid locale `{locale}`: {e}")))?;
+            let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+            bundle.set_use_isolating(false);
+            self.bundles.insert(locale.to_owned(), bundle);
+            self.locales.push(locale.to_owned());
+        }
+        let bundle = self.bundles.get_mut(locale).expect("bundle should be inserted");
+        bundle.add_resource(resource).map_err(|errors| {
+            IoError::new(
+                ErrorKind::InvalidData,
+                format!("invalid fluent resource for `{locale}`: {errors:?}"),
+            )
+        })?;
+        Ok(self)
+    }
+}
+impl Translator for FluentCatalog {
+    #[inline]
+    fn locales(&self) -> &[String] {
+        &self.locales
+    }
+    fn translate(&self, locale: &str, key: &str, args: &Args) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        let (id, attribute) = match key.split_once('.') {
+            Some((id, attribute)) => (id, Some(attribute)),
+            None => (key, None),
+        };
+        let message = bundle.get_message(id)?;
+        let pattern = match attribute {
+            Some(attribute) => message.get_attribute(attribute)?.value(),
+            None => message.value()?,
+        };
+        let fluent_args = (!args.is_empty()).then(|| {
+            let mut fluent_args = FluentArgs::new();
+            for (name, value) in args.iter() {
+                let value = match value {
+                    ArgValue::String(value) => FluentValue::from(value.as_str()),
+                    ArgValue::Number(value) => FluentValue::from(*value),
+                };
+                fluent_args.set(name, value);
+            }
+            fluent_args
+        });
+        let mut errors = Vec::new();
+        let value = bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors);
+        if !errors.is_empty() {
+            tracing::warn!(locale, key, ?errors, "format fluent message failed");
+        }
+        Some(value.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fluent_catalog() {
+        let catalog = FluentCatalog::new()
+            .add_resource(
+                "en-US",
+                "emails = { $count ->\n    [one] You have one email.\n   *[other] You have { $count } emails.\n}",
+            )
+            .unwrap()
+            .add_resource("en-US", "bye = Bye")
+            .unwrap();
+        assert_eq!(catalog.locales(), ["en-US"]);
+        assert_eq!(
+            catalog
+                .translate("en-US", "emails", &Args::new().set("count", 1))
+                .unwrap(),
+            "You have one email."
+        );
+        assert_eq!(
+            catalog
+                .translate("en-US", "emails", &Args::new().set("count", 5))
+                .unwrap(),
+            "You have 5 emails."
+        );
+        assert_eq!(catalog.translate("en-US", "bye", &Args::new()).unwrap(), "Bye");
+        assert!(catalog.translate("en-US", "missing", &Args::new()).is_none());
+        assert!(catalog.translate("fr", "bye", &Args::new()).is_none());
+        assert!(FluentCatalog::new().add_resource("en", "bad message").is_err());
+    }
+}
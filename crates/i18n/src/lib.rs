@@ -0,0 +1,307 @@
+//! Internationalization and localization for Savlo web server framework.
+//!
+//! [`I18n`] negotiates the locale of every request from query parameter, cookie and `Accept-Language` header,
+//! then inserts a [`Localizer`] into [`Depot`], handlers and templates can use it to translate messages.
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::future_not_send)]
+#![warn(rustdoc::broken_intra_doc_links)]
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use salvo_core::http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE};
+use salvo_core::http::HeaderValue;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+#[macro_use]
+mod cfg;
+
+mod catalog;
+pub mod negotiate;
+
+pub use catalog::{ArgValue, Args, MemoryCatalog, Translator};
+pub use negotiate::{negotiate, parse_accept_language};
+
+cfg_feature! {
+    #![feature = "fluent"]
+
+    mod fluent;
+    pub use fluent::FluentCatalog;
+}
+
+/// Key used to insert [`Localizer`] into depot.
+pub const LOCALIZER_KEY: &str = "::salvo::i18n::localizer";
+
+/// Translate messages to the negotiated locale of current request.
+///
+/// Messages missing in the negotiated locale are translated with the default locale, the message key is
+/// returned if it is still not found.
+#[derive(Clone)]
+pub struct Localizer {
+    translator: Arc<dyn Translator>,
+    locale: String,
+    default_locale: Arc<str>,
+}
+impl Debug for Localizer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Localizer")
+            .field("locale", &self.locale)
+            .field("default_locale", &self.default_locale)
+            .finish()
+    }
+}
+impl Localizer {
+    /// Create a new `Localizer`.
+    #[inline]
+    pub fn new(
+        translator: Arc<dyn Translator>,
+        locale: impl Into<String>,
+        default_locale: impl Into<Arc<str>>,
+    ) -> Self {
+        Self {
+            translator,
+            locale: locale.into(),
+            default_locale: default_locale.into(),
+        }
+    }
+    /// Get the negotiated locale.
+    #[inline]
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+    /// Translate message without arguments.
+    #[inline]
+    pub fn t(&self, key: &str) -> String {
+        self.t_with(key, &Args::new())
+    }
+    /// Translate message with arguments.
+    pub fn t_with(&self, key: &str, args: &Args) -> String {
+        self.translator
+            .translate(&self.locale, key, args)
+            .or_else(|| {
+                if *self.default_locale != self.locale {
+                    self.translator.translate(&self.default_locale, key, args)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_else(|| {
+                tracing::debug!(locale = self.locale, key, "translation is not found");
+                key.to_owned()
+            })
+    }
+}
+
+/// Extension for Depot.
+pub trait I18nDepotExt {
+    /// Get the negotiated locale.
+    fn locale(&self) -> Option<&str>;
+    /// Get [`Localizer`] of current request.
+    fn localizer(&self) -> Option<&Localizer>;
+    /// Translate message without arguments, returns the key if [`I18n`] is not used.
+    fn t(&self, key: &str) -> String;
+    /// Translate message with arguments, returns the key if [`I18n`] is not used.
+    fn t_with(&self, key: &str, args: &Args) -> String;
+}
+
+impl I18nDepotExt for Depot {
+    #[inline]
+    fn locale(&self) -> Option<&str> {
+        self.localizer().map(Localizer::locale)
+    }
+    #[inline]
+    fn localizer(&self) -> Option<&Localizer> {
+        self.get::<Localizer>(LOCALIZER_KEY).ok()
+    }
+    #[inline]
+    fn t(&self, key: &str) -> String {
+        self.t_with(key, &Args::new())
+    }
+    #[inline]
+    fn t_with(&self, key: &str, args: &Args) -> String {
+        match self.localizer() {
+            Some(localizer) => localizer.t_with(key, args),
+            None => key.to_owned(),
+        }
+    }
+}
+
+/// Middleware negotiates locale of requests.
+///
+/// The locale is chosen from the query parameter, the cookie and the `Accept-Language` header in order, the
+/// first one matches an available locale of the translator wins, otherwise the default locale is used. The
+/// `Content-Language` header is set to the negotiated locale if the handler does not set it.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_i18n::{I18n, I18nDepotExt, MemoryCatalog};
+///
+/// #[handler]
+/// async fn hello(depot: &mut Depot) -> String {
+///     depot.t("hello")
+/// }
+///
+/// let catalog = MemoryCatalog::new().add("en", "hello", "Hello").add("fr", "hello", "Bonjour");
+/// let router = Router::new().hoop(I18n::new(catalog, "en").query_param("lang")).get(hello);
+/// ```
+pub struct I18n {
+    translator: Arc<dyn Translator>,
+    default_locale: Arc<str>,
+    query_param: Option<String>,
+    #[cfg(feature = "cookie")]
+    cookie_name: Option<String>,
+}
+impl Debug for I18n {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("I18n");
+        s.field("locales", &self.translator.locales())
+            .field("default_locale", &self.default_locale)
+            .field("query_param", &self.query_param);
+        #[cfg(feature = "cookie")]
+        s.field("cookie_name", &self.cookie_name);
+        s.finish()
+    }
+}
+impl I18n {
+    /// Create a new `I18n` with a translator and the default locale.
+    #[inline]
+    pub fn new(translator: impl Translator, default_locale: impl Into<Arc<str>>) -> Self {
+        Self::with_shared(Arc::new(translator), default_locale)
+    }
+    /// Create a new `I18n` with a shared translator and the default locale.
+    #[inline]
+    pub fn with_shared(translator: Arc<dyn Translator>, default_locale: impl Into<Arc<str>>) -> Self {
+        Self {
+            translator,
+            default_locale: default_locale.into(),
+            query_param: None,
+            #[cfg(feature = "cookie")]
+            cookie_name: None,
+        }
+    }
+    /// Sets the query parameter used to select locale, such as `lang` for `?lang=fr`.
+    #[inline]
+    pub fn query_param(mut self, name: impl Into<String>) -> Self {
+        self.query_param = Some(name.into());
+        self
+    }
+    cfg_feature! {
+        #![feature = "cookie"]
+        /// Sets the cookie name used to select locale.
+        #[inline]
+        pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+            self.cookie_name = Some(name.into());
+            self
+        }
+    }
+
+    /// Negotiate locale for the request.
+    pub fn negotiate(&self, req: &Request) -> String {
+        let available = self.translator.locales();
+        let mut requested = Vec::new();
+        if let Some(name) = &self.query_param {
+            if let Some(value) = req.queries().get(name) {
+                requested.push(value.clone());
+            }
+        }
+        #[cfg(feature = "cookie")]
+        if let Some(name) = &self.cookie_name {
+            if let Some(cookie) = req.cookie(name) {
+                requested.push(cookie.value().to_owned());
+            }
+        }
+        if let Some(value) = req.headers().get(ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()) {
+            requested.extend(parse_accept_language(value));
+        }
+        negotiate(&requested, available)
+            .unwrap_or(&self.default_locale)
+            .to_owned()
+    }
+}
+
+#[async_trait]
+impl Handler for I18n {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let locale = self.negotiate(req);
+        let content_language = HeaderValue::from_str(&locale).ok();
+        depot.insert(
+            LOCALIZER_KEY,
+            Localizer::new(self.translator.clone(), locale, self.default_locale.clone()),
+        );
+        ctrl.call_next(req, depot, res).await;
+        if let Some(value) = content_language {
+            res.headers_mut().entry(CONTENT_LANGUAGE).or_insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        format!(
+            "{}: {} {}",
+            depot.locale().unwrap(),
+            depot.t_with("hello", &Args::new().set("name", "Salvo")),
+            depot.t("bye")
+        )
+    }
+
+    #[tokio::test]
+    async fn test_i18n() {
+        let catalog = MemoryCatalog::new()
+            .add("en", "hello", "Hello, {name}!")
+            .add("en", "bye", "Bye")
+            .add("zh-CN", "hello", "你好，{name}！")
+            .add("fr", "hello", "Bonjour, {name} !");
+        let router = Router::new()
+            .hoop(I18n::new(catalog, "en").query_param("lang").cookie_name("lang"))
+            .get(hello);
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.headers().get(CONTENT_LANGUAGE).unwrap(), "en");
+        assert_eq!(res.take_string().await.unwrap(), "en: Hello, Salvo! Bye");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(ACCEPT_LANGUAGE, "de, zh-Hans-CN;q=0.9, en;q=0.5", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_LANGUAGE).unwrap(), "zh-CN");
+        assert_eq!(res.take_string().await.unwrap(), "zh-CN: 你好，Salvo！ Bye");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("cookie", "lang=zh-CN", true)
+            .add_header(ACCEPT_LANGUAGE, "en", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "zh-CN: 你好，Salvo！ Bye");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/?lang=fr")
+            .add_header("cookie", "lang=zh-CN", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "fr: Bonjour, Salvo ! Bye");
+    }
+
+    #[test]
+    fn test_depot_without_i18n() {
+        let depot = Depot::new();
+        assert!(depot.locale().is_none());
+        assert_eq!(depot.t("hello"), "hello");
+    }
+}
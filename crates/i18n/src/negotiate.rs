@@ -0,0 +1,101 @@
+//! `Accept-Language` parsing and locale negotiation.
+
+/// Parse `Accept-Language` header value, returns language tags sorted by quality in descending order.
+///
+/// Tags with zero quality and the `*` wildcard are skipped.
+///
+/// # Example
+///
+/// ```
+/// use salvo_i18n::parse_accept_language;
+///
+/// let tags = parse_accept_language("fr;q=0.8, en-US, de;q=0.5, *;q=0.1");
+/// assert_eq!(tags, vec!["en-US", "fr", "de"]);
+/// ```
+pub fn parse_accept_language(value: &str) -> Vec<String> {
+    let mut tags = value
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+            if tag.is_empty() || tag == "*" {
+                return None;
+            }
+            let mut quality = 1.0f32;
+            for param in parts {
+                if let Some(q) = param.trim().strip_prefix("q=") {
+                    quality = q.trim().parse().ok()?;
+                }
+            }
+            (quality > 0.0).then(|| (tag.to_owned(), quality))
+        })
+        .collect::<Vec<_>>();
+    // Stable sort keeps the order of tags with the same quality.
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+fn primary(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// Choose the best locale in `available` for `requested` locales in preference order.
+///
+/// For every requested locale, an exact match (case insensitive, `_` and `-` are treated as the same) is
+/// preferred, then an available locale with the same primary language. Returns `None` if nothing matches.
+///
+/// # Example
+///
+/// ```
+/// use salvo_i18n::negotiate;
+///
+/// let available = ["en-US", "zh-CN"];
+/// assert_eq!(negotiate(["en-GB", "zh-CN"], &available), Some("en-US"));
+/// assert_eq!(negotiate(["zh-cn"], &available), Some("zh-CN"));
+/// assert_eq!(negotiate(["fr"], &available), None);
+/// ```
+pub fn negotiate<I, S>(requested: I, available: &[S]) -> Option<&str>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+    S: AsRef<str>,
+{
+    let same = |a: &str, b: &str| a.replace('_', "-").eq_ignore_ascii_case(&b.replace('_', "-"));
+    for requested in requested {
+        let requested = requested.as_ref();
+        if let Some(locale) = available.iter().find(|a| same(a.as_ref(), requested)) {
+            return Some(locale.as_ref());
+        }
+        if let Some(locale) = available
+            .iter()
+            .find(|a| primary(a.as_ref()).eq_ignore_ascii_case(primary(requested)))
+        {
+            return Some(locale.as_ref());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accept_language() {
+        assert_eq!(
+            parse_accept_language("da, en-GB;q=0.8, en;q=0.7"),
+            vec!["da", "en-GB", "en"]
+        );
+        assert_eq!(parse_accept_language("en;q=0.5, fr;q=0.5, de"), vec!["de", "en", "fr"]);
+        assert_eq!(parse_accept_language("en;q=0, fr;q=bad, ,*"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let available = vec!["en".to_owned(), "zh_CN".to_owned()];
+        assert_eq!(negotiate(["zh-CN"], &available), Some("zh_CN"));
+        assert_eq!(negotiate(["zh-TW", "en"], &available), Some("zh_CN"));
+        assert_eq!(negotiate(["fr", "en-AU"], &available), Some("en"));
+        assert_eq!(negotiate(Vec::<String>::new(), &available), None);
+    }
+}
@@ -17,6 +17,7 @@ pub use jsonwebtoken::{decode, Algorithm, DecodingKey, TokenData, Validation};
 use serde::de::DeserializeOwned;
 use thiserror::Error;
 
+use salvo_core::handler::{none_skipper, Skipper};
 use salvo_core::http::{Method, Request, Response, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
@@ -160,6 +161,8 @@ pub struct JwtAuth<C, D> {
     pub decoder: D,
     /// The finders list.
     pub finders: Vec<Box<dyn JwtTokenFinder>>,
+    /// Requests are passed without token when skipper returns `true`.
+    pub skipper: Box<dyn Skipper>,
 }
 
 impl<C, D> JwtAuth<C, D>
@@ -175,6 +178,7 @@ where
             decoder,
             _claims: PhantomData::<C>,
             finders: vec![Box::new(HeaderFinder::new())],
+            skipper: Box::new(none_skipper),
         }
     }
     /// Sets force_passed value and return Self.
@@ -201,6 +205,12 @@ where
         self.finders = finders;
         self
     }
+    /// Sets skipper and return Self.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
 
     async fn find_token(&self, req: &mut Request) -> Option<String> {
         for finder in &self.finders {
@@ -219,6 +229,9 @@ where
     D: JwtAuthDecoder + Send + Sync + 'static,
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
         let token = self.find_token(req).await;
         if let Some(token) = token {
             match self.decoder.decode::<C>(&token, depot).await {
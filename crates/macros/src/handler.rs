@@ -1,16 +1,56 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::{Ident, ImplItem, Item, ReturnType, Signature, Type};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{parse_quote, Expr, Ident, ImplItem, Item, Lit, LitInt, MetaNameValue, ReturnType, Signature, Type};
 
 use crate::shared::*;
 
-pub(crate) fn generate(input: Item) -> syn::Result<TokenStream> {
+/// Arguments of `#[handler]`, such as `#[handler(wrap = [timeout(5s), cache(60s)])]`.
+#[derive(Default)]
+pub(crate) struct HandlerArgs {
+    wraps: Vec<Expr>,
+}
+impl Parse for HandlerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = HandlerArgs::default();
+        for meta in Punctuated::<MetaNameValue, Comma>::parse_terminated(input)? {
+            if !meta.path.is_ident("wrap") {
+                return Err(syn::Error::new_spanned(meta.path, "unknown argument, expected `wrap`"));
+            }
+            match meta.value {
+                Expr::Array(array) => args.wraps.extend(array.elems),
+                value => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "`wrap` should be a list of hoops, such as `wrap = [timeout(5s), cache(60s)]`",
+                    ))
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+pub(crate) fn generate(args: HandlerArgs, input: Item) -> syn::Result<TokenStream> {
     let salvo = salvo_crate();
+    if !args.wraps.is_empty() {
+        return match input {
+            Item::Fn(item_fn) => generate_wrapped(&salvo, args, item_fn),
+            _ => Err(syn::Error::new_spanned(
+                input,
+                "`wrap` is only supported by #[handler] on `fn`",
+            )),
+        };
+    }
     match input {
         Item::Fn(mut item_fn) => {
+            let hfn = handle_fn(&salvo, &item_fn.sig, &quote!(Self))?;
+            strip_state_attrs(&mut item_fn.sig);
             let attrs = &item_fn.attrs;
             let vis = &item_fn.vis;
-            let sig = &mut item_fn.sig;
+            let sig = &item_fn.sig;
             let body = &item_fn.block;
             let name = &sig.ident;
             let docs = item_fn
@@ -33,7 +73,6 @@ pub(crate) fn generate(input: Item) -> syn::Result<TokenStream> {
                 }
             };
 
-            let hfn = handle_fn(&salvo, sig)?;
             Ok(quote! {
                 #sdef
                 #[#salvo::async_trait]
@@ -42,20 +81,20 @@ pub(crate) fn generate(input: Item) -> syn::Result<TokenStream> {
                 }
             })
         }
-        Item::Impl(item_impl) => {
+        Item::Impl(mut item_impl) => {
             let mut hmtd = None;
-            for item in &item_impl.items {
+            for item in &mut item_impl.items {
                 if let ImplItem::Fn(method) = item {
                     if method.sig.ident == Ident::new("handle", Span::call_site()) {
                         hmtd = Some(method);
                     }
                 }
             }
-            if hmtd.is_none() {
+            let Some(hmtd) = hmtd else {
                 return Err(syn::Error::new_spanned(item_impl.impl_token, "missing handle function"));
-            }
-            let hmtd = hmtd.unwrap();
-            let hfn = handle_fn(&salvo, &hmtd.sig)?;
+            };
+            let hfn = handle_fn(&salvo, &hmtd.sig, &quote!(Self))?;
+            strip_state_attrs(&mut hmtd.sig);
             let ty = &item_impl.self_ty;
             let (impl_generics, _, where_clause) = &item_impl.generics.split_for_impl();
 
@@ -74,7 +113,128 @@ pub(crate) fn generate(input: Item) -> syn::Result<TokenStream> {
     }
 }
 
-fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
+/// Generate a handler which calls the hoops in `wrap` before the function.
+///
+/// The function is called by a hidden handler wrapped in a `HoopedHandler`, which is created on first use.
+fn generate_wrapped(salvo: &Ident, args: HandlerArgs, mut item_fn: syn::ItemFn) -> syn::Result<TokenStream> {
+    let hfn = handle_fn(salvo, &item_fn.sig, &item_fn.sig.ident.to_token_stream())?;
+    strip_state_attrs(&mut item_fn.sig);
+    let attrs = &item_fn.attrs;
+    let vis = &item_fn.vis;
+    let sig = &item_fn.sig;
+    let body = &item_fn.block;
+    let name = &sig.ident;
+    let docs = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect::<Vec<_>>();
+    let hoops = args
+        .wraps
+        .into_iter()
+        .map(|expr| wrap_hoop(salvo, expr))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #(#docs)*
+        #[allow(non_camel_case_types)]
+        #[derive(Debug)]
+        #vis struct #name;
+        impl #name {
+            #(#attrs)*
+            #sig {
+                #body
+            }
+        }
+        #[#salvo::async_trait]
+        impl #salvo::Handler for #name {
+            #[inline]
+            async fn handle(&self, req: &mut #salvo::Request, depot: &mut #salvo::Depot, res: &mut #salvo::Response, ctrl: &mut #salvo::FlowCtrl) {
+                #[allow(non_camel_case_types)]
+                struct __salvo_wrapped;
+                #[#salvo::async_trait]
+                impl #salvo::Handler for __salvo_wrapped {
+                    #hfn
+                }
+                static __SALVO_HOOPED: #salvo::__private::once_cell::sync::Lazy<#salvo::handler::HoopedHandler> =
+                    #salvo::__private::once_cell::sync::Lazy::new(|| {
+                        #salvo::handler::HoopedHandler::new(__salvo_wrapped)#(.hoop(#hoops))*
+                    });
+                #salvo::Handler::handle(&*__SALVO_HOOPED, req, depot, res, ctrl).await
+            }
+        }
+    })
+}
+
+/// Expand a hoop in `wrap`, `timeout(..)` and `cache(..)` are shorthands of the middlewares of `salvo`, other
+/// expressions are used as is. Integer literals with `ms`, `s`, `m` or `h` suffix are durations.
+fn wrap_hoop(salvo: &Ident, mut expr: Expr) -> syn::Result<TokenStream> {
+    expand_durations(&mut expr)?;
+    if let Expr::Call(call) = &expr {
+        if let Expr::Path(func) = &*call.func {
+            let shorthand = func.path.get_ident().map(|ident| ident.to_string());
+            if matches!(shorthand.as_deref(), Some("timeout") | Some("cache")) {
+                if call.args.len() != 1 {
+                    return Err(syn::Error::new_spanned(
+                        call,
+                        "expected one duration argument, such as `5s`",
+                    ));
+                }
+                let duration = &call.args[0];
+                return Ok(if shorthand.as_deref() == Some("timeout") {
+                    quote! { #salvo::timeout::Timeout::new(#duration) }
+                } else {
+                    quote! {
+                        #salvo::cache::Cache::new(
+                            #salvo::cache::MokaStore::builder().time_to_live(#duration).build(),
+                            #salvo::cache::RequestIssuer::new(),
+                        )
+                    }
+                });
+            }
+        }
+    }
+    Ok(expr.into_token_stream())
+}
+
+/// Replace integer literals with duration suffix, such as `500ms` and `5s`, by `std::time::Duration`.
+fn expand_durations(expr: &mut Expr) -> syn::Result<()> {
+    match expr {
+        Expr::Lit(lit) => {
+            if let Lit::Int(int) = &lit.lit {
+                let multiplier = match int.suffix() {
+                    "ms" => 1,
+                    "s" => 1_000,
+                    "m" => 60_000,
+                    "h" => 3_600_000,
+                    _ => return Ok(()),
+                };
+                let millis = int
+                    .base10_parse::<u64>()?
+                    .checked_mul(multiplier)
+                    .ok_or_else(|| syn::Error::new_spanned(int, "duration overflow"))?;
+                let millis = LitInt::new(&millis.to_string(), int.span());
+                *expr = parse_quote!(::std::time::Duration::from_millis(#millis));
+            }
+        }
+        Expr::Call(call) => {
+            for arg in &mut call.args {
+                expand_durations(arg)?;
+            }
+        }
+        Expr::MethodCall(call) => {
+            expand_durations(&mut call.receiver)?;
+            for arg in &mut call.args {
+                expand_durations(arg)?;
+            }
+        }
+        Expr::Paren(paren) => expand_durations(&mut paren.expr)?,
+        Expr::Group(group) => expand_durations(&mut group.expr)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_fn(salvo: &Ident, sig: &Signature, target: &TokenStream) -> syn::Result<TokenStream> {
     let name = &sig.ident;
     let mut extract_ts = Vec::with_capacity(sig.inputs.len());
     let mut call_args: Vec<Ident> = Vec::with_capacity(sig.inputs.len());
@@ -126,6 +286,26 @@ fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
             InputType::Receiver(_) => {
                 call_args.push(Ident::new("self", Span::call_site()));
             }
+            InputType::State(pat) => {
+                if let (_, Type::Path(ty)) = (&*pat.pat, &*pat.ty) {
+                    let id = Ident::new(&format!("s{count}"), Span::call_site());
+                    let ty = omit_type_path_lifetimes(ty);
+                    extract_ts.push(quote! {
+                        let #id: #ty = match <#ty>::from_depot(depot) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                #salvo::__private::tracing::error!(error = ?e, "failed to get state from depot");
+                                res.render(e);
+                                return;
+                            }
+                        };
+                    });
+                    call_args.push(id);
+                    count += 1;
+                } else {
+                    return Err(syn::Error::new_spanned(pat, "invalid param definition"));
+                }
+            }
         }
     }
 
@@ -136,7 +316,7 @@ fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
                     #[inline]
                     async fn handle(&self, req: &mut #salvo::Request, depot: &mut #salvo::Depot, res: &mut #salvo::Response, ctrl: &mut #salvo::FlowCtrl) {
                         #(#extract_ts)*
-                        Self::#name(#(#call_args),*)
+                        #target::#name(#(#call_args),*)
                     }
                 })
             } else {
@@ -144,7 +324,7 @@ fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
                     #[inline]
                     async fn handle(&self, req: &mut #salvo::Request, depot: &mut #salvo::Depot, res: &mut #salvo::Response, ctrl: &mut #salvo::FlowCtrl) {
                         #(#extract_ts)*
-                        Self::#name(#(#call_args),*).await
+                        #target::#name(#(#call_args),*).await
                     }
                 })
             }
@@ -155,7 +335,7 @@ fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
                     #[inline]
                     async fn handle(&self, req: &mut #salvo::Request, depot: &mut #salvo::Depot, res: &mut #salvo::Response, ctrl: &mut #salvo::FlowCtrl) {
                         #(#extract_ts)*
-                        #salvo::Writer::write(Self::#name(#(#call_args),*), req, depot, res).await;
+                        #salvo::Writer::write(#target::#name(#(#call_args),*), req, depot, res).await;
                     }
                 })
             } else {
@@ -163,7 +343,7 @@ fn handle_fn(salvo: &Ident, sig: &Signature) -> syn::Result<TokenStream> {
                     #[inline]
                     async fn handle(&self, req: &mut #salvo::Request, depot: &mut #salvo::Depot, res: &mut #salvo::Response, ctrl: &mut #salvo::FlowCtrl) {
                         #(#extract_ts)*
-                        #salvo::Writer::write(Self::#name(#(#call_args),*).await, req, depot, res).await;
+                        #salvo::Writer::write(#target::#name(#(#call_args),*).await, req, depot, res).await;
                     }
                 })
             }
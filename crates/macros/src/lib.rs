@@ -39,10 +39,23 @@ use shared::*;
 ///     "Hello World"
 /// }
 /// ```
+///
+/// Middlewares of a handler can be attached next to it by `wrap`, they are called in order before the handler,
+/// as if they are added to the router by `hoop`. `timeout(..)` and `cache(..)` are shorthands of `Timeout` and
+/// `Cache` with a `MokaStore` of `salvo`, which need `timeout` and `cache` features. Any other expression of a
+/// handler can be used as well. Integer literals with `ms`, `s`, `m` or `h` suffix are `std::time::Duration`s.
+///
+/// ```ignore
+/// #[handler(wrap = [timeout(5s), cache(60s), CachingHeaders::new()])]
+/// async fn report() -> String {
+///     build_report().await
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn handler(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn handler(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as handler::HandlerArgs);
     let item = parse_macro_input!(input as Item);
-    match handler::generate(item) {
+    match handler::generate(args, item) {
         Ok(stream) => stream.into(),
         Err(e) => e.to_compile_error().into(),
     }
@@ -75,7 +88,7 @@ mod tests {
         };
         let item = parse2(input).unwrap();
         assert_eq!(
-            handler::generate(item).unwrap().to_string(),
+            handler::generate(Default::default(), item).unwrap().to_string(),
             quote! {
                 #[allow(non_camel_case_types)]
                 #[derive(Debug)]
@@ -116,7 +129,7 @@ mod tests {
         };
         let item = parse2(input).unwrap();
         assert_eq!(
-            handler::generate(item).unwrap().to_string(),
+            handler::generate(Default::default(), item).unwrap().to_string(),
             quote! {
                 #[allow(non_camel_case_types)]
                 #[derive(Debug)]
@@ -152,6 +165,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handler_state_param() {
+        let input = quote! {
+            #[handler]
+            async fn hello(#[salvo(state)] config: State<Config>, query: State<Query>) {}
+        };
+        let item = parse2(input).unwrap();
+        let output = handler::generate(Default::default(), item).unwrap().to_string();
+        // the attribute is removed, and `State` without it is extracted from request.
+        assert!(!output.contains("[salvo (") && !output.contains("[salvo("));
+        assert_eq!(output.matches("from_depot").count(), 1);
+        assert_eq!(output.matches("Extractible").count(), 1);
+    }
+
     #[test]
     fn test_handler_for_impl() {
         let input = quote! {
@@ -164,7 +191,7 @@ mod tests {
         };
         let item = parse2(input).unwrap();
         assert_eq!(
-            handler::generate(item).unwrap().to_string(),
+            handler::generate(Default::default(), item).unwrap().to_string(),
             quote! {
                 #[handler]
                 impl Hello {
@@ -190,6 +217,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handler_wrap() {
+        let args: handler::HandlerArgs =
+            parse2(quote! { wrap = [timeout(5s), cache(2m), auth, Limiter::new(500ms)] }).unwrap();
+        let item = parse2(quote! {
+            async fn hello() -> &'static str {
+                "hello"
+            }
+        })
+        .unwrap();
+        let output = handler::generate(args, item).unwrap().to_string();
+        assert!(output.contains(
+            &quote! {
+                salvo::handler::HoopedHandler::new(__salvo_wrapped)
+                    .hoop(salvo::timeout::Timeout::new(::std::time::Duration::from_millis(5000)))
+                    .hoop(salvo::cache::Cache::new(
+                        salvo::cache::MokaStore::builder().time_to_live(::std::time::Duration::from_millis(120000)).build(),
+                        salvo::cache::RequestIssuer::new(),
+                    ))
+                    .hoop(auth)
+                    .hoop(Limiter::new(::std::time::Duration::from_millis(500)))
+            }
+            .to_string()
+        ));
+        assert!(output.contains(&quote! { hello::hello().await }.to_string()));
+
+        let args = parse2::<handler::HandlerArgs>(quote! { wrap = timeout(5s) });
+        assert!(args.is_err());
+    }
+
     #[test]
     fn test_extract_simple() {
         let input = quote! {
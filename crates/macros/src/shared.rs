@@ -2,7 +2,7 @@ use proc_macro2::Span;
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::ToTokens;
 use regex::Regex;
-use syn::{FnArg, Ident, PatType, Receiver, Type, TypePath};
+use syn::{Attribute, FnArg, Ident, PatType, Receiver, Signature, Type, TypePath};
 
 pub(crate) enum InputType<'a> {
     Request(&'a PatType),
@@ -12,6 +12,7 @@ pub(crate) enum InputType<'a> {
     Unknown,
     Receiver(&'a Receiver),
     NoReference(&'a PatType),
+    State(&'a PatType),
 }
 
 pub(crate) fn salvo_crate() -> syn::Ident {
@@ -30,6 +31,44 @@ pub(crate) fn salvo_crate() -> syn::Ident {
     }
 }
 
+/// Returns `true` if the parameter is extracted from depot by `State::from_depot`.
+///
+/// Imported names can not be resolved by macros, so only `State<T>` with full path, such as
+/// `salvo::extract::State<T>`, or parameters marked with `#[salvo(state)]` are treated as state.
+fn is_state_input(p: &PatType) -> bool {
+    if p.attrs.iter().any(is_state_attr) {
+        return true;
+    }
+    if let Type::Path(ty) = &*p.ty {
+        let segments = &ty.path.segments;
+        if ty.qself.is_none() && segments.len() == 3 {
+            return matches!(segments[0].ident.to_string().as_str(), "salvo" | "salvo_core")
+                && segments[1].ident == "extract"
+                && segments[2].ident == "State"
+                && matches!(segments[2].arguments, syn::PathArguments::AngleBracketed(_));
+        }
+    }
+    false
+}
+
+/// Returns `true` if the attribute is `#[salvo(state)]`.
+fn is_state_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("salvo")
+        && attr
+            .parse_args::<Ident>()
+            .map(|ident| ident == "state")
+            .unwrap_or(false)
+}
+
+/// Removes `#[salvo(state)]` of parameters, it is only used by macros.
+pub(crate) fn strip_state_attrs(sig: &mut Signature) {
+    for input in &mut sig.inputs {
+        if let FnArg::Typed(p) = input {
+            p.attrs.retain(|attr| !is_state_attr(attr));
+        }
+    }
+}
+
 pub(crate) fn parse_input_type(input: &FnArg) -> InputType {
     if let FnArg::Typed(p) = input {
         if let Type::Reference(ty) = &*p.ty {
@@ -53,6 +92,8 @@ pub(crate) fn parse_input_type(input: &FnArg) -> InputType {
             } else {
                 InputType::Unknown
             }
+        } else if is_state_input(p) {
+            InputType::State(p)
         } else {
             InputType::NoReference(p)
         }
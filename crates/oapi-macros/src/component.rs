@@ -174,14 +174,37 @@ impl<'c> ComponentSchema {
 
                 quote! { .additional_properties(#schema_property) }
             });
-
-        tokens.extend(quote! {
+        let object = quote! {
             #oapi::oapi::Object::new()
                 #additional_properties
                 #description_stream
                 #deprecated_stream
                 #default
-        });
+        };
+
+        // Keys of maps are serialized as strings, only keys with a referenced schema such as
+        // enums are described by `propertyNames`.
+        let key_path = type_tree
+            .children
+            .as_ref()
+            .and_then(|children| children.first())
+            .filter(|key| key.value_type == ValueType::Object && key.generic_type.is_none())
+            .and_then(|key| key.path.as_ref());
+        if let Some(key_path) = key_path {
+            tokens.extend(quote! {
+                {
+                    let object = #object;
+                    let property_names = <#key_path as #oapi::oapi::ToSchema>::to_schema(components);
+                    if let #oapi::oapi::RefOr::Ref(_) = property_names {
+                        object.property_names(property_names)
+                    } else {
+                        object
+                    }
+                }
+            });
+        } else {
+            tokens.extend(object);
+        }
 
         example.to_tokens(tokens);
         nullable.to_tokens(tokens)
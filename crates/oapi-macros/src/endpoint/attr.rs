@@ -1,7 +1,7 @@
 use proc_macro2::Ident;
 use syn::punctuated::Punctuated;
 use syn::{parenthesized, parse::Parse};
-use syn::{Expr, LitStr};
+use syn::{Expr, LitStr, Path};
 
 use crate::operation::request_body::RequestBodyAttr;
 use crate::{parse_utils, security_requirement::SecurityRequirementAttr, Array, Parameter, Response, Token};
@@ -15,6 +15,7 @@ pub(crate) struct EndpointAttr<'p> {
     pub(crate) tags: Option<Vec<String>>,
     pub(crate) parameters: Vec<Parameter<'p>>,
     pub(crate) security: Option<Array<'p, SecurityRequirementAttr>>,
+    pub(crate) callbacks: Vec<CallbackAttr>,
 
     pub(crate) doc_comments: Option<Vec<String>>,
     pub(crate) deprecated: Option<bool>,
@@ -23,7 +24,7 @@ pub(crate) struct EndpointAttr<'p> {
 impl Parse for EndpointAttr<'_> {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         const EXPECTED_ATTRIBUTE_MESSAGE: &str =
-            "unexpected identifier, expected any of: operation_id, request_body, responses, parameters, tags, security";
+            "unexpected identifier, expected any of: operation_id, request_body, responses, parameters, tags, security, callbacks";
         let mut attr = EndpointAttr::default();
 
         while !input.is_empty() {
@@ -68,6 +69,12 @@ impl Parse for EndpointAttr<'_> {
                     parenthesized!(security in input);
                     attr.security = Some(parse_utils::parse_groups(&security)?)
                 }
+                "callbacks" => {
+                    let callbacks;
+                    parenthesized!(callbacks in input);
+                    attr.callbacks = Punctuated::<CallbackAttr, Token![,]>::parse_terminated(&callbacks)
+                        .map(|punctuated| punctuated.into_iter().collect::<Vec<CallbackAttr>>())?;
+                }
                 _ => {
                     return Err(syn::Error::new(ident.span(), EXPECTED_ATTRIBUTE_MESSAGE));
                 }
@@ -81,3 +88,47 @@ impl Parse for EndpointAttr<'_> {
         Ok(attr)
     }
 }
+
+/// Callback of the endpoint, which is parsed from `("name" = "expression", method = handler)`,
+/// the `handler` must be another endpoint which describes the callback request.
+#[derive(Debug)]
+pub(crate) struct CallbackAttr {
+    pub(crate) name: LitStr,
+    pub(crate) expression: LitStr,
+    pub(crate) method: Ident,
+    pub(crate) handler: Path,
+}
+
+impl Parse for CallbackAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        const EXPECTED_METHOD_MESSAGE: &str =
+            "unexpected method, expected any of: get, post, put, delete, options, head, patch, trace, connect";
+        let content;
+        parenthesized!(content in input);
+        let name = content.parse::<LitStr>()?;
+        content.parse::<Token![=]>()?;
+        let expression = content.parse::<LitStr>()?;
+        content.parse::<Token![,]>()?;
+        let method = content.parse::<Ident>()?;
+        let variant = match &*method.to_string() {
+            "get" => "Get",
+            "post" => "Post",
+            "put" => "Put",
+            "delete" => "Delete",
+            "options" => "Options",
+            "head" => "Head",
+            "patch" => "Patch",
+            "trace" => "Trace",
+            "connect" => "Connect",
+            _ => return Err(syn::Error::new(method.span(), EXPECTED_METHOD_MESSAGE)),
+        };
+        content.parse::<Token![=]>()?;
+        let handler = content.parse::<Path>()?;
+        Ok(Self {
+            name,
+            expression,
+            method: Ident::new(variant, method.span()),
+            handler,
+        })
+    }
+}
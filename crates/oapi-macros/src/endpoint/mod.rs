@@ -3,10 +3,10 @@ use quote::{quote, ToTokens};
 use syn::{Expr, Ident, ImplItem, Item, Pat, ReturnType, Signature, Type};
 
 use crate::doc_comment::CommentAttributes;
-use crate::{omit_type_path_lifetimes, parse_input_type, Array, InputType, Operation};
+use crate::{omit_type_path_lifetimes, parse_input_type, strip_state_attrs, Array, InputType, Operation};
 
 mod attr;
-pub(crate) use attr::EndpointAttr;
+pub(crate) use attr::{CallbackAttr, EndpointAttr};
 
 fn metadata(
     salvo: &Ident,
@@ -43,9 +43,6 @@ fn metadata(
             }
             let mut operation = #oapi::oapi::Operation::new();
             modify(&mut components, &mut operation);
-            if operation.operation_id.is_none() {
-                operation.operation_id = Some(::std::any::type_name::<#name>().replace("::", "."));
-            }
             if !status_codes.is_empty() {
                 let responses = std::ops::DerefMut::deref_mut(&mut operation.responses);
                 responses.retain(|k,_| {
@@ -72,9 +69,11 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
     let oapi = crate::oapi_crate();
     match input {
         Item::Fn(mut item_fn) => {
+            let (hfn, modifiers) = handle_fn(&salvo, &oapi, &item_fn.sig)?;
+            strip_state_attrs(&mut item_fn.sig);
             let attrs = &item_fn.attrs;
             let vis = &item_fn.vis;
-            let sig = &mut item_fn.sig;
+            let sig = &item_fn.sig;
             let body = &item_fn.block;
             let name = &sig.ident;
             let docs = item_fn
@@ -104,7 +103,6 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
                 None
             };
 
-            let (hfn, modifiers) = handle_fn(&salvo, &oapi, sig)?;
             let meta = metadata(&salvo, &oapi, attr, name, modifiers)?;
             Ok(quote! {
                 #sdef
@@ -115,7 +113,7 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
                 #meta
             })
         }
-        Item::Impl(item_impl) => {
+        Item::Impl(mut item_impl) => {
             let attrs = &item_impl.attrs;
 
             attr.doc_comments = Some(CommentAttributes::from_attributes(attrs).0);
@@ -126,18 +124,18 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
             };
 
             let mut hmtd = None;
-            for item in &item_impl.items {
+            for item in &mut item_impl.items {
                 if let ImplItem::Fn(method) = item {
                     if method.sig.ident == Ident::new("handle", Span::call_site()) {
                         hmtd = Some(method);
                     }
                 }
             }
-            if hmtd.is_none() {
+            let Some(hmtd) = hmtd else {
                 return Err(syn::Error::new_spanned(item_impl.impl_token, "missing handle function"));
-            }
-            let hmtd = hmtd.unwrap();
+            };
             let (hfn, modifiers) = handle_fn(&salvo, &oapi, &hmtd.sig)?;
+            strip_state_attrs(&mut hmtd.sig);
             let ty = &item_impl.self_ty;
             let (impl_generics, _, where_clause) = &item_impl.generics.split_for_impl();
             let name = Ident::new(&ty.to_token_stream().to_string(), Span::call_site());
@@ -215,6 +213,25 @@ fn handle_fn(salvo: &Ident, oapi: &Ident, sig: &Signature) -> syn::Result<(Token
             InputType::Receiver(_) => {
                 call_args.push(Ident::new("self", Span::call_site()));
             }
+            InputType::State(pat) => {
+                if let (Pat::Ident(ident), Type::Path(ty)) = (&*pat.pat, &*pat.ty) {
+                    call_args.push(ident.ident.clone());
+                    let id = &pat.pat;
+                    let ty = omit_type_path_lifetimes(ty);
+                    extract_ts.push(quote! {
+                        let #id: #ty = match <#ty>::from_depot(depot) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                #salvo::__private::tracing::error!(error = ?e, "failed to get state from depot in endpoint macro");
+                                res.render(e);
+                                return;
+                            }
+                        };
+                    });
+                } else {
+                    return Err(syn::Error::new_spanned(pat, "invalid param definition"));
+                }
+            }
         }
     }
 
@@ -592,9 +592,6 @@ mod tests {
                     fn modify(components: &mut salvo::oapi::Components, operation: &mut salvo::oapi::Operation) {}
                     let mut operation = salvo::oapi::Operation::new();
                     modify(&mut components, &mut operation);
-                    if operation.operation_id.is_none() {
-                        operation.operation_id = Some(::std::any::type_name::<hello>().replace("::", "."));
-                    }
                     if !status_codes.is_empty() {
                         let responses = std::ops::DerefMut::deref_mut(&mut operation.responses);
                         responses.retain(|k, _| {
@@ -5,7 +5,7 @@ use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::quote;
 use syn::{parenthesized, parse::Parse, token::Paren, Expr, ExprPath, Path, Token, Type};
 
-use crate::endpoint::EndpointAttr;
+use crate::endpoint::{CallbackAttr, EndpointAttr};
 use crate::schema_type::SchemaType;
 use crate::security_requirement::SecurityRequirementAttr;
 use crate::type_tree::{GenericType, TypeTree};
@@ -28,6 +28,7 @@ pub(crate) struct Operation<'a> {
     request_body: Option<&'a RequestBodyAttr<'a>>,
     responses: &'a Vec<Response<'a>>,
     security: Option<&'a Array<'a, SecurityRequirementAttr>>,
+    callbacks: &'a Vec<CallbackAttr>,
 }
 
 impl<'a> Operation<'a> {
@@ -42,6 +43,7 @@ impl<'a> Operation<'a> {
             request_body: attr.request_body.as_ref(),
             responses: attr.responses.as_ref(),
             security: attr.security.as_ref(),
+            callbacks: &attr.callbacks,
         }
     }
     pub(crate) fn modifiers(&self) -> Vec<TokenStream2> {
@@ -70,6 +72,27 @@ impl<'a> Operation<'a> {
                 operation.securities.append(&mut #security_requirements.into_iter().collect());
             })
         }
+        for callback in self.callbacks {
+            let CallbackAttr {
+                name,
+                expression,
+                method,
+                handler,
+            } = callback;
+            modifiers.push(quote! {
+                if let Some(creator) = #oapi::oapi::EndpointRegistry::find(&::std::any::TypeId::of::<#handler>()) {
+                    let #oapi::oapi::Endpoint {
+                        operation: callback,
+                        components: mut callback_components,
+                    } = (creator)();
+                    components.append(&mut callback_components);
+                    operation.callbacks.entry(#name.to_owned()).or_default().insert(
+                        #expression,
+                        #oapi::oapi::PathItem::new(#oapi::oapi::PathItemType::#method, callback),
+                    );
+                }
+            });
+        }
         if let Some(operation_id) = &self.operation_id {
             modifiers.push(quote! {
                 operation.operation_id = Some(#operation_id);
@@ -4,8 +4,8 @@ use proc_macro2::{Span, TokenStream};
 use proc_macro_error::abort;
 use quote::{quote, ToTokens};
 use syn::{
-    parse::Parse, punctuated::Punctuated, Attribute, Data, Field, GenericParam, Generics, Ident, Lifetime,
-    LifetimeParam, Token,
+    parse::Parse, punctuated::Punctuated, Attribute, Data, Expr, ExprLit, Field, GenericParam, Generics, Ident,
+    Lifetime, LifetimeParam, Lit, Meta, MetaNameValue, Token,
 };
 
 use crate::component::{self, ComponentSchema};
@@ -96,17 +96,34 @@ impl ToTokens for ToParameters {
         let style = pop_feature!(parameters_features => Feature::Style(_));
         let parameter_in = pop_feature!(parameters_features => Feature::ParameterIn(_));
         let rename_all = pop_feature!(parameters_features => Feature::RenameAll(_));
-        let source_from = if let Some(Feature::ParameterIn(feature::ParameterIn(parameter_in))) = parameter_in {
-            match parameter_in {
-                ParameterIn::Query => quote! {  #salvo::extract::metadata::SourceFrom::Query },
-                ParameterIn::Header => quote! {  #salvo::extract::metadata::SourceFrom::Header },
-                ParameterIn::Path => quote! { #salvo::extract::metadata::SourceFrom::Param },
-                ParameterIn::Cookie => quote! {  #salvo::extract::metadata::SourceFrom::Cookie },
-            }
+        let extract_default_sources = ExtractSource::find_all(&self.attrs, "default_source");
+        // `#[salvo(extract(default_source(...)))]` documents the parameters when no explicit `parameter_in` is given.
+        let parameter_in = parameter_in.or_else(|| {
+            extract_default_sources
+                .first()
+                .and_then(ExtractSource::parameter_in)
+                .map(|parameter_in| Feature::ParameterIn(feature::ParameterIn(parameter_in)))
+        });
+        let default_sources = if extract_default_sources.is_empty() {
+            let source_from = if let Some(Feature::ParameterIn(feature::ParameterIn(parameter_in))) = parameter_in {
+                match parameter_in {
+                    ParameterIn::Query => quote! {  #salvo::extract::metadata::SourceFrom::Query },
+                    ParameterIn::Header => quote! {  #salvo::extract::metadata::SourceFrom::Header },
+                    ParameterIn::Path => quote! { #salvo::extract::metadata::SourceFrom::Param },
+                    ParameterIn::Cookie => quote! {  #salvo::extract::metadata::SourceFrom::Cookie },
+                }
+            } else {
+                quote! { #salvo::extract::metadata::SourceFrom::Query }
+            };
+            vec![
+                quote! { #salvo::extract::metadata::Source::new(#source_from, #salvo::extract::metadata::SourceFormat::MultiMap) },
+            ]
         } else {
-            quote! { #salvo::extract::metadata::SourceFrom::Query }
+            extract_default_sources
+                .iter()
+                .map(|source| source.to_metadata(&salvo))
+                .collect()
         };
-        let default_source = quote! { #salvo::extract::metadata::Source::new(#source_from, #salvo::extract::metadata::SourceFormat::MultiMap) };
         let fields = self
         .get_struct_fields(&names.as_ref())
         .enumerate()
@@ -122,9 +139,23 @@ impl ToTokens for ToParameters {
                     help = "consider using a struct with named fields instead, or use `#[salvo(parameters(names(\"...\")))]` to specify a name for each field",
                 }
             };
-            quote!{ #salvo::extract::metadata::Field::new(#name)}
+            let sources = ExtractSource::find_all(&field.attrs, "source")
+                .iter()
+                .map(|source| {
+                    let source = source.to_metadata(&salvo);
+                    quote! { .add_source(#source) }
+                })
+                .collect::<Vec<_>>();
+            let rename = ExtractSource::find_rename(&field.attrs).map(|rename| quote! { .rename(#rename) });
+            quote!{ #salvo::extract::metadata::Field::new(#name) #(#sources)* #rename }
         })
         .collect::<Vec<_>>();
+        let rename_all_rule = rename_all.as_ref().and_then(|feature| match feature {
+            Feature::RenameAll(rename_all) => Some(rename_all.as_rename_rule()),
+            _ => None,
+        });
+        let mut body_properties = Vec::new();
+        let mut body_content_type = "application/json";
         let params = self
             .get_struct_fields(&names.as_ref())
             .enumerate()
@@ -136,10 +167,31 @@ impl ToTokens for ToParameters {
                     None
                 }
             })
-            .map(|(index, field, field_serde_params)|{
+            .filter_map(|(index, field, field_serde_params)| {
+                let source = ExtractSource::find_all(&field.attrs, "source").into_iter().next();
+                match source.as_ref().map(|source| source.from.as_str()) {
+                    Some("body") => {
+                        if source.as_ref().map(|source| source.format.as_str()) == Some("multimap") {
+                            body_content_type = "application/x-www-form-urlencoded";
+                        }
+                        body_properties.push(BodyProperty {
+                            field,
+                            field_serde_params,
+                            rename_all: rename_all_rule,
+                            serde_container: serde_container.as_ref(),
+                        });
+                        None
+                    }
+                    // Nested extractible types describe themselves, they are not a single parameter.
+                    Some("request") => None,
+                    _ => Some((index, field, field_serde_params, source.as_ref().and_then(ExtractSource::parameter_in))),
+                }
+            })
+            .map(|(index, field, field_serde_params, source_in)|{
                 Parameter {
                     field,
                     field_serde_params,
+                    source_in: source_in.map(|parameter_in| Feature::ParameterIn(feature::ParameterIn(parameter_in))),
                     container_attributes: FieldParameterContainerAttributes {
                         rename_all: rename_all.as_ref().and_then(|feature| {
                             match feature {
@@ -181,6 +233,18 @@ impl ToTokens for ToParameters {
                 _ => quote! {None},
             })
             .unwrap_or_else(|| quote! {None});
+        let request_body = if body_properties.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                let schema = #oapi::oapi::Object::new() #(#body_properties)*;
+                operation.request_body = Some(
+                    #oapi::oapi::RequestBody::new()
+                        .add_content(#body_content_type, #oapi::oapi::Content::new(schema))
+                        .required(#oapi::oapi::Required::True)
+                );
+            })
+        };
         let name = ident.to_string();
         tokens.extend(quote! {
             impl #de_impl_generics #oapi::oapi::ToParameters<'__de> for #ident #ty_generics #where_clause {
@@ -193,6 +257,7 @@ impl ToTokens for ToParameters {
                     for parameter in <Self as #oapi::oapi::ToParameters>::to_parameters(components) {
                         operation.parameters.insert(parameter);
                     }
+                    #request_body
                 }
             }
             #[#salvo::async_trait]
@@ -201,7 +266,7 @@ impl ToTokens for ToParameters {
                     static METADATA: #salvo::__private::once_cell::sync::OnceCell<#salvo::extract::Metadata> = #salvo::__private::once_cell::sync::OnceCell::new();
                     METADATA.get_or_init(||
                         #salvo::extract::Metadata::new(#name)
-                            .default_sources(vec![#default_source])
+                            .default_sources(vec![#(#default_sources),*])
                             .fields(vec![#(#fields),*])
                             .rename_all(#rename_all)
                     )
@@ -328,12 +393,173 @@ impl Parse for FieldFeatures {
     }
 }
 
+/// Source declared with `#[salvo(extract(source(...)))]` or `#[salvo(extract(default_source(...)))]`.
+#[derive(Debug)]
+struct ExtractSource {
+    from: String,
+    format: String,
+}
+
+impl Parse for ExtractSource {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut source = ExtractSource {
+            from: "request".to_string(),
+            format: String::new(),
+        };
+        let metas: Punctuated<MetaNameValue, Token![,]> = Punctuated::parse_terminated(input)?;
+        for meta in metas {
+            let value = match &meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => s.value(),
+                _ => return Err(syn::Error::new_spanned(&meta.value, "expected a string literal")),
+            };
+            if meta.path.is_ident("from") {
+                source.from = value;
+            } else if meta.path.is_ident("format") {
+                source.format = value;
+            } else {
+                return Err(syn::Error::new_spanned(meta.path, "unexpected attribute"));
+            }
+        }
+        if source.format.is_empty() {
+            source.format = if source.from == "request" {
+                "request".to_string()
+            } else {
+                "multimap".to_string()
+            };
+        }
+        Ok(source)
+    }
+}
+
+impl ExtractSource {
+    /// Collect all sources declared under `#[salvo(extract(...))]` with the given `key`.
+    fn find_all(attrs: &[Attribute], key: &str) -> Vec<Self> {
+        Self::find_extract_metas(attrs)
+            .into_iter()
+            .filter_map(|meta| match meta {
+                Meta::List(list) if list.path.is_ident(key) => Some(list.parse_args::<Self>().unwrap_or_abort()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find `#[salvo(extract(rename = "..."))]` for a field.
+    fn find_rename(attrs: &[Attribute]) -> Option<String> {
+        Self::find_extract_metas(attrs)
+            .into_iter()
+            .filter_map(|meta| match meta {
+                Meta::NameValue(MetaNameValue {
+                    path,
+                    value: Expr::Lit(ExprLit { lit: Lit::Str(s), .. }),
+                    ..
+                }) if path.is_ident("rename") => Some(s.value()),
+                _ => None,
+            })
+            .last()
+    }
+
+    fn find_extract_metas(attrs: &[Attribute]) -> Vec<Meta> {
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("salvo"))
+            .filter_map(|attr| attribute::find_nested_list(attr, "extract").ok().flatten())
+            .flat_map(|list| {
+                list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .unwrap_or_abort()
+            })
+            .collect()
+    }
+
+    fn parameter_in(&self) -> Option<ParameterIn> {
+        match self.from.as_str() {
+            "query" => Some(ParameterIn::Query),
+            "param" => Some(ParameterIn::Path),
+            "header" => Some(ParameterIn::Header),
+            "cookie" => Some(ParameterIn::Cookie),
+            _ => None,
+        }
+    }
+
+    fn to_metadata(&self, salvo: &Ident) -> TokenStream {
+        let from = match self.from.as_str() {
+            "request" => quote! { Request },
+            "param" => quote! { Param },
+            "query" => quote! { Query },
+            "header" => quote! { Header },
+            "cookie" => quote! { Cookie },
+            "body" => quote! { Body },
+            from => abort!(Span::call_site(), "source from is invalid: {}", from),
+        };
+        let format = match self.format.as_str() {
+            "multimap" => quote! { MultiMap },
+            "json" => quote! { Json },
+            "request" => quote! { Request },
+            format => abort!(Span::call_site(), "source format is invalid: {}", format),
+        };
+        quote! {
+            #salvo::extract::metadata::Source::new(
+                #salvo::extract::metadata::SourceFrom::#from,
+                #salvo::extract::metadata::SourceFormat::#format
+            )
+        }
+    }
+}
+
+/// Field extracted from the request body, rendered as a property of the request body schema.
+struct BodyProperty<'a> {
+    field: &'a Field,
+    field_serde_params: Option<SerdeValue>,
+    rename_all: Option<&'a RenameRule>,
+    serde_container: Option<&'a SerdeContainer>,
+}
+
+impl ToTokens for BodyProperty<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let field = self.field;
+        let name = field
+            .ident
+            .as_ref()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_string())
+            .unwrap_or_else(|| abort!(field, "fields extracted from body must be named"));
+        let rename_to = self
+            .field_serde_params
+            .as_ref()
+            .and_then(|serde_params| serde_params.rename.clone())
+            .or_else(|| ExtractSource::find_rename(&field.attrs))
+            .map(Cow::Owned);
+        let rename_all = self
+            .serde_container
+            .and_then(|serde_container| serde_container.rename_all.as_ref())
+            .or(self.rename_all);
+        let name = crate::rename::<FieldRename>(&name, rename_to, rename_all)
+            .map(Cow::into_owned)
+            .unwrap_or(name);
+
+        let type_tree = TypeTree::from_type(&field.ty);
+        let description = CommentAttributes::from_attributes(&field.attrs);
+        let schema = ComponentSchema::new(component::ComponentSchemaProps {
+            type_tree: &type_tree,
+            features: None,
+            description: Some(&description),
+            deprecated: None,
+            object_name: "",
+            type_definition: false,
+        });
+        tokens.extend(quote! { .property(#name, #schema) });
+        if !type_tree.is_option() && crate::is_required(self.field_serde_params.as_ref(), self.serde_container) {
+            tokens.extend(quote! { .required(#name) });
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Parameter<'a> {
     /// Field in the container used to create a single parameter.
     field: &'a Field,
     //// Field serde params parsed from field attributes.
     field_serde_params: Option<SerdeValue>,
+    /// Location taken from the field's `#[salvo(extract(source(...)))]` attribute.
+    source_in: Option<Feature>,
     /// Attributes on the container which are relevant for this macro.
     container_attributes: FieldParameterContainerAttributes<'a>,
     /// Either serde rename all rule or to_parameters rename all rule if provided.
@@ -434,7 +660,8 @@ impl ToTokens for Parameter<'_> {
         let rename_to = field_serde_params
             .as_ref()
             .and_then(|field_param_serde| field_param_serde.rename.as_deref().map(Cow::Borrowed))
-            .or_else(|| rename.map(Cow::Owned));
+            .or_else(|| rename.map(Cow::Owned))
+            .or_else(|| ExtractSource::find_rename(&field.attrs).map(Cow::Owned));
         let rename_all = self
             .serde_container
             .as_ref()
@@ -448,7 +675,9 @@ impl ToTokens for Parameter<'_> {
         let type_tree = TypeTree::from_type(&field.ty);
 
         tokens.extend(quote! { #oapi::oapi::parameter::Parameter::new(#name)});
-        if let Some(ref parameter_in) = self.container_attributes.parameter_in {
+        if let Some(ref parameter_in) = self.source_in {
+            tokens.extend(parameter_in.into_token_stream());
+        } else if let Some(ref parameter_in) = self.container_attributes.parameter_in {
             tokens.extend(parameter_in.into_token_stream());
         }
 
@@ -712,52 +712,43 @@ impl ComplexEnum<'_> {
                 }
             }
             Fields::Unnamed(unnamed_fields) => {
-                if unnamed_fields.unnamed.len() == 1 {
-                    let (symbol_features, mut unnamed_struct_features) = variant
-                        .attrs
-                        .parse_features::<EnumUnnamedFieldVariantFeatures>()
-                        .into_inner()
-                        .map(|features| features.split_for_symbol())
-                        .unwrap_or_default();
-                    let variant_name = rename_enum_variant(
-                        name.as_ref(),
-                        &mut unnamed_struct_features,
-                        variant_rules,
-                        container_rules,
-                        rename_all,
-                    );
-
-                    let unnamed_enum = UnnamedStructSchema {
-                        struct_name: Cow::Borrowed(&*self.enum_name),
-                        attributes: &variant.attrs,
-                        features: Some(unnamed_struct_features),
-                        fields: &unnamed_fields.unnamed,
-                        symbol: None,
-                        inline: None,
-                    };
+                // Content of tuple variant with multiple fields is an array of the fields.
+                let (symbol_features, mut unnamed_struct_features) = variant
+                    .attrs
+                    .parse_features::<EnumUnnamedFieldVariantFeatures>()
+                    .into_inner()
+                    .map(|features| features.split_for_symbol())
+                    .unwrap_or_default();
+                let variant_name = rename_enum_variant(
+                    name.as_ref(),
+                    &mut unnamed_struct_features,
+                    variant_rules,
+                    container_rules,
+                    rename_all,
+                );
 
-                    let symbol = symbol_features.first().map(ToTokens::to_token_stream);
-                    let variant_name_tokens = Enum::new([SimpleEnumVariant {
-                        value: variant_name.unwrap_or(Cow::Borrowed(&name)).to_token_stream(),
-                    }]);
+                let unnamed_enum = UnnamedStructSchema {
+                    struct_name: Cow::Borrowed(&*self.enum_name),
+                    attributes: &variant.attrs,
+                    features: Some(unnamed_struct_features),
+                    fields: &unnamed_fields.unnamed,
+                    symbol: None,
+                    inline: None,
+                };
 
-                    quote! {
-                        #oapi::oapi::schema::Object::new()
-                            #symbol
-                            .schema_type(#oapi::oapi::schema::SchemaType::Object)
-                            .property(#tag, #variant_name_tokens)
-                            .required(#tag)
-                            .property(#content, #unnamed_enum)
-                            .required(#content)
-                    }
-                } else {
-                    abort!(
-                        variant,
-                        "Unnamed (tuple) enum variants are unsupported for adjacently tagged enums using the `tag = <tag>, content = <content>` serde attribute";
+                let symbol = symbol_features.first().map(ToTokens::to_token_stream);
+                let variant_name_tokens = Enum::new([SimpleEnumVariant {
+                    value: variant_name.unwrap_or(Cow::Borrowed(&name)).to_token_stream(),
+                }]);
 
-                        help = "Try using a different serde enum representation";
-                        note = "See more about enum limitations here: `https://serde.rs/enum-representations.html#adjacently-tagged`"
-                    );
+                quote! {
+                    #oapi::oapi::schema::Object::new()
+                        #symbol
+                        .schema_type(#oapi::oapi::schema::SchemaType::Object)
+                        .property(#tag, #variant_name_tokens)
+                        .required(#tag)
+                        .property(#content, #unnamed_enum)
+                        .required(#content)
                 }
             }
             Fields::Unit => {
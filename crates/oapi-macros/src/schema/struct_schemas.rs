@@ -240,9 +240,10 @@ pub(super) struct UnnamedStructSchema<'a> {
 }
 
 impl ToTokens for UnnamedStructSchema<'_> {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+    fn to_tokens(&self, stream: &mut TokenStream) {
         let oapi = crate::oapi_crate();
         let fields_len = self.fields.len();
+        let mut tokens = TokenStream::new();
         let first_field = self.fields.first().unwrap();
         let first_part = &TypeTree::from_type(&first_field.ty);
 
@@ -305,9 +306,15 @@ impl ToTokens for UnnamedStructSchema<'_> {
 
         if fields_len > 1 {
             let description = CommentAttributes::from_attributes(self.attributes).as_formatted_string();
-            tokens.extend(
-                quote! { .to_array_builder().description(Some(#description)).max_items(Some(#fields_len)).min_items(Some(#fields_len)) },
-            )
+            let description = (!description.is_empty()).then(|| quote! { .description(#description) });
+            stream.extend(quote! {
+                #oapi::oapi::schema::Array::new(#tokens)
+                    #description
+                    .max_items(#fields_len)
+                    .min_items(#fields_len)
+            })
+        } else {
+            stream.extend(tokens);
         }
     }
 }
@@ -52,7 +52,10 @@ impl SchemaType<'_> {
 
             #[cfg(feature = "chrono")]
             if !primitive {
-                primitive = matches!(name, "DateTime" | "NaiveDate" | "Duration" | "NaiveDateTime");
+                primitive = matches!(
+                    name,
+                    "DateTime" | "NaiveDate" | "NaiveTime" | "Duration" | "NaiveDateTime"
+                );
             }
             #[cfg(any(feature = "decimal", feature = "decimal-float"))]
             if !primitive {
@@ -72,7 +75,10 @@ impl SchemaType<'_> {
             }
             #[cfg(feature = "time")]
             if !primitive {
-                primitive = matches!(name, "Date" | "PrimitiveDateTime" | "OffsetDateTime" | "Duration");
+                primitive = matches!(
+                    name,
+                    "Date" | "Time" | "PrimitiveDateTime" | "OffsetDateTime" | "Duration"
+                );
             }
 
             primitive
@@ -158,6 +164,8 @@ impl ToTokens for SchemaType<'_> {
             "NaiveDateTime" => tokens.extend(quote! { #oapi::oapi::SchemaType::String }),
             #[cfg(feature = "chrono")]
             "NaiveDate" => tokens.extend(quote!(#oapi::oapi::SchemaType::String)),
+            #[cfg(feature = "chrono")]
+            "NaiveTime" => tokens.extend(quote!(#oapi::oapi::SchemaType::String)),
             #[cfg(any(feature = "chrono", feature = "time"))]
             "Date" | "Duration" => tokens.extend(quote! { #oapi::oapi::SchemaType::String }),
             #[cfg(all(feature = "decimal", feature = "decimal-float"))]
@@ -173,7 +181,9 @@ impl ToTokens for SchemaType<'_> {
             #[cfg(feature = "uuid")]
             "Uuid" => tokens.extend(quote! { #oapi::oapi::SchemaType::String }),
             #[cfg(feature = "time")]
-            "PrimitiveDateTime" | "OffsetDateTime" => tokens.extend(quote! { #oapi::oapi::SchemaType::String }),
+            "Time" | "PrimitiveDateTime" | "OffsetDateTime" => {
+                tokens.extend(quote! { #oapi::oapi::SchemaType::String })
+            }
             _ => tokens.extend(quote! { #oapi::oapi::SchemaType::Object }),
         }
     }
@@ -258,7 +268,7 @@ impl Type<'_> {
 
             #[cfg(feature = "chrono")]
             if !known_format {
-                known_format = matches!(name, "DateTime" | "NaiveDate" | "NaiveDateTime");
+                known_format = matches!(name, "DateTime" | "NaiveDate" | "NaiveTime" | "NaiveDateTime");
             }
             #[cfg(feature = "decimal")]
             if !known_format {
@@ -283,7 +293,7 @@ impl Type<'_> {
 
             #[cfg(feature = "time")]
             if !known_format {
-                known_format = matches!(name, "Date" | "PrimitiveDateTime" | "OffsetDateTime");
+                known_format = matches!(name, "Date" | "Time" | "PrimitiveDateTime" | "OffsetDateTime");
             }
 
             known_format
@@ -327,6 +337,10 @@ impl ToTokens for Type<'_> {
                 tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::Date) })
             }
             #[cfg(feature = "chrono")]
+            "NaiveTime" => {
+                tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::Time) })
+            }
+            #[cfg(feature = "chrono")]
             "DateTime" => {
                 tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::DateTime) })
             }
@@ -336,6 +350,8 @@ impl ToTokens for Type<'_> {
             }
             #[cfg(feature = "time")]
             "Date" => tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::Date) }),
+            #[cfg(feature = "time")]
+            "Time" => tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::Time) }),
             #[cfg(feature = "url")]
             "Url" => tokens.extend(quote! { #oapi::oapi::SchemaFormat::KnownFormat(#oapi::oapi::KnownFormat::Url) }),
             #[cfg(feature = "ulid")]
@@ -361,6 +377,7 @@ pub(crate) enum Variant {
     Byte,
     Binary,
     Date,
+    Time,
     DateTime,
     Password,
     #[cfg(feature = "url")]
@@ -374,9 +391,9 @@ pub(crate) enum Variant {
 
 impl Parse for Variant {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        const FORMATS: [&str; 12] = [
-            "Int32", "Int64", "Float", "Double", "Byte", "Binary", "Date", "DateTime", "Password", "Ulid", "Uuid",
-            "Url",
+        const FORMATS: [&str; 13] = [
+            "Int32", "Int64", "Float", "Double", "Byte", "Binary", "Date", "Time", "DateTime", "Password", "Ulid",
+            "Uuid", "Url",
         ];
         let excluded_format: &[&str] = &[
             #[cfg(not(feature = "url"))]
@@ -404,6 +421,7 @@ impl Parse for Variant {
                 "Byte" => Ok(Self::Byte),
                 "Binary" => Ok(Self::Binary),
                 "Date" => Ok(Self::Date),
+                "Time" => Ok(Self::Time),
                 "DateTime" => Ok(Self::DateTime),
                 "Password" => Ok(Self::Password),
                 #[cfg(feature = "url")]
@@ -454,6 +472,9 @@ impl ToTokens for Variant {
             Self::Date => tokens.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
                 #oapi::oapi::KnownFormat::Date
             ))),
+            Self::Time => tokens.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
+                #oapi::oapi::KnownFormat::Time
+            ))),
             Self::DateTime => tokens.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
                 #oapi::oapi::KnownFormat::DateTime
             ))),
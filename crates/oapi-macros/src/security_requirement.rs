@@ -9,48 +9,50 @@ use syn::{
 
 use crate::Array;
 
+/// Security requirement parsed from `("name" = ["scope"], "other" = [])`, all schemes in one requirement
+/// are required together.
 #[derive(Default, Debug)]
 pub(crate) struct SecurityRequirementAttr {
-    name: Option<String>,
-    scopes: Option<Vec<String>>,
+    schemes: Vec<(String, Vec<String>)>,
 }
 
 impl Parse for SecurityRequirementAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.is_empty() {
-            return Ok(Self { ..Default::default() });
-        }
-        let name = input.parse::<LitStr>()?.value();
-        input.parse::<Token![=]>()?;
+        let mut schemes = Vec::new();
+        while !input.is_empty() {
+            let name = input.parse::<LitStr>()?.value();
+            input.parse::<Token![=]>()?;
+
+            let scopes_stream;
+            bracketed!(scopes_stream in input);
+            let scopes = Punctuated::<LitStr, Token![,]>::parse_terminated(&scopes_stream)?
+                .iter()
+                .map(LitStr::value)
+                .collect::<Vec<_>>();
+            schemes.push((name, scopes));
 
-        let scopes_stream;
-        bracketed!(scopes_stream in input);
-        let scopes = Punctuated::<LitStr, Token![,]>::parse_terminated(&scopes_stream)?
-            .iter()
-            .map(LitStr::value)
-            .collect::<Vec<_>>();
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
 
-        Ok(Self {
-            name: Some(name),
-            scopes: Some(scopes),
-        })
+        Ok(Self { schemes })
     }
 }
 
 impl ToTokens for SecurityRequirementAttr {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let oapi = crate::oapi_crate();
-        if let (Some(name), Some(scopes)) = (&self.name, &self.scopes) {
+        let mut requirement = quote! {
+            #oapi::oapi::security::SecurityRequirement::default()
+        };
+        for (name, scopes) in &self.schemes {
             let scopes_array = scopes.iter().collect::<Array<&String>>();
             let scopes_len = scopes.len();
-
-            tokens.extend(quote! {
-                #oapi::oapi::security::SecurityRequirement::new::<&str, [&str; #scopes_len], &str>(#name, #scopes_array)
-            })
-        } else {
-            tokens.extend(quote! {
-                #oapi::oapi::security::SecurityRequirement::default()
-            })
+            requirement = quote! {
+                #requirement.add::<&str, [&str; #scopes_len], &str>(#name, #scopes_array)
+            };
         }
+        tokens.extend(requirement);
     }
 }
@@ -35,6 +35,10 @@ cfg_feature! {
     #![feature ="redoc"]
     pub mod redoc;
 }
+cfg_feature! {
+    #![feature ="validation"]
+    pub mod validation;
+}
 
 #[doc = include_str!("../docs/endpoint.md")]
 pub use salvo_oapi_macros::endpoint;
@@ -183,7 +187,12 @@ impl_to_schema_primitive!(
 impl_to_schema!(&str);
 
 #[cfg(feature = "chrono")]
-impl_to_schema_primitive!(chrono::NaiveDate, chrono::Duration, chrono::NaiveDateTime);
+impl_to_schema_primitive!(
+    chrono::NaiveDate,
+    chrono::NaiveTime,
+    chrono::Duration,
+    chrono::NaiveDateTime
+);
 #[cfg(feature = "chrono")]
 impl<T: chrono::TimeZone> ToSchema for chrono::DateTime<T> {
     fn to_schema(_components: &mut Components) -> RefOr<schema::Schema> {
@@ -201,6 +210,7 @@ impl_to_schema!(ulid::Ulid);
 #[cfg(feature = "time")]
 impl_to_schema_primitive!(
     time::Date,
+    time::Time,
     time::PrimitiveDateTime,
     time::OffsetDateTime,
     time::Duration
@@ -668,4 +678,116 @@ mod tests {
             assert_json_eq!(schema, value);
         }
     }
+
+    #[test]
+    fn test_enum_key_map_schema() {
+        #[derive(ToSchema, serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        #[salvo(schema(symbol = "Color"))]
+        #[allow(dead_code)]
+        enum Color {
+            Red,
+            Green,
+        }
+        #[derive(ToSchema, serde::Serialize)]
+        #[salvo(schema(symbol = "Palette"))]
+        #[allow(dead_code)]
+        struct Palette {
+            weights: BTreeMap<Color, u32>,
+            names: HashMap<String, u32>,
+        }
+
+        let mut components = Components::new();
+        Palette::to_schema(&mut components);
+        let schema = serde_json::to_value(components.schemas.get("Palette").unwrap()).unwrap();
+        assert_json_eq!(
+            schema.pointer("/properties/weights/propertyNames").unwrap(),
+            json!({"$ref": "#/components/schemas/Color"})
+        );
+        assert!(schema.pointer("/properties/names/propertyNames").is_none());
+        assert!(components.schemas.contains_key("Color"));
+    }
+
+    #[test]
+    fn test_adjacently_tagged_tuple_variant_schema() {
+        #[derive(ToSchema, serde::Serialize)]
+        #[salvo(schema(symbol = "Shape"))]
+        #[serde(tag = "kind", content = "data")]
+        #[allow(dead_code)]
+        enum Shape {
+            Point(i32, i32),
+            Circle(u32),
+        }
+
+        let mut components = Components::new();
+        Shape::to_schema(&mut components);
+        let schema = serde_json::to_value(components.schemas.get("Shape").unwrap()).unwrap();
+        assert_json_eq!(
+            schema.pointer("/oneOf/0/properties/data").unwrap(),
+            json!({"type": "array", "items": {"type": "integer", "format": "int32"}, "maxItems": 2, "minItems": 2})
+        );
+        assert_json_eq!(schema.pointer("/oneOf/0/required").unwrap(), json!(["kind", "data"]));
+    }
+
+    #[test]
+    fn test_extract_sources_to_parameters() {
+        #[derive(serde::Deserialize, ToParameters)]
+        #[salvo(extract(default_source(from = "query")))]
+        #[allow(dead_code)]
+        struct UpdatePet {
+            #[salvo(extract(source(from = "param")))]
+            id: i64,
+            #[salvo(extract(source(from = "header"), rename = "x-request-id"))]
+            request_id: Option<String>,
+            notify: Option<bool>,
+            #[salvo(extract(source(from = "body", format = "json")))]
+            name: String,
+        }
+
+        let mut components = Components::new();
+        let mut operation = Operation::new();
+        <UpdatePet as EndpointArgRegister>::register(&mut components, &mut operation, "pet");
+        let operation = serde_json::to_value(&operation).unwrap();
+        let parameters = operation["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| (parameter["name"].as_str().unwrap(), parameter["in"].as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parameters,
+            vec![("id", "path"), ("x-request-id", "header"), ("notify", "query")]
+        );
+        assert_json_eq!(
+            operation
+                .pointer("/requestBody/content/application~1json/schema")
+                .unwrap(),
+            json!({"type": "object", "properties": {"name": {"type": "string"}}, "required": ["name"]})
+        );
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn test_time_schema() {
+        let mut components = Components::new();
+        for (schema, value) in [
+            (
+                chrono::NaiveTime::to_schema(&mut components),
+                json!({"type": "string", "format": "time"}),
+            ),
+            (
+                chrono::DateTime::<chrono::Utc>::to_schema(&mut components),
+                json!({"type": "string", "format": "date-time"}),
+            ),
+            (
+                time::Time::to_schema(&mut components),
+                json!({"type": "string", "format": "time"}),
+            ),
+            (
+                time::Date::to_schema(&mut components),
+                json!({"type": "string", "format": "date"}),
+            ),
+        ] {
+            assert_json_eq!(serde_json::to_value(schema).unwrap(), value);
+        }
+    }
 }
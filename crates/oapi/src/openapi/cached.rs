@@ -0,0 +1,248 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::sync::{Arc, RwLock};
+
+use bytes::Bytes;
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use salvo_core::http::{ResBody, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Router};
+
+use super::OpenApi;
+
+/// The format which the document is served in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum DocFormat {
+    Json,
+    PrettyJson,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+impl DocFormat {
+    pub(super) fn from_request(req: &Request) -> Self {
+        #[cfg(feature = "yaml")]
+        {
+            let path = req.uri().path();
+            let format = req.queries().get("format").map(|v| &**v);
+            if path.ends_with(".yaml") || path.ends_with(".yml") || format == Some("yaml") {
+                return Self::Yaml;
+            }
+        }
+        if req.queries().get("pretty").map(|v| &**v != "false").unwrap_or(false) {
+            Self::PrettyJson
+        } else {
+            Self::Json
+        }
+    }
+    pub(super) fn content_type(self) -> &'static str {
+        match self {
+            Self::Json | Self::PrettyJson => "application/json; charset=utf-8",
+            #[cfg(feature = "yaml")]
+            Self::Yaml => "application/yaml; charset=utf-8",
+        }
+    }
+    pub(super) fn serialize(self, doc: &OpenApi) -> Result<String, String> {
+        match self {
+            Self::Json => doc.to_json().map_err(|e| e.to_string()),
+            Self::PrettyJson => doc.to_pretty_json().map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => doc.to_yaml().map_err(|e| e.to_string()),
+        }
+    }
+    fn index(self) -> usize {
+        match self {
+            Self::Json => 0,
+            Self::PrettyJson => 1,
+            #[cfg(feature = "yaml")]
+            Self::Yaml => 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Rendered {
+    body: Bytes,
+    etag: HeaderValue,
+}
+impl Rendered {
+    fn new(content: String) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(content.as_bytes());
+        let etag = format!("\"{:x}-{:016x}\"", content.len(), hasher.finish());
+        Self {
+            body: Bytes::from(content),
+            etag: HeaderValue::from_str(&etag).expect("etag should be a valid header value"),
+        }
+    }
+    fn matches(&self, if_none_match: &HeaderValue) -> bool {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        let Ok(etag) = self.etag.to_str() else {
+            return false;
+        };
+        if_none_match
+            .split(',')
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    doc: Arc<OpenApi>,
+    rendered: [Option<Rendered>; 3],
+}
+
+/// An [`OpenApi`] document handler which serializes the document only once for each format.
+///
+/// The serialized bytes and their `ETag` are cached on first request, requests with a matching `If-None-Match` header
+/// are responded with `304 Not Modified`. The cache is shared by all clones, so a clone can be kept to change the
+/// document by [`CachedOpenApi::replace`] or drop the cache by [`CachedOpenApi::invalidate`].
+///
+/// [`OpenApi::into_router`] serves the document by this handler.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_oapi::{CachedOpenApi, OpenApi};
+///
+/// let doc = CachedOpenApi::new(OpenApi::new("pet api", "0.1.0"));
+/// let router = Router::new().push(doc.clone().into_router("/api-doc/openapi.json"));
+///
+/// // Later, such as after plugins are reloaded.
+/// doc.replace(OpenApi::new("pet api", "0.2.0"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedOpenApi {
+    state: Arc<RwLock<State>>,
+}
+impl CachedOpenApi {
+    /// Create a new `CachedOpenApi` with the document.
+    #[inline]
+    pub fn new(doc: OpenApi) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(State {
+                doc: Arc::new(doc),
+                rendered: Default::default(),
+            })),
+        }
+    }
+
+    /// Get the served document.
+    #[inline]
+    pub fn doc(&self) -> Arc<OpenApi> {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).doc.clone()
+    }
+
+    /// Replace the served document, the cached bytes of the old document are dropped.
+    pub fn replace(&self, doc: OpenApi) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.doc = Arc::new(doc);
+        state.rendered = Default::default();
+    }
+
+    /// Drop the cached bytes, the document is serialized again on next request.
+    pub fn invalidate(&self) {
+        self.state.write().unwrap_or_else(|e| e.into_inner()).rendered = Default::default();
+    }
+
+    /// Consumes the `CachedOpenApi` and returns [`Router`] with it as handler.
+    #[inline]
+    pub fn into_router(self, path: impl Into<String>) -> Router {
+        Router::with_path(path.into()).goal(self)
+    }
+
+    fn rendered(&self, format: DocFormat) -> Result<Rendered, String> {
+        let doc = {
+            let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(rendered) = &state.rendered[format.index()] {
+                return Ok(rendered.clone());
+            }
+            state.doc.clone()
+        };
+        let rendered = Rendered::new(format.serialize(&doc)?);
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        // Do not cache the bytes if the document is replaced during serialization.
+        if Arc::ptr_eq(&state.doc, &doc) {
+            state.rendered[format.index()] = Some(rendered.clone());
+        }
+        Ok(rendered)
+    }
+}
+impl From<OpenApi> for CachedOpenApi {
+    #[inline]
+    fn from(doc: OpenApi) -> Self {
+        Self::new(doc)
+    }
+}
+
+#[async_trait]
+impl Handler for CachedOpenApi {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let format = DocFormat::from_request(req);
+        let rendered = match self.rendered(format) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                tracing::error!(error = %e, ?format, "serialize openapi failed");
+                res.render(StatusError::internal_server_error());
+                return;
+            }
+        };
+        res.headers_mut().insert(ETAG, rendered.etag.clone());
+        if req
+            .headers()
+            .get(IF_NONE_MATCH)
+            .map(|v| rendered.matches(v))
+            .unwrap_or(false)
+        {
+            res.status_code(StatusCode::NOT_MODIFIED);
+            return;
+        }
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+        res.body(ResBody::Once(rendered.body));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_openapi() {
+        let doc = CachedOpenApi::new(OpenApi::new("pet api", "0.1.0"));
+        let service = Service::new(doc.clone().into_router("openapi.json"));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/openapi.json")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let etag = res.headers().get(ETAG).cloned().unwrap();
+        assert!(res.take_string().await.unwrap().contains(r#""version":"0.1.0""#));
+
+        let res = TestClient::get("http://127.0.0.1:5801/openapi.json")
+            .add_header(IF_NONE_MATCH, etag.clone(), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/openapi.json?pretty=true")
+            .send(&service)
+            .await;
+        assert_ne!(res.headers().get(ETAG), Some(&etag));
+        assert!(res.take_string().await.unwrap().contains('\n'));
+
+        doc.replace(OpenApi::new("pet api", "0.2.0"));
+        let mut res = TestClient::get("http://127.0.0.1:5801/openapi.json")
+            .add_header(IF_NONE_MATCH, etag.clone(), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_ne!(res.headers().get(ETAG), Some(&etag));
+        assert!(res.take_string().await.unwrap().contains(r#""version":"0.2.0""#));
+    }
+}
@@ -6,7 +6,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{RefOr, Response, Responses, Schema, SecurityScheme};
+use crate::{Example, RefOr, Response, Responses, Schema, SecurityScheme};
 
 /// Implements [OpenAPI Components Object][components] which holds supported
 /// reusable objects.
@@ -17,7 +17,7 @@ use crate::{RefOr, Response, Responses, Schema, SecurityScheme};
 /// [components]: https://spec.openapis.org/oas/latest.html#components-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Components {
     /// Map of reusable [OpenAPI Schema Object][schema]s.
     ///
@@ -38,6 +38,14 @@ pub struct Components {
     /// [security_scheme]: https://spec.openapis.org/oas/latest.html#security-scheme-object
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub security_schemes: BTreeMap<String, SecurityScheme>,
+
+    /// Map of reusable [OpenAPI Example Object][example]s, they can be referenced by
+    /// [`Ref::from_example_name`][ref].
+    ///
+    /// [example]: https://spec.openapis.org/oas/latest.html#example-object
+    /// [ref]: ../schema/struct.Ref.html#method.from_example_name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub examples: BTreeMap<String, RefOr<Example>>,
 }
 
 impl Components {
@@ -145,6 +153,12 @@ impl Components {
         self
     }
 
+    /// Add a new named [`Example`] and returns `self`.
+    pub fn add_example<S: Into<String>, E: Into<RefOr<Example>>>(mut self, name: S, example: E) -> Self {
+        self.examples.insert(name.into(), example.into());
+        self
+    }
+
     /// Moves all elements from `other` into `self`, leaving `other` empty.
     ///
     /// If a key from `other` is already present in `self`, the respective
@@ -160,10 +174,16 @@ impl Components {
             .security_schemes
             .retain(|name, _| !self.security_schemes.contains_key(name));
         self.security_schemes.append(&mut other.security_schemes);
+
+        other.examples.retain(|name, _| !self.examples.contains_key(name));
+        self.examples.append(&mut other.examples);
     }
 
     /// Returns `true` if instance contains no elements.
     pub fn is_empty(&self) -> bool {
-        self.schemas.is_empty() && self.responses.is_empty() && self.security_schemes.is_empty()
+        self.schemas.is_empty()
+            && self.responses.is_empty()
+            && self.security_schemes.is_empty()
+            && self.examples.is_empty()
     }
 }
@@ -11,6 +11,7 @@ use super::{encoding::Encoding, RefOr, Schema};
 /// Content holds request body content or response content.
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 #[non_exhaustive]
+#[serde(default)]
 pub struct Content {
     /// Schema used in response body or request body.
     pub schema: RefOr<Schema>,
@@ -10,7 +10,7 @@ use super::Header;
 /// A single encoding definition applied to a single schema [`Object
 /// property`](crate::openapi::schema::Object::properties).
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 #[non_exhaustive]
 pub struct Encoding {
     /// The Content-Type for encoding a specific property. Default value depends on the property
@@ -6,6 +6,8 @@
 //! [request_body]: request_body/struct.RequestBody.html
 use serde::{Deserialize, Serialize};
 
+use super::{Ref, RefOr};
+
 /// Implements [OpenAPI Example Object][example].
 ///
 /// Example is used on path operations to describe possible response bodies.
@@ -13,7 +15,7 @@ use serde::{Deserialize, Serialize};
 /// [example]: https://spec.openapis.org/oas/latest.html#example-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Example {
     /// Short description for the [`Example`].
     #[serde(skip_serializing_if = "String::is_empty")]
@@ -76,3 +78,9 @@ impl Example {
         self
     }
 }
+
+impl From<Ref> for RefOr<Example> {
+    fn from(r: Ref) -> Self {
+        Self::Ref(r)
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 /// Reference of external resource allowing extended documentation.
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct ExternalDocs {
     /// Target url for external documentation location.
     pub url: String,
@@ -25,7 +25,7 @@ use serde::{Deserialize, Serialize};
 /// [info]: <https://spec.openapis.org/oas/latest.html#info-object>
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Info {
     /// Title of the API.
     pub title: String,
@@ -119,7 +119,7 @@ impl Info {
 /// [contact]: <https://spec.openapis.org/oas/latest.html#contact-object>
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Contact {
     /// Identifying name of the contact person or organization of the API.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -163,7 +163,7 @@ impl Contact {
 /// [license]: <https://spec.openapis.org/oas/latest.html#license-object>
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct License {
     /// Name of the license used e.g MIT or Apache-2.0
     pub name: String,
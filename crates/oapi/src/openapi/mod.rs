@@ -3,16 +3,18 @@ use std::collections::{btree_map, BTreeSet};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use salvo_core::{async_trait, writing, Depot, FlowCtrl, Handler, Router};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Router};
 use serde::{de::Visitor, Deserialize, Serialize, Serializer};
 
 pub use self::{
+    cached::CachedOpenApi,
     components::Components,
     content::Content,
     example::Example,
     external_docs::ExternalDocs,
     header::Header,
     info::{Contact, Info, License},
+    naming::{OperationIdStrategy, OperationNaming},
     operation::{Operation, Operations},
     parameter::{Parameter, ParameterIn, ParameterStyle, Parameters},
     path::{PathItem, PathItemType, Paths},
@@ -25,6 +27,7 @@ pub use self::{
     xml::Xml,
 };
 
+mod cached;
 mod components;
 mod content;
 mod encoding;
@@ -32,6 +35,7 @@ mod example;
 mod external_docs;
 mod header;
 pub mod info;
+pub mod naming;
 pub mod operation;
 pub mod parameter;
 pub mod path;
@@ -43,7 +47,8 @@ pub mod server;
 mod tag;
 mod xml;
 
-use crate::{routing::NormNode, Endpoint};
+use self::cached::DocFormat;
+use crate::{naming::NamingContext, routing::NormNode, Endpoint};
 
 static PATH_PARAMETER_NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}:]+)").unwrap());
 
@@ -57,7 +62,7 @@ static PATH_PARAMETER_NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}
 /// See more details at <https://spec.openapis.org/oas/latest.html#openapi-object>.
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct OpenApi {
     /// OpenAPI document version.
     pub openapi: OpenApiVersion,
@@ -80,6 +85,13 @@ pub struct OpenApi {
     /// See more details at <https://spec.openapis.org/oas/latest.html#paths-object>.
     pub paths: Paths,
 
+    /// Incoming webhooks that may be received as part of this API and that the API consumer may choose
+    /// to implement. The key is an unique name of the webhook.
+    ///
+    /// See more details at <https://spec.openapis.org/oas/latest.html#oasWebhooks>.
+    #[serde(skip_serializing_if = "Paths::is_empty", default)]
+    pub webhooks: Paths,
+
     /// Holds various reusable schemas for the OpenAPI document.
     ///
     /// Few of these elements are security schemas and object schemas.
@@ -107,6 +119,10 @@ pub struct OpenApi {
     /// See more details at <https://spec.openapis.org/oas/latest.html#external-documentation-object>.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_docs: Option<ExternalDocs>,
+
+    /// How operation ids and tags are generated for endpoints merged from routers.
+    #[serde(skip)]
+    pub naming: OperationNaming,
 }
 
 impl OpenApi {
@@ -160,6 +176,27 @@ impl OpenApi {
         serde_yaml::to_string(self)
     }
 
+    /// Parses [`OpenApi`] from JSON string, it can be used to serve a document dumped at build time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::OpenApi;
+    /// let dumped = OpenApi::new("pet api", "0.1.0").to_json().unwrap();
+    /// let doc = OpenApi::from_json(&dumped).unwrap();
+    /// let router = doc.into_router("/api-doc/openapi.json");
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Parses [`OpenApi`] from YAML string, it can be used to serve a document dumped at build time.
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "yaml")))]
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
     /// Merge `other` [`OpenApi`] consuming it and resuming it's content.
     ///
     /// Merge function will take all `self` nonexistent _`servers`, `paths`, `schemas`, `responses`,
@@ -176,6 +213,7 @@ impl OpenApi {
     pub fn merge(mut self, mut other: OpenApi) -> Self {
         self.servers.append(&mut other.servers);
         self.paths.append(&mut other.paths);
+        self.webhooks.append(&mut other.webhooks);
         self.components.append(&mut other.components);
         self.security.append(&mut other.security);
         self.tags.append(&mut other.tags);
@@ -217,12 +255,53 @@ impl OpenApi {
         self
     }
 
+    /// Add [`PathItem`] of a webhook named `name` and returns `Self`.
+    pub fn add_webhook<N, I>(mut self, name: N, item: I) -> Self
+    where
+        N: Into<String>,
+        I: Into<PathItem>,
+    {
+        self.webhooks.insert(name.into(), item.into());
+        self
+    }
+    /// Add a webhook named `name` which is described by the handler `H` created by `#[endpoint]`,
+    /// components used by the handler are also added to the [`OpenApi`].
+    ///
+    /// Nothing is added if `H` is not an endpoint.
+    pub fn add_webhook_endpoint<H: 'static>(mut self, name: impl Into<String>, path_item_type: PathItemType) -> Self {
+        if let Some(creator) = crate::EndpointRegistry::find(&std::any::TypeId::of::<H>()) {
+            let Endpoint {
+                mut operation,
+                mut components,
+            } = (creator)();
+            let name = name.into();
+            let ctx = NamingContext {
+                path: &name,
+                method: path_item_type,
+                type_name: std::any::type_name::<H>(),
+            };
+            self.apply_naming(&mut operation, &ctx);
+            self.components.append(&mut components);
+            self.webhooks.insert(name, PathItem::new(path_item_type, operation));
+        }
+        self
+    }
+
     /// Add [`Components`] to configure reusable schemas.
     pub fn components(mut self, components: impl Into<Components>) -> Self {
         self.components = components.into();
         self
     }
 
+    /// Add a [`SecurityScheme`] to components, it can be referenced by its name from
+    /// `#[endpoint(security(...))]` and [`SecurityRequirement`]s.
+    pub fn add_security_scheme(mut self, name: impl Into<String>, security_scheme: impl Into<SecurityScheme>) -> Self {
+        self.components
+            .security_schemes
+            .insert(name.into(), security_scheme.into());
+        self
+    }
+
     /// Add iterator of [`SecurityRequirement`]s that are globally available for all operations.
     pub fn security<S: IntoIterator<Item = SecurityRequirement>>(mut self, security: S) -> Self {
         self.security = security.into_iter().collect();
@@ -245,9 +324,29 @@ impl OpenApi {
         self
     }
 
-    /// Consusmes the [`OpenApi`] and returns [`Router`] with the [`OpenApi`] as handler.
+    /// Consusmes the [`OpenApi`] and returns [`Router`] with a [`CachedOpenApi`] of it as handler.
+    ///
+    /// The document is served as JSON, add `pretty=true` query to get pretty JSON. With `yaml` feature,
+    /// it is served as YAML if the path ends with `.yaml` or `.yml`, or `format=yaml` query is added.
+    /// The document is serialized only once for each format, use [`CachedOpenApi::into_router`] instead to keep
+    /// a handle which can replace the document or invalidate the cache.
+    ///
+    /// The returned router can be protected by a guard, such as `BasicAuth` of `salvo-extra`:
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # use salvo_oapi::OpenApi;
+    /// #[handler]
+    /// async fn guard(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+    ///     if req.header::<String>("x-doc-token").as_deref() != Some("secret") {
+    ///         res.render(StatusError::unauthorized());
+    ///         ctrl.skip_rest();
+    ///     }
+    /// }
+    /// let router = OpenApi::new("pet api", "0.1.0").into_router("/api-doc/openapi.json").hoop(guard);
+    /// ```
     pub fn into_router(self, path: impl Into<String>) -> Router {
-        Router::with_path(path.into()).goal(self)
+        CachedOpenApi::new(self).into_router(path)
     }
 
     /// Consusmes the [`OpenApi`] and informations from a [`Router`].
@@ -262,6 +361,31 @@ impl OpenApi {
         self
     }
 
+    /// Sets how operation ids and tags are generated for endpoints merged afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::{OpenApi, OperationIdStrategy, OperationNaming};
+    /// let doc = OpenApi::new("pet api", "0.1.0")
+    ///     .naming(OperationNaming::new().strategy(OperationIdStrategy::Route).derive_tags(true));
+    /// ```
+    pub fn naming(mut self, naming: OperationNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    fn apply_naming(&self, operation: &mut Operation, ctx: &NamingContext<'_>) {
+        if operation.operation_id.is_none() {
+            operation.operation_id = Some(self.naming.operation_id(ctx));
+        }
+        if operation.tags.is_empty() {
+            if let Some(tag) = self.naming.tag(ctx) {
+                operation.tags.push(tag);
+            }
+        }
+    }
+
     fn merge_norm_node(&mut self, node: &mut NormNode, base_path: &str) {
         fn join_path(a: &str, b: &str) -> String {
             if a.is_empty() {
@@ -323,10 +447,17 @@ impl OpenApi {
                 if !meta_not_exist_parameters.is_empty() {
                     tracing::warn!(parameters = ?meta_not_exist_parameters, path, handler_name = node.handler_type_name, "parameters information not provided");
                 }
-                let path_item = self.paths.entry(path.clone()).or_default();
                 for method in methods {
+                    let mut operation = operation.clone();
+                    let ctx = NamingContext {
+                        path: &path,
+                        method,
+                        type_name: node.handler_type_name.unwrap_or_default(),
+                    };
+                    self.apply_naming(&mut operation, &ctx);
+                    let path_item = self.paths.entry(path.clone()).or_default();
                     if let btree_map::Entry::Vacant(e) = path_item.operations.entry(method) {
-                        e.insert(operation.clone());
+                        e.insert(operation);
                     } else {
                         tracing::warn!("path `{}` already contains operation for method `{:?}`", path, method);
                     }
@@ -349,13 +480,18 @@ impl Handler for OpenApi {
         res: &mut salvo_core::Response,
         _ctrl: &mut FlowCtrl,
     ) {
-        let pretty = req.queries().get("pretty").map(|v| &**v != "false").unwrap_or(false);
-        let content = if pretty {
-            self.to_pretty_json().unwrap()
-        } else {
-            self.to_json().unwrap()
-        };
-        res.render(writing::Text::Json(&content));
+        let format = DocFormat::from_request(req);
+        match format.serialize(self) {
+            Ok(content) => {
+                res.add_header(salvo_core::http::header::CONTENT_TYPE, format.content_type(), true)
+                    .ok();
+                res.write_body(content).ok();
+            }
+            Err(e) => {
+                tracing::error!(error = %e, ?format, "serialize openapi failed");
+                res.render(salvo_core::http::StatusError::internal_server_error());
+            }
+        }
     }
 }
 /// Represents available [OpenAPI versions][version].
@@ -898,4 +1034,107 @@ mod tests {
             Value::from_str(&doc.to_json().unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn document_with_combined_security() {
+        #[salvo_oapi::endpoint(security(("api_key" = [], "oauth2" = ["read:items"]), ("token_jwt" = [])))]
+        async fn list_items() -> &'static str {
+            "items"
+        }
+
+        let doc = OpenApi::new("my application", "0.1.0")
+            .add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(crate::security::ApiKey::Header(crate::security::ApiKeyValue::new(
+                    "x-api-key",
+                ))),
+            )
+            .add_security_scheme(
+                "token_jwt",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer).bearer_format("JWT")),
+            )
+            .merge_router(&Router::with_path("items").get(list_items));
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(
+            value.pointer("/paths/~1items/get/security").unwrap(),
+            &json!([{"api_key": [], "oauth2": ["read:items"]}, {"token_jwt": []}])
+        );
+        assert_eq!(
+            value.pointer("/components/securitySchemes/api_key").unwrap(),
+            &json!({"type": "apiKey", "in": "header", "name": "x-api-key"})
+        );
+    }
+
+    #[test]
+    fn document_with_callbacks_and_webhooks() {
+        #[salvo_oapi::endpoint]
+        async fn on_data(body: salvo_oapi::extract::JsonBody<String>) -> &'static str {
+            let _ = body;
+            "ok"
+        }
+        #[salvo_oapi::endpoint(callbacks(("onData" = "{$request.query.callbackUrl}/data", post = on_data)))]
+        async fn subscribe() -> &'static str {
+            "subscribed"
+        }
+
+        let doc = OpenApi::new("my application", "0.1.0")
+            .add_webhook_endpoint::<on_data>("newData", PathItemType::Post)
+            .components(
+                Components::new().add_example("bob", Example::new().summary("Bob").value(json!({"name": "bob"}))),
+            )
+            .merge_router(&Router::with_path("subscribe").post(subscribe));
+        let value = serde_json::to_value(&doc).unwrap();
+        assert!(value
+            .pointer("/paths/~1subscribe/post/callbacks/onData/{$request.query.callbackUrl}~1data/post/requestBody")
+            .is_some());
+        assert!(value.pointer("/webhooks/newData/post/requestBody").is_some());
+        assert_eq!(
+            value.pointer("/components/examples/bob").unwrap(),
+            &json!({"summary": "Bob", "value": {"name": "bob"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_dumped_document() {
+        use salvo_core::test::{ResponseExt, TestClient};
+
+        #[salvo_oapi::endpoint(
+            tags("items"),
+            parameters(("id", description = "Item id")),
+            responses((status_code = 200, description = "Item found"))
+        )]
+        async fn get_item(id: salvo_oapi::extract::PathParam<u64>) -> salvo_core::writing::Json<Vec<String>> {
+            let _ = id;
+            salvo_core::writing::Json(vec![])
+        }
+        let dumped = OpenApi::new("my application", "0.1.0")
+            .merge_router(&Router::with_path("items/<id>").get(get_item))
+            .to_json()
+            .unwrap();
+        let doc = OpenApi::from_json(&dumped).unwrap();
+        let router = Router::new()
+            .push(doc.clone().into_router("openapi.json"))
+            .push(doc.into_router("openapi.yaml"));
+        let service = salvo_core::Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/openapi.json")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, dumped);
+
+        #[cfg(feature = "yaml")]
+        {
+            let mut res = TestClient::get("http://127.0.0.1:5801/openapi.yaml")
+                .send(&service)
+                .await;
+            assert_eq!(
+                res.headers().get("content-type").unwrap(),
+                "application/yaml; charset=utf-8"
+            );
+            assert!(res.take_string().await.unwrap().contains("title: my application"));
+        }
+    }
 }
@@ -0,0 +1,196 @@
+//! Implements deterministic naming of operation ids and tags.
+//!
+//! Client generators use `operationId` for method names and `tags` for grouping, so these values should only
+//! change when the API itself changes. [`OperationNaming`] derives them from the handler or the route it is
+//! mounted on, independent of the order in which routes are registered.
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use super::PathItemType;
+
+/// Strategy used to generate an operation id when `#[endpoint(operation_id = ...)]` is not given.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum OperationIdStrategy {
+    /// Use the full path of the handler type, with `::` replaced by `.`, e.g. `my_app.pets.get_pet`.
+    #[default]
+    TypePath,
+    /// Use the last segment of the handler type path, e.g. `get_pet`.
+    TypeName,
+    /// Use the method and the route path, e.g. `get_pets_by_id` for `GET /pets/{id}`.
+    Route,
+}
+
+/// Information about an operation passed to the naming hook.
+#[derive(Clone, Copy, Debug)]
+pub struct NamingContext<'a> {
+    /// Full path of the route, with parameters written as `{name}`.
+    pub path: &'a str,
+    /// Method the operation is registered for.
+    pub method: PathItemType,
+    /// Type name of the handler, as returned by [`std::any::type_name`].
+    pub type_name: &'a str,
+}
+
+type NamingHook = Arc<dyn Fn(&NamingContext<'_>) -> Option<String> + Send + Sync>;
+
+/// Controls how operation ids and tags are generated when merging routers into an [`OpenApi`].
+///
+/// [`OpenApi`]: super::OpenApi
+#[derive(Clone, Default)]
+pub struct OperationNaming {
+    strategy: OperationIdStrategy,
+    derive_tags: bool,
+    hook: Option<NamingHook>,
+}
+
+impl OperationNaming {
+    /// Create a new `OperationNaming` with [`OperationIdStrategy::TypePath`] and no derived tags.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the strategy used to generate operation ids.
+    pub fn strategy(mut self, strategy: OperationIdStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets whether operations without tags are tagged with the first static segment of their path.
+    pub fn derive_tags(mut self, derive_tags: bool) -> Self {
+        self.derive_tags = derive_tags;
+        self
+    }
+
+    /// Sets a hook called before the strategy. Returning `None` falls back to the strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::naming::{OperationNaming, OperationIdStrategy};
+    /// let naming = OperationNaming::new()
+    ///     .strategy(OperationIdStrategy::TypeName)
+    ///     .hook(|ctx| ctx.path.starts_with("/admin").then(|| format!("admin_{}", ctx.method.as_str())));
+    /// ```
+    pub fn hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&NamingContext<'_>) -> Option<String> + Send + Sync + 'static,
+    {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Generate an operation id for the given context.
+    pub fn operation_id(&self, ctx: &NamingContext<'_>) -> String {
+        if let Some(id) = self.hook.as_ref().and_then(|hook| hook(ctx)) {
+            return id;
+        }
+        match self.strategy {
+            OperationIdStrategy::TypePath => ctx.type_name.replace("::", "."),
+            OperationIdStrategy::TypeName => {
+                // Strip generic arguments before taking the last segment.
+                let name = ctx.type_name.split('<').next().unwrap_or(ctx.type_name);
+                name.rsplit("::").next().unwrap_or(name).to_owned()
+            }
+            OperationIdStrategy::Route => {
+                let mut id = ctx.method.as_str().to_owned();
+                for segment in ctx.path.split('/').filter(|segment| !segment.is_empty()) {
+                    id.push('_');
+                    if let Some(name) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                        id.push_str("by_");
+                        push_identifier(&mut id, name.trim_start_matches('*'));
+                    } else {
+                        push_identifier(&mut id, segment);
+                    }
+                }
+                id
+            }
+        }
+    }
+
+    /// Derive a tag from the route path, if enabled.
+    pub fn tag(&self, ctx: &NamingContext<'_>) -> Option<String> {
+        if !self.derive_tags {
+            return None;
+        }
+        ctx.path
+            .split('/')
+            .find(|segment| !segment.is_empty() && !segment.starts_with('{'))
+            .map(ToOwned::to_owned)
+    }
+}
+
+fn push_identifier(id: &mut String, segment: &str) {
+    id.extend(segment.chars().map(|c| {
+        if c.is_ascii_alphanumeric() {
+            c.to_ascii_lowercase()
+        } else {
+            '_'
+        }
+    }));
+}
+
+impl Debug for OperationNaming {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperationNaming")
+            .field("strategy", &self.strategy)
+            .field("derive_tags", &self.derive_tags)
+            .field("hook", &self.hook.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
+
+impl PartialEq for OperationNaming {
+    fn eq(&self, other: &Self) -> bool {
+        self.strategy == other.strategy
+            && self.derive_tags == other.derive_tags
+            && match (&self.hook, &other.hook) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(path: &str, method: PathItemType) -> NamingContext<'_> {
+        NamingContext {
+            path,
+            method,
+            type_name: "my_app::pets::get_pet",
+        }
+    }
+
+    #[test]
+    fn test_operation_id_strategies() {
+        let ctx = ctx("/pets/{id}/owner-info", PathItemType::Get);
+        assert_eq!(OperationNaming::new().operation_id(&ctx), "my_app.pets.get_pet");
+        assert_eq!(
+            OperationNaming::new()
+                .strategy(OperationIdStrategy::TypeName)
+                .operation_id(&ctx),
+            "get_pet"
+        );
+        assert_eq!(
+            OperationNaming::new()
+                .strategy(OperationIdStrategy::Route)
+                .operation_id(&ctx),
+            "get_pets_by_id_owner_info"
+        );
+        assert_eq!(
+            OperationNaming::new()
+                .hook(|ctx| Some(format!("custom_{}", ctx.method.as_str())))
+                .operation_id(&ctx),
+            "custom_get"
+        );
+    }
+
+    #[test]
+    fn test_derive_tags() {
+        let ctx = ctx("/{tenant}/pets/{id}", PathItemType::Get);
+        assert_eq!(OperationNaming::new().tag(&ctx), None);
+        assert_eq!(OperationNaming::new().derive_tags(true).tag(&ctx), Some("pets".into()));
+    }
+}
@@ -11,7 +11,7 @@ use super::{
     response::{Response, Responses},
     Deprecated, ExternalDocs, RefOr, SecurityRequirement, Server,
 };
-use crate::{Parameter, Parameters, PathItemType, Servers};
+use crate::{Parameter, Parameters, PathItem, PathItemType, Paths, Servers};
 
 /// Collection for save [`Operation`]s.
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
@@ -80,7 +80,7 @@ impl Operations {
 /// [operation]: https://spec.openapis.org/oas/latest.html#operation-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Operation {
     /// List of tags used for grouping operations.
     ///
@@ -136,9 +136,11 @@ pub struct Operation {
     /// List of possible responses returned by the [`Operation`].
     pub responses: Responses,
 
-    /// Callback information.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub callbacks: Option<String>,
+    /// Map of possible out-of band callbacks related to the parent [`Operation`]. The key is an
+    /// unique name for the callback, and the value is [`Paths`] keyed by runtime expressions, such as
+    /// `{$request.body#/callbackUrl}`, which are evaluated to the urls of the callback requests.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub callbacks: BTreeMap<String, Paths>,
 
     /// Define whether the operation is deprecated or not and thus should be avoided consuming.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -229,6 +231,29 @@ impl Operation {
         self
     }
 
+    /// Add or change callbacks of the [`Operation`].
+    pub fn callbacks<I, N>(mut self, callbacks: I) -> Self
+    where
+        I: IntoIterator<Item = (N, Paths)>,
+        N: Into<String>,
+    {
+        self.callbacks = callbacks
+            .into_iter()
+            .map(|(name, paths)| (name.into(), paths))
+            .collect();
+        self
+    }
+    /// Append a [`PathItem`] requested at runtime `expression` to the callback named `name` and returns `Self`.
+    pub fn add_callback<N, E, I>(mut self, name: N, expression: E, item: I) -> Self
+    where
+        N: Into<String>,
+        E: Into<String>,
+        I: Into<PathItem>,
+    {
+        self.callbacks.entry(name.into()).or_default().insert(expression, item);
+        self
+    }
+
     /// Add or change deprecated status of the [`Operation`].
     pub fn deprecated<D: Into<Deprecated>>(mut self, deprecated: D) -> Self {
         self.deprecated = Some(deprecated.into());
@@ -269,7 +294,7 @@ impl Operation {
 #[cfg(test)]
 mod tests {
     use super::Operation;
-    use crate::{security::SecurityRequirement, server::Server};
+    use crate::{security::SecurityRequirement, server::Server, PathItem, PathItemType};
 
     #[test]
     fn operation_new() {
@@ -283,7 +308,7 @@ mod tests {
         assert!(operation.parameters.is_empty());
         assert!(operation.request_body.is_none());
         assert!(operation.responses.is_empty());
-        assert!(operation.callbacks.is_none());
+        assert!(operation.callbacks.is_empty());
         assert!(operation.deprecated.is_none());
         assert!(operation.securities.is_empty());
         assert!(operation.servers.is_empty());
@@ -307,4 +332,22 @@ mod tests {
         let operation = Operation::new().add_server(server1).add_server(server2);
         assert!(!operation.servers.is_empty());
     }
+
+    #[test]
+    fn operation_callback() {
+        let operation = Operation::new()
+            .add_callback(
+                "onData",
+                "{$request.query.callbackUrl}/data",
+                PathItem::new(PathItemType::Post, Operation::new()),
+            )
+            .add_callback(
+                "onData",
+                "{$request.query.callbackUrl}/data",
+                PathItem::new(PathItemType::Put, Operation::new()),
+            );
+        let callback = operation.callbacks.get("onData").unwrap();
+        let item = callback.get("{$request.query.callbackUrl}/data").unwrap();
+        assert_eq!(item.operations.len(), 2);
+    }
 }
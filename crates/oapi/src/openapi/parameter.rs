@@ -78,7 +78,7 @@ impl Parameters {
 /// [parameter]: https://spec.openapis.org/oas/latest.html#parameter-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Parameter {
     /// Name of the parameter.
     ///
@@ -31,6 +31,10 @@ impl Paths {
     pub fn new() -> Self {
         Default::default()
     }
+    /// Returns `true` if instance contains no paths.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
     /// Inserts a key-value pair into the instance and returns `self`.
     pub fn path<K: Into<String>, V: Into<PathItem>>(mut self, key: K, value: V) -> Self {
         self.insert(key, value);
@@ -84,7 +88,7 @@ impl Paths {
 /// [path_item]: https://spec.openapis.org/oas/latest.html#path-item-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct PathItem {
     /// Optional summary intended to apply all operations in this [`PathItem`].
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -104,7 +108,6 @@ pub struct PathItem {
     /// contain duplicate parameters. They can be overridden in [`Operation`] level but cannot be
     /// removed there.
     #[serde(skip_serializing_if = "Parameters::is_empty")]
-    #[serde(flatten)]
     pub parameters: Parameters,
 
     /// Map of operations in this [`PathItem`]. Operations can hold only one operation
@@ -196,3 +199,20 @@ pub enum PathItemType {
     /// Type mapping for HTTP _CONNECT_ request.
     Connect,
 }
+
+impl PathItemType {
+    /// Returns the lowercase method name, as used in the OpenAPI document.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Put => "put",
+            Self::Delete => "delete",
+            Self::Options => "options",
+            Self::Head => "head",
+            Self::Patch => "patch",
+            Self::Trace => "trace",
+            Self::Connect => "connect",
+        }
+    }
+}
@@ -11,7 +11,7 @@ use super::{Content, Required};
 /// [request_body]: https://spec.openapis.org/oas/latest.html#request-body-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct RequestBody {
     /// Additional description of [`RequestBody`] supporting markdown syntax.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -128,7 +128,7 @@ where
 /// [response]: https://spec.openapis.org/oas/latest.html#response-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Response {
     /// Description of the response. Response support markdown syntax.
     pub description: String,
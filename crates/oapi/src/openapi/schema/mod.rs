@@ -176,6 +176,12 @@ impl Ref {
     pub fn from_response_name<I: Into<String>>(response_name: I) -> Self {
         Self::new(format!("#/components/responses/{}", response_name.into()))
     }
+
+    /// Construct a new [`Ref`] from provided example name. This will create a [`Ref`] that
+    /// references the reusable example.
+    pub fn from_example_name<I: Into<String>>(example_name: I) -> Self {
+        Self::new(format!("#/components/examples/{}", example_name.into()))
+    }
 }
 
 impl From<Ref> for RefOr<Schema> {
@@ -271,6 +277,8 @@ pub enum KnownFormat {
     Binary,
     /// ISO-8601 full date [FRC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#anchor14).
     Date,
+    /// ISO-8601 partial time [FRC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#anchor14).
+    Time,
     /// ISO-8601 full date time [FRC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#anchor14).
     #[serde(rename = "date-time")]
     DateTime,
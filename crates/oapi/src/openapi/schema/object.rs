@@ -71,6 +71,10 @@ pub struct Object {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub additional_properties: Option<Box<AdditionalProperties<Schema>>>,
 
+    /// [`Schema`] of the property names of the [`Object`] (Useful for typed maps with enum keys).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property_names: Option<Box<RefOr<Schema>>>,
+
     /// Changes the [`Object`] deprecated status.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deprecated: Option<Deprecated>,
@@ -193,6 +197,12 @@ impl Object {
         self
     }
 
+    /// Add schema of the property names to the [`Object`].
+    pub fn property_names<I: Into<RefOr<Schema>>>(mut self, property_names: I) -> Self {
+        self.property_names = Some(Box::new(property_names.into()));
+        self
+    }
+
     /// Add field to the required fields of [`Object`].
     pub fn required(mut self, required_field: impl Into<String>) -> Self {
         self.required.insert(required_field.into());
@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 /// [endpoint]: ../../attr.endpoint.html
 /// [openapi]: ../../derive.OpenApi.html
 #[derive(Serialize, Deserialize, Debug, Ord, PartialOrd, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
 pub struct SecurityRequirement {
     #[serde(flatten)]
     value: BTreeMap<String, Vec<String>>,
@@ -59,6 +60,30 @@ impl SecurityRequirement {
             })),
         }
     }
+    /// Add a security scheme which is required together with the schemes already in this requirement.
+    ///
+    /// # Examples
+    ///
+    /// Require both api key and oauth2 scopes.
+    /// ```
+    /// # use salvo_oapi::security::SecurityRequirement;
+    /// SecurityRequirement::new("api_key", [] as [&str; 0]).add("oauth2", ["read:items"]);
+    /// ```
+    pub fn add<N: Into<String>, S: IntoIterator<Item = I>, I: Into<String>>(mut self, name: N, scopes: S) -> Self {
+        self.value
+            .insert(name.into(), scopes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns `true` if this requirement is empty, which means the security is optional.
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Get names of the security schemes in this requirement.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.value.keys().map(|k| k.as_str())
+    }
 }
 
 /// OpenAPI [security scheme][security] for path operations.
@@ -176,7 +201,7 @@ impl ApiKeyValue {
 /// Methods can be chained to configure _bearer_format_ or to add _description_.
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Http {
     /// Http authorization scheme in HTTP `Authorization` header value.
     pub scheme: HttpAuthScheme,
@@ -809,6 +834,7 @@ impl ClientCredentials {
 /// ]);
 /// ```
 #[derive(Default, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(default)]
 pub struct Scopes {
     scopes: BTreeMap<String, String>,
 }
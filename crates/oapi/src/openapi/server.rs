@@ -132,7 +132,7 @@ impl Servers {
 /// [openapi]: ../struct.OpenApi.html
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Server {
     /// Target url of the [`Server`]. It can be valid http url or relative path.
     ///
@@ -282,6 +282,7 @@ impl ServerVariables {
 /// [server_variable]: https://spec.openapis.org/oas/latest.html#server-variable-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
 pub struct ServerVariable {
     /// Default value used to substitute parameter if no other value is being provided.
     #[serde(rename = "default")]
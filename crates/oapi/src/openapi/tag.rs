@@ -14,7 +14,7 @@ use super::external_docs::ExternalDocs;
 /// [tag]: https://spec.openapis.org/oas/latest.html#tag-object
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Tag {
     /// Name of the tag. Should match to tag of **operation**.
     pub name: String,
@@ -15,6 +15,7 @@ use serde::{Deserialize, Serialize};
 /// [schema]: ../schema/index.html
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
 pub struct Xml {
     /// Used to replace the name of attribute or type used in schema property.
     /// When used with [`Xml::wrapped`] attribute the name will be used as a wrapper name
@@ -7,12 +7,15 @@
 use salvo_core::writing::Text;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Router};
 
+const DEFAULT_LIB_URL: &str = "https://unpkg.com/rapidoc/dist/rapidoc-min.js";
+
 const INDEX_TMPL: &str = r#"
 <!doctype html>
 <html>
   <head>
     <meta charset="utf-8">
-    <script type="module" src="https://unpkg.com/rapidoc/dist/rapidoc-min.js"></script>
+    <title>{{title}}</title>
+    <script type="module" src="{{lib_url}}"></script>
   </head>
   <body>
     <rapi-doc spec-url="{{spec_url}}"></rapi-doc>
@@ -23,6 +26,8 @@ const INDEX_TMPL: &str = r#"
 /// Implements [`Handler`] for serving RapiDoc.
 #[derive(Clone, Debug)]
 pub struct RapiDoc {
+    title: String,
+    lib_url: String,
     spec_url: String,
     html: String,
 }
@@ -39,11 +44,37 @@ impl RapiDoc {
     /// let doc = RapiDoc::new("/openapi.json");
     /// ```
     pub fn new(spec_url: impl Into<String>) -> Self {
-        let spec_url = spec_url.into();
-        Self {
-            html: INDEX_TMPL.replace("{{spec_url}}", &spec_url),
-            spec_url,
-        }
+        let mut doc = Self {
+            title: "RapiDoc".into(),
+            lib_url: DEFAULT_LIB_URL.into(),
+            spec_url: spec_url.into(),
+            html: String::new(),
+        };
+        doc.build_html();
+        doc
+    }
+
+    /// Set title of the html page. The default title is `RapiDoc`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self.build_html();
+        self
+    }
+
+    /// Set url of the RapiDoc javascript bundle, the default one is loaded from CDN.
+    ///
+    /// It can be used to serve the bundle from your own server in air-gapped environments.
+    pub fn lib_url(mut self, lib_url: impl Into<String>) -> Self {
+        self.lib_url = lib_url.into();
+        self.build_html();
+        self
+    }
+
+    fn build_html(&mut self) {
+        self.html = INDEX_TMPL
+            .replace("{{title}}", &self.title)
+            .replace("{{lib_url}}", &self.lib_url)
+            .replace("{{spec_url}}", &self.spec_url);
     }
 
     /// Returns the spec url.
@@ -7,11 +7,13 @@
 use salvo_core::writing::Text;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Router};
 
+const DEFAULT_LIB_URL: &str = "https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js";
+
 const INDEX_TMPL: &str = r#"
 <!DOCTYPE html>
 <html>
   <head>
-    <title>Redoc</title>
+    <title>{{title}}</title>
     <meta charset="utf-8" />
     <meta name="viewport" content="width=device-width, initial-scale=1" />
     <link
@@ -28,7 +30,7 @@ const INDEX_TMPL: &str = r#"
 
   <body>
     <div id="redoc-container"></div>
-    <script src="https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js"></script>
+    <script src="{{lib_url}}"></script>
     <script>
       Redoc.init(
         "{{spec_url}}",
@@ -44,6 +46,8 @@ const INDEX_TMPL: &str = r#"
 /// Implements [`Handler`] for serving ReDoc.
 #[derive(Clone, Debug)]
 pub struct ReDoc {
+    title: String,
+    lib_url: String,
     spec_url: String,
     html: String,
 }
@@ -60,11 +64,37 @@ impl ReDoc {
     /// let doc = ReDoc::new("/openapi.json");
     /// ```
     pub fn new(spec_url: impl Into<String>) -> Self {
-        let spec_url = spec_url.into();
-        Self {
-            html: INDEX_TMPL.replace("{{spec_url}}", &spec_url),
-            spec_url,
-        }
+        let mut doc = Self {
+            title: "Redoc".into(),
+            lib_url: DEFAULT_LIB_URL.into(),
+            spec_url: spec_url.into(),
+            html: String::new(),
+        };
+        doc.build_html();
+        doc
+    }
+
+    /// Set title of the html page. The default title is `Redoc`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self.build_html();
+        self
+    }
+
+    /// Set url of the ReDoc javascript bundle, the default one is loaded from CDN.
+    ///
+    /// It can be used to serve the bundle from your own server in air-gapped environments.
+    pub fn lib_url(mut self, lib_url: impl Into<String>) -> Self {
+        self.lib_url = lib_url.into();
+        self.build_html();
+        self
+    }
+
+    fn build_html(&mut self) {
+        self.html = INDEX_TMPL
+            .replace("{{title}}", &self.title)
+            .replace("{{lib_url}}", &self.lib_url)
+            .replace("{{spec_url}}", &self.spec_url);
     }
 
     /// Returns the spec url.
@@ -7,11 +7,13 @@
 use salvo_core::writing::Text;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Router};
 
+const DEFAULT_LIB_URL: &str = "https://www.unpkg.com/@scalar/api-reference";
+
 const INDEX_TMPL: &str = r#"
 <!DOCTYPE html>
 <html>
   <head>
-    <title>Scalar</title>
+    <title>{{title}}</title>
     <meta charset="utf-8" />
     <meta name="viewport" content="width=device-width, initial-scale=1" />
     <link
@@ -28,22 +30,24 @@ const INDEX_TMPL: &str = r#"
 
   <body>
     <script id="api-reference" data-url="{{spec_url}}"></script>
-    <script src="https://www.unpkg.com/@scalar/api-reference"></script>
+    <script src="{{lib_url}}"></script>
   </body>
 </html>
 
 "#;
 
-/// Implements [`Handler`] for serving ReDoc.
+/// Implements [`Handler`] for serving Scalar.
 #[derive(Clone, Debug)]
 pub struct Scalar {
+    title: String,
+    lib_url: String,
     spec_url: String,
     html: String,
 }
 impl Scalar {
-    /// Create a new [`ReDoc`] for given path.
+    /// Create a new [`Scalar`] for given path.
     ///
-    /// Path argument will expose the ReDoc to the user and should be something that
+    /// Path argument will expose the Scalar to the user and should be something that
     /// the underlying application framework / library supports.
     ///
     /// # Examples
@@ -53,11 +57,37 @@ impl Scalar {
     /// let doc = Scalar::new("/openapi.json");
     /// ```
     pub fn new(spec_url: impl Into<String>) -> Self {
-        let spec_url = spec_url.into();
-        Self {
-            html: INDEX_TMPL.replace("{{spec_url}}", &spec_url),
-            spec_url,
-        }
+        let mut doc = Self {
+            title: "Scalar".into(),
+            lib_url: DEFAULT_LIB_URL.into(),
+            spec_url: spec_url.into(),
+            html: String::new(),
+        };
+        doc.build_html();
+        doc
+    }
+
+    /// Set title of the html page. The default title is `Scalar`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self.build_html();
+        self
+    }
+
+    /// Set url of the Scalar javascript bundle, the default one is loaded from CDN.
+    ///
+    /// It can be used to serve the bundle from your own server in air-gapped environments.
+    pub fn lib_url(mut self, lib_url: impl Into<String>) -> Self {
+        self.lib_url = lib_url.into();
+        self.build_html();
+        self
+    }
+
+    fn build_html(&mut self) {
+        self.html = INDEX_TMPL
+            .replace("{{title}}", &self.title)
+            .replace("{{lib_url}}", &self.lib_url)
+            .replace("{{spec_url}}", &self.spec_url);
     }
 
     /// Returns the spec url.
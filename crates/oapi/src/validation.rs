@@ -0,0 +1,804 @@
+//! Request validation against the generated OpenAPI document.
+//!
+//! [`RequestValidator`] looks up the operation matching the current request in an [`OpenApi`] document and
+//! checks parameters, content type and JSON body before the handler runs. Invalid requests are rejected
+//! with `400 Bad Request` or `415 Unsupported Media Type` and a JSON body listing every problem found.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_oapi::validation::RequestValidator;
+//! use salvo_oapi::{endpoint, OpenApi};
+//!
+//! #[endpoint]
+//! async fn hello(name: salvo_oapi::extract::QueryParam<String, true>) -> String {
+//!     format!("Hello, {}!", name.into_inner())
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::new().push(Router::with_path("hello").get(hello));
+//!     let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+//!     let router = router
+//!         .hoop(RequestValidator::new(&doc))
+//!         .push(doc.into_router("/api-doc/openapi.json"));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Display, Formatter};
+
+use regex::Regex;
+use salvo_core::http::{mime, Method, Mime, StatusCode};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::path::{PathItem, PathItemType};
+use crate::schema::AdditionalProperties;
+use crate::{Components, OpenApi, Operation, Parameter, ParameterIn, RefOr, Required, Schema, SchemaType};
+
+/// Maximum depth followed when resolving nested schemas and references.
+const MAX_DEPTH: usize = 64;
+
+/// Path item and operation matched by a request, with the path params extracted from the request path.
+type MatchedOperation<'a> = (&'a PathItem, &'a Operation, Vec<(&'a str, &'a str)>);
+
+/// Where a [`ValidationError`] was found.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorLocation {
+    /// Path parameter.
+    Path,
+    /// Query parameter.
+    Query,
+    /// Header value.
+    Header,
+    /// Cookie value.
+    Cookie,
+    /// Request body.
+    Body,
+}
+
+impl From<ParameterIn> for ErrorLocation {
+    fn from(parameter_in: ParameterIn) -> Self {
+        match parameter_in {
+            ParameterIn::Path => Self::Path,
+            ParameterIn::Query => Self::Query,
+            ParameterIn::Header => Self::Header,
+            ParameterIn::Cookie => Self::Cookie,
+        }
+    }
+}
+
+/// A single problem found while validating a request.
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct ValidationError {
+    /// Where the invalid value was found.
+    pub location: ErrorLocation,
+    /// Parameter name, or JSON pointer into the body.
+    pub name: String,
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+impl ValidationError {
+    /// Create a new `ValidationError`.
+    pub fn new(location: ErrorLocation, name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location,
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{:?}: {}", self.location, self.message)
+        } else {
+            write!(f, "{:?} `{}`: {}", self.location, self.name, self.message)
+        }
+    }
+}
+
+/// Body rendered when a request is rejected.
+#[derive(Serialize, Debug)]
+struct ValidationErrors {
+    code: u16,
+    name: &'static str,
+    errors: Vec<ValidationError>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest,
+}
+
+#[derive(Clone, Debug)]
+struct ValidationRoute {
+    segments: Vec<Segment>,
+    item: PathItem,
+}
+
+impl ValidationRoute {
+    fn new(path: &str, item: PathItem) -> Self {
+        let segments = split_path(path)
+            .map(|segment| {
+                if segment.starts_with('{') && segment.ends_with('}') {
+                    let name = &segment[1..segment.len() - 1];
+                    if name.starts_with('*') {
+                        Segment::Rest
+                    } else {
+                        Segment::Param(name.to_owned())
+                    }
+                } else {
+                    Segment::Literal(segment.to_owned())
+                }
+            })
+            .collect();
+        Self { segments, item }
+    }
+
+    /// Returns captured path parameters and the number of matched literal segments.
+    fn matches<'a>(&self, path: &'a str) -> Option<(Vec<(&str, &'a str)>, usize)> {
+        let mut params = Vec::new();
+        let mut literals = 0;
+        let mut parts = split_path(path);
+        for segment in &self.segments {
+            match segment {
+                Segment::Rest => return Some((params, literals)),
+                Segment::Literal(literal) => {
+                    if parts.next()? != literal {
+                        return None;
+                    }
+                    literals += 1;
+                }
+                Segment::Param(name) => {
+                    params.push((&**name, parts.next()?));
+                }
+            }
+        }
+        if parts.next().is_some() {
+            None
+        } else {
+            Some((params, literals))
+        }
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// Middleware validating requests against an [`OpenApi`] document.
+///
+/// Requests that do not match any documented path and method are passed through unchanged.
+#[derive(Clone, Debug)]
+pub struct RequestValidator {
+    routes: Vec<ValidationRoute>,
+    components: Components,
+    validate_parameters: bool,
+    validate_body: bool,
+    max_body_size: Option<usize>,
+}
+
+impl RequestValidator {
+    /// Create a new `RequestValidator` from the given document.
+    pub fn new(doc: &OpenApi) -> Self {
+        let routes = doc
+            .paths
+            .iter()
+            .map(|(path, item)| ValidationRoute::new(path, item.clone()))
+            .collect();
+        Self {
+            routes,
+            components: doc.components.clone(),
+            validate_parameters: true,
+            validate_body: true,
+            max_body_size: None,
+        }
+    }
+
+    /// Sets whether path, query, header and cookie parameters are validated. Default is `true`.
+    pub fn validate_parameters(mut self, validate_parameters: bool) -> Self {
+        self.validate_parameters = validate_parameters;
+        self
+    }
+
+    /// Sets whether the request body is validated. Default is `true`.
+    pub fn validate_body(mut self, validate_body: bool) -> Self {
+        self.validate_body = validate_body;
+        self
+    }
+
+    /// Sets the maximum body size read for validation. Default uses the request's secure max size.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    fn find_operation<'a>(
+        &'a self,
+        method: &Method,
+        path: &'a str,
+    ) -> Option<MatchedOperation<'a>> {
+        let item_type = match method.as_str() {
+            "GET" => PathItemType::Get,
+            "POST" => PathItemType::Post,
+            "PUT" => PathItemType::Put,
+            "DELETE" => PathItemType::Delete,
+            "OPTIONS" => PathItemType::Options,
+            "HEAD" => PathItemType::Head,
+            "PATCH" => PathItemType::Patch,
+            "TRACE" => PathItemType::Trace,
+            "CONNECT" => PathItemType::Connect,
+            _ => return None,
+        };
+        self.routes
+            .iter()
+            .filter_map(|route| {
+                let operation = route.item.operations.get(&item_type)?;
+                let (params, literals) = route.matches(path)?;
+                Some((&route.item, operation, params, literals))
+            })
+            .max_by_key(|(_, _, _, literals)| *literals)
+            .map(|(item, operation, params, _)| (item, operation, params))
+    }
+
+    /// Validate the request, returning the status code to reject it with and the errors found.
+    async fn validate(&self, req: &mut Request) -> Option<(StatusCode, Vec<ValidationError>)> {
+        let path = req.uri().path().to_owned();
+        let (item, operation, path_params) = self.find_operation(req.method(), &path)?;
+        let mut errors = Vec::new();
+        if self.validate_parameters {
+            // Operation level parameters override path item level ones with the same name and location.
+            let parameters = operation.parameters.0.iter().chain(
+                item.parameters
+                    .0
+                    .iter()
+                    .filter(|parameter| !operation.parameters.contains(&parameter.name, parameter.parameter_in)),
+            );
+            for parameter in parameters {
+                self.validate_parameter(req, parameter, &path_params, &mut errors);
+            }
+        }
+        if self.validate_body {
+            if let Some(request_body) = &operation.request_body {
+                match req.content_type() {
+                    None => {
+                        if request_body.required == Some(Required::True) {
+                            errors.push(ValidationError::new(
+                                ErrorLocation::Body,
+                                "",
+                                "request body is required",
+                            ));
+                        }
+                    }
+                    Some(ctype) => {
+                        let content = request_body
+                            .contents
+                            .iter()
+                            .find(|(kind, _)| mime_matches(kind, &ctype))
+                            .map(|(_, content)| content);
+                        let Some(content) = content else {
+                            let expected = request_body.contents.keys().cloned().collect::<Vec<_>>().join(", ");
+                            errors.push(ValidationError::new(
+                                ErrorLocation::Body,
+                                "",
+                                format!(
+                                    "unsupported content type `{}`, expected one of: {}",
+                                    ctype.essence_str(),
+                                    expected
+                                ),
+                            ));
+                            return Some((StatusCode::UNSUPPORTED_MEDIA_TYPE, errors));
+                        };
+                        if ctype.subtype() == mime::JSON || ctype.suffix() == Some(mime::JSON) {
+                            let payload = match self.max_body_size {
+                                Some(max_size) => req.payload_with_max_size(max_size).await,
+                                None => req.payload().await,
+                            };
+                            match payload {
+                                Ok(payload) if payload.is_empty() => {
+                                    if request_body.required == Some(Required::True) {
+                                        errors.push(ValidationError::new(
+                                            ErrorLocation::Body,
+                                            "",
+                                            "request body is required",
+                                        ));
+                                    }
+                                }
+                                Ok(payload) => match serde_json::from_slice::<Value>(payload) {
+                                    Ok(value) => self.validate_value(
+                                        &value,
+                                        &content.schema,
+                                        ErrorLocation::Body,
+                                        "",
+                                        &mut errors,
+                                        0,
+                                    ),
+                                    Err(e) => errors.push(ValidationError::new(
+                                        ErrorLocation::Body,
+                                        "",
+                                        format!("invalid json: {}", e),
+                                    )),
+                                },
+                                Err(e) => errors.push(ValidationError::new(
+                                    ErrorLocation::Body,
+                                    "",
+                                    format!("failed to read request body: {}", e),
+                                )),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            None
+        } else {
+            Some((StatusCode::BAD_REQUEST, errors))
+        }
+    }
+
+    fn validate_parameter(
+        &self,
+        req: &Request,
+        parameter: &Parameter,
+        path_params: &[(&str, &str)],
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let location = ErrorLocation::from(parameter.parameter_in);
+        let name = &*parameter.name;
+        let values: Vec<&str> = match parameter.parameter_in {
+            ParameterIn::Path => req
+                .params()
+                .get(name)
+                .map(|value| &**value)
+                .or_else(|| path_params.iter().find(|(n, _)| *n == name).map(|(_, value)| *value))
+                .into_iter()
+                .collect(),
+            ParameterIn::Query => req
+                .queries()
+                .get_vec(name)
+                .map(|values| values.iter().map(|value| &**value).collect())
+                .unwrap_or_default(),
+            ParameterIn::Header => req
+                .headers()
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .collect(),
+            ParameterIn::Cookie => req.cookie(name).map(|cookie| cookie.value()).into_iter().collect(),
+        };
+        if values.is_empty() {
+            if parameter.required == Required::True || parameter.parameter_in == ParameterIn::Path {
+                errors.push(ValidationError::new(location, name, "missing required parameter"));
+            }
+            return;
+        }
+        let Some(schema) = &parameter.schema else {
+            return;
+        };
+        let value = match self.resolve(schema, 0) {
+            Some(Schema::Array(array)) => {
+                let values = if values.len() == 1 {
+                    values[0].split(',').collect()
+                } else {
+                    values
+                };
+                let item_schema = self.resolve(&array.items, 0);
+                Value::Array(values.into_iter().map(|value| coerce_str(value, item_schema)).collect())
+            }
+            schema => coerce_str(values[0], schema),
+        };
+        self.validate_value(&value, schema, location, name, errors, 0);
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a RefOr<Schema>, depth: usize) -> Option<&'a Schema> {
+        match schema {
+            RefOr::T(schema) => Some(schema),
+            RefOr::Ref(reference) => {
+                if depth > MAX_DEPTH {
+                    return None;
+                }
+                let name = reference.ref_location.strip_prefix("#/components/schemas/")?;
+                self.resolve(self.components.schemas.get(name)?, depth + 1)
+            }
+        }
+    }
+
+    fn validate_value(
+        &self,
+        value: &Value,
+        schema: &RefOr<Schema>,
+        location: ErrorLocation,
+        name: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let Some(schema) = self.resolve(schema, depth) else {
+            return;
+        };
+        let mut error = |message: String| errors.push(ValidationError::new(location, name, message));
+        match schema {
+            Schema::Object(object) => {
+                if value.is_null() {
+                    if !object.nullable && !is_free_form(object) {
+                        error("value must not be null".into());
+                    }
+                    return;
+                }
+                if let Some(enum_values) = &object.enum_values {
+                    if !enum_values.contains(value) {
+                        error(format!("value must be one of: {}", Value::Array(enum_values.clone())));
+                        return;
+                    }
+                }
+                match object.schema_type {
+                    SchemaType::String => match value.as_str() {
+                        Some(s) => {
+                            let len = s.chars().count();
+                            if let Some(max_length) = object.max_length {
+                                if len > max_length {
+                                    error(format!("length must be at most {}", max_length));
+                                }
+                            }
+                            if let Some(min_length) = object.min_length {
+                                if len < min_length {
+                                    error(format!("length must be at least {}", min_length));
+                                }
+                            }
+                            if let Some(pattern) = &object.pattern {
+                                if let Ok(regex) = Regex::new(pattern) {
+                                    if !regex.is_match(s) {
+                                        error(format!("value must match pattern `{}`", pattern));
+                                    }
+                                }
+                            }
+                        }
+                        None => error("expected string".into()),
+                    },
+                    SchemaType::Integer | SchemaType::Number => {
+                        let number = if object.schema_type == SchemaType::Integer {
+                            value
+                                .as_i64()
+                                .map(|v| v as f64)
+                                .or_else(|| value.as_u64().map(|v| v as f64))
+                        } else {
+                            value.as_f64()
+                        };
+                        let Some(number) = number else {
+                            if object.schema_type == SchemaType::Integer {
+                                error("expected integer".into());
+                            } else {
+                                error("expected number".into());
+                            }
+                            return;
+                        };
+                        if let Some(maximum) = object.maximum {
+                            if number > maximum {
+                                error(format!("value must be less than or equal to {}", maximum));
+                            }
+                        }
+                        if let Some(minimum) = object.minimum {
+                            if number < minimum {
+                                error(format!("value must be greater than or equal to {}", minimum));
+                            }
+                        }
+                        if let Some(exclusive_maximum) = object.exclusive_maximum {
+                            if number >= exclusive_maximum {
+                                error(format!("value must be less than {}", exclusive_maximum));
+                            }
+                        }
+                        if let Some(exclusive_minimum) = object.exclusive_minimum {
+                            if number <= exclusive_minimum {
+                                error(format!("value must be greater than {}", exclusive_minimum));
+                            }
+                        }
+                        if let Some(multiple_of) = object.multiple_of {
+                            if multiple_of > 0.0 && (number / multiple_of).fract() != 0.0 {
+                                error(format!("value must be a multiple of {}", multiple_of));
+                            }
+                        }
+                    }
+                    SchemaType::Boolean => {
+                        if !value.is_boolean() {
+                            error("expected boolean".into());
+                        }
+                    }
+                    SchemaType::Array => {
+                        if !value.is_array() {
+                            error("expected array".into());
+                        }
+                    }
+                    SchemaType::Object => {
+                        if is_free_form(object) {
+                            return;
+                        }
+                        let Some(map) = value.as_object() else {
+                            error("expected object".into());
+                            return;
+                        };
+                        self.validate_object(map, object, location, name, errors, depth);
+                    }
+                }
+            }
+            Schema::Array(array) => {
+                if value.is_null() {
+                    if !array.nullable {
+                        error("value must not be null".into());
+                    }
+                    return;
+                }
+                let Some(items) = value.as_array() else {
+                    error("expected array".into());
+                    return;
+                };
+                if let Some(max_items) = array.max_items {
+                    if items.len() > max_items {
+                        error(format!("must contain at most {} items", max_items));
+                    }
+                }
+                if let Some(min_items) = array.min_items {
+                    if items.len() < min_items {
+                        error(format!("must contain at least {} items", min_items));
+                    }
+                }
+                if array.unique_items && items.iter().enumerate().any(|(i, item)| items[..i].contains(item)) {
+                    error("items must be unique".into());
+                }
+                for (index, item) in items.iter().enumerate() {
+                    let name = child_name(location, name, &index.to_string());
+                    self.validate_value(item, &array.items, location, &name, errors, depth + 1);
+                }
+            }
+            Schema::OneOf(one_of) => {
+                if value.is_null() && one_of.nullable {
+                    return;
+                }
+                let matched = one_of
+                    .items
+                    .iter()
+                    .filter(|item| self.is_valid(value, item, depth + 1))
+                    .count();
+                if matched != 1 {
+                    error(format!("value must match exactly one schema, matched {}", matched));
+                }
+            }
+            Schema::AnyOf(any_of) => {
+                if value.is_null() && any_of.nullable {
+                    return;
+                }
+                if !any_of.items.iter().any(|item| self.is_valid(value, item, depth + 1)) {
+                    error("value must match at least one schema".into());
+                }
+            }
+            Schema::AllOf(all_of) => {
+                if value.is_null() && all_of.nullable {
+                    return;
+                }
+                for item in &all_of.items {
+                    self.validate_value(value, item, location, name, errors, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn validate_object(
+        &self,
+        map: &Map<String, Value>,
+        object: &crate::Object,
+        location: ErrorLocation,
+        name: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        for required in &object.required {
+            if !map.contains_key(required) {
+                errors.push(ValidationError::new(
+                    location,
+                    child_name(location, name, required),
+                    "missing required property",
+                ));
+            }
+        }
+        if let Some(max_properties) = object.max_properties {
+            if map.len() > max_properties {
+                errors.push(ValidationError::new(
+                    location,
+                    name,
+                    format!("must contain at most {} properties", max_properties),
+                ));
+            }
+        }
+        if let Some(min_properties) = object.min_properties {
+            if map.len() < min_properties {
+                errors.push(ValidationError::new(
+                    location,
+                    name,
+                    format!("must contain at least {} properties", min_properties),
+                ));
+            }
+        }
+        for (key, value) in map {
+            let child = child_name(location, name, key);
+            if let Some(property) = object.properties.get(key) {
+                self.validate_value(value, property, location, &child, errors, depth + 1);
+                continue;
+            }
+            match object.additional_properties.as_deref() {
+                Some(AdditionalProperties::FreeForm(false)) => {
+                    errors.push(ValidationError::new(location, child, "unknown property"));
+                }
+                Some(AdditionalProperties::RefOr(schema)) => {
+                    self.validate_value(value, schema, location, &child, errors, depth + 1);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn is_valid(&self, value: &Value, schema: &RefOr<Schema>, depth: usize) -> bool {
+        let mut errors = Vec::new();
+        self.validate_value(value, schema, ErrorLocation::Body, "", &mut errors, depth);
+        errors.is_empty()
+    }
+}
+
+/// An object schema without properties describes any value, e.g. `serde_json::Value`.
+fn is_free_form(object: &crate::Object) -> bool {
+    object.schema_type == SchemaType::Object
+        && object.properties.is_empty()
+        && object.additional_properties.is_none()
+        && object.enum_values.is_none()
+}
+
+/// Body errors are named by JSON pointer, parameter errors by parameter name.
+fn child_name(location: ErrorLocation, name: &str, key: &str) -> String {
+    if location == ErrorLocation::Body {
+        format!("{}/{}", name, key.replace('~', "~0").replace('/', "~1"))
+    } else {
+        format!("{}[{}]", name, key)
+    }
+}
+
+/// Convert a raw parameter string to the JSON value its schema expects, leaving it a string if it does not parse.
+fn coerce_str(value: &str, schema: Option<&Schema>) -> Value {
+    let Some(Schema::Object(object)) = schema else {
+        return Value::String(value.to_owned());
+    };
+    let coerced = match object.schema_type {
+        SchemaType::Integer => value
+            .parse::<i64>()
+            .ok()
+            .map(Number::from)
+            .or_else(|| value.parse::<u64>().ok().map(Number::from))
+            .map(Value::Number),
+        SchemaType::Number => value.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number),
+        SchemaType::Boolean => value.parse::<bool>().ok().map(Value::Bool),
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| Value::String(value.to_owned()))
+}
+
+fn mime_matches(kind: &str, ctype: &Mime) -> bool {
+    let Ok(kind) = kind.parse::<Mime>() else {
+        return false;
+    };
+    (kind.type_() == mime::STAR || kind.type_() == ctype.type_())
+        && (kind.subtype() == mime::STAR || kind.subtype() == ctype.subtype())
+}
+
+#[async_trait]
+impl Handler for RequestValidator {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if let Some((code, errors)) = self.validate(req).await {
+            tracing::debug!(%code, ?errors, "request rejected by openapi validation");
+            res.status_code(code);
+            res.render(Json(ValidationErrors {
+                code: code.as_u16(),
+                name: code.canonical_reason().unwrap_or_default(),
+                errors,
+            }));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Content, Object, Operation, PathItem, RequestBody};
+
+    fn doc() -> OpenApi {
+        let body = Object::new()
+            .property("name", Object::with_type(SchemaType::String).min_length(1))
+            .property("age", Object::with_type(SchemaType::Integer).minimum(0.0))
+            .required("name");
+        let operation = Operation::new()
+            .add_parameter(
+                Parameter::new("id")
+                    .parameter_in(ParameterIn::Path)
+                    .required(Required::True)
+                    .schema(Object::with_type(SchemaType::Integer)),
+            )
+            .add_parameter(
+                Parameter::new("notify")
+                    .parameter_in(ParameterIn::Query)
+                    .required(Required::False)
+                    .schema(Object::with_type(SchemaType::Boolean)),
+            )
+            .request_body(
+                RequestBody::new()
+                    .add_content("application/json", Content::new(body))
+                    .required(Required::True),
+            );
+        OpenApi::new("test api", "0.0.1").add_path("/pets/{id}", PathItem::new(PathItemType::Put, operation))
+    }
+
+    #[handler]
+    async fn update() -> &'static str {
+        "updated"
+    }
+
+    fn service() -> Service {
+        let router = Router::new()
+            .hoop(RequestValidator::new(&doc()))
+            .push(Router::with_path("pets/<id>").put(update));
+        Service::new(router)
+    }
+
+    #[tokio::test]
+    async fn test_valid_request() {
+        let mut res = TestClient::put("http://127.0.0.1:5800/pets/1?notify=true")
+            .json(&json!({"name": "Tom", "age": 3}))
+            .send(&service())
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "updated");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request() {
+        let mut res = TestClient::put("http://127.0.0.1:5800/pets/abc?notify=maybe")
+            .json(&json!({"age": -1}))
+            .send(&service())
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        let body: Value = res.take_json().await.unwrap();
+        let errors = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| (error["location"].as_str().unwrap(), error["name"].as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![("path", "id"), ("query", "notify"), ("body", "/name"), ("body", "/age")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_media_type() {
+        let res = TestClient::put("http://127.0.0.1:5800/pets/1")
+            .text("name=Tom")
+            .send(&service())
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+}
@@ -0,0 +1,572 @@
+//! OAuth2 and OpenID Connect login support for Salvo web framework.
+//!
+//! This crate implements the authorization code flow with PKCE. [`OAuth::login`] redirects users to the provider
+//! and [`OAuth::callback`] validates `state`, exchanges the code for tokens, verifies the id token and its
+//! `nonce`, fetches userinfo and stores the authenticated [`Principal`] in the session. So
+//! [`SessionHandler`](salvo_session::SessionHandler) must be added before these handlers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_oauth::{OAuth, OAuthDepotExt, Provider};
+//! use salvo_session::{MemoryStore, SessionHandler};
+//!
+//! #[handler]
+//! async fn home(depot: &mut Depot) -> String {
+//!     match depot.principal() {
+//!         Some(principal) => format!("Hello, {}", principal.subject),
+//!         None => "Hello, guest".into(),
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let provider = Provider::builder("client-id", "http://localhost:5800/auth/callback")
+//!         .client_secret("client-secret")
+//!         .scopes(["openid", "email", "profile"])
+//!         .discover("https://accounts.google.com")
+//!         .await
+//!         .unwrap();
+//!     let oauth = OAuth::new(provider);
+//!     let session_handler = SessionHandler::builder(
+//!         MemoryStore::new(),
+//!         b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+//!     )
+//!     .build()
+//!     .unwrap();
+//!     let router = Router::new()
+//!         .hoop(session_handler)
+//!         .get(home)
+//!         .push(Router::with_path("login").get(oauth.login()))
+//!         .push(Router::with_path("auth/callback").get(oauth.callback()));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::future_not_send)]
+#![warn(rustdoc::broken_intra_doc_links)]
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use salvo_core::http::{StatusCode, StatusError};
+use salvo_core::writing::Redirect;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use salvo_session::SessionDepotExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+mod provider;
+pub use provider::{AuthMethod, Provider, ProviderBuilder, ProviderMetadata, TokenResponse};
+
+/// Key for store the authenticated principal in session.
+pub const PRINCIPAL_KEY: &str = "::salvo::oauth::principal";
+/// Key for store the pending authorization request in session.
+pub const PENDING_KEY: &str = "::salvo::oauth::pending";
+
+/// Errors of OAuth login.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum OAuthError {
+    /// Http request to the provider failed.
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Invalid url.
+    #[error("invalid url: {0}")]
+    Url(#[from] url::ParseError),
+    /// Invalid id token.
+    #[error("invalid id token: {0}")]
+    IdToken(#[from] jsonwebtoken::errors::Error),
+    /// The provider returned an error.
+    #[error("provider returned error `{error}`")]
+    Provider {
+        /// Error code.
+        error: String,
+        /// Error description.
+        description: Option<String>,
+    },
+    /// Issuer in discovered metadata is not the requested issuer.
+    #[error("issuer mismatch")]
+    IssuerMismatch,
+    /// No pending authorization request is found in session.
+    #[error("authorization request not found")]
+    MissingRequest,
+    /// The `code` parameter is missing in callback.
+    #[error("authorization code not found")]
+    MissingCode,
+    /// The `state` parameter does not match the pending authorization request.
+    #[error("state mismatch")]
+    StateMismatch,
+    /// The `nonce` claim of id token does not match the pending authorization request.
+    #[error("nonce mismatch")]
+    NonceMismatch,
+    /// No key is found to verify the id token.
+    #[error("verification key not found")]
+    MissingKey,
+    /// No subject is found in id token or userinfo.
+    #[error("subject not found")]
+    MissingSubject,
+    /// The subject of userinfo is not the subject of id token.
+    #[error("subject mismatch")]
+    SubjectMismatch,
+}
+impl OAuthError {
+    fn status_error(&self) -> StatusError {
+        match self {
+            Self::MissingRequest | Self::MissingCode | Self::StateMismatch | Self::Provider { .. } => {
+                StatusError::bad_request()
+            }
+            Self::IdToken(_) | Self::NonceMismatch | Self::MissingSubject | Self::SubjectMismatch => {
+                StatusError::unauthorized()
+            }
+            _ => StatusError::bad_gateway(),
+        }
+    }
+}
+
+/// The authenticated user, it is stored in session after login.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct Principal {
+    /// Issuer of the provider.
+    pub issuer: String,
+    /// Subject identifier of the user at the provider.
+    pub subject: String,
+    /// Claims of id token merged with userinfo.
+    pub claims: Map<String, Value>,
+    /// The access token.
+    pub access_token: String,
+    /// The refresh token.
+    pub refresh_token: Option<String>,
+    /// The id token.
+    pub id_token: Option<String>,
+    /// Expiry of the access token as unix timestamp.
+    pub expires_at: Option<u64>,
+}
+impl Principal {
+    /// Get claim by name.
+    #[inline]
+    pub fn claim<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.claims
+            .get(name)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// Trait for `Depot` to get the authenticated principal from session.
+pub trait OAuthDepotExt {
+    /// Get the authenticated principal.
+    fn principal(&self) -> Option<Principal>;
+    /// Remove the authenticated principal from session.
+    fn clear_principal(&mut self) -> &mut Self;
+}
+impl OAuthDepotExt for Depot {
+    #[inline]
+    fn principal(&self) -> Option<Principal> {
+        self.session()?.get(PRINCIPAL_KEY)
+    }
+    #[inline]
+    fn clear_principal(&mut self) -> &mut Self {
+        if let Some(session) = self.session_mut() {
+            session.remove(PRINCIPAL_KEY);
+        }
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingRequest {
+    state: String,
+    nonce: String,
+    code_verifier: String,
+    return_to: Option<String>,
+}
+
+/// OAuth2 or OpenID Connect login with a [`Provider`].
+#[derive(Clone, Debug)]
+pub struct OAuth {
+    provider: Arc<Provider>,
+    redirect_to: String,
+}
+impl OAuth {
+    /// Create a new `OAuth`.
+    #[inline]
+    pub fn new(provider: Provider) -> Self {
+        Self::with_shared(Arc::new(provider))
+    }
+    /// Create a new `OAuth` with a shared provider.
+    #[inline]
+    pub fn with_shared(provider: Arc<Provider>) -> Self {
+        Self {
+            provider,
+            redirect_to: "/".into(),
+        }
+    }
+    /// Sets where users are redirected after login if `return_to` is not given. The default is "/".
+    #[inline]
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.redirect_to = redirect_to.into();
+        self
+    }
+    /// Get the provider.
+    #[inline]
+    pub fn provider(&self) -> &Provider {
+        &self.provider
+    }
+    /// Handler redirects users to the authorization endpoint of provider.
+    ///
+    /// A local path in `return_to` query parameter is saved, and users are redirected to it after login.
+    #[inline]
+    pub fn login(&self) -> OAuthLogin {
+        OAuthLogin(self.clone())
+    }
+    /// Handler of the redirect url, it completes login and redirects users back.
+    #[inline]
+    pub fn callback(&self) -> OAuthCallback {
+        OAuthCallback(self.clone())
+    }
+
+    async fn complete(
+        &self,
+        req: &Request,
+        pending: PendingRequest,
+    ) -> Result<(Principal, Option<String>), OAuthError> {
+        if let Some(error) = req.query::<String>("error") {
+            return Err(OAuthError::Provider {
+                error,
+                description: req.query::<String>("error_description"),
+            });
+        }
+        if req.query::<String>("state").as_deref() != Some(&*pending.state) {
+            return Err(OAuthError::StateMismatch);
+        }
+        let code = req.query::<String>("code").ok_or(OAuthError::MissingCode)?;
+        let tokens = self.provider.exchange_code(&code, &pending.code_verifier).await?;
+        let mut claims = match &tokens.id_token {
+            Some(id_token) => self.provider.verify_id_token(id_token, &pending.nonce).await?,
+            None => Map::new(),
+        };
+        if let Some(userinfo) = self.provider.userinfo(&tokens.access_token).await? {
+            // The userinfo `sub` must be the same as the id token `sub`.
+            if let (Some(sub), Some(userinfo_sub)) = (claims.get("sub"), userinfo.get("sub")) {
+                if sub != userinfo_sub {
+                    return Err(OAuthError::SubjectMismatch);
+                }
+            }
+            claims.extend(userinfo);
+        }
+        let subject = match claims.get("sub").or_else(|| claims.get("id")) {
+            Some(Value::String(sub)) => sub.clone(),
+            Some(Value::Number(sub)) => sub.to_string(),
+            _ => return Err(OAuthError::MissingSubject),
+        };
+        let expires_at = tokens.expires_in.map(|expires_in| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|now| now.as_secs())
+                .unwrap_or_default()
+                + expires_in
+        });
+        let principal = Principal {
+            issuer: self.provider.metadata().issuer.clone(),
+            subject,
+            claims,
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            id_token: tokens.id_token,
+            expires_at,
+        };
+        Ok((principal, pending.return_to))
+    }
+}
+
+/// Handler redirects users to the authorization endpoint, created by [`OAuth::login`].
+#[derive(Clone, Debug)]
+pub struct OAuthLogin(OAuth);
+#[async_trait]
+impl Handler for OAuthLogin {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let Some(session) = depot.session_mut() else {
+            tracing::error!("session not found in depot, `SessionHandler` should be added before `OAuthLogin`");
+            res.render(StatusError::internal_server_error());
+            return;
+        };
+        let pending = PendingRequest {
+            state: random_string(),
+            nonce: random_string(),
+            code_verifier: random_string(),
+            return_to: req.query::<String>("return_to").filter(|path| is_local_path(path)),
+        };
+        let url = match self.0.provider.authorization_url(
+            &pending.state,
+            &pending.nonce,
+            &code_challenge(&pending.code_verifier),
+        ) {
+            Ok(url) => url,
+            Err(e) => {
+                tracing::error!(error = ?e, "build authorization url failed");
+                res.render(StatusError::internal_server_error());
+                return;
+            }
+        };
+        if let Err(e) = session.insert(PENDING_KEY, &pending) {
+            tracing::error!(error = ?e, "save authorization request failed");
+            res.render(StatusError::internal_server_error());
+            return;
+        }
+        res.render(Redirect::found(url.as_str()));
+    }
+}
+
+/// Handler of the redirect url, created by [`OAuth::callback`].
+#[derive(Clone, Debug)]
+pub struct OAuthCallback(OAuth);
+#[async_trait]
+impl Handler for OAuthCallback {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let Some(session) = depot.session_mut() else {
+            tracing::error!("session not found in depot, `SessionHandler` should be added before `OAuthCallback`");
+            res.render(StatusError::internal_server_error());
+            return;
+        };
+        let pending = session.get::<PendingRequest>(PENDING_KEY);
+        session.remove(PENDING_KEY);
+        let result = match pending {
+            Some(pending) => self.0.complete(req, pending).await,
+            None => Err(OAuthError::MissingRequest),
+        };
+        match result {
+            Ok((principal, return_to)) => {
+                // Rotate session id to prevent session fixation.
+                let session = depot.cycle_session_id().expect("session should exist in depot");
+                if let Err(e) = session.insert(PRINCIPAL_KEY, &principal) {
+                    tracing::error!(error = ?e, "save principal failed");
+                    res.render(StatusError::internal_server_error());
+                    return;
+                }
+                res.render(Redirect::found(return_to.as_deref().unwrap_or(&self.0.redirect_to)));
+            }
+            Err(e) => {
+                tracing::warn!(error = ?e, "oauth login failed");
+                let status = e.status_error();
+                if status.code == StatusCode::BAD_GATEWAY {
+                    res.render(status);
+                } else {
+                    res.render(status.brief(e.to_string()));
+                }
+            }
+        }
+    }
+}
+
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn is_local_path(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use jsonwebtoken::{EncodingKey, Header};
+    use salvo_core::conn::{Acceptor, TcpListener};
+    use salvo_core::http::header::{AUTHORIZATION, COOKIE, LOCATION, SET_COOKIE};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use salvo_session::{MemoryStore, SessionHandler};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_code_challenge() {
+        // Example from RFC 7636 appendix B.
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert!(is_local_path("/account"));
+        assert!(!is_local_path("//evil.com"));
+        assert!(!is_local_path("https://evil.com"));
+    }
+
+    #[derive(Clone, Default)]
+    struct MockProvider {
+        issuer: Arc<Mutex<String>>,
+        // code challenge and nonce of the authorization request.
+        request: Arc<Mutex<Option<(String, String)>>>,
+    }
+    #[async_trait]
+    impl Handler for MockProvider {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let issuer = self.issuer.lock().unwrap().clone();
+            match req.uri().path() {
+                "/.well-known/openid-configuration" => res.render(Json(json!({
+                    "issuer": issuer,
+                    "authorization_endpoint": format!("{issuer}/authorize"),
+                    "token_endpoint": format!("{issuer}/token"),
+                    "userinfo_endpoint": format!("{issuer}/userinfo"),
+                }))),
+                "/token" => {
+                    let (challenge, nonce) = self.request.lock().unwrap().clone().unwrap();
+                    let verifier = req.form::<String>("code_verifier").await.unwrap_or_default();
+                    if req.form::<String>("code").await.as_deref() != Some("code")
+                        || code_challenge(&verifier) != challenge
+                    {
+                        res.status_code(StatusCode::BAD_REQUEST);
+                        res.render(Json(json!({"error": "invalid_grant"})));
+                        return;
+                    }
+                    let claims = json!({
+                        "iss": issuer,
+                        "aud": "client",
+                        "sub": "alice",
+                        "nonce": nonce,
+                        "exp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 60,
+                    });
+                    let id_token =
+                        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(b"secret"))
+                            .unwrap();
+                    res.render(Json(json!({
+                        "access_token": "access",
+                        "token_type": "Bearer",
+                        "expires_in": 3600,
+                        "id_token": id_token,
+                    })));
+                }
+                "/userinfo" => {
+                    if req.header::<String>(AUTHORIZATION).as_deref() == Some("Bearer access") {
+                        res.render(Json(json!({"sub": "alice", "email": "alice@example.com"})));
+                    } else {
+                        res.status_code(StatusCode::UNAUTHORIZED);
+                    }
+                }
+                _ => {
+                    res.status_code(StatusCode::NOT_FOUND);
+                }
+            }
+        }
+    }
+
+    #[handler]
+    async fn home(depot: &mut Depot) -> String {
+        match depot.principal() {
+            Some(principal) => principal.claim::<String>("email").unwrap_or_default(),
+            None => "guest".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oauth_login() {
+        let mock = MockProvider::default();
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        *mock.issuer.lock().unwrap() = format!("http://{addr}");
+        let mock_router = Router::with_path("<**>").goal(mock.clone());
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(mock_router).await;
+        });
+
+        let provider = Provider::builder("client", "http://127.0.0.1:5800/callback")
+            .client_secret("secret")
+            .scopes(["openid", "email"])
+            .http_client(reqwest::Client::builder().no_proxy().build().unwrap())
+            .discover(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let oauth = OAuth::new(provider);
+        let session_handler = SessionHandler::builder(
+            MemoryStore::new(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .build()
+        .unwrap();
+        let service = Service::new(
+            Router::new()
+                .hoop(session_handler)
+                .get(home)
+                .push(Router::with_path("login").get(oauth.login()))
+                .push(Router::with_path("callback").get(oauth.callback())),
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5800/login?return_to=/account")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FOUND));
+        let cookie = res.headers().get(SET_COOKIE).unwrap().clone();
+        let location = url::Url::parse(res.headers().get(LOCATION).unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(location.path(), "/authorize");
+        let query = location
+            .query_pairs()
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(query["client_id"], "client");
+        assert_eq!(query["scope"], "openid email");
+        assert_eq!(query["code_challenge_method"], "S256");
+        *mock.request.lock().unwrap() = Some((query["code_challenge"].clone(), query["nonce"].clone()));
+
+        let res = TestClient::get("http://127.0.0.1:5800/callback?code=code&state=wrong")
+            .add_header(COOKIE, &cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+
+        // The pending request is removed after a failed callback, so login again.
+        let res = TestClient::get("http://127.0.0.1:5800/login?return_to=/account")
+            .add_header(COOKIE, &cookie, true)
+            .send(&service)
+            .await;
+        let location = url::Url::parse(res.headers().get(LOCATION).unwrap().to_str().unwrap()).unwrap();
+        let query = location
+            .query_pairs()
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        *mock.request.lock().unwrap() = Some((query["code_challenge"].clone(), query["nonce"].clone()));
+
+        let res = TestClient::get(format!(
+            "http://127.0.0.1:5800/callback?code=code&state={}",
+            query["state"]
+        ))
+        .add_header(COOKIE, &cookie, true)
+        .send(&service)
+        .await;
+        assert_eq!(res.status_code, Some(StatusCode::FOUND));
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "/account");
+        let user_cookie = res.headers().get(SET_COOKIE).unwrap().clone();
+        assert_ne!(cookie, user_cookie);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "alice@example.com");
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "guest");
+    }
+}
@@ -0,0 +1,380 @@
+use std::fmt::{self, Formatter};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use url::Url;
+
+use crate::OAuthError;
+
+/// Endpoints and capabilities of an OAuth2 or OpenID Connect provider.
+///
+/// It is fetched from `{issuer}/.well-known/openid-configuration` by [`ProviderBuilder::discover`], or created
+/// manually for OAuth2 providers without discovery support.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProviderMetadata {
+    /// Issuer identifier of the provider.
+    pub issuer: String,
+    /// URL of the authorization endpoint.
+    pub authorization_endpoint: String,
+    /// URL of the token endpoint.
+    pub token_endpoint: String,
+    /// URL of the userinfo endpoint.
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    /// URL of the JSON Web Key Set used to verify id tokens.
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+}
+impl ProviderMetadata {
+    /// Create a new `ProviderMetadata`.
+    #[inline]
+    pub fn new(
+        issuer: impl Into<String>,
+        authorization_endpoint: impl Into<String>,
+        token_endpoint: impl Into<String>,
+    ) -> Self {
+        Self {
+            issuer: issuer.into(),
+            authorization_endpoint: authorization_endpoint.into(),
+            token_endpoint: token_endpoint.into(),
+            userinfo_endpoint: None,
+            jwks_uri: None,
+        }
+    }
+    /// Sets the userinfo endpoint.
+    #[inline]
+    pub fn userinfo_endpoint(mut self, userinfo_endpoint: impl Into<String>) -> Self {
+        self.userinfo_endpoint = Some(userinfo_endpoint.into());
+        self
+    }
+    /// Sets the JSON Web Key Set URL.
+    #[inline]
+    pub fn jwks_uri(mut self, jwks_uri: impl Into<String>) -> Self {
+        self.jwks_uri = Some(jwks_uri.into());
+        self
+    }
+}
+
+/// How the client authenticates itself at the token endpoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthMethod {
+    /// Sends client credentials with HTTP Basic authentication.
+    #[default]
+    ClientSecretBasic,
+    /// Sends client credentials in the request body.
+    ClientSecretPost,
+}
+
+/// A builder for [`Provider`].
+#[derive(Clone)]
+pub struct ProviderBuilder {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: String,
+    scopes: Vec<String>,
+    auth_method: AuthMethod,
+    http_client: Option<Client>,
+}
+impl fmt::Debug for ProviderBuilder {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderBuilder")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret.as_ref().map(|_| ".."))
+            .field("redirect_url", &self.redirect_url)
+            .field("scopes", &self.scopes)
+            .field("auth_method", &self.auth_method)
+            .finish()
+    }
+}
+impl ProviderBuilder {
+    /// Create a new `ProviderBuilder`.
+    ///
+    /// `redirect_url` is the absolute URL of the callback handler registered at the provider.
+    #[inline]
+    pub fn new(client_id: impl Into<String>, redirect_url: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_url: redirect_url.into(),
+            scopes: vec!["openid".into()],
+            auth_method: AuthMethod::default(),
+            http_client: None,
+        }
+    }
+    /// Sets the client secret. Public clients only use PKCE and have no secret.
+    #[inline]
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+    /// Sets the requested scopes. The default is `openid`.
+    #[inline]
+    pub fn scopes<I, S>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Sets how the client authenticates at the token endpoint. The default is [`AuthMethod::ClientSecretBasic`].
+    #[inline]
+    pub fn auth_method(mut self, auth_method: AuthMethod) -> Self {
+        self.auth_method = auth_method;
+        self
+    }
+    /// Sets the http client.
+    #[inline]
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Fetch provider metadata from `{issuer}/.well-known/openid-configuration` and build a [`Provider`].
+    pub async fn discover(self, issuer: impl AsRef<str>) -> Result<Provider, OAuthError> {
+        let issuer = issuer.as_ref().trim_end_matches('/');
+        let http_client = self.http_client.clone().unwrap_or_else(default_client);
+        let metadata: ProviderMetadata = http_client
+            .get(format!("{issuer}/.well-known/openid-configuration"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(OAuthError::IssuerMismatch);
+        }
+        Ok(self.http_client(http_client).build(metadata))
+    }
+
+    /// Build a [`Provider`] with `metadata`.
+    pub fn build(self, metadata: ProviderMetadata) -> Provider {
+        let Self {
+            client_id,
+            client_secret,
+            redirect_url,
+            scopes,
+            auth_method,
+            http_client,
+        } = self;
+        Provider {
+            metadata,
+            client_id,
+            client_secret,
+            redirect_url,
+            scopes,
+            auth_method,
+            http_client: http_client.unwrap_or_else(default_client),
+            jwks: RwLock::new(None),
+        }
+    }
+}
+
+fn default_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .expect("http client should be built")
+}
+
+/// Response of the token endpoint.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct TokenResponse {
+    /// The access token.
+    pub access_token: String,
+    /// Type of the access token, usually `Bearer`.
+    #[serde(default)]
+    pub token_type: Option<String>,
+    /// Lifetime of the access token in seconds.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    /// The refresh token.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// The OpenID Connect id token.
+    #[serde(default)]
+    pub id_token: Option<String>,
+    /// Granted scopes.
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// An OAuth2 or OpenID Connect provider with client registration.
+pub struct Provider {
+    metadata: ProviderMetadata,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: String,
+    scopes: Vec<String>,
+    auth_method: AuthMethod,
+    http_client: Client,
+    jwks: RwLock<Option<JwkSet>>,
+}
+impl fmt::Debug for Provider {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Provider")
+            .field("metadata", &self.metadata)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret.as_ref().map(|_| ".."))
+            .field("redirect_url", &self.redirect_url)
+            .field("scopes", &self.scopes)
+            .field("auth_method", &self.auth_method)
+            .finish()
+    }
+}
+impl Provider {
+    /// Create a new [`ProviderBuilder`].
+    #[inline]
+    pub fn builder(client_id: impl Into<String>, redirect_url: impl Into<String>) -> ProviderBuilder {
+        ProviderBuilder::new(client_id, redirect_url)
+    }
+    /// Get the provider metadata.
+    #[inline]
+    pub fn metadata(&self) -> &ProviderMetadata {
+        &self.metadata
+    }
+    /// Get the client id.
+    #[inline]
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Build the URL users are redirected to for authorization.
+    pub fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<Url, OAuthError> {
+        let mut url = Url::parse(&self.metadata.authorization_endpoint)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+        Ok(url)
+    }
+
+    /// Exchange the authorization code for tokens.
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<TokenResponse, OAuthError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.redirect_url),
+            ("code_verifier", code_verifier),
+        ];
+        let mut request = self.http_client.post(&self.metadata.token_endpoint);
+        match (&self.client_secret, self.auth_method) {
+            (Some(secret), AuthMethod::ClientSecretBasic) => {
+                request = request.basic_auth(&self.client_id, Some(secret));
+            }
+            (Some(secret), AuthMethod::ClientSecretPost) => {
+                form.push(("client_id", &self.client_id));
+                form.push(("client_secret", secret));
+            }
+            (None, _) => form.push(("client_id", &self.client_id)),
+        }
+        let response = request.header("accept", "application/json").form(&form).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(match response.json::<ErrorResponse>().await {
+                Ok(e) => OAuthError::Provider {
+                    error: e.error,
+                    description: e.error_description,
+                },
+                Err(_) => OAuthError::Provider {
+                    error: status.to_string(),
+                    description: None,
+                },
+            });
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Fetch claims from the userinfo endpoint, returns `None` if the provider has no userinfo endpoint.
+    pub async fn userinfo(&self, access_token: &str) -> Result<Option<Map<String, Value>>, OAuthError> {
+        let Some(endpoint) = &self.metadata.userinfo_endpoint else {
+            return Ok(None);
+        };
+        let claims = self
+            .http_client
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .header("accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(Some(claims))
+    }
+
+    /// Verify the id token and returns its claims.
+    ///
+    /// The signature, issuer, audience, expiry and `nonce` are checked. Tokens signed with HMAC algorithms are
+    /// verified with the client secret, others are verified with keys from `jwks_uri`.
+    pub async fn verify_id_token(&self, id_token: &str, nonce: &str) -> Result<Map<String, Value>, OAuthError> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = self.client_secret.as_ref().ok_or(OAuthError::MissingKey)?;
+                DecodingKey::from_secret(secret.as_bytes())
+            }
+            _ => self.decoding_key(header.kid.as_deref()).await?,
+        };
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.metadata.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        let claims = jsonwebtoken::decode::<Map<String, Value>>(id_token, &key, &validation)?.claims;
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err(OAuthError::NonceMismatch);
+        }
+        Ok(claims)
+    }
+
+    async fn decoding_key(&self, kid: Option<&str>) -> Result<DecodingKey, OAuthError> {
+        if let Some(key) = self.find_key(kid)? {
+            return Ok(key);
+        }
+        // Keys may be rotated by the provider, so fetch them again when the key is not found.
+        let jwks_uri = self.metadata.jwks_uri.as_ref().ok_or(OAuthError::MissingKey)?;
+        let jwks: JwkSet = self
+            .http_client
+            .get(jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        *self.jwks.write().expect("lock should not be poisoned") = Some(jwks);
+        self.find_key(kid)?.ok_or(OAuthError::MissingKey)
+    }
+
+    fn find_key(&self, kid: Option<&str>) -> Result<Option<DecodingKey>, OAuthError> {
+        let jwks = self.jwks.read().expect("lock should not be poisoned");
+        let Some(jwks) = jwks.as_ref() else {
+            return Ok(None);
+        };
+        let jwk = match kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        };
+        Ok(jwk.map(DecodingKey::from_jwk).transpose()?)
+    }
+}
@@ -15,3 +15,9 @@ mod tracing;
 
 pub use metrics::Metrics;
 pub use tracing::Tracing;
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "prometheus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+pub use crate::prometheus::PrometheusExporter;
@@ -1,16 +1,27 @@
 use std::time::Instant;
 
 use opentelemetry::global;
-use opentelemetry::metrics::{Counter, Histogram, Unit};
+use opentelemetry::metrics::{Counter, Histogram, Meter, Unit, UpDownCounter};
+use opentelemetry::KeyValue;
 use opentelemetry_semantic_conventions::trace;
-use salvo_core::http::ResBody;
+use salvo_core::http::body::BodyStats;
 use salvo_core::prelude::*;
 
 /// Middleware for metrics with OpenTelemetry.
+///
+/// Metrics are labelled by request method, route path template, such as `/users/<id>`, and response status.
+/// They can be exported by any exporter of OpenTelemetry, such as OTLP, or
+/// [`PrometheusExporter`](crate::PrometheusExporter) with `prometheus` feature.
+///
+/// Bytes of request and response bodies are counted when [`Service::body_stats`] is enabled, the response bytes
+/// are the final size written to the connection, such as the size after compression.
 pub struct Metrics {
     request_count: Counter<u64>,
     error_count: Counter<u64>,
     duration: Histogram<f64>,
+    in_flight: UpDownCounter<i64>,
+    request_body_size: Counter<u64>,
+    response_body_size: Counter<u64>,
 }
 
 impl Default for Metrics {
@@ -20,9 +31,13 @@ impl Default for Metrics {
 }
 
 impl Metrics {
-    /// Create `Metrics` middleware with `meter`.
+    /// Create `Metrics` middleware with global meter.
     pub fn new() -> Self {
-        let meter = global::meter("salvo");
+        Self::with_meter(&global::meter("salvo"))
+    }
+
+    /// Create `Metrics` middleware with `meter`.
+    pub fn with_meter(meter: &Meter) -> Self {
         Self {
             request_count: meter
                 .u64_counter("salvo_request_count")
@@ -37,6 +52,20 @@ impl Metrics {
                 .with_unit(Unit::new("milliseconds"))
                 .with_description("request duration histogram (in milliseconds, since start of service)")
                 .init(),
+            in_flight: meter
+                .i64_up_down_counter("salvo_requests_in_flight")
+                .with_description("number of requests being processed")
+                .init(),
+            request_body_size: meter
+                .u64_counter("salvo_request_body_bytes")
+                .with_unit(Unit::new("bytes"))
+                .with_description("total bytes of request bodies read (since start of service)")
+                .init(),
+            response_body_size: meter
+                .u64_counter("salvo_response_body_bytes")
+                .with_unit(Unit::new("bytes"))
+                .with_description("total bytes of response bodies written (since start of service)")
+                .init(),
         }
     }
 }
@@ -44,27 +73,37 @@ impl Metrics {
 #[async_trait]
 impl Handler for Metrics {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        let mut labels = Vec::with_capacity(3);
+        let mut labels = Vec::with_capacity(4);
         labels.push(trace::HTTP_REQUEST_METHOD.string(req.method().to_string()));
-        labels.push(trace::URL_FULL.string(req.uri().to_string()));
+        labels.push(trace::HTTP_ROUTE.string(req.matched_path().unwrap_or_default().to_owned()));
 
+        self.in_flight.add(1, &labels);
         let s = Instant::now();
         ctrl.call_next(req, depot, res).await;
         let elapsed = s.elapsed();
+        self.in_flight.add(-1, &labels);
 
         let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
         labels.push(trace::HTTP_RESPONSE_STATUS_CODE.i64(status.as_u16() as i64));
         if status.is_client_error() || status.is_server_error() {
-            self.error_count.add(1, &labels);
-            let msg = if let ResBody::Error(body) = &res.body {
-                body.to_string()
-            } else {
-                format!("ErrorCode: {}", status.as_u16())
-            };
-            labels.push(trace::EXCEPTION_MESSAGE.string(msg));
+            let mut error_labels: Vec<KeyValue> = labels.clone();
+            // The status class keeps the cardinality bounded, error messages may contain arbitrary values.
+            let error_type = if status.is_client_error() { "4xx" } else { "5xx" };
+            error_labels.push(KeyValue::new("error.type", error_type));
+            self.error_count.add(1, &error_labels);
         }
 
         self.request_count.add(1, &labels);
         self.duration.record(elapsed.as_secs_f64() * 1000.0, &labels);
+
+        if let Ok(stats) = depot.obtain::<BodyStats>() {
+            let request_body_size = self.request_body_size.clone();
+            let response_body_size = self.response_body_size.clone();
+            // The response body is written after all handlers, so bytes are recorded when it is finished.
+            stats.on_finish(move |stats| {
+                request_body_size.add(stats.bytes_read(), &labels);
+                response_body_size.add(stats.bytes_written(), &labels);
+            });
+        }
     }
 }
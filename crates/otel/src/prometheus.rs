@@ -0,0 +1,114 @@
+use prometheus::{Encoder, Registry, TextEncoder};
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::prelude::*;
+
+/// Handler renders metrics of a prometheus [`Registry`] in Prometheus text exposition format.
+///
+/// Register the registry to an OpenTelemetry meter provider with `opentelemetry-prometheus` exporter,
+/// then metrics recorded by [`Metrics`](crate::Metrics) are rendered by this handler.
+///
+/// # Example
+///
+/// ```ignore
+/// let registry = prometheus::Registry::new();
+/// let exporter = opentelemetry_prometheus::exporter().with_registry(registry.clone()).build()?;
+/// let provider = opentelemetry_sdk::metrics::MeterProvider::builder().with_reader(exporter).build();
+///
+/// let router = Router::new()
+///     .hoop(Metrics::with_meter(&provider.meter("salvo")))
+///     .push(Router::with_path("metrics").get(PrometheusExporter::new(registry)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrometheusExporter {
+    registry: Registry,
+}
+
+impl PrometheusExporter {
+    /// Create `PrometheusExporter` with registry.
+    pub fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+
+    /// Get the registry.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+}
+
+#[async_trait]
+impl Handler for PrometheusExporter {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let encoder = TextEncoder::new();
+        let mut body = Vec::new();
+        match encoder.encode(&self.registry.gather(), &mut body) {
+            Ok(()) => {
+                if let Ok(content_type) = HeaderValue::from_str(encoder.format_type()) {
+                    res.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+                res.body(body.into());
+            }
+            Err(e) => {
+                res.render(StatusError::internal_server_error().cause(e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::metrics::MeterProvider;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::Metrics;
+
+    #[tokio::test]
+    async fn test_prometheus_exporter() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let registry = Registry::new();
+        let exporter = opentelemetry_prometheus::exporter()
+            .with_registry(registry.clone())
+            .build()
+            .unwrap();
+        let provider = MeterProvider::builder().with_reader(exporter).build();
+
+        let router = Router::new()
+            .push(Router::with_path("metrics").get(PrometheusExporter::new(registry)))
+            .push(
+                Router::new()
+                    .hoop(Metrics::with_meter(&provider.meter("salvo")))
+                    .push(Router::with_path("users/<id>").get(hello)),
+            );
+        let service = Service::new(router).body_stats(true);
+        for id in 0..3 {
+            TestClient::get(format!("http://127.0.0.1:5801/users/{id}"))
+                .send(&service)
+                .await
+                .take_string()
+                .await
+                .unwrap();
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/metrics").send(&service).await;
+        assert!(res
+            .headers()
+            .get(CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let content = res.take_string().await.unwrap();
+        assert!(content.contains("salvo_request_count"));
+        assert!(content.contains(r#"http_route="/users/<id>""#));
+        assert!(content.contains("salvo_requests_in_flight"));
+        let written = content
+            .lines()
+            .find(|line| line.starts_with("salvo_response_body_bytes"))
+            .unwrap();
+        assert!(written.ends_with(" 15"), "{written}");
+    }
+}
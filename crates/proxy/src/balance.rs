@@ -0,0 +1,401 @@
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use salvo_core::Error;
+use tokio::task::JoinHandle;
+
+use super::{UpstreamGuard, Upstreams};
+
+/// Strategy used by [`BalancedUpstreams`] to elect upstream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Strategy {
+    /// Elect upstreams one by one.
+    #[default]
+    RoundRobin,
+    /// Elect the upstream with the least number of active requests.
+    LeastConnections,
+    /// Elect upstreams by their weights, using smooth weighted round robin.
+    Weighted,
+}
+
+/// Upstream with its weight, used by [`BalancedUpstreams`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Upstream {
+    /// Upstream url.
+    pub url: String,
+    /// Upstream weight, only used by [`Strategy::Weighted`]. The default is 1.
+    pub weight: usize,
+}
+impl Upstream {
+    /// Create new `Upstream`.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            weight: 1,
+        }
+    }
+    /// Sets weight and returns a new `Upstream`.
+    #[inline]
+    pub fn weight(mut self, weight: usize) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+impl From<&str> for Upstream {
+    #[inline]
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+impl From<String> for Upstream {
+    #[inline]
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+/// Active health check probe config.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct HealthCheck {
+    /// Path requested on every upstream, such as `/healthz`.
+    pub path: String,
+    /// Interval between probes. The default is 10 seconds.
+    pub interval: Duration,
+    /// Probe request timeout. The default is 3 seconds.
+    pub timeout: Duration,
+}
+impl HealthCheck {
+    /// Create new `HealthCheck` with path.
+    #[inline]
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(3),
+        }
+    }
+    /// Sets interval and returns a new `HealthCheck`.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    /// Sets timeout and returns a new `HealthCheck`.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct PeerState {
+    active: usize,
+    fails: usize,
+    down_until: Option<Instant>,
+    unhealthy: bool,
+    current_weight: isize,
+}
+impl PeerState {
+    #[inline]
+    fn is_available(&self, now: Instant) -> bool {
+        !self.unhealthy && self.down_until.map(|until| until <= now).unwrap_or(true)
+    }
+}
+
+/// Counts an active request to upstream until it is dropped.
+struct ActiveGuard {
+    inner: Arc<Inner>,
+    index: usize,
+}
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        let mut states = self.inner.states.lock().unwrap();
+        let state = &mut states[self.index];
+        state.active = state.active.saturating_sub(1);
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    upstreams: Vec<Upstream>,
+    states: Mutex<Vec<PeerState>>,
+    cursor: Mutex<usize>,
+}
+
+/// Upstreams with load balancing, passive failure detection and active health checks.
+///
+/// Upstreams failed `max_fails` times in a row are not elected until `fail_timeout` is elapsed.
+/// Connections to upstreams are pooled per upstream host by the [`Client`] of [`Proxy`](super::Proxy).
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use salvo_proxy::{BalancedUpstreams, Proxy, Strategy, Upstream};
+///
+/// let upstreams = BalancedUpstreams::new([Upstream::new("http://10.0.0.1").weight(3), Upstream::new("http://10.0.0.2")])
+///     .strategy(Strategy::Weighted)
+///     .max_fails(3)
+///     .fail_timeout(Duration::from_secs(30));
+/// let proxy = Proxy::new(upstreams);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BalancedUpstreams {
+    inner: Arc<Inner>,
+    strategy: Strategy,
+    max_fails: usize,
+    fail_timeout: Duration,
+}
+impl BalancedUpstreams {
+    /// Create new `BalancedUpstreams`.
+    pub fn new<I>(upstreams: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Upstream>,
+    {
+        let upstreams: Vec<Upstream> = upstreams.into_iter().map(Into::into).collect();
+        let states = upstreams.iter().map(|_| PeerState::default()).collect();
+        Self {
+            inner: Arc::new(Inner {
+                upstreams,
+                states: Mutex::new(states),
+                cursor: Mutex::new(0),
+            }),
+            strategy: Strategy::RoundRobin,
+            max_fails: 1,
+            fail_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Sets strategy and returns a new `BalancedUpstreams`.
+    #[inline]
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the number of consecutive failures to mark an upstream as down, `0` disables
+    /// passive failure detection. The default is 1.
+    #[inline]
+    pub fn max_fails(mut self, max_fails: usize) -> Self {
+        self.max_fails = max_fails;
+        self
+    }
+
+    /// Sets how long an upstream is marked as down after failures. The default is 10 seconds.
+    #[inline]
+    pub fn fail_timeout(mut self, fail_timeout: Duration) -> Self {
+        self.fail_timeout = fail_timeout;
+        self
+    }
+
+    /// Get upstreams list.
+    #[inline]
+    pub fn upstreams(&self) -> &[Upstream] {
+        &self.inner.upstreams
+    }
+
+    /// Returns `true` if the upstream can be elected now.
+    pub fn is_available(&self, url: &str) -> bool {
+        let now = Instant::now();
+        let states = self.inner.states.lock().unwrap();
+        self.position(url)
+            .map(|index| states[index].is_available(now))
+            .unwrap_or(false)
+    }
+
+    /// Spawn a task probing all upstreams periodically, upstreams do not respond success status
+    /// are not elected until they recover.
+    ///
+    /// The task stops when all clones of this `BalancedUpstreams` are dropped.
+    /// This function must be called from the context of a tokio runtime.
+    pub fn spawn_health_check(&self, check: HealthCheck) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            let client = match Client::builder().timeout(check.timeout).build() {
+                Ok(client) => client,
+                Err(e) => {
+                    tracing::error!(error = ?e, "build health check client failed");
+                    return;
+                }
+            };
+            while probe_all(&inner, &client, &check.path).await {
+                tokio::time::sleep(check.interval).await;
+            }
+        })
+    }
+
+    #[inline]
+    fn position(&self, url: &str) -> Option<usize> {
+        self.inner.upstreams.iter().position(|upstream| upstream.url == url)
+    }
+}
+
+/// Probe all upstreams once, returns `false` if upstreams are dropped.
+async fn probe_all(inner: &Weak<Inner>, client: &Client, path: &str) -> bool {
+    let urls: Vec<String> = match inner.upgrade() {
+        Some(inner) => inner.upstreams.iter().map(|upstream| upstream.url.clone()).collect(),
+        None => return false,
+    };
+    let mut results = Vec::with_capacity(urls.len());
+    for url in urls {
+        let probe_url = format!("{}/{}", url.trim_end_matches('/'), path.trim_start_matches('/'));
+        let healthy = match client.get(&probe_url).send().await {
+            Ok(res) => res.status().is_success() || res.status().is_redirection(),
+            Err(_) => false,
+        };
+        if !healthy {
+            tracing::warn!(upstream = %url, "upstream health check failed");
+        }
+        results.push(healthy);
+    }
+    match inner.upgrade() {
+        Some(inner) => {
+            let mut states = inner.states.lock().unwrap();
+            for (state, healthy) in states.iter_mut().zip(results) {
+                state.unhealthy = !healthy;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+impl Upstreams for BalancedUpstreams {
+    type Error = Error;
+
+    fn elect(&self) -> Result<&str, Self::Error> {
+        let now = Instant::now();
+        let mut states = self.inner.states.lock().unwrap();
+        let available: Vec<usize> = (0..states.len()).filter(|i| states[*i].is_available(now)).collect();
+        if available.is_empty() {
+            return Err(Error::other("no available upstream"));
+        }
+        let index = match self.strategy {
+            Strategy::RoundRobin => {
+                let mut cursor = self.inner.cursor.lock().unwrap();
+                let index = available[*cursor % available.len()];
+                *cursor = cursor.wrapping_add(1);
+                index
+            }
+            Strategy::LeastConnections => {
+                let mut cursor = self.inner.cursor.lock().unwrap();
+                let min = available.iter().map(|i| states[*i].active).min().unwrap_or_default();
+                let least: Vec<usize> = available.into_iter().filter(|i| states[*i].active == min).collect();
+                let index = least[*cursor % least.len()];
+                *cursor = cursor.wrapping_add(1);
+                index
+            }
+            Strategy::Weighted => {
+                let mut total = 0;
+                let mut best: Option<usize> = None;
+                for index in available {
+                    let weight = self.inner.upstreams[index].weight as isize;
+                    total += weight;
+                    states[index].current_weight += weight;
+                    if best
+                        .map(|best| states[index].current_weight > states[best].current_weight)
+                        .unwrap_or(true)
+                    {
+                        best = Some(index);
+                    }
+                }
+                let best = best.unwrap_or_default();
+                states[best].current_weight -= total;
+                best
+            }
+        };
+        Ok(&self.inner.upstreams[index].url)
+    }
+
+    fn on_start(&self, upstream: &str) -> Option<UpstreamGuard> {
+        let index = self.position(upstream)?;
+        self.inner.states.lock().unwrap()[index].active += 1;
+        Some(Box::new(ActiveGuard {
+            inner: self.inner.clone(),
+            index,
+        }))
+    }
+
+    fn on_end(&self, upstream: &str, success: bool) {
+        if let Some(index) = self.position(upstream) {
+            let mut states = self.inner.states.lock().unwrap();
+            let state = &mut states[index];
+            if success {
+                state.fails = 0;
+            } else {
+                state.fails += 1;
+                if self.max_fails > 0 && state.fails >= self.max_fails {
+                    tracing::warn!(upstream = %upstream, fails = state.fails, "upstream is marked as down");
+                    state.fails = 0;
+                    state.down_until = Some(Instant::now() + self.fail_timeout);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let upstreams = BalancedUpstreams::new(["http://a", "http://b", "http://c"]);
+        let elected: Vec<_> = (0..6).map(|_| upstreams.elect().unwrap().to_owned()).collect();
+        assert_eq!(
+            elected,
+            ["http://a", "http://b", "http://c", "http://a", "http://b", "http://c"]
+        );
+    }
+
+    #[test]
+    fn test_weighted() {
+        let upstreams = BalancedUpstreams::new([Upstream::new("http://a").weight(5), Upstream::new("http://b")])
+            .strategy(Strategy::Weighted);
+        let elected: Vec<_> = (0..6).map(|_| upstreams.elect().unwrap().to_owned()).collect();
+        assert_eq!(elected.iter().filter(|url| *url == "http://a").count(), 5);
+        assert_eq!(elected.iter().filter(|url| *url == "http://b").count(), 1);
+    }
+
+    #[test]
+    fn test_least_connections() {
+        let upstreams = BalancedUpstreams::new(["http://a", "http://b"]).strategy(Strategy::LeastConnections);
+        let guard = upstreams.on_start("http://a");
+        assert_eq!(upstreams.elect().unwrap(), "http://b");
+        assert_eq!(upstreams.elect().unwrap(), "http://b");
+        // the request is still active until the response body is finished.
+        upstreams.on_end("http://a", true);
+        assert_eq!(upstreams.elect().unwrap(), "http://b");
+        drop(guard);
+        let _guard = upstreams.on_start("http://b");
+        assert_eq!(upstreams.elect().unwrap(), "http://a");
+    }
+
+    #[test]
+    fn test_passive_failure() {
+        let upstreams = BalancedUpstreams::new(["http://a", "http://b"])
+            .max_fails(2)
+            .fail_timeout(Duration::from_millis(50));
+        upstreams.on_start("http://a");
+        upstreams.on_end("http://a", false);
+        assert!(upstreams.is_available("http://a"));
+        upstreams.on_start("http://a");
+        upstreams.on_end("http://a", false);
+        assert!(!upstreams.is_available("http://a"));
+        assert!((0..4).all(|_| upstreams.elect().unwrap() == "http://b"));
+
+        upstreams.on_end("http://b", false);
+        upstreams.on_end("http://b", false);
+        assert!(upstreams.elect().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(upstreams.is_available("http://a") && upstreams.is_available("http://b"));
+    }
+}
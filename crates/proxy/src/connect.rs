@@ -0,0 +1,356 @@
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::time::Duration;
+
+use base64::engine::{general_purpose, Engine};
+use salvo_core::http::header::{HeaderValue, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION};
+use salvo_core::http::{Method, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use tokio::io::copy_bidirectional;
+use tokio::net::TcpStream;
+
+/// The target of a `CONNECT` request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TunnelTarget {
+    /// Host of the target, in lowercase.
+    pub host: String,
+    /// Port of the target.
+    pub port: u16,
+}
+impl TunnelTarget {
+    /// Get the target of a `CONNECT` request, returns `None` if the request target has no port.
+    pub fn from_request(req: &Request) -> Option<Self> {
+        let authority = req.uri().authority()?;
+        Some(Self {
+            host: authority
+                .host()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_lowercase(),
+            port: authority.port_u16().filter(|port| *port != 0)?,
+        })
+    }
+}
+
+/// A rule of the targets allowed by [`Tunnel`].
+///
+/// It is parsed from `host:port`, the host can be `*` for any host or `*.example.com` for the subdomains of
+/// `example.com`, and the port can be `*` for any port. A rule without port allows port `443` only, and a rule with
+/// an invalid port allows nothing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetRule {
+    host: String,
+    port: Option<u16>,
+}
+impl TargetRule {
+    /// Parse a rule from `host:port`.
+    pub fn new(rule: &str) -> Self {
+        let (host, port) = match rule.rsplit_once(':') {
+            // The colon is in an IPv6 address such as `[::1]`.
+            Some((_, port)) if port.ends_with(']') => (rule, Some(443)),
+            Some((host, "*")) => (host, None),
+            // Port `0` is never a valid target, so a rule with invalid port allows nothing.
+            Some((host, port)) => (host, Some(port.parse().unwrap_or(0))),
+            None => (rule, Some(443)),
+        };
+        Self {
+            host: host.trim_start_matches('[').trim_end_matches(']').to_lowercase(),
+            port,
+        }
+    }
+    /// Check if the rule allows the target.
+    pub fn allows(&self, target: &TunnelTarget) -> bool {
+        if self.port.map(|port| port != target.port).unwrap_or(false) {
+            return false;
+        }
+        if self.host == "*" {
+            true
+        } else if let Some(suffix) = self.host.strip_prefix("*.") {
+            target
+                .host
+                .strip_suffix(suffix)
+                .map(|sub| sub.ends_with('.') && sub.len() > 1)
+                .unwrap_or(false)
+        } else {
+            self.host == target.host
+        }
+    }
+}
+impl From<&str> for TargetRule {
+    #[inline]
+    fn from(rule: &str) -> Self {
+        Self::new(rule)
+    }
+}
+
+/// Authorizer of `CONNECT` requests.
+#[async_trait]
+pub trait TunnelAuthorizer: Send + Sync + 'static {
+    /// Returns `true` if the request is allowed to connect to the target.
+    async fn authorize(&self, req: &mut Request, depot: &Depot, target: &TunnelTarget) -> bool;
+}
+#[async_trait]
+impl<F, Fut> TunnelAuthorizer for F
+where
+    F: Fn(&Request, &TunnelTarget) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send,
+{
+    async fn authorize(&self, req: &mut Request, _depot: &Depot, target: &TunnelTarget) -> bool {
+        self(req, target).await
+    }
+}
+
+/// Get the user name and the password of `Proxy-Authorization` header with `Basic` scheme.
+pub fn proxy_basic_credentials(req: &Request) -> Option<(String, String)> {
+    let value = req.headers().get(PROXY_AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, credentials) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let credentials = general_purpose::STANDARD.decode(credentials.trim()).ok()?;
+    let credentials = String::from_utf8(credentials).ok()?;
+    let (name, password) = credentials.split_once(':')?;
+    Some((name.to_owned(), password.to_owned()))
+}
+
+/// Handler which handles `CONNECT` requests, so the server works as a forward proxy for HTTPS and other TCP traffic.
+///
+/// A tunnel is established only if the target is allowed by the rules and the request is authorized, no target is
+/// allowed by default. Requests with other methods are responded with `405 Method Not Allowed`, forbidden targets with
+/// `403 Forbidden`, unauthorized requests with `407 Proxy Authentication Required`, and targets which can not be
+/// connected with `502 Bad Gateway`.
+///
+/// The tunnel needs to take over the connection, so it only works for HTTP/1 connections.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_proxy::{proxy_basic_credentials, Tunnel, TunnelTarget};
+///
+/// let tunnel = Tunnel::new()
+///     .allow("*.internal.example.com:443")
+///     .allow("api.github.com")
+///     .authorizer(|req: &Request, _target: &TunnelTarget| {
+///         let credentials = proxy_basic_credentials(req);
+///         async move { credentials == Some(("ci".into(), "secret".into())) }
+///     });
+/// let router = Router::new().goal(tunnel);
+/// ```
+pub struct Tunnel {
+    rules: Vec<TargetRule>,
+    authorizer: Option<Box<dyn TunnelAuthorizer>>,
+    realm: String,
+    connect_timeout: Duration,
+}
+impl Debug for Tunnel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tunnel")
+            .field("rules", &self.rules)
+            .field("realm", &self.realm)
+            .field("connect_timeout", &self.connect_timeout)
+            .finish()
+    }
+}
+impl Default for Tunnel {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Tunnel {
+    /// Create a new `Tunnel` which allows no target.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            rules: vec![],
+            authorizer: None,
+            realm: "salvo".into(),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+    /// Allow the targets matching a rule, see [`TargetRule`] for the syntax.
+    #[inline]
+    pub fn allow(mut self, rule: impl Into<TargetRule>) -> Self {
+        self.rules.push(rule.into());
+        self
+    }
+    /// Sets the authorizer, requests are not checked by default.
+    #[inline]
+    pub fn authorizer(mut self, authorizer: impl TunnelAuthorizer) -> Self {
+        self.authorizer = Some(Box::new(authorizer));
+        self
+    }
+    /// Sets the realm of `Proxy-Authenticate` header of unauthorized responses, default is `salvo`.
+    #[inline]
+    pub fn realm(mut self, realm: impl Into<String>) -> Self {
+        self.realm = realm.into();
+        self
+    }
+    /// Sets the timeout of connecting to the target, default is 10 seconds.
+    #[inline]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Check if the target is allowed by the rules.
+    #[inline]
+    pub fn allows(&self, target: &TunnelTarget) -> bool {
+        self.rules.iter().any(|rule| rule.allows(target))
+    }
+}
+
+#[async_trait]
+impl Handler for Tunnel {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.skip_rest();
+        if req.method() != Method::CONNECT {
+            res.render(StatusError::method_not_allowed());
+            return;
+        }
+        let Some(target) = TunnelTarget::from_request(req) else {
+            res.render(StatusError::bad_request().brief("The target of CONNECT request should be `host:port`."));
+            return;
+        };
+        if !self.allows(&target) {
+            tracing::debug!(host = %target.host, port = target.port, "tunnel target is forbidden");
+            res.render(StatusError::forbidden());
+            return;
+        }
+        if let Some(authorizer) = &self.authorizer {
+            if !authorizer.authorize(req, depot, &target).await {
+                if let Ok(value) = HeaderValue::from_str(&format!("Basic realm=\"{}\"", self.realm)) {
+                    res.headers_mut().insert(PROXY_AUTHENTICATE, value);
+                }
+                res.render(StatusError::proxy_authentication_required());
+                return;
+            }
+        }
+        let take_over = match req.take_over() {
+            Ok(take_over) => take_over,
+            Err(e) => {
+                res.render(e);
+                return;
+            }
+        };
+        let connect = TcpStream::connect((target.host.as_str(), target.port));
+        let mut stream = match tokio::time::timeout(self.connect_timeout, connect).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                tracing::debug!(error = ?e, host = %target.host, port = target.port, "connect tunnel target failed");
+                res.render(StatusError::bad_gateway());
+                return;
+            }
+            Err(_) => {
+                res.render(StatusError::gateway_timeout());
+                return;
+            }
+        };
+        res.status_code(StatusCode::OK);
+        take_over.spawn(move |mut upgraded| async move {
+            if let Err(e) = copy_bidirectional(&mut upgraded, &mut stream).await {
+                tracing::debug!(error = ?e, "tunnel closed with error");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::server::conn::http1;
+    use salvo_core::http::uri::Scheme;
+    use salvo_core::prelude::*;
+    use salvo_core::rt::tokio::TokioIo;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn connect(service: &Service) -> DuplexStream {
+        let handler = service.hyper_handler(
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5801)).into(),
+            std::net::SocketAddr::from(([127, 0, 0, 1], 5802)).into(),
+            Scheme::HTTP,
+            None,
+        );
+        let (client, server) = tokio::io::duplex(1024);
+        tokio::spawn(
+            http1::Builder::new()
+                .serve_connection(TokioIo::new(server), handler)
+                .with_upgrades(),
+        );
+        client
+    }
+
+    async fn read_head(client: &mut DuplexStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            client.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    #[test]
+    fn test_target_rule() {
+        let target = |host: &str, port| TunnelTarget {
+            host: host.into(),
+            port,
+        };
+        assert!(TargetRule::new("example.com").allows(&target("example.com", 443)));
+        assert!(!TargetRule::new("example.com").allows(&target("example.com", 80)));
+        assert!(TargetRule::new("*.example.com:*").allows(&target("api.example.com", 8443)));
+        assert!(!TargetRule::new("*.example.com:*").allows(&target("example.com", 443)));
+        assert!(!TargetRule::new("*.example.com:*").allows(&target("badexample.com", 443)));
+        assert!(TargetRule::new("[::1]:22").allows(&target("::1", 22)));
+        assert!(!TargetRule::new("example.com:bad").allows(&target("example.com", 443)));
+    }
+
+    #[tokio::test]
+    async fn test_tunnel() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.ok();
+        });
+        let tunnel =
+            Tunnel::new()
+                .allow(format!("127.0.0.1:{port}").as_str())
+                .authorizer(|req: &Request, _: &TunnelTarget| {
+                    let credentials = proxy_basic_credentials(req);
+                    async move { credentials == Some(("ci".into(), "secret".into())) }
+                });
+        let service = Service::new(Router::new().goal(tunnel));
+
+        let mut client = connect(&service);
+        client
+            .write_all(b"CONNECT 127.0.0.1:22 HTTP/1.1\r\nhost: 127.0.0.1:22\r\n\r\n")
+            .await
+            .unwrap();
+        assert!(read_head(&mut client).await.starts_with("HTTP/1.1 403"));
+
+        let mut client = connect(&service);
+        let request = format!("CONNECT 127.0.0.1:{port} HTTP/1.1\r\nhost: 127.0.0.1:{port}\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+        let head = read_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 407"));
+        assert!(head.contains("proxy-authenticate: Basic realm=\"salvo\"\r\n"));
+
+        let mut client = connect(&service);
+        // `ci:secret` in base64.
+        let request = format!(
+            "CONNECT 127.0.0.1:{port} HTTP/1.1\r\nhost: 127.0.0.1:{port}\r\n\
+             proxy-authorization: Basic Y2k6c2VjcmV0\r\n\r\n"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        assert!(read_head(&mut client).await.starts_with("HTTP/1.1 200"));
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    }
+}
@@ -10,15 +10,21 @@
 #![warn(clippy::future_not_send)]
 #![warn(rustdoc::broken_intra_doc_links)]
 
+use std::any::Any;
 use std::convert::{Infallible, TryFrom};
+use std::time::Duration;
 
 use futures_util::TryStreamExt;
+use hyper::body::{Body, Bytes};
 use hyper::upgrade::OnUpgrade;
 use percent_encoding::{utf8_percent_encode, CONTROLS};
 use reqwest::Client;
-use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, HOST, UPGRADE};
+use salvo_core::http::header::{
+    HeaderMap, HeaderName, HeaderValue, CONNECTION, HOST, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION, TE, TRAILER,
+    TRANSFER_ENCODING, UPGRADE,
+};
 use salvo_core::http::uri::Uri;
-use salvo_core::http::{ReqBody, ResBody, StatusCode};
+use salvo_core::http::{DeadlineHeader, ReqBody, ResBody, StatusCode};
 use salvo_core::rt::tokio::TokioIo;
 use salvo_core::{async_trait, BoxedError, Depot, Error, FlowCtrl, Handler, Request, Response};
 use tokio::io::copy_bidirectional;
@@ -35,12 +41,36 @@ pub(crate) fn encode_url_path(path: &str) -> String {
         .join("/")
 }
 
+mod balance;
+mod connect;
+mod mirror;
+mod policy;
+pub use balance::{BalancedUpstreams, HealthCheck, Strategy, Upstream};
+pub use connect::{proxy_basic_credentials, TargetRule, Tunnel, TunnelAuthorizer, TunnelTarget};
+pub use mirror::Mirror;
+pub use policy::{CircuitBreaker, RetryPolicy};
+
+/// Guard returned by [`Upstreams::on_start`].
+pub type UpstreamGuard = Box<dyn Any + Send + Sync>;
+
 /// Upstreams trait.
 pub trait Upstreams: Send + Sync + 'static {
     /// Error type.
     type Error;
     /// Elect a upstream to process current request.
     fn elect(&self) -> Result<&str, Self::Error>;
+    /// Called before the request is sent to the elected upstream.
+    ///
+    /// The returned guard is dropped when the response body is finished or dropped, or the request is failed.
+    #[inline]
+    fn on_start(&self, _upstream: &str) -> Option<UpstreamGuard> {
+        None
+    }
+    /// Called after the response head is received from the elected upstream, or the request is failed.
+    ///
+    /// `success` is `false` if the upstream can not be connected or responds with `502`, `503` or `504`.
+    #[inline]
+    fn on_end(&self, _upstream: &str, _success: bool) {}
 }
 impl Upstreams for &'static str {
     type Error = Infallible;
@@ -99,8 +129,10 @@ pub fn default_url_query_getter(req: &Request, _depot: &Depot) -> Option<String>
 }
 
 /// Handler that can proxy request to other server.
+///
+/// Request and response bodies are streamed without buffering, and protocol upgrades, such as
+/// WebSocket, are proxied by copying data between the client and upstream connections.
 #[non_exhaustive]
-
 pub struct Proxy<U> {
     /// Upstreams list.
     pub upstreams: U,
@@ -110,6 +142,14 @@ pub struct Proxy<U> {
     pub url_path_getter: UrlPartGetter,
     /// Url query getter.
     pub url_query_getter: UrlPartGetter,
+    /// Timeout of waiting for the response head from upstream, `504` is responded when it is elapsed.
+    pub timeout: Option<Duration>,
+    /// Retry policy.
+    pub retry: Option<RetryPolicy>,
+    /// Circuit breaker.
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Header used to forward the remaining time until [`Request::deadline`] to upstreams.
+    pub deadline_header: Option<DeadlineHeader>,
 }
 
 impl<U> Proxy<U>
@@ -124,6 +164,10 @@ where
             client: Client::new(),
             url_path_getter: Box::new(default_url_path_getter),
             url_query_getter: Box::new(default_url_query_getter),
+            timeout: None,
+            retry: None,
+            circuit_breaker: None,
+            deadline_header: None,
         }
     }
     /// Create new `Proxy` with upstreams list and [`Client`].
@@ -133,6 +177,10 @@ where
             client,
             url_path_getter: Box::new(default_url_path_getter),
             url_query_getter: Box::new(default_url_query_getter),
+            timeout: None,
+            retry: None,
+            circuit_breaker: None,
+            deadline_header: None,
         }
     }
 
@@ -156,6 +204,38 @@ where
         self
     }
 
+    /// Set timeout of waiting for the response head from upstream.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set retry policy.
+    #[inline]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set circuit breaker.
+    #[inline]
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Set the header used to forward the request deadline to upstreams.
+    ///
+    /// When the request has a deadline, usually set by the `Timeout` middleware, its remaining time is sent to
+    /// upstreams in this header, and waiting for upstreams is bounded by it. Requests whose deadline has passed
+    /// are responded with `504` without calling upstreams.
+    #[inline]
+    pub fn deadline_header(mut self, header: DeadlineHeader) -> Self {
+        self.deadline_header = Some(header);
+        self
+    }
+
     /// Get upstreams list.
     #[inline]
     pub fn upstreams(&self) -> &U {
@@ -179,13 +259,13 @@ where
     }
 
     #[inline]
-    fn build_proxied_request(&self, req: &mut Request, depot: &Depot) -> Result<HyperRequest, Error> {
-        let upstream = self.upstreams.elect().map_err(Error::other)?;
-        if upstream.is_empty() {
-            tracing::error!("upstreams is empty");
-            return Err(Error::other("upstreams is empty"));
-        }
-
+    fn build_proxied_request(
+        &self,
+        req: &mut Request,
+        depot: &Depot,
+        upstream: &str,
+        body: ReqBody,
+    ) -> Result<HyperRequest, Error> {
         let path = encode_url_path(&(self.url_path_getter)(req, depot).unwrap_or_default());
         let query = (self.url_query_getter)(req, depot);
         let rest = if let Some(query) = query {
@@ -205,36 +285,27 @@ where
             format!("{}/{}", upstream, rest)
         };
         let forward_url: Uri = TryFrom::try_from(forward_url).map_err(Error::other)?;
-        let mut build = hyper::Request::builder().method(req.method()).uri(&forward_url);
-        for (key, value) in req.headers() {
-            if key != HOST {
-                build = build.header(key, value);
-            } else {
-                build = build.header(HOST, forward_url.host().unwrap());
-            }
+        let mut headers = req.headers().clone();
+        let upgrade_type = get_upgrade_type(&headers).map(|s| s.to_owned());
+        strip_hop_by_hop_headers(&mut headers);
+        if let Some(upgrade_type) = upgrade_type.and_then(|s| HeaderValue::from_str(&s).ok()) {
+            headers.insert(CONNECTION, HeaderValue::from_static("upgrade"));
+            headers.insert(UPGRADE, upgrade_type);
         }
-        if let Some(host) = forward_url.host().and_then(|host| HeaderValue::from_str(host).ok()) {
-            build = build.header(HeaderName::from_static("host"), host);
+        if let Some(host) = forward_url
+            .authority()
+            .and_then(|authority| HeaderValue::from_str(authority.as_str()).ok())
+        {
+            headers.insert(HOST, host);
+        }
+        if let (Some(header), Some(deadline)) = (&self.deadline_header, req.deadline()) {
+            header.insert(&mut headers, deadline);
+        }
+        let mut build = hyper::Request::builder().method(req.method()).uri(&forward_url);
+        if let Some(build_headers) = build.headers_mut() {
+            *build_headers = headers;
         }
-        // let x_forwarded_for_header_name = "x-forwarded-for";
-        // // Add forwarding information in the headers
-        // match request.headers_mut().entry(x_forwarded_for_header_name) {
-        //     Ok(header_entry) => {
-        //         match header_entry {
-        //             hyper::header::Entry::Vacant(entry) => {
-        //                 let addr = format!("{}", client_ip);
-        //                 entry.insert(addr.parse().unwrap());
-        //             },
-        //             hyper::header::Entry::Occupied(mut entry) => {
-        //                 let addr = format!("{}, {}", entry.get().to_str().unwrap(), client_ip);
-        //                 entry.insert(addr.parse().unwrap());
-        //             }
-        //         }
-        //     }
-        //     // shouldn't happen...
-        //     Err(_) => panic!("Invalid header name: {}", x_forwarded_for_header_name),
-        // }
-        build.body(req.take_body()).map_err(Error::other)
+        build.body(body).map_err(Error::other)
     }
 
     #[inline]
@@ -242,6 +313,7 @@ where
         &self,
         proxied_request: HyperRequest,
         request_upgraded: Option<OnUpgrade>,
+        guard: Option<UpstreamGuard>,
     ) -> Result<HyperResponse, Error> {
         let request_upgrade_type = get_upgrade_type(proxied_request.headers()).map(|s| s.to_owned());
 
@@ -253,7 +325,7 @@ where
             .await
             .map_err(Error::other)?;
 
-        let res_headers = response.headers().clone();
+        let mut res_headers = response.headers().clone();
         let hyper_response = hyper::Response::builder()
             .status(response.status())
             .version(response.version());
@@ -268,6 +340,7 @@ where
                     .map_err(|e| Error::other(format!("response does not have an upgrade extension. {}", e)))?;
                 if let Some(request_upgraded) = request_upgraded {
                     tokio::spawn(async move {
+                        let _guard = guard;
                         match request_upgraded.await {
                             Ok(request_upgraded) => {
                                 let mut request_upgraded = TokioIo::new(request_upgraded);
@@ -289,9 +362,13 @@ where
             }
             hyper_response.body(ResBody::None).map_err(Error::other)?
         } else {
-            hyper_response
-                .body(ResBody::stream(response.bytes_stream()))
-                .map_err(Error::other)?
+            strip_hop_by_hop_headers(&mut res_headers);
+            // The guard is kept by the body stream, so it is dropped when the body is finished or dropped.
+            let body = response.bytes_stream().map_ok(move |chunk| {
+                let _guard = &guard;
+                chunk
+            });
+            hyper_response.body(ResBody::stream(body)).map_err(Error::other)?
         };
         *hyper_response.headers_mut() = res_headers;
         Ok(hyper_response)
@@ -306,35 +383,127 @@ where
 {
     #[inline]
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        match self.build_proxied_request(req, depot) {
-            Ok(proxied_request) => {
-                match self
-                    .call_proxied_server(proxied_request, req.extensions_mut().remove())
-                    .await
-                {
-                    Ok(response) => {
-                        let (
-                            salvo_core::http::response::Parts {
-                                status,
-                                // version,
-                                headers,
-                                // extensions,
-                                ..
-                            },
-                            body,
-                        ) = response.into_parts();
-                        res.status_code(status);
-                        res.set_headers(headers);
-                        res.body(body);
+        // only requests can be replayed are retried.
+        let mut max_retries = 0;
+        let mut replay_body = None;
+        if let Some(retry) = &self.retry {
+            if RetryPolicy::is_retryable(req.method()) && get_upgrade_type(req.headers()).is_none() {
+                match req.body() {
+                    ReqBody::Once(bytes) => replay_body = Some(bytes.clone()),
+                    body if body.is_end_stream() => replay_body = Some(Bytes::new()),
+                    _ => {}
+                }
+                if replay_body.is_some() {
+                    max_retries = retry.max_retries;
+                }
+            }
+        }
+        let mut attempt = 0;
+        let result = loop {
+            if attempt > 0 {
+                if let Some(retry) = &self.retry {
+                    tokio::time::sleep(retry.backoff_for(attempt - 1)).await;
+                }
+            }
+            let remaining = self.deadline_header.as_ref().and_then(|_| req.remaining_time());
+            if remaining == Some(Duration::ZERO) {
+                tracing::warn!(uri = ?req.uri(), "request deadline exceeded before calling upstream");
+                break Err(StatusCode::GATEWAY_TIMEOUT);
+            }
+            let upstream = match self.upstreams.elect() {
+                Ok(upstream) if !upstream.is_empty() => upstream,
+                Ok(_) => {
+                    tracing::error!("upstreams is empty");
+                    break Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e.into(), "elect upstream failed");
+                    break Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+            };
+            // failure is recorded if the attempt is ended without result, such as the request is cancelled.
+            let breaker_guard = match &self.circuit_breaker {
+                Some(breaker) if !breaker.allow(upstream) => {
+                    tracing::warn!(upstream = %upstream, "circuit breaker is open");
+                    if attempt < max_retries {
+                        attempt += 1;
+                        continue;
                     }
-                    Err(e) => {
+                    break Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                Some(breaker) => Some(breaker.guard(upstream)),
+                None => None,
+            };
+            let body = match (attempt, &replay_body) {
+                (0, _) | (_, None) => req.take_body(),
+                (_, Some(bytes)) if bytes.is_empty() => ReqBody::None,
+                (_, Some(bytes)) => ReqBody::Once(bytes.clone()),
+            };
+            let proxied_request = match self.build_proxied_request(req, depot, upstream, body) {
+                Ok(proxied_request) => proxied_request,
+                Err(e) => {
+                    tracing::error!(error = ?e, "build proxied request failed");
+                    break Err(StatusCode::BAD_GATEWAY);
+                }
+            };
+            let guard = self.upstreams.on_start(upstream);
+            let call = self.call_proxied_server(proxied_request, req.extensions_mut().remove(), guard);
+            let timeout = match (self.timeout, remaining) {
+                (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+                (timeout, remaining) => timeout.or(remaining),
+            };
+            let result = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                    Ok(Ok(response)) => Ok(response),
+                    Ok(Err(e)) => {
                         tracing::error!(error = ?e, uri = ?req.uri(), "get response data failed");
-                        res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                        Err(StatusCode::INTERNAL_SERVER_ERROR)
                     }
-                };
+                    Err(_) => {
+                        tracing::error!(upstream = %upstream, uri = ?req.uri(), "proxied request timed out");
+                        Err(StatusCode::GATEWAY_TIMEOUT)
+                    }
+                },
+                None => call.await.map_err(|e| {
+                    tracing::error!(error = ?e, uri = ?req.uri(), "get response data failed");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }),
+            };
+            let success = match &result {
+                Ok(response) => !matches!(
+                    response.status(),
+                    StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(_) => false,
+            };
+            self.upstreams.on_end(upstream, success);
+            if let Some(breaker_guard) = breaker_guard {
+                breaker_guard.record(success);
+            }
+            if !success && attempt < max_retries {
+                attempt += 1;
+                continue;
+            }
+            break result;
+        };
+        match result {
+            Ok(response) => {
+                let (
+                    salvo_core::http::response::Parts {
+                        status,
+                        // version,
+                        headers,
+                        // extensions,
+                        ..
+                    },
+                    body,
+                ) = response.into_parts();
+                res.status_code(status);
+                res.set_headers(headers);
+                res.body(body);
             }
-            Err(e) => {
-                tracing::error!(error = ?e, "build proxied request failed");
+            Err(status) => {
+                res.status_code(status);
             }
         }
         if ctrl.has_next() {
@@ -359,9 +528,39 @@ fn get_upgrade_type(headers: &HeaderMap) -> Option<&str> {
     None
 }
 
+/// Remove hop-by-hop headers, they are meaningful only for a single transport-level connection
+/// and must not be forwarded by proxies.
+fn strip_hop_by_hop_headers(headers: &mut HeaderMap) {
+    let connection_headers: Vec<HeaderName> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+    for name in connection_headers {
+        headers.remove(name);
+    }
+    for name in [
+        CONNECTION,
+        HeaderName::from_static("keep-alive"),
+        PROXY_AUTHENTICATE,
+        PROXY_AUTHORIZATION,
+        TE,
+        TRAILER,
+        TRANSFER_ENCODING,
+        UPGRADE,
+    ] {
+        headers.remove(name);
+    }
+}
+
 // Unit tests for Proxy
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use salvo_core::conn::Acceptor;
     use salvo_core::prelude::*;
     use salvo_core::test::*;
 
@@ -404,6 +603,312 @@ mod tests {
             .unwrap();
         assert!(content.contains("Install Rust"));
     }
+    #[test]
+    fn test_strip_hop_by_hop_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONNECTION, HeaderValue::from_static("keep-alive, x-custom"));
+        headers.insert("keep-alive", HeaderValue::from_static("timeout=5"));
+        headers.insert("x-custom", HeaderValue::from_static("value"));
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        headers.insert("x-kept", HeaderValue::from_static("value"));
+        strip_hop_by_hop_headers(&mut headers);
+        assert_eq!(headers.len(), 1);
+        assert!(headers.contains_key("x-kept"));
+    }
+
+    async fn serve(router: Router) -> std::net::SocketAddr {
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_proxy_stream_body() {
+        #[handler]
+        async fn echo(req: &mut Request, res: &mut Response) {
+            let headers = format!(
+                "{}|{}",
+                req.header::<String>("host").unwrap_or_default(),
+                req.header::<String>("x-custom").unwrap_or_default()
+            );
+            res.headers_mut().insert("x-upstream", headers.parse().unwrap());
+            let body = req.take_body().map_ok(|frame| frame.into_data().unwrap_or_default());
+            res.body(ResBody::stream(body));
+        }
+        let upstream = serve(Router::with_path("echo").post(echo)).await;
+        let router = Router::with_path("<**rest>").goal(Proxy::new(format!("http://{upstream}")));
+
+        let body = "a".repeat(1024 * 1024);
+        let mut res = TestClient::post("http://127.0.0.1:5801/echo")
+            .add_header(CONNECTION, "x-custom", true)
+            .add_header("x-custom", "value", true)
+            .body(body.clone())
+            .send(router)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("x-upstream").unwrap().to_str().unwrap(),
+            format!("{upstream}|")
+        );
+        assert_eq!(res.take_string().await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_upgrade() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        #[handler]
+        async fn upgrade(req: &mut Request, res: &mut Response) {
+            let on_upgrade = req.extensions_mut().remove::<OnUpgrade>().unwrap();
+            res.status_code(StatusCode::SWITCHING_PROTOCOLS);
+            res.headers_mut()
+                .insert(CONNECTION, HeaderValue::from_static("upgrade"));
+            res.headers_mut().insert(UPGRADE, HeaderValue::from_static("echo"));
+            tokio::spawn(async move {
+                let mut upgraded = TokioIo::new(on_upgrade.await.unwrap());
+                let mut buf = [0u8; 5];
+                upgraded.read_exact(&mut buf).await.unwrap();
+                upgraded.write_all(&buf).await.unwrap();
+            });
+        }
+        let upstream = serve(Router::with_path("ws").get(upgrade)).await;
+        let proxy = serve(Router::with_path("<**rest>").goal(Proxy::new(format!("http://{upstream}")))).await;
+
+        let mut stream = tokio::net::TcpStream::connect(proxy).await.unwrap();
+        stream
+            .write_all(
+                format!("GET /ws HTTP/1.1\r\nhost: {proxy}\r\nconnection: upgrade\r\nupgrade: echo\r\n\r\n").as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        let head = String::from_utf8(head).unwrap().to_lowercase();
+        assert!(head.starts_with("http/1.1 101"));
+        assert!(head.contains("upgrade: echo"));
+
+        stream.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_balanced_upstreams() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let upstream = serve(
+            Router::new()
+                .push(Router::with_path("healthz").get(hello))
+                .push(Router::with_path("hello").get(hello)),
+        )
+        .await;
+        let down = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let upstreams = BalancedUpstreams::new([format!("http://{down}"), format!("http://{upstream}")]);
+        let router = Router::with_path("<**rest>").goal(Proxy::new(upstreams.clone()));
+        let service = Service::new(router);
+
+        // the down upstream fails once, then it is not elected any more.
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            let res = TestClient::get("http://127.0.0.1:5801/hello").send(&service).await;
+            statuses.push(res.status_code.unwrap());
+        }
+        assert_eq!(statuses.iter().filter(|s| **s != StatusCode::OK).count(), 1);
+        assert!(!upstreams.is_available(&format!("http://{down}")));
+
+        let upstreams = BalancedUpstreams::new([format!("http://{down}"), format!("http://{upstream}")]);
+        let handle = upstreams.spawn_health_check(HealthCheck::new("/healthz").interval(Duration::from_millis(20)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!upstreams.is_available(&format!("http://{down}")));
+        assert!(upstreams.is_available(&format!("http://{upstream}")));
+        drop(upstreams);
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_proxy_policies() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static FLAKY_HITS: AtomicUsize = AtomicUsize::new(0);
+        static DOWN_HITS: AtomicUsize = AtomicUsize::new(0);
+        #[handler]
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            "slow"
+        }
+        #[handler]
+        async fn flaky(res: &mut Response) {
+            if FLAKY_HITS.fetch_add(1, Ordering::SeqCst) % 3 < 2 {
+                res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+            } else {
+                res.render("ok");
+            }
+        }
+        #[handler]
+        async fn down(res: &mut Response) {
+            DOWN_HITS.fetch_add(1, Ordering::SeqCst);
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        let upstream = serve(
+            Router::new()
+                .push(Router::with_path("slow").get(slow))
+                .push(Router::with_path("flaky").get(flaky).post(flaky))
+                .push(Router::with_path("down").get(down)),
+        )
+        .await;
+        let upstream = format!("http://{upstream}");
+        let router = Router::new()
+            .push(
+                Router::with_path("timeout/<**rest>")
+                    .goal(Proxy::new(upstream.clone()).timeout(Duration::from_millis(50))),
+            )
+            .push(
+                Router::with_path("retry/<**rest>")
+                    .goal(Proxy::new(upstream.clone()).retry(RetryPolicy::new(3).backoff(Duration::from_millis(1)))),
+            )
+            .push(
+                Router::with_path("breaker/<**rest>").goal(
+                    Proxy::new(upstream.clone()).circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60))),
+                ),
+            );
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/timeout/slow")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::GATEWAY_TIMEOUT);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/retry/flaky")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.take_string().await.unwrap(), "ok");
+        assert_eq!(FLAKY_HITS.load(Ordering::SeqCst), 3);
+        // non idempotent requests are not retried.
+        let res = TestClient::post("http://127.0.0.1:5801/retry/flaky")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(FLAKY_HITS.load(Ordering::SeqCst), 4);
+
+        for _ in 0..3 {
+            let res = TestClient::get("http://127.0.0.1:5801/breaker/down")
+                .send(&service)
+                .await;
+            assert_eq!(res.status_code.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+        assert_eq!(DOWN_HITS.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mirror() {
+        use std::sync::Mutex;
+
+        static MIRRORED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        #[handler]
+        async fn shadow(req: &mut Request) -> &'static str {
+            let body = String::from_utf8(req.payload().await.unwrap().to_vec()).unwrap();
+            MIRRORED.lock().unwrap().push(format!(
+                "{}|{}|{body}",
+                req.uri().path_and_query().unwrap(),
+                req.header::<String>("x-custom").unwrap()
+            ));
+            "shadow"
+        }
+        #[handler]
+        async fn primary(req: &mut Request) -> String {
+            String::from_utf8(req.payload_with_max_size(1024 * 1024).await.unwrap().to_vec()).unwrap()
+        }
+        let upstream = serve(Router::with_path("<**rest>").goal(shadow)).await;
+        let upstream = format!("http://{upstream}");
+        let router = Router::new()
+            .push(
+                Router::with_path("all/<**rest>")
+                    .hoop(Mirror::new(upstream.clone()).max_body_size(16))
+                    .goal(primary),
+            )
+            .push(
+                Router::with_path("none/<**rest>")
+                    .hoop(Mirror::new(upstream.clone()).percentage(0.0))
+                    .goal(primary),
+            );
+        let service = Service::new(router);
+
+        let mut res = TestClient::post("http://127.0.0.1:5801/all/echo?a=1")
+            .add_header("x-custom", "value", true)
+            .body("hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        let body = "a".repeat(1024);
+        let mut res = TestClient::post("http://127.0.0.1:5801/all/large")
+            .add_header("x-custom", "value", true)
+            .body(body.clone())
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), body);
+        let mut res = TestClient::post("http://127.0.0.1:5801/none/echo")
+            .add_header("x-custom", "value", true)
+            .body("hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        for _ in 0..100 {
+            if !MIRRORED.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(*MIRRORED.lock().unwrap(), vec!["/all/echo?a=1|value|hello".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_deadline() {
+        #[handler]
+        async fn timeout(req: &mut Request) -> String {
+            req.header::<String>("grpc-timeout").unwrap_or_default()
+        }
+        #[handler]
+        async fn set_deadline(req: &mut Request) {
+            let millis = req.query::<u64>("millis").unwrap_or_default();
+            req.set_deadline(Some(std::time::Instant::now() + Duration::from_millis(millis)));
+        }
+        let upstream = serve(Router::with_path("timeout").get(timeout)).await;
+        let router = Router::with_path("<**rest>").hoop(set_deadline).goal(
+            Proxy::new(format!("http://{upstream}"))
+                .url_query_getter(|_, _| None)
+                .deadline_header(DeadlineHeader::grpc_timeout()),
+        );
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/timeout?millis=5000")
+            .send(&service)
+            .await;
+        let content = res.take_string().await.unwrap();
+        let micros: u64 = content.trim_end_matches('u').parse().unwrap();
+        assert!(micros > 4_000_000 && micros <= 5_000_000);
+
+        let res = TestClient::get("http://127.0.0.1:5801/timeout?millis=0")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
     #[test]
     fn test_others() {
         let mut handler = Proxy::new(["https://www.bing.com"]);
@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+use http_body_util::StreamBody;
+use hyper::body::{Body, Bytes};
+use reqwest::Client;
+use salvo_core::http::header::{HeaderValue, HOST};
+use salvo_core::http::uri::Uri;
+use salvo_core::http::ReqBody;
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler, Request, Response};
+
+use super::{get_upgrade_type, strip_hop_by_hop_headers, Upstreams};
+
+/// Middleware that duplicates requests to a shadow upstream.
+///
+/// A configurable percentage of requests is copied, with headers and the body buffered up to
+/// `max_body_size`, and sent to the shadow upstream in a background task. The response of the shadow
+/// upstream is discarded, and the request is always processed by the next handlers as usual, so it can be
+/// used to test a new version of a service with production traffic.
+///
+/// Requests with a body larger than `max_body_size` and protocol upgrade requests are not mirrored.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::prelude::*;
+/// use salvo_proxy::{Mirror, Proxy};
+///
+/// let router = Router::with_path("<**rest>")
+///     .hoop(Mirror::new("http://shadow.local:8080").percentage(10.0))
+///     .goal(Proxy::new("http://primary.local:8080"));
+/// ```
+#[non_exhaustive]
+pub struct Mirror<U> {
+    /// Shadow upstreams list.
+    pub upstreams: U,
+    /// [`Client`] for mirrored requests.
+    pub client: Client,
+    /// Percentage of requests to mirror, from `0.0` to `100.0`. The default is `100.0`.
+    pub percentage: f64,
+    /// Max size of the buffered request body. The default is 64KB.
+    pub max_body_size: usize,
+    /// Timeout of a mirrored request. The default is 10 seconds.
+    pub timeout: Duration,
+}
+
+impl<U> Mirror<U>
+where
+    U: Upstreams,
+    U::Error: Into<BoxedError>,
+{
+    /// Create new `Mirror` with shadow upstreams list.
+    #[inline]
+    pub fn new(upstreams: U) -> Self {
+        Self::with_client(upstreams, Client::new())
+    }
+    /// Create new `Mirror` with shadow upstreams list and [`Client`].
+    #[inline]
+    pub fn with_client(upstreams: U, client: Client) -> Self {
+        Mirror {
+            upstreams,
+            client,
+            percentage: 100.0,
+            max_body_size: 64 * 1024,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Set percentage of requests to mirror, it is clamped to `0.0..=100.0`.
+    #[inline]
+    pub fn percentage(mut self, percentage: f64) -> Self {
+        self.percentage = percentage.clamp(0.0, 100.0);
+        self
+    }
+
+    /// Set max size of the buffered request body.
+    #[inline]
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Set timeout of a mirrored request.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get upstreams list.
+    #[inline]
+    pub fn upstreams(&self) -> &U {
+        &self.upstreams
+    }
+
+    fn sampled(&self) -> bool {
+        self.percentage >= 100.0 || (self.percentage > 0.0 && fastrand::f64() * 100.0 < self.percentage)
+    }
+
+    /// Read the request body up to `max_body_size`, the body of the request is restored after it is read.
+    ///
+    /// Returns `None` if the body is too large or can not be read.
+    async fn buffer_body(&self, req: &mut Request) -> Option<Bytes> {
+        let body = req.body();
+        if body.is_end_stream() {
+            return Some(Bytes::new());
+        }
+        if let ReqBody::Once(bytes) = body {
+            return (bytes.len() <= self.max_body_size).then(|| bytes.clone());
+        }
+        if body.size_hint().lower() > self.max_body_size as u64 {
+            return None;
+        }
+
+        let mut body = req.take_body();
+        let mut frames = Vec::new();
+        let mut size = 0;
+        let mut failed = false;
+        while let Some(frame) = body.next().await {
+            match &frame {
+                Ok(frame) => size += frame.data_ref().map(|data| data.len()).unwrap_or_default(),
+                Err(_) => failed = true,
+            }
+            frames.push(frame);
+            if size > self.max_body_size || failed {
+                break;
+            }
+        }
+        if size > self.max_body_size || failed {
+            // restore the read frames and the rest of the body for the next handlers.
+            let rest = stream::iter(frames).chain(body);
+            req.replace_body(ReqBody::Boxed(Box::pin(StreamBody::new(
+                rest.map_err(|e| -> BoxedError { e.into() }),
+            ))));
+            return None;
+        }
+        let mut data = Vec::with_capacity(size);
+        for frame in frames.iter().flatten() {
+            if let Some(bytes) = frame.data_ref() {
+                data.extend_from_slice(bytes);
+            }
+        }
+        let data = Bytes::from(data);
+        req.replace_body(ReqBody::Once(data.clone()));
+        Some(data)
+    }
+
+    fn build_mirrored_request(&self, req: &Request, upstream: &str, body: Bytes) -> Option<reqwest::Request> {
+        let rest = req.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+        let url = format!("{}{}", upstream.trim_end_matches('/'), rest);
+        let uri: Uri = match url.parse() {
+            Ok(uri) => uri,
+            Err(e) => {
+                tracing::error!(error = ?e, url = %url, "invalid mirrored url");
+                return None;
+            }
+        };
+        let mut headers = req.headers().clone();
+        strip_hop_by_hop_headers(&mut headers);
+        if let Some(host) = uri
+            .authority()
+            .and_then(|authority| HeaderValue::from_str(authority.as_str()).ok())
+        {
+            headers.insert(HOST, host);
+        }
+        let mut builder = hyper::Request::builder().method(req.method()).uri(uri);
+        if let Some(build_headers) = builder.headers_mut() {
+            *build_headers = headers;
+        }
+        let request = match builder.body(body) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::error!(error = ?e, "build mirrored request failed");
+                return None;
+            }
+        };
+        match reqwest::Request::try_from(request) {
+            Ok(mut request) => {
+                *request.timeout_mut() = Some(self.timeout);
+                Some(request)
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "build mirrored request failed");
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<U> Handler for Mirror<U>
+where
+    U: Upstreams,
+    U::Error: Into<BoxedError>,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.sampled() && get_upgrade_type(req.headers()).is_none() {
+            let upstream = match self.upstreams.elect() {
+                Ok(upstream) if !upstream.is_empty() => Some(upstream.to_owned()),
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::error!(error = ?e.into(), "elect mirror upstream failed");
+                    None
+                }
+            };
+            if let Some(upstream) = upstream {
+                match self.buffer_body(req).await {
+                    Some(body) => {
+                        if let Some(request) = self.build_mirrored_request(req, &upstream, body) {
+                            let client = self.client.clone();
+                            tokio::spawn(async move {
+                                match client.execute(request).await {
+                                    Ok(response) => {
+                                        // drain the body, so that the connection can be reused.
+                                        let _ = response.bytes().await;
+                                    }
+                                    Err(e) => {
+                                        tracing::debug!(error = ?e, upstream = %upstream, "mirrored request failed");
+                                    }
+                                }
+                            });
+                        }
+                    }
+                    None => {
+                        tracing::debug!(uri = ?req.uri(), "request body is too large to be mirrored");
+                    }
+                }
+            }
+        }
+        ctrl.call_next(req, depot, res).await;
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use salvo_core::http::Method;
+
+/// Retry policy for [`Proxy`](super::Proxy).
+///
+/// Only requests with idempotent methods and without streaming body are retried, when the upstream
+/// can not be connected, timed out, or responds with `502`, `503` or `504`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// Max retries after the first attempt.
+    pub max_retries: usize,
+    /// Backoff before the first retry, it is doubled for every next retry. The default is 100 milliseconds.
+    pub backoff: Duration,
+    /// Max backoff between retries. The default is 2 seconds.
+    pub max_backoff: Duration,
+}
+impl RetryPolicy {
+    /// Create new `RetryPolicy` with max retries.
+    #[inline]
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+    /// Sets backoff and returns a new `RetryPolicy`.
+    #[inline]
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    /// Sets max backoff and returns a new `RetryPolicy`.
+    #[inline]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Returns `true` if requests with this method can be retried.
+    #[inline]
+    pub fn is_retryable(method: &Method) -> bool {
+        matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+        )
+    }
+
+    /// Get the backoff before the retry, `retry` starts from 0.
+    pub fn backoff_for(&self, retry: usize) -> Duration {
+        let factor = 1u32.checked_shl(retry.min(31) as u32).unwrap_or(u32::MAX);
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { fails: usize },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+/// Circuit breaker for [`Proxy`](super::Proxy).
+///
+/// When an upstream fails `failure_threshold` times in a row, the circuit of the upstream is opened, and
+/// requests are responded with `503` directly. After `open_duration`, one trial request is allowed, the
+/// circuit is closed if it succeeds, otherwise it is opened again.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    open_duration: Duration,
+    states: Mutex<HashMap<String, BreakerState>>,
+}
+impl CircuitBreaker {
+    /// Create new `CircuitBreaker`.
+    #[inline]
+    pub fn new(failure_threshold: usize, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns `true` if a request can be sent to the upstream.
+    pub fn allow(&self, upstream: &str) -> bool {
+        let mut states = self.states.lock().unwrap();
+        match states.get(upstream) {
+            None | Some(BreakerState::Closed { .. }) => true,
+            Some(BreakerState::HalfOpen) => false,
+            Some(BreakerState::Open { until }) => {
+                if *until <= Instant::now() {
+                    states.insert(upstream.to_owned(), BreakerState::HalfOpen);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Create a guard which records failure to the upstream if it is dropped before the result is recorded.
+    #[inline]
+    pub(crate) fn guard<'a>(&'a self, upstream: &'a str) -> BreakerGuard<'a> {
+        BreakerGuard {
+            breaker: self,
+            upstream,
+            recorded: false,
+        }
+    }
+
+    /// Record the result of a request sent to the upstream.
+    pub fn record(&self, upstream: &str, success: bool) {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(upstream.to_owned())
+            .or_insert(BreakerState::Closed { fails: 0 });
+        *state = match (&*state, success) {
+            (_, true) => BreakerState::Closed { fails: 0 },
+            (BreakerState::Closed { fails }, false) if fails + 1 < self.failure_threshold => {
+                BreakerState::Closed { fails: fails + 1 }
+            }
+            (BreakerState::Open { until }, false) => BreakerState::Open { until: *until },
+            _ => {
+                tracing::warn!(upstream = %upstream, "circuit breaker is opened");
+                BreakerState::Open {
+                    until: Instant::now() + self.open_duration,
+                }
+            }
+        };
+    }
+}
+
+/// Guard makes sure the result of a request allowed by [`CircuitBreaker`] is always recorded, even if the
+/// request is failed early or cancelled, so the half open circuit does not wait for a result forever.
+pub(crate) struct BreakerGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    upstream: &'a str,
+    recorded: bool,
+}
+impl BreakerGuard<'_> {
+    /// Record the result of the request.
+    #[inline]
+    pub(crate) fn record(mut self, success: bool) {
+        self.breaker.record(self.upstream, success);
+        self.recorded = true;
+    }
+}
+impl Drop for BreakerGuard<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.record(self.upstream, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500));
+        assert_eq!(policy.backoff_for(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff_for(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff_for(100), Duration::from_millis(500));
+        assert!(RetryPolicy::is_retryable(&Method::GET));
+        assert!(!RetryPolicy::is_retryable(&Method::POST));
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+        assert!(breaker.allow("a"));
+        breaker.record("a", false);
+        assert!(breaker.allow("a"));
+        breaker.record("a", false);
+        assert!(!breaker.allow("a"));
+        assert!(breaker.allow("b"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow("a"));
+        // only one trial request is allowed in half open state.
+        assert!(!breaker.allow("a"));
+        breaker.record("a", false);
+        assert!(!breaker.allow("a"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow("a"));
+        breaker.record("a", true);
+        assert!(breaker.allow("a") && breaker.allow("a"));
+    }
+
+    #[test]
+    fn test_circuit_breaker_guard() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        assert!(breaker.allow("a"));
+        breaker.guard("a").record(true);
+        assert!(breaker.allow("a"));
+        // dropped without result, such as the request is cancelled.
+        drop(breaker.guard("a"));
+        assert!(!breaker.allow("a"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow("a"));
+        drop(breaker.guard("a"));
+        assert!(!breaker.allow("a"));
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use salvo_core::async_trait;
+use salvo_core::http::Request;
+use salvo_core::routing::{PathFilter, PathState};
+use salvo_core::Depot;
+
+/// `RateClassifier` is used to resolve the quota class of a request.
+///
+/// The class name is used by [`ClassedRateLimiter`](super::ClassedRateLimiter) to choose which
+/// limiter will handle the request.
+#[async_trait]
+pub trait RateClassifier: Send + Sync + 'static {
+    /// Classify the request, returns `None` if the request does not belong to any class.
+    async fn classify(&self, req: &mut Request, depot: &Depot) -> Option<String>;
+}
+#[async_trait]
+impl<F, C> RateClassifier for F
+where
+    F: Fn(&mut Request, &Depot) -> Option<C> + Send + Sync + 'static,
+    C: Into<String>,
+{
+    async fn classify(&self, req: &mut Request, depot: &Depot) -> Option<String> {
+        (self)(req, depot).map(Into::into)
+    }
+}
+
+/// Quota class attached to routers by [`Router::meta`](salvo_core::Router::meta).
+///
+/// It is resolved by [`PathClassifier`] before path patterns, the innermost class of the matched routers is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateClass(pub String);
+impl RateClass {
+    /// Create a new `RateClass`.
+    #[inline]
+    pub fn new(class: impl Into<String>) -> Self {
+        Self(class.into())
+    }
+}
+
+/// Classify requests by [`RateClass`] of the matched routers, or by route path patterns.
+///
+/// Patterns use the same syntax as [`Router::with_path`](salvo_core::Router::with_path),
+/// and they are matched against the whole request path in the order they are added. They are
+/// only used when the matched routers have no [`RateClass`], so a `PathClassifier` without patterns
+/// classifies requests by route metadata only.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_rate_limiter::{PathClassifier, RateClass};
+///
+/// let classifier = PathClassifier::new().add("api", "api/<**>");
+/// let router = Router::with_path("login").meta(RateClass::new("login"));
+/// ```
+#[derive(Default)]
+pub struct PathClassifier {
+    routes: Vec<(Arc<PathFilter>, String)>,
+}
+impl PathClassifier {
+    /// Create a new `PathClassifier`.
+    #[inline]
+    pub fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Add a path pattern, requests matched by this pattern belong to `class`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if path value is not in correct format.
+    #[inline]
+    pub fn add(mut self, class: impl Into<String>, path: impl Into<String>) -> Self {
+        self.routes.push((Arc::new(PathFilter::new(path)), class.into()));
+        self
+    }
+}
+#[async_trait]
+impl RateClassifier for PathClassifier {
+    async fn classify(&self, req: &mut Request, _depot: &Depot) -> Option<String> {
+        if let Some(class) = req.route_metadata().get::<RateClass>() {
+            return Some(class.0.clone());
+        }
+        for (filter, class) in &self.routes {
+            let mut state = PathState::new(req.uri().path());
+            if filter.detect(&mut state) && state.is_ended() {
+                return Some(class.clone());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_path_classifier() {
+        let classifier = PathClassifier::new()
+            .add("login", "login")
+            .add("api", "api/<**>")
+            .add("user", "users/<id:num>");
+        let depot = Depot::new();
+
+        let mut req = TestClient::get("http://127.0.0.1:5800/login").build();
+        assert_eq!(classifier.classify(&mut req, &depot).await, Some("login".into()));
+        let mut req = TestClient::get("http://127.0.0.1:5800/login/other").build();
+        assert_eq!(classifier.classify(&mut req, &depot).await, None);
+        let mut req = TestClient::get("http://127.0.0.1:5800/api/v1/items").build();
+        assert_eq!(classifier.classify(&mut req, &depot).await, Some("api".into()));
+        let mut req = TestClient::get("http://127.0.0.1:5800/users/12").build();
+        assert_eq!(classifier.classify(&mut req, &depot).await, Some("user".into()));
+        let mut req = TestClient::get("http://127.0.0.1:5800/users/abc").build();
+        assert_eq!(classifier.classify(&mut req, &depot).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_path_classifier_with_metadata() {
+        #[handler]
+        async fn probe(req: &mut Request, depot: &mut Depot) -> String {
+            let classifier = PathClassifier::new().add("api", "api/<**>");
+            classifier.classify(req, depot).await.unwrap_or_default()
+        }
+        let router = Router::with_path("api")
+            .push(Router::with_path("login").meta(RateClass::new("login")).get(probe))
+            .push(Router::with_path("items").get(probe));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/login").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "login");
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/items").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "api");
+    }
+}
@@ -10,6 +10,10 @@
 //!
 //! [`RateGuard`] is strategy to verify is the request exceeded quota.
 //!
+//! [`ClassedRateLimiter`] is used to apply different limiters to different classes of requests,
+//! the class is resolved by a [`RateClassifier`], such as [`PathClassifier`] which reads [`RateClass`] attached to
+//! routers, or a closure over [`Depot`].
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -21,6 +25,7 @@
 #![warn(rustdoc::broken_intra_doc_links)]
 
 use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::hash::Hash;
 
@@ -31,6 +36,8 @@ use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
 mod quota;
 pub use quota::{BasicQuota, CelledQuota, QuotaGetter};
+mod classifier;
+pub use classifier::{PathClassifier, RateClass, RateClassifier};
 #[macro_use]
 mod cfg;
 
@@ -195,6 +202,93 @@ where
     }
 }
 
+/// `ClassedRateLimiter` dispatches requests to different limiters by their quota class.
+///
+/// Every class has its own limiter, so classes can use different issuers, quotas and stores.
+/// Requests without a class, or with a class that has no limiter, are handled by the fallback
+/// limiter if it is set, otherwise they are not limited.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_rate_limiter::*;
+///
+/// let limiter = ClassedRateLimiter::new(PathClassifier::new().add("api", "api/<**>"))
+///     .add(
+///         "login",
+///         RateLimiter::new(FixedGuard::new(), MokaStore::new(), RemoteIpIssuer, BasicQuota::per_minute(5)),
+///     )
+///     .add(
+///         "api",
+///         RateLimiter::new(
+///             FixedGuard::new(),
+///             MokaStore::new(),
+///             |req: &mut Request, _depot: &Depot| req.header::<String>("x-api-key"),
+///             BasicQuota::per_minute(1000),
+///         ),
+///     );
+/// let router = Router::new()
+///     .hoop(limiter)
+///     .push(Router::with_path("login").meta(RateClass::new("login")));
+/// ```
+pub struct ClassedRateLimiter<C> {
+    classifier: C,
+    limiters: HashMap<String, Box<dyn Handler>>,
+    fallback: Option<Box<dyn Handler>>,
+    skipper: Box<dyn Skipper>,
+}
+
+impl<C: RateClassifier> ClassedRateLimiter<C> {
+    /// Create a new `ClassedRateLimiter`.
+    #[inline]
+    pub fn new(classifier: C) -> Self {
+        Self {
+            classifier,
+            limiters: HashMap::new(),
+            fallback: None,
+            skipper: Box::new(none_skipper),
+        }
+    }
+
+    /// Add a limiter for the class and returns new `ClassedRateLimiter`.
+    #[inline]
+    pub fn add(mut self, class: impl Into<String>, limiter: impl Handler) -> Self {
+        self.limiters.insert(class.into(), Box::new(limiter));
+        self
+    }
+
+    /// Sets the limiter used for requests without matched class and returns new `ClassedRateLimiter`.
+    #[inline]
+    pub fn fallback(mut self, limiter: impl Handler) -> Self {
+        self.fallback = Some(Box::new(limiter));
+        self
+    }
+
+    /// Sets skipper and returns new `ClassedRateLimiter`.
+    #[inline]
+    pub fn with_skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+}
+
+#[async_trait]
+impl<C: RateClassifier> Handler for ClassedRateLimiter<C> {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let limiter = match self.classifier.classify(req, depot).await {
+            Some(class) => self.limiters.get(&class).or(self.fallback.as_ref()),
+            None => self.fallback.as_ref(),
+        };
+        if let Some(limiter) = limiter {
+            limiter.handle(req, depot, res, ctrl).await;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -381,4 +475,67 @@ mod tests {
         assert_eq!(respone.status_code, Some(StatusCode::OK));
         assert_eq!(respone.take_string().await.unwrap(), "Limited page");
     }
+
+    #[tokio::test]
+    async fn test_classed_quota() {
+        let limiter = ClassedRateLimiter::new(PathClassifier::new().add("login", "login").add("api", "api/<**>"))
+            .add(
+                "login",
+                RateLimiter::new(
+                    FixedGuard::new(),
+                    MokaStore::new(),
+                    UserIssuer,
+                    BasicQuota::per_minute(1),
+                ),
+            )
+            .add(
+                "api",
+                RateLimiter::new(
+                    FixedGuard::new(),
+                    MokaStore::new(),
+                    |req: &mut Request, _depot: &Depot| req.header::<String>("x-api-key"),
+                    BasicQuota::per_minute(2),
+                ),
+            );
+        let router = Router::new()
+            .hoop(limiter)
+            .push(Router::with_path("login").get(limited))
+            .push(Router::with_path("api/items").get(limited))
+            .push(Router::with_path("free").get(limited));
+        let service = Service::new(router);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/login?user=user1")
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::OK));
+        let respone = TestClient::get("http://127.0.0.1:5800/login?user=user1")
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+
+        for _ in 0..2 {
+            let respone = TestClient::get("http://127.0.0.1:5800/api/items")
+                .add_header("x-api-key", "key1", true)
+                .send(&service)
+                .await;
+            assert_eq!(respone.status_code, Some(StatusCode::OK));
+        }
+        let respone = TestClient::get("http://127.0.0.1:5800/api/items")
+            .add_header("x-api-key", "key1", true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+        let respone = TestClient::get("http://127.0.0.1:5800/api/items")
+            .add_header("x-api-key", "key2", true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::OK));
+        let respone = TestClient::get("http://127.0.0.1:5800/api/items").send(&service).await;
+        assert_eq!(respone.status_code, Some(StatusCode::BAD_REQUEST));
+
+        for _ in 0..3 {
+            let respone = TestClient::get("http://127.0.0.1:5800/free").send(&service).await;
+            assert_eq!(respone.status_code, Some(StatusCode::OK));
+        }
+    }
 }
@@ -22,11 +22,31 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_extra::affix;
 }
+cfg_feature! {
+    #![feature ="api-key-auth"]
+    #[doc(no_inline)]
+    pub use salvo_extra::api_key_auth;
+}
+cfg_feature! {
+    #![feature ="audit"]
+    #[doc(no_inline)]
+    pub use salvo_extra::audit;
+}
+cfg_feature! {
+    #![feature ="authorization"]
+    #[doc(no_inline)]
+    pub use salvo_extra::authorization;
+}
 cfg_feature! {
     #![feature ="basic-auth"]
     #[doc(no_inline)]
     pub use salvo_extra::basic_auth;
 }
+cfg_feature! {
+    #![feature ="cache-control"]
+    #[doc(no_inline)]
+    pub use salvo_extra::cache_control;
+}
 cfg_feature! {
     #![feature ="caching-headers"]
     #[doc(no_inline)]
@@ -67,6 +87,21 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_extra::size_limiter;
 }
+cfg_feature! {
+    #![feature ="archive"]
+    #[doc(no_inline)]
+    pub use salvo_extra::archive;
+}
+cfg_feature! {
+    #![feature ="csv"]
+    #[doc(no_inline)]
+    pub use salvo_extra::csv;
+}
+cfg_feature! {
+    #![feature ="ndjson"]
+    #[doc(no_inline)]
+    pub use salvo_extra::ndjson;
+}
 cfg_feature! {
     #![feature ="sse"]
     #[doc(no_inline)]
@@ -88,10 +123,115 @@ cfg_feature! {
     pub use salvo_extra::websocket;
 }
 cfg_feature! {
-    #![feature ="request_id"]
+    #![feature ="redirects"]
+    #[doc(no_inline)]
+    pub use salvo_extra::redirects;
+}
+cfg_feature! {
+    #![feature ="request-id"]
     #[doc(no_inline)]
     pub use salvo_extra::request_id;
 }
+cfg_feature! {
+    #![feature ="response-headers"]
+    #[doc(no_inline)]
+    pub use salvo_extra::response_headers;
+}
+cfg_feature! {
+    #![feature ="rewrite"]
+    #[doc(no_inline)]
+    pub use salvo_extra::rewrite;
+}
+cfg_feature! {
+    #![feature ="secure-headers"]
+    #[doc(no_inline)]
+    pub use salvo_extra::secure_headers;
+}
+cfg_feature! {
+    #![feature ="server-timing"]
+    #[doc(no_inline)]
+    pub use salvo_extra::server_timing;
+}
+cfg_feature! {
+    #![feature ="signed-url"]
+    #[doc(no_inline)]
+    pub use salvo_extra::signed_url;
+}
+cfg_feature! {
+    #![feature ="health"]
+    #[doc(no_inline)]
+    pub use salvo_extra::health;
+}
+cfg_feature! {
+    #![feature ="idempotency"]
+    #[doc(no_inline)]
+    pub use salvo_extra::idempotency;
+}
+cfg_feature! {
+    #![feature ="maintenance"]
+    #[doc(no_inline)]
+    pub use salvo_extra::maintenance;
+}
+cfg_feature! {
+    #![feature ="engine-io"]
+    #[doc(no_inline)]
+    pub use salvo_extra::engine_io;
+}
+cfg_feature! {
+    #![feature ="webhook"]
+    #[doc(no_inline)]
+    pub use salvo_extra::webhook;
+}
+cfg_feature! {
+    #![feature ="feature-flags"]
+    #[doc(no_inline)]
+    pub use salvo_extra::feature_flags;
+}
+cfg_feature! {
+    #![feature ="http-client"]
+    #[doc(no_inline)]
+    pub use salvo_extra::http_client;
+}
+cfg_feature! {
+    #![feature ="slow-request"]
+    #[doc(no_inline)]
+    pub use salvo_extra::slow_request;
+}
+cfg_feature! {
+    #![feature ="firewall"]
+    #[doc(no_inline)]
+    pub use salvo_extra::firewall;
+}
+cfg_feature! {
+    #![feature ="bot-detection"]
+    #[doc(no_inline)]
+    pub use salvo_extra::bot_detection;
+}
+cfg_feature! {
+    #![feature ="geoip"]
+    #[doc(no_inline)]
+    pub use salvo_extra::geoip;
+}
+cfg_feature! {
+    #![any(feature = "bot-detection", feature = "firewall", feature = "geoip")]
+    #[doc(no_inline)]
+    pub use salvo_extra::trusted_proxies;
+}
+cfg_feature! {
+    #![feature ="buffer-body"]
+    #[doc(no_inline)]
+    pub use salvo_extra::buffer_body;
+}
+cfg_feature! {
+    #![feature ="load-shedder"]
+    #[doc(no_inline)]
+    pub use salvo_extra::load_shedder;
+}
+cfg_feature! {
+    #![feature ="query-filter"]
+    #[doc(no_inline)]
+    pub use salvo_extra::query_filter;
+}
 cfg_feature! {
     #![feature ="cache"]
     #[doc(no_inline)]
@@ -112,6 +252,16 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_flash as flash;
 }
+cfg_feature! {
+    #![feature ="grpc-web"]
+    #[doc(no_inline)]
+    pub use salvo_grpc_web as grpc_web;
+}
+cfg_feature! {
+    #![feature ="i18n"]
+    #[doc(no_inline)]
+    pub use salvo_i18n as i18n;
+}
 cfg_feature! {
     #![feature ="proxy"]
     #[doc(no_inline)]
@@ -132,6 +282,26 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_serve_static as serve_static;
 }
+cfg_feature! {
+    #![feature ="template"]
+    #[doc(no_inline)]
+    pub use salvo_template as template;
+}
+cfg_feature! {
+    #![feature ="tus"]
+    #[doc(no_inline)]
+    pub use salvo_tus as tus;
+}
+cfg_feature! {
+    #![feature ="oauth"]
+    #[doc(no_inline)]
+    pub use salvo_oauth as oauth;
+}
+cfg_feature! {
+    #![feature ="tenancy"]
+    #[doc(no_inline)]
+    pub use salvo_tenancy as tenancy;
+}
 cfg_feature! {
     #![feature ="otel"]
     #[doc(no_inline)]
@@ -151,13 +321,29 @@ pub mod prelude {
         #![feature ="affix"]
         pub use salvo_extra::affix;
     }
+    cfg_feature! {
+        #![feature ="api-key-auth"]
+        pub use salvo_extra::api_key_auth::{ApiKeyAuth, ApiKeyAuthDepotExt, ApiKeyAuthState, ApiKeyResolver};
+    }
+    cfg_feature! {
+        #![feature ="audit"]
+        pub use salvo_extra::audit::{Audit, AuditSink};
+    }
+    cfg_feature! {
+        #![feature ="authorization"]
+        pub use salvo_extra::authorization::{Authorization, AuthorizationDepotExt, Grants, Policy, Requires};
+    }
     cfg_feature! {
         #![feature ="basic-auth"]
         pub use salvo_extra::basic_auth::{BasicAuth, BasicAuthDepotExt, BasicAuthValidator};
     }
+    cfg_feature! {
+        #![feature ="cache-control"]
+        pub use salvo_extra::cache_control::{CacheControlPolicy, CacheDirectives};
+    }
     cfg_feature! {
         #![feature ="caching-headers"]
-        pub use salvo_extra::caching_headers::CachingHeaders;
+        pub use salvo_extra::caching_headers::{AutoETag, CachingHeaders};
     }
     cfg_feature! {
         #![feature ="catch-panic"]
@@ -183,21 +369,49 @@ pub mod prelude {
         #![feature ="logging"]
         pub use salvo_extra::logging::Logger;
     }
+    cfg_feature! {
+        #![feature ="oauth"]
+        pub use salvo_oauth::{OAuth, OAuthDepotExt};
+    }
+    cfg_feature! {
+        #![feature ="grpc-web"]
+        pub use salvo_grpc_web::GrpcWeb;
+    }
     cfg_feature! {
         #![feature ="proxy"]
-        pub use salvo_proxy::Proxy;
+        pub use salvo_proxy::{Mirror, Proxy, Tunnel};
     }
     cfg_feature! {
         #![feature ="session"]
         pub use salvo_session::{SessionDepotExt, SessionHandler, SessionStore};
     }
+    cfg_feature! {
+        #![feature ="tus"]
+        pub use salvo_tus::{Tus, UploadStore};
+    }
+    cfg_feature! {
+        #![feature ="tenancy"]
+        pub use salvo_tenancy::{TenantDepotExt, TenantGuard, TenantResolver, TenantScoped, TenantSource};
+    }
     cfg_feature! {
         #![feature ="concurrency-limiter"]
-        pub use salvo_extra::concurrency_limiter::max_concurrency;
+        pub use salvo_extra::concurrency_limiter::{max_concurrency, ConcurrencyLimiter};
     }
     cfg_feature! {
         #![feature ="size-limiter"]
-        pub use salvo_extra::size_limiter::max_size;
+        pub use salvo_extra::size_limiter::{max_response_size, max_size};
+    }
+    cfg_feature! {
+        #![feature ="archive"]
+        pub use salvo_extra::archive::ArchiveStream;
+    }
+    cfg_feature! {
+        #![feature ="csv"]
+        pub use salvo_extra::csv::{CsvBody, CsvStream};
+    }
+    cfg_feature! {
+        #![feature ="ndjson"]
+        pub use salvo_extra::ndjson::NdJsonStream;
     }
     cfg_feature! {
         #![feature ="sse"]
@@ -215,13 +429,97 @@ pub mod prelude {
         #![feature ="websocket"]
         pub use salvo_extra::websocket::WebSocketUpgrade;
     }
+    cfg_feature! {
+        #![feature ="redirects"]
+        pub use salvo_extra::redirects::{RedirectRule, Redirects};
+    }
     cfg_feature! {
         #![feature ="request-id"]
         pub use salvo_extra::request_id::RequestId;
     }
+    cfg_feature! {
+        #![feature ="response-headers"]
+        pub use salvo_extra::response_headers::{DefaultHeaders, RemoveHeaders};
+    }
+    cfg_feature! {
+        #![feature ="rewrite"]
+        pub use salvo_extra::rewrite::{Rewrite, RewriteRule};
+    }
+    cfg_feature! {
+        #![feature ="secure-headers"]
+        pub use salvo_extra::secure_headers::{SecureHeaders, SecureHeadersDepotExt};
+    }
+    cfg_feature! {
+        #![feature ="server-timing"]
+        pub use salvo_extra::server_timing::{ServerTiming, ServerTimingDepotExt};
+    }
+    cfg_feature! {
+        #![feature ="signed-url"]
+        pub use salvo_extra::signed_url::{SignedUrlDepotExt, UrlSigner};
+    }
+    cfg_feature! {
+        #![feature ="health"]
+        pub use salvo_extra::health::{Health, HealthIndicator, Indication};
+    }
+    cfg_feature! {
+        #![feature ="idempotency"]
+        pub use salvo_extra::idempotency::{Idempotency, IdempotencyStore};
+    }
+    cfg_feature! {
+        #![feature ="maintenance"]
+        pub use salvo_extra::maintenance::MaintenanceMode;
+    }
+    cfg_feature! {
+        #![feature ="engine-io"]
+        pub use salvo_extra::engine_io::EngineIo;
+    }
+    cfg_feature! {
+        #![feature ="webhook"]
+        pub use salvo_extra::webhook::{WebhookSender, WebhookVerifier};
+    }
+    cfg_feature! {
+        #![feature ="feature-flags"]
+        pub use salvo_extra::feature_flags::{FeatureFlags, Flags};
+    }
+    cfg_feature! {
+        #![feature ="http-client"]
+        pub use salvo_extra::http_client::HttpClient;
+    }
+    cfg_feature! {
+        #![feature ="slow-request"]
+        pub use salvo_extra::slow_request::SlowRequestLogger;
+    }
+    cfg_feature! {
+        #![feature ="firewall"]
+        pub use salvo_extra::firewall::Firewall;
+    }
+    cfg_feature! {
+        #![feature ="bot-detection"]
+        pub use salvo_extra::bot_detection::{BotDetector, RobotsTxt};
+    }
+    cfg_feature! {
+        #![feature ="geoip"]
+        pub use salvo_extra::geoip::GeoIp;
+    }
+    cfg_feature! {
+        #![feature ="buffer-body"]
+        pub use salvo_extra::buffer_body::BufferBody;
+    }
+    cfg_feature! {
+        #![feature ="load-shedder"]
+        pub use salvo_extra::load_shedder::LoadShedder;
+    }
+    cfg_feature! {
+        #![feature ="query-filter"]
+        pub use salvo_extra::query_filter::QueryFilter;
+    }
     cfg_feature! {
         #![feature ="serve-static"]
-        pub use salvo_serve_static::{StaticFile, StaticDir};
+        pub use salvo_serve_static::{StaticFile, StaticDir, StaticStore};
+    }
+    cfg_feature! {
+        #![feature ="template"]
+        pub use salvo_template::{Template, Templates};
     }
     cfg_feature! {
         #![feature ="oapi"]
@@ -5,9 +5,11 @@ use std::ffi::OsStr;
 use std::fmt::Write;
 use std::fs::Metadata;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use salvo_core::fs::NamedFile;
+use salvo_core::http::header::{HeaderValue, ACCEPT_ENCODING, VARY};
 use salvo_core::http::{Request, Response, StatusCode, StatusError};
 use salvo_core::writing::Text;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, IntoVecString};
@@ -15,7 +17,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::{macros::format_description, OffsetDateTime};
 
-use super::{decode_url_path_safely, encode_url_path, format_url_path_safely, redirect_to_dir_url};
+use super::{decode_url_path_safely, encode_url_path, format_url_path_safely, guess_content_type, redirect_to_dir_url};
+
+/// Content encodings and file extensions of precompressed files, in preferred order.
+const PRECOMPRESSED_VARIANTS: [(&str, &str); 3] = [("br", "br"), ("zstd", "zst"), ("gzip", "gz")];
 
 /// Trait for collecting static roots.
 pub trait StaticRoots {
@@ -66,6 +71,73 @@ where
     }
 }
 
+/// Template used to render directory listing as html.
+///
+/// It is implemented for `Fn(&CurrentInfo) -> String`, so a closure can be used as template.
+///
+/// # Example
+///
+/// ```
+/// use salvo_serve_static::dir::CurrentInfo;
+/// use salvo_serve_static::StaticDir;
+///
+/// let handler = StaticDir::new(["assets"]).listing(true).listing_template(|current: &CurrentInfo| {
+///     let mut html = format!("<h1>{}</h1>", current.path);
+///     for file in &current.files {
+///         html.push_str(&format!("<p>{} ({} bytes)</p>", file.name, file.size));
+///     }
+///     html
+/// });
+/// ```
+pub trait ListingTemplate: Send + Sync + 'static {
+    /// Render the directory listing to html.
+    fn render(&self, current: &CurrentInfo) -> String;
+}
+impl<F> ListingTemplate for F
+where
+    F: Fn(&CurrentInfo) -> String + Send + Sync + 'static,
+{
+    #[inline]
+    fn render(&self, current: &CurrentInfo) -> String {
+        (self)(current)
+    }
+}
+
+/// Built-in minimal directory listing template, without styles and icons.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MinimalListing;
+impl ListingTemplate for MinimalListing {
+    fn render(&self, current: &CurrentInfo) -> String {
+        let path = escape_html(&current.path);
+        let mut ftxt = format!(
+            r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>{path}</title></head><body><h3>Index of: {path}</h3><ul>"#
+        );
+        if !(current.path.is_empty() || current.path == "/") {
+            write!(ftxt, r#"<li><a href="../">../</a></li>"#).ok();
+        }
+        for dir in &current.dirs {
+            write!(
+                ftxt,
+                r#"<li><a href="./{}/">{}/</a></li>"#,
+                encode_url_path(&dir.name),
+                escape_html(&dir.name)
+            )
+            .ok();
+        }
+        for file in &current.files {
+            write!(
+                ftxt,
+                r#"<li><a href="./{}">{}</a></li>"#,
+                encode_url_path(&file.name),
+                escape_html(&file.name)
+            )
+            .ok();
+        }
+        ftxt.push_str("</ul></body></html>");
+        ftxt
+    }
+}
+
 /// Handler that serves a directory.
 #[non_exhaustive]
 #[derive(Clone)]
@@ -87,6 +159,16 @@ pub struct StaticDir {
     pub defaults: Vec<String>,
     /// Fallback file name. This is used when the requested file is not found.
     pub fallback: Option<String>,
+    /// Serve precompressed sibling files, such as `app.js.br`, `app.js.zst` and `app.js.gz`,
+    /// when the client accepts their encodings.
+    pub precompressed: bool,
+    /// Single page application mode.
+    ///
+    /// In this mode, fallback file is only used for paths without an extension, so unknown
+    /// routes are handled by the application, but missing assets are still responded with 404.
+    pub spa: bool,
+    /// Template used to render html directory listing, the built-in template is used if it is `None`.
+    pub listing_template: Option<Arc<dyn ListingTemplate>>,
 }
 impl StaticDir {
     /// Create new `StaticDir`.
@@ -99,6 +181,9 @@ impl StaticDir {
             listing: false,
             defaults: vec![],
             fallback: None,
+            precompressed: false,
+            spa: false,
+            listing_template: None,
         }
     }
 
@@ -129,6 +214,37 @@ impl StaticDir {
         self
     }
 
+    /// Sets listing template and returns a new `StaticDirOptions`.
+    ///
+    /// The template is only used for html listing, the plain text, json and xml listings are not affected.
+    #[inline]
+    pub fn listing_template(mut self, template: impl ListingTemplate) -> Self {
+        self.listing_template = Some(Arc::new(template));
+        self
+    }
+
+    /// Sets precompressed and returns a new `StaticDirOptions`.
+    ///
+    /// When it is enabled, if the client accepts `br`, `zstd` or `gzip` encoding, and a sibling file
+    /// with extension `.br`, `.zst` or `.gz` exists, the sibling file will be served with correct
+    /// `Content-Encoding` header.
+    #[inline]
+    pub fn precompressed(mut self, precompressed: bool) -> Self {
+        self.precompressed = precompressed;
+        self
+    }
+
+    /// Sets single page application mode and returns a new `StaticDirOptions`.
+    ///
+    /// In this mode, the fallback file, which is `index.html` if fallback is not set, is used for
+    /// unknown paths without an extension, and missing assets, such as `/app.js`, are still responded
+    /// with 404.
+    #[inline]
+    pub fn spa(mut self, spa: bool) -> Self {
+        self.spa = spa;
+        self
+    }
+
     /// During the file chunk read, the maximum read size at one time will affect the
     /// access experience and the demand for server memory.
     ///
@@ -140,12 +256,58 @@ impl StaticDir {
         self.chunk_size = Some(size);
         self
     }
+
+    /// Find the precompressed sibling file of `path`, returns the file path and its encoding.
+    fn find_precompressed(&self, req: &Request, path: &Path) -> Option<(PathBuf, &'static str)> {
+        let accept = req.headers().get(ACCEPT_ENCODING)?.to_str().ok()?;
+        let mut matched: Option<(PathBuf, &'static str, f32)> = None;
+        for (encoding, ext) in PRECOMPRESSED_VARIANTS {
+            let quality = match encoding_quality(accept, encoding) {
+                Some(quality) if quality > 0.0 => quality,
+                _ => continue,
+            };
+            if matched.as_ref().map(|(_, _, q)| quality > *q).unwrap_or(true) {
+                let mut file_name = path.file_name()?.to_os_string();
+                file_name.push(".");
+                file_name.push(ext);
+                let variant = path.with_file_name(file_name);
+                if variant.is_file() {
+                    matched = Some((variant, encoding, quality));
+                }
+            }
+        }
+        matched.map(|(path, encoding, _)| (path, encoding))
+    }
 }
+
+/// Get the quality of `encoding` in `Accept-Encoding` header value.
+fn encoding_quality(accept: &str, encoding: &str) -> Option<f32> {
+    let mut wildcard = None;
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let quality = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if name.eq_ignore_ascii_case(encoding) {
+            return Some(quality);
+        } else if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+    wildcard
+}
+/// The information of current listing directory.
 #[derive(Serialize, Deserialize, Debug)]
-struct CurrentInfo {
-    path: String,
-    files: Vec<FileInfo>,
-    dirs: Vec<DirInfo>,
+#[non_exhaustive]
+pub struct CurrentInfo {
+    /// Request path of the directory.
+    pub path: String,
+    /// Files in the directory.
+    pub files: Vec<FileInfo>,
+    /// Sub directories in the directory.
+    pub dirs: Vec<DirInfo>,
 }
 impl CurrentInfo {
     #[inline]
@@ -153,11 +315,16 @@ impl CurrentInfo {
         CurrentInfo { path, files, dirs }
     }
 }
+/// The information of a file in listing directory.
 #[derive(Serialize, Deserialize, Debug)]
-struct FileInfo {
-    name: String,
-    size: u64,
-    modified: OffsetDateTime,
+#[non_exhaustive]
+pub struct FileInfo {
+    /// File name.
+    pub name: String,
+    /// File size in bytes.
+    pub size: u64,
+    /// Last modified time.
+    pub modified: OffsetDateTime,
 }
 impl FileInfo {
     #[inline]
@@ -169,10 +336,14 @@ impl FileInfo {
         }
     }
 }
+/// The information of a sub directory in listing directory.
 #[derive(Serialize, Deserialize, Debug)]
-struct DirInfo {
-    name: String,
-    modified: OffsetDateTime,
+#[non_exhaustive]
+pub struct DirInfo {
+    /// Directory name.
+    pub name: String,
+    /// Last modified time.
+    pub modified: OffsetDateTime,
 }
 impl DirInfo {
     #[inline]
@@ -231,8 +402,13 @@ impl Handler for StaticDir {
                 }
             }
         }
-        let fallback = self.fallback.as_deref().unwrap_or_default();
-        if abs_path.is_none() && !fallback.is_empty() {
+        let fallback = match self.fallback.as_deref() {
+            Some(fallback) => fallback,
+            None if self.spa => "index.html",
+            None => "",
+        };
+        let is_asset = self.spa && Path::new(&rel_path).extension().is_some();
+        if abs_path.is_none() && !fallback.is_empty() && !is_asset {
             for root in &self.roots {
                 let path = root.join(fallback);
                 if path.is_file() {
@@ -252,7 +428,18 @@ impl Handler for StaticDir {
 
         if abs_path.is_file() {
             let builder = {
-                let mut builder = NamedFile::builder(abs_path);
+                let mut builder = if self.precompressed {
+                    res.headers_mut()
+                        .append(VARY, HeaderValue::from_static("accept-encoding"));
+                    match self.find_precompressed(req, &abs_path) {
+                        Some((path, encoding)) => NamedFile::builder(path)
+                            .content_type(guess_content_type(&abs_path))
+                            .content_encoding(encoding),
+                        None => NamedFile::builder(abs_path),
+                    }
+                } else {
+                    NamedFile::builder(abs_path)
+                };
                 if let Some(size) = self.chunk_size {
                     builder = builder.buffer_size(size);
                 }
@@ -300,7 +487,10 @@ impl Handler for StaticDir {
                 "plain" => res.render(Text::Plain(list_text(&root))),
                 "json" => res.render(Text::Json(list_json(&root))),
                 "xml" => res.render(Text::Xml(list_xml(&root))),
-                _ => res.render(Text::Html(list_html(&root))),
+                _ => match &self.listing_template {
+                    Some(template) => res.render(Text::Html(template.render(&root))),
+                    None => res.render(Text::Html(list_html(&root))),
+                },
             };
         }
     }
@@ -408,6 +598,20 @@ fn list_html(current: &CurrentInfo) -> String {
     .ok();
     ftxt
 }
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
 #[inline]
 fn list_text(current: &CurrentInfo) -> String {
     json!(current).to_string()
@@ -449,3 +653,18 @@ const HTML_STYLE: &str = r#"
 const DIR_ICON: &str = r#"<svg aria-label="Directory" data-icon="dir" width="20" height="20" viewBox="0 0 512 512" version="1.1" role="img"><path fill="currentColor" d="M464 128H272l-64-64H48C21.49 64 0 85.49 0 112v288c0 26.51 21.49 48 48 48h416c26.51 0 48-21.49 48-48V176c0-26.51-21.49-48-48-48z"></path></svg>"#;
 const FILE_ICON: &str = r#"<svg aria-label="File" data-icon="file" width="20" height="20" viewBox="0 0 384 512" version="1.1" role="img"><path d="M369.9 97.9L286 14C277 5 264.8-.1 252.1-.1H48C21.5 0 0 21.5 0 48v416c0 26.5 21.5 48 48 48h288c26.5 0 48-21.5 48-48V131.9c0-12.7-5.1-25-14.1-34zM332.1 128H256V51.9l76.1 76.1zM48 464V48h160v104c0 13.3 10.7 24 24 24h104v288H48z"/></svg>"#;
 const HOME_ICON: &str = r#"<svg aria-hidden="true" data-icon="home" viewBox="0 0 576 512"><path fill="currentColor" d="M280.37 148.26L96 300.11V464a16 16 0 0 0 16 16l112.06-.29a16 16 0 0 0 15.92-16V368a16 16 0 0 1 16-16h64a16 16 0 0 1 16 16v95.64a16 16 0 0 0 16 16.05L464 480a16 16 0 0 0 16-16V300L295.67 148.26a12.19 12.19 0 0 0-15.3 0zM571.6 251.47L488 182.56V44.05a12 12 0 0 0-12-12h-56a12 12 0 0 0-12 12v72.61L318.47 43a48 48 0 0 0-61 0L4.34 251.47a12 12 0 0 0-1.6 16.9l25.5 31A12 12 0 0 0 45.15 301l235.22-193.74a12.19 12.19 0 0 1 15.3 0L530.9 301a12 12 0 0 0 16.9-1.6l25.5-31a12 12 0 0 0-1.7-16.93z"></path></svg>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_quality() {
+        assert_eq!(encoding_quality("gzip, br", "br"), Some(1.0));
+        assert_eq!(encoding_quality("gzip;q=0.8, br;q=0.5", "gzip"), Some(0.8));
+        assert_eq!(encoding_quality("gzip;q=0", "gzip"), Some(0.0));
+        assert_eq!(encoding_quality("gzip", "zstd"), None);
+        assert_eq!(encoding_quality("*;q=0.1", "zstd"), Some(0.1));
+        assert_eq!(encoding_quality("*;q=0.1, zstd;q=0", "zstd"), Some(0.0));
+    }
+}
@@ -1,12 +1,13 @@
 use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::path::Path;
 
 use rust_embed::{EmbeddedFile, Metadata, RustEmbed};
 use salvo_core::http::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use salvo_core::http::{Mime, Request, Response, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, IntoVecString};
 
-use super::{decode_url_path_safely, format_url_path_safely, redirect_to_dir_url};
+use super::{decode_url_path_safely, format_url_path_safely, guess_content_type, redirect_to_dir_url};
 
 macro_rules! join_path {
     ($($part:expr),+) => {
@@ -29,6 +30,11 @@ pub struct StaticEmbed<T> {
     pub defaults: Vec<String>,
     /// Fallback file name. This is used when the requested file is not found.
     pub fallback: Option<String>,
+    /// Single page application mode.
+    ///
+    /// In this mode, fallback file is only used for paths without an extension, so unknown
+    /// routes are handled by the application, but missing assets are still responded with 404.
+    pub spa: bool,
 }
 
 /// Create a new `StaticEmbed` middleware.
@@ -38,6 +44,7 @@ pub fn static_embed<T: RustEmbed>() -> StaticEmbed<T> {
         _assets: PhantomData,
         defaults: vec![],
         fallback: None,
+        spa: false,
     }
 }
 
@@ -56,24 +63,31 @@ fn render_embedded_data(
     res: &mut Response,
     mime: Option<Mime>,
 ) {
-    let mime = mime.unwrap_or_else(|| mime_infer::from_path(req.uri().path()).first_or_octet_stream());
+    let mime = mime.unwrap_or_else(|| guess_content_type(Path::new(req.uri().path())));
     res.headers_mut().insert(CONTENT_TYPE, mime.as_ref().parse().unwrap());
 
-    let hash = hex::encode(metadata.sha256_hash());
+    // etag is the hash of the content, so it is always strong
+    let etag = format!("\"{}\"", hex::encode(metadata.sha256_hash()));
+    res.headers_mut().insert(ETAG, etag.parse().unwrap());
+
     // if etag is matched, return 304
-    if req
+    let matched = req
         .headers()
         .get(IF_NONE_MATCH)
-        .map(|etag| etag.to_str().unwrap_or("000000").eq(&hash))
-        .unwrap_or(false)
-    {
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value.split(',').any(|tag| {
+                let tag = tag.trim();
+                let tag = tag.strip_prefix("W/").unwrap_or(tag);
+                tag == "*" || tag == etag || tag == etag.trim_matches('"')
+            })
+        })
+        .unwrap_or(false);
+    if matched {
         res.status_code(StatusCode::NOT_MODIFIED);
         return;
     }
 
-    // otherwise, return 200 with etag hash
-    res.headers_mut().insert(ETAG, hash.parse().unwrap());
-
     match data {
         Cow::Borrowed(data) => {
             res.write_body(data).ok();
@@ -95,6 +109,7 @@ where
             _assets: PhantomData,
             defaults: vec![],
             fallback: None,
+            spa: false,
         }
     }
 
@@ -111,6 +126,17 @@ where
         self.fallback = Some(fallback.into());
         self
     }
+
+    /// Create a new `StaticEmbed` with single page application mode.
+    ///
+    /// In this mode, the fallback file, which is `index.html` if fallback is not set, is used for
+    /// unknown paths without an extension, and missing assets, such as `/app.js`, are still responded
+    /// with 404.
+    #[inline]
+    pub fn spa(mut self, spa: bool) -> Self {
+        self.spa = spa;
+        self
+    }
 }
 #[async_trait]
 impl<T> Handler for StaticEmbed<T>
@@ -142,8 +168,13 @@ where
                 return;
             }
         }
-        if embedded_file.is_none() {
-            let fallback = self.fallback.as_deref().unwrap_or_default();
+        let is_asset = self.spa && Path::new(&req_path).extension().is_some();
+        if embedded_file.is_none() && !is_asset {
+            let fallback = match self.fallback.as_deref() {
+                Some(fallback) => fallback,
+                None if self.spa => "index.html",
+                None => "",
+            };
             if !fallback.is_empty() {
                 if let Some(file) = T::get(fallback) {
                     embedded_file = Some(file);
@@ -154,7 +185,7 @@ where
 
         match embedded_file {
             Some(file) => {
-                let mime = mime_infer::from_path(&*key_path).first_or_octet_stream();
+                let mime = guess_content_type(Path::new(&*key_path));
                 render_embedded_file(file, req, res, Some(mime));
             }
             None => {
@@ -1,5 +1,7 @@
 //! serve static dir and file middleware for Savlo web server framework.
 //!
+//! Files can also be served from local disk or object storage services with [`StaticStore`].
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -18,12 +20,15 @@ use salvo_core::http::uri::{Parts as UriParts, Uri};
 use salvo_core::writing::Redirect;
 use salvo_core::Response;
 
-pub use dir::StaticDir;
+pub use dir::{ListingTemplate, MinimalListing, StaticDir};
 pub use file::StaticFile;
 
 #[macro_use]
 mod cfg;
 
+pub mod store;
+pub use store::{FileStore, LocalStore, StaticStore};
+
 cfg_feature! {
     #![feature = "embed"]
     mod embed;
@@ -60,6 +65,19 @@ pub(crate) fn format_url_path_safely(path: &str) -> String {
     used_parts.join("/")
 }
 
+/// Guess the content type of file by its path, text files use utf-8 as default charset.
+#[inline]
+pub(crate) fn guess_content_type(path: &std::path::Path) -> mime::Mime {
+    let ct = mime_infer::from_path(path).first_or_octet_stream();
+    if (ct.type_() == mime::TEXT || ct.subtype() == mime::JSON || ct.subtype() == mime::JAVASCRIPT)
+        && ct.get_param(mime::CHARSET).is_none()
+    {
+        format!("{ct}; charset=utf-8").parse::<mime::Mime>().unwrap_or(ct)
+    } else {
+        ct
+    }
+}
+
 #[inline]
 pub(crate) fn redirect_to_dir_url(req_uri: &Uri, res: &mut Response) {
     let UriParts {
@@ -172,6 +190,101 @@ mod tests {
         assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_serve_static_dir_listing_template() {
+        let router = Router::new()
+            .push(
+                Router::with_path("minimal/<*path>").get(
+                    StaticDir::new(vec!["test/static"])
+                        .listing(true)
+                        .listing_template(MinimalListing),
+                ),
+            )
+            .push(
+                Router::with_path("custom/<*path>").get(
+                    StaticDir::new(vec!["test/static"])
+                        .listing(true)
+                        .listing_template(|current: &dir::CurrentInfo| {
+                            format!("custom {} {}", current.files.len(), current.dirs.len())
+                        }),
+                ),
+            );
+        let service = Service::new(router);
+
+        async fn access(service: &Service, accept: &str, url: &str) -> String {
+            TestClient::get(url)
+                .add_header("accept", accept, true)
+                .send(service)
+                .await
+                .take_string()
+                .await
+                .unwrap()
+        }
+        let content = access(&service, "text/html", "http://127.0.0.1:5801/minimal/dir1/").await;
+        assert!(content.contains("<ul>") && content.contains(r#"<a href="./test3.txt">test3.txt</a>"#));
+        assert!(content.contains(r#"<a href="./dir2/">dir2/</a>"#) && !content.contains("<svg"));
+
+        let content = access(&service, "text/html", "http://127.0.0.1:5801/custom/dir1/").await;
+        assert_eq!(content, "custom 1 1");
+
+        let content = access(&service, "application/json", "http://127.0.0.1:5801/custom/dir1/").await;
+        let current: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(current["files"][0]["name"], "test3.txt");
+        assert_eq!(current["dirs"][0]["name"], "dir2");
+    }
+
+    #[tokio::test]
+    async fn test_serve_precompressed() {
+        let router = Router::with_path("<*path>").get(StaticDir::new(vec!["test/precompressed"]).precompressed(true));
+        let service = Service::new(router);
+
+        async fn access(service: &Service, accept_encoding: &str, url: &str) -> Response {
+            TestClient::get(url)
+                .add_header("accept-encoding", accept_encoding, true)
+                .send(service)
+                .await
+        }
+        let mut res = access(&service, "gzip, deflate", "http://127.0.0.1:5801/app.js").await;
+        assert_eq!(res.headers().get("content-encoding").unwrap(), "gzip");
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "application/javascript; charset=utf-8"
+        );
+        assert_eq!(res.headers().get("vary").unwrap(), "accept-encoding");
+        assert_eq!(res.take_string().await.unwrap(), "console.log(\"app\");\n");
+
+        let mut res = access(&service, "gzip, br", "http://127.0.0.1:5801/app.js").await;
+        assert_eq!(res.headers().get("content-encoding").unwrap(), "br");
+        assert_eq!(res.take_bytes(None).await.unwrap(), "fake brotli content");
+
+        let mut res = access(&service, "gzip;q=1.0, br;q=0.5", "http://127.0.0.1:5801/app.js").await;
+        assert_eq!(res.headers().get("content-encoding").unwrap(), "gzip");
+        assert_eq!(res.take_string().await.unwrap(), "console.log(\"app\");\n");
+
+        let mut res = access(&service, "identity", "http://127.0.0.1:5801/app.js").await;
+        assert!(res.headers().get("content-encoding").is_none());
+        assert_eq!(res.take_string().await.unwrap(), "console.log(\"app\");\n");
+
+        let res = access(&service, "gzip, br", "http://127.0.0.1:5801/index.html").await;
+        assert!(res.headers().get("content-encoding").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serve_spa() {
+        let router = Router::with_path("<*path>").get(StaticDir::new(vec!["test/precompressed"]).spa(true));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/users/12").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert!(res.take_string().await.unwrap().contains("Spa page"));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/app.js").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "console.log(\"app\");\n");
+
+        let res = TestClient::get("http://127.0.0.1:5801/missing.js").send(&service).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::NOT_FOUND);
+    }
+
     #[cfg(feature = "embed")]
     #[tokio::test]
     async fn test_serve_embed_files() {
@@ -234,4 +347,38 @@ mod tests {
             .await;
         assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
     }
+
+    #[cfg(feature = "embed")]
+    #[tokio::test]
+    async fn test_serve_embed_etag_and_spa() {
+        #[derive(rust_embed::RustEmbed)]
+        #[folder = "test/precompressed"]
+        struct Assets;
+
+        let router = Router::with_path("<*path>").get(static_embed::<Assets>().spa(true));
+        let service = Service::new(router);
+
+        let mut response = TestClient::get("http://127.0.0.1:5801/app.js").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/javascript; charset=utf-8"
+        );
+        let etag = response.headers().get("etag").unwrap().to_str().unwrap().to_owned();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(response.take_string().await.unwrap(), "console.log(\"app\");\n");
+
+        let response = TestClient::get("http://127.0.0.1:5801/app.js")
+            .add_header("if-none-match", format!("\"other\", W/{etag}"), true)
+            .send(&service)
+            .await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::NOT_MODIFIED);
+
+        let mut response = TestClient::get("http://127.0.0.1:5801/users/12").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::OK);
+        assert!(response.take_string().await.unwrap().contains("Spa page"));
+
+        let response = TestClient::get("http://127.0.0.1:5801/missing.js").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,64 @@
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, SeekFrom};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use futures_util::StreamExt;
+use salvo_core::async_trait;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::{FileMeta, FileStore, FileStream};
+use crate::format_url_path_safely;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// [`FileStore`] reads files in a local directory.
+#[derive(Clone, Debug)]
+pub struct LocalStore {
+    root: PathBuf,
+}
+impl LocalStore {
+    /// Create a new `LocalStore` serves files in `root`.
+    #[inline]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn full_path(&self, path: &str) -> PathBuf {
+        self.root.join(format_url_path_safely(path))
+    }
+}
+
+#[async_trait]
+impl FileStore for LocalStore {
+    async fn meta(&self, path: &str) -> IoResult<FileMeta> {
+        let metadata = tokio::fs::metadata(self.full_path(path)).await?;
+        if !metadata.is_file() {
+            return Err(IoError::new(ErrorKind::NotFound, "not a file"));
+        }
+        let mut meta = FileMeta::new(metadata.len());
+        if let Ok(modified) = metadata.modified() {
+            meta = meta.last_modified(modified);
+            if let Ok(dur) = modified.duration_since(UNIX_EPOCH) {
+                meta = meta.etag(format!(
+                    "\"{:x}-{:x}-{:x}\"",
+                    metadata.len(),
+                    dur.as_secs(),
+                    dur.subsec_nanos()
+                ));
+            }
+        }
+        Ok(meta)
+    }
+
+    async fn read(&self, path: &str, range: Range<u64>) -> IoResult<FileStream> {
+        let mut file = File::open(self.full_path(path)).await?;
+        if range.start > 0 {
+            file.seek(SeekFrom::Start(range.start)).await?;
+        }
+        let reader = file.take(range.end.saturating_sub(range.start));
+        Ok(ReaderStream::with_capacity(reader, CHUNK_SIZE).boxed())
+    }
+}
@@ -0,0 +1,399 @@
+//! Serve static files from pluggable storage backends.
+//!
+//! [`StaticStore`] serves files from any [`FileStore`], such as [`LocalStore`] for local disk and, with features
+//! `s3`, `gcs` or `azure`, `ObjectFileStore` for object storage services. Files are streamed, range and
+//! conditional requests are supported.
+use std::io::{ErrorKind, Result as IoResult};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use futures_util::stream::BoxStream;
+use salvo_core::http::header::{HeaderName, HeaderValue, CACHE_CONTROL, RANGE};
+use salvo_core::http::headers::{
+    AcceptRanges, ContentLength, ContentRange, ContentType, ETag, HeaderMapExt, IfMatch, IfModifiedSince, IfNoneMatch,
+    IfUnmodifiedSince, LastModified,
+};
+use salvo_core::http::{HeaderMap, HttpRange, Method, Mime, Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, IntoVecString};
+
+use super::{decode_url_path_safely, format_url_path_safely, guess_content_type, redirect_to_dir_url};
+
+mod local;
+pub use local::LocalStore;
+
+cfg_feature! {
+    #![feature = "object-store"]
+    mod object;
+    pub use object::ObjectFileStore;
+    pub use object_store;
+}
+
+/// Header name of `CDN-Cache-Control` defined in [RFC 9213](https://www.rfc-editor.org/rfc/rfc9213).
+pub const CDN_CACHE_CONTROL: HeaderName = HeaderName::from_static("cdn-cache-control");
+
+/// Stream of file content returned by [`FileStore::read`].
+pub type FileStream = BoxStream<'static, IoResult<Bytes>>;
+
+/// Metadata of a stored file.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct FileMeta {
+    /// File size in bytes.
+    pub size: u64,
+    /// Last modified time.
+    pub last_modified: Option<SystemTime>,
+    /// Entity tag of the file, it is quoted if not.
+    pub etag: Option<String>,
+    /// Content type of the file, it is guessed from path if it is `None`.
+    pub content_type: Option<Mime>,
+}
+impl FileMeta {
+    /// Create a new `FileMeta` with file size.
+    #[inline]
+    pub fn new(size: u64) -> Self {
+        Self {
+            size,
+            ..Default::default()
+        }
+    }
+    /// Sets last modified time.
+    #[inline]
+    pub fn last_modified(mut self, last_modified: SystemTime) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+    /// Sets entity tag.
+    #[inline]
+    pub fn etag(mut self, etag: impl Into<String>) -> Self {
+        self.etag = Some(etag.into());
+        self
+    }
+    /// Sets content type.
+    #[inline]
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+}
+
+/// Async storage of static files.
+///
+/// Paths are relative and separated by `/`, an error with kind [`ErrorKind::NotFound`] should be returned if
+/// the file does not exist.
+#[async_trait]
+pub trait FileStore: Send + Sync + 'static {
+    /// Get metadata of the file.
+    async fn meta(&self, path: &str) -> IoResult<FileMeta>;
+    /// Read bytes in `range` of the file.
+    async fn read(&self, path: &str, range: Range<u64>) -> IoResult<FileStream>;
+}
+
+/// Handler that serves files from a [`FileStore`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_serve_static::store::{LocalStore, StaticStore};
+///
+/// let router = Router::with_path("<*path>").get(
+///     StaticStore::new(LocalStore::new("static"))
+///         .defaults("index.html")
+///         .cache_control("public, max-age=60")
+///         .edge_cache_control("max-age=86400"),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct StaticStore {
+    store: Arc<dyn FileStore>,
+    defaults: Vec<String>,
+    cache_control: Option<HeaderValue>,
+    edge_cache_control: Option<HeaderValue>,
+}
+impl std::fmt::Debug for StaticStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticStore")
+            .field("defaults", &self.defaults)
+            .field("cache_control", &self.cache_control)
+            .field("edge_cache_control", &self.edge_cache_control)
+            .finish()
+    }
+}
+impl StaticStore {
+    /// Create a new `StaticStore`.
+    #[inline]
+    pub fn new(store: impl FileStore) -> Self {
+        Self::with_shared(Arc::new(store))
+    }
+    /// Create a new `StaticStore` with a shared store.
+    #[inline]
+    pub fn with_shared(store: Arc<dyn FileStore>) -> Self {
+        Self {
+            store,
+            defaults: vec![],
+            cache_control: None,
+            edge_cache_control: None,
+        }
+    }
+    /// Sets default file names list, they are used when a directory is requested.
+    #[inline]
+    pub fn defaults(mut self, defaults: impl IntoVecString) -> Self {
+        self.defaults = defaults.into_vec_string();
+        self
+    }
+    /// Sets `Cache-Control` header of served files, such as `public, max-age=3600`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    #[inline]
+    pub fn cache_control(mut self, value: impl AsRef<str>) -> Self {
+        self.cache_control = Some(HeaderValue::from_str(value.as_ref()).expect("invalid `cache-control` value"));
+        self
+    }
+    /// Sets `CDN-Cache-Control` header of served files, it is only respected by CDNs and edge caches, so static
+    /// assets can be cached longer at the edge than in browsers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    #[inline]
+    pub fn edge_cache_control(mut self, value: impl AsRef<str>) -> Self {
+        self.edge_cache_control =
+            Some(HeaderValue::from_str(value.as_ref()).expect("invalid `cdn-cache-control` value"));
+        self
+    }
+
+    async fn find(&self, req_path: &str) -> IoResult<Option<(String, FileMeta)>> {
+        if !req_path.is_empty() {
+            match self.store.meta(req_path).await {
+                Ok(meta) => return Ok(Some((req_path.to_owned(), meta))),
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        for default in &self.defaults {
+            let path = if req_path.is_empty() {
+                default.clone()
+            } else {
+                format!("{req_path}/{default}")
+            };
+            match self.store.meta(&path).await {
+                Ok(meta) => return Ok(Some((path, meta))),
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    async fn send(&self, path: &str, meta: FileMeta, req: &Request, res: &mut Response) {
+        let req_headers = req.headers();
+        let etag = meta.etag.as_deref().and_then(|etag| {
+            let etag = if etag.starts_with('"') || etag.starts_with("W/\"") {
+                etag.parse::<ETag>()
+            } else {
+                format!("\"{etag}\"").parse::<ETag>()
+            };
+            etag.map_err(|e| tracing::warn!(error = ?e, path, "invalid etag of stored file"))
+                .ok()
+        });
+
+        let content_type = meta
+            .content_type
+            .clone()
+            .unwrap_or_else(|| guess_content_type(Path::new(path)));
+        let headers = res.headers_mut();
+        headers.typed_insert(ContentType::from(content_type));
+        if let Some(last_modified) = meta.last_modified {
+            headers.typed_insert(LastModified::from(last_modified));
+        }
+        if let Some(etag) = etag.clone() {
+            headers.typed_insert(etag);
+        }
+        headers.typed_insert(AcceptRanges::bytes());
+        if let Some(value) = &self.cache_control {
+            headers.insert(CACHE_CONTROL, value.clone());
+        }
+        if let Some(value) = &self.edge_cache_control {
+            headers.insert(CDN_CACHE_CONTROL, value.clone());
+        }
+
+        if precondition_failed(etag.as_ref(), meta.last_modified, req_headers) {
+            res.status_code(StatusCode::PRECONDITION_FAILED);
+            return;
+        }
+        if not_modified(etag.as_ref(), meta.last_modified, req_headers) {
+            res.status_code(StatusCode::NOT_MODIFIED);
+            return;
+        }
+
+        let mut range = 0..meta.size;
+        if let Some(value) = req_headers.get(RANGE) {
+            let Ok(value) = value.to_str() else {
+                res.status_code(StatusCode::BAD_REQUEST);
+                return;
+            };
+            match HttpRange::parse(value, meta.size) {
+                Ok(ranges) if !ranges.is_empty() => {
+                    range = ranges[0].start..ranges[0].start + ranges[0].length;
+                    match ContentRange::bytes(range.clone(), meta.size) {
+                        Ok(content_range) => res.headers_mut().typed_insert(content_range),
+                        Err(e) => tracing::error!(error = ?e, "set file's content range failed"),
+                    }
+                    res.status_code(StatusCode::PARTIAL_CONTENT);
+                }
+                _ => {
+                    res.headers_mut()
+                        .typed_insert(ContentRange::unsatisfied_bytes(meta.size));
+                    res.status_code(StatusCode::RANGE_NOT_SATISFIABLE);
+                    return;
+                }
+            }
+        } else {
+            res.status_code(StatusCode::OK);
+        }
+        res.headers_mut().typed_insert(ContentLength(range.end - range.start));
+        if req.method() == Method::HEAD || range.is_empty() {
+            return;
+        }
+        match self.store.read(path, range).await {
+            Ok(stream) => res.stream(stream),
+            Err(e) => {
+                tracing::error!(error = ?e, path, "read stored file failed");
+                res.headers_mut().clear();
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for StaticStore {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let param = req.params().iter().find(|(key, _)| key.starts_with('*'));
+        let req_path = if let Some((_, value)) = param {
+            value.clone()
+        } else {
+            decode_url_path_safely(req.uri().path())
+        };
+        let req_path = format_url_path_safely(&req_path);
+        match self.find(&req_path).await {
+            Ok(Some((path, meta))) => {
+                if path != req_path && !req_path.is_empty() && !req.uri().path().ends_with('/') {
+                    redirect_to_dir_url(req.uri(), res);
+                    return;
+                }
+                self.send(&path, meta, req, res).await;
+            }
+            Ok(None) => {
+                res.render(StatusError::not_found());
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, path = req_path, "get stored file metadata failed");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+fn precondition_failed(etag: Option<&ETag>, last_modified: Option<SystemTime>, req_headers: &HeaderMap) -> bool {
+    if let Some(if_match) = req_headers.typed_get::<IfMatch>() {
+        if if_match == IfMatch::any() {
+            false
+        } else if let Some(etag) = etag {
+            !if_match.precondition_passes(etag)
+        } else {
+            true
+        }
+    } else if let (Some(last_modified), Some(since)) = (last_modified, req_headers.typed_get::<IfUnmodifiedSince>()) {
+        !since.precondition_passes(last_modified)
+    } else {
+        false
+    }
+}
+
+fn not_modified(etag: Option<&ETag>, last_modified: Option<SystemTime>, req_headers: &HeaderMap) -> bool {
+    if let Some(if_none_match) = req_headers.typed_get::<IfNoneMatch>() {
+        if if_none_match == IfNoneMatch::any() {
+            true
+        } else if let Some(etag) = etag {
+            !if_none_match.precondition_passes(etag)
+        } else {
+            false
+        }
+    } else if let (Some(last_modified), Some(since)) = (last_modified, req_headers.typed_get::<IfModifiedSince>()) {
+        !since.is_modified(last_modified)
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::{ETAG, IF_NONE_MATCH};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_store() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/index.html"), "<h1>docs</h1>").unwrap();
+        std::fs::write(dir.path().join("hello.txt"), "hello world").unwrap();
+        let router = Router::with_path("<*path>").get(
+            StaticStore::new(LocalStore::new(dir.path()))
+                .defaults("index.html")
+                .cache_control("public, max-age=60")
+                .edge_cache_control("max-age=3600"),
+        );
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello.txt").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers().get(CACHE_CONTROL).unwrap(), "public, max-age=60");
+        assert_eq!(res.headers().get(CDN_CACHE_CONTROL).unwrap(), "max-age=3600");
+        assert_eq!(res.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
+        let etag = res.headers().get(ETAG).unwrap().clone();
+        assert_eq!(res.take_string().await.unwrap(), "hello world");
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello.txt")
+            .add_header(IF_NONE_MATCH, etag, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello.txt")
+            .add_header(RANGE, "bytes=6-", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.headers().get("content-range").unwrap(), "bytes 6-10/11");
+        assert_eq!(res.take_string().await.unwrap(), "world");
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello.txt")
+            .add_header(RANGE, "bytes=20-", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::RANGE_NOT_SATISFIABLE));
+
+        let res = TestClient::get("http://127.0.0.1:5801/docs").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::FOUND));
+        let mut res = TestClient::get("http://127.0.0.1:5801/docs/").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "<h1>docs</h1>");
+
+        let res = TestClient::get("http://127.0.0.1:5801/missing.txt")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        let res = TestClient::get("http://127.0.0.1:5801/../Cargo.toml")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+}
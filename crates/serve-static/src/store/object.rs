@@ -0,0 +1,117 @@
+use std::io::{Error as IoError, Result as IoResult};
+use std::ops::Range;
+use std::sync::Arc;
+
+use futures_util::{StreamExt, TryStreamExt};
+use object_store::path::Path as ObjectPath;
+use object_store::{GetOptions, ObjectStore};
+use salvo_core::async_trait;
+
+use super::{FileMeta, FileStore, FileStream};
+
+/// [`FileStore`] reads objects from object storage services, such as Amazon S3, Google Cloud Storage and Azure
+/// Blob Storage, with [`object_store`].
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "s3")]
+/// # fn build() -> Result<(), Box<dyn std::error::Error>> {
+/// use salvo_serve_static::store::object_store::aws::AmazonS3Builder;
+/// use salvo_serve_static::store::{ObjectFileStore, StaticStore};
+///
+/// let s3 = AmazonS3Builder::from_env().with_bucket_name("assets").build()?;
+/// let handler = StaticStore::new(ObjectFileStore::new(s3).prefix("public"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ObjectFileStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: Option<ObjectPath>,
+}
+impl ObjectFileStore {
+    /// Create a new `ObjectFileStore`.
+    #[inline]
+    pub fn new(store: impl ObjectStore) -> Self {
+        Self::with_shared(Arc::new(store))
+    }
+    /// Create a new `ObjectFileStore` with a shared store.
+    #[inline]
+    pub fn with_shared(store: Arc<dyn ObjectStore>) -> Self {
+        Self { store, prefix: None }
+    }
+    /// Sets prefix of object keys, files are served from `{prefix}/{path}`.
+    #[inline]
+    pub fn prefix(mut self, prefix: impl AsRef<str>) -> Self {
+        self.prefix = Some(ObjectPath::from(prefix.as_ref()));
+        self
+    }
+
+    fn location(&self, path: &str) -> IoResult<ObjectPath> {
+        let path = ObjectPath::parse(path).map_err(IoError::other)?;
+        Ok(match &self.prefix {
+            Some(prefix) => prefix.parts().chain(path.parts()).collect(),
+            None => path,
+        })
+    }
+}
+
+#[async_trait]
+impl FileStore for ObjectFileStore {
+    async fn meta(&self, path: &str) -> IoResult<FileMeta> {
+        let meta = self.store.head(&self.location(path)?).await?;
+        let mut file_meta = FileMeta::new(meta.size as u64).last_modified(meta.last_modified.into());
+        if let Some(etag) = meta.e_tag {
+            file_meta = file_meta.etag(etag);
+        }
+        Ok(file_meta)
+    }
+
+    async fn read(&self, path: &str, range: Range<u64>) -> IoResult<FileStream> {
+        let options = GetOptions {
+            range: Some((range.start as usize..range.end as usize).into()),
+            ..Default::default()
+        };
+        let result = self.store.get_opts(&self.location(path)?, options).await?;
+        Ok(result.into_stream().map_err(IoError::from).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+    use salvo_core::http::header::RANGE;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::store::StaticStore;
+
+    #[tokio::test]
+    async fn test_object_file_store() {
+        let memory = InMemory::new();
+        memory
+            .put(&ObjectPath::from("public/app.js"), "console.log(1);".into())
+            .await
+            .unwrap();
+        let router = Router::with_path("<*path>")
+            .get(StaticStore::new(ObjectFileStore::new(memory).prefix("public")).defaults("index.html"));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/app.js").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(res.headers().contains_key("etag"));
+        assert_eq!(res.take_string().await.unwrap(), "console.log(1);");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/app.js")
+            .add_header(RANGE, "bytes=0-6", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.take_string().await.unwrap(), "console");
+
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+}
@@ -14,8 +14,10 @@ duration of a browser session.
 ## Stores
 
 It is highly recommended that salvo applications use an
-external-datastore-backed session storage. For a list of currently
-available session stores, see [the documentation for
+external-datastore-backed session storage. This crate provides
+`RedisStore` (feature `redis-store`), `PostgresStore` (feature
+`postgres-store`) and `SqliteStore` (feature `sqlite-store`). For a list of
+other available session stores, see [the documentation for
 async-session](https://github.com/http-rs/async-session).
 
 ## Security
@@ -49,6 +51,26 @@ adversary were able to tamper with the expiry of a cookie, salvo
 sessions would still check the expiry on the contained session before
 using it
 
+Sessions have an idle expiry, configured by `session_ttl`, which is
+extended on every request. An absolute expiry, configured by
+`absolute_ttl`, can be set to limit the total lifetime of a session
+no matter how active it is.
+
+### Rotation
+
+When the privilege of the user changes, such as login or logout, the
+session id should be rotated to prevent session fixation. Use
+`SessionDepotExt::cycle_session_id` to rotate the id while keeping the
+session data, or `SessionDepotExt::renew_session` to start a new empty
+session. The session stored with the previous id is destroyed.
+
+### Remember me
+
+The [`remember_me`] module provides [`RememberMe`](remember_me::RememberMe)
+middleware, which logs users in again with a persistent cookie after the
+session is expired. Tokens are rotated on every use and all tokens of the
+user are invalidated when a stolen token is detected.
+
 ### If anything goes wrong with the above process
 
 If there are any failures in the above session retrieval process, a
@@ -80,14 +102,34 @@ use std::fmt::{self, Formatter};
 use std::time::Duration;
 
 use async_session::base64;
+use async_session::chrono::Utc;
 use async_session::hmac::{Hmac, Mac, NewMac};
 use async_session::sha2::Sha256;
 use cookie::{Cookie, Key, SameSite};
 use salvo_core::http::uri::Scheme;
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
 
+pub mod remember_me;
+
+#[cfg(feature = "redis-store")]
+mod redis_store;
+#[cfg(feature = "redis-store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redis-store")))]
+pub use redis_store::RedisStore;
+
+#[cfg(any(feature = "postgres-store", feature = "sqlite-store"))]
+mod sqlx_store;
+#[cfg(feature = "postgres-store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres-store")))]
+pub use sqlx_store::PostgresStore;
+#[cfg(feature = "sqlite-store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite-store")))]
+pub use sqlx_store::SqliteStore;
+
 /// Key for store data in depot.
 pub const SESSION_KEY: &str = "::salvo::session";
+/// Key for store the creation time of session, it is used to check absolute expiry.
+pub const SESSION_CREATED_AT_KEY: &str = "::salvo::session::created_at";
 const BASE64_DIGEST_LEN: usize = 44;
 
 /// Trait for `Depot` to get and set session.
@@ -100,6 +142,14 @@ pub trait SessionDepotExt {
     fn session(&self) -> Option<&Session>;
     /// Get session mutable reference
     fn session_mut(&mut self) -> Option<&mut Session>;
+    /// Replace current session with a new empty session, the old session will be destroyed.
+    fn renew_session(&mut self) -> &mut Session;
+    /// Generate a new id for current session and keep its data, the session stored with
+    /// the old id will be destroyed. The session is stored with the new id even if its data
+    /// is not changed.
+    ///
+    /// Returns `None` if there is no session in depot.
+    fn cycle_session_id(&mut self) -> Option<&mut Session>;
 }
 
 impl SessionDepotExt for Depot {
@@ -120,6 +170,17 @@ impl SessionDepotExt for Depot {
     fn session_mut(&mut self) -> Option<&mut Session> {
         self.get_mut(SESSION_KEY).ok()
     }
+    #[inline]
+    fn renew_session(&mut self) -> &mut Session {
+        self.set_session(Session::new());
+        self.session_mut().expect("session should exist in depot")
+    }
+    #[inline]
+    fn cycle_session_id(&mut self) -> Option<&mut Session> {
+        let session = self.session_mut()?;
+        session.regenerate();
+        Some(session)
+    }
 }
 
 /// `HandlerBuilder` is a builder for [`SessionHandler`].
@@ -129,6 +190,7 @@ pub struct HandlerBuilder<S> {
     cookie_name: String,
     cookie_domain: Option<String>,
     session_ttl: Option<Duration>,
+    absolute_ttl: Option<Duration>,
     save_unchanged: bool,
     same_site_policy: SameSite,
     key: Key,
@@ -143,6 +205,7 @@ impl<S: SessionStore> fmt::Debug for HandlerBuilder<S> {
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
             .field("session_ttl", &self.session_ttl)
+            .field("absolute_ttl", &self.absolute_ttl)
             .field("same_site_policy", &self.same_site_policy)
             .field("key", &"..")
             .field("fallback_keys", &"..")
@@ -166,6 +229,7 @@ where
             cookie_domain: None,
             same_site_policy: SameSite::Lax,
             session_ttl: Some(Duration::from_secs(24 * 60 * 60)),
+            absolute_ttl: None,
             key: Key::from(secret),
             fallback_keys: vec![],
         }
@@ -191,6 +255,21 @@ where
         self
     }
 
+    /// Sets a session absolute ttl.
+    ///
+    /// Unlike `session_ttl`, which is the idle timeout and is extended on every
+    /// request, the absolute ttl limits the whole lifetime of a session since it was
+    /// created. Expired sessions are destroyed and replaced by new empty sessions.
+    ///
+    /// The default for this value is None, which means sessions have no absolute expiry.
+    /// When it is set, the creation time is saved in session data with key
+    /// [`SESSION_CREATED_AT_KEY`].
+    #[inline]
+    pub fn absolute_ttl(mut self, absolute_ttl: Option<Duration>) -> Self {
+        self.absolute_ttl = absolute_ttl;
+        self
+    }
+
     /// Sets the name of the cookie that the session is stored with or in.
     ///
     /// If you are running multiple tide applications on the same
@@ -257,6 +336,7 @@ where
             cookie_name,
             cookie_domain,
             session_ttl,
+            absolute_ttl,
             same_site_policy,
             key,
             fallback_keys,
@@ -275,6 +355,7 @@ where
             cookie_name,
             cookie_domain,
             session_ttl,
+            absolute_ttl,
             same_site_policy,
             hmac,
             fallback_hmacs,
@@ -289,6 +370,7 @@ pub struct SessionHandler<S> {
     cookie_name: String,
     cookie_domain: Option<String>,
     session_ttl: Option<Duration>,
+    absolute_ttl: Option<Duration>,
     save_unchanged: bool,
     same_site_policy: SameSite,
     hmac: Hmac<Sha256>,
@@ -303,6 +385,7 @@ impl<S: SessionStore> fmt::Debug for SessionHandler<S> {
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
             .field("session_ttl", &self.session_ttl)
+            .field("absolute_ttl", &self.absolute_ttl)
             .field("same_site_policy", &self.same_site_policy)
             .field("key", &"..")
             .field("fallback_keys", &"..")
@@ -319,7 +402,7 @@ where
         let cookie = req.cookies().get(&self.cookie_name);
         let cookie_value = cookie.and_then(|cookie| self.verify_signature(cookie.value()).ok());
 
-        let mut session = self.load_or_create(cookie_value).await;
+        let (mut session, loaded) = self.load_or_create(cookie_value).await;
 
         if let Some(ttl) = self.session_ttl {
             session.expire_in(ttl);
@@ -332,23 +415,39 @@ where
             return;
         }
 
-        let session = depot.take_session().expect("session should exist in depot");
-        if session.is_destroyed() {
-            if let Err(e) = self.store.destroy_session(session).await {
-                tracing::error!(error = ?e, "unable to destroy session");
+        let mut session = depot.take_session().expect("session should exist in depot");
+        // A session with new id must be stored even if its data is not changed, the old one is destroyed.
+        let rotated = matches!(&loaded, Some(loaded) if loaded.id() != session.id());
+        if let Some(loaded) = loaded {
+            if loaded.id() != session.id() || session.is_destroyed() {
+                if let Err(e) = self.store.destroy_session(loaded).await {
+                    tracing::error!(error = ?e, "unable to destroy session");
+                }
             }
+        }
+        if session.is_destroyed() {
             res.remove_cookie(&self.cookie_name);
-        } else if self.save_unchanged || session.data_changed() {
-            match self.store.store_session(session).await {
-                Ok(cookie_value) => {
-                    if let Some(cookie_value) = cookie_value {
-                        let secure_cookie = req.uri().scheme() == Some(&Scheme::HTTPS);
-                        let cookie = self.build_cookie(secure_cookie, cookie_value);
-                        res.add_cookie(cookie);
-                    }
+        } else {
+            if session.expiry().is_none() {
+                if let Some(ttl) = self.session_ttl {
+                    session.expire_in(ttl);
                 }
-                Err(e) => {
-                    tracing::error!(error = ?e, "store session error");
+            }
+            if self.absolute_ttl.is_some() && session.get_raw(SESSION_CREATED_AT_KEY).is_none() {
+                session.insert_raw(SESSION_CREATED_AT_KEY, Utc::now().timestamp().to_string());
+            }
+            if self.save_unchanged || rotated || session.data_changed() {
+                match self.store.store_session(session).await {
+                    Ok(cookie_value) => {
+                        if let Some(cookie_value) = cookie_value {
+                            let secure_cookie = req.uri().scheme() == Some(&Scheme::HTTPS);
+                            let cookie = self.build_cookie(secure_cookie, cookie_value);
+                            res.add_cookie(cookie);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!(error = ?e, "store session error");
+                    }
                 }
             }
         }
@@ -363,14 +462,37 @@ where
     pub fn builder(store: S, secret: &[u8]) -> HandlerBuilder<S> {
         HandlerBuilder::new(store, secret)
     }
+    /// Returns the session used by current request, and the session loaded from store if it exists.
     #[inline]
-    async fn load_or_create(&self, cookie_value: Option<String>) -> Session {
+    async fn load_or_create(&self, cookie_value: Option<String>) -> (Session, Option<Session>) {
         let session = match cookie_value {
             Some(cookie_value) => self.store.load_session(cookie_value).await.ok().flatten(),
             None => None,
         };
-
-        session.and_then(|session| session.validate()).unwrap_or_default()
+        let session = match session.and_then(|session| session.validate()) {
+            Some(session) => session,
+            None => return (Session::new(), None),
+        };
+        if self.is_absolute_expired(&session) {
+            if let Err(e) = self.store.destroy_session(session).await {
+                tracing::error!(error = ?e, "unable to destroy session");
+            }
+            (Session::new(), None)
+        } else {
+            (session.clone(), Some(session))
+        }
+    }
+    #[inline]
+    fn is_absolute_expired(&self, session: &Session) -> bool {
+        let (Some(ttl), Some(created_at)) = (
+            self.absolute_ttl,
+            session
+                .get_raw(SESSION_CREATED_AT_KEY)
+                .and_then(|v| v.parse::<i64>().ok()),
+        ) else {
+            return false;
+        };
+        created_at.saturating_add(ttl.as_secs() as i64) <= Utc::now().timestamp()
     }
     // the following is reused verbatim from
     // https://github.com/SergioBenitez/cookie-rs/blob/master/src/secure/signed.rs#L51-L66
@@ -538,4 +660,175 @@ mod tests {
         let mut respone = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
         assert_eq!(respone.take_string().await.unwrap(), "home");
     }
+
+    #[tokio::test]
+    async fn test_session_rotation() {
+        #[handler]
+        pub async fn login(depot: &mut Depot, res: &mut Response) {
+            let session = depot.cycle_session_id().unwrap();
+            session.insert("username", "salvo").unwrap();
+            res.render(Text::Plain("login"));
+        }
+        #[handler]
+        pub async fn renew(depot: &mut Depot, res: &mut Response) {
+            depot.renew_session();
+            res.render(Text::Plain("renew"));
+        }
+        #[handler]
+        pub async fn home(depot: &mut Depot, res: &mut Response) {
+            let username = depot
+                .session()
+                .and_then(|s| s.get::<String>("username"))
+                .unwrap_or_else(|| "guest".into());
+            res.render(Text::Plain(username));
+        }
+
+        let store = MemoryStore::new();
+        let session_handler = SessionHandler::builder(
+            store.clone(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .build()
+        .unwrap();
+        let router = Router::new()
+            .hoop(session_handler)
+            .get(home)
+            .push(Router::with_path("login").get(login))
+            .push(Router::with_path("renew").get(renew));
+        let service = Service::new(router);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        let guest_cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+        assert_eq!(store.count().await, 1);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/login")
+            .add_header(COOKIE, &guest_cookie, true)
+            .send(&service)
+            .await;
+        let user_cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+        assert_ne!(guest_cookie, user_cookie);
+        assert_eq!(store.count().await, 1);
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &guest_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "guest");
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "salvo");
+
+        let respone = TestClient::get("http://127.0.0.1:5800/renew")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        let renewed_cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &renewed_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "guest");
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "guest");
+    }
+
+    #[tokio::test]
+    async fn test_session_rotate_only() {
+        #[handler]
+        pub async fn login(depot: &mut Depot, res: &mut Response) {
+            let session = depot.session_mut().unwrap();
+            session.insert("username", "salvo").unwrap();
+            res.render(Text::Plain("login"));
+        }
+        #[handler]
+        pub async fn rotate(depot: &mut Depot, res: &mut Response) {
+            depot.cycle_session_id().unwrap();
+            res.render(Text::Plain("rotate"));
+        }
+        #[handler]
+        pub async fn home(depot: &mut Depot, res: &mut Response) {
+            let username = depot
+                .session()
+                .and_then(|s| s.get::<String>("username"))
+                .unwrap_or_else(|| "guest".into());
+            res.render(Text::Plain(username));
+        }
+
+        let store = MemoryStore::new();
+        let session_handler = SessionHandler::builder(
+            store.clone(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .save_unchanged(false)
+        .build()
+        .unwrap();
+        let router = Router::new()
+            .hoop(session_handler)
+            .get(home)
+            .push(Router::with_path("login").get(login))
+            .push(Router::with_path("rotate").get(rotate));
+        let service = Service::new(router);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/login").send(&service).await;
+        let user_cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+
+        let respone = TestClient::get("http://127.0.0.1:5800/rotate")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        let rotated_cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+        assert_ne!(user_cookie, rotated_cookie);
+        assert_eq!(store.count().await, 1);
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &rotated_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "salvo");
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "guest");
+    }
+
+    #[tokio::test]
+    async fn test_session_absolute_ttl() {
+        #[handler]
+        pub async fn home(depot: &mut Depot, res: &mut Response) {
+            let session = depot.session_mut().unwrap();
+            let count = session.get::<usize>("count").unwrap_or_default() + 1;
+            session.insert("count", count).unwrap();
+            res.render(Text::Plain(count.to_string()));
+        }
+
+        let session_handler = SessionHandler::builder(
+            MemoryStore::new(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .absolute_ttl(Some(Duration::from_secs(1)))
+        .build()
+        .unwrap();
+        let service = Service::new(Router::new().hoop(session_handler).get(home));
+
+        let respone = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        let cookie = respone.headers().get(SET_COOKIE).unwrap().clone();
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "2");
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "1");
+    }
 }
@@ -0,0 +1,136 @@
+use async_session::{async_trait, serde_json, Result, Session, SessionStore};
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Client, IntoConnectionInfo};
+
+/// Session store backed by redis.
+///
+/// Sessions are stored as json strings, and redis expires them by their expiry. Keys are prefixed with
+/// [`RedisStore::DEFAULT_PREFIX`] by default, so [`SessionStore::clear_store`] only removes sessions, other keys in the same
+/// database are kept.
+#[derive(Clone)]
+pub struct RedisStore {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl std::fmt::Debug for RedisStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStore").field("prefix", &self.prefix).finish()
+    }
+}
+
+impl RedisStore {
+    /// The default prefix of the keys used to store sessions.
+    pub const DEFAULT_PREFIX: &'static str = "salvo-session:";
+
+    /// Create new `RedisStore` from a redis connection manager.
+    #[inline]
+    pub fn new(conn: ConnectionManager) -> Self {
+        Self {
+            conn,
+            prefix: Self::DEFAULT_PREFIX.into(),
+        }
+    }
+
+    /// Create new `RedisStore` from a redis client.
+    pub async fn from_client(client: Client) -> Result<Self> {
+        Ok(Self::new(ConnectionManager::new(client).await?))
+    }
+
+    /// Create new `RedisStore` from a redis connection info, such as `redis://127.0.0.1`.
+    pub async fn connect(info: impl IntoConnectionInfo) -> Result<Self> {
+        Self::from_client(Client::open(info)?).await
+    }
+
+    /// Sets the prefix of the keys used to store sessions, the default is [`RedisStore::DEFAULT_PREFIX`].
+    ///
+    /// The prefix should not be empty, otherwise [`SessionStore::clear_store`] removes all keys in the database.
+    #[inline]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn prefix_key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+
+    /// Returns the keys of all stored sessions, they are iterated by `SCAN`, so redis is not blocked.
+    async fn keys(&self) -> Result<Vec<String>> {
+        let mut pattern = String::with_capacity(self.prefix.len() + 1);
+        for ch in self.prefix.chars() {
+            if matches!(ch, '*' | '?' | '[' | ']' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(ch);
+        }
+        pattern.push('*');
+        let mut conn = self.conn.clone();
+        let mut iter = conn.scan_match::<_, String>(pattern).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+
+    /// Returns the ids of all stored sessions.
+    pub async fn ids(&self) -> Result<Vec<String>> {
+        let prefix_len = self.prefix.len();
+        Ok(self
+            .keys()
+            .await?
+            .into_iter()
+            .map(|key| key[prefix_len..].to_owned())
+            .collect())
+    }
+
+    /// Returns the number of stored sessions.
+    pub async fn count(&self) -> Result<usize> {
+        Ok(self.keys().await?.len())
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisStore {
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>> {
+        let id = Session::id_from_cookie_value(&cookie_value)?;
+        let mut conn = self.conn.clone();
+        let record: Option<String> = conn.get(self.prefix_key(&id)).await?;
+        match record {
+            Some(value) => Ok(serde_json::from_str::<Session>(&value)?.validate()),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>> {
+        let key = self.prefix_key(session.id());
+        let value = serde_json::to_string(&session)?;
+        let mut conn = self.conn.clone();
+        match session.expires_in() {
+            Some(expiry) => {
+                conn.set_ex::<_, _, ()>(key, value, expiry.as_secs().max(1) as usize)
+                    .await?
+            }
+            None => conn.set::<_, _, ()>(key, value).await?,
+        }
+        session.reset_data_changed();
+        Ok(session.into_cookie_value())
+    }
+
+    async fn destroy_session(&self, session: Session) -> Result {
+        let mut conn = self.conn.clone();
+        conn.del::<_, ()>(self.prefix_key(session.id())).await?;
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> Result {
+        let keys = self.keys().await?;
+        let mut conn = self.conn.clone();
+        // `UNLINK` frees memory in background, keys are removed in batches to keep commands small.
+        for keys in keys.chunks(1000) {
+            redis::cmd("UNLINK").arg(keys).query_async::<_, ()>(&mut conn).await?;
+        }
+        Ok(())
+    }
+}
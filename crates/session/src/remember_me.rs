@@ -0,0 +1,520 @@
+//! Persistent login with remember-me cookies.
+//!
+//! The remember-me cookie contains a `series` identifier and a `token`. The series is created when a user logs
+//! in and stays the same for this browser, while the token is rotated on every use. Only a SHA256 digest of the
+//! token is saved in the [`TokenStore`].
+//!
+//! When a request comes with a known series but a wrong token, the cookie has been stolen and used by someone
+//! else, so all persistent tokens of the user are removed and the user has to log in again.
+use std::collections::HashMap;
+use std::fmt::{self, Formatter};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use async_session::base64;
+use async_session::chrono::Utc;
+use async_session::sha2::{Digest, Sha256};
+use cookie::{Cookie, SameSite};
+use rand::RngCore;
+use salvo_core::http::uri::Scheme;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
+
+use crate::SessionDepotExt;
+
+/// Key for store remember-me state in depot.
+pub const REMEMBER_ME_KEY: &str = "::salvo::session::remember_me";
+
+/// Persistent login token saved in [`TokenStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PersistentToken {
+    /// Series identifier, it stays the same for a login in a browser.
+    pub series: String,
+    /// SHA256 digest of the current token.
+    pub token_hash: String,
+    /// The user this token belongs to.
+    pub user: String,
+    /// Expiry of the token as unix timestamp.
+    pub expires_at: i64,
+}
+impl PersistentToken {
+    /// Create a new `PersistentToken`.
+    #[inline]
+    pub fn new(
+        series: impl Into<String>,
+        token_hash: impl Into<String>,
+        user: impl Into<String>,
+        expires_at: i64,
+    ) -> Self {
+        Self {
+            series: series.into(),
+            token_hash: token_hash.into(),
+            user: user.into(),
+            expires_at,
+        }
+    }
+    /// Returns `true` if the token is expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now().timestamp()
+    }
+}
+
+/// Store for persistent login tokens.
+#[async_trait]
+pub trait TokenStore: Send + Sync + 'static {
+    /// Insert or replace the token with the same series.
+    async fn save(&self, token: PersistentToken) -> Result<(), Error>;
+    /// Load the token with `series`.
+    async fn load(&self, series: &str) -> Result<Option<PersistentToken>, Error>;
+    /// Remove the token with `series`.
+    async fn remove(&self, series: &str) -> Result<(), Error>;
+    /// Remove all tokens of `user`.
+    async fn remove_user(&self, user: &str) -> Result<(), Error>;
+}
+
+/// In memory [`TokenStore`], tokens are lost when the server restarts.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Arc<RwLock<HashMap<String, PersistentToken>>>,
+}
+impl MemoryTokenStore {
+    /// Create a new `MemoryTokenStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the count of tokens.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.tokens.read().expect("lock should not be poisoned").len()
+    }
+}
+#[async_trait]
+impl TokenStore for MemoryTokenStore {
+    async fn save(&self, token: PersistentToken) -> Result<(), Error> {
+        self.tokens
+            .write()
+            .expect("lock should not be poisoned")
+            .insert(token.series.clone(), token);
+        Ok(())
+    }
+    async fn load(&self, series: &str) -> Result<Option<PersistentToken>, Error> {
+        Ok(self
+            .tokens
+            .read()
+            .expect("lock should not be poisoned")
+            .get(series)
+            .cloned())
+    }
+    async fn remove(&self, series: &str) -> Result<(), Error> {
+        self.tokens.write().expect("lock should not be poisoned").remove(series);
+        Ok(())
+    }
+    async fn remove_user(&self, user: &str) -> Result<(), Error> {
+        self.tokens
+            .write()
+            .expect("lock should not be poisoned")
+            .retain(|_, token| token.user != user);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RememberMeState {
+    /// The user is logged in by remember-me cookie in current request.
+    Remembered(String),
+    /// Create a new series for the user.
+    Remember(String),
+    /// Remove the series of current request.
+    Forget,
+    /// Remove all series of the user.
+    ForgetAll(String),
+}
+
+/// Trait for `Depot` to control remember-me cookie.
+pub trait RememberMeDepotExt {
+    /// Issue a remember-me cookie for `user`, it should be called after the user logged in with credentials.
+    fn remember(&mut self, user: impl Into<String>) -> &mut Self;
+    /// Remove the remember-me cookie of current request, it should be called when the user logs out.
+    fn forget(&mut self) -> &mut Self;
+    /// Remove all remember-me tokens of `user`, it logs the user out of all browsers.
+    fn forget_all(&mut self, user: impl Into<String>) -> &mut Self;
+    /// Returns the user if the user is logged in by remember-me cookie in current request.
+    ///
+    /// The user is not authenticated by credentials, so it is recommended to ask user to log in again before
+    /// sensitive operations, such as changing password.
+    fn remembered_user(&self) -> Option<&str>;
+}
+impl RememberMeDepotExt for Depot {
+    #[inline]
+    fn remember(&mut self, user: impl Into<String>) -> &mut Self {
+        self.insert(REMEMBER_ME_KEY, RememberMeState::Remember(user.into()));
+        self
+    }
+    #[inline]
+    fn forget(&mut self) -> &mut Self {
+        self.insert(REMEMBER_ME_KEY, RememberMeState::Forget);
+        self
+    }
+    #[inline]
+    fn forget_all(&mut self, user: impl Into<String>) -> &mut Self {
+        self.insert(REMEMBER_ME_KEY, RememberMeState::ForgetAll(user.into()));
+        self
+    }
+    #[inline]
+    fn remembered_user(&self) -> Option<&str> {
+        match self.get::<RememberMeState>(REMEMBER_ME_KEY).ok()? {
+            RememberMeState::Remembered(user) => Some(user),
+            _ => None,
+        }
+    }
+}
+
+/// `RememberMe` is a middleware logs users in by persistent remember-me cookies.
+///
+/// It must be added after [`SessionHandler`](crate::SessionHandler). When the session has no user and the
+/// request comes with a valid remember-me cookie, the user is saved in session with key `session_key`, the
+/// session id is rotated and the token in cookie is rotated.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_session::remember_me::{MemoryTokenStore, RememberMe, RememberMeDepotExt};
+/// use salvo_session::{MemoryStore, SessionDepotExt, SessionHandler};
+///
+/// #[handler]
+/// async fn login(depot: &mut Depot) {
+///     // Verify credentials here.
+///     depot.session_mut().unwrap().insert("user", "alice").unwrap();
+///     depot.remember("alice");
+/// }
+///
+/// let session_handler = SessionHandler::builder(
+///     MemoryStore::new(),
+///     b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+/// )
+/// .build()
+/// .unwrap();
+/// let router = Router::new()
+///     .hoop(session_handler)
+///     .hoop(RememberMe::new(MemoryTokenStore::new()).session_key("user"))
+///     .push(Router::with_path("login").post(login));
+/// ```
+pub struct RememberMe<S> {
+    store: S,
+    cookie_name: String,
+    cookie_path: String,
+    cookie_domain: Option<String>,
+    same_site_policy: SameSite,
+    session_key: String,
+    ttl: Duration,
+}
+impl<S: fmt::Debug> fmt::Debug for RememberMe<S> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RememberMe")
+            .field("store", &self.store)
+            .field("cookie_name", &self.cookie_name)
+            .field("cookie_path", &self.cookie_path)
+            .field("cookie_domain", &self.cookie_domain)
+            .field("same_site_policy", &self.same_site_policy)
+            .field("session_key", &self.session_key)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+impl<S> RememberMe<S>
+where
+    S: TokenStore,
+{
+    /// Create a new `RememberMe`.
+    #[inline]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cookie_name: "salvo.remember_me".into(),
+            cookie_path: "/".into(),
+            cookie_domain: None,
+            same_site_policy: SameSite::Lax,
+            session_key: "user".into(),
+            ttl: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+    /// Sets the name of remember-me cookie. The default value is "salvo.remember_me".
+    #[inline]
+    pub fn cookie_name(mut self, cookie_name: impl AsRef<str>) -> Self {
+        self.cookie_name = cookie_name.as_ref().to_owned();
+        self
+    }
+    /// Sets the path of remember-me cookie. The default value is "/".
+    #[inline]
+    pub fn cookie_path(mut self, cookie_path: impl AsRef<str>) -> Self {
+        self.cookie_path = cookie_path.as_ref().to_owned();
+        self
+    }
+    /// Sets the domain of remember-me cookie.
+    #[inline]
+    pub fn cookie_domain(mut self, cookie_domain: impl AsRef<str>) -> Self {
+        self.cookie_domain = Some(cookie_domain.as_ref().to_owned());
+        self
+    }
+    /// Sets the same site policy of remember-me cookie. Defaults to SameSite::Lax.
+    #[inline]
+    pub fn same_site_policy(mut self, policy: SameSite) -> Self {
+        self.same_site_policy = policy;
+        self
+    }
+    /// Sets the session key the user is stored with. The default value is "user".
+    #[inline]
+    pub fn session_key(mut self, session_key: impl AsRef<str>) -> Self {
+        self.session_key = session_key.as_ref().to_owned();
+        self
+    }
+    /// Sets the lifetime of remember-me tokens. The default value is 30 days.
+    #[inline]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Validate the cookie value, returns the user and the rotated token if it is valid.
+    async fn authenticate(&self, cookie_value: &str) -> Result<Option<(String, PersistentToken, String)>, Error> {
+        let Some((series, token)) = cookie_value.split_once('.') else {
+            return Ok(None);
+        };
+        let Some(persistent) = self.store.load(series).await? else {
+            return Ok(None);
+        };
+        if persistent.is_expired() {
+            self.store.remove(series).await?;
+            return Ok(None);
+        }
+        if persistent.token_hash != hash_token(token) {
+            tracing::warn!(user = %persistent.user, "remember-me token mismatch, all tokens of the user are removed");
+            self.store.remove_user(&persistent.user).await?;
+            return Ok(None);
+        }
+        let token = generate_token();
+        let rotated = PersistentToken::new(
+            persistent.series,
+            hash_token(&token),
+            persistent.user.clone(),
+            self.expires_at(),
+        );
+        self.store.save(rotated.clone()).await?;
+        Ok(Some((persistent.user, rotated, token)))
+    }
+
+    #[inline]
+    fn expires_at(&self) -> i64 {
+        Utc::now().timestamp().saturating_add(self.ttl.as_secs() as i64)
+    }
+
+    fn build_cookie(&self, req: &Request, value: String) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.cookie_name.clone(), value))
+            .http_only(true)
+            .same_site(self.same_site_policy)
+            .secure(req.uri().scheme() == Some(&Scheme::HTTPS))
+            .path(self.cookie_path.clone())
+            .build();
+        cookie.set_expires(Some((std::time::SystemTime::now() + self.ttl).into()));
+        if let Some(cookie_domain) = self.cookie_domain.clone() {
+            cookie.set_domain(cookie_domain);
+        }
+        cookie
+    }
+
+    fn remove_cookie(&self, req: &Request, res: &mut Response) {
+        let mut cookie = self.build_cookie(req, String::new());
+        cookie.make_removal();
+        res.add_cookie(cookie);
+    }
+}
+
+#[async_trait]
+impl<S> Handler for RememberMe<S>
+where
+    S: TokenStore,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let cookie_value = req.cookies().get(&self.cookie_name).map(|c| c.value().to_owned());
+        let Some(session) = depot.session() else {
+            tracing::warn!("session not found in depot, `RememberMe` should be added after `SessionHandler`");
+            ctrl.call_next(req, depot, res).await;
+            return;
+        };
+        let logged_in = session.get_raw(&self.session_key).is_some();
+        if let (Some(cookie_value), false) = (&cookie_value, logged_in) {
+            match self.authenticate(cookie_value).await {
+                Ok(Some((user, persistent, token))) => {
+                    let session = depot.cycle_session_id().expect("session should exist in depot");
+                    if let Err(e) = session.insert(&self.session_key, &user) {
+                        tracing::error!(error = ?e, "unable to insert user into session");
+                    }
+                    depot.insert(REMEMBER_ME_KEY, RememberMeState::Remembered(user));
+                    res.add_cookie(self.build_cookie(req, format!("{}.{}", persistent.series, token)));
+                }
+                Ok(None) => self.remove_cookie(req, res),
+                Err(e) => tracing::error!(error = ?e, "remember-me authentication failed"),
+            }
+        }
+
+        ctrl.call_next(req, depot, res).await;
+
+        let state = depot.remove::<RememberMeState>(REMEMBER_ME_KEY).ok();
+        let result = match state {
+            Some(RememberMeState::Remember(user)) => {
+                let series = generate_token();
+                let token = generate_token();
+                let persistent = PersistentToken::new(&*series, hash_token(&token), user, self.expires_at());
+                let result = self.store.save(persistent).await;
+                if result.is_ok() {
+                    res.add_cookie(self.build_cookie(req, format!("{series}.{token}")));
+                }
+                result
+            }
+            Some(RememberMeState::Forget) => {
+                self.remove_cookie(req, res);
+                match cookie_value.as_deref().and_then(|v| v.split_once('.')) {
+                    Some((series, _)) => self.store.remove(series).await,
+                    None => Ok(()),
+                }
+            }
+            Some(RememberMeState::ForgetAll(user)) => {
+                self.remove_cookie(req, res);
+                self.store.remove_user(&user).await
+            }
+            Some(RememberMeState::Remembered(_)) | None => Ok(()),
+        };
+        if let Err(e) = result {
+            tracing::error!(error = ?e, "remember-me token store error");
+        }
+    }
+}
+
+#[inline]
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+#[inline]
+fn hash_token(token: &str) -> String {
+    base64::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::*;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::{MemoryStore, SessionHandler};
+
+    #[handler]
+    async fn login(depot: &mut Depot) -> &'static str {
+        depot.session_mut().unwrap().insert("user", "salvo").unwrap();
+        depot.remember("salvo");
+        "login"
+    }
+    #[handler]
+    async fn logout(depot: &mut Depot) -> &'static str {
+        depot.session_mut().unwrap().destroy();
+        depot.forget();
+        "logout"
+    }
+    #[handler]
+    async fn home(depot: &mut Depot) -> String {
+        let user = depot
+            .session()
+            .and_then(|s| s.get::<String>("user"))
+            .unwrap_or_else(|| "guest".into());
+        match depot.remembered_user() {
+            Some(_) => format!("{user} remembered"),
+            None => user,
+        }
+    }
+
+    fn remember_cookie(res: &Response) -> Option<String> {
+        res.headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| Cookie::parse(v.to_owned()).ok())
+            .find(|c| c.name() == "salvo.remember_me")
+            .map(|c| c.value().to_owned())
+    }
+
+    async fn get(service: &Service, path: &str, cookie: Option<&str>) -> Response {
+        let mut client = TestClient::get(format!("http://127.0.0.1:5800{path}"));
+        if let Some(cookie) = cookie {
+            client = client.add_header(COOKIE, format!("salvo.remember_me={cookie}"), true);
+        }
+        client.send(service).await
+    }
+
+    fn service(store: MemoryTokenStore) -> Service {
+        let session_handler = SessionHandler::builder(
+            MemoryStore::new(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .build()
+        .unwrap();
+        let router = Router::new()
+            .hoop(session_handler)
+            .hoop(RememberMe::new(store))
+            .get(home)
+            .push(Router::with_path("login").get(login))
+            .push(Router::with_path("logout").get(logout));
+        Service::new(router)
+    }
+
+    #[tokio::test]
+    async fn test_remember_me_rotation() {
+        let store = MemoryTokenStore::new();
+        let service = service(store.clone());
+
+        let res = get(&service, "/login", None).await;
+        let first = remember_cookie(&res).unwrap();
+        assert_eq!(store.count(), 1);
+
+        let mut res = get(&service, "/", Some(&first)).await;
+        let second = remember_cookie(&res).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(first.split_once('.').unwrap().0, second.split_once('.').unwrap().0);
+        assert_eq!(res.take_string().await.unwrap(), "salvo remembered");
+
+        let mut res = get(&service, "/", Some(&second)).await;
+        let third = remember_cookie(&res).unwrap();
+        assert_eq!(res.take_string().await.unwrap(), "salvo remembered");
+
+        let mut res = get(&service, "/logout", Some(&third)).await;
+        assert_eq!(res.take_string().await.unwrap(), "logout");
+        assert_eq!(store.count(), 0);
+        let mut res = get(&service, "/", Some(&third)).await;
+        assert_eq!(res.take_string().await.unwrap(), "guest");
+    }
+
+    #[tokio::test]
+    async fn test_remember_me_theft_detection() {
+        let store = MemoryTokenStore::new();
+        let service = service(store.clone());
+
+        let stolen = remember_cookie(&get(&service, "/login", None).await).unwrap();
+        let _other_browser = remember_cookie(&get(&service, "/login", None).await).unwrap();
+        assert_eq!(store.count(), 2);
+
+        // The attacker uses the stolen cookie first, so the token is rotated.
+        let mut res = get(&service, "/", Some(&stolen)).await;
+        assert_eq!(res.take_string().await.unwrap(), "salvo remembered");
+
+        // The victim comes back with the old token, all tokens of the user are invalidated.
+        let mut res = get(&service, "/", Some(&stolen)).await;
+        assert_eq!(res.take_string().await.unwrap(), "guest");
+        assert_eq!(store.count(), 0);
+    }
+}
@@ -0,0 +1,211 @@
+use async_session::chrono::Utc;
+use async_session::{async_trait, serde_json, Result, Session, SessionStore};
+
+const DEFAULT_TABLE_NAME: &str = "salvo_sessions";
+
+fn valid_table_name(table_name: &str) -> bool {
+    !table_name.is_empty()
+        && table_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+macro_rules! sqlx_store {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $pool:ty,
+        create: $create:literal,
+        load: $load:literal,
+        store: $store:literal,
+        destroy: $destroy:literal,
+        clear: $clear:literal,
+        cleanup: $cleanup:literal,
+        count: $count:literal,
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            pool: $pool,
+            table_name: String,
+        }
+
+        impl $name {
+            /// Create new store from a connection pool, the default table name is `salvo_sessions`.
+            #[inline]
+            pub fn new(pool: $pool) -> Self {
+                Self {
+                    pool,
+                    table_name: DEFAULT_TABLE_NAME.into(),
+                }
+            }
+
+            /// Sets the table name used to store sessions.
+            ///
+            /// # Panics
+            ///
+            /// Panics if table name contains characters other than ascii alphanumeric, `_` and `.`.
+            #[inline]
+            pub fn with_table_name(mut self, table_name: impl Into<String>) -> Self {
+                let table_name = table_name.into();
+                assert!(valid_table_name(&table_name), "invalid session table name: {table_name}");
+                self.table_name = table_name;
+                self
+            }
+
+            /// Get the connection pool.
+            #[inline]
+            pub fn pool(&self) -> &$pool {
+                &self.pool
+            }
+
+            fn sql(&self, sql: &str) -> String {
+                sql.replace("%%TABLE_NAME%%", &self.table_name)
+            }
+
+            /// Create the sessions table if it does not exist.
+            pub async fn migrate(&self) -> Result {
+                sqlx::query(&self.sql($create)).execute(&self.pool).await?;
+                Ok(())
+            }
+
+            /// Remove all expired sessions from the table.
+            ///
+            /// This should be run on an intermittent basis, stale sessions are never returned by this store,
+            /// but they are not removed until cleanup.
+            pub async fn cleanup(&self) -> Result {
+                sqlx::query(&self.sql($cleanup))
+                    .bind(Utc::now().timestamp())
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            /// Returns the number of stored sessions, including expired sessions not cleaned up yet.
+            pub async fn count(&self) -> Result<i64> {
+                let (count,): (i64,) = sqlx::query_as(&self.sql($count)).fetch_one(&self.pool).await?;
+                Ok(count)
+            }
+        }
+
+        #[async_trait]
+        impl SessionStore for $name {
+            async fn load_session(&self, cookie_value: String) -> Result<Option<Session>> {
+                let id = Session::id_from_cookie_value(&cookie_value)?;
+                let record: Option<(String,)> = sqlx::query_as(&self.sql($load))
+                    .bind(&id)
+                    .bind(Utc::now().timestamp())
+                    .fetch_optional(&self.pool)
+                    .await?;
+                match record {
+                    Some((value,)) => Ok(serde_json::from_str::<Session>(&value)?.validate()),
+                    None => Ok(None),
+                }
+            }
+
+            async fn store_session(&self, session: Session) -> Result<Option<String>> {
+                let value = serde_json::to_string(&session)?;
+                sqlx::query(&self.sql($store))
+                    .bind(session.id())
+                    .bind(session.expiry().map(|expiry| expiry.timestamp()))
+                    .bind(value)
+                    .execute(&self.pool)
+                    .await?;
+                session.reset_data_changed();
+                Ok(session.into_cookie_value())
+            }
+
+            async fn destroy_session(&self, session: Session) -> Result {
+                sqlx::query(&self.sql($destroy))
+                    .bind(session.id())
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
+            async fn clear_store(&self) -> Result {
+                sqlx::query(&self.sql($clear)).execute(&self.pool).await?;
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "postgres-store")]
+sqlx_store! {
+    /// Session store backed by postgres.
+    ///
+    /// Call [`PostgresStore::migrate`] to create the sessions table before using it.
+    #[cfg_attr(docsrs, doc(cfg(feature = "postgres-store")))]
+    PostgresStore, sqlx::PgPool,
+    create: "CREATE TABLE IF NOT EXISTS %%TABLE_NAME%% (id TEXT PRIMARY KEY NOT NULL, expires BIGINT NULL, session TEXT NOT NULL)",
+    load: "SELECT session FROM %%TABLE_NAME%% WHERE id = $1 AND (expires IS NULL OR expires > $2)",
+    store: "INSERT INTO %%TABLE_NAME%% (id, expires, session) VALUES ($1, $2, $3) ON CONFLICT(id) DO UPDATE SET expires = EXCLUDED.expires, session = EXCLUDED.session",
+    destroy: "DELETE FROM %%TABLE_NAME%% WHERE id = $1",
+    clear: "TRUNCATE %%TABLE_NAME%%",
+    cleanup: "DELETE FROM %%TABLE_NAME%% WHERE expires < $1",
+    count: "SELECT COUNT(*) FROM %%TABLE_NAME%%",
+}
+
+#[cfg(feature = "sqlite-store")]
+sqlx_store! {
+    /// Session store backed by sqlite.
+    ///
+    /// Call [`SqliteStore::migrate`] to create the sessions table before using it.
+    #[cfg_attr(docsrs, doc(cfg(feature = "sqlite-store")))]
+    SqliteStore, sqlx::SqlitePool,
+    create: "CREATE TABLE IF NOT EXISTS %%TABLE_NAME%% (id TEXT PRIMARY KEY NOT NULL, expires INTEGER NULL, session TEXT NOT NULL)",
+    load: "SELECT session FROM %%TABLE_NAME%% WHERE id = ? AND (expires IS NULL OR expires > ?)",
+    store: "INSERT INTO %%TABLE_NAME%% (id, expires, session) VALUES (?, ?, ?) ON CONFLICT(id) DO UPDATE SET expires = excluded.expires, session = excluded.session",
+    destroy: "DELETE FROM %%TABLE_NAME%% WHERE id = ?",
+    clear: "DELETE FROM %%TABLE_NAME%%",
+    cleanup: "DELETE FROM %%TABLE_NAME%% WHERE expires < ?",
+    count: "SELECT COUNT(*) FROM %%TABLE_NAME%%",
+}
+
+#[cfg(all(test, feature = "sqlite-store"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn store() -> SqliteStore {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqliteStore::new(pool);
+        store.migrate().await.unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        let store = store().await;
+        let mut session = Session::new();
+        session.insert("key", "value").unwrap();
+        let id = session.id().to_owned();
+        let cookie_value = store.store_session(session).await.unwrap().unwrap();
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        let loaded = store.load_session(cookie_value.clone()).await.unwrap().unwrap();
+        assert_eq!(loaded.id(), id);
+        assert_eq!(loaded.get::<String>("key").unwrap(), "value");
+
+        store.destroy_session(loaded).await.unwrap();
+        assert!(store.load_session(cookie_value).await.unwrap().is_none());
+        assert_eq!(store.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_expiry() {
+        let store = store().await;
+        let mut session = Session::new();
+        session.expire_in(Duration::from_secs(0));
+        let cookie_value = store.store_session(session).await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(store.load_session(cookie_value).await.unwrap().is_none());
+        store.cleanup().await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,74 @@
+use askama::Template;
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::http::{Response, StatusError};
+use salvo_core::writing::Scribe;
+
+/// Write an [Askama](https://github.com/djc/askama) template to response.
+///
+/// The `content-type` is set according to the template's extension.
+///
+/// # Example
+///
+/// ```
+/// use askama::Template;
+/// use salvo_core::prelude::*;
+/// use salvo_template::Askama;
+///
+/// #[derive(Template)]
+/// #[template(source = "<h1>Hello, {{ name }}!</h1>", ext = "html")]
+/// struct Hello<'a> {
+///     name: &'a str,
+/// }
+///
+/// #[handler]
+/// async fn hello() -> Askama<Hello<'static>> {
+///     Askama(Hello { name: "Salvo" })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Askama<T>(pub T);
+impl<T> Scribe for Askama<T>
+where
+    T: Template,
+{
+    fn render(self, res: &mut Response) {
+        match self.0.render() {
+            Ok(content) => {
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(T::MIME_TYPE));
+                res.write_body(content).ok();
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "render askama template failed");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use askama::Template;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[derive(Template)]
+    #[template(source = "<p>{{ name }}</p>", ext = "html")]
+    struct Hello<'a> {
+        name: &'a str,
+    }
+
+    #[handler]
+    async fn hello() -> Askama<Hello<'static>> {
+        Askama(Hello { name: "<jobs>" })
+    }
+
+    #[tokio::test]
+    async fn test_askama() {
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(Router::new().get(hello)).await;
+        assert_eq!(res.headers().get("content-type").unwrap(), "text/html; charset=utf-8");
+        assert_eq!(res.take_string().await.unwrap(), "<p>&lt;jobs&gt;</p>");
+    }
+}
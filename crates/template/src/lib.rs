@@ -0,0 +1,296 @@
+//! Template engine integration for Savlo web server framework.
+//!
+//! Register an engine implements [`Render`] with [`Templates`] as a hoop, then return [`Template`] from
+//! handlers to render it. Adapters are provided for [Tera](https://keats.github.io/tera/) and
+//! [MiniJinja](https://github.com/mitsuhiko/minijinja), compile time [Askama](https://github.com/djc/askama)
+//! templates can be written with [`Askama`].
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::future_not_send)]
+#![warn(rustdoc::broken_intra_doc_links)]
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::writing::Text;
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler, Writer};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+#[macro_use]
+mod cfg;
+
+cfg_feature! {
+    #![feature = "tera"]
+
+    mod tera;
+    pub use self::tera::TeraEngine;
+}
+
+cfg_feature! {
+    #![feature = "minijinja"]
+
+    mod minijinja;
+    pub use self::minijinja::MiniJinjaEngine;
+}
+
+cfg_feature! {
+    #![feature = "askama"]
+
+    mod askama;
+    pub use self::askama::Askama;
+}
+
+/// Key used to insert shared template context into depot.
+pub const TEMPLATE_CONTEXT_KEY: &str = "::salvo::template::context";
+
+/// Extension for Depot.
+///
+/// Middlewares can insert values shared by all templates rendered in current request, such as the current
+/// user or a csrf token, values inserted by [`Template`] take precedence.
+pub trait TemplateDepotExt {
+    /// Get shared template context.
+    fn template_context(&self) -> Option<&Map<String, Value>>;
+    /// Get mutable shared template context, it is created if it does not exist.
+    fn template_context_mut(&mut self) -> &mut Map<String, Value>;
+}
+impl TemplateDepotExt for Depot {
+    #[inline]
+    fn template_context(&self) -> Option<&Map<String, Value>> {
+        self.get(TEMPLATE_CONTEXT_KEY).ok()
+    }
+    fn template_context_mut(&mut self) -> &mut Map<String, Value> {
+        if !self.contains_key(TEMPLATE_CONTEXT_KEY) {
+            self.insert(TEMPLATE_CONTEXT_KEY, Map::<String, Value>::new());
+        }
+        self.get_mut(TEMPLATE_CONTEXT_KEY)
+            .expect("template context should be inserted")
+    }
+}
+
+/// Template engine renders named templates with a context.
+///
+/// Functions with signature `Fn(&str, &Value) -> Result<String, BoxedError>` implement this trait.
+pub trait Render: Send + Sync + 'static {
+    /// Render template `name` with `context`.
+    fn render(&self, name: &str, context: &Value) -> Result<String, BoxedError>;
+}
+impl<F> Render for F
+where
+    F: Fn(&str, &Value) -> Result<String, BoxedError> + Send + Sync + 'static,
+{
+    #[inline]
+    fn render(&self, name: &str, context: &Value) -> Result<String, BoxedError> {
+        self(name, context)
+    }
+}
+
+/// App level template engine state.
+///
+/// `Templates` is cheap to clone, use it as a hoop to make the engine available to [`Template`] writers.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::BoxedError;
+/// use salvo_template::{Template, Templates};
+/// use serde_json::Value;
+///
+/// #[handler]
+/// async fn hello() -> Template {
+///     Template::new("hello.html").insert("name", "Salvo")
+/// }
+///
+/// fn render(name: &str, context: &Value) -> Result<String, BoxedError> {
+///     Ok(format!("<h1>Hello, {}!</h1>", context["name"].as_str().unwrap_or_default()))
+/// }
+/// let router = Router::new().hoop(Templates::new(render)).get(hello);
+/// ```
+#[derive(Clone)]
+pub struct Templates {
+    engine: Arc<dyn Render>,
+}
+impl Debug for Templates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Templates").finish()
+    }
+}
+impl Templates {
+    /// Create a new `Templates` with the engine.
+    #[inline]
+    pub fn new(engine: impl Render) -> Self {
+        Self {
+            engine: Arc::new(engine),
+        }
+    }
+    /// Render template `name` with a serializable context.
+    pub fn render<T>(&self, name: &str, context: &T) -> Result<String, BoxedError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let context = serde_json::to_value(context)?;
+        self.engine.render(name, &context)
+    }
+}
+#[async_trait]
+impl Handler for Templates {
+    #[inline]
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        depot.inject(self.clone());
+    }
+}
+
+/// Write a rendered template to response as html content.
+///
+/// The engine is taken from [`Templates`] in depot, so [`Templates`] must be used as a hoop before. The context
+/// is merged with the shared context in depot, see [`TemplateDepotExt`].
+#[derive(Debug)]
+pub struct Template {
+    name: String,
+    context: Result<Map<String, Value>, serde_json::Error>,
+}
+impl Template {
+    /// Create a new `Template` with empty context.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            context: Ok(Map::new()),
+        }
+    }
+    /// Merge fields of a serializable struct or map into the context.
+    pub fn context<T>(mut self, context: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Ok(map) = &mut self.context {
+            match serde_json::to_value(context) {
+                Ok(Value::Object(fields)) => map.extend(fields),
+                Ok(_) => {
+                    self.context = Err(serde::ser::Error::custom("template context must be a struct or map"));
+                }
+                Err(e) => self.context = Err(e),
+            }
+        }
+        self
+    }
+    /// Insert a value into the context.
+    pub fn insert<T>(mut self, key: impl Into<String>, value: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Ok(map) = &mut self.context {
+            match serde_json::to_value(value) {
+                Ok(value) => {
+                    map.insert(key.into(), value);
+                }
+                Err(e) => self.context = Err(e),
+            }
+        }
+        self
+    }
+}
+#[async_trait]
+impl Writer for Template {
+    async fn write(self, _req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        let Ok(templates) = depot.obtain::<Templates>() else {
+            tracing::error!("`Templates` is not found in depot, add it as a hoop");
+            res.render(StatusError::internal_server_error());
+            return;
+        };
+        let context = match self.context {
+            Ok(context) => match depot.template_context() {
+                Some(shared) => {
+                    let mut shared = shared.clone();
+                    shared.extend(context);
+                    Value::Object(shared)
+                }
+                None => Value::Object(context),
+            },
+            Err(e) => {
+                tracing::error!(error = ?e, template = self.name, "serialize template context failed");
+                res.render(StatusError::internal_server_error());
+                return;
+            }
+        };
+        match templates.engine.render(&self.name, &context) {
+            Ok(content) => res.render(Text::Html(content)),
+            Err(e) => {
+                tracing::error!(error = ?e, template = self.name, "render template failed");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    struct Echo;
+    impl Render for Echo {
+        fn render(&self, name: &str, context: &Value) -> Result<String, BoxedError> {
+            if name == "missing" {
+                return Err("template not found".into());
+            }
+            Ok(format!("{name}: {context}"))
+        }
+    }
+
+    #[derive(Serialize)]
+    struct User<'a> {
+        name: &'a str,
+    }
+
+    #[handler]
+    async fn show(req: &mut Request) -> Template {
+        Template::new(req.param::<String>("name").unwrap())
+            .context(&User { name: "jobs" })
+            .insert("age", &30)
+    }
+
+    #[tokio::test]
+    async fn test_template() {
+        let res = TestClient::get("http://127.0.0.1:5801/index")
+            .send(Router::with_path("<name>").get(show))
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+
+        #[handler]
+        async fn shared(depot: &mut Depot) {
+            depot.template_context_mut().insert("name".into(), "shared".into());
+            depot.template_context_mut().insert("site".into(), "salvo".into());
+        }
+        let router = Router::new()
+            .hoop(Templates::new(Echo))
+            .hoop(shared)
+            .push(Router::with_path("<name>").get(show));
+        let service = Service::new(router);
+        let mut res = TestClient::get("http://127.0.0.1:5801/index").send(&service).await;
+        assert_eq!(res.headers().get("content-type").unwrap(), "text/html; charset=utf-8");
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            r#"index: {"age":30,"name":"jobs","site":"salvo"}"#
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5801/missing").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_invalid_context() {
+        let template = Template::new("index").context(&1);
+        assert!(template.context.is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{self, Debug, Formatter};
+use std::path::Path;
+use std::sync::RwLock;
+
+use minijinja::{path_loader, Environment};
+use salvo_core::BoxedError;
+use serde_json::Value;
+
+use crate::Render;
+
+/// [`Render`] adapter for [MiniJinja](https://github.com/mitsuhiko/minijinja).
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::prelude::*;
+/// use salvo_template::{MiniJinjaEngine, Templates};
+///
+/// let router = Router::new().hoop(Templates::new(MiniJinjaEngine::new("templates")));
+/// ```
+pub struct MiniJinjaEngine {
+    env: RwLock<Environment<'static>>,
+    auto_reload: bool,
+}
+impl Debug for MiniJinjaEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiniJinjaEngine")
+            .field("auto_reload", &self.auto_reload)
+            .finish()
+    }
+}
+impl MiniJinjaEngine {
+    /// Create a new `MiniJinjaEngine` loads templates from directory `dir` lazily.
+    ///
+    /// Auto reload is enabled in debug build.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        let mut env = Environment::new();
+        env.set_loader(path_loader(dir.as_ref()));
+        Self {
+            env: RwLock::new(env),
+            auto_reload: cfg!(debug_assertions),
+        }
+    }
+    /// Create a new `MiniJinjaEngine` from a configured [`Environment`], auto reload is disabled by default.
+    #[inline]
+    pub fn from_env(env: Environment<'static>) -> Self {
+        Self {
+            env: RwLock::new(env),
+            auto_reload: false,
+        }
+    }
+    /// Sets whether loaded templates are dropped before every rendering, so they are loaded from disk again.
+    ///
+    /// It only works for environments with a loader, templates added manually are dropped too.
+    #[inline]
+    pub fn auto_reload(mut self, auto_reload: bool) -> Self {
+        self.auto_reload = auto_reload;
+        self
+    }
+}
+impl Render for MiniJinjaEngine {
+    fn render(&self, name: &str, context: &Value) -> Result<String, BoxedError> {
+        if self.auto_reload {
+            self.env
+                .write()
+                .map_err(|_| "minijinja lock is poisoned")?
+                .clear_templates();
+        }
+        let env = self.env.read().map_err(|_| "minijinja lock is poisoned")?;
+        Ok(env.get_template(name)?.render(context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_minijinja_engine() {
+        let mut env = Environment::new();
+        env.add_template("hello.html", "Hello, {{ name }}!").unwrap();
+        let engine = MiniJinjaEngine::from_env(env);
+        assert_eq!(engine.render("hello.html", &json!({"name": "Salvo"})).unwrap(), "Hello, Salvo!");
+        assert!(engine.render("missing.html", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_minijinja_auto_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.html");
+        std::fs::write(&path, "v1").unwrap();
+        let engine = MiniJinjaEngine::new(dir.path()).auto_reload(true);
+        assert_eq!(engine.render("index.html", &json!({})).unwrap(), "v1");
+        std::fs::write(&path, "v2").unwrap();
+        assert_eq!(engine.render("index.html", &json!({})).unwrap(), "v2");
+    }
+}
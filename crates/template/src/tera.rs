@@ -0,0 +1,176 @@
+use std::fmt::{self, Debug, Formatter};
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+use salvo_core::BoxedError;
+use serde_json::Value;
+use tera::{Context, Tera};
+
+use crate::Render;
+
+/// [`Render`] adapter for [Tera](https://keats.github.io/tera/).
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::prelude::*;
+/// use salvo_template::{Templates, TeraEngine};
+///
+/// let engine = TeraEngine::new("templates/**/*.html").unwrap();
+/// let router = Router::new().hoop(Templates::new(engine));
+/// ```
+pub struct TeraEngine {
+    tera: RwLock<Tera>,
+    auto_reload: bool,
+    template_dir: Option<PathBuf>,
+    stamp: Mutex<Stamp>,
+}
+impl Debug for TeraEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeraEngine")
+            .field("auto_reload", &self.auto_reload)
+            .finish()
+    }
+}
+impl TeraEngine {
+    /// Create a new `TeraEngine` loads templates matched by `glob`.
+    ///
+    /// Auto reload is enabled in debug build.
+    pub fn new(glob: &str) -> Result<Self, BoxedError> {
+        let tera = Tera::new(glob)?;
+        let template_dir = glob_base(glob);
+        let stamp = Stamp::scan(&template_dir).unwrap_or_default();
+        Ok(Self {
+            tera: RwLock::new(tera),
+            auto_reload: cfg!(debug_assertions),
+            template_dir: Some(template_dir),
+            stamp: Mutex::new(stamp),
+        })
+    }
+    /// Create a new `TeraEngine` from a configured [`Tera`], auto reload is disabled by default.
+    #[inline]
+    pub fn from_tera(tera: Tera) -> Self {
+        Self {
+            tera: RwLock::new(tera),
+            auto_reload: false,
+            template_dir: None,
+            stamp: Mutex::new(Stamp::default()),
+        }
+    }
+    /// Sets whether templates are reloaded from disk when they are changed.
+    ///
+    /// Before rendering, the modified time and size of files in the directory of the glob are checked, templates are
+    /// only reloaded if any file is added, removed or changed. It only works for engines created with a glob.
+    #[inline]
+    pub fn auto_reload(mut self, auto_reload: bool) -> Self {
+        self.auto_reload = auto_reload;
+        self
+    }
+
+    /// Returns `true` if any file in the template directory is changed since the last check.
+    fn templates_changed(&self) -> bool {
+        let Some(template_dir) = &self.template_dir else {
+            return false;
+        };
+        let current = match Stamp::scan(template_dir) {
+            Ok(current) => current,
+            Err(e) => {
+                tracing::warn!(error = ?e, dir = %template_dir.display(), "scan tera template directory failed");
+                return false;
+            }
+        };
+        let mut stamp = self.stamp.lock().unwrap_or_else(|e| e.into_inner());
+        if *stamp == current {
+            false
+        } else {
+            *stamp = current;
+            true
+        }
+    }
+}
+
+/// Summary of files in the template directory, it is changed when any file is added, removed or changed.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+struct Stamp {
+    files: usize,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+impl Stamp {
+    fn scan(dir: &Path) -> IoResult<Self> {
+        let mut stamp = Self::default();
+        stamp.scan_dir(dir)?;
+        Ok(stamp)
+    }
+    fn scan_dir(&mut self, dir: &Path) -> IoResult<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                self.scan_dir(&entry.path())?;
+            } else {
+                self.files += 1;
+                self.size += metadata.len();
+                self.modified = self.modified.max(metadata.modified().ok());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the directory part of `glob` before the first wildcard.
+fn glob_base(glob: &str) -> PathBuf {
+    let prefix = &glob[..glob.find(['*', '?', '[', '{']).unwrap_or(glob.len())];
+    match prefix.rfind('/') {
+        Some(index) => PathBuf::from(&prefix[..=index]),
+        None => PathBuf::from("."),
+    }
+}
+impl Render for TeraEngine {
+    fn render(&self, name: &str, context: &Value) -> Result<String, BoxedError> {
+        let context = Context::from_value(context.clone())?;
+        if self.auto_reload && self.templates_changed() {
+            self.tera.write().map_err(|_| "tera lock is poisoned")?.full_reload()?;
+        }
+        let tera = self.tera.read().map_err(|_| "tera lock is poisoned")?;
+        Ok(tera.render(name, &context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_tera_engine() {
+        let mut tera = Tera::default();
+        tera.add_raw_template("hello.html", "Hello, {{ name }}!").unwrap();
+        let engine = TeraEngine::from_tera(tera);
+        assert_eq!(
+            engine.render("hello.html", &json!({"name": "Salvo"})).unwrap(),
+            "Hello, Salvo!"
+        );
+        assert!(engine.render("missing.html", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_tera_auto_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.html");
+        std::fs::write(&path, "v1").unwrap();
+        let engine = TeraEngine::new(&format!("{}/*.html", dir.path().display()))
+            .unwrap()
+            .auto_reload(true);
+        assert_eq!(engine.render("index.html", &json!({})).unwrap(), "v1");
+        std::fs::write(&path, "version 2").unwrap();
+        assert_eq!(engine.render("index.html", &json!({})).unwrap(), "version 2");
+        assert!(!engine.templates_changed());
+
+        assert_eq!(glob_base("templates/**/*.html"), PathBuf::from("templates/"));
+        assert_eq!(glob_base("*.html"), PathBuf::from("."));
+    }
+}
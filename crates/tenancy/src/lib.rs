@@ -0,0 +1,444 @@
+//! Multi-tenancy support for Savlo web server framework.
+//!
+//! [`TenantResolver`] reads the tenant id of each request from its [`TenantSource`]s, loads the tenant from a
+//! [`TenantStore`] and puts it into the depot, [`TenantScoped`] keeps state such as database pools per tenant, and
+//! [`TenantGuard`] limits routers to the tenants matching a predicate.
+//!
+//! # Example
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use salvo_core::http::header::HeaderName;
+//! use salvo_core::prelude::*;
+//! use salvo_tenancy::{TenantDepotExt, TenantGuard, TenantResolver, TenantSource};
+//!
+//! #[derive(Clone, Debug)]
+//! struct Tenant {
+//!     name: String,
+//!     premium: bool,
+//! }
+//!
+//! #[handler]
+//! async fn hello(depot: &mut Depot) -> String {
+//!     format!("Hello {}", depot.tenant::<Tenant>().unwrap().name)
+//! }
+//!
+//! let mut tenants = HashMap::new();
+//! tenants.insert("acme".to_owned(), Tenant { name: "Acme".into(), premium: true });
+//! let resolver = TenantResolver::new(tenants)
+//!     .source(TenantSource::subdomain("example.com"))
+//!     .source(TenantSource::header(HeaderName::from_static("x-tenant-id")));
+//! let router = Router::new()
+//!     .hoop(resolver)
+//!     .get(hello)
+//!     .push(Router::with_path("reports").hoop(TenantGuard::new(|t: &Tenant| t.premium)).get(hello));
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::future_not_send)]
+#![warn(rustdoc::broken_intra_doc_links)]
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use salvo_core::handler::{none_skipper, Skipper};
+use salvo_core::http::{StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+#[macro_use]
+mod cfg;
+
+mod scoped;
+mod source;
+
+pub use scoped::TenantScoped;
+pub use source::{TenantIdReader, TenantSource};
+
+/// The id of the tenant of current request, it is put into the depot by [`TenantResolver`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TenantId(pub String);
+impl Deref for TenantId {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Store which loads tenants by their ids.
+#[async_trait]
+pub trait TenantStore: Send + Sync + 'static {
+    /// The tenant type.
+    type Tenant: Clone + Send + Sync + 'static;
+    /// Error type.
+    type Error: StdError + Send + Sync + 'static;
+
+    /// Load the tenant by its id, returns `None` if it does not exist.
+    async fn load(&self, tenant_id: &str) -> Result<Option<Self::Tenant>, Self::Error>;
+}
+#[async_trait]
+impl<T> TenantStore for HashMap<String, T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    type Tenant = T;
+    type Error = Infallible;
+
+    async fn load(&self, tenant_id: &str) -> Result<Option<Self::Tenant>, Self::Error> {
+        Ok(self.get(tenant_id).cloned())
+    }
+}
+#[async_trait]
+impl<S> TenantStore for Arc<S>
+where
+    S: TenantStore,
+{
+    type Tenant = S::Tenant;
+    type Error = S::Error;
+
+    async fn load(&self, tenant_id: &str) -> Result<Option<Self::Tenant>, Self::Error> {
+        self.as_ref().load(tenant_id).await
+    }
+}
+
+/// Middleware which resolves the tenant of each request and puts it into the depot.
+///
+/// The sources are tried in order, and the first id found is used. Requests without a tenant id are rejected with
+/// `400 Bad Request` unless the resolver is [optional](TenantResolver::optional), and requests of unknown tenants are
+/// rejected with `404 Not Found`.
+pub struct TenantResolver<S> {
+    store: S,
+    sources: Vec<TenantSource>,
+    optional: bool,
+    missing_status: StatusCode,
+    unknown_status: StatusCode,
+    skipper: Box<dyn Skipper>,
+}
+impl<S> Debug for TenantResolver<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantResolver")
+            .field("sources", &self.sources)
+            .field("optional", &self.optional)
+            .field("missing_status", &self.missing_status)
+            .field("unknown_status", &self.unknown_status)
+            .finish()
+    }
+}
+impl<S> TenantResolver<S>
+where
+    S: TenantStore,
+{
+    /// Create a new `TenantResolver` which loads tenants from `store`.
+    #[inline]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            sources: vec![],
+            optional: false,
+            missing_status: StatusCode::BAD_REQUEST,
+            unknown_status: StatusCode::NOT_FOUND,
+            skipper: Box::new(none_skipper),
+        }
+    }
+    /// Add a source to read the tenant id from.
+    #[inline]
+    pub fn source(mut self, source: TenantSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+    /// Let requests without a tenant id pass through, such as the landing page of a SaaS. Requests with an unknown
+    /// tenant id are still rejected.
+    #[inline]
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+    /// Sets the status of responses for requests without a tenant id, default is `400 Bad Request`.
+    #[inline]
+    pub fn missing_status(mut self, status: StatusCode) -> Self {
+        self.missing_status = status;
+        self
+    }
+    /// Sets the status of responses for requests of unknown tenants, default is `404 Not Found`.
+    #[inline]
+    pub fn unknown_status(mut self, status: StatusCode) -> Self {
+        self.unknown_status = status;
+        self
+    }
+    /// Sets skipper.
+    #[inline]
+    pub fn skipper(mut self, skipper: impl Skipper) -> Self {
+        self.skipper = Box::new(skipper);
+        self
+    }
+
+    /// Resolve the tenant id of the request from the sources.
+    pub fn resolve_id(&self, req: &Request, depot: &Depot) -> Option<String> {
+        self.sources.iter().find_map(|source| source.resolve(req, depot))
+    }
+}
+
+#[async_trait]
+impl<S> Handler for TenantResolver<S>
+where
+    S: TenantStore,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.skipper.skipped(req, depot) {
+            return;
+        }
+        let Some(tenant_id) = self.resolve_id(req, depot) else {
+            if !self.optional {
+                res.render(StatusError::from_code(self.missing_status).unwrap_or_else(StatusError::bad_request));
+                ctrl.skip_rest();
+            }
+            return;
+        };
+        match self.store.load(&tenant_id).await {
+            Ok(Some(tenant)) => {
+                depot.inject(tenant);
+                depot.inject(TenantId(tenant_id));
+            }
+            Ok(None) => {
+                res.render(StatusError::from_code(self.unknown_status).unwrap_or_else(StatusError::not_found));
+                ctrl.skip_rest();
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, tenant_id = %tenant_id, "load tenant failed");
+                res.render(StatusError::internal_server_error());
+                ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+/// Extension for getting the tenant of current request from depot.
+pub trait TenantDepotExt {
+    /// Get the tenant put by [`TenantResolver`].
+    fn tenant<T: Send + Sync + 'static>(&self) -> Option<&T>;
+    /// Get the id of the tenant.
+    fn tenant_id(&self) -> Option<&str>;
+    /// Get the state of the tenant put by [`TenantScoped`].
+    fn tenant_state<V: Send + Sync + 'static>(&self) -> Option<&Arc<V>>;
+}
+impl TenantDepotExt for Depot {
+    #[inline]
+    fn tenant<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.obtain::<T>().ok()
+    }
+    #[inline]
+    fn tenant_id(&self) -> Option<&str> {
+        self.obtain::<TenantId>().ok().map(|id| &*id.0)
+    }
+    #[inline]
+    fn tenant_state<V: Send + Sync + 'static>(&self) -> Option<&Arc<V>> {
+        self.obtain::<Arc<V>>().ok()
+    }
+}
+
+/// Middleware which limits a router to the tenants matching a predicate, such as features for premium tenants.
+///
+/// Requests of other tenants, or without a tenant, are rejected with `404 Not Found` by default so the router looks
+/// like it does not exist for them. It should be added after [`TenantResolver`].
+pub struct TenantGuard<T> {
+    predicate: Box<dyn Fn(&T) -> bool + Send + Sync>,
+    status: StatusCode,
+    _marker: PhantomData<fn(&T)>,
+}
+impl<T> Debug for TenantGuard<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantGuard").field("status", &self.status).finish()
+    }
+}
+impl<T> TenantGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Create a new `TenantGuard` which allows the tenants matching `predicate`.
+    #[inline]
+    pub fn new(predicate: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            predicate: Box::new(predicate),
+            status: StatusCode::NOT_FOUND,
+            _marker: PhantomData,
+        }
+    }
+    /// Sets the status of responses for rejected requests, default is `404 Not Found`.
+    #[inline]
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+#[async_trait]
+impl<T> Handler for TenantGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if !depot
+            .tenant::<T>()
+            .map(|tenant| (self.predicate)(tenant))
+            .unwrap_or(false)
+        {
+            res.render(StatusError::from_code(self.status).unwrap_or_else(StatusError::not_found));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use salvo_core::http::header::HeaderName;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct Tenant {
+        name: String,
+        premium: bool,
+    }
+
+    fn tenants() -> HashMap<String, Tenant> {
+        let mut tenants = HashMap::new();
+        tenants.insert(
+            "acme".to_owned(),
+            Tenant {
+                name: "Acme".into(),
+                premium: true,
+            },
+        );
+        tenants.insert(
+            "globex".to_owned(),
+            Tenant {
+                name: "Globex".into(),
+                premium: false,
+            },
+        );
+        tenants
+    }
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        let tenant = depot.tenant::<Tenant>().map(|t| t.name.clone()).unwrap_or_default();
+        format!("{tenant}|{}", depot.tenant_id().unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_sources() {
+        let resolver = TenantResolver::new(tenants())
+            .source(TenantSource::subdomain("example.com"))
+            .source(TenantSource::header(HeaderName::from_static("x-tenant-id")));
+        let service = Service::new(Router::new().hoop(resolver).get(hello));
+
+        let content = TestClient::get("http://acme.example.com:5801/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Acme|acme");
+
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-tenant-id", "globex", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Globex|globex");
+
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+
+        let res = TestClient::get("http://initech.example.com:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_path() {
+        let resolver = TenantResolver::new(tenants()).source(TenantSource::param("tenant"));
+        let router = Router::with_path("<tenant>/hello").hoop(resolver).get(hello);
+        let content = TestClient::get("http://127.0.0.1:5801/acme/hello")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Acme|acme");
+
+        let resolver = TenantResolver::new(tenants())
+            .source(TenantSource::PathSegment)
+            .optional();
+        let service = Service::new(Router::new().hoop(resolver).push(Router::with_path("<**>").get(hello)));
+        let content = TestClient::get("http://127.0.0.1:5801/globex/hello")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Globex|globex");
+        let res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_scoped_and_guard() {
+        #[handler]
+        async fn counter(depot: &mut Depot) -> String {
+            let count = depot.tenant_state::<AtomicUsize>().unwrap();
+            (count.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+        }
+
+        let scoped = TenantScoped::new(|_: &str| AtomicUsize::new(0));
+        let router = Router::new()
+            .hoop(TenantResolver::new(tenants()).source(TenantSource::query("tenant")))
+            .hoop(scoped.clone())
+            .get(counter)
+            .push(
+                Router::with_path("premium")
+                    .hoop(TenantGuard::new(|t: &Tenant| t.premium))
+                    .get(hello),
+            );
+        let service = Service::new(router);
+
+        for (url, expected) in [("/?tenant=acme", "1"), ("/?tenant=acme", "2"), ("/?tenant=globex", "1")] {
+            let content = TestClient::get(format!("http://127.0.0.1:5801{url}"))
+                .send(&service)
+                .await
+                .take_string()
+                .await
+                .unwrap();
+            assert_eq!(content, expected);
+        }
+        assert_eq!(scoped.get("acme").unwrap().load(Ordering::SeqCst), 2);
+        scoped.remove("acme");
+        assert!(scoped.get("acme").is_none());
+
+        let res = TestClient::get("http://127.0.0.1:5801/premium?tenant=acme")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let res = TestClient::get("http://127.0.0.1:5801/premium?tenant=globex")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+use salvo_core::http::StatusError;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+use crate::TenantDepotExt;
+
+/// State scoped per tenant, such as a database pool or a cache of each tenant.
+///
+/// The state of a tenant is created by the factory on first use and shared afterwards. As a handler, it injects the
+/// state of the current tenant into the depot, which can be got by [`TenantDepotExt::tenant_state`], it should be
+/// added after [`TenantResolver`](crate::TenantResolver).
+pub struct TenantScoped<V> {
+    factory: Arc<dyn Fn(&str) -> V + Send + Sync>,
+    values: Arc<RwLock<HashMap<String, Arc<V>>>>,
+}
+impl<V> Clone for TenantScoped<V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            factory: self.factory.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+impl<V> Debug for TenantScoped<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let tenants = self.values.read().map(|values| values.len()).unwrap_or_default();
+        f.debug_struct("TenantScoped").field("tenants", &tenants).finish()
+    }
+}
+impl<V> TenantScoped<V>
+where
+    V: Send + Sync + 'static,
+{
+    /// Create a new `TenantScoped` which creates the state of a tenant by `factory` with the tenant id.
+    #[inline]
+    pub fn new(factory: impl Fn(&str) -> V + Send + Sync + 'static) -> Self {
+        Self {
+            factory: Arc::new(factory),
+            values: Default::default(),
+        }
+    }
+    /// Get the state of the tenant, creating it if it does not exist yet.
+    pub fn get_or_init(&self, tenant_id: &str) -> Arc<V> {
+        if let Some(value) = self.get(tenant_id) {
+            return value;
+        }
+        let mut values = self.values.write().unwrap_or_else(|e| e.into_inner());
+        values
+            .entry(tenant_id.to_owned())
+            .or_insert_with(|| Arc::new((self.factory)(tenant_id)))
+            .clone()
+    }
+    /// Get the state of the tenant if it has been created.
+    #[inline]
+    pub fn get(&self, tenant_id: &str) -> Option<Arc<V>> {
+        self.values
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tenant_id)
+            .cloned()
+    }
+    /// Remove the state of the tenant, such as when the tenant is deleted or its config is changed. It will be created
+    /// again on next use.
+    #[inline]
+    pub fn remove(&self, tenant_id: &str) -> Option<Arc<V>> {
+        self.values.write().unwrap_or_else(|e| e.into_inner()).remove(tenant_id)
+    }
+    /// Remove the states of all tenants.
+    #[inline]
+    pub fn clear(&self) {
+        self.values.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+#[async_trait]
+impl<V> Handler for TenantScoped<V>
+where
+    V: Send + Sync + 'static,
+{
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let value = match depot.tenant_id() {
+            Some(tenant_id) => self.get_or_init(tenant_id),
+            None => {
+                tracing::error!("tenant id not found in depot, `TenantResolver` should be added before `TenantScoped`");
+                res.render(StatusError::internal_server_error());
+                ctrl.skip_rest();
+                return;
+            }
+        };
+        depot.inject(value);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::{self, Debug, Formatter};
+
+use salvo_core::http::header::{HeaderName, HOST};
+use salvo_core::{Depot, Request};
+
+/// Function reading the tenant id of a request, used by [`TenantSource::Custom`].
+pub type TenantIdReader = Box<dyn Fn(&Request, &Depot) -> Option<String> + Send + Sync>;
+
+/// Where the tenant id of a request is read from.
+pub enum TenantSource {
+    /// The host of the request, such as `acme.com` for tenants with their own domains.
+    Host,
+    /// The subdomain under a base domain, such as `acme` of `acme.example.com`.
+    Subdomain(String),
+    /// The first segment of the path, such as `acme` of `/acme/orders`.
+    PathSegment,
+    /// A path param of the matched route, such as `<tenant>` of `/<tenant>/orders`.
+    Param(String),
+    /// A request header.
+    Header(HeaderName),
+    /// A query param.
+    Query(String),
+    /// A custom function, such as reading a claim set by an authentication middleware.
+    Custom(TenantIdReader),
+}
+impl Debug for TenantSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Host => f.write_str("Host"),
+            Self::Subdomain(base) => f.debug_tuple("Subdomain").field(base).finish(),
+            Self::PathSegment => f.write_str("PathSegment"),
+            Self::Param(name) => f.debug_tuple("Param").field(name).finish(),
+            Self::Header(name) => f.debug_tuple("Header").field(name).finish(),
+            Self::Query(name) => f.debug_tuple("Query").field(name).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+impl TenantSource {
+    /// Read the tenant id from the subdomain under `base_domain`.
+    #[inline]
+    pub fn subdomain(base_domain: impl Into<String>) -> Self {
+        Self::Subdomain(base_domain.into().trim_matches('.').to_lowercase())
+    }
+    /// Read the tenant id from a path param of the matched route.
+    #[inline]
+    pub fn param(name: impl Into<String>) -> Self {
+        Self::Param(name.into())
+    }
+    /// Read the tenant id from a request header.
+    #[inline]
+    pub fn header(name: HeaderName) -> Self {
+        Self::Header(name)
+    }
+    /// Read the tenant id from a query param.
+    #[inline]
+    pub fn query(name: impl Into<String>) -> Self {
+        Self::Query(name.into())
+    }
+    /// Read the tenant id by a custom function.
+    #[inline]
+    pub fn custom(f: impl Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::Custom(Box::new(f))
+    }
+
+    cfg_feature! {
+        #![feature = "jwt-auth"]
+        /// Read the tenant id from the claims decoded by [`JwtAuth`](salvo_jwt_auth::JwtAuth), it should be
+        /// added before [`TenantResolver`](crate::TenantResolver).
+        pub fn jwt_claim<C, F>(f: F) -> Self
+        where
+            C: serde::de::DeserializeOwned + Send + Sync + 'static,
+            F: Fn(&C) -> Option<String> + Send + Sync + 'static,
+        {
+            use salvo_jwt_auth::JwtAuthDepotExt;
+            Self::custom(move |_req, depot| depot.jwt_auth_data::<C>().and_then(|data| f(&data.claims)))
+        }
+    }
+
+    /// Resolve the tenant id of the request, returns `None` if it is not found.
+    pub fn resolve(&self, req: &Request, depot: &Depot) -> Option<String> {
+        let id = match self {
+            Self::Host => host(req),
+            Self::Subdomain(base) => {
+                let host = host(req)?;
+                let subdomain = host.strip_suffix(base.as_str())?.strip_suffix('.')?;
+                // Only the label just under the base domain is the tenant, such as `acme` of `www.acme.example.com`.
+                subdomain.rsplit('.').next().map(ToOwned::to_owned)
+            }
+            Self::PathSegment => req
+                .uri()
+                .path()
+                .split('/')
+                .find(|s| !s.is_empty())
+                .map(ToOwned::to_owned),
+            Self::Param(name) => req.params().get(name).cloned(),
+            Self::Header(name) => req
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned),
+            Self::Query(name) => req.query::<String>(name),
+            Self::Custom(f) => f(req, depot),
+        };
+        id.filter(|id| !id.is_empty())
+    }
+}
+
+/// Get the host of the request without port, in lowercase.
+fn host(req: &Request) -> Option<String> {
+    let host = match req.uri().host() {
+        Some(host) => host,
+        None => req.headers().get(HOST)?.to_str().ok()?,
+    };
+    let host = match host.rsplit_once(':') {
+        // Keep IPv6 hosts such as `[::1]`.
+        Some((host, port)) if !host.is_empty() && !port.contains(']') => host,
+        _ => host,
+    };
+    Some(host.to_lowercase())
+}
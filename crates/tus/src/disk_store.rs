@@ -0,0 +1,149 @@
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use salvo_core::async_trait;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use super::{UploadInfo, UploadStore};
+
+/// A store saves uploads in a directory.
+///
+/// The data of an upload is saved in `<id>.bin` and its information is saved in `<id>.json`, the offset is the
+/// size of the data file, so data written before a crash is not lost.
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+impl DiskStore {
+    /// Create a new `DiskStore`, the directory is created when the first upload is created.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    /// Get the directory of uploads.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Get the path of the data file of an upload.
+    #[inline]
+    pub fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.bin"))
+    }
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    async fn save_info(&self, info: &UploadInfo) -> Result<(), IoError> {
+        let json = serde_json::to_vec(info)?;
+        let tmp = self.dir.join(format!("{}.json.tmp", info.id));
+        fs::write(&tmp, json).await?;
+        fs::rename(&tmp, self.info_path(&info.id)).await
+    }
+}
+
+async fn remove_file(path: PathBuf) -> Result<(), IoError> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl UploadStore for DiskStore {
+    type Error = IoError;
+
+    async fn create(&self, info: &UploadInfo) -> Result<(), Self::Error> {
+        fs::create_dir_all(&self.dir).await?;
+        fs::File::create(self.path(&info.id)).await?;
+        self.save_info(info).await
+    }
+    async fn info(&self, id: &str) -> Result<Option<UploadInfo>, Self::Error> {
+        let json = match fs::read(self.info_path(id)).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut info: UploadInfo = serde_json::from_slice(&json)?;
+        info.offset = fs::metadata(self.path(id)).await?.len();
+        Ok(Some(info))
+    }
+    async fn append(&self, id: &str, offset: u64, data: Bytes) -> Result<u64, Self::Error> {
+        let mut file = OpenOptions::new().append(true).open(self.path(id)).await?;
+        let len = file.metadata().await?.len();
+        if len != offset {
+            return Err(IoError::new(ErrorKind::InvalidInput, "offset does not match"));
+        }
+        file.write_all(&data).await?;
+        file.flush().await?;
+        Ok(len + data.len() as u64)
+    }
+    async fn update(&self, info: &UploadInfo) -> Result<(), Self::Error> {
+        self.save_info(info).await
+    }
+    async fn delete(&self, id: &str) -> Result<(), Self::Error> {
+        remove_file(self.info_path(id)).await?;
+        remove_file(self.path(id)).await
+    }
+    async fn delete_expired(&self, now: SystemTime) -> Result<usize, Self::Error> {
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut count = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if let Some(info) = self.info(id).await? {
+                if info.is_expired(now) {
+                    self.delete(id).await?;
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disk_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskStore::new(dir.path().join("uploads"));
+        let mut info = UploadInfo::new("abc", Some(11));
+        info.expires_at = Some(SystemTime::now() + Duration::from_secs(60));
+        store.create(&info).await.unwrap();
+
+        assert_eq!(store.append("abc", 0, Bytes::from_static(b"hello")).await.unwrap(), 5);
+        assert!(store.append("abc", 0, Bytes::from_static(b"hello")).await.is_err());
+        assert_eq!(store.info("abc").await.unwrap().unwrap().offset, 5);
+        assert_eq!(store.append("abc", 5, Bytes::from_static(b" world")).await.unwrap(), 11);
+        assert_eq!(std::fs::read(store.path("abc")).unwrap(), b"hello world");
+        assert!(store.info("abc").await.unwrap().unwrap().is_complete());
+
+        assert_eq!(store.delete_expired(SystemTime::now()).await.unwrap(), 0);
+        assert_eq!(
+            store
+                .delete_expired(SystemTime::now() + Duration::from_secs(120))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(store.info("abc").await.unwrap().is_none());
+        assert!(!store.path("abc").exists());
+    }
+}
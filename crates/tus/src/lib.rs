@@ -0,0 +1,766 @@
+//! Tus resumable upload protocol support for Salvo web framework.
+//!
+//! [`Tus`] implements the core protocol of [tus 1.0.0](https://tus.io/protocols/resumable-upload) with the
+//! `creation`, `creation-with-upload`, `expiration` and `termination` extensions. Clients create an upload with
+//! `POST`, query its offset with `HEAD` and append data with `PATCH`, so an interrupted upload resumes from the
+//! last received byte instead of restarting.
+//!
+//! Uploads are saved by an [`UploadStore`], [`MemoryStore`] and [`DiskStore`] are provided. You can define your
+//! own store by implementing [`UploadStore`].
+//!
+//! # CORS
+//!
+//! Browser clients send preflight requests, so [`Cors`](https://docs.rs/salvo-cors) should be added before
+//! `Tus`, allowing [`ALLOW_HEADERS`] and exposing [`EXPOSE_HEADERS`].
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_tus::{MemoryStore, Tus};
+//!
+//! let tus = Tus::new(MemoryStore::new())
+//!     .max_size(1024 * 1024 * 1024)
+//!     .on_complete(|info| async move {
+//!         println!("upload {} is completed", info.id);
+//!     });
+//! let router = Router::new().push(tus.into_router("files"));
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(unreachable_pub)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error as StdError;
+use std::fmt::{self, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use rand::Rng;
+use salvo_core::http::header::{HeaderName, HeaderValue, CACHE_CONTROL, EXPIRES, LOCATION};
+use salvo_core::http::headers::{Expires, HeaderMapExt};
+use salvo_core::http::{Method, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Router};
+use serde::{Deserialize, Serialize};
+
+#[macro_use]
+mod cfg;
+
+cfg_feature! {
+    #![feature = "memory-store"]
+
+    mod memory_store;
+    pub use memory_store::MemoryStore;
+}
+cfg_feature! {
+    #![feature = "disk-store"]
+
+    mod disk_store;
+    pub use disk_store::DiskStore;
+}
+
+/// The version of the tus protocol.
+pub const TUS_VERSION: &str = "1.0.0";
+/// Extensions supported by [`Tus`].
+pub const TUS_EXTENSIONS: &str = "creation,creation-with-upload,expiration,termination";
+/// Content type of `PATCH` requests.
+pub const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// `Tus-Resumable` header.
+pub const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+/// `Tus-Version` header.
+pub const TUS_VERSION_HEADER: HeaderName = HeaderName::from_static("tus-version");
+/// `Tus-Extension` header.
+pub const TUS_EXTENSION: HeaderName = HeaderName::from_static("tus-extension");
+/// `Tus-Max-Size` header.
+pub const TUS_MAX_SIZE: HeaderName = HeaderName::from_static("tus-max-size");
+/// `Upload-Offset` header.
+pub const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+/// `Upload-Length` header.
+pub const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+/// `Upload-Defer-Length` header.
+pub const UPLOAD_DEFER_LENGTH: HeaderName = HeaderName::from_static("upload-defer-length");
+/// `Upload-Metadata` header.
+pub const UPLOAD_METADATA: HeaderName = HeaderName::from_static("upload-metadata");
+/// `Upload-Expires` header.
+pub const UPLOAD_EXPIRES: HeaderName = HeaderName::from_static("upload-expires");
+/// `X-HTTP-Method-Override` header, it is used by clients which can not send `PATCH` or `DELETE` requests.
+pub const X_HTTP_METHOD_OVERRIDE: HeaderName = HeaderName::from_static("x-http-method-override");
+
+/// Request headers should be allowed by CORS.
+pub const ALLOW_HEADERS: [&str; 7] = [
+    "tus-resumable",
+    "upload-length",
+    "upload-defer-length",
+    "upload-metadata",
+    "upload-offset",
+    "x-http-method-override",
+    "content-type",
+];
+/// Response headers should be exposed by CORS.
+pub const EXPOSE_HEADERS: [&str; 10] = [
+    "location",
+    "tus-resumable",
+    "tus-version",
+    "tus-extension",
+    "tus-max-size",
+    "upload-length",
+    "upload-defer-length",
+    "upload-metadata",
+    "upload-offset",
+    "upload-expires",
+];
+
+/// Information of an upload.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct UploadInfo {
+    /// Id of the upload.
+    pub id: String,
+    /// Number of bytes received.
+    pub offset: u64,
+    /// Total size of the upload, `None` if the client deferred it.
+    pub length: Option<u64>,
+    /// Metadata sent by the client, values are decoded from base64.
+    pub metadata: BTreeMap<String, String>,
+    /// Time when the upload is created.
+    pub created_at: SystemTime,
+    /// Time when the unfinished upload expires, completed uploads do not expire.
+    pub expires_at: Option<SystemTime>,
+}
+impl UploadInfo {
+    /// Create a new `UploadInfo`.
+    #[inline]
+    pub fn new(id: impl Into<String>, length: Option<u64>) -> Self {
+        Self {
+            id: id.into(),
+            offset: 0,
+            length,
+            metadata: BTreeMap::new(),
+            created_at: SystemTime::now(),
+            expires_at: None,
+        }
+    }
+    /// Returns `true` if all bytes are received.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.length == Some(self.offset)
+    }
+    /// Returns `true` if the upload is expired.
+    #[inline]
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at.map(|expires_at| expires_at <= now).unwrap_or(false)
+    }
+}
+
+/// Store of uploads.
+#[async_trait]
+pub trait UploadStore: Send + Sync + 'static {
+    /// Error type for UploadStore.
+    type Error: StdError + Sync + Send + 'static;
+    /// Create a new upload.
+    async fn create(&self, info: &UploadInfo) -> Result<(), Self::Error>;
+    /// Get the information of an upload, `None` if it does not exist.
+    async fn info(&self, id: &str) -> Result<Option<UploadInfo>, Self::Error>;
+    /// Append data at the offset of an upload and returns the new offset.
+    ///
+    /// It should fail if the offset is not the current offset of the upload.
+    async fn append(&self, id: &str, offset: u64, data: Bytes) -> Result<u64, Self::Error>;
+    /// Save the length and the expiration of an upload, the offset is managed by [`UploadStore::append`].
+    async fn update(&self, info: &UploadInfo) -> Result<(), Self::Error>;
+    /// Delete an upload and its data.
+    async fn delete(&self, id: &str) -> Result<(), Self::Error>;
+    /// Delete all expired uploads and returns the number of them.
+    async fn delete_expired(&self, now: SystemTime) -> Result<usize, Self::Error>;
+}
+
+/// Parses `Upload-Metadata` header, returns `None` if it is invalid.
+fn parse_metadata(value: &str) -> Option<BTreeMap<String, String>> {
+    let mut metadata = BTreeMap::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (key, value) = match pair.split_once(' ') {
+            Some((key, value)) => (key, STANDARD.decode(value.trim()).ok()?),
+            None => (pair, Vec::new()),
+        };
+        if key.is_empty() {
+            return None;
+        }
+        metadata.insert(key.to_owned(), String::from_utf8_lossy(&value).into_owned());
+    }
+    Some(metadata)
+}
+
+fn encode_metadata(metadata: &BTreeMap<String, String>) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| {
+            if value.is_empty() {
+                key.clone()
+            } else {
+                format!("{key} {}", STANDARD.encode(value))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns `true` if the id can be used as a file name.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+type CompleteHandler = Box<dyn Fn(UploadInfo) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Tus resumable upload handler.
+///
+/// It should handle all requests under its path, use [`Tus::into_router`] to create the router.
+/// Unfinished uploads expire after 24 hours without new data by default, expired uploads are deleted when they
+/// are accessed, or by [`Tus::delete_expired`] which can be called periodically.
+pub struct Tus<S> {
+    store: S,
+    max_size: Option<u64>,
+    expiration: Option<Duration>,
+    on_complete: Option<CompleteHandler>,
+    locks: Mutex<HashSet<String>>,
+}
+impl<S: fmt::Debug> fmt::Debug for Tus<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tus")
+            .field("store", &self.store)
+            .field("max_size", &self.max_size)
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+/// Releases the lock of an upload when the `PATCH` request finishes, even if it is cancelled.
+struct UploadLock<'a> {
+    locks: &'a Mutex<HashSet<String>>,
+    id: String,
+}
+impl Drop for UploadLock<'_> {
+    fn drop(&mut self) {
+        self.locks.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+    }
+}
+
+impl<S: UploadStore> Tus<S> {
+    /// Create new `Tus` with the store.
+    #[inline]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            max_size: None,
+            expiration: Some(Duration::from_secs(24 * 60 * 60)),
+            on_complete: None,
+            locks: Mutex::new(HashSet::new()),
+        }
+    }
+    /// Get the store.
+    #[inline]
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+    /// Sets the max size of an upload in bytes, uploads have no limit by default.
+    #[inline]
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+    /// Sets how long an unfinished upload is kept without new data, `None` means it never expires.
+    #[inline]
+    pub fn expiration(mut self, expiration: Option<Duration>) -> Self {
+        self.expiration = expiration;
+        self
+    }
+    /// Sets the callback which is called when an upload is completed, the response is sent after it returns.
+    #[inline]
+    pub fn on_complete<F, Fut>(mut self, on_complete: F) -> Self
+    where
+        F: Fn(UploadInfo) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(move |info| Box::pin(on_complete(info))));
+        self
+    }
+    /// Consumes the `Tus` and returns [`Router`] with the `Tus` as handler.
+    #[inline]
+    pub fn into_router(self, path: impl Into<String>) -> Router {
+        Router::with_path(format!("{}/<**>", path.into())).goal(self)
+    }
+    /// Delete all expired uploads and returns the number of them.
+    #[inline]
+    pub async fn delete_expired(&self) -> Result<usize, S::Error> {
+        self.store.delete_expired(SystemTime::now()).await
+    }
+
+    fn expires_at(&self, info: &UploadInfo) -> Option<SystemTime> {
+        if info.is_complete() {
+            None
+        } else {
+            self.expiration.map(|expiration| SystemTime::now() + expiration)
+        }
+    }
+
+    async fn create(&self, req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+        let length = req.header::<String>(UPLOAD_LENGTH);
+        let defer = req.header::<String>(UPLOAD_DEFER_LENGTH);
+        let length = match (length, defer.as_deref()) {
+            (Some(length), None) => Some(
+                length
+                    .parse::<u64>()
+                    .map_err(|_| StatusError::bad_request().brief("Upload-Length is invalid."))?,
+            ),
+            (None, Some("1")) => None,
+            _ => {
+                return Err(StatusError::bad_request().brief("Either Upload-Length or Upload-Defer-Length is required."))
+            }
+        };
+        if let (Some(length), Some(max_size)) = (length, self.max_size) {
+            if length > max_size {
+                return Err(StatusError::payload_too_large().brief("Upload-Length exceeds Tus-Max-Size."));
+            }
+        }
+        let metadata = match req.header::<String>(UPLOAD_METADATA) {
+            Some(value) => {
+                parse_metadata(&value).ok_or_else(|| StatusError::bad_request().brief("Upload-Metadata is invalid."))?
+            }
+            None => BTreeMap::new(),
+        };
+
+        let id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+        let mut info = UploadInfo::new(id, length);
+        info.metadata = metadata;
+        info.expires_at = self.expires_at(&info);
+        self.store.create(&info).await.map_err(|e| {
+            tracing::error!(error = ?e, "create upload failed");
+            StatusError::internal_server_error()
+        })?;
+
+        let location = format!("{}/{}", req.uri().path().trim_end_matches('/'), info.id);
+        if let Ok(location) = HeaderValue::from_str(&location) {
+            res.headers_mut().insert(LOCATION, location);
+        }
+        if is_offset_octet_stream(req) {
+            self.write(&mut info, req).await?;
+            res.headers_mut().insert(UPLOAD_OFFSET, info.offset.into());
+        } else if info.is_complete() {
+            self.complete(&info).await;
+        }
+        insert_expires(res, &info);
+        res.status_code(StatusCode::CREATED);
+        Ok(())
+    }
+
+    async fn head(&self, info: UploadInfo, res: &mut Response) {
+        let headers = res.headers_mut();
+        headers.insert(UPLOAD_OFFSET, info.offset.into());
+        match info.length {
+            Some(length) => headers.insert(UPLOAD_LENGTH, length.into()),
+            None => headers.insert(UPLOAD_DEFER_LENGTH, HeaderValue::from_static("1")),
+        };
+        if !info.metadata.is_empty() {
+            if let Ok(metadata) = HeaderValue::from_str(&encode_metadata(&info.metadata)) {
+                headers.insert(UPLOAD_METADATA, metadata);
+            }
+        }
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        insert_expires(res, &info);
+        res.status_code(StatusCode::OK);
+    }
+
+    async fn patch(&self, mut info: UploadInfo, req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+        if !is_offset_octet_stream(req) {
+            return Err(
+                StatusError::unsupported_media_type().brief("Content-Type must be application/offset+octet-stream.")
+            );
+        }
+        let Some(offset) = req.header::<u64>(UPLOAD_OFFSET) else {
+            return Err(StatusError::bad_request().brief("Upload-Offset is invalid."));
+        };
+        if offset != info.offset {
+            return Err(StatusError::conflict().brief("Upload-Offset does not match the offset of the upload."));
+        }
+        if let Some(length) = req.header::<String>(UPLOAD_LENGTH) {
+            let length = length
+                .parse::<u64>()
+                .map_err(|_| StatusError::bad_request().brief("Upload-Length is invalid."))?;
+            match info.length {
+                Some(current) if current != length => {
+                    return Err(StatusError::bad_request().brief("Upload-Length can not be changed."));
+                }
+                Some(_) => {}
+                None => {
+                    if length < info.offset {
+                        return Err(StatusError::bad_request().brief("Upload-Length is less than Upload-Offset."));
+                    }
+                    if self.max_size.map(|max_size| length > max_size).unwrap_or(false) {
+                        return Err(StatusError::payload_too_large().brief("Upload-Length exceeds Tus-Max-Size."));
+                    }
+                    info.length = Some(length);
+                }
+            }
+        }
+
+        let _lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            if !locks.insert(info.id.clone()) {
+                return Err(StatusError::locked().brief("The upload is being written by another request."));
+            }
+            UploadLock {
+                locks: &self.locks,
+                id: info.id.clone(),
+            }
+        };
+        self.write(&mut info, req).await?;
+        res.headers_mut().insert(UPLOAD_OFFSET, info.offset.into());
+        insert_expires(res, &info);
+        res.status_code(StatusCode::NO_CONTENT);
+        Ok(())
+    }
+
+    /// Appends the request body to the upload, data received before the connection is lost is kept.
+    async fn write(&self, info: &mut UploadInfo, req: &mut Request) -> Result<(), StatusError> {
+        let limit = info.length.or(self.max_size);
+        let mut body = req.take_body();
+        let mut result = Ok(());
+        while let Some(frame) = body.frame().await {
+            let data = match frame {
+                Ok(frame) => match frame.into_data() {
+                    Ok(data) => data,
+                    Err(_) => continue,
+                },
+                Err(e) => {
+                    tracing::debug!(error = ?e, id = info.id, "read upload body failed");
+                    break;
+                }
+            };
+            if data.is_empty() {
+                continue;
+            }
+            if limit
+                .map(|limit| info.offset + data.len() as u64 > limit)
+                .unwrap_or(false)
+            {
+                result = Err(StatusError::payload_too_large().brief("Upload exceeds Upload-Length or Tus-Max-Size."));
+                break;
+            }
+            match self.store.append(&info.id, info.offset, data).await {
+                Ok(offset) => info.offset = offset,
+                Err(e) => {
+                    tracing::error!(error = ?e, id = info.id, "append upload failed");
+                    result = Err(StatusError::internal_server_error());
+                    break;
+                }
+            }
+        }
+
+        info.expires_at = self.expires_at(info);
+        if let Err(e) = self.store.update(info).await {
+            tracing::error!(error = ?e, id = info.id, "update upload failed");
+            return Err(StatusError::internal_server_error());
+        }
+        if result.is_ok() && info.is_complete() {
+            self.complete(info).await;
+        }
+        result
+    }
+
+    async fn complete(&self, info: &UploadInfo) {
+        if let Some(on_complete) = &self.on_complete {
+            on_complete(info.clone()).await;
+        }
+    }
+
+    fn options(&self, res: &mut Response) {
+        let headers = res.headers_mut();
+        headers.insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+        headers.insert(TUS_EXTENSION, HeaderValue::from_static(TUS_EXTENSIONS));
+        if let Some(max_size) = self.max_size {
+            headers.insert(TUS_MAX_SIZE, max_size.into());
+        }
+        res.status_code(StatusCode::NO_CONTENT);
+    }
+
+    async fn handle_upload(
+        &self,
+        id: &str,
+        method: &Method,
+        req: &mut Request,
+        res: &mut Response,
+    ) -> Result<(), StatusError> {
+        let info = if is_valid_id(id) {
+            self.store.info(id).await.map_err(|e| {
+                tracing::error!(error = ?e, id, "load upload failed");
+                StatusError::internal_server_error()
+            })?
+        } else {
+            None
+        };
+        let Some(info) = info else {
+            return Err(StatusError::not_found());
+        };
+        if info.is_expired(SystemTime::now()) {
+            if let Err(e) = self.store.delete(id).await {
+                tracing::error!(error = ?e, id, "delete expired upload failed");
+            }
+            return Err(StatusError::gone().brief("The upload is expired."));
+        }
+        match *method {
+            Method::HEAD => {
+                self.head(info, res).await;
+                Ok(())
+            }
+            Method::PATCH => self.patch(info, req, res).await,
+            Method::DELETE => {
+                self.store.delete(id).await.map_err(|e| {
+                    tracing::error!(error = ?e, id, "delete upload failed");
+                    StatusError::internal_server_error()
+                })?;
+                res.status_code(StatusCode::NO_CONTENT);
+                Ok(())
+            }
+            _ => Err(StatusError::method_not_allowed()),
+        }
+    }
+}
+
+fn is_offset_octet_stream(req: &Request) -> bool {
+    req.content_type()
+        .map(|ctype| ctype.essence_str() == OFFSET_OCTET_STREAM)
+        .unwrap_or(false)
+}
+
+fn insert_expires(res: &mut Response, info: &UploadInfo) {
+    if let Some(expires_at) = info.expires_at {
+        let mut headers = salvo_core::http::HeaderMap::new();
+        headers.typed_insert(Expires::from(expires_at));
+        if let Some(value) = headers.remove(EXPIRES) {
+            res.headers_mut().insert(UPLOAD_EXPIRES, value);
+        }
+    }
+}
+
+#[async_trait]
+impl<S: UploadStore> Handler for Tus<S> {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.skip_rest();
+        res.headers_mut()
+            .insert(TUS_RESUMABLE, HeaderValue::from_static(TUS_VERSION));
+        let method = req
+            .header::<String>(X_HTTP_METHOD_OVERRIDE)
+            .and_then(|method| method.parse::<Method>().ok())
+            .unwrap_or_else(|| req.method().clone());
+        if method == Method::OPTIONS {
+            return self.options(res);
+        }
+        if req.header::<String>(TUS_RESUMABLE).as_deref() != Some(TUS_VERSION) {
+            res.headers_mut()
+                .insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+            res.render(StatusError::precondition_failed().brief("Tus-Resumable 1.0.0 is required."));
+            return;
+        }
+        let id = req.params().get("**").cloned().unwrap_or_default();
+        let result = if id.is_empty() {
+            if method == Method::POST {
+                self.create(req, res).await
+            } else {
+                Err(StatusError::method_not_allowed())
+            }
+        } else {
+            self.handle_upload(&id, &method, req, res).await
+        };
+        if let Err(e) = result {
+            res.render(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let metadata = parse_metadata("filename d29ybGRfZG9taW5hdGlvbl9wbGFuLnBkZg==,is_confidential").unwrap();
+        assert_eq!(metadata["filename"], "world_domination_plan.pdf");
+        assert_eq!(metadata["is_confidential"], "");
+        assert_eq!(
+            encode_metadata(&metadata),
+            "filename d29ybGRfZG9taW5hdGlvbl9wbGFuLnBkZg==,is_confidential"
+        );
+        assert!(parse_metadata("filename !!!").is_none());
+    }
+
+    fn header(res: &Response, name: HeaderName) -> String {
+        res.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_owned()
+    }
+
+    async fn create(service: &Service, length: &str) -> String {
+        let res = TestClient::post("http://127.0.0.1:5801/files")
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(UPLOAD_LENGTH, length, true)
+            .add_header(UPLOAD_METADATA, "filename aGVsbG8udHh0", true)
+            .send(service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        header(&res, LOCATION)
+    }
+
+    async fn patch(service: &Service, location: &str, offset: u64, data: &'static [u8]) -> Response {
+        TestClient::patch(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(UPLOAD_OFFSET, offset.to_string(), true)
+            .add_header("content-type", OFFSET_OCTET_STREAM, true)
+            .bytes(data.to_vec())
+            .send(service)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_tus() {
+        let completed = Arc::new(AtomicUsize::new(0));
+        let tus = {
+            let completed = completed.clone();
+            Tus::new(MemoryStore::new()).max_size(100).on_complete(move |info| {
+                let completed = completed.clone();
+                async move {
+                    assert_eq!(info.offset, 11);
+                    completed.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+        let store = tus.store().clone();
+        let service = Service::new(Router::new().push(tus.into_router("files")));
+
+        let res = TestClient::options("http://127.0.0.1:5801/files").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(header(&res, TUS_EXTENSION), TUS_EXTENSIONS);
+        assert_eq!(header(&res, TUS_MAX_SIZE), "100");
+
+        let res = TestClient::post("http://127.0.0.1:5801/files")
+            .add_header(UPLOAD_LENGTH, "11", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PRECONDITION_FAILED));
+        assert_eq!(header(&res, TUS_VERSION_HEADER), TUS_VERSION);
+
+        let res = TestClient::post("http://127.0.0.1:5801/files")
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(UPLOAD_LENGTH, "101", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+
+        let location = create(&service, "11").await;
+        assert!(location.starts_with("/files/"));
+        let id = location.trim_start_matches("/files/").to_owned();
+
+        let res = patch(&service, &location, 0, b"hello").await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(header(&res, UPLOAD_OFFSET), "5");
+        assert!(!header(&res, UPLOAD_EXPIRES).is_empty());
+
+        let res = patch(&service, &location, 0, b"hello").await;
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+
+        let res = TestClient::head(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(header(&res, UPLOAD_OFFSET), "5");
+        assert_eq!(header(&res, UPLOAD_LENGTH), "11");
+        assert_eq!(header(&res, UPLOAD_METADATA), "filename aGVsbG8udHh0");
+        assert_eq!(header(&res, CACHE_CONTROL), "no-store");
+
+        let res = patch(&service, &location, 5, b" world, too long").await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+        let res = patch(&service, &location, 5, b" world").await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(header(&res, UPLOAD_OFFSET), "11");
+        assert!(header(&res, UPLOAD_EXPIRES).is_empty());
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+        assert_eq!(store.data(&id).unwrap(), &b"hello world"[..]);
+
+        let res = TestClient::post(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(X_HTTP_METHOD_OVERRIDE, "DELETE", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        let res = TestClient::head(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_tus_creation_with_upload() {
+        let service = Service::new(Router::new().push(Tus::new(MemoryStore::new()).into_router("files")));
+        let res = TestClient::post("http://127.0.0.1:5801/files")
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(UPLOAD_DEFER_LENGTH, "1", true)
+            .add_header("content-type", OFFSET_OCTET_STREAM, true)
+            .bytes(b"hello".to_vec())
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        assert_eq!(header(&res, UPLOAD_OFFSET), "5");
+        let location = header(&res, LOCATION);
+
+        let res = TestClient::patch(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .add_header(UPLOAD_OFFSET, "5", true)
+            .add_header(UPLOAD_LENGTH, "5", true)
+            .add_header("content-type", OFFSET_OCTET_STREAM, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        let res = TestClient::head(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(header(&res, UPLOAD_LENGTH), "5");
+    }
+
+    #[tokio::test]
+    async fn test_tus_expiration() {
+        let tus = Tus::new(MemoryStore::new()).expiration(Some(Duration::from_millis(10)));
+        let store = tus.store().clone();
+        let service = Service::new(Router::new().push(tus.into_router("files")));
+        let location = create(&service, "11").await;
+        create(&service, "11").await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let mut res = TestClient::head(format!("http://127.0.0.1:5801{location}"))
+            .add_header(TUS_RESUMABLE, TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::GONE));
+        res.take_string().await.ok();
+        assert_eq!(store.delete_expired(SystemTime::now()).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use bytes::{Bytes, BytesMut};
+use salvo_core::async_trait;
+
+use super::{UploadInfo, UploadStore};
+
+/// A store saves uploads in memory, it is useful for tests and small uploads.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    uploads: Arc<Mutex<HashMap<String, (UploadInfo, BytesMut)>>>,
+}
+impl MemoryStore {
+    /// Create a new `MemoryStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Get the received data of an upload.
+    #[inline]
+    pub fn data(&self, id: &str) -> Option<Bytes> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|(_, data)| Bytes::copy_from_slice(data))
+    }
+}
+
+#[async_trait]
+impl UploadStore for MemoryStore {
+    type Error = IoError;
+
+    async fn create(&self, info: &UploadInfo) -> Result<(), Self::Error> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .insert(info.id.clone(), (info.clone(), BytesMut::new()));
+        Ok(())
+    }
+    async fn info(&self, id: &str) -> Result<Option<UploadInfo>, Self::Error> {
+        Ok(self.uploads.lock().unwrap().get(id).map(|(info, _)| info.clone()))
+    }
+    async fn append(&self, id: &str, offset: u64, data: Bytes) -> Result<u64, Self::Error> {
+        let mut uploads = self.uploads.lock().unwrap();
+        let (info, buf) = uploads
+            .get_mut(id)
+            .ok_or_else(|| IoError::new(ErrorKind::NotFound, "upload is not found"))?;
+        if info.offset != offset {
+            return Err(IoError::new(ErrorKind::InvalidInput, "offset does not match"));
+        }
+        buf.extend_from_slice(&data);
+        info.offset = buf.len() as u64;
+        Ok(info.offset)
+    }
+    async fn update(&self, info: &UploadInfo) -> Result<(), Self::Error> {
+        if let Some((current, _)) = self.uploads.lock().unwrap().get_mut(&info.id) {
+            current.length = info.length;
+            current.metadata = info.metadata.clone();
+            current.expires_at = info.expires_at;
+        }
+        Ok(())
+    }
+    async fn delete(&self, id: &str) -> Result<(), Self::Error> {
+        self.uploads.lock().unwrap().remove(id);
+        Ok(())
+    }
+    async fn delete_expired(&self, now: SystemTime) -> Result<usize, Self::Error> {
+        let mut uploads = self.uploads.lock().unwrap();
+        let count = uploads.len();
+        uploads.retain(|_, (info, _)| !info.is_expired(now));
+        Ok(count - uploads.len())
+    }
+}